@@ -9,7 +9,9 @@ use {
     solana_core::{
         banking_stage::BankingStage,
         banking_trace::{BankingPacketBatch, BankingTracer, BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT},
-        bundle_stage::bundle_account_locker::BundleAccountLocker,
+        bundle_stage::{
+            bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+        },
         validator::BlockProductionMethod,
     },
     solana_gossip::cluster_info::{ClusterInfo, Node},
@@ -485,6 +487,7 @@ fn main() {
         false,
         HashSet::default(),
         BundleAccountLocker::default(),
+        SandwichTracker::default(),
         |_| 0,
         Duration::from_millis(50),
     );
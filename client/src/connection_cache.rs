@@ -256,7 +256,7 @@ mod tests {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -25,7 +25,9 @@ use {
             BankingStage, BankingStageStats,
         },
         banking_trace::{BankingPacketBatch, BankingTracer},
-        bundle_stage::bundle_account_locker::BundleAccountLocker,
+        bundle_stage::{
+            bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+        },
     },
     solana_entry::entry::{next_hash, Entry},
     solana_gossip::cluster_info::{ClusterInfo, Node},
@@ -126,6 +128,7 @@ fn bench_consume_buffered(bencher: &mut Bencher) {
         None,
         HashSet::default(),
         BundleAccountLocker::default(),
+        SandwichTracker::default(),
     );
     // This tests the performance of buffering packets.
     // If the packet buffers are copied, performance will be poor.
@@ -323,6 +326,7 @@ fn bench_banking(bencher: &mut Bencher, tx_type: TransactionType) {
         false,
         HashSet::default(),
         BundleAccountLocker::default(),
+        SandwichTracker::default(),
         |_| 0,
     );
 
@@ -0,0 +1,289 @@
+#![allow(clippy::arithmetic_side_effects)]
+#![feature(test)]
+
+use {
+    crossbeam_channel::{unbounded, Receiver},
+    rayon::{
+        iter::IndexedParallelIterator,
+        prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    },
+    solana_core::{
+        banking_stage::{
+            qos_service::QosService, unprocessed_transaction_storage::UnprocessedTransactionStorage,
+        },
+        bundle_stage::{
+            amm_registry::SharedAmmRegistry, bundle_account_locker::BundleAccountLocker,
+            bundle_consumer::BundleConsumer, bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+            bundle_worker_pool::BundleWorkerPool, committer::Committer,
+            sandwich_tracker::SandwichTracker, NUM_BUNDLE_STAGE_THREADS,
+        },
+        immutable_deserialized_bundle::ImmutableDeserializedBundle,
+        packet_bundle::PacketBundle,
+        tip_manager::{TipManager, TipManagerConfig},
+    },
+    solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
+    solana_ledger::{
+        blockstore::Blockstore,
+        genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        leader_schedule_cache::LeaderScheduleCache,
+    },
+    solana_perf::packet::{Packet, PacketBatch},
+    solana_poh::{
+        poh_recorder::{create_test_recorder, PohRecorder, WorkingBankEntry},
+        poh_service::PohService,
+    },
+    solana_runtime::{
+        bank::Bank, bank_forks::BankForks, prioritization_fee_cache::PrioritizationFeeCache,
+    },
+    solana_sdk::{
+        account::{Account, ReadableAccount},
+        bundle::derive_bundle_id,
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        stake_history::Epoch,
+        system_program, system_transaction,
+        transaction::VersionedTransaction,
+    },
+    solana_streamer::socket::SocketAddrSpace,
+    std::{
+        collections::HashSet,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        time::Duration,
+    },
+    tempfile::TempDir,
+    test::Bencher,
+};
+
+extern crate test;
+
+const TRANSACTIONS_PER_BUNDLE: usize = 4;
+const BUNDLES_PER_ITERATION: usize = 64;
+// Enough to pay for the same transfers on every iteration.
+const LAMPORTS_PER_ACCOUNT: u64 = 1_000 * LAMPORTS_PER_SOL;
+
+fn create_funded_accounts(bank: &Bank, num: usize) -> Vec<Keypair> {
+    let accounts: Vec<_> = (0..num).into_par_iter().map(|_| Keypair::new()).collect();
+
+    accounts.par_iter().for_each(|account| {
+        bank.store_account(
+            &account.pubkey(),
+            &Account {
+                lamports: LAMPORTS_PER_ACCOUNT,
+                data: vec![],
+                owner: system_program::id(),
+                executable: false,
+                rent_epoch: Epoch::MAX,
+            }
+            .to_account_shared_data(),
+        );
+    });
+
+    accounts
+}
+
+/// Creates bundles of transfers. Each bundle pays from its own accounts, and when `hot_account` is
+/// set every bundle also writes to it so all bundles conflict with each other.
+fn create_bundles(
+    bank: &Bank,
+    num_bundles: usize,
+    hot_account: Option<Pubkey>,
+) -> Vec<PacketBundle> {
+    let lamports = bank.get_minimum_balance_for_rent_exemption(0);
+    let funded_accounts = create_funded_accounts(bank, num_bundles * TRANSACTIONS_PER_BUNDLE);
+    funded_accounts
+        .par_iter()
+        .chunks(TRANSACTIONS_PER_BUNDLE)
+        .map(|payers| {
+            let transactions: Vec<_> = payers
+                .into_iter()
+                .map(|payer| {
+                    let to = hot_account.unwrap_or_else(Pubkey::new_unique);
+                    VersionedTransaction::from(system_transaction::transfer(
+                        payer,
+                        &to,
+                        lamports,
+                        bank.last_blockhash(),
+                    ))
+                })
+                .collect();
+            PacketBundle {
+                batch: PacketBatch::new(
+                    transactions
+                        .iter()
+                        .map(|tx| Packet::from_data(None, tx).unwrap())
+                        .collect(),
+                ),
+                bundle_id: derive_bundle_id(&transactions).unwrap(),
+                block_builder_fee_info: None,
+            }
+        })
+        .collect()
+}
+
+fn deserialize_bundles(packet_bundles: &[PacketBundle]) -> Vec<ImmutableDeserializedBundle> {
+    packet_bundles
+        .iter()
+        .map(|packet_bundle| {
+            ImmutableDeserializedBundle::new(
+                &mut packet_bundle.clone(),
+                None,
+                &HashSet::default(),
+                &Ok,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn create_consumer(
+    bank: &Arc<Bank>,
+    blockstore: Arc<Blockstore>,
+    poh_recorder: &RwLock<PohRecorder>,
+    num_workers: usize,
+) -> BundleConsumer {
+    let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+    let committer = Committer::new(
+        None,
+        replay_vote_sender,
+        Arc::new(PrioritizationFeeCache::new(0)),
+    );
+    let leader_keypair = Arc::new(Keypair::new());
+    let cluster_info = Arc::new(ClusterInfo::new(
+        ContactInfo::new(leader_keypair.pubkey(), 0, 0),
+        leader_keypair,
+        SocketAddrSpace::Unspecified,
+    ));
+    let tip_manager = TipManager::new(
+        blockstore,
+        cluster_info.clone(),
+        Arc::new(LeaderScheduleCache::new_from_bank(bank)),
+        TipManagerConfig::default(),
+    );
+    // Nothing reads the bundle results in the bench.
+    let (bundle_result_sender, _bundle_result_receiver) = unbounded();
+    BundleConsumer::new(
+        committer,
+        poh_recorder.read().unwrap().new_recorder(),
+        QosService::new(0),
+        None,
+        tip_manager,
+        BundleAccountLocker::default(),
+        Duration::from_millis(40),
+        cluster_info,
+        SharedAmmRegistry::default(),
+        SandwichTracker::default(),
+        BundleWorkerPool::new(0, num_workers),
+        bundle_result_sender,
+    )
+}
+
+struct BenchFrame {
+    bank: Arc<Bank>,
+    _bank_forks: Arc<RwLock<BankForks>>,
+    blockstore: Arc<Blockstore>,
+    ledger_path: TempDir,
+    exit: Arc<AtomicBool>,
+    poh_recorder: Arc<RwLock<PohRecorder>>,
+    poh_service: PohService,
+    signal_receiver: Receiver<WorkingBankEntry>,
+}
+
+fn setup() -> BenchFrame {
+    let mint_total = u64::MAX;
+    let GenesisConfigInfo {
+        mut genesis_config, ..
+    } = create_genesis_config(mint_total);
+
+    // Set a high ticks_per_slot so we don't run out of ticks
+    // during the benchmark
+    genesis_config.ticks_per_slot = 10_000;
+
+    let mut bank = Bank::new_for_benches(&genesis_config);
+
+    // Allow arbitrary transaction processing time for the purposes of this bench
+    bank.ns_per_slot = u128::MAX;
+
+    // set cost tracker limits to MAX so it will not filter out TXs
+    bank.write_cost_tracker()
+        .unwrap()
+        .set_limits(u64::MAX, u64::MAX, u64::MAX);
+    let (bank, bank_forks) = bank.wrap_with_bank_forks_for_tests();
+
+    let ledger_path = TempDir::new().unwrap();
+    let blockstore = Arc::new(
+        Blockstore::open(ledger_path.path()).expect("Expected to be able to open database ledger"),
+    );
+    let (exit, poh_recorder, poh_service, signal_receiver) =
+        create_test_recorder(bank.clone(), blockstore.clone(), None, None);
+
+    BenchFrame {
+        bank,
+        _bank_forks: bank_forks,
+        blockstore,
+        ledger_path,
+        exit,
+        poh_recorder,
+        poh_service,
+        signal_receiver,
+    }
+}
+
+/// Runs the same batch of bundles through the bundle consumer on every iteration. The consumer
+/// groups them into rounds of non-conflicting bundles and executes each round on its worker pool.
+fn bench_bundle_stage(bencher: &mut Bencher, num_workers: usize, conflicting: bool) {
+    let BenchFrame {
+        bank,
+        _bank_forks,
+        blockstore,
+        ledger_path: _ledger_path,
+        exit,
+        poh_recorder,
+        poh_service,
+        signal_receiver: _signal_receiver,
+    } = setup();
+    let mut consumer = create_consumer(&bank, blockstore, &poh_recorder, num_workers);
+    let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
+    let hot_account = conflicting.then(Pubkey::new_unique);
+    let bundles = create_bundles(&bank, BUNDLES_PER_ITERATION, hot_account);
+    let mut bundle_storage = UnprocessedTransactionStorage::new_bundle_storage();
+    let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(0);
+
+    bencher.iter(|| {
+        bundle_storage.insert_bundles(deserialize_bundles(&bundles));
+        consumer.consume_buffered_bundles(
+            &bank_start,
+            &mut bundle_storage,
+            &mut bundle_stage_leader_metrics,
+        );
+        // Let the same transactions execute again on the next iteration
+        bank.clear_signatures();
+    });
+
+    exit.store(true, Ordering::Relaxed);
+    poh_service.join().unwrap();
+}
+
+#[bench]
+fn bench_bundle_stage_non_conflicting_single_thread(bencher: &mut Bencher) {
+    bench_bundle_stage(bencher, 1, false);
+}
+
+#[bench]
+fn bench_bundle_stage_non_conflicting_multi_thread(bencher: &mut Bencher) {
+    bench_bundle_stage(bencher, NUM_BUNDLE_STAGE_THREADS, false);
+}
+
+#[bench]
+fn bench_bundle_stage_conflicting_single_thread(bencher: &mut Bencher) {
+    bench_bundle_stage(bencher, 1, true);
+}
+
+#[bench]
+fn bench_bundle_stage_conflicting_multi_thread(bencher: &mut Bencher) {
+    bench_bundle_stage(bencher, NUM_BUNDLE_STAGE_THREADS, true);
+}
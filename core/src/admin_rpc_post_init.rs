@@ -1,15 +1,23 @@
 use {
     crate::{
+        bundle_stage::amm_registry::SharedAmmRegistry,
         cluster_slots_service::cluster_slots::ClusterSlots,
-        proxy::{block_engine_stage::BlockEngineConfig, relayer_stage::RelayerConfig},
+        p3_quic::P3Config,
+        proxy::{
+            block_engine_stage::BlockEngineConfig,
+            relayer_stage::{RelayerConfig, RelayerStates},
+            shredstream_stage::{ShredstreamConfig, ShredstreamState},
+        },
         repair::{outstanding_requests::OutstandingRequests, serve_repair::ShredRepairType},
     },
     solana_gossip::cluster_info::ClusterInfo,
     solana_runtime::bank_forks::BankForks,
     solana_sdk::{pubkey::Pubkey, quic::NotifyKeyUpdate},
+    solana_turbine::shred_receiver::ShredReceivers,
     std::{
         collections::HashSet,
-        net::{SocketAddr, UdpSocket},
+        net::UdpSocket,
+        path::PathBuf,
         sync::{Arc, Mutex, RwLock},
     },
 };
@@ -26,6 +34,11 @@ pub struct AdminRpcRequestMetadataPostInit {
     pub cluster_slots: Arc<ClusterSlots>,
     pub block_engine_config: Arc<Mutex<BlockEngineConfig>>,
     pub relayer_config: Arc<Mutex<RelayerConfig>>,
-    pub shred_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
-    pub shred_retransmit_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
+    pub relayer_states: Arc<Mutex<RelayerStates>>,
+    pub shred_receivers: Arc<ShredReceivers>,
+    pub shredstream_config: Arc<Mutex<ShredstreamConfig>>,
+    pub shredstream_state: Arc<Mutex<ShredstreamState>>,
+    pub p3_config: Arc<Mutex<P3Config>>,
+    pub amm_registry: SharedAmmRegistry,
+    pub amm_registry_path: Option<PathBuf>,
 }
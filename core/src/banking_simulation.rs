@@ -3,10 +3,15 @@ use {
     crate::{
         banking_stage::{BankingStage, LikeClusterInfo},
         banking_trace::{
-            BankingPacketBatch, BankingTracer, ChannelLabel, TimedTracedEvent, TracedEvent,
-            TracedSender, TracerThread, BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT, BASENAME,
+            BankingPacketBatch, BankingTracer, ChannelLabel, TimedTracedEvent, TracedBundleSender,
+            TracedEvent, TracedPacketBundle, TracedSender, TracerThread,
+            BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT, BASENAME,
         },
-        bundle_stage::bundle_account_locker::BundleAccountLocker,
+        bundle_stage::{
+            bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+            BundleStage,
+        },
+        tip_manager::{TipManager, TipManagerConfig},
         validator::BlockProductionMethod,
     },
     bincode::deserialize_from,
@@ -113,6 +118,13 @@ use {
 ///
 /// Warm-up starts at T=-WARMUP_DURATION (~ 13 secs). As soon as warm up is initiated, we invoke
 /// `BankingStage::new_num_threads()` as well to simulate the pre-leader slot's tx-buffering time.
+///
+/// Traced bundles are sent to a `BundleStage` in the same fashion, pretending to be
+/// `BlockEngineStage`, if a `BundleSimulationConfig` is given. `BundleStage` signs the tip
+/// cranking transactions, so this requires the simulated leader's identity keypair. Otherwise, the
+/// bundles are skipped and only `BankingStage` is simulated. Traced bundles keep the fee info of
+/// the block engine that sent them, while the funnel and rewards split aren't traced and must be
+/// given in the `TipManagerConfig` as the simulated leader ran with.
 pub struct BankingSimulator {
     banking_trace_events: BankingTraceEvents,
     first_simulated_slot: Slot,
@@ -131,21 +143,56 @@ pub enum SimulateError {
 const WARMUP_DURATION: Duration =
     Duration::from_millis(HOLD_TRANSACTIONS_SLOT_OFFSET * DEFAULT_MS_PER_SLOT + 5000);
 
+/// Events sent to `BankingStage` and `BundleStage` during simulation.
+enum SimulatedEvent {
+    PacketBatch(ChannelLabel, BankingPacketBatch),
+    PacketBundles(Vec<TracedPacketBundle>),
+}
+
+impl SimulatedEvent {
+    fn batch_and_tx_counts(&self) -> (usize, usize) {
+        match self {
+            Self::PacketBatch(_label, batches_with_stats) => {
+                let batches = &batches_with_stats.0;
+                (
+                    batches.len(),
+                    batches.iter().map(|batch| batch.len()).sum::<usize>(),
+                )
+            }
+            Self::PacketBundles(bundles) => (
+                bundles.len(),
+                bundles
+                    .iter()
+                    .map(|bundle| bundle.bundle.batch.len())
+                    .sum::<usize>(),
+            ),
+        }
+    }
+}
+
 /// BTreeMap is intentional because events could be unordered slightly due to tracing jitter.
-type PacketBatchesByTime = BTreeMap<SystemTime, (ChannelLabel, BankingPacketBatch)>;
+type EventsByTime = BTreeMap<SystemTime, SimulatedEvent>;
 
 type FreezeTimeBySlot = BTreeMap<Slot, SystemTime>;
 
-type TimedBatchesToSend = Vec<(
-    (Duration, (ChannelLabel, BankingPacketBatch)),
-    (usize, usize),
+type TimedEventsToSend = Vec<((Duration, SimulatedEvent), (usize, usize))>;
+
+type EventSenderThread = JoinHandle<(
+    TracedSender,
+    TracedSender,
+    TracedSender,
+    Option<TracedBundleSender>,
 )>;
 
-type EventSenderThread = JoinHandle<(TracedSender, TracedSender, TracedSender)>;
+/// Needed to simulate `BundleStage` as the simulated leader.
+pub struct BundleSimulationConfig {
+    pub identity_keypair: Arc<Keypair>,
+    pub tip_manager_config: TipManagerConfig,
+}
 
 #[derive(Default)]
 pub struct BankingTraceEvents {
-    packet_batches_by_time: PacketBatchesByTime,
+    events_by_time: EventsByTime,
     freeze_time_by_slot: FreezeTimeBySlot,
     hash_overrides: HashOverrides,
 }
@@ -206,20 +253,10 @@ impl BankingTraceEvents {
     fn load_event(&mut self, TimedTracedEvent(event_time, event): TimedTracedEvent) {
         match event {
             TracedEvent::PacketBatch(label, batch) => {
-                // Deserialized PacketBatches will mostly be ordered by event_time, but this
-                // isn't guaranteed when traced, because time are measured by multiple _sender_
-                // threads without synchronization among them to avoid overhead.
-                //
-                // Also, there's a possibility of system clock change. In this case,
-                // the simulation is meaningless, though...
-                //
-                // Somewhat naively assume that event_times (nanosecond resolution) won't
-                // collide.
-                let is_new = self
-                    .packet_batches_by_time
-                    .insert(event_time, (label, batch))
-                    .is_none();
-                assert!(is_new);
+                self.insert_event(event_time, SimulatedEvent::PacketBatch(label, batch));
+            }
+            TracedEvent::PacketBundles(bundles) => {
+                self.insert_event(event_time, SimulatedEvent::PacketBundles(bundles));
             }
             TracedEvent::BlockAndBankHash(slot, blockhash, bank_hash) => {
                 let is_new = self.freeze_time_by_slot.insert(slot, event_time).is_none();
@@ -229,6 +266,19 @@ impl BankingTraceEvents {
         }
     }
 
+    fn insert_event(&mut self, event_time: SystemTime, event: SimulatedEvent) {
+        // Deserialized events will mostly be ordered by event_time, but this isn't guaranteed
+        // when traced, because time are measured by multiple _sender_ threads without
+        // synchronization among them to avoid overhead.
+        //
+        // Also, there's a possibility of system clock change. In this case, the simulation is
+        // meaningless, though...
+        //
+        // Somewhat naively assume that event_times (nanosecond resolution) won't collide.
+        let is_new = self.events_by_time.insert(event_time, event).is_none();
+        assert!(is_new);
+    }
+
     pub fn hash_overrides(&self) -> &HashOverrides {
         &self.hash_overrides
     }
@@ -325,19 +375,21 @@ struct SenderLoop {
     parent_slot: Slot,
     first_simulated_slot: Slot,
     non_vote_sender: TracedSender,
+    trusted_non_vote_sender: TracedSender,
     tpu_vote_sender: TracedSender,
     gossip_vote_sender: TracedSender,
+    bundle_sender: Option<TracedBundleSender>,
     exit: Arc<AtomicBool>,
     raw_base_event_time: SystemTime,
-    total_batch_count: usize,
-    timed_batches_to_send: TimedBatchesToSend,
+    total_event_count: usize,
+    timed_events_to_send: TimedEventsToSend,
 }
 
 impl SenderLoop {
     fn log_starting(&self) {
         info!(
             "simulating events: {} (out of {}), starting at slot {} (based on {} from traced event slot: {}) (warmup: -{:?})",
-            self.timed_batches_to_send.len(), self.total_batch_count, self.first_simulated_slot,
+            self.timed_events_to_send.len(), self.total_event_count, self.first_simulated_slot,
             SenderLoopLogger::format_as_timestamp(self.raw_base_event_time),
             self.parent_slot, WARMUP_DURATION,
         );
@@ -353,15 +405,20 @@ impl SenderLoop {
     fn start(
         mut self,
         base_simulation_time: SystemTime,
-    ) -> (TracedSender, TracedSender, TracedSender) {
+    ) -> (
+        TracedSender,
+        TracedSender,
+        TracedSender,
+        Option<TracedBundleSender>,
+    ) {
         let mut logger = SenderLoopLogger::new(
             &self.non_vote_sender,
             &self.tpu_vote_sender,
             &self.gossip_vote_sender,
         );
         let mut simulation_duration = Duration::default();
-        for ((required_duration, (label, batches_with_stats)), (batch_count, tx_count)) in
-            self.timed_batches_to_send.drain(..)
+        for ((required_duration, event), (batch_count, tx_count)) in
+            self.timed_events_to_send.drain(..)
         {
             // Busy loop for most accurate sending timings
             while simulation_duration < required_duration {
@@ -371,26 +428,43 @@ impl SenderLoop {
                     .unwrap();
             }
 
-            let sender = match label {
-                ChannelLabel::NonVote => &self.non_vote_sender,
-                ChannelLabel::TpuVote => &self.tpu_vote_sender,
-                ChannelLabel::GossipVote => &self.gossip_vote_sender,
-                ChannelLabel::Dummy => unreachable!(),
-            };
-            sender.send(batches_with_stats).unwrap();
-
-            logger.on_sending_batches(&simulation_duration, label, batch_count, tx_count);
+            match event {
+                SimulatedEvent::PacketBatch(label, batches_with_stats) => {
+                    let sender = match label {
+                        ChannelLabel::NonVote => &self.non_vote_sender,
+                        ChannelLabel::TrustedNonVote => &self.trusted_non_vote_sender,
+                        ChannelLabel::TpuVote => &self.tpu_vote_sender,
+                        ChannelLabel::GossipVote => &self.gossip_vote_sender,
+                        ChannelLabel::Dummy => unreachable!(),
+                    };
+                    sender.send(batches_with_stats).unwrap();
+
+                    logger.on_sending_batches(&simulation_duration, label, batch_count, tx_count);
+                }
+                SimulatedEvent::PacketBundles(bundles) => {
+                    if let Some(bundle_sender) = &self.bundle_sender {
+                        // Bundles of a single event are received from the same block engine
+                        let trusted = bundles.iter().any(|bundle| bundle.trusted);
+                        let bundles = bundles.into_iter().map(|bundle| bundle.bundle).collect();
+                        bundle_sender.send(bundles, trusted).unwrap();
+
+                        logger.on_sending_bundles(batch_count, tx_count);
+                    }
+                }
+            }
             if self.exit.load(Ordering::Relaxed) {
                 break;
             }
         }
         logger.on_terminating();
-        drop(self.timed_batches_to_send);
+        drop(self.timed_events_to_send);
+        drop(self.trusted_non_vote_sender);
         // hold these senders in join_handle to control banking stage termination!
         (
             self.non_vote_sender,
             self.tpu_vote_sender,
             self.gossip_vote_sender,
+            self.bundle_sender,
         )
     }
 }
@@ -509,6 +583,7 @@ impl SimulatorLoop {
 struct SimulatorThreads {
     poh_service: PohService,
     banking_stage: BankingStage,
+    bundle_stage: Option<BundleStage>,
     broadcast_stage: BroadcastStage,
     retracer_thread: TracerThread,
     exit: Arc<AtomicBool>,
@@ -524,6 +599,9 @@ impl SimulatorThreads {
         // triggers termination of banking_stage, in turn retracer thread will be terminated.
         sender_thread.join().unwrap();
         self.banking_stage.join().unwrap();
+        if let Some(bundle_stage) = self.bundle_stage {
+            bundle_stage.join().unwrap();
+        }
         self.poh_service.join().unwrap();
         if let Some(retracer_thread) = self.retracer_thread {
             retracer_thread.join().unwrap().unwrap();
@@ -550,6 +628,8 @@ struct SenderLoopLogger<'a> {
     tpu_vote_tx_count: usize,
     gossip_vote_batch_count: usize,
     gossip_vote_tx_count: usize,
+    bundle_count: usize,
+    bundle_tx_count: usize,
 }
 
 impl<'a> SenderLoopLogger<'a> {
@@ -573,6 +653,8 @@ impl<'a> SenderLoopLogger<'a> {
             tpu_vote_tx_count: 0,
             gossip_vote_batch_count: 0,
             gossip_vote_tx_count: 0,
+            bundle_count: 0,
+            bundle_tx_count: 0,
         }
     }
 
@@ -590,7 +672,9 @@ impl<'a> SenderLoopLogger<'a> {
 
         use ChannelLabel::*;
         let (total_batch_count, total_tx_count) = match label {
-            NonVote => (&mut self.non_vote_batch_count, &mut self.non_vote_tx_count),
+            NonVote | TrustedNonVote => {
+                (&mut self.non_vote_batch_count, &mut self.non_vote_tx_count)
+            }
             TpuVote => (&mut self.tpu_vote_batch_count, &mut self.tpu_vote_tx_count),
             GossipVote => (
                 &mut self.gossip_vote_batch_count,
@@ -632,15 +716,23 @@ impl<'a> SenderLoopLogger<'a> {
         }
     }
 
+    fn on_sending_bundles(&mut self, bundle_count: usize, tx_count: usize) {
+        debug!("sent {} bundles ({} txes)", bundle_count, tx_count);
+        self.bundle_count += bundle_count;
+        self.bundle_tx_count += tx_count;
+    }
+
     fn on_terminating(self) {
         info!(
-            "terminating to send...: non_vote: {} ({}), tpu_vote: {} ({}), gossip_vote: {} ({})",
+            "terminating to send...: non_vote: {} ({}), tpu_vote: {} ({}), gossip_vote: {} ({}), bundle: {} ({})",
             self.non_vote_batch_count,
             self.non_vote_tx_count,
             self.tpu_vote_batch_count,
             self.tpu_vote_tx_count,
             self.gossip_vote_batch_count,
             self.gossip_vote_tx_count,
+            self.bundle_count,
+            self.bundle_tx_count,
         );
     }
 
@@ -673,9 +765,10 @@ impl BankingSimulator {
         bank_forks: Arc<RwLock<BankForks>>,
         blockstore: Arc<Blockstore>,
         block_production_method: BlockProductionMethod,
+        bundle_simulation_config: Option<BundleSimulationConfig>,
     ) -> (SenderLoop, SimulatorLoop, SimulatorThreads) {
         let parent_slot = self.parent_slot().unwrap();
-        let mut packet_batches_by_time = self.banking_trace_events.packet_batches_by_time;
+        let mut events_by_time = self.banking_trace_events.events_by_time;
         let freeze_time_by_slot = self.banking_trace_events.freeze_time_by_slot;
         let bank = bank_forks
             .read()
@@ -760,6 +853,7 @@ impl BankingSimulator {
         );
 
         let (non_vote_sender, non_vote_receiver) = retracer.create_channel_non_vote();
+        let trusted_non_vote_sender = retracer.create_sender_trusted_non_vote(&non_vote_sender);
         let (tpu_vote_sender, tpu_vote_receiver) = retracer.create_channel_tpu_vote();
         let (gossip_vote_sender, gossip_vote_receiver) = retracer.create_channel_gossip_vote();
 
@@ -793,7 +887,7 @@ impl BankingSimulator {
             bank_forks.clone(),
             shred_version,
             sender,
-            Arc::new(RwLock::new(None)),
+            Arc::default(),
         );
 
         info!("Start banking stage!...");
@@ -802,6 +896,65 @@ impl BankingSimulator {
             id: simulated_leader.into(),
         });
         let prioritization_fee_cache = &Arc::new(PrioritizationFeeCache::new(0u64));
+        let bundle_account_locker = BundleAccountLocker::default();
+        let sandwich_tracker = SandwichTracker::default();
+        let mut blacklisted_accounts = collections::HashSet::default();
+
+        let bundle_simulation_config = bundle_simulation_config.filter(|config| {
+            let identity = config.identity_keypair.pubkey();
+            if identity != simulated_leader {
+                warn!(
+                    "skipping bundles as the identity {} isn't the simulated leader {}",
+                    identity, simulated_leader,
+                );
+            }
+            identity == simulated_leader
+        });
+        let (bundle_sender, bundle_stage) = if let Some(BundleSimulationConfig {
+            identity_keypair,
+            tip_manager_config,
+        }) = bundle_simulation_config
+        {
+            info!("Start bundle stage!...");
+            // BundleStage needs the leader's keypair to sign tip cranking transactions.
+            let cluster_info = Arc::new(ClusterInfo::new(
+                Node::new_localhost_with_pubkey(&identity_keypair.pubkey()).info,
+                identity_keypair.clone(),
+                SocketAddrSpace::Unspecified,
+            ));
+            let tip_manager = TipManager::new(
+                blockstore.clone(),
+                cluster_info.clone(),
+                leader_schedule_cache.clone(),
+                tip_manager_config,
+            );
+            // The tip program can't be used in BankingStage like the real environment.
+            blacklisted_accounts.insert(tip_manager.tip_payment_program_id());
+
+            let (bundle_sender, bundle_receiver) = retracer.create_channel_bundle();
+            let (bundle_result_sender, _bundle_result_receiver) = unbounded();
+            let bundle_stage = BundleStage::new(
+                &cluster_info,
+                &poh_recorder,
+                bundle_receiver,
+                bundle_result_sender,
+                None,
+                replay_vote_sender.clone(),
+                None,
+                exit.clone(),
+                tip_manager,
+                bundle_account_locker.clone(),
+                prioritization_fee_cache,
+                Arc::default(),
+                sandwich_tracker.clone(),
+                BundleStage::num_threads(),
+            );
+            (Some(bundle_sender), Some(bundle_stage))
+        } else {
+            warn!("skipping bundles without a BundleSimulationConfig for the simulated leader");
+            (None, None)
+        };
+
         let banking_stage = BankingStage::new_num_threads(
             block_production_method.clone(),
             &cluster_info,
@@ -817,8 +970,9 @@ impl BankingSimulator {
             bank_forks.clone(),
             prioritization_fee_cache,
             false,
-            collections::HashSet::default(),
-            BundleAccountLocker::default(),
+            blacklisted_accounts,
+            bundle_account_locker,
+            sandwich_tracker,
             |_| 0,
             Duration::from_millis(50),
         );
@@ -829,25 +983,17 @@ impl BankingSimulator {
             .expect("timed hashes");
         let base_event_time = raw_base_event_time - WARMUP_DURATION;
 
-        let total_batch_count = packet_batches_by_time.len();
-        let timed_batches_to_send = packet_batches_by_time.split_off(&base_event_time);
-        let batch_and_tx_counts = timed_batches_to_send
+        let total_event_count = events_by_time.len();
+        let timed_events_to_send = events_by_time.split_off(&base_event_time);
+        let batch_and_tx_counts = timed_events_to_send
             .values()
-            .map(|(_label, batches_with_stats)| {
-                let batches = &batches_with_stats.0;
-                (
-                    batches.len(),
-                    batches.iter().map(|batch| batch.len()).sum::<usize>(),
-                )
-            })
+            .map(SimulatedEvent::batch_and_tx_counts)
             .collect::<Vec<_>>();
         // Convert to a large plain old Vec and drain on it, finally dropping it outside
         // the simulation loop to avoid jitter due to interleaved deallocs of BTreeMap.
-        let timed_batches_to_send = timed_batches_to_send
+        let timed_events_to_send = timed_events_to_send
             .into_iter()
-            .map(|(event_time, batches)| {
-                (event_time.duration_since(base_event_time).unwrap(), batches)
-            })
+            .map(|(event_time, event)| (event_time.duration_since(base_event_time).unwrap(), event))
             .zip_eq(batch_and_tx_counts)
             .collect::<Vec<_>>();
 
@@ -855,12 +1001,14 @@ impl BankingSimulator {
             parent_slot,
             first_simulated_slot: self.first_simulated_slot,
             non_vote_sender,
+            trusted_non_vote_sender,
             tpu_vote_sender,
             gossip_vote_sender,
+            bundle_sender,
             exit: exit.clone(),
             raw_base_event_time,
-            total_batch_count,
-            timed_batches_to_send,
+            total_event_count,
+            timed_events_to_send,
         };
 
         let simulator_loop = SimulatorLoop {
@@ -881,6 +1029,7 @@ impl BankingSimulator {
         let simulator_threads = SimulatorThreads {
             poh_service,
             banking_stage,
+            bundle_stage,
             broadcast_stage,
             retracer_thread,
             exit,
@@ -895,12 +1044,14 @@ impl BankingSimulator {
         bank_forks: Arc<RwLock<BankForks>>,
         blockstore: Arc<Blockstore>,
         block_production_method: BlockProductionMethod,
+        bundle_simulation_config: Option<BundleSimulationConfig>,
     ) -> Result<(), SimulateError> {
         let (sender_loop, simulator_loop, simulator_threads) = self.prepare_simulation(
             genesis_config,
             bank_forks,
             blockstore,
             block_production_method,
+            bundle_simulation_config,
         );
 
         sender_loop.log_starting();
@@ -924,3 +1075,103 @@ impl BankingSimulator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            banking_trace::for_test::sample_packet_batch,
+            packet_bundle::{BlockBuilderFeeInfo, PacketBundle},
+        },
+        bincode::serialize_into,
+        tempfile::TempDir,
+    };
+
+    #[test]
+    fn test_replay_traced_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let event_file_path = temp_dir.path().join(BASENAME);
+        let block_builder_fee_info = BlockBuilderFeeInfo {
+            block_builder: Pubkey::new_unique(),
+            block_builder_commission: 5,
+        };
+        let bundle = PacketBundle {
+            batch: sample_packet_batch().0[0].clone(),
+            bundle_id: "bundle".to_string(),
+            block_builder_fee_info: Some(block_builder_fee_info),
+        };
+        let base_event_time = SystemTime::now();
+        {
+            let mut file = File::create(&event_file_path).unwrap();
+            serialize_into(
+                &mut file,
+                &TimedTracedEvent(
+                    base_event_time,
+                    TracedEvent::PacketBatch(ChannelLabel::TrustedNonVote, sample_packet_batch()),
+                ),
+            )
+            .unwrap();
+            serialize_into(
+                &mut file,
+                &TimedTracedEvent(
+                    base_event_time + Duration::from_millis(1),
+                    TracedEvent::PacketBundles(vec![TracedPacketBundle {
+                        bundle,
+                        drop_on_revert: false,
+                        trusted: true,
+                    }]),
+                ),
+            )
+            .unwrap();
+        }
+
+        let events = BankingTraceEvents::load(&[event_file_path]).unwrap();
+        let total_event_count = events.events_by_time.len();
+        assert_eq!(total_event_count, 2);
+        let timed_events_to_send = events
+            .events_by_time
+            .into_iter()
+            .map(|(event_time, event)| {
+                let batch_and_tx_counts = event.batch_and_tx_counts();
+                (
+                    (event_time.duration_since(base_event_time).unwrap(), event),
+                    batch_and_tx_counts,
+                )
+            })
+            .collect();
+
+        let (non_vote_sender, non_vote_receiver) = BankingTracer::channel_for_test();
+        let trusted_non_vote_sender =
+            BankingTracer::new_disabled().create_sender_trusted_non_vote(&non_vote_sender);
+        let (tpu_vote_sender, _tpu_vote_receiver) = BankingTracer::channel_for_test();
+        let (gossip_vote_sender, _gossip_vote_receiver) = BankingTracer::channel_for_test();
+        let (bundle_sender, bundle_receiver) = BankingTracer::bundle_channel_for_test();
+        let sender_loop = SenderLoop {
+            parent_slot: 0,
+            first_simulated_slot: 1,
+            non_vote_sender,
+            trusted_non_vote_sender,
+            tpu_vote_sender,
+            gossip_vote_sender,
+            bundle_sender: Some(bundle_sender),
+            exit: Arc::default(),
+            raw_base_event_time: base_event_time,
+            total_event_count,
+            timed_events_to_send,
+        };
+        let _senders = sender_loop.start(SystemTime::now());
+
+        // The trusted packets are sent along the sigverified ones, and the bundle is sent to
+        // BundleStage with the fee info of the block engine that sent it.
+        assert_eq!(non_vote_receiver.try_recv().unwrap().0[0].len(), 4);
+        let bundles = bundle_receiver.try_recv().unwrap();
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].bundle_id, "bundle");
+        assert_eq!(
+            bundles[0].block_builder_fee_info,
+            Some(block_builder_fee_info)
+        );
+        assert!(bundle_receiver.try_recv().is_err());
+    }
+}
@@ -25,7 +25,9 @@ use {
             },
         },
         banking_trace::BankingPacketReceiver,
-        bundle_stage::bundle_account_locker::BundleAccountLocker,
+        bundle_stage::{
+            bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+        },
         tracer_packet_stats::TracerPacketStats,
         validator::BlockProductionMethod,
     },
@@ -76,7 +78,7 @@ mod multi_iterator_scanner;
 mod packet_deserializer;
 pub(crate) mod packet_filter;
 mod packet_receiver;
-mod read_write_account_set;
+pub(crate) mod read_write_account_set;
 mod scheduler_messages;
 mod transaction_scheduler;
 
@@ -368,6 +370,7 @@ impl BankingStage {
         enable_forwarding: bool,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
         // callback function for compute space reservation for BundleStage
         block_cost_limit_block_cost_limit_reservation_cb: impl Fn(&Bank) -> u64 + Clone + Send + 'static,
         batch_interval: Duration,
@@ -389,6 +392,7 @@ impl BankingStage {
             enable_forwarding,
             blacklisted_accounts,
             bundle_account_locker,
+            sandwich_tracker,
             block_cost_limit_block_cost_limit_reservation_cb,
             batch_interval,
         )
@@ -412,6 +416,7 @@ impl BankingStage {
         enable_forwarding: bool,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
         block_cost_limit_reservation_cb: impl Fn(&Bank) -> u64 + Clone + Send + 'static,
         batch_interval: Duration,
     ) -> Self {
@@ -432,6 +437,7 @@ impl BankingStage {
                     prioritization_fee_cache,
                     blacklisted_accounts,
                     bundle_account_locker,
+                    sandwich_tracker,
                     block_cost_limit_reservation_cb,
                 )
             }
@@ -458,6 +464,7 @@ impl BankingStage {
                     enable_forwarding,
                     blacklisted_accounts,
                     bundle_account_locker,
+                    sandwich_tracker,
                     block_cost_limit_reservation_cb,
                     batch_interval,
                 )
@@ -481,6 +488,7 @@ impl BankingStage {
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
         block_cost_limit_reservation_cb: impl Fn(&Bank) -> u64 + Clone + Send + 'static,
     ) -> Self {
         assert!(num_threads >= MIN_TOTAL_THREADS);
@@ -550,6 +558,7 @@ impl BankingStage {
                     unprocessed_transaction_storage,
                     blacklisted_accounts.clone(),
                     bundle_account_locker.clone(),
+                    sandwich_tracker.clone(),
                     block_cost_limit_reservation_cb.clone(),
                 )
             })
@@ -575,6 +584,7 @@ impl BankingStage {
         enable_forwarding: bool,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
         block_cost_limit_reservation_cb: impl Fn(&Bank) -> u64 + Clone + Send + 'static,
         batch_interval: Duration,
     ) -> Self {
@@ -625,6 +635,7 @@ impl BankingStage {
                 ),
                 blacklisted_accounts.clone(),
                 bundle_account_locker.clone(),
+                sandwich_tracker.clone(),
                 block_cost_limit_reservation_cb.clone(),
             ));
         }
@@ -649,6 +660,7 @@ impl BankingStage {
                     log_messages_bytes_limit,
                     blacklisted_accounts.clone(),
                     bundle_account_locker.clone(),
+                    sandwich_tracker.clone(),
                 ),
                 finished_work_sender.clone(),
                 poh_recorder.read().unwrap().new_leader_bank_notifier(),
@@ -754,6 +766,7 @@ impl BankingStage {
         unprocessed_transaction_storage: UnprocessedTransactionStorage,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
         block_cost_limit_reservation_cb: impl Fn(&Bank) -> u64 + Clone + Send + 'static,
     ) -> JoinHandle<()> {
         let mut packet_receiver = PacketReceiver::new(id, packet_receiver);
@@ -764,6 +777,7 @@ impl BankingStage {
             log_messages_bytes_limit,
             blacklisted_accounts.clone(),
             bundle_account_locker.clone(),
+            sandwich_tracker.clone(),
         );
 
         Builder::new()
@@ -1013,6 +1027,7 @@ mod tests {
                 false,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
                 |_| 0,
                 Duration::from_millis(50),
             );
@@ -1073,6 +1088,7 @@ mod tests {
                 false,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
                 |_| 0,
                 Duration::from_millis(50),
             );
@@ -1162,6 +1178,7 @@ mod tests {
                 false,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
                 |_| 0,
                 Duration::from_millis(50),
             );
@@ -1341,6 +1358,7 @@ mod tests {
                     &Arc::new(PrioritizationFeeCache::new(0u64)),
                     HashSet::default(),
                     BundleAccountLocker::default(),
+                    SandwichTracker::default(),
                     |_| 0,
                 );
 
@@ -1545,6 +1563,7 @@ mod tests {
                 false,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
                 |_| 0,
                 Duration::from_millis(50),
             );
@@ -1689,7 +1708,9 @@ mod tests {
                     false,
                     HashSet::from_iter([blacklisted_keypair.pubkey()]),
                     BundleAccountLocker::default(),
+                    SandwichTracker::default(),
                     |_| 0,
+                    DEFAULT_BATCH_INTERVAL,
                 );
 
                 // bad tx
@@ -331,6 +331,7 @@ impl ConsumeWorkerMetrics {
             blockhash_too_old,
             call_chain_too_deep,
             already_processed,
+            drop_on_revert,
             instruction_error,
             insufficient_funds,
             invalid_account_for_fee,
@@ -375,6 +376,9 @@ impl ConsumeWorkerMetrics {
         self.error_metrics
             .already_processed
             .fetch_add(*already_processed, Ordering::Relaxed);
+        self.error_metrics
+            .drop_on_revert
+            .fetch_add(*drop_on_revert, Ordering::Relaxed);
         self.error_metrics
             .instruction_error
             .fetch_add(*instruction_error, Ordering::Relaxed);
@@ -572,6 +576,7 @@ struct ConsumeWorkerTransactionErrorMetrics {
     blockhash_too_old: AtomicUsize,
     call_chain_too_deep: AtomicUsize,
     already_processed: AtomicUsize,
+    drop_on_revert: AtomicUsize,
     instruction_error: AtomicUsize,
     insufficient_funds: AtomicUsize,
     invalid_account_for_fee: AtomicUsize,
@@ -635,6 +640,11 @@ impl ConsumeWorkerTransactionErrorMetrics {
                 self.already_processed.swap(0, Ordering::Relaxed),
                 i64
             ),
+            (
+                "drop_on_revert",
+                self.drop_on_revert.swap(0, Ordering::Relaxed),
+                i64
+            ),
             (
                 "instruction_error",
                 self.instruction_error.swap(0, Ordering::Relaxed),
@@ -716,7 +726,9 @@ mod tests {
                 scheduler_messages::{MaxAge, TransactionBatchId, TransactionId},
                 tests::{create_slow_genesis_config, sanitize_transactions, simulate_poh},
             },
-            bundle_stage::bundle_account_locker::BundleAccountLocker,
+            bundle_stage::{
+                bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+            },
         },
         crossbeam_channel::unbounded,
         solana_ledger::{
@@ -816,6 +828,7 @@ mod tests {
             None,
             HashSet::default(),
             BundleAccountLocker::default(),
+            SandwichTracker::default(),
         );
 
         let (consume_sender, consume_receiver) = unbounded();
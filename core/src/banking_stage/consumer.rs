@@ -11,7 +11,9 @@ use {
         unprocessed_transaction_storage::{ConsumeScannerPayload, UnprocessedTransactionStorage},
         BankingStageStats,
     },
-    crate::bundle_stage::bundle_account_locker::BundleAccountLocker,
+    crate::bundle_stage::{
+        bundle_account_locker::BundleAccountLocker, sandwich_tracker::SandwichTracker,
+    },
     itertools::Itertools,
     solana_feature_set as feature_set,
     solana_ledger::token_balances::collect_token_balances,
@@ -96,6 +98,7 @@ pub struct Consumer {
     log_messages_bytes_limit: Option<usize>,
     blacklisted_accounts: HashSet<Pubkey>,
     bundle_account_locker: BundleAccountLocker,
+    sandwich_tracker: SandwichTracker,
 }
 
 impl Consumer {
@@ -106,6 +109,7 @@ impl Consumer {
         log_messages_bytes_limit: Option<usize>,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
+        sandwich_tracker: SandwichTracker,
     ) -> Self {
         Self {
             committer,
@@ -114,6 +118,7 @@ impl Consumer {
             log_messages_bytes_limit,
             blacklisted_accounts,
             bundle_account_locker,
+            sandwich_tracker,
         }
     }
 
@@ -739,6 +744,21 @@ impl Consumer {
             };
         }
 
+        // The transactions are in PoH so they land in this slot; remember their pool writes.
+        self.sandwich_tracker.record_banking_commit(
+            bank.slot(),
+            batch
+                .sanitized_transactions()
+                .iter()
+                .zip(&processing_results)
+                .filter_map(|(tx, processing_result)| {
+                    let executed = processing_result.as_ref().ok()?.executed_transaction()?;
+                    executed
+                        .was_successful()
+                        .then_some((tx, &executed.loaded_transaction))
+                }),
+        );
+
         let (commit_time_us, commit_transaction_statuses) =
             if processed_counts.processed_transactions_count != 0 {
                 self.committer.commit_transactions(
@@ -973,6 +993,7 @@ mod tests {
             None,
             HashSet::default(),
             BundleAccountLocker::default(),
+            SandwichTracker::default(),
         );
         let process_transactions_summary =
             consumer.process_transactions(&bank, &Instant::now(), &transactions, &|_| 0);
@@ -1155,6 +1176,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let process_transactions_batch_output =
@@ -1357,6 +1379,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let process_transactions_batch_output =
@@ -1472,6 +1495,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let process_transactions_batch_output =
@@ -1558,6 +1582,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let get_block_cost = || bank.read_cost_tracker().unwrap().block_cost();
@@ -1728,6 +1753,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let process_transactions_batch_output =
@@ -1940,6 +1966,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let process_transactions_summary =
@@ -2076,6 +2103,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let _ = consumer.process_and_record_transactions(&bank, &transactions, 0, &|_| 0);
@@ -2229,6 +2257,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             let _ =
@@ -2297,6 +2326,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             // When the working bank in poh_recorder is None, no packets should be processed (consume will not be called)
@@ -2390,6 +2420,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             // When the working bank in poh_recorder is None, no packets should be processed
@@ -2450,6 +2481,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             // When the working bank in poh_recorder is None, no packets should be processed (consume will not be called)
@@ -2590,6 +2622,7 @@ mod tests {
                 None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
+                SandwichTracker::default(),
             );
 
             // When the working bank in poh_recorder is None, no packets should be processed (consume will not be called)
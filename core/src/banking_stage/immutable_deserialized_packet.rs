@@ -3,7 +3,10 @@ use {
     solana_compute_budget::compute_budget_limits::ComputeBudgetLimits,
     solana_perf::packet::Packet,
     solana_runtime::bank::Bank,
-    solana_runtime_transaction::instructions_processor::process_compute_budget_instructions,
+    solana_runtime_transaction::{
+        drop_on_revert::is_drop_on_revert_requested,
+        instructions_processor::process_compute_budget_instructions,
+    },
     solana_sanitize::SanitizeError,
     solana_sdk::{
         clock::Slot,
@@ -53,6 +56,7 @@ pub struct ImmutableDeserializedPacket {
     transaction: SanitizedVersionedTransaction,
     message_hash: Hash,
     is_simple_vote: bool,
+    drop_on_revert: bool,
     compute_unit_price: u64,
     compute_unit_limit: u32,
 }
@@ -64,6 +68,13 @@ impl ImmutableDeserializedPacket {
         let message_bytes = packet_message(&packet)?;
         let message_hash = Message::hash_raw_message(message_bytes);
         let is_simple_vote = packet.meta().is_simple_vote_tx();
+        let drop_on_revert = packet.meta().is_drop_on_revert_packet()
+            || is_drop_on_revert_requested(
+                sanitized_transaction
+                    .get_message()
+                    .program_instructions_iter()
+                    .map(|(pubkey, ix)| (pubkey, SVMInstruction::from(ix))),
+            );
 
         // drop transaction if prioritization fails.
         let ComputeBudgetLimits {
@@ -89,6 +100,7 @@ impl ImmutableDeserializedPacket {
             transaction: sanitized_transaction,
             message_hash,
             is_simple_vote,
+            drop_on_revert,
             compute_unit_price,
             compute_unit_limit,
         })
@@ -110,6 +122,12 @@ impl ImmutableDeserializedPacket {
         self.is_simple_vote
     }
 
+    /// True if the packet arrived on a drop-on-revert socket or the transaction opted in with the
+    /// drop-on-revert memo.
+    pub fn drop_on_revert(&self) -> bool {
+        self.drop_on_revert
+    }
+
     pub fn compute_unit_price(&self) -> u64 {
         self.compute_unit_price
     }
@@ -140,7 +158,7 @@ impl ImmutableDeserializedPacket {
             self.transaction().clone(),
             *self.message_hash(),
             self.is_simple_vote(),
-            self.original_packet.meta().is_drop_on_revert_packet(),
+            self.drop_on_revert(),
             address_loader,
             reserved_account_keys,
         )
@@ -201,6 +219,7 @@ fn packet_message(packet: &Packet) -> Result<&[u8], DeserializedPacketError> {
 mod tests {
     use {
         super::*,
+        solana_runtime_transaction::drop_on_revert,
         solana_sdk::{
             compute_budget, instruction::Instruction, pubkey::Pubkey, signature::Keypair,
             signer::Signer, system_instruction, system_transaction, transaction::Transaction,
@@ -253,4 +272,35 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn drop_on_revert_from_flag_or_memo() {
+        let keypair = Keypair::new();
+        let transfer = system_instruction::transfer(&keypair.pubkey(), &Pubkey::new_unique(), 1);
+        let memo = Instruction::new_with_bytes(
+            drop_on_revert::MEMO_PROGRAM_ID,
+            drop_on_revert::DROP_ON_REVERT_MEMO,
+            vec![],
+        );
+        let packet = |ixs: &[Instruction]| {
+            let tx = Transaction::new_signed_with_payer(
+                ixs,
+                Some(&keypair.pubkey()),
+                &[&keypair],
+                Hash::new_unique(),
+            );
+            Packet::from_data(None, tx).unwrap()
+        };
+
+        let plain = ImmutableDeserializedPacket::new(packet(&[transfer.clone()])).unwrap();
+        assert!(!plain.drop_on_revert());
+
+        let mut flagged = packet(&[transfer.clone()]);
+        flagged.meta_mut().set_drop_on_revert(true);
+        let flagged = ImmutableDeserializedPacket::new(flagged).unwrap();
+        assert!(flagged.drop_on_revert());
+
+        let opted_in = ImmutableDeserializedPacket::new(packet(&[transfer, memo])).unwrap();
+        assert!(opted_in.drop_on_revert());
+    }
 }
@@ -420,6 +420,7 @@ fn report_transaction_error_metrics(errors: &TransactionErrorMetrics, id: &str,
         ("blockhash_too_old", errors.blockhash_too_old as i64, i64),
         ("call_chain_too_deep", errors.call_chain_too_deep as i64, i64),
         ("already_processed", errors.already_processed as i64, i64),
+        ("drop_on_revert", errors.drop_on_revert as i64, i64),
         ("instruction_error", errors.instruction_error as i64, i64),
         ("insufficient_funds", errors.insufficient_funds as i64, i64),
         (
@@ -11,6 +11,7 @@ use {
     solana_feature_set::FeatureSet,
     solana_measure::measure::Measure,
     solana_runtime::bank::Bank,
+    solana_runtime_transaction::drop_on_revert::MAX_DROP_ON_REVERT_COST,
     solana_sdk::{
         clock::Slot,
         saturating_add_assign,
@@ -20,8 +21,6 @@ use {
     std::sync::atomic::{AtomicU64, Ordering},
 };
 
-const MAX_DROP_ON_REVERT_COST: u64 = 550_000;
-
 // QosService is local to each banking thread, each instance of QosService provides services to
 // one banking thread.
 // Banking thread calls `report_metrics(slot)` at end of `process_and_record_transaction()`, or any time
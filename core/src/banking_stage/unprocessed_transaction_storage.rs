@@ -1,3 +1,5 @@
+#[cfg(feature = "dev-context-only-utils")]
+use qualifier_attr::qualifiers;
 use {
     super::{
         consumer::Consumer,
@@ -16,7 +18,7 @@ use {
         BankingStageStats, FilterForwardingResults, ForwardOption,
     },
     crate::{
-        bundle_stage::bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+        bundle_stage::{bundle_stage_leader_metrics::BundleStageLeaderMetrics, BundleDropReason},
         immutable_deserialized_bundle::ImmutableDeserializedBundle,
     },
     itertools::Itertools,
@@ -304,6 +306,7 @@ impl UnprocessedTransactionStorage {
             cost_model_buffered_bundle_storage: VecDeque::with_capacity(
                 BundleStorage::BUNDLE_STORAGE_CAPACITY,
             ),
+            dropped_bundles: Vec::new(),
         })
     }
 
@@ -515,6 +518,7 @@ impl UnprocessedTransactionStorage {
     }
 
     /// Inserts bundles into storage. Only supported for UnprocessedTransactionStorage::BundleStorage
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
     pub(crate) fn insert_bundles(
         &mut self,
         deserialized_bundles: Vec<ImmutableDeserializedBundle>,
@@ -1149,10 +1153,13 @@ pub struct InsertPacketBundlesSummary {
     pub num_bundles_inserted: usize,
     pub num_packets_inserted: usize,
     pub num_bundles_dropped: usize,
+    /// Tips of the bundles dropped, including buffered bundles evicted for more valuable ones
+    pub tip_lamports_dropped: u64,
 }
 
 /// Bundle storage has two deques: one for unprocessed bundles and another for ones that exceeded
-/// the cost model and need to get retried next slot.
+/// the cost model and need to get retried next slot. Both are ordered by the bundles' estimated tip
+/// per compute unit, highest first.
 #[derive(Debug)]
 pub struct BundleStorage {
     last_update_slot: Slot,
@@ -1160,6 +1167,8 @@ pub struct BundleStorage {
     // Storage for bundles that exceeded the cost model for the slot they were last attempted
     // execution on
     pub cost_model_buffered_bundle_storage: VecDeque<ImmutableDeserializedBundle>,
+    // Ids of the bundles dropped without being executed since they were last taken
+    dropped_bundles: Vec<(String, BundleDropReason)>,
 }
 
 impl BundleStorage {
@@ -1191,7 +1200,7 @@ impl BundleStorage {
     }
 
     pub(crate) fn max_receive_size(&self) -> usize {
-        MAX_BUFFERED_BUNDLES - self.unprocessed_bundle_storage.len()
+        MAX_BUFFERED_BUNDLES.saturating_sub(self.unprocessed_bundle_storage.len())
     }
 
     fn forward_option(&self) -> ForwardOption {
@@ -1202,43 +1211,72 @@ impl BundleStorage {
     pub fn reset(&mut self) -> (usize, usize) {
         let num_unprocessed_bundles = self.unprocessed_bundle_storage.len();
         let num_cost_model_buffered_bundles = self.cost_model_buffered_bundle_storage.len();
-        self.unprocessed_bundle_storage.clear();
-        self.cost_model_buffered_bundle_storage.clear();
+        self.dropped_bundles.extend(
+            self.unprocessed_bundle_storage
+                .drain(..)
+                .chain(self.cost_model_buffered_bundle_storage.drain(..))
+                .map(|bundle| (bundle.bundle_id().to_string(), BundleDropReason::NotLeader)),
+        );
         (num_unprocessed_bundles, num_cost_model_buffered_bundles)
     }
 
+    /// Records bundles dropped before they reached the storage.
+    pub(crate) fn extend_dropped_bundles(
+        &mut self,
+        dropped_bundles: impl IntoIterator<Item = (String, BundleDropReason)>,
+    ) {
+        self.dropped_bundles.extend(dropped_bundles);
+    }
+
+    /// Takes the ids of the bundles dropped without being executed, and why, since the last call.
+    pub(crate) fn take_dropped_bundles(&mut self) -> Vec<(String, BundleDropReason)> {
+        std::mem::take(&mut self.dropped_bundles)
+    }
+
+    /// Inserts bundles in descending order of tip per compute unit. Bundles pushed to the back
+    /// queue behind buffered bundles with an equal tip, bundles pushed to the front ahead of them.
+    /// Once the deque holds [Self::BUNDLE_STORAGE_CAPACITY] bundles, the lowest-value bundle is
+    /// dropped to make room for a more valuable one.
     fn insert_bundles(
         deque: &mut VecDeque<ImmutableDeserializedBundle>,
+        dropped_bundles: &mut Vec<(String, BundleDropReason)>,
         deserialized_bundles: Vec<ImmutableDeserializedBundle>,
         push_back: bool,
     ) -> InsertPacketBundlesSummary {
-        // deque should be initialized with size [Self::BUNDLE_STORAGE_CAPACITY]
-        let deque_free_space = Self::BUNDLE_STORAGE_CAPACITY
-            .checked_sub(deque.len())
-            .unwrap();
-        let bundles_to_insert_count = std::cmp::min(deque_free_space, deserialized_bundles.len());
-        let num_bundles_dropped = deserialized_bundles
-            .len()
-            .checked_sub(bundles_to_insert_count)
-            .unwrap();
-        let num_packets_inserted = deserialized_bundles
-            .iter()
-            .take(bundles_to_insert_count)
-            .map(|b| b.len())
-            .sum::<usize>();
-        let num_packets_dropped = deserialized_bundles
-            .iter()
-            .skip(bundles_to_insert_count)
-            .map(|b| b.len())
-            .sum::<usize>();
+        let mut num_bundles_inserted: usize = 0;
+        let mut num_packets_inserted: usize = 0;
+        let mut num_bundles_dropped: usize = 0;
+        let mut num_packets_dropped: usize = 0;
+        let mut tip_lamports_dropped: u64 = 0;
+        let mut drop_bundle = |bundle: ImmutableDeserializedBundle| {
+            saturating_add_assign!(num_bundles_dropped, 1);
+            saturating_add_assign!(num_packets_dropped, bundle.len());
+            saturating_add_assign!(tip_lamports_dropped, bundle.tip_lamports());
+            dropped_bundles.push((bundle.bundle_id().to_string(), BundleDropReason::Evicted));
+        };
 
-        let to_insert = deserialized_bundles
-            .into_iter()
-            .take(bundles_to_insert_count);
-        if push_back {
-            deque.extend(to_insert)
-        } else {
-            to_insert.for_each(|b| deque.push_front(b));
+        for bundle in deserialized_bundles {
+            let tip_per_compute_unit = bundle.tip_per_compute_unit();
+            if deque.len() >= Self::BUNDLE_STORAGE_CAPACITY {
+                match deque.back() {
+                    Some(lowest) if lowest.tip_per_compute_unit() < tip_per_compute_unit => {
+                        drop_bundle(deque.pop_back().unwrap());
+                    }
+                    _ => {
+                        drop_bundle(bundle);
+                        continue;
+                    }
+                }
+            }
+
+            let index = if push_back {
+                deque.partition_point(|b| b.tip_per_compute_unit() >= tip_per_compute_unit)
+            } else {
+                deque.partition_point(|b| b.tip_per_compute_unit() > tip_per_compute_unit)
+            };
+            saturating_add_assign!(num_bundles_inserted, 1);
+            saturating_add_assign!(num_packets_inserted, bundle.len());
+            deque.insert(index, bundle);
         }
 
         InsertPacketBundlesSummary {
@@ -1247,9 +1285,10 @@ impl BundleStorage {
                 num_dropped_tracer_packets: 0,
             }
             .into(),
-            num_bundles_inserted: bundles_to_insert_count,
+            num_bundles_inserted,
             num_packets_inserted,
             num_bundles_dropped,
+            tip_lamports_dropped,
         }
     }
 
@@ -1259,6 +1298,7 @@ impl BundleStorage {
     ) -> InsertPacketBundlesSummary {
         Self::insert_bundles(
             &mut self.unprocessed_bundle_storage,
+            &mut self.dropped_bundles,
             deserialized_bundles,
             false,
         )
@@ -1270,6 +1310,7 @@ impl BundleStorage {
     ) -> InsertPacketBundlesSummary {
         Self::insert_bundles(
             &mut self.cost_model_buffered_bundle_storage,
+            &mut self.dropped_bundles,
             deserialized_bundles,
             true,
         )
@@ -1282,6 +1323,7 @@ impl BundleStorage {
     ) -> InsertPacketBundlesSummary {
         Self::insert_bundles(
             &mut self.unprocessed_bundle_storage,
+            &mut self.dropped_bundles,
             deserialized_bundles,
             push_back,
         )
@@ -1315,6 +1357,8 @@ impl BundleStorage {
         let mut is_slot_over = false;
 
         let mut rebuffered_bundles = Vec::new();
+        let mut tip_lamports_landed: u64 = 0;
+        let mut tip_lamports_dropped: u64 = 0;
 
         sanitized_bundles
             .into_iter()
@@ -1324,6 +1368,10 @@ impl BundleStorage {
                     Ok(_) => {
                         debug!("bundle={} executed ok", sanitized_bundle.bundle_id);
                         // yippee
+                        saturating_add_assign!(
+                            tip_lamports_landed,
+                            deserialized_bundle.tip_lamports()
+                        );
                     }
                     Err(BundleExecutionError::PohRecordError(e)) => {
                         // buffer the bundle to the front of the queue to be attempted next slot
@@ -1346,7 +1394,9 @@ impl BundleStorage {
                             "bundle={} exceeds cost model, rebuffering",
                             sanitized_bundle.bundle_id
                         );
-                        self.push_back_cost_model_buffered_bundles(vec![deserialized_bundle]);
+                        let summary =
+                            self.push_back_cost_model_buffered_bundles(vec![deserialized_bundle]);
+                        saturating_add_assign!(tip_lamports_dropped, summary.tip_lamports_dropped);
                     }
                     Err(BundleExecutionError::TransactionFailure(
                         LoadAndExecuteBundleError::ProcessingTimeExceeded(_),
@@ -1357,34 +1407,60 @@ impl BundleStorage {
                             "bundle={} processing time exceeded, rebuffering",
                             sanitized_bundle.bundle_id
                         );
-                        self.push_back_cost_model_buffered_bundles(vec![deserialized_bundle]);
+                        let summary =
+                            self.push_back_cost_model_buffered_bundles(vec![deserialized_bundle]);
+                        saturating_add_assign!(tip_lamports_dropped, summary.tip_lamports_dropped);
                     }
                     Err(BundleExecutionError::TransactionFailure(e)) => {
                         debug!(
                             "bundle={} execution error: {:?}",
                             sanitized_bundle.bundle_id, e
                         );
-                        // do nothing
+                        saturating_add_assign!(
+                            tip_lamports_dropped,
+                            deserialized_bundle.tip_lamports()
+                        );
                     }
                     Err(BundleExecutionError::TipError(e)) => {
                         debug!("bundle={} tip error: {}", sanitized_bundle.bundle_id, e);
                         // Tip errors are _typically_ due to misconfiguration (except for poh record error, bank processing error, exceeds cost model)
                         // in order to prevent buffering too many bundles, we'll just drop the bundle
+                        saturating_add_assign!(
+                            tip_lamports_dropped,
+                            deserialized_bundle.tip_lamports()
+                        );
                     }
                     Err(BundleExecutionError::LockError) => {
                         // lock errors are irrecoverable due to malformed transactions
                         debug!("bundle={} lock error", sanitized_bundle.bundle_id);
+                        saturating_add_assign!(
+                            tip_lamports_dropped,
+                            deserialized_bundle.tip_lamports()
+                        );
                     }
                     // NB: Tip cutoff is static & front-runs will never succeed.
-                    Err(BundleExecutionError::FrontRun) => {}
+                    Err(BundleExecutionError::FrontRun) => {
+                        saturating_add_assign!(
+                            tip_lamports_dropped,
+                            deserialized_bundle.tip_lamports()
+                        );
+                    }
                 },
             );
 
         // rebuffered bundles are pushed onto deque in reverse order so the first bundle is at the front
         for bundle in rebuffered_bundles.into_iter().rev() {
-            self.push_front_unprocessed_bundles(vec![bundle]);
+            let summary = self.push_front_unprocessed_bundles(vec![bundle]);
+            saturating_add_assign!(tip_lamports_dropped, summary.tip_lamports_dropped);
         }
 
+        bundle_stage_leader_metrics
+            .bundle_stage_metrics_tracker()
+            .increment_landed_bundle_tip_lamports(tip_lamports_landed);
+        bundle_stage_leader_metrics
+            .bundle_stage_metrics_tracker()
+            .increment_dropped_bundle_tip_lamports(tip_lamports_dropped);
+
         is_slot_over
     }
 
@@ -1400,6 +1476,7 @@ impl BundleStorage {
         let start = Instant::now();
 
         let mut sanitized_bundles = Vec::new();
+        let dropped_bundles = &mut self.dropped_bundles;
 
         let move_precompile_verification_to_svm = bank
             .feature_set
@@ -1429,6 +1506,10 @@ impl BundleStorage {
                                     packet_bundle.bundle_id(),
                                     e
                                 );
+                                dropped_bundles.push((
+                                    packet_bundle.bundle_id().to_string(),
+                                    BundleDropReason::Invalid(e.to_string()),
+                                ));
                                 None
                             }
                         }
@@ -1457,6 +1538,10 @@ impl BundleStorage {
                             packet_bundle.bundle_id(),
                             e
                         );
+                        dropped_bundles.push((
+                            packet_bundle.bundle_id().to_string(),
+                            BundleDropReason::Invalid(e.to_string()),
+                        ));
                         None
                     }
                 }
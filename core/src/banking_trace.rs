@@ -1,5 +1,5 @@
 use {
-    crate::sigverify::SigverifyTracerPacketStats,
+    crate::{packet_bundle::PacketBundle, sigverify::SigverifyTracerPacketStats},
     bincode::serialize_into,
     chrono::{DateTime, Local},
     crossbeam_channel::{unbounded, Receiver, SendError, Sender, TryRecvError},
@@ -65,7 +65,7 @@ pub struct BankingTracer {
 #[cfg_attr(
     feature = "frozen-abi",
     derive(AbiExample),
-    frozen_abi(digest = "6PCDw6YSEivfbwhbPmE4NAsXb88ZX6hkFnruP8B38nma")
+    frozen_abi(digest = "9qR4wekCHCCCi6B2oZdNK78MC4KiWBfULLyaM9wHSnsr")
 )]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TimedTracedEvent(pub std::time::SystemTime, pub TracedEvent);
@@ -75,6 +75,32 @@ pub struct TimedTracedEvent(pub std::time::SystemTime, pub TracedEvent);
 pub enum TracedEvent {
     PacketBatch(ChannelLabel, BankingPacketBatch),
     BlockAndBankHash(Slot, Hash, Hash),
+    PacketBundles(Vec<TracedPacketBundle>),
+}
+
+/// A bundle as sent to `BundleStage`, along with how it was received.
+#[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TracedPacketBundle {
+    pub bundle: PacketBundle,
+    /// Whether any of the bundle's packets is flagged to be dropped if it reverts.
+    pub drop_on_revert: bool,
+    /// Whether the bundle came from a block engine whose packets are trusted to skip sigverify.
+    pub trusted: bool,
+}
+
+impl TracedPacketBundle {
+    fn new(bundle: PacketBundle, trusted: bool) -> Self {
+        let drop_on_revert = bundle
+            .batch
+            .iter()
+            .any(|packet| packet.meta().is_drop_on_revert_packet());
+        Self {
+            bundle,
+            drop_on_revert,
+            trusted,
+        }
+    }
 }
 
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample, AbiEnumVisitor))]
@@ -84,6 +110,8 @@ pub enum ChannelLabel {
     TpuVote,
     GossipVote,
     Dummy,
+    /// Packets from the block engine and relayer, which skip sigverify if trusted.
+    TrustedNonVote,
 }
 
 struct RollingConditionGrouped {
@@ -236,6 +264,23 @@ impl BankingTracer {
         self.create_channel(ChannelLabel::GossipVote)
     }
 
+    /// Packets of the returned sender are received by the non-vote `receiver`, but traced apart
+    /// from the sigverified ones.
+    pub fn create_sender_trusted_non_vote(
+        &self,
+        non_vote_sender: &BankingPacketSender,
+    ) -> BankingPacketSender {
+        TracedSender::new(
+            ChannelLabel::TrustedNonVote,
+            non_vote_sender.sender.clone(),
+            self.active_tracer.as_ref().cloned(),
+        )
+    }
+
+    pub fn create_channel_bundle(&self) -> (TracedBundleSender, Receiver<Vec<PacketBundle>>) {
+        Self::bundle_channel(self.active_tracer.as_ref().cloned())
+    }
+
     pub fn hash_event(&self, slot: Slot, blockhash: &Hash, bank_hash: &Hash) {
         self.trace_event(|| {
             TimedTracedEvent(
@@ -259,6 +304,23 @@ impl BankingTracer {
         Self::channel(ChannelLabel::Dummy, None)
     }
 
+    pub fn bundle_channel_for_test() -> (TracedBundleSender, Receiver<Vec<PacketBundle>>) {
+        Self::bundle_channel(None)
+    }
+
+    fn bundle_channel(
+        active_tracer: Option<ActiveTracer>,
+    ) -> (TracedBundleSender, Receiver<Vec<PacketBundle>>) {
+        let (sender, receiver) = unbounded();
+        (
+            TracedBundleSender {
+                sender,
+                active_tracer,
+            },
+            receiver,
+        )
+    }
+
     fn channel(
         label: ChannelLabel,
         active_tracer: Option<ActiveTracer>,
@@ -370,6 +432,48 @@ impl TracedSender {
     }
 }
 
+#[derive(Clone)]
+pub struct TracedBundleSender {
+    sender: Sender<Vec<PacketBundle>>,
+    active_tracer: Option<ActiveTracer>,
+}
+
+impl TracedBundleSender {
+    pub fn send(
+        &self,
+        bundles: Vec<PacketBundle>,
+        trusted: bool,
+    ) -> Result<(), SendError<Vec<PacketBundle>>> {
+        if let Some(ActiveTracer { trace_sender, exit }) = &self.active_tracer {
+            if !exit.load(Ordering::Relaxed) {
+                let traced_bundles = bundles
+                    .iter()
+                    .map(|bundle| TracedPacketBundle::new(bundle.clone(), trusted))
+                    .collect();
+                if let Err(err) = trace_sender.send(TimedTracedEvent(
+                    SystemTime::now(),
+                    TracedEvent::PacketBundles(traced_bundles),
+                )) {
+                    error!(
+                        "unexpected error when tracing a banking event...: {:?}",
+                        err
+                    );
+                    return Err(SendError(bundles));
+                }
+            }
+        }
+        self.sender.send(bundles)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[cfg(any(test, feature = "dev-context-only-utils"))]
 pub mod for_test {
     use {
@@ -547,6 +651,78 @@ mod tests {
         for_test::drop_and_clean_temp_dir_unless_suppressed(temp_dir);
     }
 
+    #[test]
+    fn test_record_and_restore_bundles() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("banking-trace");
+        let exit = Arc::<AtomicBool>::default();
+        let (tracer, tracer_thread) =
+            BankingTracer::new(Some((&path, exit.clone(), DirByteLimit::MAX))).unwrap();
+        let (non_vote_sender, non_vote_receiver) = tracer.create_channel_non_vote();
+        let trusted_non_vote_sender = tracer.create_sender_trusted_non_vote(&non_vote_sender);
+        let (bundle_sender, bundle_receiver) = tracer.create_channel_bundle();
+
+        let dummy_main_thread = thread::spawn(move || {
+            receiving_loop_with_minimized_sender_overhead::<_, TraceError, 0>(
+                exit,
+                non_vote_receiver,
+                |_packet_batch| Ok(()),
+            )
+        });
+
+        trusted_non_vote_sender
+            .send(for_test::sample_packet_batch())
+            .unwrap();
+        let mut batch = for_test::sample_packet_batch().0[0].clone();
+        batch[1].meta_mut().set_drop_on_revert(true);
+        let bundle = PacketBundle {
+            batch,
+            bundle_id: "bundle".to_string(),
+            block_builder_fee_info: None,
+        };
+        bundle_sender.send(vec![bundle], true).unwrap();
+        assert_eq!(bundle_receiver.try_recv().unwrap()[0].bundle_id, "bundle");
+
+        drop((trusted_non_vote_sender, bundle_sender));
+        for_test::terminate_tracer(
+            tracer,
+            tracer_thread,
+            dummy_main_thread,
+            non_vote_sender,
+            None,
+        );
+
+        let mut stream = BufReader::new(File::open(path.join(BASENAME)).unwrap());
+        let results = (0..=2)
+            .map(|_| bincode::deserialize_from::<_, TimedTracedEvent>(&mut stream))
+            .collect::<Vec<_>>();
+
+        assert_matches!(
+            results[0],
+            Ok(TimedTracedEvent(
+                _,
+                TracedEvent::PacketBatch(ChannelLabel::TrustedNonVote, _)
+            ))
+        );
+        assert_matches!(
+            &results[1],
+            Ok(TimedTracedEvent(_, TracedEvent::PacketBundles(bundles)))
+                if bundles.len() == 1
+                    && bundles[0].bundle.bundle_id == "bundle"
+                    && bundles[0].drop_on_revert
+                    && bundles[0].trusted
+        );
+        assert_matches!(
+            results[2],
+            Err(ref err) if matches!(
+                **err,
+                BincodeIoError(ref error) if error.kind() == UnexpectedEof
+            )
+        );
+
+        for_test::drop_and_clean_temp_dir_unless_suppressed(temp_dir);
+    }
+
     #[test]
     fn test_spill_over_at_rotation() {
         let temp_dir = TempDir::new().unwrap();
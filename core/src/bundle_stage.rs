@@ -8,15 +8,17 @@ use {
             unprocessed_transaction_storage::UnprocessedTransactionStorage,
         },
         bundle_stage::{
-            bundle_account_locker::BundleAccountLocker, bundle_consumer::BundleConsumer,
-            bundle_packet_receiver::BundleReceiver,
-            bundle_stage_leader_metrics::BundleStageLeaderMetrics, committer::Committer,
+            amm_registry::SharedAmmRegistry, bundle_account_locker::BundleAccountLocker,
+            bundle_consumer::BundleConsumer, bundle_packet_receiver::BundleReceiver,
+            bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+            bundle_worker_pool::BundleWorkerPool, committer::Committer,
+            sandwich_tracker::SandwichTracker,
         },
         packet_bundle::PacketBundle,
-        proxy::block_engine_stage::BlockBuilderFeeInfo,
         tip_manager::TipManager,
     },
-    crossbeam_channel::{Receiver, RecvTimeoutError},
+    crossbeam_channel::{Receiver, RecvTimeoutError, Sender},
+    solana_bundle::BundleExecutionError,
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::blockstore_processor::TransactionStatusSender,
     solana_measure::measure_us,
@@ -24,28 +26,66 @@ use {
     solana_runtime::{
         prioritization_fee_cache::PrioritizationFeeCache, vote_sender_types::ReplayVoteSender,
     },
-    solana_sdk::timing::AtomicInterval,
+    solana_sdk::{clock::Slot, timing::AtomicInterval},
     std::{
+        env,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
-            Arc, Mutex, RwLock,
+            Arc, RwLock,
         },
         thread::{self, Builder, JoinHandle},
         time::{Duration, Instant},
     },
 };
 
+pub mod amm_registry;
 pub mod bundle_account_locker;
-mod bundle_consumer;
+pub mod bundle_checks;
+pub mod bundle_consumer;
 mod bundle_packet_deserializer;
 mod bundle_packet_receiver;
-pub(crate) mod bundle_stage_leader_metrics;
-mod committer;
+pub mod bundle_scheduler;
+pub mod bundle_stage_leader_metrics;
+pub mod bundle_worker_pool;
+pub mod committer;
 mod front_run_identifier;
+pub mod sandwich_tracker;
 
 const MAX_BUNDLE_RETRY_DURATION: Duration = Duration::from_millis(40);
+/// Number of threads executing non-conflicting bundles in parallel
+pub const NUM_BUNDLE_STAGE_THREADS: usize = 4;
 const SLOT_BOUNDARY_CHECK_PERIOD: Duration = Duration::from_millis(10);
 
+/// Why a bundle was dropped without being executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleDropReason {
+    /// Failed to deserialize or sanitize.
+    Invalid(String),
+    /// Pushed out of the full bundle buffer by more valuable bundles.
+    Evicted,
+    /// Cleared from the buffer because this validator isn't leader soon.
+    NotLeader,
+}
+
+#[derive(Debug)]
+pub enum BundleResult {
+    /// Executed in the event's slot, and landed if Ok.
+    Executed(Result<(), BundleExecutionError>),
+    Dropped(BundleDropReason),
+}
+
+/// Outcome of a bundle, reported back to the block engine that sent it.
+#[derive(Debug)]
+pub struct BundleResultEvent {
+    pub bundle_id: String,
+    /// Slot the bundle was executed in, 0 for dropped bundles.
+    pub slot: Slot,
+    pub result: BundleResult,
+}
+
+pub type BundleResultSender = Sender<Vec<BundleResultEvent>>;
+pub type BundleResultReceiver = Receiver<Vec<BundleResultEvent>>;
+
 // Stats emitted periodically
 #[derive(Default)]
 pub struct BundleStageLoopMetrics {
@@ -199,19 +239,23 @@ impl BundleStage {
         cluster_info: &Arc<ClusterInfo>,
         poh_recorder: &Arc<RwLock<PohRecorder>>,
         bundle_receiver: Receiver<Vec<PacketBundle>>,
+        bundle_result_sender: BundleResultSender,
         transaction_status_sender: Option<TransactionStatusSender>,
         replay_vote_sender: ReplayVoteSender,
         log_messages_bytes_limit: Option<usize>,
         exit: Arc<AtomicBool>,
         tip_manager: TipManager,
         bundle_account_locker: BundleAccountLocker,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        amm_registry: SharedAmmRegistry,
+        sandwich_tracker: SandwichTracker,
+        num_threads: usize,
     ) -> Self {
         Self::start_bundle_thread(
             cluster_info,
             poh_recorder,
             bundle_receiver,
+            bundle_result_sender,
             transaction_status_sender,
             replay_vote_sender,
             log_messages_bytes_limit,
@@ -219,11 +263,22 @@ impl BundleStage {
             tip_manager,
             bundle_account_locker,
             MAX_BUNDLE_RETRY_DURATION,
-            block_builder_fee_info,
             prioritization_fee_cache,
+            amm_registry,
+            sandwich_tracker,
+            num_threads,
         )
     }
 
+    /// Number of bundle execution threads, overridable with `SOLANA_BUNDLE_STAGE_THREADS`.
+    pub fn num_threads() -> usize {
+        env::var("SOLANA_BUNDLE_STAGE_THREADS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(NUM_BUNDLE_STAGE_THREADS)
+            .max(1)
+    }
+
     pub fn join(self) -> thread::Result<()> {
         self.bundle_thread.join()
     }
@@ -233,6 +288,7 @@ impl BundleStage {
         cluster_info: &Arc<ClusterInfo>,
         poh_recorder: &Arc<RwLock<PohRecorder>>,
         bundle_receiver: Receiver<Vec<PacketBundle>>,
+        bundle_result_sender: BundleResultSender,
         transaction_status_sender: Option<TransactionStatusSender>,
         replay_vote_sender: ReplayVoteSender,
         log_message_bytes_limit: Option<usize>,
@@ -240,14 +296,21 @@ impl BundleStage {
         tip_manager: TipManager,
         bundle_account_locker: BundleAccountLocker,
         max_bundle_retry_duration: Duration,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
+        amm_registry: SharedAmmRegistry,
+        sandwich_tracker: SandwichTracker,
+        num_threads: usize,
     ) -> Self {
         const BUNDLE_STAGE_ID: u32 = 10_000;
         let poh_recorder = poh_recorder.clone();
         let cluster_info = cluster_info.clone();
 
-        let mut bundle_receiver = BundleReceiver::new(BUNDLE_STAGE_ID, bundle_receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(
+            BUNDLE_STAGE_ID,
+            bundle_receiver,
+            Some(5),
+            tip_manager.get_tip_accounts().clone(),
+        );
 
         let committer = Committer::new(
             transaction_status_sender,
@@ -265,9 +328,12 @@ impl BundleStage {
             log_message_bytes_limit,
             tip_manager,
             bundle_account_locker,
-            block_builder_fee_info.clone(),
             max_bundle_retry_duration,
             cluster_info,
+            amm_registry,
+            sandwich_tracker,
+            BundleWorkerPool::new(BUNDLE_STAGE_ID, num_threads),
+            bundle_result_sender,
         );
 
         let bundle_thread = Builder::new()
@@ -328,6 +394,8 @@ impl BundleStage {
             }
 
             let bundle_storage = unprocessed_bundle_storage.bundle_storage().unwrap();
+            consumer.report_dropped_bundles(bundle_storage.take_dropped_bundles());
+            consumer.record_rooted_funnel_payments();
             bundle_stage_metrics.increment_current_buffered_bundles_count(
                 bundle_storage.unprocessed_bundles_len() as u64,
             );
@@ -356,6 +424,7 @@ impl BundleStage {
 
         let (metrics_action, banking_stage_metrics_action) = bundle_stage_leader_metrics
             .check_leader_slot_boundary(decision.bank_start(), Some(unprocessed_bundle_storage));
+        consumer.check_worker_metrics_slot_boundary(decision.bank_start());
         bundle_stage_leader_metrics
             .leader_slot_metrics_tracker()
             .increment_make_decision_us(make_decision_time_us);
@@ -384,6 +453,7 @@ impl BundleStage {
             // BufferedPacketsDecision::Forward means the leader is slot is far away.
             // Bundles aren't forwarded because it breaks atomicity guarantees, so just drop them.
             BufferedPacketsDecision::Forward => {
+                // The cleared bundles are reported as dropped to their block engines
                 let (_num_bundles_cleared, _num_cost_model_buffered_bundles) =
                     unprocessed_bundle_storage.bundle_storage().unwrap().reset();
 
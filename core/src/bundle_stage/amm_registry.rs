@@ -0,0 +1,258 @@
+//! The set of AMM programs protected by front-run detection in the bundle stage.
+//!
+//! The registry can be loaded from a JSON file of the form:
+//!
+//! ```json
+//! {
+//!   "programs": [
+//!     { "program_id": "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", "policy": "reject" },
+//!     { "program_id": "PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY", "policy": "log-only" }
+//!   ]
+//! }
+//! ```
+//!
+//! and reloaded at runtime through the admin RPC.
+use {
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        fs,
+        path::Path,
+        str::FromStr,
+        sync::{Arc, RwLock},
+    },
+    thiserror::Error,
+};
+
+pub const DEFAULT_AMM_PROGRAMS: &[Pubkey] = &[
+    solana_sdk::pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"), // RaydiumV4
+    solana_sdk::pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"), // Serum DEX V3
+    solana_sdk::pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB"), // Meteora CPMM
+    solana_sdk::pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"),  // Whirlpool
+    solana_sdk::pubkey!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX"),  // Serum
+    solana_sdk::pubkey!("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1"), // Orca AMM V1
+    solana_sdk::pubkey!("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP"), // Orca AMM V2
+    solana_sdk::pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"),  // Meteora DLMM
+    solana_sdk::pubkey!("SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ"),  // Saber
+    solana_sdk::pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"), // Raydium CLMM
+    solana_sdk::pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"), // Raydium CPMM
+    solana_sdk::pubkey!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY"),  // Phoenix
+    solana_sdk::pubkey!("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb"),  // Open Book
+    solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"),  // Pump.fun
+];
+
+/// An [AmmRegistry] that can be swapped at runtime.
+///
+/// Readers take a snapshot with [SharedAmmRegistry::load] rather than holding the lock, so a
+/// reload never waits on (or blocks) a pass over the bundles.
+#[derive(Debug, Clone, Default)]
+pub struct SharedAmmRegistry(Arc<RwLock<Arc<AmmRegistry>>>);
+
+impl SharedAmmRegistry {
+    pub fn new(registry: AmmRegistry) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(registry))))
+    }
+
+    /// Returns the current registry.
+    pub fn load(&self) -> Arc<AmmRegistry> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Replaces the registry; snapshots already taken keep the previous one.
+    pub fn store(&self, registry: AmmRegistry) {
+        *self.0.write().unwrap() = Arc::new(registry);
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AmmRegistryError {
+    #[error("failed to read registry: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse registry: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("invalid program id: {0}")]
+    InvalidProgramId(String),
+
+    #[error("duplicate program id: {0}")]
+    DuplicateProgramId(Pubkey),
+}
+
+/// What the bundle stage does when a bundle front-runs a pool owned by the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AmmPolicy {
+    /// Drop the bundle.
+    Reject,
+    /// Log and count the front-run but still commit the bundle.
+    LogOnly,
+}
+
+impl AmmPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AmmPolicy::Reject => "reject",
+            AmmPolicy::LogOnly => "log-only",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AmmRegistryFile {
+    programs: Vec<AmmRegistryEntry>,
+}
+
+#[derive(Deserialize)]
+struct AmmRegistryEntry {
+    program_id: String,
+    policy: AmmRegistryPolicy,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+enum AmmRegistryPolicy {
+    Reject,
+    LogOnly,
+    /// Do not inspect the program's accounts at all; the same as leaving the program out.
+    Allow,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmmRegistry {
+    programs: HashMap<Pubkey, AmmPolicy>,
+}
+
+impl Default for AmmRegistry {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_AMM_PROGRAMS
+                .iter()
+                .map(|program| (*program, AmmPolicy::Reject)),
+        )
+    }
+}
+
+impl AmmRegistry {
+    pub fn new(programs: impl IntoIterator<Item = (Pubkey, AmmPolicy)>) -> Self {
+        Self {
+            programs: programs.into_iter().collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AmmRegistryError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, AmmRegistryError> {
+        let file: AmmRegistryFile = serde_json::from_str(json)?;
+
+        let mut entries = HashMap::with_capacity(file.programs.len());
+        for entry in file.programs {
+            let program_id = Pubkey::from_str(&entry.program_id)
+                .map_err(|_| AmmRegistryError::InvalidProgramId(entry.program_id))?;
+            if entries.insert(program_id, entry.policy).is_some() {
+                return Err(AmmRegistryError::DuplicateProgramId(program_id));
+            }
+        }
+
+        Ok(Self::new(entries.into_iter().filter_map(
+            |(program_id, policy)| match policy {
+                AmmRegistryPolicy::Reject => Some((program_id, AmmPolicy::Reject)),
+                AmmRegistryPolicy::LogOnly => Some((program_id, AmmPolicy::LogOnly)),
+                AmmRegistryPolicy::Allow => None,
+            },
+        )))
+    }
+
+    /// Returns the policy for `program`, or `None` if the program is not an AMM we protect.
+    pub fn policy(&self, program: &Pubkey) -> Option<AmmPolicy> {
+        self.programs.get(program).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Write};
+
+    #[test]
+    fn test_default_rejects_builtin_programs() {
+        let registry = AmmRegistry::default();
+
+        assert_eq!(registry.len(), DEFAULT_AMM_PROGRAMS.len());
+        for program in DEFAULT_AMM_PROGRAMS {
+            assert_eq!(registry.policy(program), Some(AmmPolicy::Reject));
+        }
+        assert_eq!(registry.policy(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_load() {
+        let reject = Pubkey::new_unique();
+        let log_only = Pubkey::new_unique();
+        let allow = Pubkey::new_unique();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"{{"programs": [
+                {{"program_id": "{reject}", "policy": "reject"}},
+                {{"program_id": "{log_only}", "policy": "log-only"}},
+                {{"program_id": "{allow}", "policy": "allow"}}
+            ]}}"#
+        )
+        .unwrap();
+
+        let registry = AmmRegistry::load(file.path()).unwrap();
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.policy(&reject), Some(AmmPolicy::Reject));
+        assert_eq!(registry.policy(&log_only), Some(AmmPolicy::LogOnly));
+        assert_eq!(registry.policy(&allow), None);
+    }
+
+    #[test]
+    fn test_shared_registry_snapshot() {
+        let shared = SharedAmmRegistry::default();
+        let snapshot = shared.load();
+
+        // Storing doesn't wait for the snapshot to be dropped, and doesn't change it.
+        let program = Pubkey::new_unique();
+        shared.store(AmmRegistry::new([(program, AmmPolicy::LogOnly)]));
+
+        assert_eq!(*snapshot, AmmRegistry::default());
+        assert_eq!(shared.load().policy(&program), Some(AmmPolicy::LogOnly));
+    }
+
+    #[test]
+    fn test_from_json_errors() {
+        assert_matches!(
+            AmmRegistry::from_json(r#"{"programs": [{"program_id": "bad", "policy": "reject"}]}"#),
+            Err(AmmRegistryError::InvalidProgramId(_))
+        );
+
+        let program = Pubkey::new_unique();
+        assert_matches!(
+            AmmRegistry::from_json(&format!(
+                r#"{{"programs": [
+                    {{"program_id": "{program}", "policy": "reject"}},
+                    {{"program_id": "{program}", "policy": "allow"}}
+                ]}}"#
+            )),
+            Err(AmmRegistryError::DuplicateProgramId(p)) if p == program
+        );
+
+        assert_matches!(
+            AmmRegistry::from_json(&format!(
+                r#"{{"programs": [{{"program_id": "{program}", "policy": "block"}}]}}"#
+            )),
+            Err(AmmRegistryError::Parse(_))
+        );
+    }
+}
@@ -268,22 +268,26 @@ mod tests {
         let mut packet_bundle0 = PacketBundle {
             batch: PacketBatch::new(vec![Packet::from_data(None, &tx0).unwrap()]),
             bundle_id: tx0.signatures[0].to_string(),
+            block_builder_fee_info: None,
         };
         let mut packet_bundle1 = PacketBundle {
             batch: PacketBatch::new(vec![Packet::from_data(None, &tx1).unwrap()]),
             bundle_id: tx1.signatures[0].to_string(),
+            block_builder_fee_info: None,
         };
 
         let mut transaction_errors = TransactionErrorMetrics::default();
 
-        let sanitized_bundle0 = ImmutableDeserializedBundle::new(&mut packet_bundle0, None, &Ok)
-            .unwrap()
-            .build_sanitized_bundle(&bank, &HashSet::default(), &mut transaction_errors, false)
-            .expect("sanitize bundle 0");
-        let sanitized_bundle1 = ImmutableDeserializedBundle::new(&mut packet_bundle1, None, &Ok)
-            .unwrap()
-            .build_sanitized_bundle(&bank, &HashSet::default(), &mut transaction_errors, false)
-            .expect("sanitize bundle 1");
+        let sanitized_bundle0 =
+            ImmutableDeserializedBundle::new(&mut packet_bundle0, None, &HashSet::default(), &Ok)
+                .unwrap()
+                .build_sanitized_bundle(&bank, &HashSet::default(), &mut transaction_errors, false)
+                .expect("sanitize bundle 0");
+        let sanitized_bundle1 =
+            ImmutableDeserializedBundle::new(&mut packet_bundle1, None, &HashSet::default(), &Ok)
+                .unwrap()
+                .build_sanitized_bundle(&bank, &HashSet::default(), &mut transaction_errors, false)
+                .expect("sanitize bundle 1");
 
         let locked_bundle0 = bundle_account_locker
             .prepare_locked_bundle(&sanitized_bundle0, &bank)
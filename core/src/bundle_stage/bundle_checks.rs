@@ -0,0 +1,51 @@
+//! The bundle stage's checks exposed to `simulateBundle`.
+use {
+    super::{
+        amm_registry::{AmmPolicy, SharedAmmRegistry},
+        front_run_identifier::{find_bundle_front_run, FrontRun},
+    },
+    crate::tip_manager::{TipManager, TipManagerConfig},
+    solana_bundle::bundle_execution::LoadAndExecuteBundleOutput,
+    solana_rpc::bundle_checks::BundleChecks,
+    solana_rpc_client_api::bundles::RpcBundleFrontRun,
+    solana_sdk::pubkey::Pubkey,
+    std::collections::HashSet,
+};
+
+#[derive(Debug)]
+pub struct BundleStageChecks {
+    tip_accounts: HashSet<Pubkey>,
+    amm_registry: SharedAmmRegistry,
+}
+
+impl BundleStageChecks {
+    pub fn new(tip_manager_config: &TipManagerConfig, amm_registry: SharedAmmRegistry) -> Self {
+        Self {
+            tip_accounts: TipManager::derive_tip_accounts(
+                &tip_manager_config.tip_payment_program_id,
+            ),
+            amm_registry,
+        }
+    }
+}
+
+impl BundleChecks for BundleStageChecks {
+    fn tip_accounts(&self) -> &HashSet<Pubkey> {
+        &self.tip_accounts
+    }
+
+    fn find_front_run<'a>(
+        &self,
+        bundle: &'a LoadAndExecuteBundleOutput<'a>,
+    ) -> Option<RpcBundleFrontRun> {
+        let FrontRun {
+            program, policy, ..
+        } = find_bundle_front_run(bundle, &self.amm_registry.load())?;
+
+        Some(RpcBundleFrontRun {
+            program: program.to_string(),
+            policy: policy.as_str().to_string(),
+            rejected: policy == AmmPolicy::Reject,
+        })
+    }
+}
@@ -8,12 +8,18 @@ use {
             unprocessed_transaction_storage::UnprocessedTransactionStorage,
         },
         bundle_stage::{
+            amm_registry::{AmmPolicy, AmmRegistry, SharedAmmRegistry},
             bundle_account_locker::{BundleAccountLocker, LockedBundle},
+            bundle_scheduler::schedule_bundle_rounds,
             bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+            bundle_worker_pool::BundleWorkerPool,
             committer::Committer,
+            front_run_identifier::{find_bundle_front_run, FrontRun},
+            sandwich_tracker::SandwichTracker,
+            BundleDropReason, BundleResult, BundleResultEvent, BundleResultSender,
         },
         immutable_deserialized_bundle::ImmutableDeserializedBundle,
-        proxy::block_engine_stage::BlockBuilderFeeInfo,
+        packet_bundle::BlockBuilderFeeInfo,
         tip_manager::TipManager,
     },
     itertools::Itertools,
@@ -33,9 +39,9 @@ use {
     },
     solana_svm::transaction_error_metrics::TransactionErrorMetrics,
     std::{
-        collections::HashSet,
-        sync::{Arc, Mutex},
-        time::{Duration, Instant},
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
     },
 };
 
@@ -50,6 +56,7 @@ pub struct ExecuteRecordCommitResult {
     execution_metrics: BundleExecutionMetrics,
     execute_and_commit_timings: LeaderExecuteAndCommitTimings,
     transaction_error_counter: TransactionErrorMetrics,
+    front_run: Option<FrontRun>,
 }
 
 pub struct BundleConsumer {
@@ -59,7 +66,8 @@ pub struct BundleConsumer {
     log_messages_bytes_limit: Option<usize>,
 
     tip_manager: TipManager,
-    last_tip_update_slot: Slot,
+    // Slot and fee info the tip programs were last cranked with
+    last_tip_update: Option<(Slot, BlockBuilderFeeInfo)>,
 
     blacklisted_accounts: HashSet<Pubkey>,
 
@@ -67,11 +75,18 @@ pub struct BundleConsumer {
     // with BankingStage
     bundle_account_locker: BundleAccountLocker,
 
-    block_builder_fee_info: Arc<Mutex<BlockBuilderFeeInfo>>,
-
     max_bundle_retry_duration: Duration,
 
     cluster_info: Arc<ClusterInfo>,
+
+    amm_registry: SharedAmmRegistry,
+    sandwich_tracker: SandwichTracker,
+
+    // Executes non-conflicting bundles in parallel
+    worker_pool: BundleWorkerPool,
+
+    // Reports the outcome of each executed bundle to the block engine stage
+    bundle_result_sender: BundleResultSender,
 }
 
 impl BundleConsumer {
@@ -83,9 +98,12 @@ impl BundleConsumer {
         log_messages_bytes_limit: Option<usize>,
         tip_manager: TipManager,
         bundle_account_locker: BundleAccountLocker,
-        block_builder_fee_info: Arc<Mutex<BlockBuilderFeeInfo>>,
         max_bundle_retry_duration: Duration,
         cluster_info: Arc<ClusterInfo>,
+        amm_registry: SharedAmmRegistry,
+        sandwich_tracker: SandwichTracker,
+        worker_pool: BundleWorkerPool,
+        bundle_result_sender: BundleResultSender,
     ) -> Self {
         let blacklisted_accounts = HashSet::from_iter([tip_manager.tip_payment_program_id()]);
         Self {
@@ -94,16 +112,46 @@ impl BundleConsumer {
             qos_service,
             log_messages_bytes_limit,
             tip_manager,
-            // MAX because sending tips during slot 0 in tests doesn't work
-            last_tip_update_slot: u64::MAX,
+            last_tip_update: None,
             blacklisted_accounts,
             bundle_account_locker,
-            block_builder_fee_info,
             max_bundle_retry_duration,
             cluster_info,
+            amm_registry,
+            sandwich_tracker,
+            worker_pool,
+            bundle_result_sender,
         }
     }
 
+    /// Starts, reports and resets the per-slot metrics of the bundle workers.
+    pub(crate) fn check_worker_metrics_slot_boundary(&mut self, bank_start: Option<&BankStart>) {
+        self.worker_pool.check_leader_slot_boundary(bank_start);
+    }
+
+    /// Persists the funnel settlements of crank bundles whose slot was rooted.
+    pub(crate) fn record_rooted_funnel_payments(&self) {
+        self.tip_manager.record_rooted_funnel_payments();
+    }
+
+    /// Reports the bundles dropped without being executed to the block engines that sent them.
+    pub(crate) fn report_dropped_bundles(&self, dropped_bundles: Vec<(String, BundleDropReason)>) {
+        if dropped_bundles.is_empty() {
+            return;
+        }
+        // The block engine stage may have shut down, nothing to report to then
+        let _ = self.bundle_result_sender.send(
+            dropped_bundles
+                .into_iter()
+                .map(|(bundle_id, reason)| BundleResultEvent {
+                    bundle_id,
+                    slot: 0,
+                    result: BundleResult::Dropped(reason),
+                })
+                .collect(),
+        );
+    }
+
     // A bundle is a series of transactions to be executed sequentially, atomically, and all-or-nothing.
     // Sequentially:
     //  - Transactions are executed in order
@@ -127,6 +175,10 @@ impl BundleConsumer {
         unprocessed_transaction_storage: &mut UnprocessedTransactionStorage,
         bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
     ) {
+        self.tip_manager
+            .observe_leader_bank(&bank_start.working_bank);
+
+        let amm_registry = self.amm_registry.load();
         let reached_end_of_slot = unprocessed_transaction_storage.process_bundles(
             bank_start.working_bank.clone(),
             bundle_stage_leader_metrics,
@@ -135,14 +187,17 @@ impl BundleConsumer {
                 Self::do_process_bundles(
                     &self.bundle_account_locker,
                     &self.tip_manager,
-                    &mut self.last_tip_update_slot,
+                    &mut self.last_tip_update,
                     &self.cluster_info,
-                    &self.block_builder_fee_info,
                     &self.committer,
                     &self.transaction_recorder,
                     &self.qos_service,
                     &self.log_messages_bytes_limit,
                     self.max_bundle_retry_duration,
+                    &amm_registry,
+                    &self.sandwich_tracker,
+                    &mut self.worker_pool,
+                    &self.bundle_result_sender,
                     bundles,
                     bank_start,
                     bundle_stage_leader_metrics,
@@ -163,14 +218,17 @@ impl BundleConsumer {
     fn do_process_bundles(
         bundle_account_locker: &BundleAccountLocker,
         tip_manager: &TipManager,
-        last_tip_updated_slot: &mut Slot,
+        last_tip_update: &mut Option<(Slot, BlockBuilderFeeInfo)>,
         cluster_info: &Arc<ClusterInfo>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         committer: &Committer,
         recorder: &TransactionRecorder,
         qos_service: &QosService,
         log_messages_bytes_limit: &Option<usize>,
         max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
+        worker_pool: &mut BundleWorkerPool,
+        bundle_result_sender: &BundleResultSender,
         bundles: &[(ImmutableDeserializedBundle, SanitizedBundle)],
         bank_start: &BankStart,
         bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
@@ -191,36 +249,93 @@ impl BundleConsumer {
             .bundle_stage_metrics_tracker()
             .increment_locked_bundle_elapsed_us(locked_bundles_elapsed_us);
 
-        let (execution_results, execute_locked_bundles_elapsed_us) =
-            measure_us!(locked_bundle_results
-                .into_iter()
-                .map(|r| match r {
-                    Ok(locked_bundle) => {
-                        let (r, measure) = measure_us!(Self::process_bundle(
-                            bundle_account_locker,
-                            tip_manager,
-                            last_tip_updated_slot,
-                            cluster_info,
-                            block_builder_fee_info,
+        let mut execution_results: Vec<_> = locked_bundle_results
+            .iter()
+            .map(|r| {
+                r.as_ref()
+                    .err()
+                    .map(|_| Err(BundleExecutionError::LockError))
+            })
+            .collect();
+        let locked_bundles: Vec<_> = locked_bundle_results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, r)| Some((index, r.as_ref().ok()?)))
+            .collect();
+
+        let ((), execute_locked_bundles_elapsed_us) = measure_us!({
+            // Cranks that failed for a block builder aren't retried until the next batch
+            let mut tip_crank_errors = HashMap::new();
+            for round in Self::schedule_rounds(&locked_bundles) {
+                // The tip programs are cranked ahead of the round so the crank never runs
+                // concurrently with other bundles
+                let tip_crank_result = match Self::tip_block_builder_fee_info(
+                    &round,
+                    &locked_bundles,
+                    bundles,
+                    tip_manager,
+                    cluster_info,
+                ) {
+                    Some(block_builder_fee_info) => Self::maybe_crank_tip_programs(
+                        bundle_account_locker,
+                        tip_manager,
+                        last_tip_update,
+                        &mut tip_crank_errors,
+                        cluster_info,
+                        &block_builder_fee_info,
+                        committer,
+                        recorder,
+                        qos_service,
+                        log_messages_bytes_limit,
+                        max_bundle_retry_duration,
+                        amm_registry,
+                        sandwich_tracker,
+                        bank_start,
+                        bundle_stage_leader_metrics,
+                    ),
+                    None => Ok(()),
+                };
+
+                let round_results = worker_pool.execute(&round, |&index, worker_metrics| {
+                    let (_, locked_bundle) = locked_bundles[index];
+                    let sanitized_bundle = locked_bundle.sanitized_bundle();
+                    let (r, measure) = measure_us!(match &tip_crank_result {
+                        Err(e)
+                            if Self::bundle_touches_tip_pdas(
+                                sanitized_bundle,
+                                tip_manager.get_tip_accounts(),
+                            ) =>
+                        {
+                            Err(e.clone())
+                        }
+                        _ => Self::process_bundle(
                             committer,
                             recorder,
                             qos_service,
                             log_messages_bytes_limit,
                             max_bundle_retry_duration,
-                            &locked_bundle,
+                            amm_registry,
+                            sandwich_tracker,
+                            locked_bundle,
                             bank_start,
-                            bundle_stage_leader_metrics,
-                        ));
-                        bundle_stage_leader_metrics
-                            .leader_slot_metrics_tracker()
-                            .increment_process_packets_transactions_us(measure);
-                        r
-                    }
-                    Err(_) => {
-                        Err(BundleExecutionError::LockError)
-                    }
-                })
-                .collect::<Vec<_>>());
+                            worker_metrics,
+                        ),
+                    });
+                    worker_metrics
+                        .leader_slot_metrics_tracker()
+                        .increment_process_packets_transactions_us(measure);
+                    r
+                });
+                for (index, result) in round.into_iter().zip(round_results) {
+                    let (bundle_index, _) = locked_bundles[index];
+                    execution_results[bundle_index] = Some(result);
+                }
+            }
+        });
+        let execution_results: Vec<_> = execution_results
+            .into_iter()
+            .map(|r| r.expect("every bundle is either locked and executed or failed to lock"))
+            .collect();
 
         bundle_stage_leader_metrics
             .bundle_stage_metrics_tracker()
@@ -231,21 +346,72 @@ impl BundleConsumer {
                 .increment_bundle_execution_result(result);
         });
 
+        // The block engine stage may have shut down, nothing to report to then
+        let _ = bundle_result_sender.send(
+            bundles
+                .iter()
+                .zip(&execution_results)
+                .map(|((_, sanitized_bundle), result)| BundleResultEvent {
+                    bundle_id: sanitized_bundle.bundle_id.clone(),
+                    slot: bank_start.working_bank.slot(),
+                    result: BundleResult::Executed(result.clone()),
+                })
+                .collect(),
+        );
+
         execution_results
     }
 
+    /// Groups the locked bundles into rounds of non-conflicting bundles, returned as indexes into
+    /// `locked_bundles`.
+    fn schedule_rounds(locked_bundles: &[(usize, &LockedBundle)]) -> Vec<Vec<usize>> {
+        schedule_bundle_rounds(
+            locked_bundles
+                .iter()
+                .map(|(_, locked_bundle)| locked_bundle.sanitized_bundle()),
+        )
+    }
+
+    /// Returns the fee info the tip programs must be cranked with before executing `round`, if it
+    /// has a bundle touching the tip accounts. Tipping bundles write the tip accounts, so a round
+    /// holds at most one, and bundles sent without fee info pay the whole tip to the leader.
+    fn tip_block_builder_fee_info(
+        round: &[usize],
+        locked_bundles: &[(usize, &LockedBundle)],
+        bundles: &[(ImmutableDeserializedBundle, SanitizedBundle)],
+        tip_manager: &TipManager,
+        cluster_info: &ClusterInfo,
+    ) -> Option<BlockBuilderFeeInfo> {
+        let (bundle_index, _) =
+            round
+                .iter()
+                .map(|&index| locked_bundles[index])
+                .find(|(_, locked_bundle)| {
+                    Self::bundle_touches_tip_pdas(
+                        locked_bundle.sanitized_bundle(),
+                        tip_manager.get_tip_accounts(),
+                    )
+                })?;
+        let (bundle, _) = &bundles[bundle_index];
+        Some(
+            bundle
+                .block_builder_fee_info()
+                .unwrap_or(BlockBuilderFeeInfo {
+                    block_builder: cluster_info.id(),
+                    block_builder_commission: 0,
+                }),
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn process_bundle(
-        bundle_account_locker: &BundleAccountLocker,
-        tip_manager: &TipManager,
-        last_tip_updated_slot: &mut Slot,
-        cluster_info: &Arc<ClusterInfo>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         committer: &Committer,
         recorder: &TransactionRecorder,
         qos_service: &QosService,
         log_messages_bytes_limit: &Option<usize>,
         max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
         locked_bundle: &LockedBundle,
         bank_start: &BankStart,
         bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
@@ -257,42 +423,14 @@ impl BundleConsumer {
             return Err(BundleExecutionError::BankProcessingTimeLimitReached);
         }
 
-        if bank_start.working_bank.slot() != *last_tip_updated_slot
-            && Self::bundle_touches_tip_pdas(
-                locked_bundle.sanitized_bundle(),
-                tip_manager.get_tip_accounts(),
-            )
-        {
-            let start = Instant::now();
-            let result = Self::handle_tip_programs(
-                bundle_account_locker,
-                tip_manager,
-                cluster_info,
-                block_builder_fee_info,
-                committer,
-                recorder,
-                qos_service,
-                log_messages_bytes_limit,
-                max_bundle_retry_duration,
-                bank_start,
-                bundle_stage_leader_metrics,
-            );
-
-            bundle_stage_leader_metrics
-                .bundle_stage_metrics_tracker()
-                .increment_change_tip_receiver_elapsed_us(start.elapsed().as_micros() as u64);
-
-            result?;
-
-            *last_tip_updated_slot = bank_start.working_bank.slot();
-        }
-
         Self::update_qos_and_execute_record_commit_bundle(
             committer,
             recorder,
             qos_service,
             log_messages_bytes_limit,
             max_bundle_retry_duration,
+            amm_registry,
+            sandwich_tracker,
             locked_bundle.sanitized_bundle(),
             bank_start,
             bundle_stage_leader_metrics,
@@ -301,18 +439,79 @@ impl BundleConsumer {
         Ok(())
     }
 
+    /// Cranks the tip programs with `block_builder_fee_info` unless they already were this slot.
+    #[allow(clippy::too_many_arguments)]
+    fn maybe_crank_tip_programs(
+        bundle_account_locker: &BundleAccountLocker,
+        tip_manager: &TipManager,
+        last_tip_update: &mut Option<(Slot, BlockBuilderFeeInfo)>,
+        tip_crank_errors: &mut HashMap<BlockBuilderFeeInfo, BundleExecutionError>,
+        cluster_info: &Arc<ClusterInfo>,
+        block_builder_fee_info: &BlockBuilderFeeInfo,
+        committer: &Committer,
+        recorder: &TransactionRecorder,
+        qos_service: &QosService,
+        log_messages_bytes_limit: &Option<usize>,
+        max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
+        bank_start: &BankStart,
+        bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
+    ) -> Result<(), BundleExecutionError> {
+        let tip_update = (bank_start.working_bank.slot(), *block_builder_fee_info);
+        if *last_tip_update == Some(tip_update)
+            || !Bank::should_bank_still_be_processing_txs(
+                &bank_start.bank_creation_time,
+                bank_start.working_bank.ns_per_slot,
+            )
+        {
+            return Ok(());
+        }
+        if let Some(e) = tip_crank_errors.get(block_builder_fee_info) {
+            return Err(e.clone());
+        }
+
+        let (result, elapsed_us) = measure_us!(Self::handle_tip_programs(
+            bundle_account_locker,
+            tip_manager,
+            cluster_info,
+            block_builder_fee_info,
+            committer,
+            recorder,
+            qos_service,
+            log_messages_bytes_limit,
+            max_bundle_retry_duration,
+            amm_registry,
+            sandwich_tracker,
+            bank_start,
+            bundle_stage_leader_metrics,
+        ));
+        bundle_stage_leader_metrics
+            .bundle_stage_metrics_tracker()
+            .increment_change_tip_receiver_elapsed_us(elapsed_us);
+        match &result {
+            Ok(()) => *last_tip_update = Some(tip_update),
+            Err(e) => {
+                tip_crank_errors.insert(*block_builder_fee_info, e.clone());
+            }
+        }
+        result
+    }
+
     /// The validator needs to manage state on two programs related to tips
     #[allow(clippy::too_many_arguments)]
     fn handle_tip_programs(
         bundle_account_locker: &BundleAccountLocker,
         tip_manager: &TipManager,
         cluster_info: &Arc<ClusterInfo>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
+        block_builder_fee_info: &BlockBuilderFeeInfo,
         committer: &Committer,
         recorder: &TransactionRecorder,
         qos_service: &QosService,
         log_messages_bytes_limit: &Option<usize>,
         max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
         bank_start: &BankStart,
         bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
     ) -> Result<(), BundleExecutionError> {
@@ -341,6 +540,8 @@ impl BundleConsumer {
                 qos_service,
                 log_messages_bytes_limit,
                 max_bundle_retry_duration,
+                amm_registry,
+                sandwich_tracker,
                 locked_init_tip_programs_bundle.sanitized_bundle(),
                 bank_start,
                 bundle_stage_leader_metrics,
@@ -372,11 +573,11 @@ impl BundleConsumer {
         let tip_crank_bundle = tip_manager.get_tip_programs_crank_bundle(
             &bank_start.working_bank,
             &kp,
-            &block_builder_fee_info.lock().unwrap(),
+            block_builder_fee_info,
         )?;
         debug!("tip_crank_bundle is_some: {}", tip_crank_bundle.is_some());
 
-        if let Some(bundle) = tip_crank_bundle {
+        if let Some((bundle, funnel_payment)) = tip_crank_bundle {
             info!(
                 "bundle id: {} cranking tip programs with {} transactions",
                 bundle.bundle_id,
@@ -393,6 +594,8 @@ impl BundleConsumer {
                 qos_service,
                 log_messages_bytes_limit,
                 max_bundle_retry_duration,
+                amm_registry,
+                sandwich_tracker,
                 locked_tip_crank_bundle.sanitized_bundle(),
                 bank_start,
                 bundle_stage_leader_metrics,
@@ -401,17 +604,30 @@ impl BundleConsumer {
                 bundle_stage_leader_metrics
                     .bundle_stage_metrics_tracker()
                     .increment_num_change_tip_receiver_errors(1);
-                error!(
-                    "bundle: {} error cranking tip programs: {:?}",
-                    locked_tip_crank_bundle.sanitized_bundle().bundle_id,
-                    e
-                );
+                match TipManager::find_funnel_error(locked_tip_crank_bundle.sanitized_bundle(), &e)
+                {
+                    Some(funnel_error) => error!(
+                        "bundle: {} funnel rejected tip crank: {}; error: {:?}",
+                        locked_tip_crank_bundle.sanitized_bundle().bundle_id,
+                        funnel_error,
+                        e
+                    ),
+                    None => error!(
+                        "bundle: {} error cranking tip programs: {:?}",
+                        locked_tip_crank_bundle.sanitized_bundle().bundle_id,
+                        e
+                    ),
+                }
                 BundleExecutionError::TipError(TipError::CrankTipError)
             })?;
 
             bundle_stage_leader_metrics
                 .bundle_stage_metrics_tracker()
                 .increment_num_change_tip_receiver_ok(1);
+
+            if let Some(funnel_payment) = funnel_payment {
+                tip_manager.record_funnel_payment(bank_start.working_bank.slot(), &funnel_payment);
+            }
         }
 
         Ok(())
@@ -452,6 +668,8 @@ impl BundleConsumer {
         qos_service: &QosService,
         log_messages_bytes_limit: &Option<usize>,
         max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
         sanitized_bundle: &SanitizedBundle,
         bank_start: &BankStart,
         bundle_stage_leader_metrics: &mut BundleStageLeaderMetrics,
@@ -481,6 +699,8 @@ impl BundleConsumer {
             recorder,
             log_messages_bytes_limit,
             max_bundle_retry_duration,
+            amm_registry,
+            sandwich_tracker,
             sanitized_bundle,
             bank_start,
         ));
@@ -488,6 +708,11 @@ impl BundleConsumer {
         bundle_stage_leader_metrics
             .bundle_stage_metrics_tracker()
             .increment_num_execution_retries(result.execution_metrics.num_retries);
+        if let Some(front_run) = result.front_run {
+            bundle_stage_leader_metrics
+                .bundle_stage_metrics_tracker()
+                .increment_front_run(front_run);
+        }
         bundle_stage_leader_metrics
             .leader_slot_metrics_tracker()
             .accumulate_transaction_errors(&result.transaction_error_counter);
@@ -562,6 +787,8 @@ impl BundleConsumer {
         recorder: &TransactionRecorder,
         log_messages_bytes_limit: &Option<usize>,
         max_bundle_retry_duration: Duration,
+        amm_registry: &AmmRegistry,
+        sandwich_tracker: &SandwichTracker,
         sanitized_bundle: &SanitizedBundle,
         bank_start: &BankStart,
     ) -> ExecuteRecordCommitResult {
@@ -607,28 +834,58 @@ impl BundleConsumer {
                 execution_metrics,
                 execute_and_commit_timings,
                 transaction_error_counter,
+                front_run: None,
             };
         }
 
-        // NB: Must run before we start committing the transactions.
-        if super::front_run_identifier::is_bundle_front_run(&bundle_execution_results) {
-            info!(
-                "Dropping front run bundle; bundle_id={}; txs=[{}]",
-                sanitized_bundle.bundle_id,
-                sanitized_bundle
-                    .transactions
-                    .iter()
-                    .map(|tx| tx.signature().to_string())
-                    .join(", ")
-            );
+        let (freeze_lock, freeze_lock_us) = measure_us!(bank_start.working_bank.freeze_lock());
+        execute_and_commit_timings.freeze_lock_us = freeze_lock_us;
 
-            return ExecuteRecordCommitResult {
-                commit_transaction_details: vec![],
-                result: Err(BundleExecutionError::FrontRun),
-                execution_metrics,
-                execute_and_commit_timings,
-                transaction_error_counter,
-            };
+        // NB: Must run before we start committing the transactions. The slot's AMM writes are
+        // only locked for the check so recording into PoH doesn't hold up other commits.
+        let slot = bank_start.working_bank.slot();
+        let front_run = match find_bundle_front_run(&bundle_execution_results, amm_registry) {
+            Some(front_run) if front_run.policy == AmmPolicy::Reject => Some(front_run),
+            front_run => sandwich_tracker
+                .lock(slot)
+                .find_bundle_sandwich(amm_registry, &bundle_execution_results)
+                .or(front_run),
+        };
+        if let Some(FrontRun {
+            program,
+            policy,
+            sandwich,
+        }) = front_run
+        {
+            let txs = sanitized_bundle
+                .transactions
+                .iter()
+                .map(|tx| tx.signature().to_string())
+                .join(", ");
+
+            match policy {
+                AmmPolicy::Reject => {
+                    info!(
+                        "Dropping front run bundle; bundle_id={}; program={program}; \
+                         sandwich={sandwich}; txs=[{txs}]",
+                        sanitized_bundle.bundle_id,
+                    );
+
+                    return ExecuteRecordCommitResult {
+                        commit_transaction_details: vec![],
+                        result: Err(BundleExecutionError::FrontRun),
+                        execution_metrics,
+                        execute_and_commit_timings,
+                        transaction_error_counter,
+                        front_run,
+                    };
+                }
+                AmmPolicy::LogOnly => info!(
+                    "Allowing front run bundle; bundle_id={}; program={program}; \
+                     sandwich={sandwich}; txs=[{txs}]",
+                    sanitized_bundle.bundle_id,
+                ),
+            }
         }
 
         let (executed_batches, execution_results_to_transactions_us) =
@@ -644,9 +901,6 @@ impl BundleConsumer {
                 .collect::<Vec<usize>>()
         );
 
-        let (freeze_lock, freeze_lock_us) = measure_us!(bank_start.working_bank.freeze_lock());
-        execute_and_commit_timings.freeze_lock_us = freeze_lock_us;
-
         let (
             RecordTransactionsSummary {
                 result: record_transactions_result,
@@ -678,9 +932,15 @@ impl BundleConsumer {
                 execution_metrics,
                 execute_and_commit_timings,
                 transaction_error_counter,
+                front_run,
             };
         }
 
+        // The bundle is in PoH so it lands in this slot; remember its pool writes.
+        sandwich_tracker
+            .lock(slot)
+            .record_bundle(amm_registry, &bundle_execution_results);
+
         // note: execute_and_commit_timings.commit_us handled inside this function
         let (commit_us, commit_bundle_details) = committer.commit_bundle(
             &mut bundle_execution_results,
@@ -716,6 +976,7 @@ impl BundleConsumer {
             execution_metrics,
             execute_and_commit_timings,
             transaction_error_counter,
+            front_run,
         }
     }
 
@@ -735,13 +996,17 @@ mod tests {
     use {
         crate::{
             bundle_stage::{
-                bundle_account_locker::BundleAccountLocker, bundle_consumer::BundleConsumer,
+                amm_registry::{AmmRegistry, SharedAmmRegistry},
+                bundle_account_locker::BundleAccountLocker,
+                bundle_consumer::BundleConsumer,
                 bundle_packet_deserializer::BundlePacketDeserializer,
-                bundle_stage_leader_metrics::BundleStageLeaderMetrics, committer::Committer,
-                QosService, UnprocessedTransactionStorage,
+                bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+                bundle_worker_pool::BundleWorkerPool,
+                committer::Committer,
+                sandwich_tracker::SandwichTracker,
+                BundleResult, BundleResultEvent, QosService, UnprocessedTransactionStorage,
             },
-            packet_bundle::PacketBundle,
-            proxy::block_engine_stage::BlockBuilderFeeInfo,
+            packet_bundle::{BlockBuilderFeeInfo, PacketBundle},
             tip_manager::{
                 tests::MockBlockstore, TipDistributionAccountConfig, TipManager, TipManagerConfig,
             },
@@ -770,6 +1035,7 @@ mod tests {
             prioritization_fee_cache::PrioritizationFeeCache,
         },
         solana_sdk::{
+            account::AccountSharedData,
             bundle::derive_bundle_id,
             clock::MAX_PROCESSING_AGE,
             fee_calculator::{FeeRateGovernor, DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE},
@@ -780,7 +1046,8 @@ mod tests {
             poh_config::PohConfig,
             pubkey::Pubkey,
             rent::Rent,
-            signature::{Keypair, Signer},
+            signature::{Keypair, Signature, Signer},
+            system_program,
             system_transaction::transfer,
             transaction::{SanitizedTransaction, TransactionError, VersionedTransaction},
             vote::state::VoteState,
@@ -795,7 +1062,7 @@ mod tests {
             str::FromStr,
             sync::{
                 atomic::{AtomicBool, Ordering},
-                Arc, Mutex, RwLock,
+                Arc, RwLock,
             },
             thread::{Builder, JoinHandle},
             time::Duration,
@@ -964,6 +1231,7 @@ mod tests {
                             .collect(),
                     ),
                     bundle_id,
+                    block_builder_fee_info: None,
                 }
             })
             .collect()
@@ -976,7 +1244,7 @@ mod tests {
         funnel: Option<Pubkey>,
     ) -> TipManager {
         TipManager::new(
-            Arc::new(RwLock::new(MockBlockstore(vec![]))),
+            Arc::new(RwLock::new(MockBlockstore::default())),
             cluster_info,
             leader_schedule_cache,
             TipManagerConfig {
@@ -1041,12 +1309,8 @@ mod tests {
             &genesis_config_info.voting_keypair.pubkey(),
             None,
         );
-        let block_builder_pubkey = Pubkey::new_unique();
-        let block_builder_info = Arc::new(Mutex::new(BlockBuilderFeeInfo {
-            block_builder: block_builder_pubkey,
-            block_builder_commission: 10,
-        }));
 
+        let (bundle_result_sender, bundle_result_receiver) = unbounded();
         let mut consumer = BundleConsumer::new(
             committer,
             recorder,
@@ -1054,9 +1318,12 @@ mod tests {
             None,
             tip_manager,
             BundleAccountLocker::default(),
-            block_builder_info,
             Duration::from_secs(10),
             cluster_info,
+            SharedAmmRegistry::default(),
+            SandwichTracker::default(),
+            BundleWorkerPool::new(1, 1),
+            bundle_result_sender,
         );
 
         let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
@@ -1074,6 +1341,7 @@ mod tests {
         let deserialized_bundle = BundlePacketDeserializer::deserialize_bundle(
             packet_bundles.get_mut(0).unwrap(),
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -1127,6 +1395,15 @@ mod tests {
             .collect();
         assert_eq!(transactions, bundle_versioned_transactions);
 
+        let bundle_results = bundle_result_receiver.try_recv().unwrap();
+        assert_eq!(bundle_results.len(), 1);
+        assert_eq!(bundle_results[0].bundle_id, sanitized_bundle.bundle_id);
+        assert_eq!(bundle_results[0].slot, bank.slot());
+        assert!(matches!(
+            bundle_results[0].result,
+            BundleResult::Executed(Ok(()))
+        ));
+
         let check_results = bank.check_transactions(
             &sanitized_bundle.transactions,
             &vec![Ok(()); sanitized_bundle.transactions.len()],
@@ -1149,6 +1426,207 @@ mod tests {
         // TODO (LB): cleanup blockstore
     }
 
+    fn packet_bundle(transactions: &[VersionedTransaction]) -> PacketBundle {
+        PacketBundle {
+            batch: PacketBatch::new(
+                transactions
+                    .iter()
+                    .map(|tx| Packet::from_data(None, tx).unwrap())
+                    .collect(),
+            ),
+            bundle_id: derive_bundle_id(transactions).unwrap(),
+            block_builder_fee_info: None,
+        }
+    }
+
+    /// Executes `bundles`, in priority order, on a consumer with `num_workers` workers. Returns
+    /// the reported results along with the signatures in the order they were recorded to PoH.
+    fn consume_bundles_with_workers(
+        num_workers: usize,
+        make_bundles: impl FnOnce(&GenesisConfigInfo, &Bank) -> Vec<Vec<VersionedTransaction>>,
+    ) -> (Vec<BundleResultEvent>, Vec<Signature>) {
+        let TestFixture {
+            genesis_config_info,
+            leader_keypair,
+            bank,
+            exit,
+            poh_recorder,
+            poh_simulator,
+            entry_receiver,
+            bank_forks: _bank_forks,
+        } = create_test_fixture(1_000_000);
+        let bundles = make_bundles(&genesis_config_info, &bank);
+
+        let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+        let committer = Committer::new(
+            None,
+            replay_vote_sender,
+            Arc::new(PrioritizationFeeCache::new(0u64)),
+        );
+        let cluster_info = Arc::new(ClusterInfo::new(
+            ContactInfo::new(leader_keypair.pubkey(), 0, 0),
+            Arc::new(leader_keypair),
+            SocketAddrSpace::new(true),
+        ));
+        let tip_manager = get_tip_manager(
+            cluster_info.clone(),
+            Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+            &genesis_config_info.voting_keypair.pubkey(),
+            None,
+        );
+        let (bundle_result_sender, bundle_result_receiver) = unbounded();
+        let mut consumer = BundleConsumer::new(
+            committer,
+            poh_recorder.read().unwrap().new_recorder(),
+            QosService::new(1),
+            None,
+            tip_manager,
+            BundleAccountLocker::default(),
+            Duration::from_secs(10),
+            cluster_info,
+            SharedAmmRegistry::default(),
+            SandwichTracker::default(),
+            BundleWorkerPool::new(1, num_workers),
+            bundle_result_sender,
+        );
+
+        let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
+        let mut bundle_storage = UnprocessedTransactionStorage::new_bundle_storage();
+        let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(1);
+        let deserialized_bundles: Vec<_> = bundles
+            .iter()
+            .map(|transactions| {
+                BundlePacketDeserializer::deserialize_bundle(
+                    &mut packet_bundle(transactions),
+                    None,
+                    &HashSet::default(),
+                    &Ok,
+                )
+                .unwrap()
+            })
+            .collect();
+        let summary = bundle_storage.insert_bundles(deserialized_bundles);
+        assert_eq!(summary.num_bundles_inserted, bundles.len());
+
+        consumer.consume_buffered_bundles(
+            &bank_start,
+            &mut bundle_storage,
+            &mut bundle_stage_leader_metrics,
+        );
+
+        let num_transactions = bundles.iter().map(Vec::len).sum();
+        let mut recorded = Vec::new();
+        while recorded.len() < num_transactions {
+            let WorkingBankEntry { entries_ticks, .. } = entry_receiver.recv().unwrap();
+            recorded.extend(
+                entries_ticks
+                    .into_iter()
+                    .flat_map(|(entry, _)| entry.transactions)
+                    .map(|tx| tx.signatures[0]),
+            );
+        }
+        let results = bundle_result_receiver.try_iter().flatten().collect();
+
+        poh_recorder
+            .write()
+            .unwrap()
+            .is_exited
+            .store(true, Ordering::Relaxed);
+        exit.store(true, Ordering::Relaxed);
+        poh_simulator.join().unwrap();
+
+        (results, recorded)
+    }
+
+    /// Bundles that all write the same account are spread over several workers, but must still
+    /// run one at a time, without failing on each other's account locks, in priority order.
+    #[test]
+    fn test_multiple_workers_conflicting_bundles() {
+        solana_logger::setup();
+        let mut signatures = Vec::new();
+        let (results, recorded) = consume_bundles_with_workers(4, |genesis_config_info, bank| {
+            let mint_keypair = &genesis_config_info.mint_keypair;
+            let lamports = bank.get_minimum_balance_for_rent_exemption(0);
+            (0..8)
+                .map(|_| {
+                    let tx = VersionedTransaction::from(transfer(
+                        mint_keypair,
+                        &Pubkey::new_unique(),
+                        lamports,
+                        genesis_config_info.genesis_config.hash(),
+                    ));
+                    signatures.push(tx.signatures[0]);
+                    vec![tx]
+                })
+                .collect()
+        });
+
+        assert_eq!(results.len(), signatures.len());
+        assert!(results
+            .iter()
+            .all(|result| matches!(result.result, BundleResult::Executed(Ok(())))));
+        assert_eq!(recorded, signatures);
+    }
+
+    /// Non-conflicting bundles may be recorded in any order, but bundles that conflict with each
+    /// other keep their priority order even when interleaved with them.
+    #[test]
+    fn test_multiple_workers_mixed_bundles() {
+        solana_logger::setup();
+        let mut mint_signatures = Vec::new();
+        let mut all_signatures = Vec::new();
+        let (results, recorded) = consume_bundles_with_workers(4, |genesis_config_info, bank| {
+            let mint_keypair = &genesis_config_info.mint_keypair;
+            let blockhash = genesis_config_info.genesis_config.hash();
+            let lamports = bank.get_minimum_balance_for_rent_exemption(0);
+            (0..12)
+                .map(|i| {
+                    let payer = if i % 3 == 0 {
+                        mint_keypair.insecure_clone()
+                    } else {
+                        let payer = Keypair::new();
+                        bank.store_account(
+                            &payer.pubkey(),
+                            &AccountSharedData::new(sol_to_lamports(1.0), 0, &system_program::id()),
+                        );
+                        payer
+                    };
+                    let bundle: Vec<_> = (0..2)
+                        .map(|_| {
+                            VersionedTransaction::from(transfer(
+                                &payer,
+                                &Pubkey::new_unique(),
+                                lamports,
+                                blockhash,
+                            ))
+                        })
+                        .collect();
+                    if payer.pubkey() == mint_keypair.pubkey() {
+                        mint_signatures.extend(bundle.iter().map(|tx| tx.signatures[0]));
+                    }
+                    all_signatures.extend(bundle.iter().map(|tx| tx.signatures[0]));
+                    bundle
+                })
+                .collect()
+        });
+
+        assert_eq!(results.len(), 12);
+        assert!(results
+            .iter()
+            .all(|result| matches!(result.result, BundleResult::Executed(Ok(())))));
+
+        let mut sorted_recorded = recorded.clone();
+        sorted_recorded.sort();
+        all_signatures.sort();
+        assert_eq!(sorted_recorded, all_signatures);
+
+        let recorded_mint_signatures: Vec<_> = recorded
+            .into_iter()
+            .filter(|signature| mint_signatures.contains(signature))
+            .collect();
+        assert_eq!(recorded_mint_signatures, mint_signatures);
+    }
+
     /// Happy-path bundle execution to ensure tip management works.
     /// Tip management involves cranking setup bundles before executing the test bundle
     #[test]
@@ -1187,10 +1665,6 @@ mod tests {
             &genesis_config_info.voting_keypair.pubkey(),
             None,
         );
-        let block_builder_info = Arc::new(Mutex::new(BlockBuilderFeeInfo {
-            block_builder: block_builder_pubkey,
-            block_builder_commission: 10,
-        }));
 
         let mut consumer = BundleConsumer::new(
             committer,
@@ -1199,9 +1673,12 @@ mod tests {
             None,
             tip_manager.clone(),
             BundleAccountLocker::default(),
-            block_builder_info,
             Duration::from_secs(10),
             cluster_info.clone(),
+            SharedAmmRegistry::default(),
+            SandwichTracker::default(),
+            BundleWorkerPool::new(1, 1),
+            unbounded().0,
         );
 
         let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
@@ -1225,10 +1702,20 @@ mod tests {
             )
             .unwrap()]),
             bundle_id: "test_transfer".to_string(),
+            block_builder_fee_info: Some(BlockBuilderFeeInfo {
+                block_builder: block_builder_pubkey,
+                block_builder_commission: 10,
+            }),
         };
 
-        let deserialized_bundle =
-            BundlePacketDeserializer::deserialize_bundle(&mut packet_bundle, None, &Ok).unwrap();
+        let deserialized_bundle = BundlePacketDeserializer::deserialize_bundle(
+            &mut packet_bundle,
+            None,
+            tip_accounts,
+            &Ok,
+        )
+        .unwrap();
+        assert_eq!(deserialized_bundle.tip_lamports(), 1);
         let mut error_metrics = TransactionErrorMetrics::default();
         let sanitized_bundle = deserialized_bundle
             .build_sanitized_bundle(
@@ -1326,6 +1813,170 @@ mod tests {
         poh_simulator.join().unwrap();
     }
 
+    /// Bundles from different block engines crank the tip programs with their own block builder
+    /// before landing.
+    #[test]
+    fn test_bundle_tips_pay_their_block_builder() {
+        solana_logger::setup();
+        let TestFixture {
+            genesis_config_info,
+            leader_keypair,
+            bank,
+            exit,
+            poh_recorder,
+            poh_simulator,
+            entry_receiver,
+            bank_forks: _bank_forks,
+        } = create_test_fixture(1_000_000);
+        let recorder = poh_recorder.read().unwrap().new_recorder();
+
+        let (replay_vote_sender, _replay_vote_receiver) = unbounded();
+        let committer = Committer::new(
+            None,
+            replay_vote_sender,
+            Arc::new(PrioritizationFeeCache::new(0u64)),
+        );
+        let cluster_info = Arc::new(ClusterInfo::new(
+            ContactInfo::new(leader_keypair.pubkey(), 0, 0),
+            Arc::new(leader_keypair),
+            SocketAddrSpace::new(true),
+        ));
+        let tip_manager = get_tip_manager(
+            cluster_info.clone(),
+            Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+            &genesis_config_info.voting_keypair.pubkey(),
+            None,
+        );
+        let mut consumer = BundleConsumer::new(
+            committer,
+            recorder,
+            QosService::new(1),
+            None,
+            tip_manager.clone(),
+            BundleAccountLocker::default(),
+            Duration::from_secs(10),
+            cluster_info.clone(),
+            SharedAmmRegistry::default(),
+            SandwichTracker::default(),
+            BundleWorkerPool::new(1, 1),
+            unbounded().0,
+        );
+
+        let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
+        let mut bundle_storage = UnprocessedTransactionStorage::new_bundle_storage();
+        let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(1);
+
+        // Both bundles tip the same account, so they land in separate rounds, highest tip first.
+        let tip_account = *tip_manager.get_tip_accounts().iter().next().unwrap();
+        let block_builder_fee_infos = [
+            BlockBuilderFeeInfo {
+                block_builder: Pubkey::new_unique(),
+                block_builder_commission: 10,
+            },
+            BlockBuilderFeeInfo {
+                block_builder: Pubkey::new_unique(),
+                block_builder_commission: 20,
+            },
+        ];
+        let sanitized_bundles: Vec<_> = block_builder_fee_infos
+            .iter()
+            .zip([2, 1])
+            .map(|(block_builder_fee_info, tip)| {
+                let transaction = VersionedTransaction::from(transfer(
+                    &genesis_config_info.mint_keypair,
+                    &tip_account,
+                    tip,
+                    genesis_config_info.genesis_config.hash(),
+                ));
+                let mut packet_bundle = packet_bundle(&[transaction]);
+                packet_bundle.block_builder_fee_info = Some(*block_builder_fee_info);
+                let deserialized_bundle = BundlePacketDeserializer::deserialize_bundle(
+                    &mut packet_bundle,
+                    None,
+                    tip_manager.get_tip_accounts(),
+                    &Ok,
+                )
+                .unwrap();
+                let sanitized_bundle = deserialized_bundle
+                    .build_sanitized_bundle(
+                        &bank_start.working_bank,
+                        &HashSet::default(),
+                        &mut TransactionErrorMetrics::default(),
+                        false,
+                    )
+                    .unwrap();
+                let summary = bundle_storage.insert_bundles(vec![deserialized_bundle]);
+                assert_eq!(summary.num_bundles_inserted, 1);
+                sanitized_bundle
+            })
+            .collect();
+
+        consumer.consume_buffered_bundles(
+            &bank_start,
+            &mut bundle_storage,
+            &mut bundle_stage_leader_metrics,
+        );
+
+        let mut transactions = Vec::new();
+        while let Ok(WorkingBankEntry { entries_ticks, .. }) = entry_receiver.recv() {
+            transactions.extend(entries_ticks.into_iter().flat_map(|(e, _)| e.transactions));
+            if transactions.len() == 7 {
+                break;
+            }
+        }
+
+        // After initializing the tip programs, each bundle is preceded by a crank switching the
+        // block builder to its own
+        let keypair = cluster_info.keypair().clone();
+        let my_tip_receiver = derive_tip_distribution_account_address(
+            &tip_manager.tip_distribution_program_id(),
+            &genesis_config_info.validator_pubkey,
+            bank_start.working_bank.epoch(),
+        )
+        .0;
+        let change_block_builder_tx =
+            |old_tip_receiver: &Pubkey,
+             old_block_builder: &Pubkey,
+             block_builder_fee_info: &BlockBuilderFeeInfo| {
+                tip_manager
+                    .build_change_tip_receiver_and_block_builder_tx(
+                        old_tip_receiver,
+                        &my_tip_receiver,
+                        &bank_start.working_bank,
+                        &keypair,
+                        old_block_builder,
+                        &block_builder_fee_info.block_builder,
+                        block_builder_fee_info.block_builder_commission,
+                    )
+                    .to_versioned_transaction()
+            };
+        assert_eq!(
+            transactions[3..],
+            [
+                change_block_builder_tx(
+                    &keypair.pubkey(),
+                    &keypair.pubkey(),
+                    &block_builder_fee_infos[0]
+                ),
+                sanitized_bundles[0].transactions[0].to_versioned_transaction(),
+                change_block_builder_tx(
+                    &my_tip_receiver,
+                    &block_builder_fee_infos[0].block_builder,
+                    &block_builder_fee_infos[1]
+                ),
+                sanitized_bundles[1].transactions[0].to_versioned_transaction(),
+            ]
+        );
+
+        poh_recorder
+            .write()
+            .unwrap()
+            .is_exited
+            .store(true, Ordering::Relaxed);
+        exit.store(true, Ordering::Relaxed);
+        poh_simulator.join().unwrap();
+    }
+
     #[test]
     fn test_handle_tip_programs() {
         solana_logger::setup();
@@ -1362,10 +2013,10 @@ mod tests {
             &genesis_config_info.voting_keypair.pubkey(),
             None,
         );
-        let block_builder_info = Arc::new(Mutex::new(BlockBuilderFeeInfo {
+        let block_builder_info = BlockBuilderFeeInfo {
             block_builder: block_builder_pubkey,
             block_builder_commission: 10,
-        }));
+        };
 
         let bank_start = poh_recorder.read().unwrap().bank_start().unwrap();
 
@@ -1381,6 +2032,8 @@ mod tests {
                 &QosService::new(1),
                 &None,
                 Duration::from_secs(10),
+                &AmmRegistry::default(),
+                &SandwichTracker::default(),
                 &bank_start,
                 &mut bundle_stage_leader_metrics
             ),
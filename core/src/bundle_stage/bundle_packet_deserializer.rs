@@ -5,12 +5,16 @@ use {
             immutable_deserialized_packet::ImmutableDeserializedPacket,
             packet_filter::PacketFilterFailure,
         },
+        bundle_stage::BundleDropReason,
         immutable_deserialized_bundle::{DeserializedBundleError, ImmutableDeserializedBundle},
         packet_bundle::PacketBundle,
     },
     crossbeam_channel::{Receiver, RecvTimeoutError},
-    solana_sdk::saturating_add_assign,
-    std::time::{Duration, Instant},
+    solana_sdk::{pubkey::Pubkey, saturating_add_assign},
+    std::{
+        collections::HashSet,
+        time::{Duration, Instant},
+    },
 };
 
 /// Results from deserializing packet batches.
@@ -18,8 +22,8 @@ use {
 pub struct ReceiveBundleResults {
     /// Deserialized bundles from all received bundle packets
     pub deserialized_bundles: Vec<ImmutableDeserializedBundle>,
-    /// Number of dropped bundles
-    pub num_dropped_bundles: usize,
+    /// Ids of the bundles that failed to deserialize, and why
+    pub dropped_bundles: Vec<(String, BundleDropReason)>,
 }
 
 pub struct BundlePacketDeserializer {
@@ -27,16 +31,20 @@ pub struct BundlePacketDeserializer {
     bundle_packet_receiver: Receiver<Vec<PacketBundle>>,
     /// Max packets per bundle
     max_packets_per_bundle: Option<usize>,
+    /// Accounts whose inbound transfers count towards a bundle's tip
+    tip_accounts: HashSet<Pubkey>,
 }
 
 impl BundlePacketDeserializer {
     pub fn new(
         bundle_packet_receiver: Receiver<Vec<PacketBundle>>,
         max_packets_per_bundle: Option<usize>,
+        tip_accounts: HashSet<Pubkey>,
     ) -> Self {
         Self {
             bundle_packet_receiver,
             max_packets_per_bundle,
+            tip_accounts,
         }
     }
 
@@ -56,6 +64,7 @@ impl BundlePacketDeserializer {
             bundle_count,
             &mut bundles,
             self.max_packets_per_bundle,
+            &self.tip_accounts,
             packet_filter,
         ))
     }
@@ -66,27 +75,36 @@ impl BundlePacketDeserializer {
         bundle_count: usize,
         bundles: &mut [PacketBundle],
         max_packets_per_bundle: Option<usize>,
+        tip_accounts: &HashSet<Pubkey>,
         packet_filter: &impl Fn(
             ImmutableDeserializedPacket,
         ) -> Result<ImmutableDeserializedPacket, PacketFilterFailure>,
     ) -> ReceiveBundleResults {
         let mut deserialized_bundles = Vec::with_capacity(bundle_count);
-        let mut num_dropped_bundles: usize = 0;
+        let mut dropped_bundles = Vec::new();
 
         for bundle in bundles.iter_mut() {
-            match Self::deserialize_bundle(bundle, max_packets_per_bundle, packet_filter) {
+            match Self::deserialize_bundle(
+                bundle,
+                max_packets_per_bundle,
+                tip_accounts,
+                packet_filter,
+            ) {
                 Ok(deserialized_bundle) => {
                     deserialized_bundles.push(deserialized_bundle);
                 }
-                Err(_) => {
-                    saturating_add_assign!(num_dropped_bundles, 1);
+                Err(e) => {
+                    dropped_bundles.push((
+                        bundle.bundle_id.clone(),
+                        BundleDropReason::Invalid(e.to_string()),
+                    ));
                 }
             }
         }
 
         ReceiveBundleResults {
             deserialized_bundles,
-            num_dropped_bundles,
+            dropped_bundles,
         }
     }
 
@@ -133,11 +151,17 @@ impl BundlePacketDeserializer {
     pub fn deserialize_bundle(
         bundle: &mut PacketBundle,
         max_packets_per_bundle: Option<usize>,
+        tip_accounts: &HashSet<Pubkey>,
         packet_filter: &impl Fn(
             ImmutableDeserializedPacket,
         ) -> Result<ImmutableDeserializedPacket, PacketFilterFailure>,
     ) -> Result<ImmutableDeserializedBundle, DeserializedBundleError> {
-        ImmutableDeserializedBundle::new(bundle, max_packets_per_bundle, packet_filter)
+        ImmutableDeserializedBundle::new(
+            bundle,
+            max_packets_per_bundle,
+            tip_accounts,
+            packet_filter,
+        )
     }
 }
 
@@ -154,12 +178,15 @@ mod tests {
 
     #[test]
     fn test_deserialize_and_collect_bundles_empty() {
-        let results =
-            BundlePacketDeserializer::deserialize_and_collect_bundles(0, &mut [], Some(5), &|p| {
-                Ok(p)
-            });
+        let results = BundlePacketDeserializer::deserialize_and_collect_bundles(
+            0,
+            &mut [],
+            Some(5),
+            &HashSet::default(),
+            &Ok,
+        );
         assert_eq!(results.deserialized_bundles.len(), 0);
-        assert_eq!(results.num_dropped_bundles, 0);
+        assert_eq!(results.dropped_bundles.len(), 0);
     }
 
     #[test]
@@ -173,7 +200,7 @@ mod tests {
         } = create_genesis_config(10_000);
         let (sender, receiver) = unbounded();
 
-        let deserializer = BundlePacketDeserializer::new(receiver, Some(10));
+        let deserializer = BundlePacketDeserializer::new(receiver, Some(10), HashSet::default());
 
         let packet_bundles: Vec<_> = (0..10)
             .map(|_| PacketBundle {
@@ -188,6 +215,7 @@ mod tests {
                 )
                 .unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             })
             .collect();
 
@@ -198,7 +226,7 @@ mod tests {
             .unwrap();
         // this is confusing, but it's sent as one batch
         assert_eq!(bundles.deserialized_bundles.len(), 10);
-        assert_eq!(bundles.num_dropped_bundles, 0);
+        assert_eq!(bundles.dropped_bundles.len(), 0);
 
         // make sure empty
         assert_matches!(
@@ -213,13 +241,13 @@ mod tests {
             .receive_bundles(Duration::from_millis(100), 5, &Ok)
             .unwrap();
         assert_eq!(bundles.deserialized_bundles.len(), 10);
-        assert_eq!(bundles.num_dropped_bundles, 0);
+        assert_eq!(bundles.dropped_bundles.len(), 0);
 
         let bundles = deserializer
             .receive_bundles(Duration::from_millis(100), 5, &Ok)
             .unwrap();
         assert_eq!(bundles.deserialized_bundles.len(), 10);
-        assert_eq!(bundles.num_dropped_bundles, 0);
+        assert_eq!(bundles.dropped_bundles.len(), 0);
 
         assert_matches!(
             deserializer.receive_bundles(Duration::from_millis(100), 5, &Ok),
@@ -232,12 +260,13 @@ mod tests {
         solana_logger::setup();
         let (sender, receiver) = unbounded();
 
-        let deserializer = BundlePacketDeserializer::new(receiver, Some(10));
+        let deserializer = BundlePacketDeserializer::new(receiver, Some(10), HashSet::default());
 
         let packet_bundles: Vec<_> = (0..10)
             .map(|_| PacketBundle {
                 batch: PacketBatch::new(vec![]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             })
             .collect();
         sender.send(packet_bundles).unwrap();
@@ -247,6 +276,6 @@ mod tests {
             .unwrap();
         // this is confusing, but it's sent as one batch
         assert_eq!(bundles.deserialized_bundles.len(), 0);
-        assert_eq!(bundles.num_dropped_bundles, 10);
+        assert_eq!(bundles.dropped_bundles.len(), 10);
     }
 }
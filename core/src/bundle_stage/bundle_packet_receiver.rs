@@ -14,8 +14,8 @@ use {
     },
     crossbeam_channel::{Receiver, RecvTimeoutError},
     solana_measure::{measure::Measure, measure_us},
-    solana_sdk::timing::timestamp,
-    std::time::Duration,
+    solana_sdk::{pubkey::Pubkey, timing::timestamp},
+    std::{collections::HashSet, time::Duration},
 };
 
 pub struct BundleReceiver {
@@ -28,12 +28,14 @@ impl BundleReceiver {
         id: u32,
         bundle_packet_receiver: Receiver<Vec<PacketBundle>>,
         max_packets_per_bundle: Option<usize>,
+        tip_accounts: HashSet<Pubkey>,
     ) -> Self {
         Self {
             id,
             bundle_packet_deserializer: BundlePacketDeserializer::new(
                 bundle_packet_receiver,
                 max_packets_per_bundle,
+                tip_accounts,
             ),
         }
     }
@@ -102,7 +104,7 @@ impl BundleReceiver {
         &self,
         ReceiveBundleResults {
             deserialized_bundles,
-            num_dropped_bundles,
+            dropped_bundles,
         }: ReceiveBundleResults,
         unprocessed_transaction_storage: &mut UnprocessedTransactionStorage,
         bundle_stage_stats: &mut BundleStageLoopMetrics,
@@ -113,7 +115,11 @@ impl BundleReceiver {
 
         bundle_stage_stats.increment_num_bundles_received(bundle_count as u64);
         bundle_stage_stats.increment_num_packets_received(packet_count as u64);
-        bundle_stage_stats.increment_num_bundles_dropped(num_dropped_bundles as u64);
+        bundle_stage_stats.increment_num_bundles_dropped(dropped_bundles.len() as u64);
+        unprocessed_transaction_storage
+            .bundle_storage()
+            .unwrap()
+            .extend_dropped_bundles(dropped_bundles);
         // TODO (LB): fix this
         // bundle_stage_leader_metrics
         //     .leader_slot_metrics_tracker()
@@ -150,6 +156,9 @@ impl BundleReceiver {
             );
             bundle_stage_stats
                 .increment_num_bundles_dropped(insert_bundles_summary.num_bundles_dropped as u64);
+            bundle_stage_leader_metrics
+                .bundle_stage_metrics_tracker()
+                .increment_dropped_bundle_tip_lamports(insert_bundles_summary.tip_lamports_dropped);
 
             bundle_stage_leader_metrics
                 .leader_slot_metrics_tracker()
@@ -172,7 +181,10 @@ impl BundleReceiver {
 mod tests {
     use {
         super::*,
-        crate::banking_stage::unprocessed_transaction_storage::BundleStorage,
+        crate::{
+            banking_stage::unprocessed_transaction_storage::BundleStorage,
+            bundle_stage::BundleDropReason,
+        },
         crossbeam_channel::unbounded,
         rand::{thread_rng, RngCore},
         solana_bundle::{
@@ -225,11 +237,27 @@ mod tests {
                             .collect(),
                     ),
                     bundle_id,
+                    block_builder_fee_info: None,
                 }
             })
             .collect()
     }
 
+    /// Makes a single-transaction bundle tipping `tip_account` `lamports`.
+    fn make_tip_bundle(
+        mint_keypair: &Keypair,
+        tip_account: &Pubkey,
+        lamports: u64,
+        hash: Hash,
+    ) -> PacketBundle {
+        let tx = VersionedTransaction::from(transfer(mint_keypair, tip_account, lamports, hash));
+        PacketBundle {
+            batch: PacketBatch::new(vec![Packet::from_data(None, &tx).unwrap()]),
+            bundle_id: derive_bundle_id(&[tx]).unwrap(),
+            block_builder_fee_info: None,
+        }
+    }
+
     fn assert_bundles_same(
         packet_bundles: &[PacketBundle],
         bundles_to_process: &[(ImmutableDeserializedBundle, SanitizedBundle)],
@@ -261,7 +289,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         let bundles = make_random_bundles(&mint_keypair, 10, 2, genesis_config.hash());
         sender.send(bundles.clone()).unwrap();
@@ -312,7 +340,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 more than capacity
         let bundles = make_random_bundles(
@@ -357,6 +385,206 @@ mod tests {
         assert_eq!(bundle_storage.cost_model_buffered_bundles_len(), 0);
     }
 
+    #[test]
+    fn test_receive_bundles_ordered_by_tip() {
+        solana_logger::setup();
+
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let (_, bank_forks) = Bank::new_no_wallclock_throttle_for_tests(&genesis_config);
+
+        let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
+
+        let tip_account = Pubkey::new_unique();
+        let (sender, receiver) = unbounded();
+        let mut bundle_receiver =
+            BundleReceiver::new(0, receiver, Some(5), HashSet::from([tip_account]));
+
+        let low = make_tip_bundle(&mint_keypair, &tip_account, 10, genesis_config.hash());
+        let untipped = make_random_bundles(&mint_keypair, 1, 1, genesis_config.hash());
+        let high = make_tip_bundle(&mint_keypair, &tip_account, 100, genesis_config.hash());
+        let equal_high = make_tip_bundle(&mint_keypair, &tip_account, 100, genesis_config.hash());
+        sender
+            .send(vec![
+                low.clone(),
+                untipped[0].clone(),
+                high.clone(),
+                equal_high.clone(),
+            ])
+            .unwrap();
+
+        let mut bundle_stage_stats = BundleStageLoopMetrics::default();
+        let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(0);
+        let result = bundle_receiver.receive_and_buffer_bundles(
+            &mut unprocessed_storage,
+            &mut bundle_stage_stats,
+            &mut bundle_stage_leader_metrics,
+        );
+        assert!(result.is_ok());
+
+        // highest tip first, equal tips in arrival order
+        let bundle_storage = unprocessed_storage.bundle_storage().unwrap();
+        assert!(!bundle_storage.process_bundles(
+            bank_forks.read().unwrap().working_bank(),
+            &mut bundle_stage_leader_metrics,
+            &HashSet::default(),
+            |bundles_to_process, _stats| {
+                assert_bundles_same(
+                    &[
+                        high.clone(),
+                        equal_high.clone(),
+                        low.clone(),
+                        untipped[0].clone(),
+                    ],
+                    bundles_to_process,
+                );
+                vec![Ok(()); bundles_to_process.len()]
+            }
+        ));
+    }
+
+    #[test]
+    fn test_receive_bundles_full_evicts_lowest_tip() {
+        solana_logger::setup();
+
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let (_, bank_forks) = Bank::new_no_wallclock_throttle_for_tests(&genesis_config);
+
+        let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
+
+        let tip_account = Pubkey::new_unique();
+        let (sender, receiver) = unbounded();
+        let mut bundle_receiver =
+            BundleReceiver::new(0, receiver, Some(5), HashSet::from([tip_account]));
+        let mut bundle_stage_stats = BundleStageLoopMetrics::default();
+        let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(0);
+
+        // fill the buffer with untipped bundles
+        let untipped = make_random_bundles(
+            &mint_keypair,
+            BundleStorage::BUNDLE_STORAGE_CAPACITY,
+            1,
+            genesis_config.hash(),
+        );
+        sender.send(untipped.clone()).unwrap();
+        assert!(bundle_receiver
+            .receive_and_buffer_bundles(
+                &mut unprocessed_storage,
+                &mut bundle_stage_stats,
+                &mut bundle_stage_leader_metrics,
+            )
+            .is_ok());
+
+        // a tipped bundle displaces the last untipped one, another untipped one is dropped
+        let tipped = make_tip_bundle(&mint_keypair, &tip_account, 10, genesis_config.hash());
+        let late_untipped = make_random_bundles(&mint_keypair, 1, 1, genesis_config.hash());
+        sender
+            .send(vec![tipped.clone(), late_untipped[0].clone()])
+            .unwrap();
+        assert!(bundle_receiver
+            .receive_and_buffer_bundles(
+                &mut unprocessed_storage,
+                &mut bundle_stage_stats,
+                &mut bundle_stage_leader_metrics,
+            )
+            .is_ok());
+
+        let bundle_storage = unprocessed_storage.bundle_storage().unwrap();
+        assert_eq!(
+            bundle_storage.unprocessed_bundles_len(),
+            BundleStorage::BUNDLE_STORAGE_CAPACITY
+        );
+        assert_eq!(
+            bundle_storage.take_dropped_bundles(),
+            vec![
+                (
+                    untipped[BundleStorage::BUNDLE_STORAGE_CAPACITY - 1]
+                        .bundle_id
+                        .clone(),
+                    BundleDropReason::Evicted
+                ),
+                (
+                    late_untipped[0].bundle_id.clone(),
+                    BundleDropReason::Evicted
+                ),
+            ]
+        );
+        assert!(!bundle_storage.process_bundles(
+            bank_forks.read().unwrap().working_bank(),
+            &mut bundle_stage_leader_metrics,
+            &HashSet::default(),
+            |bundles_to_process, _stats| {
+                let expected: Vec<_> = std::iter::once(tipped.clone())
+                    .chain(
+                        untipped[..BundleStorage::BUNDLE_STORAGE_CAPACITY - 1]
+                            .iter()
+                            .cloned(),
+                    )
+                    .collect();
+                assert_bundles_same(&expected, bundles_to_process);
+                vec![Ok(()); bundles_to_process.len()]
+            }
+        ));
+    }
+
+    #[test]
+    fn test_receive_bundles_records_dropped_bundles() {
+        solana_logger::setup();
+
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+
+        let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
+
+        let (sender, receiver) = unbounded();
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
+        let mut bundle_stage_stats = BundleStageLoopMetrics::default();
+        let mut bundle_stage_leader_metrics = BundleStageLeaderMetrics::new(0);
+
+        let bundles = make_random_bundles(&mint_keypair, 1, 1, genesis_config.hash());
+        let empty_bundle = PacketBundle {
+            batch: PacketBatch::new(vec![]),
+            bundle_id: "empty".to_string(),
+            block_builder_fee_info: None,
+        };
+        sender.send(vec![bundles[0].clone(), empty_bundle]).unwrap();
+        assert!(bundle_receiver
+            .receive_and_buffer_bundles(
+                &mut unprocessed_storage,
+                &mut bundle_stage_stats,
+                &mut bundle_stage_leader_metrics,
+            )
+            .is_ok());
+
+        let bundle_storage = unprocessed_storage.bundle_storage().unwrap();
+        assert_eq!(
+            bundle_storage.take_dropped_bundles(),
+            vec![(
+                "empty".to_string(),
+                BundleDropReason::Invalid("EmptyBatch".to_string())
+            )]
+        );
+        assert_eq!(bundle_storage.unprocessed_bundles_len(), 1);
+
+        // buffered bundles are dropped once the leader slot is far away
+        assert_eq!(bundle_storage.reset(), (1, 0));
+        assert_eq!(
+            bundle_storage.take_dropped_bundles(),
+            vec![(bundles[0].bundle_id.clone(), BundleDropReason::NotLeader)]
+        );
+        assert!(bundle_storage.take_dropped_bundles().is_empty());
+    }
+
     #[test]
     fn test_process_bundles_poh_record_error_rebuffered() {
         solana_logger::setup();
@@ -371,7 +599,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -438,7 +666,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -503,7 +731,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -551,7 +779,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -597,7 +825,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -639,7 +867,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 5 bundles across the queue
         let bundles = make_random_bundles(&mint_keypair, 5, 2, genesis_config.hash());
@@ -716,7 +944,7 @@ mod tests {
         let mut unprocessed_storage = UnprocessedTransactionStorage::new_bundle_storage();
 
         let (sender, receiver) = unbounded();
-        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5));
+        let mut bundle_receiver = BundleReceiver::new(0, receiver, Some(5), HashSet::default());
 
         // send 500 bundles across the queue
         let bundles0 = make_random_bundles(
@@ -0,0 +1,119 @@
+//! Splits a batch of bundles into rounds of bundles that don't conflict on account locks so each
+//! round can be executed in parallel by the bundle-stage workers.
+//!
+//! Bundles are visited in priority order. A bundle joins the current round unless it conflicts with
+//! a bundle already in the round or with a higher priority bundle that was deferred to a later
+//! round. Conflicting bundles therefore always execute, and commit, in priority order.
+use {
+    crate::banking_stage::read_write_account_set::ReadWriteAccountSet,
+    solana_bundle::SanitizedBundle,
+};
+
+/// Returns the indexes of `bundles` grouped into rounds, in execution order.
+pub fn schedule_bundle_rounds<'a>(
+    bundles: impl IntoIterator<Item = &'a SanitizedBundle>,
+) -> Vec<Vec<usize>> {
+    let mut pending: Vec<_> = bundles.into_iter().enumerate().collect();
+    let mut rounds = Vec::new();
+    let mut round_locks = ReadWriteAccountSet::default();
+    let mut deferred_locks = ReadWriteAccountSet::default();
+
+    while !pending.is_empty() {
+        let mut round = Vec::new();
+        let mut deferred = Vec::new();
+        for (index, bundle) in pending {
+            let available = bundle.transactions.iter().all(|tx| {
+                round_locks.check_locks(tx.message()) && deferred_locks.check_locks(tx.message())
+            });
+            let locks = if available {
+                round.push(index);
+                &mut round_locks
+            } else {
+                deferred.push((index, bundle));
+                &mut deferred_locks
+            };
+            for tx in &bundle.transactions {
+                locks.take_locks(tx.message());
+            }
+        }
+
+        round_locks.clear();
+        deferred_locks.clear();
+        rounds.push(round);
+        pending = deferred;
+    }
+
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash,
+            pubkey::Pubkey,
+            signature::{Keypair, Signer},
+            system_transaction::transfer,
+            transaction::SanitizedTransaction,
+        },
+    };
+
+    fn bundle(transfers: &[(&Keypair, Pubkey)]) -> SanitizedBundle {
+        SanitizedBundle {
+            transactions: transfers
+                .iter()
+                .map(|(from, to)| {
+                    SanitizedTransaction::from_transaction_for_tests(transfer(
+                        from,
+                        to,
+                        1,
+                        Hash::default(),
+                    ))
+                })
+                .collect(),
+            bundle_id: String::default(),
+        }
+    }
+
+    #[test]
+    fn test_non_conflicting_bundles_share_a_round() {
+        let (a, b, c) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let bundles = [
+            bundle(&[(&a, Pubkey::new_unique())]),
+            bundle(&[(&b, Pubkey::new_unique())]),
+            bundle(&[(&c, Pubkey::new_unique()), (&c, Pubkey::new_unique())]),
+        ];
+
+        assert_eq!(schedule_bundle_rounds(&bundles), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_conflicting_bundles_keep_priority_order() {
+        let (a, b, c, d) = (
+            Keypair::new(),
+            Keypair::new(),
+            Keypair::new(),
+            Keypair::new(),
+        );
+        let shared = Pubkey::new_unique();
+        let bundles = [
+            bundle(&[(&a, b.pubkey())]),
+            // conflicts with 0
+            bundle(&[(&b, shared)]),
+            bundle(&[(&c, Pubkey::new_unique())]),
+            // only conflicts with the deferred 1, so it must not overtake it
+            bundle(&[(&d, shared)]),
+        ];
+
+        assert_eq!(
+            schedule_bundle_rounds(&bundles),
+            vec![vec![0, 2], vec![1], vec![3]]
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(schedule_bundle_rounds(&[]).is_empty());
+    }
+}
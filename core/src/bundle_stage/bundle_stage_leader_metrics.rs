@@ -4,13 +4,15 @@ use {
             leader_slot_metrics::{self, LeaderSlotMetricsTracker},
             unprocessed_transaction_storage::UnprocessedTransactionStorage,
         },
+        bundle_stage::{amm_registry::AmmPolicy, front_run_identifier::FrontRun},
         immutable_deserialized_bundle::DeserializedBundleError,
     },
     solana_bundle::{
         bundle_execution::LoadAndExecuteBundleError, BundleExecutionError, SanitizedBundle,
     },
     solana_poh::poh_recorder::BankStart,
-    solana_sdk::{clock::Slot, saturating_add_assign},
+    solana_sdk::{clock::Slot, pubkey::Pubkey, saturating_add_assign},
+    std::collections::HashMap,
 };
 
 pub struct BundleStageLeaderMetrics {
@@ -268,12 +270,42 @@ impl BundleStageStatsMetricsTracker {
                 )) => {
                     saturating_add_assign!(bundle_stage_metrics.bad_argument, 1);
                 }
-                // TODO: Consider adding metrics.
+                // Counted per program by `increment_front_run`.
                 Err(BundleExecutionError::FrontRun) => {}
             }
         }
     }
 
+    pub(crate) fn increment_front_run(&mut self, front_run: FrontRun) {
+        if let Some(bundle_stage_metrics) = &mut self.bundle_stage_metrics {
+            match front_run.policy {
+                AmmPolicy::Reject => {
+                    saturating_add_assign!(bundle_stage_metrics.num_front_runs_rejected, 1);
+                }
+                AmmPolicy::LogOnly => {
+                    saturating_add_assign!(bundle_stage_metrics.num_front_runs_log_only, 1);
+                }
+            }
+            let count = bundle_stage_metrics
+                .front_runs_by_program
+                .entry((front_run.program, front_run.policy, front_run.sandwich))
+                .or_default();
+            saturating_add_assign!(*count, 1);
+        }
+    }
+
+    pub(crate) fn increment_landed_bundle_tip_lamports(&mut self, count: u64) {
+        if let Some(bundle_stage_metrics) = &mut self.bundle_stage_metrics {
+            saturating_add_assign!(bundle_stage_metrics.landed_bundle_tip_lamports, count);
+        }
+    }
+
+    pub(crate) fn increment_dropped_bundle_tip_lamports(&mut self, count: u64) {
+        if let Some(bundle_stage_metrics) = &mut self.bundle_stage_metrics {
+            saturating_add_assign!(bundle_stage_metrics.dropped_bundle_tip_lamports, count);
+        }
+    }
+
     pub(crate) fn increment_sanitize_bundle_elapsed_us(&mut self, count: u64) {
         if let Some(bundle_stage_metrics) = &mut self.bundle_stage_metrics {
             saturating_add_assign!(bundle_stage_metrics.sanitize_bundle_elapsed_us, count);
@@ -376,6 +408,13 @@ pub struct BundleStageStats {
     execution_results_max_retries: u64,
 
     bad_argument: u64,
+
+    landed_bundle_tip_lamports: u64,
+    dropped_bundle_tip_lamports: u64,
+
+    num_front_runs_rejected: u64,
+    num_front_runs_log_only: u64,
+    front_runs_by_program: HashMap<(Pubkey, AmmPolicy, bool), u64>,
 }
 
 impl BundleStageStats {
@@ -522,6 +561,30 @@ impl BundleStageStats {
                 i64
             ),
             ("bad_argument", self.bad_argument, i64),
+            (
+                "landed_bundle_tip_lamports",
+                self.landed_bundle_tip_lamports,
+                i64
+            ),
+            (
+                "dropped_bundle_tip_lamports",
+                self.dropped_bundle_tip_lamports,
+                i64
+            ),
+            ("num_front_runs_rejected", self.num_front_runs_rejected, i64),
+            ("num_front_runs_log_only", self.num_front_runs_log_only, i64),
         );
+
+        for ((program, policy, sandwich), count) in &self.front_runs_by_program {
+            datapoint_info!(
+                "bundle_stage-front_runs",
+                "program" => program.to_string(),
+                "policy" => policy.as_str(),
+                "kind" => if *sandwich { "sandwich" } else { "bundle" },
+                ("id", self.id, i64),
+                ("slot", self.slot, i64),
+                ("count", *count, i64),
+            );
+        }
     }
 }
@@ -0,0 +1,68 @@
+//! Threads executing a round of non-conflicting bundles in parallel.
+use {
+    crate::bundle_stage::bundle_stage_leader_metrics::BundleStageLeaderMetrics,
+    rayon::{prelude::*, ThreadPool},
+    solana_poh::poh_recorder::BankStart,
+};
+
+pub struct BundleWorkerPool {
+    thread_pool: ThreadPool,
+    // Each worker reports its own leader metrics, the same way each banking stage thread does.
+    worker_metrics: Vec<BundleStageLeaderMetrics>,
+}
+
+impl BundleWorkerPool {
+    /// Workers report metrics with ids following the bundle stage's `id`.
+    pub fn new(id: u32, num_workers: usize) -> Self {
+        let num_workers = num_workers.max(1);
+        Self {
+            thread_pool: rayon::ThreadPoolBuilder::new()
+                .num_threads(num_workers)
+                .thread_name(|i| format!("solBundleStgEx{i:02}"))
+                .build()
+                .expect("new rayon threadpool"),
+            worker_metrics: (1..=num_workers as u32)
+                .map(|i| BundleStageLeaderMetrics::new(id + i))
+                .collect(),
+        }
+    }
+
+    /// Starts, reports and resets the workers' per-slot metrics, see
+    /// [BundleStageLeaderMetrics::check_leader_slot_boundary].
+    pub(crate) fn check_leader_slot_boundary(&mut self, bank_start: Option<&BankStart>) {
+        for metrics in &mut self.worker_metrics {
+            let (banking_stage_metrics_action, bundle_stage_metrics_action) =
+                metrics.check_leader_slot_boundary(bank_start, None);
+            metrics.apply_action(banking_stage_metrics_action, bundle_stage_metrics_action);
+        }
+    }
+
+    /// Calls `f` on each of `items` in parallel and returns the results in the order of `items`.
+    ///
+    /// `items` is split into one contiguous chunk per worker. Each chunk is executed in order
+    /// with its worker's metrics, so the metrics are never shared while `f` runs, even if the
+    /// thread running a chunk picks up another one while `f` waits on nested parallel work.
+    pub(crate) fn execute<T, R>(
+        &mut self,
+        items: &[T],
+        f: impl Fn(&T, &mut BundleStageLeaderMetrics) -> R + Sync,
+    ) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+    {
+        let chunk_size = items.len().div_ceil(self.worker_metrics.len()).max(1);
+        let Self {
+            thread_pool,
+            worker_metrics,
+        } = self;
+        let f = &f;
+        thread_pool.install(|| {
+            items
+                .par_chunks(chunk_size)
+                .zip(worker_metrics.par_iter_mut())
+                .flat_map_iter(|(chunk, metrics)| chunk.iter().map(move |item| f(item, metrics)))
+                .collect()
+        })
+    }
+}
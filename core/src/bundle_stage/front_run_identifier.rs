@@ -1,4 +1,5 @@
 use {
+    super::amm_registry::{AmmPolicy, AmmRegistry},
     hashbrown::HashMap,
     itertools::izip,
     solana_bundle::bundle_execution::LoadAndExecuteBundleOutput,
@@ -13,109 +14,125 @@ use {
 
 const MAX_PACKETS_PER_BUNDLE: usize = 5;
 
-pub(crate) const AMM_PROGRAMS: &[Pubkey] = &[
-    solana_sdk::pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"), // RaydiumV4
-    solana_sdk::pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"), // Serum DEX V3
-    solana_sdk::pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB"), // Meteora CPMM
-    solana_sdk::pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"),  // Whirlpool
-    solana_sdk::pubkey!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX"),  // Serum
-    solana_sdk::pubkey!("DjVE6JNiYqPL2QXyCUUh8rNjHrbz9hXHNYt99MQ59qw1"), // Orca AMM V1
-    solana_sdk::pubkey!("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP"), // Orca AMM V2
-    solana_sdk::pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"),  // Meteora DLMM
-    solana_sdk::pubkey!("SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ"),  // Saber
-    solana_sdk::pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"), // Raydium CLMM
-    solana_sdk::pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"), // Raydium CPMM
-    solana_sdk::pubkey!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY"),  // Phoenix
-    solana_sdk::pubkey!("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb"),  // Open Book
-    solana_sdk::pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"),  // Pump.fun
-];
-
 thread_local! {
-    static AMM_MAP: RefCell<HashMap<Pubkey, [bool; MAX_PACKETS_PER_BUNDLE]>>
+    static AMM_MAP: RefCell<HashMap<Pubkey, AmmAccess>>
         = RefCell::new(HashMap::with_capacity(MAX_TX_ACCOUNT_LOCKS * MAX_PACKETS_PER_BUNDLE));
 }
 
+/// Writeable accesses to a single AMM owned account across a bundle.
+#[derive(Default)]
+struct AmmAccess {
+    owner: Pubkey,
+    policy: Option<AmmPolicy>,
+    txs: [bool; MAX_PACKETS_PER_BUNDLE],
+}
+
+/// A front-run detected within a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrontRun {
+    /// The AMM program that owns the front-run pool account.
+    pub(crate) program: Pubkey,
+    pub(crate) policy: AmmPolicy,
+    /// Whether the front-run spans earlier bundles or banking stage commits in the slot.
+    pub(crate) sandwich: bool,
+}
+
+/// Returns the front-run with the strictest policy found in the bundle, if any.
 #[must_use]
-pub(crate) fn is_bundle_front_run<'a>(bundle: &'a impl BundleResult<'a>) -> bool {
+pub(crate) fn find_bundle_front_run<'a>(
+    bundle: &'a impl BundleResult<'a>,
+    registry: &AmmRegistry,
+) -> Option<FrontRun> {
     AMM_MAP.with_borrow_mut(|map| map.clear());
 
     if !bundle.executed_ok() {
         eprintln!("BUG: Unexpected bundle with ERR");
-        return false;
+        return None;
     }
 
     // If the bundle did not execute okay then the results will not be valid.
     let count = bundle.transactions().count();
     if count <= 1 {
-        return false;
+        return None;
     }
 
     if count > MAX_PACKETS_PER_BUNDLE {
         eprintln!("BUG: Too many packets in bundle; packets={count}");
-        return false;
+        return None;
     }
 
     // Check all TXs for write-locked pool accounts.
     for (i, tx) in bundle.transactions().enumerate() {
         // Find all the AMM owned writeable accounts.
-        let writeable_amm_accounts = tx
-            .writable_accounts_owners()
-            .filter(|account| AMM_PROGRAMS.contains(account.owner));
+        let writeable_amm_accounts = tx.writable_accounts_owners().filter_map(|account| {
+            registry
+                .policy(account.owner)
+                .map(|policy| (account, policy))
+        });
 
         // Record this TX's access of the writeable account.
-        for account in writeable_amm_accounts {
-            AMM_MAP.with_borrow_mut(|map| map.entry_ref(account.key).or_default()[i] = true);
+        for (account, policy) in writeable_amm_accounts {
+            AMM_MAP.with_borrow_mut(|map| {
+                let access = map.entry_ref(account.key).or_default();
+                access.owner = *account.owner;
+                access.policy = Some(policy);
+                access.txs[i] = true;
+            });
         }
     }
 
-    // Compute which TXs overlap with each other.
-    let mut overlap_matrix = [[false; MAX_PACKETS_PER_BUNDLE]; MAX_PACKETS_PER_BUNDLE];
-    AMM_MAP.with_borrow(|map| {
-        for txs in map.values() {
-            if txs.iter().filter(|tx| **tx).count() <= 1 {
+    // Brute-force signer checks for TXs that overlap on a pool account.
+    let mut front_run: Option<FrontRun> = None;
+    let found = AMM_MAP.with_borrow(|map| {
+        for access in map.values() {
+            let Some(policy) = access.policy else {
+                continue;
+            };
+            if access.txs.iter().filter(|tx| **tx).count() <= 1 {
                 continue;
             }
+            // A rejection takes precedence over any log-only front-run.
+            if front_run.is_some_and(|front_run| front_run.policy == AmmPolicy::Reject) {
+                break;
+            }
 
-            for (i, tx1) in txs.iter().enumerate() {
-                for (j, tx2) in txs.iter().enumerate() {
-                    if *tx1 && *tx2 {
-                        overlap_matrix[i][j] = true;
-                        overlap_matrix[j][i] = true;
+            for i in 0..access.txs.len() {
+                for j in i + 1..access.txs.len() {
+                    if !access.txs[i] || !access.txs[j] {
+                        continue;
                     }
-                }
-            }
-        }
-    });
 
-    // Brute-force signer checks for overlapping TXs.
-    for i in 0..overlap_matrix.len() {
-        'outer: for j in i..overlap_matrix.len() {
-            if overlap_matrix[i][j] {
-                let Some(i) = bundle.transactions().nth(i) else {
-                    eprintln!("BUG: i not found");
-                    return false;
-                };
-                let Some(j) = bundle.transactions().nth(j) else {
-                    eprintln!("BUG: j not found");
-                    return false;
-                };
-
-                // Brute force compare the signers.
-                for (i, j) in i.signers().flat_map(|i| j.signers().map(move |j| (i, j))) {
+                    let Some(i) = bundle.transactions().nth(i) else {
+                        eprintln!("BUG: i not found");
+                        return false;
+                    };
+                    let Some(j) = bundle.transactions().nth(j) else {
+                        eprintln!("BUG: j not found");
+                        return false;
+                    };
+
                     // If any signer matches then this overlap is not a front-run.
-                    if i == j {
-                        continue 'outer;
+                    if i.signers().any(|i| j.signers().any(|j| i == j)) {
+                        continue;
                     }
-                }
 
-                // If we reach this point it means none of the signers matched and thus this is a
-                // frontrun.
-                return true;
+                    // If we reach this point it means none of the signers matched and thus this
+                    // is a frontrun.
+                    if front_run.is_none() || policy == AmmPolicy::Reject {
+                        front_run = Some(FrontRun {
+                            program: access.owner,
+                            policy,
+                            sandwich: false,
+                        });
+                    }
+                }
             }
         }
-    }
 
-    false
+        true
+    });
+
+    found.then_some(front_run).flatten()
 }
 
 pub(crate) trait BundleResult<'a> {
@@ -133,8 +150,8 @@ pub(crate) trait BundleTransaction {
 }
 
 pub(crate) struct AccountRef<'a> {
-    key: &'a Pubkey,
-    owner: &'a Pubkey,
+    pub(crate) key: &'a Pubkey,
+    pub(crate) owner: &'a Pubkey,
 }
 
 impl<'a> BundleResult<'a> for LoadAndExecuteBundleOutput<'a> {
@@ -188,21 +205,21 @@ impl<'a> BundleTransaction for (&'a SanitizedTransaction, &'a LoadedTransaction)
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
+pub(crate) mod tests {
+    use {super::*, crate::bundle_stage::amm_registry::DEFAULT_AMM_PROGRAMS};
 
     const NOT_AMM_0: Pubkey = Pubkey::new_from_array([1; 32]);
     const NOT_AMM_1: Pubkey = Pubkey::new_from_array([2; 32]);
-    const AMM_0: Pubkey = AMM_PROGRAMS[0];
-    const AMM_1: Pubkey = AMM_PROGRAMS[1];
-    const AMM_2: Pubkey = AMM_PROGRAMS[2];
+    const AMM_0: Pubkey = DEFAULT_AMM_PROGRAMS[0];
+    const AMM_1: Pubkey = DEFAULT_AMM_PROGRAMS[1];
+    const AMM_2: Pubkey = DEFAULT_AMM_PROGRAMS[2];
     const SIGNER_0: Pubkey = Pubkey::new_from_array([3; 32]);
     const SIGNER_1: Pubkey = Pubkey::new_from_array([4; 32]);
     const SIGNER_2: Pubkey = Pubkey::new_from_array([5; 32]);
 
-    struct MockBundleResult {
-        executed_ok: bool,
-        transactions: Vec<MockTransaction>,
+    pub(crate) struct MockBundleResult {
+        pub(crate) executed_ok: bool,
+        pub(crate) transactions: Vec<MockTransaction>,
     }
 
     impl<'a> BundleResult<'a> for MockBundleResult {
@@ -217,9 +234,9 @@ mod tests {
         }
     }
 
-    struct MockTransaction {
-        signers: Vec<Pubkey>,
-        accounts: Vec<MockAccount>,
+    pub(crate) struct MockTransaction {
+        pub(crate) signers: Vec<Pubkey>,
+        pub(crate) accounts: Vec<MockAccount>,
     }
 
     impl<'a> BundleTransaction for &'a MockTransaction {
@@ -234,9 +251,35 @@ mod tests {
         }
     }
 
-    struct MockAccount {
-        key: Pubkey,
-        owner: Pubkey,
+    pub(crate) struct MockAccount {
+        pub(crate) key: Pubkey,
+        pub(crate) owner: Pubkey,
+    }
+
+    fn is_front_run(bundle: &MockBundleResult) -> bool {
+        find_bundle_front_run(bundle, &AmmRegistry::default()).is_some()
+    }
+
+    fn sandwich(owner: Pubkey) -> MockBundleResult {
+        MockBundleResult {
+            executed_ok: true,
+            transactions: vec![
+                MockTransaction {
+                    signers: vec![SIGNER_0],
+                    accounts: vec![MockAccount {
+                        key: Pubkey::new_from_array([0; 32]),
+                        owner,
+                    }],
+                },
+                MockTransaction {
+                    signers: vec![SIGNER_1],
+                    accounts: vec![MockAccount {
+                        key: Pubkey::new_from_array([0; 32]),
+                        owner,
+                    }],
+                },
+            ],
+        }
     }
 
     #[test]
@@ -263,7 +306,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -290,7 +333,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(is_bundle_front_run(&bundle));
+        assert!(is_front_run(&bundle));
     }
 
     #[test]
@@ -324,7 +367,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(is_bundle_front_run(&bundle));
+        assert!(is_front_run(&bundle));
     }
 
     #[test]
@@ -358,7 +401,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -392,7 +435,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(is_bundle_front_run(&bundle));
+        assert!(is_front_run(&bundle));
     }
 
     #[test]
@@ -426,7 +469,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -460,7 +503,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -487,7 +530,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -514,7 +557,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -541,7 +584,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(!is_bundle_front_run(&bundle));
+        assert!(!is_front_run(&bundle));
     }
 
     #[test]
@@ -575,7 +618,7 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(is_bundle_front_run(&bundle));
+        assert!(is_front_run(&bundle));
     }
 
     #[test]
@@ -652,6 +695,102 @@ mod tests {
         };
 
         // Act & Assert.
-        assert!(is_bundle_front_run(&bundle));
+        assert!(is_front_run(&bundle));
+    }
+
+    #[test]
+    fn front_run_reports_program() {
+        // Arrange.
+        let bundle = sandwich(AMM_1);
+
+        // Act & Assert.
+        assert_eq!(
+            find_bundle_front_run(&bundle, &AmmRegistry::default()),
+            Some(FrontRun {
+                program: AMM_1,
+                policy: AmmPolicy::Reject,
+                sandwich: false,
+            })
+        );
+    }
+
+    #[test]
+    fn registry_policies() {
+        // Arrange.
+        let registry = AmmRegistry::from_json(&format!(
+            r#"{{"programs": [
+                {{"program_id": "{NOT_AMM_0}", "policy": "reject"}},
+                {{"program_id": "{AMM_0}", "policy": "log-only"}},
+                {{"program_id": "{AMM_1}", "policy": "allow"}}
+            ]}}"#
+        ))
+        .unwrap();
+
+        // Act & Assert.
+        assert_eq!(
+            find_bundle_front_run(&sandwich(NOT_AMM_0), &registry),
+            Some(FrontRun {
+                program: NOT_AMM_0,
+                policy: AmmPolicy::Reject,
+                sandwich: false,
+            })
+        );
+        assert_eq!(
+            find_bundle_front_run(&sandwich(AMM_0), &registry),
+            Some(FrontRun {
+                program: AMM_0,
+                policy: AmmPolicy::LogOnly,
+                sandwich: false,
+            })
+        );
+        assert_eq!(find_bundle_front_run(&sandwich(AMM_1), &registry), None);
+        assert_eq!(find_bundle_front_run(&sandwich(AMM_2), &registry), None);
+    }
+
+    #[test]
+    fn reject_takes_precedence_over_log_only() {
+        // Arrange.
+        let registry = AmmRegistry::new([(AMM_0, AmmPolicy::LogOnly), (AMM_1, AmmPolicy::Reject)]);
+        let bundle = MockBundleResult {
+            executed_ok: true,
+            transactions: vec![
+                MockTransaction {
+                    signers: vec![SIGNER_0],
+                    accounts: vec![
+                        MockAccount {
+                            key: Pubkey::new_from_array([0; 32]),
+                            owner: AMM_0,
+                        },
+                        MockAccount {
+                            key: Pubkey::new_from_array([1; 32]),
+                            owner: AMM_1,
+                        },
+                    ],
+                },
+                MockTransaction {
+                    signers: vec![SIGNER_1],
+                    accounts: vec![
+                        MockAccount {
+                            key: Pubkey::new_from_array([0; 32]),
+                            owner: AMM_0,
+                        },
+                        MockAccount {
+                            key: Pubkey::new_from_array([1; 32]),
+                            owner: AMM_1,
+                        },
+                    ],
+                },
+            ],
+        };
+
+        // Act & Assert.
+        assert_eq!(
+            find_bundle_front_run(&bundle, &registry),
+            Some(FrontRun {
+                program: AMM_1,
+                policy: AmmPolicy::Reject,
+                sandwich: false,
+            })
+        );
     }
 }
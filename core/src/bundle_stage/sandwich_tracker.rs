@@ -0,0 +1,415 @@
+//! Tracks writes to AMM pool accounts made by bundles and banking stage within the current slot.
+//!
+//! A single bundle is checked for front-runs by [`find_bundle_front_run`], but a searcher can
+//! reach the same outcome by landing a front-run bundle, letting a victim transaction land through
+//! banking stage (or another bundle), and then landing a back-run bundle. The tracker keeps the
+//! ordered signer sets that wrote to each pool in the slot so the back-run bundle can be caught.
+//!
+//! [`find_bundle_front_run`]: super::front_run_identifier::find_bundle_front_run
+use {
+    super::{
+        amm_registry::{AmmPolicy, AmmRegistry, SharedAmmRegistry},
+        front_run_identifier::{BundleResult, BundleTransaction, FrontRun},
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex, MutexGuard},
+    },
+};
+
+/// Writes older than this are dropped from a pool's history.
+const MAX_WRITES_PER_POOL: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteSource {
+    Bundle,
+    Banking,
+}
+
+struct PoolWrite {
+    source: WriteSource,
+    signers: Vec<Pubkey>,
+}
+
+#[derive(Default)]
+struct SlotWrites {
+    slot: Slot,
+    pools: HashMap<Pubkey, VecDeque<PoolWrite>>,
+}
+
+impl SlotWrites {
+    fn maybe_reset(&mut self, slot: Slot) {
+        if self.slot != slot {
+            self.slot = slot;
+            self.pools.clear();
+        }
+    }
+
+    fn record(&mut self, pool: &Pubkey, source: WriteSource, signers: &[Pubkey]) {
+        let writes = self.pools.entry(*pool).or_default();
+        if writes.len() == MAX_WRITES_PER_POOL {
+            writes.pop_front();
+        }
+        writes.push_back(PoolWrite {
+            source,
+            signers: signers.to_vec(),
+        });
+    }
+
+    /// Returns true if a write to `pool` by `signers` follows a bundle write by one of the same
+    /// signers and a write by unrelated signers, in that order.
+    fn completes_sandwich(&self, pool: &Pubkey, signers: &[Pubkey]) -> bool {
+        let Some(writes) = self.pools.get(pool) else {
+            return false;
+        };
+
+        let mut front_run = false;
+        for write in writes {
+            if write.signers.iter().any(|signer| signers.contains(signer)) {
+                front_run |= write.source == WriteSource::Bundle;
+            } else if front_run {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SandwichTracker {
+    slot_writes: Arc<Mutex<SlotWrites>>,
+    amm_registry: SharedAmmRegistry,
+}
+
+impl SandwichTracker {
+    pub fn new(amm_registry: SharedAmmRegistry) -> Self {
+        Self {
+            slot_writes: Arc::default(),
+            amm_registry,
+        }
+    }
+
+    /// Locks the writes of `slot` to check a bundle against them or record it.
+    ///
+    /// Callers holding a bank's freeze lock must take it before this one, as banking stage does.
+    pub(crate) fn lock(&self, slot: Slot) -> SlotWritesGuard<'_> {
+        let mut slot_writes = self.slot_writes.lock().unwrap();
+        slot_writes.maybe_reset(slot);
+
+        SlotWritesGuard { slot_writes }
+    }
+
+    /// Records the AMM writes of transactions banking stage is about to commit in `slot`.
+    pub(crate) fn record_banking_commit<T: BundleTransaction>(
+        &self,
+        slot: Slot,
+        transactions: impl IntoIterator<Item = T>,
+    ) {
+        let amm_registry = self.amm_registry.load();
+        if amm_registry.is_empty() {
+            return;
+        }
+
+        self.lock(slot)
+            .record(&amm_registry, WriteSource::Banking, transactions);
+    }
+}
+
+pub(crate) struct SlotWritesGuard<'a> {
+    slot_writes: MutexGuard<'a, SlotWrites>,
+}
+
+impl SlotWritesGuard<'_> {
+    /// Returns the sandwich with the strictest policy that `bundle` would complete.
+    pub(crate) fn find_bundle_sandwich<'a>(
+        &self,
+        amm_registry: &AmmRegistry,
+        bundle: &'a impl BundleResult<'a>,
+    ) -> Option<FrontRun> {
+        let mut sandwich: Option<FrontRun> = None;
+        for tx in bundle.transactions() {
+            let signers: Vec<_> = tx.signers().copied().collect();
+            for account in tx.writable_accounts_owners() {
+                let Some(policy) = amm_registry.policy(account.owner) else {
+                    continue;
+                };
+                if !self.slot_writes.completes_sandwich(account.key, &signers) {
+                    continue;
+                }
+
+                if policy == AmmPolicy::Reject {
+                    return Some(FrontRun {
+                        program: *account.owner,
+                        policy,
+                        sandwich: true,
+                    });
+                }
+                sandwich.get_or_insert(FrontRun {
+                    program: *account.owner,
+                    policy,
+                    sandwich: true,
+                });
+            }
+        }
+
+        sandwich
+    }
+
+    /// Records the AMM writes of a committed bundle.
+    pub(crate) fn record_bundle<'a>(
+        &mut self,
+        amm_registry: &AmmRegistry,
+        bundle: &'a impl BundleResult<'a>,
+    ) {
+        self.record(amm_registry, WriteSource::Bundle, bundle.transactions());
+    }
+
+    fn record<T: BundleTransaction>(
+        &mut self,
+        amm_registry: &AmmRegistry,
+        source: WriteSource,
+        transactions: impl IntoIterator<Item = T>,
+    ) {
+        for tx in transactions {
+            let mut signers = None;
+            for account in tx
+                .writable_accounts_owners()
+                .filter(|account| amm_registry.policy(account.owner).is_some())
+            {
+                let signers =
+                    signers.get_or_insert_with(|| tx.signers().copied().collect::<Vec<_>>());
+                self.slot_writes.record(account.key, source, signers);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::bundle_stage::{
+            amm_registry::DEFAULT_AMM_PROGRAMS,
+            front_run_identifier::tests::{MockAccount, MockBundleResult, MockTransaction},
+        },
+    };
+
+    const AMM_0: Pubkey = DEFAULT_AMM_PROGRAMS[0];
+    const AMM_1: Pubkey = DEFAULT_AMM_PROGRAMS[1];
+    const NOT_AMM: Pubkey = Pubkey::new_from_array([1; 32]);
+    const POOL_0: Pubkey = Pubkey::new_from_array([10; 32]);
+    const POOL_1: Pubkey = Pubkey::new_from_array([11; 32]);
+    const SEARCHER: Pubkey = Pubkey::new_from_array([3; 32]);
+    const VICTIM: Pubkey = Pubkey::new_from_array([4; 32]);
+    const OTHER: Pubkey = Pubkey::new_from_array([5; 32]);
+
+    fn swap(signer: Pubkey, pool: Pubkey, owner: Pubkey) -> MockTransaction {
+        MockTransaction {
+            signers: vec![signer],
+            accounts: vec![MockAccount { key: pool, owner }],
+        }
+    }
+
+    fn bundle(transactions: Vec<MockTransaction>) -> MockBundleResult {
+        MockBundleResult {
+            executed_ok: true,
+            transactions,
+        }
+    }
+
+    #[test]
+    fn bundle_banking_bundle_sandwich() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        let front_run = bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]);
+        let victim = swap(VICTIM, POOL_0, AMM_0);
+        let back_run = bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]);
+
+        // Act.
+        assert_eq!(
+            tracker.lock(1).find_bundle_sandwich(&registry, &front_run),
+            None
+        );
+        tracker.lock(1).record_bundle(&registry, &front_run);
+        tracker.record_banking_commit(1, [&victim]);
+
+        // Assert.
+        assert_eq!(
+            tracker.lock(1).find_bundle_sandwich(&registry, &back_run),
+            Some(FrontRun {
+                program: AMM_0,
+                policy: AmmPolicy::Reject,
+                sandwich: true,
+            })
+        );
+    }
+
+    #[test]
+    fn bundle_bundle_bundle_sandwich() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]));
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(VICTIM, POOL_0, AMM_0)]));
+
+        // Act & Assert.
+        assert!(tracker
+            .lock(1)
+            .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]))
+            .is_some());
+    }
+
+    #[test]
+    fn banking_front_run_is_not_tracked() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        let front_run = swap(SEARCHER, POOL_0, AMM_0);
+        let victim = swap(VICTIM, POOL_0, AMM_0);
+        tracker.record_banking_commit(1, [&front_run, &victim]);
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)])),
+            None
+        );
+    }
+
+    #[test]
+    fn no_victim_between_writes() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        let victim = swap(VICTIM, POOL_0, AMM_0);
+        tracker.record_banking_commit(1, [&victim]);
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]));
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)])),
+            None
+        );
+    }
+
+    #[test]
+    fn different_pool_or_signer() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]));
+        let victim = swap(VICTIM, POOL_0, AMM_0);
+        tracker.record_banking_commit(1, [&victim]);
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_1, AMM_0)])),
+            None
+        );
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(OTHER, POOL_0, AMM_0)])),
+            None
+        );
+    }
+
+    #[test]
+    fn non_amm_writes_are_ignored() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(SEARCHER, POOL_0, NOT_AMM)]));
+        let victim = swap(VICTIM, POOL_0, NOT_AMM);
+        tracker.record_banking_commit(1, [&victim]);
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, NOT_AMM)])),
+            None
+        );
+    }
+
+    #[test]
+    fn new_slot_resets_history() {
+        // Arrange.
+        let registry = AmmRegistry::default();
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        tracker
+            .lock(1)
+            .record_bundle(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)]));
+        let victim = swap(VICTIM, POOL_0, AMM_0);
+        tracker.record_banking_commit(1, [&victim]);
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(2)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)])),
+            None
+        );
+    }
+
+    #[test]
+    fn reject_takes_precedence_over_log_only() {
+        // Arrange.
+        let registry = AmmRegistry::new([(AMM_0, AmmPolicy::LogOnly), (AMM_1, AmmPolicy::Reject)]);
+        let tracker = SandwichTracker::new(SharedAmmRegistry::new(registry.clone()));
+        tracker.lock(1).record_bundle(
+            &registry,
+            &bundle(vec![
+                swap(SEARCHER, POOL_0, AMM_0),
+                swap(SEARCHER, POOL_1, AMM_1),
+            ]),
+        );
+        let victim_0 = swap(VICTIM, POOL_0, AMM_0);
+        let victim_1 = swap(VICTIM, POOL_1, AMM_1);
+        tracker.record_banking_commit(1, [&victim_0, &victim_1]);
+
+        // Act & Assert.
+        assert_eq!(
+            tracker
+                .lock(1)
+                .find_bundle_sandwich(&registry, &bundle(vec![swap(SEARCHER, POOL_0, AMM_0)])),
+            Some(FrontRun {
+                program: AMM_0,
+                policy: AmmPolicy::LogOnly,
+                sandwich: true,
+            })
+        );
+        assert_eq!(
+            tracker.lock(1).find_bundle_sandwich(
+                &registry,
+                &bundle(vec![
+                    swap(SEARCHER, POOL_0, AMM_0),
+                    swap(SEARCHER, POOL_1, AMM_1),
+                ])
+            ),
+            Some(FrontRun {
+                program: AMM_1,
+                policy: AmmPolicy::Reject,
+                sandwich: true,
+            })
+        );
+    }
+}
@@ -4,13 +4,14 @@ use {
             immutable_deserialized_packet::{DeserializedPacketError, ImmutableDeserializedPacket},
             packet_filter::PacketFilterFailure,
         },
-        packet_bundle::PacketBundle,
+        packet_bundle::{BlockBuilderFeeInfo, PacketBundle},
     },
     solana_bundle::SanitizedBundle,
     solana_perf::sigverify::verify_packet,
     solana_runtime::{bank::Bank, verify_precompiles::verify_precompiles},
     solana_sdk::{
-        clock::MAX_PROCESSING_AGE, pubkey::Pubkey, signature::Signature,
+        clock::MAX_PROCESSING_AGE, program_utils::limited_deserialize, pubkey::Pubkey,
+        signature::Signature, system_instruction::SystemInstruction, system_program,
         transaction::SanitizedTransaction,
     },
     solana_svm::transaction_error_metrics::TransactionErrorMetrics,
@@ -60,16 +61,23 @@ pub enum DeserializedBundleError {
     FailedVerifyPrecompiles,
 }
 
+/// Scales tips to micro-lamports per compute unit, the unit used for compute unit prices.
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ImmutableDeserializedBundle {
     bundle_id: String,
     packets: Vec<ImmutableDeserializedPacket>,
+    tip_lamports: u64,
+    tip_per_compute_unit: u64,
+    block_builder_fee_info: Option<BlockBuilderFeeInfo>,
 }
 
 impl ImmutableDeserializedBundle {
     pub fn new(
         bundle: &mut PacketBundle,
         max_len: Option<usize>,
+        tip_accounts: &HashSet<Pubkey>,
         packet_filter: &impl Fn(
             ImmutableDeserializedPacket,
         ) -> Result<ImmutableDeserializedPacket, PacketFilterFailure>,
@@ -99,12 +107,54 @@ impl ImmutableDeserializedBundle {
             immutable_packets.push(immutable_packet);
         }
 
+        let tip_lamports = immutable_packets
+            .iter()
+            .map(|packet| Self::packet_tip_lamports(packet, tip_accounts))
+            .fold(0, u64::saturating_add);
+        let compute_unit_limit = immutable_packets
+            .iter()
+            .map(|packet| packet.compute_unit_limit())
+            .fold(0, u64::saturating_add);
+        let tip_per_compute_unit = tip_lamports
+            .saturating_mul(MICRO_LAMPORTS_PER_LAMPORT)
+            .checked_div(compute_unit_limit)
+            .unwrap_or(0);
+
         Ok(Self {
             bundle_id: bundle.bundle_id.clone(),
             packets: immutable_packets,
+            tip_lamports,
+            tip_per_compute_unit,
+            block_builder_fee_info: bundle.block_builder_fee_info,
         })
     }
 
+    /// Sums the system transfers in `packet` paying into one of `tip_accounts`.
+    fn packet_tip_lamports(
+        packet: &ImmutableDeserializedPacket,
+        tip_accounts: &HashSet<Pubkey>,
+    ) -> u64 {
+        let message = &packet.transaction().get_message().message;
+        let account_keys = message.static_account_keys();
+
+        packet
+            .transaction()
+            .get_message()
+            .program_instructions_iter()
+            .filter(|(program_id, _)| **program_id == system_program::id())
+            .filter_map(|(_, instruction)| {
+                let SystemInstruction::Transfer { lamports } =
+                    limited_deserialize(&instruction.data).ok()?
+                else {
+                    return None;
+                };
+                let to = account_keys.get(usize::from(*instruction.accounts.get(1)?))?;
+
+                tip_accounts.contains(to).then_some(lamports)
+            })
+            .fold(0, u64::saturating_add)
+    }
+
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.packets.len()
@@ -114,6 +164,21 @@ impl ImmutableDeserializedBundle {
         &self.bundle_id
     }
 
+    /// Lamports the bundle transfers to the tip accounts.
+    pub fn tip_lamports(&self) -> u64 {
+        self.tip_lamports
+    }
+
+    /// Estimated tip in micro-lamports per requested compute unit, used to prioritize bundles.
+    pub fn tip_per_compute_unit(&self) -> u64 {
+        self.tip_per_compute_unit
+    }
+
+    /// Fee info of the block engine that sent the bundle.
+    pub fn block_builder_fee_info(&self) -> Option<BlockBuilderFeeInfo> {
+        self.block_builder_fee_info
+    }
+
     /// A bundle has the following requirements:
     /// - all transactions must be sanitiz-able
     /// - no duplicate signatures
@@ -206,11 +271,13 @@ mod tests {
             genesis_utils::GenesisConfigInfo,
         },
         solana_sdk::{
+            compute_budget::ComputeBudgetInstruction,
             hash::Hash,
             instruction::Instruction,
             packet::Packet,
             pubkey::Pubkey,
             signature::{Keypair, Signer},
+            system_instruction,
             system_transaction::transfer,
             transaction::Transaction,
         },
@@ -241,8 +308,10 @@ mod tests {
                     Packet::from_data(None, &tx1).unwrap(),
                 ]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -269,8 +338,10 @@ mod tests {
                 &mut PacketBundle {
                     batch: PacketBatch::new(vec![]),
                     bundle_id: String::default(),
+                    block_builder_fee_info: None,
                 },
                 None,
+                &HashSet::default(),
                 &Ok
             ),
             Err(DeserializedBundleError::EmptyBatch)
@@ -296,8 +367,10 @@ mod tests {
                             .collect()
                     ),
                     bundle_id: String::default(),
+                    block_builder_fee_info: None,
                 },
                 Some(5),
+                &HashSet::default(),
                 &Ok
             ),
             Err(DeserializedBundleError::TooManyPackets)
@@ -317,8 +390,10 @@ mod tests {
                 &mut PacketBundle {
                     batch: PacketBatch::new(vec![packet]),
                     bundle_id: String::default(),
+                    block_builder_fee_info: None,
                 },
                 Some(5),
+                &HashSet::default(),
                 &Ok
             ),
             Err(DeserializedBundleError::MarkedDiscard)
@@ -339,8 +414,10 @@ mod tests {
                 &mut PacketBundle {
                     batch: PacketBatch::new(vec![Packet::from_data(None, tx0).unwrap()]),
                     bundle_id: String::default(),
+                    block_builder_fee_info: None,
                 },
                 None,
+                &HashSet::default(),
                 &Ok
             ),
             Err(DeserializedBundleError::SignatureVerificationFailure)
@@ -372,8 +449,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx0).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -410,8 +489,10 @@ mod tests {
                     Packet::from_data(None, &tx0).unwrap(),
                 ]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -445,8 +526,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx0).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -482,8 +565,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx0).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -517,8 +602,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx0).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -559,8 +646,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -606,8 +695,10 @@ mod tests {
             &mut PacketBundle {
                 batch: PacketBatch::new(vec![Packet::from_data(None, tx).unwrap()]),
                 bundle_id: String::default(),
+                block_builder_fee_info: None,
             },
             None,
+            &HashSet::default(),
             &Ok,
         )
         .unwrap();
@@ -628,4 +719,55 @@ mod tests {
             .build_sanitized_bundle(&bank, &HashSet::default(), &mut transaction_errors, true)
             .is_ok());
     }
+
+    #[test]
+    fn test_tip_per_compute_unit() {
+        let kp = Keypair::new();
+        let tip_account = Pubkey::new_unique();
+
+        // only the transfer into the tip account counts towards the tip
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(10_000),
+                system_instruction::transfer(&kp.pubkey(), &tip_account, 5_000),
+                system_instruction::transfer(&kp.pubkey(), &Pubkey::new_unique(), 7_000),
+            ],
+            Some(&kp.pubkey()),
+            &[&kp],
+            Hash::default(),
+        );
+
+        let bundle = ImmutableDeserializedBundle::new(
+            &mut PacketBundle {
+                batch: PacketBatch::new(vec![Packet::from_data(None, tx).unwrap()]),
+                bundle_id: String::default(),
+                block_builder_fee_info: None,
+            },
+            None,
+            &HashSet::from([tip_account]),
+            &Ok,
+        )
+        .unwrap();
+        assert_eq!(bundle.tip_lamports(), 5_000);
+        assert_eq!(bundle.tip_per_compute_unit(), 500_000);
+
+        // a bundle without tips has no priority
+        let bundle = ImmutableDeserializedBundle::new(
+            &mut PacketBundle {
+                batch: PacketBatch::new(vec![Packet::from_data(
+                    None,
+                    transfer(&kp, &Pubkey::new_unique(), 5_000, Hash::default()),
+                )
+                .unwrap()]),
+                bundle_id: String::default(),
+                block_builder_fee_info: None,
+            },
+            None,
+            &HashSet::from([tip_account]),
+            &Ok,
+        )
+        .unwrap();
+        assert_eq!(bundle.tip_lamports(), 0);
+        assert_eq!(bundle.tip_per_compute_unit(), 0);
+    }
 }
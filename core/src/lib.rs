@@ -113,3 +113,23 @@ pub fn proto_packet_to_packet(p: jito_protos::proto::packet::Packet) -> Packet {
     }
     packet
 }
+
+pub fn packet_to_proto_packet(p: &Packet) -> jito_protos::proto::packet::Packet {
+    jito_protos::proto::packet::Packet {
+        data: p.data(..).unwrap_or_default().to_vec(),
+        meta: Some(jito_protos::proto::packet::Meta {
+            size: p.meta().size as u64,
+            addr: p.meta().addr.to_string(),
+            port: p.meta().port as u32,
+            flags: Some(jito_protos::proto::packet::PacketFlags {
+                discard: p.meta().discard(),
+                forwarded: p.meta().forwarded(),
+                repair: p.meta().repair(),
+                simple_vote_tx: p.meta().is_simple_vote_tx(),
+                tracer_packet: p.meta().is_tracer_packet(),
+                from_staked_node: p.meta().is_from_staked_node(),
+            }),
+            sender_stake: 0,
+        }),
+    }
+}
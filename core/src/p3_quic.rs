@@ -2,17 +2,21 @@ use {
     crate::tpu::MAX_QUIC_CONNECTIONS_PER_PEER,
     crossbeam_channel::{RecvError, TrySendError},
     paladin_lockup_program::state::LockupPool,
-    std::net::SocketAddr,
     solana_perf::packet::PacketBatch,
-    solana_poh::poh_recorder::PohRecorder,
+    solana_runtime::bank_forks::BankForks,
     solana_sdk::{
-        account::ReadableAccount, net::DEFAULT_TPU_COALESCE, pubkey::Pubkey, saturating_add_assign,
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        net::DEFAULT_TPU_COALESCE,
+        pubkey::Pubkey,
+        saturating_add_assign,
         signature::Keypair,
     },
     solana_streamer::{
         nonblocking::{
             quic::{
-                ConnectionPeerType, ConnectionTable, DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
+                ConnectionPeerType, ConnectionTableKey, P3PeerIdentities, P3ServerState,
+                DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             },
             stream_throttle::StakedStreamLoadEMAArgs,
         },
@@ -22,7 +26,7 @@ use {
     spl_discriminator::discriminator::SplDiscriminate,
     std::{
         collections::HashMap,
-        net::UdpSocket,
+        net::{SocketAddr, UdpSocket},
         sync::{
             atomic::{AtomicBool, Ordering},
             Arc, Mutex, RwLock,
@@ -31,25 +35,107 @@ use {
     },
 };
 
-const MAX_STAKED_CONNECTIONS: usize = 256;
-const MAX_UNSTAKED_CONNECTIONS: usize = 0;
+pub const DEFAULT_MAX_STAKED_CONNECTIONS: usize = 256;
+pub const DEFAULT_MAX_UNSTAKED_CONNECTIONS: usize = 0;
+
+pub const DEFAULT_STAKED_NODES_UPDATE_INTERVAL: Duration = Duration::from_secs(900); // 15 minutes
+pub const DEFAULT_POOL_KEY: Pubkey =
+    solana_sdk::pubkey!("EJi4Rj2u1VXiLpKtaqeQh3w4XxAGLFqnAG1jCorSvVmg");
+pub const DEFAULT_STAKE_CUTOFF_PERCENT: u64 = 1;
+
+/// Packets per second shared between all identities on the regular lane, split by stake.
+pub const DEFAULT_REGULAR_PACKETS_PER_SECOND: u64 = 50_000;
+/// Packets per second shared between all identities on the mev lane, split by stake.
+pub const DEFAULT_MEV_PACKETS_PER_SECOND: u64 = 10_000;
+
+/// Maximum number of throttled identities reported per lane per metrics interval.
+const MAX_THROTTLED_IDENTITIES_REPORTED: usize = 10;
+
+/// How often the shared [`P3Config`] is checked for runtime changes.
+const CONFIG_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the root bank is checked for changes to the lockup pool.
+const ROOT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct P3Config {
+    /// Lockup pool whose entries grant P3 connection privileges.
+    pub pool: Pubkey,
+    /// Lockups must hold more than this percentage of the pool's stake to be admitted.
+    pub stake_cutoff_percent: u64,
+    /// Maximum number of staked connections (only read at startup).
+    pub max_staked_connections: usize,
+    /// Maximum number of unstaked connections (only read at startup).
+    pub max_unstaked_connections: usize,
+    /// Maximum time between staked node refreshes. Refreshes otherwise happen whenever the pool
+    /// account changes in a rooted bank.
+    pub staked_nodes_update_interval: Duration,
+    /// Packets per second shared between all identities on the regular lane.
+    pub regular_packets_per_second: u64,
+    /// Packets per second shared between all identities on the mev lane.
+    pub mev_packets_per_second: u64,
+}
+
+impl Default for P3Config {
+    fn default() -> Self {
+        Self {
+            pool: DEFAULT_POOL_KEY,
+            stake_cutoff_percent: DEFAULT_STAKE_CUTOFF_PERCENT,
+            max_staked_connections: DEFAULT_MAX_STAKED_CONNECTIONS,
+            max_unstaked_connections: DEFAULT_MAX_UNSTAKED_CONNECTIONS,
+            staked_nodes_update_interval: DEFAULT_STAKED_NODES_UPDATE_INTERVAL,
+            regular_packets_per_second: DEFAULT_REGULAR_PACKETS_PER_SECOND,
+            mev_packets_per_second: DEFAULT_MEV_PACKETS_PER_SECOND,
+        }
+    }
+}
 
-const STAKED_NODES_UPDATE_INTERVAL: Duration = Duration::from_secs(900); // 15 minutes
-const POOL_KEY: Pubkey = solana_sdk::pubkey!("EJi4Rj2u1VXiLpKtaqeQh3w4XxAGLFqnAG1jCorSvVmg");
+impl P3Config {
+    /// Checks the config, returning a description of the first invalid value found.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.stake_cutoff_percent >= 100 {
+            return Err(format!(
+                "P3 stake cutoff must be below 100%; stake_cutoff_percent={}",
+                self.stake_cutoff_percent
+            ));
+        }
+        if self.staked_nodes_update_interval.is_zero() {
+            return Err("P3 staked nodes update interval must be non-zero".to_string());
+        }
+        if self.regular_packets_per_second == 0 || self.mev_packets_per_second == 0 {
+            return Err(format!(
+                "P3 packet rates must be non-zero; regular_packets_per_second={}, \
+                 mev_packets_per_second={}",
+                self.regular_packets_per_second, self.mev_packets_per_second
+            ));
+        }
+
+        Ok(())
+    }
+}
 
 pub(crate) struct P3Quic {
     exit: Arc<AtomicBool>,
+    shared_config: Arc<Mutex<P3Config>>,
+    config: P3Config,
+    config_last_check: Instant,
     quic_server_regular: std::thread::JoinHandle<()>,
     quic_server_mev: std::thread::JoinHandle<()>,
 
     staked_nodes: Arc<RwLock<StakedNodes>>,
     staked_nodes_last_update: Instant,
-    poh_recorder: Arc<RwLock<PohRecorder>>,
-    staked_connection_table: Arc<Mutex<ConnectionTable>>,
+    bank_forks: Arc<RwLock<BankForks>>,
+    root_slot: Slot,
+    root_last_check: Instant,
+    pool_account: Option<AccountSharedData>,
+    reg_state: P3ServerState,
+    mev_state: P3ServerState,
     reg_packet_rx: crossbeam_channel::Receiver<PacketBatch>,
     mev_packet_rx: crossbeam_channel::Receiver<PacketBatch>,
     packet_tx: crossbeam_channel::Sender<PacketBatch>,
 
+    reg_rate_limits: HashMap<Pubkey, RateLimit>,
+    mev_rate_limits: HashMap<Pubkey, RateLimit>,
+
     metrics: P3Metrics,
     metrics_creation: Instant,
 }
@@ -58,10 +144,13 @@ impl P3Quic {
     pub(crate) fn spawn(
         exit: Arc<AtomicBool>,
         packet_tx: crossbeam_channel::Sender<PacketBatch>,
-        poh_recorder: Arc<RwLock<PohRecorder>>,
+        bank_forks: Arc<RwLock<BankForks>>,
         keypair: &Keypair,
         (p3_socket, p3_mev_socket): (SocketAddr, SocketAddr),
+        shared_config: Arc<Mutex<P3Config>>,
     ) -> (std::thread::JoinHandle<()>, [Arc<EndpointKeyUpdater>; 2]) {
+        let config = shared_config.lock().unwrap().clone();
+
         // Bind the P3 QUIC UDP socket.
         let socket_regular = UdpSocket::bind(p3_socket).unwrap();
         let socket_mev = UdpSocket::bind(p3_mev_socket).unwrap();
@@ -73,9 +162,10 @@ impl P3Quic {
         // TODO: Would be ideal to reduce the number of threads spawned by tokio
         // in streamer (i.e. make it an argument).
 
-        // Create the connection table here as we want to share
-        let staked_connection_table: Arc<Mutex<ConnectionTable>> =
-            Arc::new(Mutex::new(ConnectionTable::new()));
+        // Share each server's connections so we can rate limit packets by identity and purge
+        // connections when their stake changes.
+        let reg_state = P3ServerState::default();
+        let mev_state = P3ServerState::default();
 
         // Spawn the P3 QUIC server (regular).
         let (reg_packet_tx, reg_packet_rx) = crossbeam_channel::unbounded();
@@ -93,8 +183,8 @@ impl P3Quic {
             exit.clone(),
             MAX_QUIC_CONNECTIONS_PER_PEER,
             staked_nodes.clone(),
-            MAX_STAKED_CONNECTIONS,
-            MAX_UNSTAKED_CONNECTIONS,
+            config.max_staked_connections,
+            config.max_unstaked_connections,
             StakedStreamLoadEMAArgs {
                 max_streams_per_ms: 1,
                 stream_throttling_interval_ms: 1000,
@@ -103,7 +193,7 @@ impl P3Quic {
             // Streams will be kept alive for 300s (5min) if no data is sent.
             Duration::from_secs(300),
             DEFAULT_TPU_COALESCE,
-            true,
+            Some(reg_state.clone()),
         )
         .unwrap();
 
@@ -123,8 +213,8 @@ impl P3Quic {
             exit.clone(),
             MAX_QUIC_CONNECTIONS_PER_PEER,
             staked_nodes.clone(),
-            MAX_STAKED_CONNECTIONS,
-            MAX_UNSTAKED_CONNECTIONS,
+            config.max_staked_connections,
+            config.max_unstaked_connections,
             StakedStreamLoadEMAArgs {
                 max_streams_per_ms: 1,
                 stream_throttling_interval_ms: 1000,
@@ -133,24 +223,34 @@ impl P3Quic {
             // Streams will be kept alive for 300s (5min) if no data is sent.
             Duration::from_secs(300),
             DEFAULT_TPU_COALESCE,
-            true,
+            Some(mev_state.clone()),
         )
         .unwrap();
 
         // Spawn the P3 management thread.
         let p3 = Self {
             exit: exit.clone(),
+            shared_config,
+            config,
+            config_last_check: Instant::now(),
             quic_server_regular,
             quic_server_mev,
 
             staked_nodes,
             staked_nodes_last_update: Instant::now(),
-            poh_recorder,
-            staked_connection_table,
+            bank_forks,
+            root_slot: Slot::default(),
+            root_last_check: Instant::now(),
+            pool_account: None,
+            reg_state,
+            mev_state,
             reg_packet_rx,
             mev_packet_rx,
             packet_tx,
 
+            reg_rate_limits: HashMap::default(),
+            mev_rate_limits: HashMap::default(),
+
             metrics: P3Metrics::default(),
             metrics_creation: Instant::now(),
         };
@@ -167,12 +267,7 @@ impl P3Quic {
     fn run(mut self) {
         info!("Spawned P3Quic");
 
-        let start = Instant::now();
-        self.update_staked_nodes();
-        saturating_add_assign!(
-            self.metrics.staked_nodes_us,
-            start.elapsed().as_micros() as u64
-        );
+        self.refresh_staked_nodes(true);
 
         while !self.exit.load(Ordering::Relaxed) {
             // Try to receive mev packets.
@@ -184,7 +279,8 @@ impl P3Quic {
                 recv(self.reg_packet_rx) -> res => match res {
                     Ok(packets) => self.on_regular_packets(packets),
                     Err(RecvError) => break,
-                }
+                },
+                default(ROOT_CHECK_INTERVAL) => {},
             }
 
             // Check if we need to report metrics for the last interval.
@@ -196,20 +292,24 @@ impl P3Quic {
                 self.metrics_creation = now;
             }
 
-            // Check if we need to update staked nodes.
-            if now - self.staked_nodes_last_update >= STAKED_NODES_UPDATE_INTERVAL {
-                let start = Instant::now();
-                self.update_staked_nodes();
-                saturating_add_assign!(
-                    self.metrics.staked_nodes_us,
-                    start.elapsed().as_micros() as u64
-                );
+            // Check if the config was changed at runtime (i.e. via admin RPC).
+            let mut config_changed = false;
+            if now - self.config_last_check >= CONFIG_REFRESH_INTERVAL {
+                let config = self.shared_config.lock().unwrap().clone();
+                if config != self.config {
+                    info!("Updated P3 config; config={config:?}");
+                    self.config = config;
+                    config_changed = true;
+                }
+                self.config_last_check = now;
+            }
 
-                self.staked_nodes_last_update = Instant::now();
-                trace!(
-                    "Updated staked_nodes; stakes={:?}",
-                    self.staked_nodes.read().unwrap().stakes,
-                );
+            // Check if we need to update staked nodes.
+            let force = config_changed
+                || now - self.staked_nodes_last_update >= self.config.staked_nodes_update_interval;
+            if force || now - self.root_last_check >= ROOT_CHECK_INTERVAL {
+                self.refresh_staked_nodes(force);
+                self.root_last_check = now;
             }
         }
 
@@ -218,7 +318,19 @@ impl P3Quic {
     }
 
     fn on_regular_packets(&mut self, mut packets: PacketBatch) {
+        // Remove packets from identities that have exhausted their quota.
+        Self::apply_rate_limits(
+            &self.reg_state.peer_identities,
+            &mut self.reg_rate_limits,
+            &mut self.metrics.reg_throttled,
+            &mut self.metrics.reg_unattributed,
+            &mut packets,
+            Instant::now(),
+        );
         let len = packets.len() as u64;
+        if len == 0 {
+            return;
+        }
         saturating_add_assign!(self.metrics.reg_forwarded, len);
 
         for packet in packets.iter_mut() {
@@ -233,7 +345,19 @@ impl P3Quic {
     }
 
     fn on_mev_packets(&mut self, mut packets: PacketBatch) {
+        // Remove packets from identities that have exhausted their quota.
+        Self::apply_rate_limits(
+            &self.mev_state.peer_identities,
+            &mut self.mev_rate_limits,
+            &mut self.metrics.mev_throttled,
+            &mut self.metrics.mev_unattributed,
+            &mut packets,
+            Instant::now(),
+        );
         let len = packets.len() as u64;
+        if len == 0 {
+            return;
+        }
         saturating_add_assign!(self.metrics.mev_forwarded, len);
 
         // Set drop on revert flag.
@@ -249,37 +373,171 @@ impl P3Quic {
         }
     }
 
-    fn update_staked_nodes(&mut self) {
-        let bank = self.poh_recorder.read().unwrap().get_poh_recorder_bank();
-        let bank = bank.bank();
+    /// Removes packets sent by identities that are over quota from the batch so they never
+    /// reach sigverify.
+    ///
+    /// Packets that cannot be attributed to a live connection (i.e. the connection closed while
+    /// its packets were queued) are removed as well and counted in `unattributed`.
+    fn apply_rate_limits(
+        peers: &P3PeerIdentities,
+        rate_limits: &mut HashMap<Pubkey, RateLimit>,
+        throttled: &mut HashMap<Pubkey, u64>,
+        unattributed: &mut u64,
+        packets: &mut PacketBatch,
+        now: Instant,
+    ) {
+        let peers = peers.read().unwrap();
+        let mut retained = 0;
+        for index in 0..packets.len() {
+            let keep = match peers.get(&packets[index].meta().socket_addr()) {
+                Some((identity, _)) => {
+                    let allowed = rate_limits
+                        .get_mut(identity)
+                        .map_or(false, |rate_limit| rate_limit.try_consume(now));
+                    if !allowed {
+                        saturating_add_assign!(*throttled.entry(*identity).or_default(), 1);
+                    }
+
+                    allowed
+                }
+                None => {
+                    saturating_add_assign!(*unattributed, 1);
+
+                    false
+                }
+            };
+
+            if keep {
+                packets[..].swap(retained, index);
+                retained += 1;
+            }
+        }
+
+        packets.truncate(retained);
+    }
+
+    /// Refreshes the staked nodes if the lockup pool changed in a newly rooted bank (or
+    /// unconditionally if `force` is set).
+    fn refresh_staked_nodes(&mut self, force: bool) {
+        let root_bank = self.bank_forks.read().unwrap().root_bank();
+        if !force && root_bank.slot() == self.root_slot {
+            return;
+        }
+        self.root_slot = root_bank.slot();
 
         // Load the lockup pool account.
-        let Some(pool) = bank.get_account(&POOL_KEY) else {
-            warn!("Lockup pool does not exist; pool={POOL_KEY}");
+        let pool_account = root_bank.get_account(&self.config.pool);
+        if !force && pool_account == self.pool_account {
+            return;
+        }
+
+        let start = Instant::now();
+        self.update_staked_nodes(pool_account.as_ref());
+        self.pool_account = pool_account;
+        self.staked_nodes_last_update = Instant::now();
+        saturating_add_assign!(
+            self.metrics.staked_nodes_us,
+            start.elapsed().as_micros() as u64
+        );
+        trace!(
+            "Updated staked_nodes; slot={}; stakes={:?}",
+            self.root_slot,
+            self.staked_nodes.read().unwrap().stakes,
+        );
+    }
+
+    fn update_staked_nodes(&mut self, pool: Option<&AccountSharedData>) {
+        let pool_key = self.config.pool;
+        let Some(pool) = pool else {
+            warn!("Lockup pool does not exist; pool={pool_key}");
 
             return;
         };
 
         // Try to deserialize the pool.
         let Some(pool) = Self::try_deserialize_lockup_pool(pool.data()) else {
-            warn!("Failed to deserialize lockup pool; pool={POOL_KEY}");
+            warn!("Failed to deserialize lockup pool; pool={pool_key}");
 
             return;
         };
 
         // Setup a new staked nodes map.
+        let stakes = Arc::new(Self::compute_stakes(pool, self.config.stake_cutoff_percent));
+        debug!("Updated stakes; stakes={stakes:?}");
+
+        // Resize each identity's quota to its new share of the pool.
+        let now = Instant::now();
+        Self::update_rate_limits(
+            &mut self.reg_rate_limits,
+            &stakes,
+            self.config.regular_packets_per_second,
+            now,
+        );
+        Self::update_rate_limits(
+            &mut self.mev_rate_limits,
+            &stakes,
+            self.config.mev_packets_per_second,
+            now,
+        );
+
+        // Determine which identities had their stake change.
+        let changed = Self::changed_identities(&self.staked_nodes.read().unwrap().stakes, &stakes);
+        if changed.is_empty() {
+            return;
+        }
+
+        // Swap the old for the new.
+        *self.staked_nodes.write().unwrap() = StakedNodes::new(stakes.clone(), HashMap::default());
+
+        // Purge the connections of identities whose stake no longer matches.
+        for state in [&self.reg_state, &self.mev_state] {
+            let connection_table_l = state.connection_table.blocking_lock();
+            for identity in &changed {
+                let Some(connections) = connection_table_l
+                    .table()
+                    .get(&ConnectionTableKey::Pubkey(*identity))
+                else {
+                    continue;
+                };
+
+                for connection in connections {
+                    match connection.peer_type {
+                        ConnectionPeerType::P3(stake) => {
+                            if stakes
+                                .get(&connection.identity)
+                                .map_or(true, |connection_stake| connection_stake != &stake)
+                            {
+                                info!(
+                                    "Purging connection due to stake; identity={}",
+                                    connection.identity
+                                );
+                                connection.cancel.cancel();
+                                saturating_add_assign!(self.metrics.connections_purged, 1);
+                            }
+                        }
+                        ConnectionPeerType::Staked(_) | ConnectionPeerType::Unstaked => {
+                            eprintln!("BUG: Non-P3 connection in staked connection table");
+                            connection.cancel.cancel();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn compute_stakes(pool: &LockupPool, stake_cutoff_percent: u64) -> HashMap<Pubkey, u64> {
         let mut stake_total = 0;
-        let stakes = pool
-            .entries
+
+        pool.entries
             .iter()
             .take_while(|entry| {
                 stake_total += entry.amount;
 
                 // Take while lockup is initialized and the locked amount exceeds
-                // 1% of total stake.
+                // the cutoff percentage of total stake.
                 entry.lockup != Pubkey::default()
                     && entry.amount > 0
-                    && entry.amount * 100 / stake_total > 1
+                    && entry.amount * 100 / stake_total > stake_cutoff_percent
             })
             .filter(|entry| entry.metadata != [0; 32])
             .map(|entry| (Pubkey::new_from_array(entry.metadata), entry.amount))
@@ -290,34 +548,42 @@ impl P3Quic {
 
                     map
                 },
-            );
-        let stakes = Arc::new(stakes);
-        debug!("Updated stakes; stakes={stakes:?}");
+            )
+    }
 
-        // Swap the old for the new.
-        *self.staked_nodes.write().unwrap() = StakedNodes::new(stakes.clone(), HashMap::default());
+    /// Returns the identities that were added, removed or had their stake changed.
+    fn changed_identities(old: &HashMap<Pubkey, u64>, new: &HashMap<Pubkey, u64>) -> Vec<Pubkey> {
+        old.iter()
+            .filter(|(identity, stake)| new.get(identity) != Some(stake))
+            .map(|(identity, _)| *identity)
+            .chain(
+                new.keys()
+                    .filter(|identity| !old.contains_key(identity))
+                    .copied(),
+            )
+            .collect()
+    }
 
-        // Purge all connections where their stake no longer matches.
-        let connection_table_l = self.staked_connection_table.lock().unwrap();
-        for connection in connection_table_l.table().values().flatten() {
-            match connection.peer_type {
-                ConnectionPeerType::P3(stake) => {
-                    if stakes
-                        .get(&connection.identity)
-                        .map_or(true, |connection_stake| connection_stake != &stake)
-                    {
-                        info!(
-                            "Purging connection due to stake; identity={}",
-                            connection.identity
-                        );
-                        connection.cancel.cancel();
-                    }
-                }
-                ConnectionPeerType::Staked(_) | ConnectionPeerType::Unstaked => {
-                    eprintln!("BUG: Non-P3 connection in staked connection table");
-                    connection.cancel.cancel();
-                }
-            }
+    /// Sizes each identity's quota from its share of the total pool stake, dropping the quotas of
+    /// identities that are no longer in the pool.
+    fn update_rate_limits(
+        rate_limits: &mut HashMap<Pubkey, RateLimit>,
+        stakes: &HashMap<Pubkey, u64>,
+        packets_per_second: u64,
+        now: Instant,
+    ) {
+        let stake_total = stakes.values().sum::<u64>().max(1);
+
+        rate_limits.retain(|identity, _| stakes.contains_key(identity));
+        for (identity, stake) in stakes {
+            let cap = (u128::from(packets_per_second) * u128::from(*stake)
+                / u128::from(stake_total)) as u64;
+            let cap = cap.max(1);
+
+            rate_limits
+                .entry(*identity)
+                .and_modify(|rate_limit| rate_limit.set_cap(cap))
+                .or_insert_with(|| RateLimit::new(cap, now));
         }
     }
 
@@ -330,7 +596,7 @@ impl P3Quic {
     }
 }
 
-#[allow(dead_code)]
+/// Token bucket holding up to `cap` packets that refills at `cap` packets per second.
 #[derive(Debug)]
 struct RateLimit {
     cap: u64,
@@ -338,6 +604,44 @@ struct RateLimit {
     last: Instant,
 }
 
+impl RateLimit {
+    fn new(cap: u64, now: Instant) -> Self {
+        Self {
+            cap,
+            remaining: cap,
+            last: now,
+        }
+    }
+
+    fn set_cap(&mut self, cap: u64) {
+        self.cap = cap;
+        self.remaining = self.remaining.min(cap);
+    }
+
+    fn try_consume(&mut self, now: Instant) -> bool {
+        // NB: Only move `last` forward once at least one token has been refilled, otherwise
+        // frequent calls would never accumulate enough time to refill.
+        let elapsed_us = now.saturating_duration_since(self.last).as_micros();
+        let refill = elapsed_us.saturating_mul(u128::from(self.cap)) / 1_000_000;
+        if refill > 0 {
+            self.remaining = u64::try_from(refill)
+                .unwrap_or(u64::MAX)
+                .saturating_add(self.remaining)
+                .min(self.cap);
+            self.last = now;
+        }
+
+        match self.remaining.checked_sub(1) {
+            Some(remaining) => {
+                self.remaining = remaining;
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
 #[derive(Default, PartialEq, Eq)]
 struct P3Metrics {
     /// Number of regular packets forwarded.
@@ -348,8 +652,18 @@ struct P3Metrics {
     mev_forwarded: u64,
     /// Number of mev packets dropped.
     mev_dropped: u64,
+    /// Number of regular packets removed for exceeding the sender's quota.
+    reg_throttled: HashMap<Pubkey, u64>,
+    /// Number of mev packets removed for exceeding the sender's quota.
+    mev_throttled: HashMap<Pubkey, u64>,
+    /// Number of regular packets removed as their connection had already closed.
+    reg_unattributed: u64,
+    /// Number of mev packets removed as their connection had already closed.
+    mev_unattributed: u64,
     /// Time taken to update staked nodes.
     staked_nodes_us: u64,
+    /// Number of connections purged due to a stake change.
+    connections_purged: u64,
 }
 
 impl P3Metrics {
@@ -366,7 +680,255 @@ impl P3Metrics {
             ("regular_packets_dropped", self.reg_dropped as i64, i64),
             ("mev_packets_forwarded", self.mev_forwarded as i64, i64),
             ("mev_packets_dropped", self.mev_dropped as i64, i64),
+            (
+                "regular_packets_throttled",
+                self.reg_throttled.values().sum::<u64>() as i64,
+                i64
+            ),
+            (
+                "mev_packets_throttled",
+                self.mev_throttled.values().sum::<u64>() as i64,
+                i64
+            ),
+            (
+                "regular_packets_unattributed",
+                self.reg_unattributed as i64,
+                i64
+            ),
+            (
+                "mev_packets_unattributed",
+                self.mev_unattributed as i64,
+                i64
+            ),
             ("staked_nodes_us", self.staked_nodes_us as i64, i64),
+            ("connections_purged", self.connections_purged as i64, i64),
         );
+        if self.reg_unattributed > 0 || self.mev_unattributed > 0 {
+            warn!(
+                "Dropped P3 packets from closed connections; regular={}, mev={}",
+                self.reg_unattributed, self.mev_unattributed,
+            );
+        }
+
+        for (lane, throttled) in [
+            ("regular", &self.reg_throttled),
+            ("mev", &self.mev_throttled),
+        ] {
+            for (identity, count) in Self::top_throttled(throttled) {
+                datapoint_info!(
+                    "p3_quic_throttled",
+                    "identity" => identity.to_string(),
+                    "lane" => lane,
+                    ("packets", count as i64, i64),
+                );
+            }
+        }
+    }
+
+    /// Returns the most throttled identities, bounding the cardinality of per-identity metrics.
+    fn top_throttled(throttled: &HashMap<Pubkey, u64>) -> Vec<(Pubkey, u64)> {
+        let mut top: Vec<_> = throttled
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(identity, count)| (*identity, *count))
+            .collect();
+        top.sort_unstable_by(|(_, a), (_, b)| b.cmp(a));
+        top.truncate(MAX_THROTTLED_IDENTITIES_REPORTED);
+
+        top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        bytemuck::Zeroable,
+        solana_perf::packet::Packet,
+        std::net::{IpAddr, Ipv4Addr},
+    };
+
+    #[test]
+    fn test_validate_config() {
+        assert!(P3Config::default().validate().is_ok());
+
+        for config in [
+            P3Config {
+                stake_cutoff_percent: 100,
+                ..P3Config::default()
+            },
+            P3Config {
+                staked_nodes_update_interval: Duration::ZERO,
+                ..P3Config::default()
+            },
+            P3Config {
+                regular_packets_per_second: 0,
+                ..P3Config::default()
+            },
+            P3Config {
+                mev_packets_per_second: 0,
+                ..P3Config::default()
+            },
+        ] {
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_refill() {
+        let start = Instant::now();
+        let mut rate_limit = RateLimit::new(10, start);
+
+        // Drain the bucket.
+        for _ in 0..10 {
+            assert!(rate_limit.try_consume(start));
+        }
+        assert!(!rate_limit.try_consume(start));
+
+        // Refills proportionally to elapsed time.
+        let later = start + Duration::from_millis(500);
+        for _ in 0..5 {
+            assert!(rate_limit.try_consume(later));
+        }
+        assert!(!rate_limit.try_consume(later));
+
+        // Never refills beyond the cap.
+        let much_later = later + Duration::from_secs(60);
+        for _ in 0..10 {
+            assert!(rate_limit.try_consume(much_later));
+        }
+        assert!(!rate_limit.try_consume(much_later));
+    }
+
+    #[test]
+    fn test_update_rate_limits_by_stake() {
+        let now = Instant::now();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let mut rate_limits = HashMap::default();
+
+        P3Quic::update_rate_limits(
+            &mut rate_limits,
+            &HashMap::from([(a, 300), (b, 100)]),
+            1_000,
+            now,
+        );
+        assert_eq!(rate_limits[&a].cap, 750);
+        assert_eq!(rate_limits[&b].cap, 250);
+
+        // Departed identities lose their quota, shrinking quotas clamp the remaining tokens.
+        P3Quic::update_rate_limits(
+            &mut rate_limits,
+            &HashMap::from([(a, 100), (c, 300)]),
+            1_000,
+            now,
+        );
+        assert_eq!(rate_limits.len(), 2);
+        assert_eq!(rate_limits[&a].cap, 250);
+        assert_eq!(rate_limits[&a].remaining, 250);
+        assert_eq!(rate_limits[&c].cap, 750);
+    }
+
+    #[test]
+    fn test_apply_rate_limits() {
+        let now = Instant::now();
+        let identity = Pubkey::new_unique();
+        let known = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8000);
+        let unknown = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8001);
+        let peers = P3PeerIdentities::default();
+        peers.write().unwrap().insert(known, (identity, 0));
+        let mut rate_limits = HashMap::from([(identity, RateLimit::new(2, now))]);
+        let mut throttled = HashMap::default();
+        let mut unattributed = 0;
+
+        let mut packets = PacketBatch::new(
+            [unknown, known, known, known]
+                .iter()
+                .enumerate()
+                .map(|(index, addr)| {
+                    let mut packet = Packet::default();
+                    packet.meta_mut().set_socket_addr(addr);
+                    packet.meta_mut().size = index;
+
+                    packet
+                })
+                .collect(),
+        );
+        P3Quic::apply_rate_limits(
+            &peers,
+            &mut rate_limits,
+            &mut throttled,
+            &mut unattributed,
+            &mut packets,
+            now,
+        );
+
+        // Over quota & unattributed packets are removed, the rest keep their order.
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.meta().size)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(throttled, HashMap::from([(identity, 1)]));
+        assert_eq!(unattributed, 1);
+    }
+
+    #[test]
+    fn test_compute_stakes() {
+        let mut pool = LockupPool::zeroed();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        for (entry, (identity, amount)) in pool
+            .entries
+            .iter_mut()
+            .zip([(a, 600), (b, 300), (a, 100), (b, 5)])
+        {
+            entry.lockup = Pubkey::new_unique();
+            entry.amount = amount;
+            entry.metadata = identity.to_bytes();
+        }
+        pool.entries_len = 4;
+
+        // Entries are taken until one falls below the cutoff of the running total.
+        assert_eq!(
+            P3Quic::compute_stakes(&pool, 1),
+            HashMap::from([(a, 700), (b, 300)])
+        );
+        assert_eq!(
+            P3Quic::compute_stakes(&pool, 20),
+            HashMap::from([(a, 600), (b, 300)])
+        );
+    }
+
+    #[test]
+    fn test_changed_identities() {
+        let unchanged = Pubkey::new_unique();
+        let changed = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        let added = Pubkey::new_unique();
+
+        let mut identities = P3Quic::changed_identities(
+            &HashMap::from([(unchanged, 1), (changed, 2), (removed, 3)]),
+            &HashMap::from([(unchanged, 1), (changed, 3), (added, 4)]),
+        );
+        identities.sort();
+        let mut expected = vec![changed, removed, added];
+        expected.sort();
+        assert_eq!(identities, expected);
+    }
+
+    #[test]
+    fn test_top_throttled() {
+        let throttled: HashMap<_, _> = (1..=MAX_THROTTLED_IDENTITIES_REPORTED as u64 + 5)
+            .map(|count| (Pubkey::new_unique(), count))
+            .collect();
+
+        let top = P3Metrics::top_throttled(&throttled);
+        assert_eq!(top.len(), MAX_THROTTLED_IDENTITIES_REPORTED);
+        assert_eq!(top[0].1, MAX_THROTTLED_IDENTITIES_REPORTED as u64 + 5);
+        assert_eq!(top[MAX_THROTTLED_IDENTITIES_REPORTED - 1].1, 6);
     }
 }
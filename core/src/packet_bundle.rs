@@ -1,7 +1,19 @@
-use solana_perf::packet::PacketBatch;
+use {solana_perf::packet::PacketBatch, solana_sdk::pubkey::Pubkey};
 
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PacketBundle {
     pub batch: PacketBatch,
     pub bundle_id: String,
+    /// Fee info of the block engine that sent the bundle, which the tip programs are cranked with
+    /// before it lands. Bundles without one pay the whole tip to the leader.
+    pub block_builder_fee_info: Option<BlockBuilderFeeInfo>,
+}
+
+/// The block builder a block engine pays its share of tips to.
+#[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockBuilderFeeInfo {
+    pub block_builder: Pubkey,
+    pub block_builder_commission: u64,
 }
@@ -5,37 +5,48 @@
 //! - Sends transactions and bundles to the validator.
 use {
     crate::{
-        banking_trace::BankingPacketSender,
-        packet_bundle::PacketBundle,
+        banking_trace::{BankingPacketSender, TracedBundleSender},
+        bundle_stage::{BundleDropReason, BundleResult, BundleResultEvent, BundleResultReceiver},
+        packet_bundle::{BlockBuilderFeeInfo, PacketBundle},
         proto_packet_to_packet,
         proxy::{
             auth::{generate_auth_tokens, maybe_refresh_auth_tokens, AuthInterceptor},
+            packet_relay::{PacketRelay, PACKET_BATCH_UPDATE_CHANNEL_CAPACITY},
             ProxyError,
         },
     },
-    crossbeam_channel::Sender,
+    crossbeam_channel::{RecvTimeoutError, Sender},
+    futures::{future::join_all, stream},
     jito_protos::proto::{
         auth::{auth_service_client::AuthServiceClient, Token},
         block_engine::{
-            self, block_engine_validator_client::BlockEngineValidatorClient,
-            BlockBuilderFeeInfoRequest,
+            self, block_engine_relayer_client::BlockEngineRelayerClient,
+            block_engine_validator_client::BlockEngineValidatorClient, packet_batch_update::Msg,
+            AccountsOfInterestRequest, BlockBuilderFeeInfoRequest, BlockBuilderFeeInfoResponse,
+            BundleOutcome, BundleOutcomeReason, BundleOutcomes, PacketBatchUpdate,
+            ProgramsOfInterestRequest,
         },
+        shared::Heartbeat,
     },
+    lru::LruCache,
+    solana_bundle::{bundle_execution::LoadAndExecuteBundleError, BundleExecutionError},
     solana_gossip::cluster_info::ClusterInfo,
     solana_perf::packet::PacketBatch,
     solana_sdk::{
         pubkey::Pubkey, saturating_add_assign, signature::Signer, signer::keypair::Keypair,
     },
     std::{
+        collections::HashMap,
         str::FromStr,
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, Mutex,
         },
         thread::{self, Builder, JoinHandle},
-        time::Duration,
+        time::{Duration, Instant},
     },
     tokio::{
+        sync::mpsc::{self, error::TrySendError},
         task,
         time::{interval, sleep, timeout},
     },
@@ -48,36 +59,90 @@ use {
 
 const CONNECTION_TIMEOUT_S: u64 = 10;
 const CONNECTION_BACKOFF_S: u64 = 5;
+/// Number of recently received bundle ids remembered to drop bundles sent by more than one block
+/// engine.
+const BUNDLE_ID_CACHE_CAPACITY: usize = 100_000;
+const BUNDLE_RESULT_RECEIVE_TIMEOUT: Duration = Duration::from_millis(100);
+const RELAYED_PACKET_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the block engines are ranked again to fail back to a faster one after a failover.
+const BLOCK_ENGINE_RERANK_INTERVAL: Duration = Duration::from_secs(60);
+/// How much faster a block engine must connect to be worth dropping the current stream for.
+const BLOCK_ENGINE_FAILBACK_MIN_IMPROVEMENT: Duration = Duration::from_millis(5);
 
+/// Url of the block engine that first sent each recently received bundle, keyed by bundle id.
+type BundleIdCache = Mutex<LruCache<String, String>>;
+/// Streams reporting bundle outcomes to each connected block engine, keyed by url.
+type BundleOutcomeSenders = Mutex<HashMap<String, mpsc::UnboundedSender<BundleOutcomes>>>;
+
+/// Stops reporting bundle outcomes and relaying packets to a block engine once its connection
+/// ends, including when the stream is dropped to fail back to a faster block engine.
+struct BlockEngineConnection<'a> {
+    block_engine_url: &'a str,
+    bundle_outcome_senders: &'a BundleOutcomeSenders,
+    packet_relay: &'a PacketRelay,
+    relay_packets_task: task::JoinHandle<()>,
+}
+
+impl Drop for BlockEngineConnection<'_> {
+    fn drop(&mut self) {
+        self.relay_packets_task.abort();
+        self.packet_relay.unregister(self.block_engine_url);
+        self.bundle_outcome_senders
+            .lock()
+            .unwrap()
+            .remove(self.block_engine_url);
+    }
+}
+
+/// Stats of the connection to a single block engine endpoint.
 #[derive(Default)]
 struct BlockEngineStageStats {
+    url: String,
+    auth_latency_us: u64,
     num_bundles: u64,
     num_bundle_packets: u64,
+    num_duplicate_bundles: u64,
     num_packets: u64,
     num_empty_packets: u64,
 }
 
 impl BlockEngineStageStats {
-    pub(crate) fn report(&self) {
+    fn new(url: &str, auth_latency: Duration) -> Self {
+        Self {
+            url: url.to_string(),
+            auth_latency_us: auth_latency.as_micros() as u64,
+            ..Self::default()
+        }
+    }
+
+    /// Reports and resets the counters, keeping the endpoint they belong to.
+    pub(crate) fn report(&mut self) {
         datapoint_info!(
             "block_engine_stage-stats",
+            ("url", self.url, String),
+            ("auth_latency_us", self.auth_latency_us, i64),
             ("num_bundles", self.num_bundles, i64),
             ("num_bundle_packets", self.num_bundle_packets, i64),
+            ("num_duplicate_bundles", self.num_duplicate_bundles, i64),
             ("num_packets", self.num_packets, i64),
             ("num_empty_packets", self.num_empty_packets, i64)
         );
+        *self = Self {
+            url: std::mem::take(&mut self.url),
+            auth_latency_us: self.auth_latency_us,
+            ..Self::default()
+        };
     }
 }
 
-pub struct BlockBuilderFeeInfo {
-    pub block_builder: Pubkey,
-    pub block_builder_commission: u64,
-}
-
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct BlockEngineConfig {
-    /// Block Engine URL
-    pub block_engine_url: String,
+    /// Block Engine URLs. Bundles received from more than one of them are deduplicated by id.
+    pub block_engine_urls: Vec<String>,
+
+    /// If set then bundles and packets are streamed from every block engine at once, otherwise
+    /// only from the lowest latency one, failing over to the next fastest when it disconnects.
+    pub connect_all: bool,
 
     /// If set then it will be assumed the backend verified packets so signature verification will be bypassed in the validator.
     pub trust_packets: bool,
@@ -91,17 +156,30 @@ impl BlockEngineStage {
     pub fn new(
         block_engine_config: Arc<Mutex<BlockEngineConfig>>,
         // Channel that bundles get piped through.
-        bundle_tx: Sender<Vec<PacketBundle>>,
+        bundle_tx: TracedBundleSender,
+        // Channel the bundle stage reports the outcome of executed bundles through.
+        bundle_result_receiver: BundleResultReceiver,
         // The keypair stored here is used to sign auth challenges.
         cluster_info: Arc<ClusterInfo>,
         // Channel that non-trusted packets get piped through.
         packet_tx: Sender<PacketBatch>,
         // Channel that trusted packets get piped through.
         banking_packet_sender: BankingPacketSender,
+        // Kept up to date with the accounts and programs each block engine is interested in.
+        packet_relay: Arc<PacketRelay>,
         exit: Arc<AtomicBool>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
     ) -> Self {
-        let block_builder_fee_info = block_builder_fee_info.clone();
+        // Shared by all connections so a bundle is forwarded once no matter how many block engines
+        // send it, including across a failover.
+        let bundle_id_cache = Arc::new(Mutex::new(LruCache::new(BUNDLE_ID_CACHE_CAPACITY)));
+        let bundle_outcome_senders = Arc::new(Mutex::new(HashMap::default()));
+
+        let bundle_result_thread = Self::start_bundle_result_thread(
+            bundle_result_receiver,
+            bundle_id_cache.clone(),
+            bundle_outcome_senders.clone(),
+            exit.clone(),
+        );
 
         let thread = Builder::new()
             .name("block-engine-stage".to_string())
@@ -114,16 +192,18 @@ impl BlockEngineStage {
                     block_engine_config,
                     cluster_info,
                     bundle_tx,
+                    bundle_id_cache,
+                    bundle_outcome_senders,
                     packet_tx,
                     banking_packet_sender,
+                    packet_relay,
                     exit,
-                    block_builder_fee_info,
                 ));
             })
             .unwrap();
 
         Self {
-            t_hdls: vec![thread],
+            t_hdls: vec![thread, bundle_result_thread],
         }
     }
 
@@ -138,15 +218,17 @@ impl BlockEngineStage {
     async fn start(
         block_engine_config: Arc<Mutex<BlockEngineConfig>>,
         cluster_info: Arc<ClusterInfo>,
-        bundle_tx: Sender<Vec<PacketBundle>>,
+        bundle_tx: TracedBundleSender,
+        bundle_id_cache: Arc<BundleIdCache>,
+        bundle_outcome_senders: Arc<BundleOutcomeSenders>,
         packet_tx: Sender<PacketBatch>,
         banking_packet_sender: BankingPacketSender,
+        packet_relay: Arc<PacketRelay>,
         exit: Arc<AtomicBool>,
-        block_builder_fee_info: Arc<Mutex<BlockBuilderFeeInfo>>,
     ) {
         const CONNECTION_TIMEOUT: Duration = Duration::from_secs(CONNECTION_TIMEOUT_S);
         const CONNECTION_BACKOFF: Duration = Duration::from_secs(CONNECTION_BACKOFF_S);
-        let mut error_count: u64 = 0;
+        let error_count = AtomicU64::default();
 
         while !exit.load(Ordering::Relaxed) {
             // Wait until a valid config is supplied (either initially or by admin rpc)
@@ -159,66 +241,325 @@ impl BlockEngineStage {
             };
             if !Self::is_valid_block_engine_config(&local_block_engine_config) {
                 sleep(CONNECTION_BACKOFF).await;
-            } else if let Err(e) = Self::connect_auth_and_stream(
+            } else if local_block_engine_config.connect_all {
+                join_all(local_block_engine_config.block_engine_urls.iter().map(
+                    |block_engine_url| {
+                        Self::maintain_block_engine_connection(
+                            block_engine_url,
+                            &local_block_engine_config,
+                            &block_engine_config,
+                            &cluster_info,
+                            &bundle_tx,
+                            &bundle_id_cache,
+                            &bundle_outcome_senders,
+                            &packet_tx,
+                            &banking_packet_sender,
+                            &packet_relay,
+                            &exit,
+                            &CONNECTION_TIMEOUT,
+                            &error_count,
+                        )
+                    },
+                ))
+                .await;
+            } else if Self::stream_from_fastest_block_engine(
                 &local_block_engine_config,
                 &block_engine_config,
                 &cluster_info,
                 &bundle_tx,
+                &bundle_id_cache,
+                &bundle_outcome_senders,
                 &packet_tx,
                 &banking_packet_sender,
+                &packet_relay,
                 &exit,
-                &block_builder_fee_info,
                 &CONNECTION_TIMEOUT,
+                &error_count,
             )
             .await
+            .is_err()
             {
-                match e {
-                    // This error is frequent on hot spares, and the parsed string does not work
-                    // with datapoints (incorrect escaping).
-                    ProxyError::AuthenticationPermissionDenied => {
-                        warn!("block engine permission denied. not on leader schedule. ignore if hot-spare.")
-                    }
-                    e => {
-                        error_count += 1;
-                        datapoint_warn!(
-                            "block_engine_stage-proxy_error",
-                            ("count", error_count, i64),
-                            ("error", e.to_string(), String),
-                        );
-                    }
-                }
                 sleep(CONNECTION_BACKOFF).await;
             }
         }
     }
 
-    async fn connect_auth_and_stream(
+    /// Keeps streaming from `block_engine_url`, reconnecting after a backoff when the connection
+    /// drops, until exit or the config changes.
+    #[allow(clippy::too_many_arguments)]
+    async fn maintain_block_engine_connection(
+        block_engine_url: &str,
         local_block_engine_config: &BlockEngineConfig,
         global_block_engine_config: &Arc<Mutex<BlockEngineConfig>>,
         cluster_info: &Arc<ClusterInfo>,
-        bundle_tx: &Sender<Vec<PacketBundle>>,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
         packet_tx: &Sender<PacketBatch>,
         banking_packet_sender: &BankingPacketSender,
+        packet_relay: &Arc<PacketRelay>,
         exit: &Arc<AtomicBool>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         connection_timeout: &Duration,
-    ) -> crate::proxy::Result<()> {
-        // Get a copy of configs here in case they have changed at runtime
-        let keypair = cluster_info.keypair().clone();
+        error_count: &AtomicU64,
+    ) {
+        const CONNECTION_BACKOFF: Duration = Duration::from_secs(CONNECTION_BACKOFF_S);
 
-        let mut backend_endpoint =
-            Endpoint::from_shared(local_block_engine_config.block_engine_url.clone())
-                .map_err(|_| {
-                    ProxyError::BlockEngineConnectionError(format!(
-                        "invalid block engine url value: {}",
-                        local_block_engine_config.block_engine_url
-                    ))
-                })?
-                .tcp_keepalive(Some(Duration::from_secs(60)));
-        if local_block_engine_config
-            .block_engine_url
-            .starts_with("https")
+        while !exit.load(Ordering::Relaxed)
+            && !Self::block_engine_config_changed(
+                local_block_engine_config,
+                global_block_engine_config,
+            )
+            .await
         {
+            match Self::connect_auth_and_stream(
+                block_engine_url,
+                local_block_engine_config,
+                global_block_engine_config,
+                cluster_info,
+                bundle_tx,
+                bundle_id_cache,
+                bundle_outcome_senders,
+                packet_tx,
+                banking_packet_sender,
+                packet_relay,
+                exit,
+                connection_timeout,
+            )
+            .await
+            {
+                Ok(()) | Err(ProxyError::BlockEngineConfigChanged) => break,
+                Err(e) => {
+                    Self::report_proxy_error(block_engine_url, e, error_count);
+                    sleep(CONNECTION_BACKOFF).await;
+                }
+            }
+        }
+    }
+
+    /// Streams from the reachable block engine with the lowest latency and fails over to the next
+    /// fastest one without a backoff when it disconnects. Returns an error once every block engine
+    /// has failed.
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_from_fastest_block_engine(
+        local_block_engine_config: &BlockEngineConfig,
+        global_block_engine_config: &Arc<Mutex<BlockEngineConfig>>,
+        cluster_info: &Arc<ClusterInfo>,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
+        packet_tx: &Sender<PacketBatch>,
+        banking_packet_sender: &BankingPacketSender,
+        packet_relay: &Arc<PacketRelay>,
+        exit: &Arc<AtomicBool>,
+        connection_timeout: &Duration,
+        error_count: &AtomicU64,
+    ) -> crate::proxy::Result<()> {
+        let ranked_block_engines = Self::rank_block_engines_by_latency(
+            &local_block_engine_config.block_engine_urls,
+            connection_timeout,
+        )
+        .await;
+        Self::stream_with_failover(
+            ranked_block_engines,
+            local_block_engine_config,
+            global_block_engine_config,
+            cluster_info,
+            bundle_tx,
+            bundle_id_cache,
+            bundle_outcome_senders,
+            packet_tx,
+            banking_packet_sender,
+            packet_relay,
+            exit,
+            connection_timeout,
+            error_count,
+        )
+        .await
+    }
+
+    /// Streams from the first of `ranked_block_engines`, failing over down the ranking when a
+    /// block engine disconnects. The block engines are ranked again every
+    /// [BLOCK_ENGINE_RERANK_INTERVAL] and the stream fails back to a much faster one.
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_with_failover<'a>(
+        mut ranked_block_engines: Vec<(&'a str, Duration)>,
+        local_block_engine_config: &'a BlockEngineConfig,
+        global_block_engine_config: &Arc<Mutex<BlockEngineConfig>>,
+        cluster_info: &Arc<ClusterInfo>,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
+        packet_tx: &Sender<PacketBatch>,
+        banking_packet_sender: &BankingPacketSender,
+        packet_relay: &Arc<PacketRelay>,
+        exit: &Arc<AtomicBool>,
+        connection_timeout: &Duration,
+        error_count: &AtomicU64,
+    ) -> crate::proxy::Result<()> {
+        let mut result = Err(ProxyError::BlockEngineConnectionError(
+            "no block engine reachable".to_string(),
+        ));
+        let mut rank = 0;
+        while let Some(&(block_engine_url, _)) = ranked_block_engines.get(rank) {
+            if exit.load(Ordering::Relaxed)
+                || Self::block_engine_config_changed(
+                    local_block_engine_config,
+                    global_block_engine_config,
+                )
+                .await
+            {
+                return Ok(());
+            }
+
+            let stream = Self::connect_auth_and_stream(
+                block_engine_url,
+                local_block_engine_config,
+                global_block_engine_config,
+                cluster_info,
+                bundle_tx,
+                bundle_id_cache,
+                bundle_outcome_senders,
+                packet_tx,
+                banking_packet_sender,
+                packet_relay,
+                exit,
+                connection_timeout,
+            );
+            tokio::pin!(stream);
+            let stream_result = loop {
+                tokio::select! {
+                    stream_result = &mut stream => break Some(stream_result),
+                    new_ranking = async {
+                        sleep(BLOCK_ENGINE_RERANK_INTERVAL).await;
+                        Self::rank_block_engines_by_latency(
+                            &local_block_engine_config.block_engine_urls,
+                            connection_timeout,
+                        )
+                        .await
+                    } => {
+                        if Self::should_fail_back(&new_ranking, block_engine_url) {
+                            datapoint_info!(
+                                "block_engine_stage-failback",
+                                ("from_url", block_engine_url, String),
+                                ("to_url", new_ranking[0].0, String),
+                            );
+                            ranked_block_engines = new_ranking;
+                            break None;
+                        }
+                    }
+                }
+            };
+
+            match stream_result {
+                // Dropping the stream disconnected from the slower block engine
+                None => rank = 0,
+                Some(Ok(()) | Err(ProxyError::BlockEngineConfigChanged)) => return Ok(()),
+                Some(Err(e)) => {
+                    datapoint_info!(
+                        "block_engine_stage-failover",
+                        ("url", block_engine_url, String),
+                        ("error", e.to_string(), String),
+                    );
+                    result = Err(ProxyError::BlockEngineConnectionError(format!(
+                        "{block_engine_url}: {e}"
+                    )));
+                    Self::report_proxy_error(block_engine_url, e, error_count);
+                    rank += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Whether the fastest of `ranked_block_engines` is enough faster than the one currently
+    /// streamed from to be worth reconnecting. A block engine missing from the ranking is left to
+    /// fail over by itself.
+    fn should_fail_back(ranked_block_engines: &[(&str, Duration)], block_engine_url: &str) -> bool {
+        let Some((_, current_latency)) = ranked_block_engines
+            .iter()
+            .find(|(url, _)| *url == block_engine_url)
+        else {
+            return false;
+        };
+        ranked_block_engines
+            .first()
+            .is_some_and(|(_, fastest_latency)| {
+                fastest_latency.saturating_add(BLOCK_ENGINE_FAILBACK_MIN_IMPROVEMENT)
+                    < *current_latency
+            })
+    }
+
+    /// Returns the block engines that accepted a connection within `connection_timeout`, ordered
+    /// by how long connecting took.
+    async fn rank_block_engines_by_latency<'a>(
+        block_engine_urls: &'a [String],
+        connection_timeout: &Duration,
+    ) -> Vec<(&'a str, Duration)> {
+        let mut latencies: Vec<_> =
+            join_all(block_engine_urls.iter().map(|block_engine_url| async move {
+                let endpoint = Self::block_engine_endpoint(block_engine_url).ok()?;
+                let start = Instant::now();
+                timeout(*connection_timeout, endpoint.connect())
+                    .await
+                    .ok()?
+                    .ok()?;
+                let latency = start.elapsed();
+                datapoint_info!(
+                    "block_engine_stage-connect_latency",
+                    ("url", block_engine_url, String),
+                    ("latency_us", latency.as_micros() as i64, i64),
+                );
+                Some((block_engine_url.as_str(), latency))
+            }))
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        latencies.sort_by_key(|(_, latency)| *latency);
+        latencies
+    }
+
+    fn report_proxy_error(block_engine_url: &str, e: ProxyError, error_count: &AtomicU64) {
+        match e {
+            // This error is frequent on hot spares, and the parsed string does not work
+            // with datapoints (incorrect escaping).
+            ProxyError::AuthenticationPermissionDenied => {
+                warn!(
+                    "block engine permission denied. not on leader schedule. ignore if hot-spare."
+                )
+            }
+            e => {
+                let error_count = error_count.fetch_add(1, Ordering::Relaxed) + 1;
+                datapoint_warn!(
+                    "block_engine_stage-proxy_error",
+                    ("url", block_engine_url, String),
+                    ("count", error_count, i64),
+                    ("error", e.to_string(), String),
+                );
+            }
+        }
+    }
+
+    async fn block_engine_config_changed(
+        local_block_engine_config: &BlockEngineConfig,
+        global_block_engine_config: &Arc<Mutex<BlockEngineConfig>>,
+    ) -> bool {
+        let global_block_engine_config = global_block_engine_config.clone();
+        *local_block_engine_config
+            != task::spawn_blocking(move || global_block_engine_config.lock().unwrap().clone())
+                .await
+                .unwrap()
+    }
+
+    fn block_engine_endpoint(block_engine_url: &str) -> crate::proxy::Result<Endpoint> {
+        let mut backend_endpoint = Endpoint::from_shared(block_engine_url.to_string())
+            .map_err(|_| {
+                ProxyError::BlockEngineConnectionError(format!(
+                    "invalid block engine url value: {block_engine_url}"
+                ))
+            })?
+            .tcp_keepalive(Some(Duration::from_secs(60)));
+        if block_engine_url.starts_with("https") {
             backend_endpoint = backend_endpoint
                 .tls_config(tonic::transport::ClientTlsConfig::new())
                 .map_err(|_| {
@@ -227,11 +568,31 @@ impl BlockEngineStage {
                     )
                 })?;
         }
+        Ok(backend_endpoint)
+    }
 
-        debug!(
-            "connecting to auth: {}",
-            local_block_engine_config.block_engine_url
-        );
+    #[allow(clippy::too_many_arguments)]
+    async fn connect_auth_and_stream(
+        block_engine_url: &str,
+        local_block_engine_config: &BlockEngineConfig,
+        global_block_engine_config: &Arc<Mutex<BlockEngineConfig>>,
+        cluster_info: &Arc<ClusterInfo>,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
+        packet_tx: &Sender<PacketBatch>,
+        banking_packet_sender: &BankingPacketSender,
+        packet_relay: &Arc<PacketRelay>,
+        exit: &Arc<AtomicBool>,
+        connection_timeout: &Duration,
+    ) -> crate::proxy::Result<()> {
+        // Get a copy of configs here in case they have changed at runtime
+        let keypair = cluster_info.keypair().clone();
+
+        let backend_endpoint = Self::block_engine_endpoint(block_engine_url)?;
+
+        debug!("connecting to auth: {}", block_engine_url);
+        let auth_start = Instant::now();
         let auth_channel = timeout(*connection_timeout, backend_endpoint.connect())
             .await
             .map_err(|_| ProxyError::AuthenticationConnectionTimeout)?
@@ -246,37 +607,44 @@ impl BlockEngineStage {
         )
         .await
         .map_err(|_| ProxyError::AuthenticationTimeout)??;
+        let auth_latency = auth_start.elapsed();
 
         datapoint_info!(
             "block_engine_stage-tokens_generated",
-            ("url", local_block_engine_config.block_engine_url, String),
+            ("url", block_engine_url, String),
             ("count", 1, i64),
         );
 
-        debug!(
-            "connecting to block engine: {}",
-            local_block_engine_config.block_engine_url
-        );
+        debug!("connecting to block engine: {}", block_engine_url);
         let block_engine_channel = timeout(*connection_timeout, backend_endpoint.connect())
             .await
             .map_err(|_| ProxyError::BlockEngineConnectionTimeout)?
             .map_err(|e| ProxyError::BlockEngineConnectionError(e.to_string()))?;
 
         let access_token = Arc::new(Mutex::new(access_token));
+        let block_engine_relayer_client = BlockEngineRelayerClient::with_interceptor(
+            block_engine_channel.clone(),
+            AuthInterceptor::new(access_token.clone()),
+        );
         let block_engine_client = BlockEngineValidatorClient::with_interceptor(
             block_engine_channel,
             AuthInterceptor::new(access_token.clone()),
         );
 
         Self::start_consuming_block_engine_bundles_and_packets(
+            block_engine_url,
+            auth_latency,
             bundle_tx,
+            bundle_id_cache,
+            bundle_outcome_senders,
             block_engine_client,
+            block_engine_relayer_client,
             packet_tx,
             local_block_engine_config,
             global_block_engine_config,
             banking_packet_sender,
+            packet_relay,
             exit,
-            block_builder_fee_info,
             auth_client,
             access_token,
             refresh_token,
@@ -289,14 +657,19 @@ impl BlockEngineStage {
 
     #[allow(clippy::too_many_arguments)]
     async fn start_consuming_block_engine_bundles_and_packets(
-        bundle_tx: &Sender<Vec<PacketBundle>>,
+        block_engine_url: &str,
+        auth_latency: Duration,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
         mut client: BlockEngineValidatorClient<InterceptedService<Channel, AuthInterceptor>>,
+        relayer_client: BlockEngineRelayerClient<InterceptedService<Channel, AuthInterceptor>>,
         packet_tx: &Sender<PacketBatch>,
         local_config: &BlockEngineConfig, // local copy of config with current connections
         global_config: &Arc<Mutex<BlockEngineConfig>>, // guarded reference for detecting run-time updates
         banking_packet_sender: &BankingPacketSender,
+        packet_relay: &Arc<PacketRelay>,
         exit: &Arc<AtomicBool>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
         auth_client: AuthServiceClient<Channel>,
         access_token: Arc<Mutex<Token>>,
         refresh_token: Token,
@@ -322,7 +695,7 @@ impl BlockEngineStage {
         .map_err(|e| ProxyError::MethodError(e.to_string()))?
         .into_inner();
 
-        let block_builder_info = timeout(
+        let block_builder_fee_info = timeout(
             *connection_timeout,
             client.get_block_builder_fee_info(BlockBuilderFeeInfoRequest {}),
         )
@@ -330,24 +703,39 @@ impl BlockEngineStage {
         .map_err(|_| ProxyError::MethodTimeout("get_block_builder_fee_info".to_string()))?
         .map_err(|e| ProxyError::MethodError(e.to_string()))?
         .into_inner();
+        let block_builder_fee_info =
+            Self::parse_block_builder_fee_info(block_engine_url, block_builder_fee_info);
 
-        {
-            let block_builder_fee_info = block_builder_fee_info.clone();
-            task::spawn_blocking(move || {
-                let mut bb_fee = block_builder_fee_info.lock().unwrap();
-                bb_fee.block_builder_commission = block_builder_info.commission;
-                if let Ok(pk) = Pubkey::from_str(&block_builder_info.pubkey) {
-                    bb_fee.block_builder = pk
-                }
-            })
-            .await
-            .unwrap();
-        }
+        // Outcomes of the bundles this block engine sent are reported back and packets from the
+        // tpu it is interested in are relayed until disconnecting
+        let (bundle_outcome_sender, bundle_outcome_receiver) = mpsc::unbounded_channel();
+        Self::start_reporting_bundle_outcomes(
+            block_engine_url,
+            client.clone(),
+            bundle_outcome_receiver,
+        );
+        bundle_outcome_senders
+            .lock()
+            .unwrap()
+            .insert(block_engine_url.to_string(), bundle_outcome_sender);
+        let _connection = BlockEngineConnection {
+            block_engine_url,
+            bundle_outcome_senders,
+            packet_relay,
+            relay_packets_task: Self::start_relaying_packets(
+                block_engine_url,
+                relayer_client,
+                packet_relay.clone(),
+            ),
+        };
 
         Self::consume_bundle_and_packet_stream(
+            block_engine_url,
+            auth_latency,
             client,
             (subscribe_bundles_stream, subscribe_packets_stream),
             bundle_tx,
+            bundle_id_cache,
             packet_tx,
             local_config,
             global_config,
@@ -364,20 +752,240 @@ impl BlockEngineStage {
         .await
     }
 
+    /// Returns the fee info the block engine's bundles are tipped with, or `None` if it sent an
+    /// invalid block builder, in which case the leader keeps the whole tip.
+    fn parse_block_builder_fee_info(
+        block_engine_url: &str,
+        block_builder_fee_info: BlockBuilderFeeInfoResponse,
+    ) -> Option<BlockBuilderFeeInfo> {
+        match Pubkey::from_str(&block_builder_fee_info.pubkey) {
+            Ok(block_builder) => Some(BlockBuilderFeeInfo {
+                block_builder,
+                block_builder_commission: block_builder_fee_info.commission,
+            }),
+            Err(e) => {
+                warn!(
+                    "invalid block builder {} from block engine {block_engine_url}: {e}",
+                    block_builder_fee_info.pubkey
+                );
+                None
+            }
+        }
+    }
+
+    /// Streams the outcomes sent to `bundle_outcome_receiver` to the block engine until the sender
+    /// is dropped. A block engine that doesn't support the rpc only disables reporting.
+    fn start_reporting_bundle_outcomes(
+        block_engine_url: &str,
+        mut client: BlockEngineValidatorClient<InterceptedService<Channel, AuthInterceptor>>,
+        bundle_outcome_receiver: mpsc::UnboundedReceiver<BundleOutcomes>,
+    ) -> task::JoinHandle<()> {
+        let block_engine_url = block_engine_url.to_string();
+        task::spawn(async move {
+            let bundle_outcomes =
+                stream::unfold(bundle_outcome_receiver, |mut receiver| async move {
+                    receiver
+                        .recv()
+                        .await
+                        .map(|bundle_outcomes| (bundle_outcomes, receiver))
+                });
+            if let Err(e) = client.report_bundle_outcomes(bundle_outcomes).await {
+                warn!("not reporting bundle outcomes to block engine {block_engine_url}: {e}");
+            }
+        })
+    }
+
+    /// Keeps the block engine's accounts and programs of interest in `packet_relay` and streams the
+    /// packets relayed to it. A block engine that doesn't support relayed packets only disables
+    /// relaying.
+    fn start_relaying_packets(
+        block_engine_url: &str,
+        mut client: BlockEngineRelayerClient<InterceptedService<Channel, AuthInterceptor>>,
+        packet_relay: Arc<PacketRelay>,
+    ) -> task::JoinHandle<()> {
+        let block_engine_url = block_engine_url.to_string();
+        task::spawn(async move {
+            if let Err(e) = Self::relay_packets(&block_engine_url, &mut client, &packet_relay).await
+            {
+                warn!("not relaying packets to block engine {block_engine_url}: {e}");
+            }
+            packet_relay.unregister(&block_engine_url);
+        })
+    }
+
+    async fn relay_packets(
+        block_engine_url: &str,
+        client: &mut BlockEngineRelayerClient<InterceptedService<Channel, AuthInterceptor>>,
+        packet_relay: &PacketRelay,
+    ) -> crate::proxy::Result<()> {
+        let mut accounts_of_interest_stream = client
+            .subscribe_accounts_of_interest(AccountsOfInterestRequest {})
+            .await?
+            .into_inner();
+        let mut programs_of_interest_stream = client
+            .subscribe_programs_of_interest(ProgramsOfInterestRequest {})
+            .await?
+            .into_inner();
+
+        let (packet_batch_update_sender, packet_batch_update_receiver) =
+            mpsc::channel(PACKET_BATCH_UPDATE_CHANNEL_CAPACITY);
+        let packet_batch_updates =
+            stream::unfold(packet_batch_update_receiver, |mut receiver| async move {
+                receiver
+                    .recv()
+                    .await
+                    .map(|packet_batch_update| (packet_batch_update, receiver))
+            });
+        let mut heartbeat_stream = client
+            .start_expiring_packet_stream(packet_batch_updates)
+            .await?
+            .into_inner();
+        packet_relay.register(block_engine_url, packet_batch_update_sender.clone());
+
+        let mut heartbeat_tick = interval(RELAYED_PACKET_HEARTBEAT_INTERVAL);
+        let mut heartbeat_count: u64 = 0;
+        loop {
+            tokio::select! {
+                maybe_update = accounts_of_interest_stream.message() => {
+                    let update = maybe_update?.ok_or(ProxyError::GrpcStreamDisconnected)?;
+                    packet_relay.update_interest(block_engine_url, |interest| interest.set_accounts(&update.accounts));
+                }
+                maybe_update = programs_of_interest_stream.message() => {
+                    let update = maybe_update?.ok_or(ProxyError::GrpcStreamDisconnected)?;
+                    packet_relay.update_interest(block_engine_url, |interest| interest.set_programs(&update.programs));
+                }
+                maybe_heartbeat = heartbeat_stream.message() => {
+                    maybe_heartbeat?.ok_or(ProxyError::GrpcStreamDisconnected)?;
+                }
+                _ = heartbeat_tick.tick() => {
+                    saturating_add_assign!(heartbeat_count, 1);
+                    // A full stream has packets queued, which keep it alive without the heartbeat
+                    if let Err(TrySendError::Closed(_)) = packet_batch_update_sender.try_send(PacketBatchUpdate {
+                        msg: Some(Msg::Heartbeat(Heartbeat { count: heartbeat_count })),
+                    }) {
+                        return Err(ProxyError::GrpcStreamDisconnected);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forwards the outcome of each bundle executed by the bundle stage to the block engine that
+    /// sent it.
+    fn start_bundle_result_thread(
+        bundle_result_receiver: BundleResultReceiver,
+        bundle_id_cache: Arc<BundleIdCache>,
+        bundle_outcome_senders: Arc<BundleOutcomeSenders>,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("solBlkEngResult".to_string())
+            .spawn(move || {
+                while !exit.load(Ordering::Relaxed) {
+                    match bundle_result_receiver.recv_timeout(BUNDLE_RESULT_RECEIVE_TIMEOUT) {
+                        Ok(bundle_results) => Self::forward_bundle_results(
+                            bundle_results,
+                            &bundle_id_cache,
+                            &bundle_outcome_senders,
+                        ),
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+            .unwrap()
+    }
+
+    fn forward_bundle_results(
+        bundle_results: Vec<BundleResultEvent>,
+        bundle_id_cache: &BundleIdCache,
+        bundle_outcome_senders: &BundleOutcomeSenders,
+    ) {
+        let mut bundle_outcomes: HashMap<String, Vec<BundleOutcome>> = HashMap::default();
+        {
+            let bundle_id_cache = bundle_id_cache.lock().unwrap();
+            for bundle_result in bundle_results {
+                // Bundles evicted from the cache can't be traced back to their block engine
+                if let Some(block_engine_url) = bundle_id_cache.peek(&bundle_result.bundle_id) {
+                    bundle_outcomes
+                        .entry(block_engine_url.clone())
+                        .or_default()
+                        .push(Self::bundle_outcome(bundle_result));
+                }
+            }
+        }
+
+        let bundle_outcome_senders = bundle_outcome_senders.lock().unwrap();
+        for (block_engine_url, outcomes) in bundle_outcomes {
+            // Outcomes for a block engine that has since disconnected are dropped
+            if let Some(bundle_outcome_sender) = bundle_outcome_senders.get(&block_engine_url) {
+                let _ = bundle_outcome_sender.send(BundleOutcomes { outcomes });
+            }
+        }
+    }
+
+    fn bundle_outcome(bundle_result: BundleResultEvent) -> BundleOutcome {
+        let BundleResultEvent {
+            bundle_id,
+            slot,
+            result,
+        } = bundle_result;
+        let (reason, msg) = match result {
+            BundleResult::Executed(Ok(())) => (BundleOutcomeReason::Landed, None),
+            BundleResult::Executed(Err(e)) => {
+                let reason = match &e {
+                    BundleExecutionError::LockError => BundleOutcomeReason::LockError,
+                    BundleExecutionError::ExceedsCostModel => BundleOutcomeReason::ExceedsCostModel,
+                    BundleExecutionError::FrontRun => BundleOutcomeReason::FrontRun,
+                    BundleExecutionError::TipError(_) => BundleOutcomeReason::TipError,
+                    BundleExecutionError::TransactionFailure(
+                        LoadAndExecuteBundleError::ProcessingTimeExceeded(_),
+                    ) => BundleOutcomeReason::ProcessingTimeExceeded,
+                    BundleExecutionError::TransactionFailure(_) => {
+                        BundleOutcomeReason::TransactionFailure
+                    }
+                    BundleExecutionError::BankProcessingTimeLimitReached => {
+                        BundleOutcomeReason::BankProcessingTimeLimitReached
+                    }
+                    BundleExecutionError::PohRecordError(_) => BundleOutcomeReason::PohRecordError,
+                };
+                (reason, Some(e.to_string()))
+            }
+            BundleResult::Dropped(BundleDropReason::Invalid(e)) => {
+                (BundleOutcomeReason::Invalid, Some(e))
+            }
+            BundleResult::Dropped(BundleDropReason::Evicted) => {
+                (BundleOutcomeReason::Evicted, None)
+            }
+            BundleResult::Dropped(BundleDropReason::NotLeader) => {
+                (BundleOutcomeReason::NotLeader, None)
+            }
+        };
+        BundleOutcome {
+            bundle_id,
+            slot,
+            reason: reason as i32,
+            msg,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn consume_bundle_and_packet_stream(
+        block_engine_url: &str,
+        auth_latency: Duration,
         mut client: BlockEngineValidatorClient<InterceptedService<Channel, AuthInterceptor>>,
         (mut bundle_stream, mut packet_stream): (
             Streaming<block_engine::SubscribeBundlesResponse>,
             Streaming<block_engine::SubscribePacketsResponse>,
         ),
-        bundle_tx: &Sender<Vec<PacketBundle>>,
+        bundle_tx: &TracedBundleSender,
+        bundle_id_cache: &BundleIdCache,
         packet_tx: &Sender<PacketBatch>,
         local_config: &BlockEngineConfig, // local copy of config with current connections
         global_config: &Arc<Mutex<BlockEngineConfig>>, // guarded reference for detecting run-time updates
         banking_packet_sender: &BankingPacketSender,
         exit: &Arc<AtomicBool>,
-        block_builder_fee_info: &Arc<Mutex<BlockBuilderFeeInfo>>,
+        mut block_builder_fee_info: Option<BlockBuilderFeeInfo>,
         mut auth_client: AuthServiceClient<Channel>,
         access_token: Arc<Mutex<Token>>,
         mut refresh_token: Token,
@@ -391,11 +999,11 @@ impl BlockEngineStage {
 
         let mut num_full_refreshes: u64 = 1;
         let mut num_refresh_access_token: u64 = 0;
-        let mut block_engine_stats = BlockEngineStageStats::default();
+        let mut block_engine_stats = BlockEngineStageStats::new(block_engine_url, auth_latency);
         let mut metrics_and_auth_tick = interval(METRICS_TICK);
         let mut maintenance_tick = interval(MAINTENANCE_TICK);
 
-        info!("connected to packet and bundle stream");
+        info!("connected to packet and bundle stream: {block_engine_url}");
 
         while !exit.load(Ordering::Relaxed) {
             tokio::select! {
@@ -404,21 +1012,17 @@ impl BlockEngineStage {
                     Self::handle_block_engine_packets(resp, packet_tx, banking_packet_sender, local_config.trust_packets, &mut block_engine_stats)?;
                 }
                 maybe_bundles = bundle_stream.message() => {
-                    Self::handle_block_engine_maybe_bundles(maybe_bundles, bundle_tx, &mut block_engine_stats)?;
+                    Self::handle_block_engine_maybe_bundles(maybe_bundles, block_engine_url, block_builder_fee_info, bundle_tx, local_config.trust_packets, bundle_id_cache, &mut block_engine_stats)?;
                 }
                 _ = metrics_and_auth_tick.tick() => {
                     block_engine_stats.report();
-                    block_engine_stats = BlockEngineStageStats::default();
 
                     if cluster_info.id() != keypair.pubkey() {
                         return Err(ProxyError::AuthenticationConnectionError("validator identity changed".to_string()));
                     }
 
-                    let global_config = global_config.clone();
-                    if *local_config != task::spawn_blocking(move || global_config.lock().unwrap().clone())
-                        .await
-                        .unwrap() {
-                        return Err(ProxyError::AuthenticationConnectionError("block engine config changed".to_string()));
+                    if Self::block_engine_config_changed(local_config, global_config).await {
+                        return Err(ProxyError::BlockEngineConfigChanged);
                     }
 
                     let (maybe_new_access, maybe_new_refresh) = maybe_refresh_auth_tokens(&mut auth_client,
@@ -433,7 +1037,7 @@ impl BlockEngineStage {
                         num_refresh_access_token += 1;
                         datapoint_info!(
                             "block_engine_stage-refresh_access_token",
-                            ("url", &local_config.block_engine_url, String),
+                            ("url", block_engine_url, String),
                             ("count", num_refresh_access_token, i64),
                         );
 
@@ -446,14 +1050,14 @@ impl BlockEngineStage {
                         num_full_refreshes += 1;
                         datapoint_info!(
                             "block_engine_stage-tokens_generated",
-                            ("url", &local_config.block_engine_url, String),
+                            ("url", block_engine_url, String),
                             ("count", num_full_refreshes, i64),
                         );
                         refresh_token = new_token;
                     }
                 }
                 _ = maintenance_tick.tick() => {
-                    let block_builder_info = timeout(
+                    let new_block_builder_fee_info = timeout(
                         *connection_timeout,
                         client.get_block_builder_fee_info(BlockBuilderFeeInfoRequest{})
                     )
@@ -461,17 +1065,7 @@ impl BlockEngineStage {
                     .map_err(|_| ProxyError::MethodTimeout("get_block_builder_fee_info".to_string()))?
                     .map_err(|e| ProxyError::MethodError(e.to_string()))?
                     .into_inner();
-
-                    let block_builder_fee_info = block_builder_fee_info.clone();
-                    task::spawn_blocking(move || {
-                        let mut bb_fee = block_builder_fee_info.lock().unwrap();
-                        bb_fee.block_builder_commission = block_builder_info.commission;
-                        if let Ok(pk) = Pubkey::from_str(&block_builder_info.pubkey) {
-                            bb_fee.block_builder = pk
-                        }
-                    })
-                    .await
-                    .unwrap();
+                    block_builder_fee_info = Self::parse_block_builder_fee_info(block_engine_url, new_block_builder_fee_info);
                 }
             }
         }
@@ -481,27 +1075,36 @@ impl BlockEngineStage {
 
     fn handle_block_engine_maybe_bundles(
         maybe_bundles_response: Result<Option<block_engine::SubscribeBundlesResponse>, Status>,
-        bundle_sender: &Sender<Vec<PacketBundle>>,
+        block_engine_url: &str,
+        block_builder_fee_info: Option<BlockBuilderFeeInfo>,
+        bundle_sender: &TracedBundleSender,
+        trust_packets: bool,
+        bundle_id_cache: &BundleIdCache,
         block_engine_stats: &mut BlockEngineStageStats,
     ) -> crate::proxy::Result<()> {
         let bundles_response = maybe_bundles_response?.ok_or(ProxyError::GrpcStreamDisconnected)?;
+        let mut bundle_id_cache = bundle_id_cache.lock().unwrap();
         let bundles: Vec<PacketBundle> = bundles_response
             .bundles
             .into_iter()
             .filter_map(|bundle| {
+                let packets = bundle.bundle?.packets;
+                // Another block engine already sent this bundle
+                if bundle_id_cache.contains(&bundle.uuid) {
+                    saturating_add_assign!(block_engine_stats.num_duplicate_bundles, 1);
+                    return None;
+                }
+                bundle_id_cache.put(bundle.uuid.clone(), block_engine_url.to_string());
                 Some(PacketBundle {
                     batch: PacketBatch::new(
-                        bundle
-                            .bundle?
-                            .packets
-                            .into_iter()
-                            .map(proto_packet_to_packet)
-                            .collect(),
+                        packets.into_iter().map(proto_packet_to_packet).collect(),
                     ),
                     bundle_id: bundle.uuid,
+                    block_builder_fee_info,
                 })
             })
             .collect();
+        drop(bundle_id_cache);
 
         saturating_add_assign!(block_engine_stats.num_bundles, bundles.len() as u64);
         saturating_add_assign!(
@@ -511,7 +1114,7 @@ impl BlockEngineStage {
 
         // NOTE: bundles are sanitized in bundle_sanitizer module
         bundle_sender
-            .send(bundles)
+            .send(bundles, trust_packets)
             .map_err(|_| ProxyError::PacketForwardError)
     }
 
@@ -555,17 +1158,708 @@ impl BlockEngineStage {
     }
 
     pub fn is_valid_block_engine_config(config: &BlockEngineConfig) -> bool {
-        if config.block_engine_url.is_empty() {
+        if config.block_engine_urls.is_empty() {
             warn!("can't connect to block_engine. missing block_engine_url.");
             return false;
         }
-        if let Err(e) = Endpoint::from_str(&config.block_engine_url) {
-            error!(
-                "can't connect to block engine. error creating block engine endpoint - {}",
-                e.to_string()
-            );
-            return false;
+        for block_engine_url in &config.block_engine_urls {
+            if let Err(e) = Endpoint::from_str(block_engine_url) {
+                error!(
+                    "can't connect to block engine {}. error creating block engine endpoint - {}",
+                    block_engine_url,
+                    e.to_string()
+                );
+                return false;
+            }
         }
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::banking_trace::BankingTracer,
+        chrono::Utc,
+        futures::{Stream, StreamExt},
+        jito_protos::proto::{
+            auth::{
+                auth_service_server::{AuthService, AuthServiceServer},
+                GenerateAuthChallengeRequest, GenerateAuthChallengeResponse,
+                GenerateAuthTokensRequest, GenerateAuthTokensResponse, RefreshAccessTokenRequest,
+                RefreshAccessTokenResponse,
+            },
+            block_engine::{
+                block_engine_relayer_server::{BlockEngineRelayer, BlockEngineRelayerServer},
+                block_engine_validator_server::{BlockEngineValidator, BlockEngineValidatorServer},
+                AccountsOfInterestUpdate, ExpiringPacketBatch, ProgramsOfInterestUpdate,
+                ReportBundleOutcomesResponse, StartExpiringPacketStreamResponse,
+                SubscribeBundlesRequest, SubscribeBundlesResponse, SubscribePacketsRequest,
+                SubscribePacketsResponse,
+            },
+            bundle::{Bundle, BundleUuid},
+            packet as proto_packet,
+        },
+        solana_gossip::contact_info::ContactInfo,
+        solana_sdk::{
+            hash::Hash, message::SimpleAddressLoader, packet::Packet, system_transaction,
+        },
+        solana_streamer::socket::SocketAddrSpace,
+        std::{net::TcpListener as StdTcpListener, pin::Pin},
+        tokio::net::TcpListener,
+        tonic::{transport::Server, Request, Response},
+    };
+
+    type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+    /// Block engine that streams a fixed set of bundles and hands bundle outcomes to the test.
+    struct MockBlockEngine {
+        block_builder_fee_info: BlockBuilderFeeInfoResponse,
+        bundles: Vec<BundleUuid>,
+        bundle_outcome_sender: mpsc::UnboundedSender<BundleOutcomes>,
+    }
+
+    impl MockBlockEngine {
+        fn new(bundle_outcome_sender: mpsc::UnboundedSender<BundleOutcomes>) -> Self {
+            Self {
+                block_builder_fee_info: BlockBuilderFeeInfoResponse::default(),
+                bundles: vec![],
+                bundle_outcome_sender,
+            }
+        }
+    }
+
+    #[tonic::async_trait]
+    impl BlockEngineValidator for MockBlockEngine {
+        type SubscribePacketsStream = ResponseStream<SubscribePacketsResponse>;
+        type SubscribeBundlesStream = ResponseStream<SubscribeBundlesResponse>;
+
+        async fn subscribe_packets(
+            &self,
+            _request: Request<SubscribePacketsRequest>,
+        ) -> Result<Response<Self::SubscribePacketsStream>, Status> {
+            Ok(Response::new(Box::pin(stream::pending())))
+        }
+
+        async fn subscribe_bundles(
+            &self,
+            _request: Request<SubscribeBundlesRequest>,
+        ) -> Result<Response<Self::SubscribeBundlesStream>, Status> {
+            let response = SubscribeBundlesResponse {
+                bundles: self.bundles.clone(),
+            };
+            Ok(Response::new(Box::pin(
+                stream::iter([Ok::<_, Status>(response)]).chain(stream::pending()),
+            )))
+        }
+
+        async fn get_block_builder_fee_info(
+            &self,
+            _request: Request<BlockBuilderFeeInfoRequest>,
+        ) -> Result<Response<BlockBuilderFeeInfoResponse>, Status> {
+            Ok(Response::new(self.block_builder_fee_info.clone()))
+        }
+
+        async fn report_bundle_outcomes(
+            &self,
+            request: Request<Streaming<BundleOutcomes>>,
+        ) -> Result<Response<ReportBundleOutcomesResponse>, Status> {
+            let mut bundle_outcomes = request.into_inner();
+            while let Some(bundle_outcomes) = bundle_outcomes.message().await? {
+                self.bundle_outcome_sender.send(bundle_outcomes).unwrap();
+            }
+            Ok(Response::new(ReportBundleOutcomesResponse {}))
+        }
+    }
+
+    /// Auth service that hands out long lived tokens, or denies every validator.
+    struct MockAuthService {
+        permission_denied: bool,
+    }
+
+    #[tonic::async_trait]
+    impl AuthService for MockAuthService {
+        async fn generate_auth_challenge(
+            &self,
+            _request: Request<GenerateAuthChallengeRequest>,
+        ) -> Result<Response<GenerateAuthChallengeResponse>, Status> {
+            if self.permission_denied {
+                return Err(Status::permission_denied("not on the leader schedule"));
+            }
+            Ok(Response::new(GenerateAuthChallengeResponse {
+                challenge: "challenge".to_string(),
+            }))
+        }
+
+        async fn generate_auth_tokens(
+            &self,
+            _request: Request<GenerateAuthTokensRequest>,
+        ) -> Result<Response<GenerateAuthTokensResponse>, Status> {
+            let token = || Token {
+                value: "token".to_string(),
+                expires_at_utc: Some(prost_types::Timestamp {
+                    seconds: Utc::now().timestamp() + 3600,
+                    nanos: 0,
+                }),
+            };
+            Ok(Response::new(GenerateAuthTokensResponse {
+                access_token: Some(token()),
+                refresh_token: Some(token()),
+            }))
+        }
+
+        async fn refresh_access_token(
+            &self,
+            _request: Request<RefreshAccessTokenRequest>,
+        ) -> Result<Response<RefreshAccessTokenResponse>, Status> {
+            Err(Status::unimplemented("refresh_access_token"))
+        }
+    }
+
+    /// Block engine relayer service that streams a fixed interest and hands relayed packets to the
+    /// test.
+    struct MockBlockEngineRelayer {
+        accounts_of_interest: Vec<String>,
+        programs_of_interest: Vec<String>,
+        packet_batch_update_sender: mpsc::UnboundedSender<PacketBatchUpdate>,
+    }
+
+    #[tonic::async_trait]
+    impl BlockEngineRelayer for MockBlockEngineRelayer {
+        type SubscribeAccountsOfInterestStream = ResponseStream<AccountsOfInterestUpdate>;
+        type SubscribeProgramsOfInterestStream = ResponseStream<ProgramsOfInterestUpdate>;
+        type StartExpiringPacketStreamStream = ResponseStream<StartExpiringPacketStreamResponse>;
+
+        async fn subscribe_accounts_of_interest(
+            &self,
+            _request: Request<AccountsOfInterestRequest>,
+        ) -> Result<Response<Self::SubscribeAccountsOfInterestStream>, Status> {
+            let update = AccountsOfInterestUpdate {
+                accounts: self.accounts_of_interest.clone(),
+            };
+            Ok(Response::new(Box::pin(
+                stream::iter([Ok::<_, Status>(update)]).chain(stream::pending()),
+            )))
+        }
+
+        async fn subscribe_programs_of_interest(
+            &self,
+            _request: Request<ProgramsOfInterestRequest>,
+        ) -> Result<Response<Self::SubscribeProgramsOfInterestStream>, Status> {
+            let update = ProgramsOfInterestUpdate {
+                programs: self.programs_of_interest.clone(),
+            };
+            Ok(Response::new(Box::pin(
+                stream::iter([Ok::<_, Status>(update)]).chain(stream::pending()),
+            )))
+        }
+
+        async fn start_expiring_packet_stream(
+            &self,
+            request: Request<Streaming<PacketBatchUpdate>>,
+        ) -> Result<Response<Self::StartExpiringPacketStreamStream>, Status> {
+            let mut packet_batch_updates = request.into_inner();
+            let packet_batch_update_sender = self.packet_batch_update_sender.clone();
+            task::spawn(async move {
+                while let Ok(Some(packet_batch_update)) = packet_batch_updates.message().await {
+                    let _ = packet_batch_update_sender.send(packet_batch_update);
+                }
+            });
+            Ok(Response::new(Box::pin(stream::pending::<
+                Result<StartExpiringPacketStreamResponse, Status>,
+            >())))
+        }
+    }
+
+    /// Serves a [MockBlockEngine], a [MockBlockEngineRelayer] and a [MockAuthService] on a local
+    /// port and returns its url.
+    async fn start_mock_block_engine(
+        block_engine: MockBlockEngine,
+        block_engine_relayer: MockBlockEngineRelayer,
+        auth_service: MockAuthService,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let block_engine_url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = stream::unfold(listener, |listener| async move {
+            let connection = listener.accept().await.map(|(connection, _)| connection);
+            Some((connection, listener))
+        });
+        task::spawn(
+            Server::builder()
+                .add_service(BlockEngineValidatorServer::new(block_engine))
+                .add_service(BlockEngineRelayerServer::new(block_engine_relayer))
+                .add_service(AuthServiceServer::new(auth_service))
+                .serve_with_incoming(incoming),
+        );
+        block_engine_url
+    }
+
+    async fn block_engine_channel(block_engine_url: &str) -> Channel {
+        BlockEngineStage::block_engine_endpoint(block_engine_url)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap()
+    }
+
+    fn auth_interceptor() -> AuthInterceptor {
+        AuthInterceptor::new(Arc::new(Mutex::new(Token::default())))
+    }
+
+    fn bundle_uuid(uuid: &str) -> BundleUuid {
+        BundleUuid {
+            bundle: Some(Bundle {
+                header: None,
+                packets: vec![proto_packet::Packet {
+                    data: vec![1, 2, 3],
+                    meta: None,
+                }],
+            }),
+            uuid: uuid.to_string(),
+        }
+    }
+
+    fn block_builder_fee_info(block_builder_commission: u64) -> BlockBuilderFeeInfo {
+        BlockBuilderFeeInfo {
+            block_builder: Pubkey::new_unique(),
+            block_builder_commission,
+        }
+    }
+
+    #[test]
+    fn test_dedup_bundles_across_block_engines() {
+        let (bundle_sender, bundle_receiver) =
+            BankingTracer::new_disabled().create_channel_bundle();
+        let bundle_id_cache = Mutex::new(LruCache::new(BUNDLE_ID_CACHE_CAPACITY));
+        let mut block_engine_stats = BlockEngineStageStats::default();
+        let first_fee_info = block_builder_fee_info(5);
+        let second_fee_info = block_builder_fee_info(10);
+
+        // The first block engine sends the "missing" bundle without its packets
+        let missing_bundle = BundleUuid {
+            bundle: None,
+            uuid: "missing".to_string(),
+        };
+        for (block_engine_url, block_builder_fee_info, bundles) in [
+            (
+                "http://first:1",
+                first_fee_info,
+                vec![bundle_uuid("shared"), bundle_uuid("first"), missing_bundle],
+            ),
+            (
+                "http://second:1",
+                second_fee_info,
+                vec![
+                    bundle_uuid("shared"),
+                    bundle_uuid("second"),
+                    bundle_uuid("missing"),
+                ],
+            ),
+        ] {
+            BlockEngineStage::handle_block_engine_maybe_bundles(
+                Ok(Some(SubscribeBundlesResponse { bundles })),
+                block_engine_url,
+                Some(block_builder_fee_info),
+                &bundle_sender,
+                false,
+                &bundle_id_cache,
+                &mut block_engine_stats,
+            )
+            .unwrap();
+        }
+
+        // Each bundle is forwarded once, with the fee info of the block engine that sent it first
+        // with its packets
+        let received: Vec<_> = bundle_receiver
+            .try_iter()
+            .flatten()
+            .map(|bundle| (bundle.bundle_id, bundle.block_builder_fee_info))
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                ("shared".to_string(), Some(first_fee_info)),
+                ("first".to_string(), Some(first_fee_info)),
+                ("second".to_string(), Some(second_fee_info)),
+                ("missing".to_string(), Some(second_fee_info)),
+            ]
+        );
+        assert_eq!(block_engine_stats.num_bundles, 4);
+        assert_eq!(block_engine_stats.num_duplicate_bundles, 1);
+        // and its outcome is reported to that block engine
+        let bundle_id_cache = bundle_id_cache.lock().unwrap();
+        assert_eq!(bundle_id_cache.peek("shared").unwrap(), "http://first:1");
+        assert_eq!(bundle_id_cache.peek("missing").unwrap(), "http://second:1");
+    }
+
+    #[tokio::test]
+    async fn test_rank_block_engines_by_latency() {
+        let (bundle_outcome_sender, _bundle_outcome_receiver) = mpsc::unbounded_channel();
+        let (packet_batch_update_sender, _packet_batch_update_receiver) = mpsc::unbounded_channel();
+        let reachable_url = start_mock_block_engine(
+            MockBlockEngine::new(bundle_outcome_sender),
+            MockBlockEngineRelayer {
+                accounts_of_interest: vec![],
+                programs_of_interest: vec![],
+                packet_batch_update_sender,
+            },
+            MockAuthService {
+                permission_denied: false,
+            },
+        )
+        .await;
+        let unreachable_url = {
+            let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let block_engine_urls = vec![
+            unreachable_url,
+            "not a url".to_string(),
+            reachable_url.clone(),
+        ];
+
+        let ranked_block_engines = BlockEngineStage::rank_block_engines_by_latency(
+            &block_engine_urls,
+            &Duration::from_secs(10),
+        )
+        .await;
+        assert_eq!(ranked_block_engines.len(), 1);
+        assert_eq!(ranked_block_engines[0].0, reachable_url);
+    }
+
+    #[test]
+    fn test_should_fail_back() {
+        let ranked_block_engines = [
+            ("http://fast:1", Duration::from_millis(2)),
+            ("http://close:1", Duration::from_millis(4)),
+            ("http://slow:1", Duration::from_millis(80)),
+        ];
+        assert!(!BlockEngineStage::should_fail_back(
+            &ranked_block_engines,
+            "http://fast:1"
+        ));
+        // not worth reconnecting for a few milliseconds
+        assert!(!BlockEngineStage::should_fail_back(
+            &ranked_block_engines,
+            "http://close:1"
+        ));
+        assert!(BlockEngineStage::should_fail_back(
+            &ranked_block_engines,
+            "http://slow:1"
+        ));
+        // left to fail over by itself
+        assert!(!BlockEngineStage::should_fail_back(
+            &ranked_block_engines,
+            "http://unreachable:1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fail_over_to_next_block_engine() {
+        let start_block_engine = |block_engine: MockBlockEngine, permission_denied| {
+            let (packet_batch_update_sender, _packet_batch_update_receiver) =
+                mpsc::unbounded_channel();
+            start_mock_block_engine(
+                block_engine,
+                MockBlockEngineRelayer {
+                    accounts_of_interest: vec![],
+                    programs_of_interest: vec![],
+                    packet_batch_update_sender,
+                },
+                MockAuthService { permission_denied },
+            )
+        };
+        let (bundle_outcome_sender, _bundle_outcome_receiver) = mpsc::unbounded_channel();
+        let denying_url =
+            start_block_engine(MockBlockEngine::new(bundle_outcome_sender.clone()), true).await;
+        let block_builder = Pubkey::new_unique();
+        let serving_url = start_block_engine(
+            MockBlockEngine {
+                block_builder_fee_info: BlockBuilderFeeInfoResponse {
+                    pubkey: block_builder.to_string(),
+                    commission: 5,
+                },
+                bundles: vec![bundle_uuid("bundle")],
+                bundle_outcome_sender,
+            },
+            false,
+        )
+        .await;
+
+        let block_engine_config = BlockEngineConfig {
+            block_engine_urls: vec![denying_url, serving_url],
+            connect_all: false,
+            trust_packets: false,
+        };
+        let keypair = Arc::new(Keypair::new());
+        let cluster_info = Arc::new(ClusterInfo::new(
+            ContactInfo::new(keypair.pubkey(), 0, 0),
+            keypair,
+            SocketAddrSpace::Unspecified,
+        ));
+        let banking_tracer = BankingTracer::new_disabled();
+        let (bundle_sender, bundle_receiver) = banking_tracer.create_channel_bundle();
+        let (banking_packet_sender, _banking_packet_receiver) =
+            banking_tracer.create_channel_non_vote();
+        let exit = Arc::new(AtomicBool::new(false));
+
+        let stream_task = task::spawn({
+            let exit = exit.clone();
+            async move {
+                // The block engine denying the validator is tried first
+                let ranked_block_engines = block_engine_config
+                    .block_engine_urls
+                    .iter()
+                    .map(|block_engine_url| (block_engine_url.as_str(), Duration::ZERO))
+                    .collect();
+                BlockEngineStage::stream_with_failover(
+                    ranked_block_engines,
+                    &block_engine_config,
+                    &Arc::new(Mutex::new(block_engine_config.clone())),
+                    &cluster_info,
+                    &bundle_sender,
+                    &Mutex::new(LruCache::new(BUNDLE_ID_CACHE_CAPACITY)),
+                    &Mutex::default(),
+                    &crossbeam_channel::unbounded().0,
+                    &banking_packet_sender,
+                    &Arc::new(PacketRelay::default()),
+                    &exit,
+                    &Duration::from_secs(10),
+                    &AtomicU64::default(),
+                )
+                .await
+            }
+        });
+
+        let bundles =
+            task::spawn_blocking(move || bundle_receiver.recv_timeout(Duration::from_secs(10)))
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].bundle_id, "bundle");
+        assert_eq!(
+            bundles[0].block_builder_fee_info,
+            Some(BlockBuilderFeeInfo {
+                block_builder,
+                block_builder_commission: 5,
+            })
+        );
+
+        exit.store(true, Ordering::Relaxed);
+        timeout(Duration::from_secs(10), stream_task)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_report_bundle_outcomes() {
+        let (received_sender, mut received_receiver) = mpsc::unbounded_channel();
+        let (packet_batch_update_sender, _packet_batch_update_receiver) = mpsc::unbounded_channel();
+        let block_engine_url = start_mock_block_engine(
+            MockBlockEngine::new(received_sender),
+            MockBlockEngineRelayer {
+                accounts_of_interest: vec![],
+                programs_of_interest: vec![],
+                packet_batch_update_sender,
+            },
+            MockAuthService {
+                permission_denied: false,
+            },
+        )
+        .await;
+
+        let client = BlockEngineValidatorClient::with_interceptor(
+            block_engine_channel(&block_engine_url).await,
+            auth_interceptor(),
+        );
+        let (bundle_outcome_sender, bundle_outcome_receiver) = mpsc::unbounded_channel();
+        BlockEngineStage::start_reporting_bundle_outcomes(
+            &block_engine_url,
+            client,
+            bundle_outcome_receiver,
+        );
+        let bundle_outcome_senders = Mutex::new(HashMap::from([(
+            block_engine_url.clone(),
+            bundle_outcome_sender,
+        )]));
+
+        let bundle_id_cache = Mutex::new(LruCache::new(BUNDLE_ID_CACHE_CAPACITY));
+        {
+            let mut bundle_id_cache = bundle_id_cache.lock().unwrap();
+            bundle_id_cache.put("landed".to_string(), block_engine_url.clone());
+            bundle_id_cache.put("front_run".to_string(), block_engine_url.clone());
+            bundle_id_cache.put("evicted".to_string(), block_engine_url.clone());
+            bundle_id_cache.put("invalid".to_string(), block_engine_url.clone());
+            bundle_id_cache.put("other".to_string(), "http://other:1".to_string());
+        }
+
+        BlockEngineStage::forward_bundle_results(
+            vec![
+                BundleResultEvent {
+                    bundle_id: "landed".to_string(),
+                    slot: 5,
+                    result: BundleResult::Executed(Ok(())),
+                },
+                // sent by another block engine
+                BundleResultEvent {
+                    bundle_id: "other".to_string(),
+                    slot: 5,
+                    result: BundleResult::Executed(Ok(())),
+                },
+                BundleResultEvent {
+                    bundle_id: "front_run".to_string(),
+                    slot: 5,
+                    result: BundleResult::Executed(Err(BundleExecutionError::FrontRun)),
+                },
+                // not sent by any block engine
+                BundleResultEvent {
+                    bundle_id: "unknown".to_string(),
+                    slot: 5,
+                    result: BundleResult::Executed(Err(BundleExecutionError::LockError)),
+                },
+                BundleResultEvent {
+                    bundle_id: "evicted".to_string(),
+                    slot: 0,
+                    result: BundleResult::Dropped(BundleDropReason::Evicted),
+                },
+                BundleResultEvent {
+                    bundle_id: "invalid".to_string(),
+                    slot: 0,
+                    result: BundleResult::Dropped(BundleDropReason::Invalid(
+                        "SignatureVerificationFailure".to_string(),
+                    )),
+                },
+            ],
+            &bundle_id_cache,
+            &bundle_outcome_senders,
+        );
+
+        let received = timeout(Duration::from_secs(10), received_receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            received.outcomes,
+            vec![
+                BundleOutcome {
+                    bundle_id: "landed".to_string(),
+                    slot: 5,
+                    reason: BundleOutcomeReason::Landed as i32,
+                    msg: None,
+                },
+                BundleOutcome {
+                    bundle_id: "front_run".to_string(),
+                    slot: 5,
+                    reason: BundleOutcomeReason::FrontRun as i32,
+                    msg: Some(BundleExecutionError::FrontRun.to_string()),
+                },
+                BundleOutcome {
+                    bundle_id: "evicted".to_string(),
+                    slot: 0,
+                    reason: BundleOutcomeReason::Evicted as i32,
+                    msg: None,
+                },
+                BundleOutcome {
+                    bundle_id: "invalid".to_string(),
+                    slot: 0,
+                    reason: BundleOutcomeReason::Invalid as i32,
+                    msg: Some("SignatureVerificationFailure".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_relay_packets_of_interest() {
+        let (bundle_outcome_sender, _bundle_outcome_receiver) = mpsc::unbounded_channel();
+        let (packet_batch_update_sender, mut packet_batch_update_receiver) =
+            mpsc::unbounded_channel();
+        let hot_account = Pubkey::new_unique();
+        let block_engine_url = start_mock_block_engine(
+            MockBlockEngine::new(bundle_outcome_sender),
+            MockBlockEngineRelayer {
+                accounts_of_interest: vec![hot_account.to_string()],
+                programs_of_interest: vec![],
+                packet_batch_update_sender,
+            },
+            MockAuthService {
+                permission_denied: false,
+            },
+        )
+        .await;
+
+        let client = BlockEngineRelayerClient::with_interceptor(
+            block_engine_channel(&block_engine_url).await,
+            auth_interceptor(),
+        );
+        let packet_relay = Arc::new(PacketRelay::default());
+        let relay_packets_task = BlockEngineStage::start_relaying_packets(
+            &block_engine_url,
+            client,
+            packet_relay.clone(),
+        );
+
+        let payer = Keypair::new();
+        let hot_packet = Packet::from_data(
+            None,
+            system_transaction::transfer(&payer, &hot_account, 1, Hash::default()),
+        )
+        .unwrap();
+        let cold_packet = Packet::from_data(
+            None,
+            system_transaction::transfer(&payer, &Pubkey::new_unique(), 1, Hash::default()),
+        )
+        .unwrap();
+        let packet_batch = PacketBatch::new(vec![cold_packet, hot_packet.clone()]);
+
+        // relays once the accounts of interest arrive
+        timeout(Duration::from_secs(10), async {
+            while packet_relay
+                .relay(&packet_batch, SimpleAddressLoader::Disabled)
+                .num_relayed
+                == 0
+            {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let relayed_batch = timeout(Duration::from_secs(10), async {
+            loop {
+                match packet_batch_update_receiver.recv().await.unwrap().msg {
+                    Some(Msg::Batches(expiring_packet_batch)) => break expiring_packet_batch,
+                    Some(Msg::Heartbeat(_)) => {}
+                    None => panic!("empty packet batch update"),
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let ExpiringPacketBatch {
+            header,
+            batch,
+            expiry_ms,
+        } = relayed_batch;
+        assert!(header.unwrap().ts.is_some());
+        assert!(expiry_ms > 0);
+        let packets = batch.unwrap().packets;
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].data, hot_packet.data(..).unwrap());
+
+        // unregisters once the block engine stops relaying
+        relay_packets_task.abort();
+        let _ = relay_packets_task.await;
+        packet_relay.unregister(&block_engine_url);
+        assert_eq!(
+            packet_relay
+                .relay(&packet_batch, SimpleAddressLoader::Disabled)
+                .num_relayed,
+            0
+        );
+    }
+}
@@ -57,6 +57,8 @@ impl FetchStageManager {
     /// When receiving heartbeat while connected, and pending for > DISCONNECT_DELAY_SEC
     ///      Sets fetch_connected to false, pending_disconnect to false
     ///      Advertises TPU ports sent in heartbeat
+    /// When receiving heartbeat while disconnected with TPU ports other than the advertised ones
+    ///      Advertises TPU ports sent in heartbeat, following the relayer stage's switch to another relayer
     /// When tick is received without heartbeat_received
     ///      Sets fetch_connected to true, pending_disconnect to false
     ///      Advertises saved contact info
@@ -75,6 +77,7 @@ impl FetchStageManager {
             let mut pending_disconnect = false;
 
             let mut pending_disconnect_ts = Instant::now();
+            let mut advertised_tpu_addresses = None;
 
             let heartbeat_tick = tick(HEARTBEAT_TIMEOUT);
             let metrics_tick = tick(METRICS_CADENCE);
@@ -114,6 +117,7 @@ impl FetchStageManager {
                                 error!("error setting tpu or tpu_fwd to ({:?}, {:?}), error: {:?}", my_fallback_contact_info.tpu(Protocol::UDP).unwrap(), my_fallback_contact_info.tpu_forwards(Protocol::UDP).unwrap(), e);
                             }
                             heartbeats_received = 0;
+                            advertised_tpu_addresses = None;
                         }
                         heartbeat_received = false;
                     }
@@ -133,6 +137,13 @@ impl FetchStageManager {
                                 if let Err(e) = Self::set_tpu_addresses(&cluster_info, tpu_addr, tpu_forward_addr) {
                                     error!("error setting tpu or tpu_fwd to ({:?}, {:?}), error: {:?}", tpu_addr, tpu_forward_addr, e);
                                 }
+                                advertised_tpu_addresses = Some((tpu_addr, tpu_forward_addr));
+                            } else if !fetch_connected && advertised_tpu_addresses != Some((tpu_addr, tpu_forward_addr)) {
+                                info!("advertised relayer changed, switching tpu addresses");
+                                if let Err(e) = Self::set_tpu_addresses(&cluster_info, tpu_addr, tpu_forward_addr) {
+                                    error!("error setting tpu or tpu_fwd to ({:?}, {:?}), error: {:?}", tpu_addr, tpu_forward_addr, e);
+                                }
+                                advertised_tpu_addresses = Some((tpu_addr, tpu_forward_addr));
                             }
                         } else {
                             {
@@ -13,7 +13,9 @@
 mod auth;
 pub mod block_engine_stage;
 pub mod fetch_stage_manager;
+pub mod packet_relay;
 pub mod relayer_stage;
+pub mod shredstream_stage;
 
 use {
     std::{
@@ -74,6 +76,9 @@ pub enum ProxyError {
     #[error("BlockEngineConnectionError: {0:?}")]
     BlockEngineConnectionError(String),
 
+    #[error("BlockEngineConfigChanged")]
+    BlockEngineConfigChanged,
+
     #[error("RelayerConnectionTimeout")]
     RelayerConnectionTimeout,
 
@@ -83,6 +88,15 @@ pub enum ProxyError {
     #[error("RelayerConnectionError: {0:?}")]
     RelayerConnectionError(String),
 
+    #[error("RelayerConfigChanged")]
+    RelayerConfigChanged,
+
+    #[error("ShredstreamConnectionTimeout")]
+    ShredstreamConnectionTimeout,
+
+    #[error("ShredstreamConnectionError: {0:?}")]
+    ShredstreamConnectionError(String),
+
     #[error("AuthenticationError: {0:?}")]
     AuthenticationError(String),
 
@@ -0,0 +1,583 @@
+//! Relays packets received on the validator's own TPU to the block engines interested in them.
+//!
+//! Each connected block engine streams the accounts (AOI) and programs (POI) it's interested in.
+//! A packet is relayed to a block engine when its transaction write-locks one of those accounts or
+//! calls one of those programs. Accounts loaded from address lookup tables are resolved against
+//! the working bank, so a table created or extended in a slot that isn't frozen yet may be missed.
+//!
+//! Only packets that passed sigverify are relayed. The sigverify stage hands them to the
+//! [PacketRelayStage] thread over a bounded channel, and the thread hands each block engine its
+//! packets over another bounded channel. Neither ever blocks: packets are dropped and counted
+//! when the next hop falls behind.
+use {
+    crate::{banking_trace::BankingPacketBatch, packet_to_proto_packet},
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender},
+    jito_protos::proto::{
+        block_engine::{packet_batch_update::Msg, ExpiringPacketBatch, PacketBatchUpdate},
+        packet::PacketBatch as ProtoPacketBatch,
+        shared::Header,
+    },
+    prost_types::Timestamp,
+    solana_perf::packet::PacketBatch,
+    solana_runtime::bank_forks::BankForks,
+    solana_sdk::{
+        message::AddressLoader, pubkey::Pubkey, saturating_add_assign,
+        transaction::VersionedTransaction,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant, SystemTime},
+    },
+    tokio::sync::mpsc::{self, error::TrySendError},
+};
+
+/// How long relayed packets stay useful to the block engine.
+const RELAYED_PACKET_EXPIRY_MS: u32 = 200;
+/// Accounts of interest entry that matches every account.
+const ALL_ACCOUNTS: &str = "*";
+/// Sigverified packet batches waiting to be relayed.
+const PACKET_RELAY_CHANNEL_CAPACITY: usize = 1_024;
+/// Packet batch updates waiting to be streamed to each block engine.
+pub(crate) const PACKET_BATCH_UPDATE_CHANNEL_CAPACITY: usize = 1_024;
+const PACKET_RELAY_RECEIVE_TIMEOUT: Duration = Duration::from_millis(100);
+const METRICS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Accounts and programs a block engine wants packets for.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct PacketInterest {
+    all_accounts: bool,
+    accounts: HashSet<Pubkey>,
+    programs: HashSet<Pubkey>,
+}
+
+impl PacketInterest {
+    pub(crate) fn set_accounts(&mut self, accounts: &[String]) {
+        self.all_accounts = accounts.iter().any(|account| account == ALL_ACCOUNTS);
+        self.accounts = parse_pubkeys(accounts);
+    }
+
+    pub(crate) fn set_programs(&mut self, programs: &[String]) {
+        self.programs = parse_pubkeys(programs);
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.all_accounts && self.accounts.is_empty() && self.programs.is_empty()
+    }
+
+    /// Whether only some accounts are of interest, so lookup table accounts must be resolved.
+    fn needs_loaded_accounts(&self) -> bool {
+        !self.all_accounts && !self.accounts.is_empty()
+    }
+
+    /// `loaded_writable_accounts` are the transaction's writable accounts loaded from address
+    /// lookup tables. Programs are always static account keys.
+    fn matches(
+        &self,
+        transaction: &VersionedTransaction,
+        loaded_writable_accounts: &[Pubkey],
+    ) -> bool {
+        if self.all_accounts {
+            return true;
+        }
+        let message = &transaction.message;
+        let account_keys = message.static_account_keys();
+        account_keys.iter().enumerate().any(|(index, account)| {
+            self.accounts.contains(account) && message.is_maybe_writable(index, None)
+        }) || loaded_writable_accounts
+            .iter()
+            .any(|account| self.accounts.contains(account))
+            || message
+                .instructions()
+                .iter()
+                .any(|instruction| self.programs.contains(instruction.program_id(account_keys)))
+    }
+}
+
+/// Invalid pubkeys, including the all accounts wildcard, are skipped.
+fn parse_pubkeys(pubkeys: &[String]) -> HashSet<Pubkey> {
+    pubkeys
+        .iter()
+        .filter_map(|pubkey| Pubkey::from_str(pubkey).ok())
+        .collect()
+}
+
+/// Writable accounts `transaction` loads from address lookup tables. Lookups that fail, e.g. for
+/// a table that doesn't exist yet, load nothing.
+fn loaded_writable_accounts(
+    transaction: &VersionedTransaction,
+    address_loader: impl AddressLoader,
+) -> Vec<Pubkey> {
+    match transaction.message.address_table_lookups() {
+        Some(address_table_lookups) if !address_table_lookups.is_empty() => address_loader
+            .load_addresses(address_table_lookups)
+            .map(|loaded_addresses| loaded_addresses.writable)
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+struct RelayTarget {
+    interest: PacketInterest,
+    sender: mpsc::Sender<PacketBatchUpdate>,
+}
+
+/// Packets handled by a call to [PacketRelay::relay], counted once per block engine.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RelayCounts {
+    pub num_relayed: usize,
+    /// Packets dropped because the block engine's stream fell behind.
+    pub num_dropped: usize,
+}
+
+/// Block engines packets are relayed to, keyed by url.
+#[derive(Default)]
+pub struct PacketRelay {
+    targets: Mutex<HashMap<String, RelayTarget>>,
+}
+
+impl PacketRelay {
+    /// Starts relaying packets to `block_engine_url` through `sender` once it has an interest.
+    pub(crate) fn register(&self, block_engine_url: &str, sender: mpsc::Sender<PacketBatchUpdate>) {
+        self.targets.lock().unwrap().insert(
+            block_engine_url.to_string(),
+            RelayTarget {
+                interest: PacketInterest::default(),
+                sender,
+            },
+        );
+    }
+
+    pub(crate) fn unregister(&self, block_engine_url: &str) {
+        self.targets.lock().unwrap().remove(block_engine_url);
+    }
+
+    pub(crate) fn update_interest(
+        &self,
+        block_engine_url: &str,
+        update: impl FnOnce(&mut PacketInterest),
+    ) {
+        if let Some(target) = self.targets.lock().unwrap().get_mut(block_engine_url) {
+            update(&mut target.interest);
+        }
+    }
+
+    /// Sends each block engine the packets in `packet_batch` it's interested in, resolving
+    /// address lookup tables with `address_loader`.
+    pub fn relay(
+        &self,
+        packet_batch: &PacketBatch,
+        address_loader: impl AddressLoader,
+    ) -> RelayCounts {
+        let targets = self.targets.lock().unwrap();
+        if targets.values().all(|target| target.interest.is_empty()) {
+            return RelayCounts::default();
+        }
+        let needs_loaded_accounts = targets
+            .values()
+            .any(|target| target.interest.needs_loaded_accounts());
+
+        let transactions: Vec<_> = packet_batch
+            .iter()
+            .filter(|packet| !packet.meta().discard())
+            .filter_map(|packet| {
+                let transaction = packet.deserialize_slice(..).ok()?;
+                let loaded_writable_accounts = if needs_loaded_accounts {
+                    loaded_writable_accounts(&transaction, address_loader.clone())
+                } else {
+                    vec![]
+                };
+                Some((packet, transaction, loaded_writable_accounts))
+            })
+            .collect();
+
+        let mut relay_counts = RelayCounts::default();
+        for target in targets.values() {
+            let packets: Vec<_> = transactions
+                .iter()
+                .filter(|(_, transaction, loaded_writable_accounts)| {
+                    target
+                        .interest
+                        .matches(transaction, loaded_writable_accounts)
+                })
+                .map(|(packet, _, _)| packet_to_proto_packet(packet))
+                .collect();
+            if packets.is_empty() {
+                continue;
+            }
+            let num_packets = packets.len();
+            match target.sender.try_send(PacketBatchUpdate {
+                msg: Some(Msg::Batches(ExpiringPacketBatch {
+                    header: Some(Header {
+                        ts: Some(Timestamp::from(SystemTime::now())),
+                    }),
+                    batch: Some(ProtoPacketBatch { packets }),
+                    expiry_ms: RELAYED_PACKET_EXPIRY_MS,
+                })),
+            }) {
+                Ok(()) => saturating_add_assign!(relay_counts.num_relayed, num_packets),
+                Err(TrySendError::Full(_)) => {
+                    saturating_add_assign!(relay_counts.num_dropped, num_packets)
+                }
+                // A block engine that disconnected is unregistered by its stream
+                Err(TrySendError::Closed(_)) => {}
+            }
+        }
+        relay_counts
+    }
+}
+
+/// Hands sigverified packets to the [PacketRelayStage], never blocking sigverify.
+#[derive(Clone)]
+pub struct PacketRelaySender {
+    sender: Sender<BankingPacketBatch>,
+    num_dropped_batches: Arc<AtomicU64>,
+}
+
+impl PacketRelaySender {
+    /// Drops `packet_batches` if the relay thread is behind.
+    pub fn send(&self, packet_batches: &BankingPacketBatch) {
+        if self.sender.try_send(packet_batches.clone()).is_err() {
+            self.num_dropped_batches.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Thread relaying sigverified TPU packets to interested block engines.
+pub struct PacketRelayStage {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl PacketRelayStage {
+    pub fn new(
+        packet_relay: Arc<PacketRelay>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        exit: Arc<AtomicBool>,
+    ) -> (Self, PacketRelaySender) {
+        let (sender, receiver) = bounded(PACKET_RELAY_CHANNEL_CAPACITY);
+        let num_dropped_batches = Arc::new(AtomicU64::default());
+        let thread_hdl = Builder::new()
+            .name("solPacketRelay".to_string())
+            .spawn({
+                let num_dropped_batches = num_dropped_batches.clone();
+                move || {
+                    Self::run(
+                        receiver,
+                        &packet_relay,
+                        &bank_forks,
+                        &num_dropped_batches,
+                        &exit,
+                    )
+                }
+            })
+            .unwrap();
+        (
+            Self { thread_hdl },
+            PacketRelaySender {
+                sender,
+                num_dropped_batches,
+            },
+        )
+    }
+
+    fn run(
+        receiver: Receiver<BankingPacketBatch>,
+        packet_relay: &PacketRelay,
+        bank_forks: &RwLock<BankForks>,
+        num_dropped_batches: &AtomicU64,
+        exit: &AtomicBool,
+    ) {
+        let mut relay_counts = RelayCounts::default();
+        let mut last_metrics_report = Instant::now();
+        while !exit.load(Ordering::Relaxed) {
+            match receiver.recv_timeout(PACKET_RELAY_RECEIVE_TIMEOUT) {
+                Ok(packet_batches) => {
+                    let bank = bank_forks.read().unwrap().working_bank();
+                    for packet_batch in &packet_batches.0 {
+                        let counts = packet_relay.relay(packet_batch, &*bank);
+                        saturating_add_assign!(relay_counts.num_relayed, counts.num_relayed);
+                        saturating_add_assign!(relay_counts.num_dropped, counts.num_dropped);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            if last_metrics_report.elapsed() >= METRICS_INTERVAL {
+                let RelayCounts {
+                    num_relayed,
+                    num_dropped,
+                } = std::mem::take(&mut relay_counts);
+                datapoint_info!(
+                    "packet_relay_stage",
+                    ("num_packets_relayed", num_relayed, i64),
+                    ("num_packets_dropped", num_dropped, i64),
+                    (
+                        "num_batches_dropped",
+                        num_dropped_batches.swap(0, Ordering::Relaxed),
+                        i64
+                    ),
+                );
+                last_metrics_report = Instant::now();
+            }
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            address_lookup_table::AddressLookupTableAccount,
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            message::{v0, v0::LoadedAddresses, SimpleAddressLoader, VersionedMessage},
+            packet::Packet,
+            signature::Keypair,
+            signer::Signer,
+            system_instruction, system_program, system_transaction,
+            transaction::Transaction,
+        },
+    };
+
+    fn to_packet_batch(transactions: &[Transaction]) -> PacketBatch {
+        PacketBatch::new(
+            transactions
+                .iter()
+                .map(|transaction| Packet::from_data(None, transaction).unwrap())
+                .collect(),
+        )
+    }
+
+    fn relayed_packets(
+        receiver: &mut mpsc::Receiver<PacketBatchUpdate>,
+    ) -> Vec<jito_protos::proto::packet::Packet> {
+        match receiver.try_recv().unwrap().msg {
+            Some(Msg::Batches(ExpiringPacketBatch {
+                batch: Some(batch),
+                expiry_ms,
+                ..
+            })) => {
+                assert_eq!(expiry_ms, RELAYED_PACKET_EXPIRY_MS);
+                batch.packets
+            }
+            msg => panic!("unexpected update: {msg:?}"),
+        }
+    }
+
+    #[test]
+    fn test_packet_interest_accounts() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let transaction = VersionedTransaction::from(system_transaction::transfer(
+            &payer,
+            &to,
+            1,
+            Hash::default(),
+        ));
+
+        let mut interest = PacketInterest::default();
+        assert!(!interest.matches(&transaction, &[]));
+
+        interest.set_accounts(&[to.to_string()]);
+        assert!(interest.matches(&transaction, &[]));
+
+        // read-only accounts aren't of interest
+        interest.set_accounts(&[system_program::id().to_string()]);
+        assert!(!interest.matches(&transaction, &[]));
+
+        interest.set_accounts(&["invalid".to_string(), ALL_ACCOUNTS.to_string()]);
+        assert!(interest.matches(&transaction, &[]));
+        assert!(interest.accounts.is_empty());
+    }
+
+    #[test]
+    fn test_packet_interest_programs() {
+        let payer = Keypair::new();
+        let program_id = Pubkey::new_unique();
+        let transaction = VersionedTransaction::from(Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![AccountMeta::new(Pubkey::new_unique(), false)],
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        ));
+
+        let mut interest = PacketInterest::default();
+        interest.set_programs(&[system_program::id().to_string()]);
+        assert!(!interest.matches(&transaction, &[]));
+
+        interest.set_programs(&[program_id.to_string()]);
+        assert!(interest.matches(&transaction, &[]));
+    }
+
+    #[test]
+    fn test_packet_interest_lookup_table_accounts() {
+        let payer = Keypair::new();
+        let hot_account = Pubkey::new_unique();
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![hot_account],
+        };
+        let transaction = VersionedTransaction::try_new(
+            VersionedMessage::V0(
+                v0::Message::try_compile(
+                    &payer.pubkey(),
+                    &[system_instruction::transfer(
+                        &payer.pubkey(),
+                        &hot_account,
+                        1,
+                    )],
+                    &[lookup_table],
+                    Hash::default(),
+                )
+                .unwrap(),
+            ),
+            &[&payer],
+        )
+        .unwrap();
+        assert!(!transaction
+            .message
+            .static_account_keys()
+            .contains(&hot_account));
+
+        let address_loader = SimpleAddressLoader::Enabled(LoadedAddresses {
+            writable: vec![hot_account],
+            readonly: vec![],
+        });
+        let loaded_accounts = loaded_writable_accounts(&transaction, address_loader);
+        assert_eq!(loaded_accounts, vec![hot_account]);
+        // tables that can't be loaded load nothing
+        assert!(loaded_writable_accounts(&transaction, SimpleAddressLoader::Disabled).is_empty());
+
+        let mut interest = PacketInterest::default();
+        interest.set_accounts(&[hot_account.to_string()]);
+        assert!(interest.needs_loaded_accounts());
+        assert!(!interest.matches(&transaction, &[]));
+        assert!(interest.matches(&transaction, &loaded_accounts));
+    }
+
+    #[test]
+    fn test_relay() {
+        let payer = Keypair::new();
+        let hot_account = Pubkey::new_unique();
+        let hot_transaction =
+            system_transaction::transfer(&payer, &hot_account, 1, Hash::default());
+        let cold_transaction = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &Pubkey::new_unique(),
+                1,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::new_unique(),
+        );
+        let packet_batch = to_packet_batch(&[hot_transaction, cold_transaction.clone()]);
+
+        let packet_relay = PacketRelay::default();
+        let (sender, mut receiver) = mpsc::channel(PACKET_BATCH_UPDATE_CHANNEL_CAPACITY);
+        let (all_sender, mut all_receiver) = mpsc::channel(PACKET_BATCH_UPDATE_CHANNEL_CAPACITY);
+        packet_relay.register("http://block-engine:1", sender);
+        packet_relay.register("http://all:1", all_sender);
+        let relay = |packet_batch: &PacketBatch| {
+            packet_relay.relay(packet_batch, SimpleAddressLoader::Disabled)
+        };
+
+        // nothing is relayed before block engines send an interest
+        assert_eq!(relay(&packet_batch), RelayCounts::default());
+        assert!(receiver.try_recv().is_err());
+
+        packet_relay.update_interest("http://block-engine:1", |interest| {
+            interest.set_accounts(&[hot_account.to_string()])
+        });
+        packet_relay.update_interest("http://all:1", |interest| {
+            interest.set_accounts(&[ALL_ACCOUNTS.to_string()])
+        });
+        assert_eq!(
+            relay(&packet_batch),
+            RelayCounts {
+                num_relayed: 3,
+                num_dropped: 0,
+            }
+        );
+
+        let packets = relayed_packets(&mut receiver);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].data, packet_batch[0].data(..).unwrap());
+        assert_eq!(relayed_packets(&mut all_receiver).len(), 2);
+
+        // discarded packets are never relayed
+        let mut discarded_batch = to_packet_batch(&[cold_transaction]);
+        discarded_batch[0].meta_mut().set_discard(true);
+        assert_eq!(relay(&discarded_batch), RelayCounts::default());
+
+        packet_relay.unregister("http://all:1");
+        assert_eq!(relay(&packet_batch).num_relayed, 1);
+        assert!(all_receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_relay_drops_when_block_engine_is_behind() {
+        let payer = Keypair::new();
+        let packet_batch = to_packet_batch(&[system_transaction::transfer(
+            &payer,
+            &Pubkey::new_unique(),
+            1,
+            Hash::default(),
+        )]);
+
+        let packet_relay = PacketRelay::default();
+        let (sender, mut receiver) = mpsc::channel(1);
+        packet_relay.register("http://block-engine:1", sender);
+        packet_relay.update_interest("http://block-engine:1", |interest| {
+            interest.set_accounts(&[ALL_ACCOUNTS.to_string()])
+        });
+
+        let relay = || packet_relay.relay(&packet_batch, SimpleAddressLoader::Disabled);
+        assert_eq!(relay().num_relayed, 1);
+        assert_eq!(
+            relay(),
+            RelayCounts {
+                num_relayed: 0,
+                num_dropped: 1,
+            }
+        );
+        assert_eq!(relayed_packets(&mut receiver).len(), 1);
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(relay().num_relayed, 1);
+
+        // a block engine that disconnected is neither relayed to nor dropped for
+        drop(receiver);
+        assert_eq!(relay(), RelayCounts::default());
+    }
+
+    #[test]
+    fn test_packet_relay_sender_drops_when_full() {
+        let (sender, receiver) = bounded(1);
+        let num_dropped_batches = Arc::new(AtomicU64::default());
+        let packet_relay_sender = PacketRelaySender {
+            sender,
+            num_dropped_batches: num_dropped_batches.clone(),
+        };
+
+        let packet_batches = BankingPacketBatch::new((vec![], None));
+        packet_relay_sender.send(&packet_batches);
+        packet_relay_sender.send(&packet_batches);
+        assert_eq!(receiver.len(), 1);
+        assert_eq!(num_dropped_batches.load(Ordering::Relaxed), 1);
+    }
+}
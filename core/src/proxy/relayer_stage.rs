@@ -1,4 +1,4 @@
-//! Maintains a connection to the Relayer.
+//! Maintains connections to the Relayers.
 //!
 //! The external Relayer is responsible for the following:
 //! - Acts as a TPU proxy.
@@ -7,6 +7,10 @@
 //! - When validator connects, it changes its TPU and TPU forward address to the relayer.
 //! - Expected to send heartbeat to validator as watchdog. If watchdog times out, the validator
 //!   disconnects and reverts the TPU and TPU forward settings.
+//!
+//! Every configured relayer is streamed from at once. Packets are deduplicated by their contents
+//! before sigverify, and the TPU addresses of the healthiest relayer, the one that missed the fewest
+//! heartbeats recently, are advertised.
 
 use {
     crate::{
@@ -18,21 +22,25 @@ use {
         },
     },
     crossbeam_channel::Sender,
+    futures::future::join_all,
     jito_protos::proto::{
         auth::{auth_service_client::AuthServiceClient, Token},
         relayer::{self, relayer_client::RelayerClient},
     },
+    lru::LruCache,
     solana_gossip::cluster_info::ClusterInfo,
-    solana_perf::packet::PacketBatch,
+    solana_perf::packet::{Packet, PacketBatch},
     solana_sdk::{
+        hash::{hash, Hash},
         saturating_add_assign,
         signature::{Keypair, Signer},
     },
     std::{
+        collections::{HashSet, VecDeque},
         net::{IpAddr, Ipv4Addr, SocketAddr},
         str::FromStr,
         sync::{
-            atomic::{AtomicBool, Ordering},
+            atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, Mutex,
         },
         thread::{self, Builder, JoinHandle},
@@ -51,29 +59,53 @@ use {
 
 const CONNECTION_TIMEOUT_S: u64 = 10;
 const CONNECTION_BACKOFF_S: u64 = 5;
+/// Number of recently received packet hashes remembered to drop packets sent by more than one
+/// relayer.
+const PACKET_CACHE_CAPACITY: usize = 250_000;
+/// How long a missed heartbeat counts against a relayer when picking the one to advertise.
+const MISSED_HEARTBEAT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Hash of each recently received packet.
+type PacketCache = Mutex<LruCache<Hash, ()>>;
 
+/// Stats of the connection to a single relayer.
 #[derive(Default)]
 struct RelayerStageStats {
+    url: String,
     num_empty_messages: u64,
     num_packets: u64,
+    num_duplicate_packets: u64,
     num_heartbeats: u64,
+    num_missed_heartbeats: u64,
 }
 
 impl RelayerStageStats {
-    pub(crate) fn report(&self) {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Reports and resets the counters, keeping the relayer they belong to.
+    pub(crate) fn report(&mut self) {
         datapoint_info!(
             "relayer_stage-stats",
+            ("url", self.url, String),
             ("num_empty_messages", self.num_empty_messages, i64),
             ("num_packets", self.num_packets, i64),
+            ("num_duplicate_packets", self.num_duplicate_packets, i64),
             ("num_heartbeats", self.num_heartbeats, i64),
+            ("num_missed_heartbeats", self.num_missed_heartbeats, i64),
         );
+        *self = Self::new(&self.url);
     }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct RelayerConfig {
-    /// Relayer URL
-    pub relayer_url: String,
+    /// Relayer URLs. Packets received from more than one of them are deduplicated.
+    pub relayer_urls: Vec<String>,
 
     /// Interval at which heartbeats are expected.
     pub expected_heartbeat_interval: Duration,
@@ -85,6 +117,139 @@ pub struct RelayerConfig {
     pub trust_packets: bool,
 }
 
+/// State of the connection to a single relayer, exposed through the admin rpc.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayerState {
+    pub relayer_url: String,
+
+    /// Set while packets are being streamed.
+    pub connected: bool,
+
+    /// Set while the relayer's TPU addresses are the ones advertised.
+    pub advertised: bool,
+
+    /// TPU and TPU forward addresses the relayer asked to be advertised.
+    pub tpu_addresses: Option<(SocketAddr, SocketAddr)>,
+
+    /// Heartbeats received over the current connection.
+    pub num_heartbeats: u64,
+
+    /// Heartbeats missed over the current connection.
+    pub num_missed_heartbeats: u64,
+
+    /// Packets received over the last second.
+    pub packets_per_second: u64,
+
+    /// Error that ended the last connection, if any.
+    pub last_error: Option<String>,
+
+    /// When each heartbeat within the last [`MISSED_HEARTBEAT_WINDOW`] was missed.
+    recently_missed_heartbeats: VecDeque<Instant>,
+}
+
+/// Connections to the configured relayers, in config order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RelayerStates {
+    pub relayers: Vec<RelayerState>,
+}
+
+impl RelayerStates {
+    /// Starts tracking a disconnected relayer for each of `relayer_urls`.
+    fn reset(&mut self, relayer_urls: &[String]) {
+        self.relayers = relayer_urls
+            .iter()
+            .map(|relayer_url| RelayerState {
+                relayer_url: relayer_url.clone(),
+                ..RelayerState::default()
+            })
+            .collect();
+    }
+
+    fn get_mut(&mut self, relayer_url: &str) -> Option<&mut RelayerState> {
+        self.relayers
+            .iter_mut()
+            .find(|relayer| relayer.relayer_url == relayer_url)
+    }
+
+    fn connected(&mut self, relayer_url: &str, tpu_addresses: HeartbeatEvent) {
+        if let Some(relayer) = self.get_mut(relayer_url) {
+            *relayer = RelayerState {
+                relayer_url: relayer.relayer_url.clone(),
+                connected: true,
+                tpu_addresses: Some(tpu_addresses),
+                ..RelayerState::default()
+            };
+        }
+    }
+
+    fn disconnected(&mut self, relayer_url: &str, error: Option<String>, now: Instant) {
+        if let Some(relayer) = self.get_mut(relayer_url) {
+            relayer.connected = false;
+            relayer.tpu_addresses = None;
+            relayer.packets_per_second = 0;
+            relayer.last_error = error;
+        }
+        self.update_advertised(now);
+    }
+
+    /// Records a heartbeat and returns whether the relayer's TPU addresses should be advertised.
+    fn heartbeat(&mut self, relayer_url: &str, now: Instant) -> bool {
+        if let Some(relayer) = self.get_mut(relayer_url) {
+            saturating_add_assign!(relayer.num_heartbeats, 1);
+        }
+        self.update_advertised(now);
+        self.relayers
+            .iter()
+            .any(|relayer| relayer.advertised && relayer.relayer_url == relayer_url)
+    }
+
+    fn missed_heartbeat(&mut self, relayer_url: &str, now: Instant) {
+        if let Some(relayer) = self.get_mut(relayer_url) {
+            saturating_add_assign!(relayer.num_missed_heartbeats, 1);
+            relayer.recently_missed_heartbeats.push_back(now);
+        }
+        self.update_advertised(now);
+    }
+
+    fn set_packets_per_second(&mut self, relayer_url: &str, packets_per_second: u64) {
+        if let Some(relayer) = self.get_mut(relayer_url) {
+            relayer.packets_per_second = packets_per_second;
+        }
+    }
+
+    /// Advertises the connected relayer that missed the fewest heartbeats within the
+    /// [`MISSED_HEARTBEAT_WINDOW`]. Ties keep the advertised relayer, then go to the relayer
+    /// configured first, so addresses only change when another relayer is strictly healthier.
+    fn update_advertised(&mut self, now: Instant) {
+        for relayer in &mut self.relayers {
+            while relayer
+                .recently_missed_heartbeats
+                .front()
+                .is_some_and(|missed| now.duration_since(*missed) > MISSED_HEARTBEAT_WINDOW)
+            {
+                relayer.recently_missed_heartbeats.pop_front();
+            }
+        }
+
+        let healthiest = self
+            .relayers
+            .iter()
+            .enumerate()
+            .filter(|(_, relayer)| relayer.connected && relayer.tpu_addresses.is_some())
+            .min_by_key(|(index, relayer)| {
+                (
+                    relayer.recently_missed_heartbeats.len(),
+                    !relayer.advertised,
+                    *index,
+                )
+            })
+            .map(|(index, _)| index);
+        for (index, relayer) in self.relayers.iter_mut().enumerate() {
+            relayer.advertised = healthiest == Some(index);
+        }
+    }
+}
+
 pub struct RelayerStage {
     t_hdls: Vec<JoinHandle<()>>,
 }
@@ -92,9 +257,10 @@ pub struct RelayerStage {
 impl RelayerStage {
     pub fn new(
         relayer_config: Arc<Mutex<RelayerConfig>>,
+        relayer_states: Arc<Mutex<RelayerStates>>,
         // The keypair stored here is used to sign auth challenges.
         cluster_info: Arc<ClusterInfo>,
-        // Channel that server-sent heartbeats are piped through.
+        // Channel that server-sent heartbeats of the advertised relayer are piped through.
         heartbeat_tx: Sender<HeartbeatEvent>,
         // Channel that non-trusted streamed packets are piped through.
         packet_tx: Sender<PacketBatch>,
@@ -112,6 +278,7 @@ impl RelayerStage {
 
                 rt.block_on(Self::start(
                     relayer_config,
+                    relayer_states,
                     cluster_info,
                     heartbeat_tx,
                     packet_tx,
@@ -136,6 +303,7 @@ impl RelayerStage {
     #[allow(clippy::too_many_arguments)]
     async fn start(
         relayer_config: Arc<Mutex<RelayerConfig>>,
+        relayer_states: Arc<Mutex<RelayerStates>>,
         cluster_info: Arc<ClusterInfo>,
         heartbeat_tx: Sender<HeartbeatEvent>,
         packet_tx: Sender<PacketBatch>,
@@ -145,73 +313,138 @@ impl RelayerStage {
         const CONNECTION_TIMEOUT: Duration = Duration::from_secs(CONNECTION_TIMEOUT_S);
         const CONNECTION_BACKOFF: Duration = Duration::from_secs(CONNECTION_BACKOFF_S);
 
-        let mut error_count: u64 = 0;
+        // Shared by all connections so a packet is forwarded once no matter how many relayers
+        // send it.
+        let packet_cache = Mutex::new(LruCache::new(PACKET_CACHE_CAPACITY));
+        let error_count = AtomicU64::default();
 
         while !exit.load(Ordering::Relaxed) {
             // Wait until a valid config is supplied (either initially or by admin rpc)
-            // Use if!/else here to avoid extra CONNECTION_BACKOFF wait on successful termination
             let local_relayer_config = {
                 let relayer_config = relayer_config.clone();
                 task::spawn_blocking(move || relayer_config.lock().unwrap().clone())
                     .await
                     .expect("Failed to get execute tokio task.")
             };
+            relayer_states
+                .lock()
+                .unwrap()
+                .reset(&local_relayer_config.relayer_urls);
             if !Self::is_valid_relayer_config(&local_relayer_config) {
                 sleep(CONNECTION_BACKOFF).await;
-            } else if let Err(e) = Self::connect_auth_and_stream(
-                &local_relayer_config,
-                &relayer_config,
-                &cluster_info,
-                &heartbeat_tx,
-                &packet_tx,
-                &banking_packet_sender,
-                &exit,
-                &CONNECTION_TIMEOUT,
-            )
-            .await
-            {
-                match e {
-                    // This error is frequent on hot spares, and the parsed string does not work
-                    // with datapoints (incorrect escaping).
-                    ProxyError::AuthenticationPermissionDenied => {
-                        warn!("relayer permission denied. not on leader schedule. ignore if hot-spare.")
-                    }
-                    e => {
-                        error_count += 1;
-                        datapoint_warn!(
-                            "relayer_stage-proxy_error",
-                            ("count", error_count, i64),
-                            ("error", e.to_string(), String),
-                        );
-                    }
-                }
-                sleep(CONNECTION_BACKOFF).await;
+                continue;
             }
+
+            join_all(local_relayer_config.relayer_urls.iter().map(|relayer_url| {
+                Self::maintain_relayer_connection(
+                    relayer_url,
+                    &local_relayer_config,
+                    &relayer_config,
+                    &relayer_states,
+                    &cluster_info,
+                    &heartbeat_tx,
+                    &packet_tx,
+                    &banking_packet_sender,
+                    &packet_cache,
+                    &exit,
+                    &CONNECTION_TIMEOUT,
+                    &error_count,
+                )
+            }))
+            .await;
         }
     }
 
-    async fn connect_auth_and_stream(
+    /// Keeps streaming from `relayer_url`, reconnecting after a backoff when the connection drops,
+    /// until exit or the config changes.
+    #[allow(clippy::too_many_arguments)]
+    async fn maintain_relayer_connection(
+        relayer_url: &str,
         local_relayer_config: &RelayerConfig,
         global_relayer_config: &Arc<Mutex<RelayerConfig>>,
+        relayer_states: &Mutex<RelayerStates>,
         cluster_info: &Arc<ClusterInfo>,
         heartbeat_tx: &Sender<HeartbeatEvent>,
         packet_tx: &Sender<PacketBatch>,
         banking_packet_sender: &BankingPacketSender,
+        packet_cache: &PacketCache,
         exit: &Arc<AtomicBool>,
         connection_timeout: &Duration,
-    ) -> crate::proxy::Result<()> {
-        // Get a copy of configs here in case they have changed at runtime
-        let keypair = cluster_info.keypair().clone();
+        error_count: &AtomicU64,
+    ) {
+        const CONNECTION_BACKOFF: Duration = Duration::from_secs(CONNECTION_BACKOFF_S);
+
+        while !exit.load(Ordering::Relaxed)
+            && !Self::relayer_config_changed(local_relayer_config, global_relayer_config).await
+        {
+            let result = Self::connect_auth_and_stream(
+                relayer_url,
+                local_relayer_config,
+                global_relayer_config,
+                relayer_states,
+                cluster_info,
+                heartbeat_tx,
+                packet_tx,
+                banking_packet_sender,
+                packet_cache,
+                exit,
+                connection_timeout,
+            )
+            .await;
+            relayer_states.lock().unwrap().disconnected(
+                relayer_url,
+                result.as_ref().err().map(ToString::to_string),
+                Instant::now(),
+            );
+            match result {
+                Ok(()) | Err(ProxyError::RelayerConfigChanged) => break,
+                Err(e) => {
+                    Self::report_proxy_error(relayer_url, e, error_count);
+                    sleep(CONNECTION_BACKOFF).await;
+                }
+            }
+        }
+    }
+
+    fn report_proxy_error(relayer_url: &str, e: ProxyError, error_count: &AtomicU64) {
+        match e {
+            // This error is frequent on hot spares, and the parsed string does not work
+            // with datapoints (incorrect escaping).
+            ProxyError::AuthenticationPermissionDenied => {
+                warn!("relayer permission denied. not on leader schedule. ignore if hot-spare.")
+            }
+            e => {
+                let error_count = error_count.fetch_add(1, Ordering::Relaxed) + 1;
+                datapoint_warn!(
+                    "relayer_stage-proxy_error",
+                    ("url", relayer_url, String),
+                    ("count", error_count, i64),
+                    ("error", e.to_string(), String),
+                );
+            }
+        }
+    }
 
-        let mut backend_endpoint = Endpoint::from_shared(local_relayer_config.relayer_url.clone())
+    async fn relayer_config_changed(
+        local_relayer_config: &RelayerConfig,
+        global_relayer_config: &Arc<Mutex<RelayerConfig>>,
+    ) -> bool {
+        let global_relayer_config = global_relayer_config.clone();
+        *local_relayer_config
+            != task::spawn_blocking(move || global_relayer_config.lock().unwrap().clone())
+                .await
+                .unwrap()
+    }
+
+    fn relayer_endpoint(relayer_url: &str) -> crate::proxy::Result<Endpoint> {
+        let mut backend_endpoint = Endpoint::from_shared(relayer_url.to_string())
             .map_err(|_| {
                 ProxyError::RelayerConnectionError(format!(
-                    "invalid relayer url value: {}",
-                    local_relayer_config.relayer_url
+                    "invalid relayer url value: {relayer_url}"
                 ))
             })?
             .tcp_keepalive(Some(Duration::from_secs(60)));
-        if local_relayer_config.relayer_url.starts_with("https") {
+        if relayer_url.starts_with("https") {
             backend_endpoint = backend_endpoint
                 .tls_config(tonic::transport::ClientTlsConfig::new())
                 .map_err(|_| {
@@ -220,8 +453,29 @@ impl RelayerStage {
                     )
                 })?;
         }
+        Ok(backend_endpoint)
+    }
 
-        debug!("connecting to auth: {}", local_relayer_config.relayer_url);
+    #[allow(clippy::too_many_arguments)]
+    async fn connect_auth_and_stream(
+        relayer_url: &str,
+        local_relayer_config: &RelayerConfig,
+        global_relayer_config: &Arc<Mutex<RelayerConfig>>,
+        relayer_states: &Mutex<RelayerStates>,
+        cluster_info: &Arc<ClusterInfo>,
+        heartbeat_tx: &Sender<HeartbeatEvent>,
+        packet_tx: &Sender<PacketBatch>,
+        banking_packet_sender: &BankingPacketSender,
+        packet_cache: &PacketCache,
+        exit: &Arc<AtomicBool>,
+        connection_timeout: &Duration,
+    ) -> crate::proxy::Result<()> {
+        // Get a copy of configs here in case they have changed at runtime
+        let keypair = cluster_info.keypair().clone();
+
+        let backend_endpoint = Self::relayer_endpoint(relayer_url)?;
+
+        debug!("connecting to auth: {}", relayer_url);
         let auth_channel = timeout(*connection_timeout, backend_endpoint.connect())
             .await
             .map_err(|_| ProxyError::AuthenticationConnectionTimeout)?
@@ -239,14 +493,11 @@ impl RelayerStage {
 
         datapoint_info!(
             "relayer_stage-tokens_generated",
-            ("url", local_relayer_config.relayer_url, String),
+            ("url", relayer_url, String),
             ("count", 1, i64),
         );
 
-        debug!(
-            "connecting to relayer: {}",
-            local_relayer_config.relayer_url
-        );
+        debug!("connecting to relayer: {}", relayer_url);
         let relayer_channel = timeout(*connection_timeout, backend_endpoint.connect())
             .await
             .map_err(|_| ProxyError::RelayerConnectionTimeout)?
@@ -259,12 +510,15 @@ impl RelayerStage {
         );
 
         Self::start_consuming_relayer_packets(
+            relayer_url,
             relayer_client,
             heartbeat_tx,
             packet_tx,
             banking_packet_sender,
             local_relayer_config,
             global_relayer_config,
+            relayer_states,
+            packet_cache,
             exit,
             auth_client,
             access_token,
@@ -278,12 +532,15 @@ impl RelayerStage {
 
     #[allow(clippy::too_many_arguments)]
     async fn start_consuming_relayer_packets(
+        relayer_url: &str,
         mut client: RelayerClient<InterceptedService<Channel, AuthInterceptor>>,
         heartbeat_tx: &Sender<HeartbeatEvent>,
         packet_tx: &Sender<PacketBatch>,
         banking_packet_sender: &BankingPacketSender,
         local_config: &RelayerConfig, // local copy of config with current connections
         global_config: &Arc<Mutex<RelayerConfig>>, // guarded reference for detecting run-time updates
+        relayer_states: &Mutex<RelayerStates>,
+        packet_cache: &PacketCache,
         exit: &Arc<AtomicBool>,
         auth_client: AuthServiceClient<Channel>,
         access_token: Arc<Mutex<Token>>,
@@ -326,13 +583,21 @@ impl RelayerStage {
         .map_err(|e| ProxyError::MethodError(e.to_string()))?
         .into_inner();
 
+        relayer_states
+            .lock()
+            .unwrap()
+            .connected(relayer_url, heartbeat_event);
+
         Self::consume_packet_stream(
+            relayer_url,
             heartbeat_event,
             heartbeat_tx,
             packet_stream,
             packet_tx,
             local_config,
             global_config,
+            relayer_states,
+            packet_cache,
             banking_packet_sender,
             exit,
             auth_client,
@@ -347,12 +612,15 @@ impl RelayerStage {
 
     #[allow(clippy::too_many_arguments)]
     async fn consume_packet_stream(
+        relayer_url: &str,
         heartbeat_event: HeartbeatEvent,
         heartbeat_tx: &Sender<HeartbeatEvent>,
         mut packet_stream: Streaming<relayer::SubscribePacketsResponse>,
         packet_tx: &Sender<PacketBatch>,
         local_config: &RelayerConfig, // local copy of config with current connections
         global_config: &Arc<Mutex<RelayerConfig>>, // guarded reference for detecting run-time updates
+        relayer_states: &Mutex<RelayerStates>,
+        packet_cache: &PacketCache,
         banking_packet_sender: &BankingPacketSender,
         exit: &Arc<AtomicBool>,
         mut auth_client: AuthServiceClient<Channel>,
@@ -365,41 +633,48 @@ impl RelayerStage {
         const METRICS_TICK: Duration = Duration::from_secs(1);
         let refresh_within_s: u64 = METRICS_TICK.as_secs().saturating_mul(3).saturating_div(2);
 
-        let mut relayer_stats = RelayerStageStats::default();
+        let mut relayer_stats = RelayerStageStats::new(relayer_url);
         let mut metrics_and_auth_tick = interval(METRICS_TICK);
 
         let mut num_full_refreshes: u64 = 1;
         let mut num_refresh_access_token: u64 = 0;
 
         let mut heartbeat_check_interval = interval(local_config.expected_heartbeat_interval);
+        // Leaves room for jitter so heartbeats sent on time aren't counted as missed.
+        let missed_heartbeat_age = local_config.expected_heartbeat_interval.saturating_mul(3) / 2;
         let mut last_heartbeat_ts = Instant::now();
 
-        info!("connected to packet stream");
+        info!("connected to packet stream: {relayer_url}");
 
         while !exit.load(Ordering::Relaxed) {
             tokio::select! {
                 maybe_msg = packet_stream.message() => {
                     let resp = maybe_msg?.ok_or(ProxyError::GrpcStreamDisconnected)?;
-                    Self::handle_relayer_packets(resp, heartbeat_event, heartbeat_tx, &mut last_heartbeat_ts, packet_tx, local_config.trust_packets, banking_packet_sender, &mut relayer_stats)?;
+                    Self::handle_relayer_packets(relayer_url, resp, heartbeat_event, heartbeat_tx, &mut last_heartbeat_ts, packet_tx, local_config.trust_packets, banking_packet_sender, relayer_states, packet_cache, &mut relayer_stats)?;
                 }
                 _ = heartbeat_check_interval.tick() => {
-                    if last_heartbeat_ts.elapsed() > local_config.oldest_allowed_heartbeat {
+                    let heartbeat_age = last_heartbeat_ts.elapsed();
+                    if heartbeat_age > local_config.oldest_allowed_heartbeat {
                         return Err(ProxyError::HeartbeatExpired);
                     }
+                    if heartbeat_age > missed_heartbeat_age {
+                        saturating_add_assign!(relayer_stats.num_missed_heartbeats, 1);
+                        relayer_states.lock().unwrap().missed_heartbeat(relayer_url, Instant::now());
+                    }
                 }
                 _ = metrics_and_auth_tick.tick() => {
+                    relayer_states.lock().unwrap().set_packets_per_second(
+                        relayer_url,
+                        relayer_stats.num_packets / METRICS_TICK.as_secs(),
+                    );
                     relayer_stats.report();
-                    relayer_stats = RelayerStageStats::default();
 
                     if cluster_info.id() != keypair.pubkey() {
                         return Err(ProxyError::AuthenticationConnectionError("validator identity changed".to_string()));
                     }
 
-                    let global_config = global_config.clone();
-                    if *local_config != task::spawn_blocking(move || global_config.lock().unwrap().clone())
-                        .await
-                        .unwrap() {
-                        return Err(ProxyError::AuthenticationConnectionError("relayer config changed".to_string()));
+                    if Self::relayer_config_changed(local_config, global_config).await {
+                        return Err(ProxyError::RelayerConfigChanged);
                     }
 
                     let (maybe_new_access, maybe_new_refresh) = maybe_refresh_auth_tokens(&mut auth_client,
@@ -414,7 +689,7 @@ impl RelayerStage {
                         num_refresh_access_token += 1;
                         datapoint_info!(
                             "relayer_stage-refresh_access_token",
-                            ("url", &local_config.relayer_url, String),
+                            ("url", relayer_url, String),
                             ("count", num_refresh_access_token, i64),
                         );
 
@@ -427,7 +702,7 @@ impl RelayerStage {
                         num_full_refreshes += 1;
                         datapoint_info!(
                             "relayer_stage-tokens_generated",
-                            ("url", &local_config.relayer_url, String),
+                            ("url", relayer_url, String),
                             ("count", num_full_refreshes, i64),
                         );
                         refresh_token = new_token;
@@ -438,7 +713,9 @@ impl RelayerStage {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn handle_relayer_packets(
+        relayer_url: &str,
         subscribe_packets_resp: relayer::SubscribePacketsResponse,
         heartbeat_event: HeartbeatEvent,
         heartbeat_tx: &Sender<HeartbeatEvent>,
@@ -446,6 +723,8 @@ impl RelayerStage {
         packet_tx: &Sender<PacketBatch>,
         trust_packets: bool,
         banking_packet_sender: &BankingPacketSender,
+        relayer_states: &Mutex<RelayerStates>,
+        packet_cache: &PacketCache,
         relayer_stats: &mut RelayerStageStats,
     ) -> crate::proxy::Result<()> {
         match subscribe_packets_resp.msg {
@@ -458,16 +737,21 @@ impl RelayerStage {
                     return Ok(());
                 }
 
-                let packet_batch = PacketBatch::new(
-                    proto_batch
-                        .packets
-                        .into_iter()
-                        .map(proto_packet_to_packet)
-                        .collect(),
+                let num_packets = proto_batch.packets.len();
+                let packets = Self::dedup_packets(
+                    proto_batch.packets.into_iter().map(proto_packet_to_packet),
+                    packet_cache,
                 );
+                saturating_add_assign!(relayer_stats.num_packets, num_packets as u64);
+                saturating_add_assign!(
+                    relayer_stats.num_duplicate_packets,
+                    (num_packets - packets.len()) as u64
+                );
+                if packets.is_empty() {
+                    return Ok(());
+                }
 
-                saturating_add_assign!(relayer_stats.num_packets, packet_batch.len() as u64);
-
+                let packet_batch = PacketBatch::new(packets);
                 if trust_packets {
                     banking_packet_sender
                         .send(Arc::new((vec![packet_batch], None)))
@@ -482,16 +766,39 @@ impl RelayerStage {
                 saturating_add_assign!(relayer_stats.num_heartbeats, 1);
 
                 *last_heartbeat_ts = Instant::now();
-                heartbeat_tx
-                    .send(heartbeat_event)
-                    .map_err(|_| ProxyError::HeartbeatChannelError)?;
+                let advertised = relayer_states
+                    .lock()
+                    .unwrap()
+                    .heartbeat(relayer_url, *last_heartbeat_ts);
+                if advertised {
+                    heartbeat_tx
+                        .send(heartbeat_event)
+                        .map_err(|_| ProxyError::HeartbeatChannelError)?;
+                }
             }
         }
         Ok(())
     }
 
+    /// Drops packets identical to one recently received, from this or another relayer.
+    ///
+    /// The whole packet is hashed rather than keyed by signature, since packets aren't verified
+    /// yet: a forged packet carrying a copied signature must not suppress the real one.
+    fn dedup_packets(
+        packets: impl Iterator<Item = Packet>,
+        packet_cache: &PacketCache,
+    ) -> Vec<Packet> {
+        let mut packet_cache = packet_cache.lock().unwrap();
+        packets
+            .filter(|packet| match packet.data(..) {
+                Some(data) => packet_cache.put(hash(data), ()).is_none(),
+                None => true,
+            })
+            .collect()
+    }
+
     pub fn is_valid_relayer_config(config: &RelayerConfig) -> bool {
-        if config.relayer_url.is_empty() {
+        if config.relayer_urls.is_empty() {
             warn!("can't connect to relayer. missing relayer_url.");
             return false;
         }
@@ -503,13 +810,155 @@ impl RelayerStage {
             error!("can't connect to relayer. expected heartbeat interval must be greater than 0.");
             return false;
         }
-        if let Err(e) = Endpoint::from_str(&config.relayer_url) {
-            error!(
-                "can't connect to relayer. error creating relayer endpoint - {}",
-                e.to_string()
-            );
-            return false;
+        let mut relayer_urls = HashSet::with_capacity(config.relayer_urls.len());
+        for relayer_url in &config.relayer_urls {
+            if !relayer_urls.insert(relayer_url) {
+                error!("can't connect to relayer {relayer_url}. it's configured more than once.");
+                return false;
+            }
+            if let Err(e) = Endpoint::from_str(relayer_url) {
+                error!(
+                    "can't connect to relayer {}. error creating relayer endpoint - {}",
+                    relayer_url,
+                    e.to_string()
+                );
+                return false;
+            }
         }
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{hash::Hash, system_transaction},
+    };
+
+    fn relayer_states(relayer_urls: &[&str]) -> RelayerStates {
+        let mut relayer_states = RelayerStates::default();
+        relayer_states.reset(
+            &relayer_urls
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        );
+        relayer_states
+    }
+
+    fn advertised(relayer_states: &RelayerStates) -> Option<&str> {
+        relayer_states
+            .relayers
+            .iter()
+            .find(|relayer| relayer.advertised)
+            .map(|relayer| relayer.relayer_url.as_str())
+    }
+
+    #[test]
+    fn test_advertise_healthiest_relayer() {
+        let tpu_addresses = (
+            "10.0.0.1:8001".parse().unwrap(),
+            "10.0.0.1:8002".parse().unwrap(),
+        );
+        let mut relayer_states = relayer_states(&["http://a", "http://b"]);
+        let now = Instant::now();
+        assert!(!relayer_states.heartbeat("http://a", now));
+
+        relayer_states.connected("http://b", tpu_addresses);
+        assert!(relayer_states.heartbeat("http://b", now));
+        // Stays on the advertised relayer while the others are no healthier.
+        relayer_states.connected("http://a", tpu_addresses);
+        assert!(!relayer_states.heartbeat("http://a", now));
+        assert!(relayer_states.heartbeat("http://b", now));
+
+        // Fails over to the relayer that missed fewer heartbeats.
+        relayer_states.missed_heartbeat("http://b", now);
+        assert_eq!(advertised(&relayer_states), Some("http://a"));
+        relayer_states.missed_heartbeat("http://a", now);
+        assert_eq!(advertised(&relayer_states), Some("http://a"));
+        relayer_states.missed_heartbeat("http://a", now);
+        assert_eq!(advertised(&relayer_states), Some("http://b"));
+        relayer_states.missed_heartbeat("http://b", now + Duration::from_secs(1));
+        assert_eq!(advertised(&relayer_states), Some("http://b"));
+
+        // Misses older than the window are forgiven.
+        let later = now + MISSED_HEARTBEAT_WINDOW + Duration::from_millis(500);
+        assert!(relayer_states.heartbeat("http://a", later));
+        assert_eq!(relayer_states.relayers[0].num_missed_heartbeats, 2);
+
+        relayer_states.disconnected("http://a", Some("stream disconnected".to_string()), later);
+        assert_eq!(advertised(&relayer_states), Some("http://b"));
+        assert_eq!(
+            relayer_states.relayers[0].last_error.as_deref(),
+            Some("stream disconnected")
+        );
+
+        relayer_states.disconnected("http://b", None, later);
+        assert_eq!(advertised(&relayer_states), None);
+    }
+
+    #[test]
+    fn test_dedup_packets() {
+        let keypair = Keypair::new();
+        let transactions: Vec<_> = (0..3)
+            .map(|lamports| {
+                system_transaction::transfer(&keypair, &keypair.pubkey(), lamports, Hash::default())
+            })
+            .collect();
+        let packets: Vec<_> = transactions
+            .iter()
+            .map(|transaction| Packet::from_data(None, transaction).unwrap())
+            .collect();
+        let packet_cache = Mutex::new(LruCache::new(PACKET_CACHE_CAPACITY));
+        let deduped = RelayerStage::dedup_packets(packets[..2].iter().cloned(), &packet_cache);
+        assert_eq!(deduped, packets[..2]);
+        let deduped = RelayerStage::dedup_packets(packets[1..].iter().cloned(), &packet_cache);
+        assert_eq!(deduped, packets[2..]);
+
+        // A packet carrying a copied signature doesn't suppress the real transaction.
+        let mut forged = transactions[0].clone();
+        forged.message.recent_blockhash = Hash::new_unique();
+        let real = Packet::from_data(None, &transactions[0]).unwrap();
+        let forged = Packet::from_data(None, &forged).unwrap();
+        let packet_cache = Mutex::new(LruCache::new(PACKET_CACHE_CAPACITY));
+        let deduped = RelayerStage::dedup_packets(
+            [forged.clone(), real.clone(), real.clone()].into_iter(),
+            &packet_cache,
+        );
+        assert_eq!(deduped, vec![forged, real]);
+
+        let mut discarded = Packet::from_data(None, [0u8; 8]).unwrap();
+        discarded.meta_mut().set_discard(true);
+        let deduped = RelayerStage::dedup_packets(
+            [discarded.clone(), discarded.clone()].into_iter(),
+            &packet_cache,
+        );
+        assert_eq!(deduped.len(), 2);
+    }
+
+    #[test]
+    fn test_is_valid_relayer_config() {
+        let mut config = RelayerConfig {
+            expected_heartbeat_interval: Duration::from_millis(500),
+            oldest_allowed_heartbeat: Duration::from_millis(1500),
+            ..RelayerConfig::default()
+        };
+        assert!(!RelayerStage::is_valid_relayer_config(&config));
+
+        config.relayer_urls = vec![
+            "http://relayer-a:11226".to_string(),
+            "http://relayer-b:11226".to_string(),
+        ];
+        assert!(RelayerStage::is_valid_relayer_config(&config));
+
+        config
+            .relayer_urls
+            .push("http://relayer-a:11226".to_string());
+        assert!(!RelayerStage::is_valid_relayer_config(&config));
+
+        config.relayer_urls.pop();
+        config.relayer_urls.push("not a url".to_string());
+        assert!(!RelayerStage::is_valid_relayer_config(&config));
+    }
+}
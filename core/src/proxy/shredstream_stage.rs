@@ -0,0 +1,648 @@
+//! Maintains a connection to a Shredstream service.
+//!
+//! Shredstream sends shreds of other leaders' slots to the validator ahead of turbine for as long
+//! as the validator keeps heartbeating. Each heartbeat names the socket shreds are sent to and
+//! the regions they're sent from, and is answered with the time to live of the subscription.
+
+use {
+    crate::proxy::{
+        auth::{generate_auth_tokens, maybe_refresh_auth_tokens, AuthInterceptor},
+        ProxyError,
+    },
+    jito_protos::proto::{
+        auth::{auth_service_client::AuthServiceClient, Token},
+        shared::Socket,
+        shredstream::{shredstream_client::ShredstreamClient, Heartbeat},
+    },
+    solana_gossip::{cluster_info::ClusterInfo, contact_info::Protocol},
+    solana_sdk::signature::{Keypair, Signer},
+    std::{
+        net::SocketAddr,
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+    tokio::{
+        task,
+        time::{interval, sleep, sleep_until, timeout, Instant},
+    },
+    tonic::{
+        codegen::InterceptedService,
+        transport::{Channel, Endpoint},
+    },
+};
+
+const CONNECTION_TIMEOUT_S: u64 = 10;
+const CONNECTION_BACKOFF_S: u64 = 5;
+/// Lower bound on the time between heartbeats in case the service returns a tiny time to live.
+const MIN_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShredstreamConfig {
+    /// Shredstream URL
+    pub shredstream_url: String,
+
+    /// Regions to receive shreds from.
+    pub desired_regions: Vec<String>,
+
+    /// Socket shreds are sent to. Defaults to the TVU socket advertised in gossip.
+    pub socket: Option<SocketAddr>,
+}
+
+/// State of the connection to Shredstream, exposed through the admin rpc.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShredstreamState {
+    /// Set while heartbeats are being accepted.
+    pub connected: bool,
+
+    /// Socket named in the last heartbeat.
+    pub socket: Option<SocketAddr>,
+
+    /// Time to live returned by the last heartbeat.
+    pub ttl_ms: u32,
+
+    /// Heartbeats accepted over the current connection.
+    pub num_heartbeats: u64,
+
+    /// Error that ended the last connection, if any.
+    pub last_error: Option<String>,
+}
+
+pub struct ShredstreamStage {
+    t_hdls: Vec<JoinHandle<()>>,
+}
+
+impl ShredstreamStage {
+    pub fn new(
+        shredstream_config: Arc<Mutex<ShredstreamConfig>>,
+        shredstream_state: Arc<Mutex<ShredstreamState>>,
+        // The keypair stored here is used to sign auth challenges.
+        cluster_info: Arc<ClusterInfo>,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread = Builder::new()
+            .name("shredstream-stage".to_string())
+            .spawn(move || {
+                let rt = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+
+                rt.block_on(Self::start(
+                    shredstream_config,
+                    shredstream_state,
+                    cluster_info,
+                    exit,
+                ));
+            })
+            .unwrap();
+
+        Self {
+            t_hdls: vec![thread],
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        for t in self.t_hdls {
+            t.join()?;
+        }
+        Ok(())
+    }
+
+    async fn start(
+        shredstream_config: Arc<Mutex<ShredstreamConfig>>,
+        shredstream_state: Arc<Mutex<ShredstreamState>>,
+        cluster_info: Arc<ClusterInfo>,
+        exit: Arc<AtomicBool>,
+    ) {
+        const CONNECTION_TIMEOUT: Duration = Duration::from_secs(CONNECTION_TIMEOUT_S);
+        const CONNECTION_BACKOFF: Duration = Duration::from_secs(CONNECTION_BACKOFF_S);
+
+        let mut error_count: u64 = 0;
+
+        while !exit.load(Ordering::Relaxed) {
+            // Wait until a valid config is supplied (either initially or by admin rpc)
+            let local_shredstream_config = {
+                let shredstream_config = shredstream_config.clone();
+                task::spawn_blocking(move || shredstream_config.lock().unwrap().clone())
+                    .await
+                    .expect("Failed to get execute tokio task.")
+            };
+            if !Self::is_valid_shredstream_config(&local_shredstream_config) {
+                sleep(CONNECTION_BACKOFF).await;
+                continue;
+            }
+
+            let result = Self::connect_auth_and_heartbeat(
+                &local_shredstream_config,
+                &shredstream_config,
+                &shredstream_state,
+                &cluster_info,
+                &exit,
+                &CONNECTION_TIMEOUT,
+            )
+            .await;
+            {
+                let mut state = shredstream_state.lock().unwrap();
+                state.connected = false;
+                state.last_error = result.as_ref().err().map(ToString::to_string);
+            }
+            if let Err(e) = result {
+                match e {
+                    // This error is frequent on hot spares, and the parsed string does not work
+                    // with datapoints (incorrect escaping).
+                    ProxyError::AuthenticationPermissionDenied => {
+                        warn!("shredstream permission denied. not on leader schedule. ignore if hot-spare.")
+                    }
+                    e => {
+                        error_count += 1;
+                        datapoint_warn!(
+                            "shredstream_stage-proxy_error",
+                            ("count", error_count, i64),
+                            ("error", e.to_string(), String),
+                        );
+                    }
+                }
+                sleep(CONNECTION_BACKOFF).await;
+            }
+        }
+    }
+
+    async fn connect_auth_and_heartbeat(
+        local_config: &ShredstreamConfig,
+        global_config: &Arc<Mutex<ShredstreamConfig>>,
+        shredstream_state: &Arc<Mutex<ShredstreamState>>,
+        cluster_info: &Arc<ClusterInfo>,
+        exit: &Arc<AtomicBool>,
+        connection_timeout: &Duration,
+    ) -> crate::proxy::Result<()> {
+        // Get a copy of configs here in case they have changed at runtime
+        let keypair = cluster_info.keypair().clone();
+
+        let backend_endpoint = Self::shredstream_endpoint(&local_config.shredstream_url)?;
+
+        debug!("connecting to auth: {}", local_config.shredstream_url);
+        let auth_channel = timeout(*connection_timeout, backend_endpoint.connect())
+            .await
+            .map_err(|_| ProxyError::AuthenticationConnectionTimeout)?
+            .map_err(|e| ProxyError::AuthenticationConnectionError(e.to_string()))?;
+
+        let mut auth_client = AuthServiceClient::new(auth_channel);
+
+        debug!("generating authentication token");
+        let (access_token, refresh_token) = timeout(
+            *connection_timeout,
+            generate_auth_tokens(&mut auth_client, &keypair),
+        )
+        .await
+        .map_err(|_| ProxyError::AuthenticationTimeout)??;
+
+        datapoint_info!(
+            "shredstream_stage-tokens_generated",
+            ("url", local_config.shredstream_url, String),
+            ("count", 1, i64),
+        );
+
+        debug!(
+            "connecting to shredstream: {}",
+            local_config.shredstream_url
+        );
+        let shredstream_channel = timeout(*connection_timeout, backend_endpoint.connect())
+            .await
+            .map_err(|_| ProxyError::ShredstreamConnectionTimeout)?
+            .map_err(|e| ProxyError::ShredstreamConnectionError(e.to_string()))?;
+
+        let access_token = Arc::new(Mutex::new(access_token));
+        let shredstream_client = ShredstreamClient::with_interceptor(
+            shredstream_channel,
+            AuthInterceptor::new(access_token.clone()),
+        );
+
+        Self::send_heartbeats(
+            shredstream_client,
+            local_config,
+            global_config,
+            shredstream_state,
+            exit,
+            auth_client,
+            access_token,
+            refresh_token,
+            keypair,
+            cluster_info,
+            connection_timeout,
+        )
+        .await
+    }
+
+    fn shredstream_endpoint(shredstream_url: &str) -> crate::proxy::Result<Endpoint> {
+        let mut backend_endpoint = Endpoint::from_shared(shredstream_url.to_string())
+            .map_err(|_| {
+                ProxyError::ShredstreamConnectionError(format!(
+                    "invalid shredstream url value: {shredstream_url}"
+                ))
+            })?
+            .tcp_keepalive(Some(Duration::from_secs(60)));
+        if shredstream_url.starts_with("https") {
+            backend_endpoint = backend_endpoint
+                .tls_config(tonic::transport::ClientTlsConfig::new())
+                .map_err(|_| {
+                    ProxyError::ShredstreamConnectionError(
+                        "failed to set tls_config for shredstream service".to_string(),
+                    )
+                })?;
+        }
+        Ok(backend_endpoint)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn send_heartbeats(
+        mut client: ShredstreamClient<InterceptedService<Channel, AuthInterceptor>>,
+        local_config: &ShredstreamConfig, // local copy of config with current connections
+        global_config: &Arc<Mutex<ShredstreamConfig>>, // guarded reference for detecting run-time updates
+        shredstream_state: &Arc<Mutex<ShredstreamState>>,
+        exit: &Arc<AtomicBool>,
+        mut auth_client: AuthServiceClient<Channel>,
+        access_token: Arc<Mutex<Token>>,
+        mut refresh_token: Token,
+        keypair: Arc<Keypair>,
+        cluster_info: &Arc<ClusterInfo>,
+        connection_timeout: &Duration,
+    ) -> crate::proxy::Result<()> {
+        const METRICS_TICK: Duration = Duration::from_secs(1);
+        let refresh_within_s: u64 = METRICS_TICK.as_secs().saturating_mul(3).saturating_div(2);
+
+        let mut metrics_and_auth_tick = interval(METRICS_TICK);
+        let mut next_heartbeat = Instant::now();
+
+        let mut num_heartbeats: u64 = 0;
+        let mut num_full_refreshes: u64 = 1;
+        let mut num_refresh_access_token: u64 = 0;
+
+        info!("sending shredstream heartbeats");
+
+        while !exit.load(Ordering::Relaxed) {
+            tokio::select! {
+                _ = sleep_until(next_heartbeat) => {
+                    let socket = Self::heartbeat_socket(local_config, cluster_info)?;
+                    let ttl_ms = Self::send_heartbeat(&mut client, socket, &local_config.desired_regions, connection_timeout).await?;
+                    num_heartbeats += 1;
+                    // Heartbeat twice per time to live so a slow heartbeat doesn't interrupt shreds
+                    next_heartbeat = Instant::now() + Self::heartbeat_interval(ttl_ms);
+
+                    *shredstream_state.lock().unwrap() = ShredstreamState {
+                        connected: true,
+                        socket: Some(socket),
+                        ttl_ms,
+                        num_heartbeats,
+                        last_error: None,
+                    };
+                }
+                _ = metrics_and_auth_tick.tick() => {
+                    datapoint_info!(
+                        "shredstream_stage-stats",
+                        ("url", &local_config.shredstream_url, String),
+                        ("num_heartbeats", num_heartbeats, i64),
+                    );
+
+                    if cluster_info.id() != keypair.pubkey() {
+                        return Err(ProxyError::AuthenticationConnectionError("validator identity changed".to_string()));
+                    }
+
+                    let global_config = global_config.clone();
+                    if *local_config != task::spawn_blocking(move || global_config.lock().unwrap().clone())
+                        .await
+                        .unwrap() {
+                        return Err(ProxyError::AuthenticationConnectionError("shredstream config changed".to_string()));
+                    }
+
+                    let (maybe_new_access, maybe_new_refresh) = maybe_refresh_auth_tokens(&mut auth_client,
+                        &access_token,
+                        &refresh_token,
+                        cluster_info,
+                        connection_timeout,
+                        refresh_within_s,
+                    ).await?;
+
+                    if let Some(new_token) = maybe_new_access {
+                        num_refresh_access_token += 1;
+                        datapoint_info!(
+                            "shredstream_stage-refresh_access_token",
+                            ("url", &local_config.shredstream_url, String),
+                            ("count", num_refresh_access_token, i64),
+                        );
+
+                        let access_token = access_token.clone();
+                        task::spawn_blocking(move || *access_token.lock().unwrap() = new_token)
+                            .await
+                            .unwrap();
+                    }
+                    if let Some(new_token) = maybe_new_refresh {
+                        num_full_refreshes += 1;
+                        datapoint_info!(
+                            "shredstream_stage-tokens_generated",
+                            ("url", &local_config.shredstream_url, String),
+                            ("count", num_full_refreshes, i64),
+                        );
+                        refresh_token = new_token;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the time to live of the heartbeat.
+    async fn send_heartbeat(
+        client: &mut ShredstreamClient<InterceptedService<Channel, AuthInterceptor>>,
+        socket: SocketAddr,
+        desired_regions: &[String],
+        connection_timeout: &Duration,
+    ) -> crate::proxy::Result<u32> {
+        let heartbeat = Heartbeat {
+            socket: Some(Socket {
+                ip: socket.ip().to_string(),
+                port: socket.port() as i64,
+            }),
+            regions: desired_regions.to_vec(),
+        };
+        let response = timeout(*connection_timeout, client.send_heartbeat(heartbeat))
+            .await
+            .map_err(|_| ProxyError::MethodTimeout("shredstream_send_heartbeat".to_string()))?
+            .map_err(|e| ProxyError::MethodError(e.to_string()))?
+            .into_inner();
+        Ok(response.ttl_ms)
+    }
+
+    fn heartbeat_socket(
+        config: &ShredstreamConfig,
+        cluster_info: &ClusterInfo,
+    ) -> crate::proxy::Result<SocketAddr> {
+        match config.socket {
+            Some(socket) => Ok(socket),
+            None => cluster_info
+                .my_contact_info()
+                .tvu(Protocol::UDP)
+                .map_err(|e| ProxyError::InvalidData(format!("missing tvu socket: {e:?}"))),
+        }
+    }
+
+    fn heartbeat_interval(ttl_ms: u32) -> Duration {
+        Duration::from_millis(u64::from(ttl_ms / 2)).max(MIN_HEARTBEAT_INTERVAL)
+    }
+
+    pub fn is_valid_shredstream_config(config: &ShredstreamConfig) -> bool {
+        if config.shredstream_url.is_empty() {
+            return false;
+        }
+        if config.desired_regions.is_empty() {
+            error!("can't connect to shredstream. missing desired regions.");
+            return false;
+        }
+        if let Err(e) = Endpoint::from_str(&config.shredstream_url) {
+            error!(
+                "can't connect to shredstream. error creating shredstream endpoint - {}",
+                e.to_string()
+            );
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        chrono::Utc,
+        futures::stream,
+        jito_protos::proto::{
+            auth::{
+                auth_service_server::{AuthService, AuthServiceServer},
+                GenerateAuthChallengeRequest, GenerateAuthChallengeResponse,
+                GenerateAuthTokensRequest, GenerateAuthTokensResponse, RefreshAccessTokenRequest,
+                RefreshAccessTokenResponse,
+            },
+            shredstream::{
+                shredstream_server::{Shredstream, ShredstreamServer},
+                HeartbeatResponse,
+            },
+        },
+        solana_gossip::contact_info::ContactInfo,
+        solana_streamer::socket::SocketAddrSpace,
+        tokio::{net::TcpListener, sync::mpsc},
+        tonic::{transport::Server, Request, Response, Status},
+    };
+
+    /// Shredstream service that hands heartbeats to the test.
+    struct MockShredstream {
+        heartbeat_sender: mpsc::UnboundedSender<Heartbeat>,
+        ttl_ms: u32,
+    }
+
+    #[tonic::async_trait]
+    impl Shredstream for MockShredstream {
+        async fn send_heartbeat(
+            &self,
+            request: Request<Heartbeat>,
+        ) -> Result<Response<HeartbeatResponse>, Status> {
+            self.heartbeat_sender.send(request.into_inner()).unwrap();
+            Ok(Response::new(HeartbeatResponse {
+                ttl_ms: self.ttl_ms,
+            }))
+        }
+    }
+
+    /// Auth service that hands out long lived tokens to anyone.
+    struct MockAuthService;
+
+    #[tonic::async_trait]
+    impl AuthService for MockAuthService {
+        async fn generate_auth_challenge(
+            &self,
+            _request: Request<GenerateAuthChallengeRequest>,
+        ) -> Result<Response<GenerateAuthChallengeResponse>, Status> {
+            Ok(Response::new(GenerateAuthChallengeResponse {
+                challenge: "challenge".to_string(),
+            }))
+        }
+
+        async fn generate_auth_tokens(
+            &self,
+            _request: Request<GenerateAuthTokensRequest>,
+        ) -> Result<Response<GenerateAuthTokensResponse>, Status> {
+            let token = || Token {
+                value: "token".to_string(),
+                expires_at_utc: Some(prost_types::Timestamp {
+                    seconds: Utc::now().timestamp() + 3600,
+                    nanos: 0,
+                }),
+            };
+            Ok(Response::new(GenerateAuthTokensResponse {
+                access_token: Some(token()),
+                refresh_token: Some(token()),
+            }))
+        }
+
+        async fn refresh_access_token(
+            &self,
+            _request: Request<RefreshAccessTokenRequest>,
+        ) -> Result<Response<RefreshAccessTokenResponse>, Status> {
+            Err(Status::unimplemented("refresh_access_token"))
+        }
+    }
+
+    /// Serves a [MockShredstream] and a [MockAuthService] on a local port and returns its url.
+    async fn start_mock_shredstream(shredstream: MockShredstream) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let shredstream_url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = stream::unfold(listener, |listener| async move {
+            let connection = listener.accept().await.map(|(connection, _)| connection);
+            Some((connection, listener))
+        });
+        task::spawn(
+            Server::builder()
+                .add_service(ShredstreamServer::new(shredstream))
+                .add_service(AuthServiceServer::new(MockAuthService))
+                .serve_with_incoming(incoming),
+        );
+        shredstream_url
+    }
+
+    #[tokio::test]
+    async fn test_send_heartbeat() {
+        let (heartbeat_sender, mut heartbeat_receiver) = mpsc::unbounded_channel();
+        let shredstream_url = start_mock_shredstream(MockShredstream {
+            heartbeat_sender,
+            ttl_ms: 500,
+        })
+        .await;
+
+        let channel = ShredstreamStage::shredstream_endpoint(&shredstream_url)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = ShredstreamClient::with_interceptor(
+            channel,
+            AuthInterceptor::new(Arc::new(Mutex::new(Token::default()))),
+        );
+        let socket = "10.0.0.1:8001".parse().unwrap();
+        let ttl_ms = ShredstreamStage::send_heartbeat(
+            &mut client,
+            socket,
+            &["amsterdam".to_string(), "ny".to_string()],
+            &Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(ttl_ms, 500);
+
+        let heartbeat = heartbeat_receiver.try_recv().unwrap();
+        assert_eq!(
+            heartbeat.socket,
+            Some(Socket {
+                ip: "10.0.0.1".to_string(),
+                port: 8001,
+            })
+        );
+        assert_eq!(heartbeat.regions, vec!["amsterdam", "ny"]);
+    }
+
+    #[tokio::test]
+    async fn test_enable_shredstream_at_runtime() {
+        let (heartbeat_sender, mut heartbeat_receiver) = mpsc::unbounded_channel();
+        let shredstream_url = start_mock_shredstream(MockShredstream {
+            heartbeat_sender,
+            ttl_ms: 500,
+        })
+        .await;
+
+        let keypair = Arc::new(Keypair::new());
+        let cluster_info = Arc::new(ClusterInfo::new(
+            ContactInfo::new(keypair.pubkey(), 0, 0),
+            keypair,
+            SocketAddrSpace::Unspecified,
+        ));
+        let shredstream_config = Arc::new(Mutex::new(ShredstreamConfig::default()));
+        let shredstream_state = Arc::new(Mutex::new(ShredstreamState::default()));
+        let exit = Arc::new(AtomicBool::new(false));
+        let stage_task = task::spawn(ShredstreamStage::start(
+            shredstream_config.clone(),
+            shredstream_state.clone(),
+            cluster_info,
+            exit.clone(),
+        ));
+
+        // The stage idles without a config.
+        sleep(Duration::from_secs(1)).await;
+        assert!(heartbeat_receiver.try_recv().is_err());
+        assert_eq!(
+            *shredstream_state.lock().unwrap(),
+            ShredstreamState::default()
+        );
+
+        *shredstream_config.lock().unwrap() = ShredstreamConfig {
+            shredstream_url,
+            desired_regions: vec!["ny".to_string()],
+            socket: Some("10.0.0.1:8001".parse().unwrap()),
+        };
+        let heartbeat = timeout(
+            Duration::from_secs(CONNECTION_BACKOFF_S * 3),
+            heartbeat_receiver.recv(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(heartbeat.regions, vec!["ny"]);
+        assert_eq!(
+            heartbeat.socket,
+            Some(Socket {
+                ip: "10.0.0.1".to_string(),
+                port: 8001,
+            })
+        );
+        // The state is updated once the heartbeat is answered.
+        timeout(Duration::from_secs(10), async {
+            while !shredstream_state.lock().unwrap().connected {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(shredstream_state.lock().unwrap().ttl_ms, 500);
+
+        exit.store(true, Ordering::Relaxed);
+        timeout(Duration::from_secs(10), stage_task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_heartbeat_interval() {
+        assert_eq!(
+            ShredstreamStage::heartbeat_interval(1_000),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            ShredstreamStage::heartbeat_interval(0),
+            MIN_HEARTBEAT_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_is_valid_shredstream_config() {
+        let mut config = ShredstreamConfig::default();
+        assert!(!ShredstreamStage::is_valid_shredstream_config(&config));
+
+        config.shredstream_url = "http://shredstream:1002".to_string();
+        assert!(!ShredstreamStage::is_valid_shredstream_config(&config));
+
+        config.desired_regions = vec!["amsterdam".to_string()];
+        assert!(ShredstreamStage::is_valid_shredstream_config(&config));
+    }
+}
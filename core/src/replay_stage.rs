@@ -66,6 +66,9 @@ use {
         prioritization_fee_cache::PrioritizationFeeCache,
         vote_sender_types::ReplayVoteSender,
     },
+    solana_runtime_plugin::runtime_plugin_event::{
+        BankInfo, RuntimePluginEvent, RuntimePluginEventSender,
+    },
     solana_sdk::{
         clock::{BankId, Slot, MAX_PROCESSING_AGE, NUM_CONSECUTIVE_LEADER_SLOTS},
         hash::Hash,
@@ -269,6 +272,7 @@ pub struct ReplayStageConfig {
     pub wait_to_vote_slot: Option<Slot>,
     pub replay_forks_threads: NonZeroUsize,
     pub replay_transactions_threads: NonZeroUsize,
+    pub runtime_plugin_event_sender: Option<RuntimePluginEventSender>,
 }
 
 /// Timing information for the ReplayStage main processing loop
@@ -554,6 +558,7 @@ impl ReplayStage {
             wait_to_vote_slot,
             replay_forks_threads,
             replay_transactions_threads,
+            runtime_plugin_event_sender,
         } = config;
 
         trace!("replay stage");
@@ -716,6 +721,7 @@ impl ReplayStage {
                     &replay_tx_thread_pool,
                     &prioritization_fee_cache,
                     &mut purge_repair_slot_counter,
+                    &runtime_plugin_event_sender,
                 );
                 replay_active_banks_time.stop();
 
@@ -982,6 +988,7 @@ impl ReplayStage {
                         &mut epoch_slots_frozen_slots,
                         &drop_bank_sender,
                         wait_to_vote_slot,
+                        &runtime_plugin_event_sender,
                     ) {
                         error!("Unable to set root: {e}");
                         return;
@@ -1134,6 +1141,7 @@ impl ReplayStage {
                         &banking_tracer,
                         has_new_vote_been_rooted,
                         transaction_status_sender.is_some(),
+                        &runtime_plugin_event_sender,
                     );
 
                     let poh_bank = poh_recorder.read().unwrap().bank();
@@ -2054,6 +2062,7 @@ impl ReplayStage {
         banking_tracer: &Arc<BankingTracer>,
         has_new_vote_been_rooted: bool,
         track_transaction_indexes: bool,
+        runtime_plugin_event_sender: &Option<RuntimePluginEventSender>,
     ) -> bool {
         // all the individual calls to poh_recorder.read() are designed to
         // increase granularity, decrease contention
@@ -2189,6 +2198,12 @@ impl ReplayStage {
                 .write()
                 .unwrap()
                 .set_bank(tpu_bank, track_transaction_indexes);
+            if let Some(sender) = runtime_plugin_event_sender {
+                sender.send(RuntimePluginEvent::SlotLeaderStart {
+                    slot: poh_slot,
+                    leader: *my_pubkey,
+                });
+            }
             true
         } else {
             error!("{} No next leader found", my_pubkey);
@@ -2372,6 +2387,7 @@ impl ReplayStage {
         epoch_slots_frozen_slots: &mut EpochSlotsFrozenSlots,
         drop_bank_sender: &Sender<Vec<BankWithScheduler>>,
         wait_to_vote_slot: Option<Slot>,
+        runtime_plugin_event_sender: &Option<RuntimePluginEventSender>,
     ) -> Result<(), SetRootError> {
         if bank.is_empty() {
             datapoint_info!("replay_stage-voted_empty_bank", ("slot", bank.slot(), i64));
@@ -2433,6 +2449,9 @@ impl ReplayStage {
             blockstore.slots_stats.mark_rooted(new_root);
 
             rpc_subscriptions.notify_roots(rooted_slots);
+            if let Some(sender) = runtime_plugin_event_sender {
+                sender.send(RuntimePluginEvent::NewRoot(BankInfo::new(&root_bank)));
+            }
             if let Some(sender) = bank_notification_sender {
                 sender
                     .sender
@@ -3020,6 +3039,7 @@ impl ReplayStage {
         replay_result_vec: &[ReplaySlotFromBlockstore],
         purge_repair_slot_counter: &mut PurgeRepairSlotCounter,
         my_pubkey: &Pubkey,
+        runtime_plugin_event_sender: &Option<RuntimePluginEventSender>,
     ) -> bool {
         // TODO: See if processing of blockstore replay results and bank completion can be made thread safe.
         let mut did_complete_bank = false;
@@ -3234,6 +3254,15 @@ impl ReplayStage {
                         .send(BankNotification::Frozen(bank.clone_without_scheduler()))
                         .unwrap_or_else(|err| warn!("bank_notification_sender failed: {:?}", err));
                 }
+                if let Some(sender) = runtime_plugin_event_sender {
+                    sender.send(RuntimePluginEvent::BankFrozen(BankInfo::new(bank)));
+                    if bank.collector_id() == my_pubkey {
+                        sender.send(RuntimePluginEvent::SlotLeaderEnd {
+                            slot: bank.slot(),
+                            leader: *my_pubkey,
+                        });
+                    }
+                }
                 blockstore_processor::cache_block_meta(bank, cache_block_meta_sender);
 
                 let bank_hash = bank.hash();
@@ -3324,6 +3353,7 @@ impl ReplayStage {
         replay_tx_thread_pool: &ThreadPool,
         prioritization_fee_cache: &PrioritizationFeeCache,
         purge_repair_slot_counter: &mut PurgeRepairSlotCounter,
+        runtime_plugin_event_sender: &Option<RuntimePluginEventSender>,
     ) -> bool /* completed a bank */ {
         let active_bank_slots = bank_forks.read().unwrap().active_bank_slots();
         let num_active_banks = active_bank_slots.len();
@@ -3404,6 +3434,7 @@ impl ReplayStage {
             &replay_result_vec,
             purge_repair_slot_counter,
             my_pubkey,
+            runtime_plugin_event_sender,
         )
     }
 
@@ -4226,6 +4257,7 @@ pub(crate) mod tests {
             commitment::{BlockCommitment, VOTE_THRESHOLD_SIZE},
             genesis_utils::{GenesisConfigInfo, ValidatorVoteKeypairs},
         },
+        solana_runtime_plugin::runtime_plugin_event::runtime_plugin_event_channel,
         solana_sdk::{
             clock::NUM_CONSECUTIVE_LEADER_SLOTS,
             genesis_config,
@@ -8419,6 +8451,7 @@ pub(crate) mod tests {
             &banking_tracer,
             has_new_vote_been_rooted,
             track_transaction_indexes,
+            &None,
         ));
     }
 
@@ -9089,6 +9122,7 @@ pub(crate) mod tests {
             &banking_tracer,
             has_new_vote_been_rooted,
             track_transaction_indexes,
+            &None,
         ));
 
         // Register another slots worth of ticks  with PoH recorder
@@ -9103,6 +9137,8 @@ pub(crate) mod tests {
 
         // We should now start leader for dummy_slot + 1
         let good_slot = dummy_slot + 1;
+        let (runtime_plugin_event_sender, runtime_plugin_event_receiver) =
+            runtime_plugin_event_channel();
         assert!(ReplayStage::maybe_start_leader(
             &my_pubkey,
             &bank_forks,
@@ -9116,7 +9152,13 @@ pub(crate) mod tests {
             &banking_tracer,
             has_new_vote_been_rooted,
             track_transaction_indexes,
+            &Some(runtime_plugin_event_sender),
         ));
+        assert_matches!(
+            runtime_plugin_event_receiver.try_recv(),
+            Ok(RuntimePluginEvent::SlotLeaderStart { slot, leader })
+                if slot == good_slot && leader == my_pubkey
+        );
         // Get the new working bank, which is also the new leader bank/slot
         let working_bank = bank_forks.read().unwrap().working_bank();
         // The new bank's slot must NOT be dummy_slot as the blockstore already
@@ -10,6 +10,7 @@ pub use solana_perf::sigverify::{
 use {
     crate::{
         banking_trace::{BankingPacketBatch, BankingPacketSender},
+        proxy::packet_relay::PacketRelaySender,
         sigverify_stage::{SigVerifier, SigVerifyServiceError},
     },
     solana_perf::{cuda_runtime::PinnedVec, packet::PacketBatch, recycler::Recycler, sigverify},
@@ -57,6 +58,8 @@ impl SigverifyTracerPacketStats {
 
 pub struct TransactionSigVerifier {
     packet_sender: BankingPacketSender,
+    // Also receives the verified packets, to relay them to block engines
+    packet_relay_sender: Option<PacketRelaySender>,
     tracer_packet_stats: SigverifyTracerPacketStats,
     recycler: Recycler<TxOffset>,
     recycler_out: Recycler<PinnedVec<u8>>,
@@ -70,10 +73,20 @@ impl TransactionSigVerifier {
         new_self
     }
 
+    pub fn new_with_packet_relay(
+        packet_sender: BankingPacketSender,
+        packet_relay_sender: PacketRelaySender,
+    ) -> Self {
+        let mut new_self = Self::new(packet_sender);
+        new_self.packet_relay_sender = Some(packet_relay_sender);
+        new_self
+    }
+
     pub fn new(packet_sender: BankingPacketSender) -> Self {
         init();
         Self {
             packet_sender,
+            packet_relay_sender: None,
             tracer_packet_stats: SigverifyTracerPacketStats::default(),
             recycler: Recycler::warmed(50, 4096),
             recycler_out: Recycler::warmed(50, 4096),
@@ -127,10 +140,12 @@ impl SigVerifier for TransactionSigVerifier {
         packet_batches: Vec<PacketBatch>,
     ) -> Result<(), SigVerifyServiceError<Self::SendType>> {
         let tracer_packet_stats_to_send = std::mem::take(&mut self.tracer_packet_stats);
-        self.packet_sender.send(BankingPacketBatch::new((
-            packet_batches,
-            Some(tracer_packet_stats_to_send),
-        )))?;
+        let banking_packet_batch =
+            BankingPacketBatch::new((packet_batches, Some(tracer_packet_stats_to_send)));
+        if let Some(packet_relay_sender) = &self.packet_relay_sender {
+            packet_relay_sender.send(&banking_packet_batch);
+        }
+        self.packet_sender.send(banking_packet_batch)?;
         Ok(())
     }
 
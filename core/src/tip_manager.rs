@@ -1,7 +1,11 @@
+#[cfg(feature = "dev-context-only-utils")]
+use qualifier_attr::qualifiers;
 use {
-    crate::proxy::block_engine_stage::BlockBuilderFeeInfo,
+    crate::packet_bundle::BlockBuilderFeeInfo,
     anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas},
-    funnel::{instructions::become_receiver::BecomeReceiverAccounts, Funnel},
+    funnel::{
+        instructions::become_receiver::BecomeReceiverAccounts, Funnel, FunnelConfig, FunnelError,
+    },
     jito_tip_distribution::sdk::{
         derive_config_account_address, derive_tip_distribution_account_address,
         instruction::{
@@ -16,31 +20,39 @@ use {
         TIP_ACCOUNT_SEED_5, TIP_ACCOUNT_SEED_6, TIP_ACCOUNT_SEED_7,
     },
     log::warn,
-    solana_bundle::{derive_bundle_id_from_sanitized_transactions, SanitizedBundle, TipError},
+    solana_bundle::{
+        bundle_execution::LoadAndExecuteBundleError, derive_bundle_id_from_sanitized_transactions,
+        BundleExecutionError, SanitizedBundle, TipError,
+    },
     solana_gossip::cluster_info::ClusterInfo,
-    solana_ledger::{blockstore::Blockstore, leader_schedule_cache::LeaderScheduleCache},
+    solana_ledger::{
+        blockstore::Blockstore, blockstore_meta::FunnelPayment,
+        leader_schedule_cache::LeaderScheduleCache,
+    },
     solana_runtime::bank::Bank,
     solana_sdk::{
         account::ReadableAccount,
         clock::Slot,
-        instruction::Instruction,
+        instruction::{Instruction, InstructionError},
         pubkey::Pubkey,
         signature::Keypair,
         signer::Signer,
         stake_history::Epoch,
         system_instruction, system_program,
-        transaction::{MessageHash, SanitizedTransaction, Transaction, VersionedTransaction},
+        transaction::{
+            MessageHash, SanitizedTransaction, Transaction, TransactionError, VersionedTransaction,
+        },
     },
+    solana_svm::transaction_processing_result::TransactionProcessingResultExtensions,
     solana_transaction_status::RewardType,
-    std::{collections::HashSet, sync::Arc},
+    std::{
+        collections::{BTreeMap, HashSet},
+        sync::{Arc, Mutex},
+    },
 };
 
 pub type Result<T> = std::result::Result<T, TipError>;
 
-fn calculate_funnel_take(reward: u64) -> u64 {
-    reward / 10
-}
-
 #[derive(Debug, Clone)]
 struct TipPaymentProgramInfo {
     program_id: Pubkey,
@@ -90,9 +102,70 @@ impl Default for TipDistributionAccountConfig {
     }
 }
 
+/// The leader slots this validator produced blocks in during the current and previous epoch, and
+/// the identity it produced each as, so the slots of an identity swapped out by `setIdentity`
+/// still get settled with the funnel.
+///
+/// The history is only kept in memory: after a restart, the slots we produced as prior
+/// identities are forgotten and left unsettled unless those identities settle them.
+#[derive(Debug, Default)]
+struct IdentityHistory {
+    produced_slots: BTreeMap<Slot, Pubkey>,
+}
+
+impl IdentityHistory {
+    /// Records that we're producing the block of `slot` as `identity`, and forgets the slots
+    /// before `oldest_slot`.
+    fn observe(&mut self, identity: &Pubkey, slot: Slot, oldest_slot: Slot) {
+        self.produced_slots.insert(slot, *identity);
+        self.produced_slots = self.produced_slots.split_off(&oldest_slot);
+    }
+
+    /// Returns the identity we produced the block of `slot` as.
+    fn identity(&self, slot: Slot) -> Option<Pubkey> {
+        self.produced_slots.get(&slot).copied()
+    }
+
+    /// Returns the unsettled slots before `slot` we produced as identities other than
+    /// `identity`, grouped by identity.
+    fn prior(&self, identity: &Pubkey, slot: Slot) -> BTreeMap<Pubkey, Vec<Slot>> {
+        let mut prior: BTreeMap<Pubkey, Vec<Slot>> = BTreeMap::new();
+        for (produced_slot, produced_identity) in self.produced_slots.range(..slot) {
+            if produced_identity != identity {
+                prior
+                    .entry(*produced_identity)
+                    .or_default()
+                    .push(*produced_slot);
+            }
+        }
+
+        prior
+    }
+
+    /// Marks `slots` as settled.
+    fn settle(&mut self, slots: &[Slot]) {
+        for slot in slots {
+            self.produced_slots.remove(slot);
+        }
+    }
+}
+
+/// A committed funnel settlement waiting for its slot to be rooted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PendingFunnelPayment {
+    slot: Slot,
+    payment: FunnelPayment,
+    /// Slots we produced as prior identities that the settlement covers. Empty if it paid less
+    /// than it owed, so they stay owed.
+    prior_slots: Vec<Slot>,
+}
+
 #[derive(Clone)]
 pub struct TipManager {
-    rewards: Arc<dyn ReadRewards + Send + Sync>,
+    ledger: Arc<dyn FunnelLedger + Send + Sync>,
+    identity_history: Arc<Mutex<IdentityHistory>>,
+    /// Committed funnel settlements waiting for their slot to be rooted before they're recorded.
+    pending_funnel_payments: Arc<Mutex<Vec<PendingFunnelPayment>>>,
     rewards_split: Option<(u64, u16)>,
     cluster_info: Arc<ClusterInfo>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
@@ -126,8 +199,9 @@ impl Default for TipManagerConfig {
 }
 
 impl TipManager {
+    #[cfg_attr(feature = "dev-context-only-utils", qualifiers(pub))]
     pub(crate) fn new(
-        rewards: Arc<dyn ReadRewards + Send + Sync>,
+        ledger: Arc<dyn FunnelLedger + Send + Sync>,
         cluster_info: Arc<ClusterInfo>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         config: TipManagerConfig,
@@ -163,7 +237,9 @@ impl TipManager {
         let config_pda_and_bump = derive_config_account_address(&tip_distribution_program_id);
 
         TipManager {
-            rewards,
+            ledger,
+            identity_history: Arc::default(),
+            pending_funnel_payments: Arc::default(),
             rewards_split,
             cluster_info,
             leader_schedule_cache,
@@ -226,6 +302,23 @@ impl TipManager {
         &self.tip_accounts
     }
 
+    /// Derives the tip accounts of the tip payment program without building a TipManager.
+    pub fn derive_tip_accounts(tip_payment_program_id: &Pubkey) -> HashSet<Pubkey> {
+        [
+            TIP_ACCOUNT_SEED_0,
+            TIP_ACCOUNT_SEED_1,
+            TIP_ACCOUNT_SEED_2,
+            TIP_ACCOUNT_SEED_3,
+            TIP_ACCOUNT_SEED_4,
+            TIP_ACCOUNT_SEED_5,
+            TIP_ACCOUNT_SEED_6,
+            TIP_ACCOUNT_SEED_7,
+        ]
+        .into_iter()
+        .map(|seed| Pubkey::find_program_address(&[seed], tip_payment_program_id).0)
+        .collect()
+    }
+
     pub fn get_tip_payment_config_account(&self, bank: &Bank) -> Result<Config> {
         let config_data = bank
             .get_account(&self.tip_payment_program_info.config_pda_bump.0)
@@ -241,9 +334,7 @@ impl TipManager {
             .get_account(&funnel)
             .ok_or(TipError::AccountMissing(funnel))?;
 
-        Funnel::try_from_bytes(funnel_data.data())
-            .copied()
-            .map_err(|err| TipError::AnchorError(err.to_string()))
+        Funnel::load(funnel_data.data()).map_err(|err| TipError::AnchorError(err.to_string()))
     }
 
     /// Only called once during contract creation.
@@ -429,7 +520,7 @@ impl TipManager {
 
     /// Builds a transaction that changes the current tip receiver to new_tip_receiver.
     /// The on-chain program will transfer tips sitting in the tip accounts to the tip receiver
-    /// before changing ownership.
+    /// before changing ownership. With a funnel, also returns the settlement the transaction pays.
     pub fn change_tip_receiver_and_block_builder_tx(
         &self,
         new_tip_receiver: &Pubkey,
@@ -437,13 +528,13 @@ impl TipManager {
         keypair: &Keypair,
         block_builder: &Pubkey,
         block_builder_commission: u64,
-    ) -> Result<SanitizedTransaction> {
+    ) -> Result<(SanitizedTransaction, Option<FunnelPayment>)> {
         let jito_config = self.get_tip_payment_config_account(bank)?;
 
         Ok(match self.funnel {
             Some(funnel_key) => {
                 let funnel = Self::get_funnel_account(bank, funnel_key)?;
-                self.build_become_receiver_tx(
+                let (tx, funnel_payment) = self.build_become_receiver_tx(
                     &jito_config.tip_receiver,
                     new_tip_receiver,
                     bank,
@@ -452,20 +543,58 @@ impl TipManager {
                     block_builder,
                     block_builder_commission,
                     (&funnel, funnel_key),
-                )
+                );
+                (tx, Some(funnel_payment))
             }
-            None => self.build_change_tip_receiver_and_block_builder_tx(
-                &jito_config.tip_receiver,
-                new_tip_receiver,
-                bank,
-                keypair,
-                &jito_config.block_builder,
-                block_builder,
-                block_builder_commission,
+            None => (
+                self.build_change_tip_receiver_and_block_builder_tx(
+                    &jito_config.tip_receiver,
+                    new_tip_receiver,
+                    bank,
+                    keypair,
+                    &jito_config.block_builder,
+                    block_builder,
+                    block_builder_commission,
+                ),
+                None,
             ),
         })
     }
 
+    /// Returns the funnel program error that failed the tip crank `bundle`, if the failure came
+    /// from a funnel instruction.
+    pub fn find_funnel_error(
+        bundle: &SanitizedBundle,
+        error: &BundleExecutionError,
+    ) -> Option<FunnelError> {
+        let BundleExecutionError::TransactionFailure(LoadAndExecuteBundleError::TransactionError {
+            signature,
+            execution_result,
+        }) = error
+        else {
+            return None;
+        };
+        let Err(TransactionError::InstructionError(index, InstructionError::Custom(code))) =
+            execution_result.flattened_result()
+        else {
+            return None;
+        };
+
+        let transaction = bundle
+            .transactions
+            .iter()
+            .find(|tx| tx.signature() == signature)?;
+        let (program_id, _) = transaction
+            .message()
+            .program_instructions_iter()
+            .nth(usize::from(index))?;
+
+        match program_id == &funnel::ID {
+            true => FunnelError::from_code(code),
+            false => None,
+        }
+    }
+
     pub fn build_become_receiver_tx(
         &self,
         old_tip_receiver: &Pubkey,
@@ -476,8 +605,9 @@ impl TipManager {
         block_builder: &Pubkey,
         block_builder_commission: u64,
         (funnel, funnel_key): (&Funnel, Pubkey),
-    ) -> SanitizedTransaction {
-        let additional_lamports = self.compute_additional_lamports(bank);
+    ) -> (SanitizedTransaction, FunnelPayment) {
+        let mut funnel_payment =
+            self.compute_funnel_payment(bank, &funnel.config, &keypair.pubkey());
 
         let become_receiver = funnel::instructions::become_receiver::ix(
             BecomeReceiverAccounts {
@@ -490,7 +620,7 @@ impl TipManager {
                 paladin_receiver_new_state: funnel::find_leader_state(new_funnel_receiver).0,
             },
             &funnel.config,
-            additional_lamports,
+            funnel_payment.paid,
         );
         let change_block_builder_ix = Instruction {
             program_id: self.tip_payment_program_info.program_id,
@@ -515,7 +645,7 @@ impl TipManager {
             }
             .to_account_metas(None),
         };
-        SanitizedTransaction::try_from_legacy_transaction(
+        let tx = SanitizedTransaction::try_from_legacy_transaction(
             Transaction::new_signed_with_payer(
                 &[become_receiver, change_block_builder_ix],
                 Some(&keypair.pubkey()),
@@ -525,7 +655,10 @@ impl TipManager {
             bank.get_reserved_account_keys(),
             false,
         )
-        .unwrap()
+        .unwrap();
+        funnel_payment.signature = *tx.signature();
+
+        (tx, funnel_payment)
     }
 
     pub fn build_change_tip_receiver_and_block_builder_tx(
@@ -709,12 +842,15 @@ impl TipManager {
         }
     }
 
+    /// Returns the bundle cranking the tip programs, if they need it, along with the funnel
+    /// settlement it pays. Record the settlement with [`Self::record_funnel_payment`] once the
+    /// bundle is committed.
     pub fn get_tip_programs_crank_bundle(
         &self,
         bank: &Bank,
         keypair: &Keypair,
         block_builder_fee_info: &BlockBuilderFeeInfo,
-    ) -> Result<Option<SanitizedBundle>> {
+    ) -> Result<Option<(SanitizedBundle, Option<FunnelPayment>)>> {
         let maybe_init_tip_distro_account_tx = if self.should_init_tip_distribution_account(bank) {
             debug!("should_init_tip_distribution_account=true");
             Some(self.initialize_tip_distribution_account_tx(bank, keypair))
@@ -741,17 +877,18 @@ impl TipManager {
             }
         };
 
-        let maybe_change_tip_receiver_tx = if requires_updating {
+        let (maybe_change_tip_receiver_tx, funnel_payment) = if requires_updating {
             debug!("change_tip_receiver=true");
-            Some(self.change_tip_receiver_and_block_builder_tx(
+            let (tx, funnel_payment) = self.change_tip_receiver_and_block_builder_tx(
                 &my_tip_receiver,
                 bank,
                 keypair,
                 &block_builder_fee_info.block_builder,
                 block_builder_fee_info.block_builder_commission,
-            )?)
+            )?;
+            (Some(tx), funnel_payment)
         } else {
-            None
+            (None, None)
         };
         debug!(
             "maybe_change_tip_receiver_tx: {:?}",
@@ -770,11 +907,88 @@ impl TipManager {
             Ok(None)
         } else {
             let bundle_id = derive_bundle_id_from_sanitized_transactions(&transactions);
-            Ok(Some(SanitizedBundle {
-                transactions,
-                bundle_id,
-            }))
+            Ok(Some((
+                SanitizedBundle {
+                    transactions,
+                    bundle_id,
+                },
+                funnel_payment,
+            )))
+        }
+    }
+
+    /// Records a funnel settlement committed in `slot`. It's persisted by
+    /// [`Self::record_rooted_funnel_payments`] once `slot` is rooted, so settlements still pending
+    /// at shutdown aren't persisted.
+    pub fn record_funnel_payment(&self, slot: Slot, funnel_payment: &FunnelPayment) {
+        let prior_slots = match funnel_payment.slot_range {
+            Some((first_slot, last_slot)) if funnel_payment.paid >= funnel_payment.owed => {
+                let identity_history = self.identity_history.lock().unwrap();
+                identity_history
+                    .identity(slot)
+                    .map(|identity| {
+                        identity_history
+                            .prior(&identity, slot)
+                            .into_values()
+                            .flatten()
+                            .filter(|prior_slot| (first_slot..=last_slot).contains(prior_slot))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            _ => vec![],
+        };
+        self.pending_funnel_payments
+            .lock()
+            .unwrap()
+            .push(PendingFunnelPayment {
+                slot,
+                payment: funnel_payment.clone(),
+                prior_slots,
+            });
+    }
+
+    /// Persists the pending funnel settlements whose slot was rooted so they can be reconciled
+    /// later and settles the prior identities' slots they cover, and drops those whose slot was
+    /// forked off.
+    pub fn record_rooted_funnel_payments(&self) {
+        let mut pending_funnel_payments = self.pending_funnel_payments.lock().unwrap();
+        if pending_funnel_payments.is_empty() {
+            return;
         }
+        let max_root = self.ledger.max_root();
+        pending_funnel_payments.retain(|pending| {
+            if pending.slot > max_root {
+                return true;
+            }
+            if self.ledger.is_root(pending.slot) {
+                self.ledger
+                    .write_funnel_payment(pending.slot, &pending.payment);
+                self.identity_history
+                    .lock()
+                    .unwrap()
+                    .settle(&pending.prior_slots);
+            } else {
+                warn!(
+                    "dropping funnel payment {} in slot {}, which was forked off",
+                    pending.payment.signature, pending.slot
+                );
+            }
+            false
+        });
+    }
+
+    /// Records that we're producing `bank` as its collector, so a later `setIdentity` doesn't
+    /// lose track of the slots we produced as it.
+    pub fn observe_leader_bank(&self, bank: &Bank) {
+        let oldest_slot = bank
+            .epoch_schedule()
+            .get_first_slot_in_epoch(bank.epoch().saturating_sub(1));
+        self.identity_history.lock().unwrap().observe(
+            bank.collector_id(),
+            bank.slot(),
+            oldest_slot,
+        );
     }
 
     fn compute_previous_leader_slot_lamports(&self, bank: &Bank) -> u64 {
@@ -835,104 +1049,151 @@ impl TipManager {
                 }
 
                 // Accumulate the rewards for the block.
-                self.rewards.read_rewards(slot)
+                self.ledger.read_rewards(slot)
             })
             .sum()
     }
 
-    fn compute_additional_lamports(&self, bank: &Bank) -> u64 {
-        // TODO: Do we need to think about handling identity migrations? Should
-        // not result in much missed rewards, right - just last leader sprint.
-        let identity = self.my_identity();
+    /// Computes the settlement the next `become_receiver` transaction signed by `identity` pays
+    /// the funnel: the funnel's commission on the block rewards of our leader slots since the
+    /// last settlement, including the slots we produced as identities we ran before a
+    /// `setIdentity`.
+    /// The signature is left for the caller to fill in.
+    fn compute_funnel_payment(
+        &self,
+        bank: &Bank,
+        funnel_config: &FunnelConfig,
+        identity: &Pubkey,
+    ) -> FunnelPayment {
         let current_slot = bank.slot();
-        let current_epoch = bank.epoch();
-        let current_epoch_start_slot = bank.epoch_schedule().get_first_slot_in_epoch(current_epoch);
-        let previous_epoch = match bank.epoch().checked_sub(1) {
-            Some(epoch) => epoch,
-            None => return 0,
+        let mut payment = FunnelPayment {
+            epoch: bank.epoch(),
+            ..FunnelPayment::default()
         };
-        let previous_epoch_start_slot = bank
-            .epoch_schedule()
-            .get_first_slot_in_epoch(previous_epoch);
 
-        // Get current & previous leader schedules.
-        let Some(previous_leader_slots) = self
-            .leader_schedule_cache
-            .get_epoch_leader_schedule(previous_epoch)
-            .map(|schedule| {
-                schedule
-                    .get_index()
-                    .get(&identity)
-                    .cloned()
-                    .unwrap_or_default()
-            })
-        else {
-            eprintln!("BUG: Previous leader schedule missing?");
-            return 0;
+        // The current identity owes every leader slot since its last settlement, wherever it was
+        // led from.
+        let Some(leader_slots) = self.unsettled_leader_slots(bank, identity) else {
+            return payment;
         };
-        let Some(current_leader_slots) = self
-            .leader_schedule_cache
-            .get_epoch_leader_schedule(current_epoch)
-            .map(|schedule| {
-                schedule
-                    .get_index()
-                    .get(&identity)
-                    .cloned()
-                    .unwrap_or_default()
-            })
-        else {
-            eprintln!("BUG: Current leader schedule missing?");
-            return 0;
-        };
-
-        // Compute the min slot and previous + current leader slots.
-        let both = [
-            (previous_epoch_start_slot, previous_leader_slots),
-            (current_epoch_start_slot, current_leader_slots.clone()),
-        ];
-        let current_only = [(current_epoch_start_slot, current_leader_slots)];
-        let (min_slot, offsets) = match self.highest_paid(bank, &identity) {
-            Some(slot) => (slot, both.as_slice()),
-            // Pay all outstanding only for the current epoch.
-            None => (0, current_only.as_slice()),
-        };
-
-        // Sum our outstanding rewards (i.e. rewards that have not been split
-        // with the funnel).
-        let mut outstanding_rewards = 0;
-        for slot in offsets
+        if leader_slots
             .iter()
-            .flat_map(|(start_slot, offsets)| {
-                offsets.iter().map(|offset| *start_slot + *offset as u64)
-            })
-            .filter(|slot| *slot >= min_slot)
+            .find(|slot| **slot >= current_slot)
+            .is_some_and(|slot| *slot != current_slot)
         {
-            // If we've caught up to the current slot, break.
-            if slot == current_slot {
-                break;
-            }
-            if slot > current_slot {
-                eprintln!("BUG: Current slot not in indexes, are we the leader?");
+            eprintln!("BUG: Current slot not in indexes, are we the leader?");
 
-                return 0;
-            }
+            return payment;
+        }
+        let mut settled_slots: Vec<Slot> = leader_slots
+            .into_iter()
+            .take_while(|slot| *slot < current_slot)
+            .collect();
 
-            // Accumulate the rewards for the block.
-            outstanding_rewards += self.rewards.read_rewards(slot);
+        // Prior identities only owe the slots we produced as them; the rest are led (and settled)
+        // by whichever validator runs them. Slots covered by a settlement on this fork that isn't
+        // rooted yet are skipped.
+        let pending_prior_slots: HashSet<Slot> = self
+            .pending_funnel_payments
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|pending| {
+                pending.slot == current_slot || bank.ancestors.contains_key(&pending.slot)
+            })
+            .flat_map(|pending| pending.prior_slots.iter().copied())
+            .collect();
+        let prior_slots = self
+            .identity_history
+            .lock()
+            .unwrap()
+            .prior(identity, current_slot);
+        for (prior_identity, produced_slots) in prior_slots {
+            let Some(leader_slots) = self.unsettled_leader_slots(bank, &prior_identity) else {
+                continue;
+            };
+            settled_slots.extend(produced_slots.into_iter().filter(|slot| {
+                leader_slots.binary_search(slot).is_ok() && !pending_prior_slots.contains(slot)
+            }));
         }
+        settled_slots.sort_unstable();
+
+        // Sum our outstanding rewards (i.e. rewards that have not been split
+        // with the funnel).
+        let outstanding_rewards: u64 = settled_slots
+            .iter()
+            .map(|slot| self.ledger.read_rewards(*slot))
+            .sum();
 
         // TODO: We need to cap the take such that we do not use the full
         // balance of our identity account affecting rent exemption.
-        let owing = calculate_funnel_take(outstanding_rewards);
-        let identity = bank.get_account(&identity).unwrap_or_default();
+        let owing = funnel_config.commission(outstanding_rewards);
+        let identity = bank.get_account(identity).unwrap_or_default();
         let min_rent_exemption = bank
             .rent_collector()
             .rent
             .minimum_balance(identity.data().len());
 
-        std::cmp::min(
+        payment.slot_range = settled_slots
+            .first()
+            .zip(settled_slots.last())
+            .map(|(first_slot, last_slot)| (*first_slot, *last_slot));
+        payment.rewards = outstanding_rewards;
+        payment.owed = owing;
+        payment.paid = std::cmp::min(
             owing,
             identity.lamports().saturating_sub(min_rent_exemption),
+        );
+
+        payment
+    }
+
+    /// Returns `identity`'s leader slots in the previous and current epoch, in order, from the
+    /// slot its `LeaderState` was last settled in. Without a `LeaderState`, only the current
+    /// epoch is owed.
+    fn unsettled_leader_slots(&self, bank: &Bank, identity: &Pubkey) -> Option<Vec<Slot>> {
+        let current_epoch = bank.epoch();
+        let previous_epoch = current_epoch.checked_sub(1)?;
+
+        // Get current & previous leader schedules.
+        let epoch_leader_slots = |epoch: Epoch| {
+            let start_slot = bank.epoch_schedule().get_first_slot_in_epoch(epoch);
+            self.leader_schedule_cache
+                .get_epoch_leader_schedule(epoch)
+                .map(|schedule| {
+                    schedule
+                        .get_index()
+                        .get(identity)
+                        .map(|offsets| {
+                            offsets
+                                .iter()
+                                .map(|offset| start_slot + *offset as u64)
+                                .collect::<Vec<Slot>>()
+                        })
+                        .unwrap_or_default()
+                })
+        };
+        let Some(previous_leader_slots) = epoch_leader_slots(previous_epoch) else {
+            eprintln!("BUG: Previous leader schedule missing?");
+            return None;
+        };
+        let Some(current_leader_slots) = epoch_leader_slots(current_epoch) else {
+            eprintln!("BUG: Current leader schedule missing?");
+            return None;
+        };
+
+        // Compute the min slot and previous + current leader slots.
+        let (min_slot, leader_slots) = match self.highest_paid(bank, identity) {
+            Some(slot) => (slot, [previous_leader_slots, current_leader_slots].concat()),
+            // Pay all outstanding only for the current epoch.
+            None => (0, current_leader_slots),
+        };
+
+        Some(
+            leader_slots
+                .into_iter()
+                .filter(|slot| *slot >= min_slot)
+                .collect(),
         )
     }
 
@@ -950,11 +1211,18 @@ impl TipManager {
     }
 }
 
-pub(crate) trait ReadRewards {
+/// Where the tip manager reads block rewards and roots from and records funnel settlements to.
+pub trait FunnelLedger {
     fn read_rewards(&self, slot: Slot) -> u64;
+
+    fn is_root(&self, slot: Slot) -> bool;
+
+    fn max_root(&self) -> Slot;
+
+    fn write_funnel_payment(&self, slot: Slot, funnel_payment: &FunnelPayment);
 }
 
-impl ReadRewards for Blockstore {
+impl FunnelLedger for Blockstore {
     fn read_rewards(&self, slot: Slot) -> u64 {
         self.read_rewards(slot)
             .ok()
@@ -969,6 +1237,23 @@ impl ReadRewards for Blockstore {
             .try_into()
             .unwrap()
     }
+
+    fn is_root(&self, slot: Slot) -> bool {
+        self.is_root(slot)
+    }
+
+    fn max_root(&self) -> Slot {
+        self.max_root()
+    }
+
+    fn write_funnel_payment(&self, slot: Slot, funnel_payment: &FunnelPayment) {
+        if let Err(err) = self.write_funnel_payment(slot, funnel_payment) {
+            error!(
+                "failed to record funnel payment {} in slot {}: {:?}",
+                funnel_payment.signature, slot, err
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -987,16 +1272,21 @@ pub(crate) mod tests {
             genesis_config::ClusterType,
             native_token::sol_to_lamports,
             rent::Rent,
+            signature::Signature,
         },
         solana_streamer::socket::SocketAddrSpace,
         solana_vote_program::vote_state::VoteState,
-        std::sync::RwLock,
+        std::{collections::BTreeSet, sync::RwLock},
     };
 
     #[derive(Default)]
-    pub(crate) struct MockBlockstore(pub(crate) Vec<u64>);
+    pub(crate) struct MockBlockstore(
+        pub(crate) Vec<u64>,
+        pub(crate) Vec<(Slot, FunnelPayment)>,
+        pub(crate) BTreeSet<Slot>,
+    );
 
-    impl ReadRewards for RwLock<MockBlockstore> {
+    impl FunnelLedger for RwLock<MockBlockstore> {
         fn read_rewards(&self, slot: Slot) -> u64 {
             self.read()
                 .unwrap()
@@ -1005,6 +1295,18 @@ pub(crate) mod tests {
                 .copied()
                 .unwrap_or(0)
         }
+
+        fn is_root(&self, slot: Slot) -> bool {
+            self.read().unwrap().2.contains(&slot)
+        }
+
+        fn max_root(&self) -> Slot {
+            self.read().unwrap().2.last().copied().unwrap_or_default()
+        }
+
+        fn write_funnel_payment(&self, slot: Slot, funnel_payment: &FunnelPayment) {
+            self.write().unwrap().1.push((slot, funnel_payment.clone()));
+        }
     }
 
     struct TestFixture {
@@ -1014,6 +1316,7 @@ pub(crate) mod tests {
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         tip_manager: TipManager,
         paladin: Pubkey,
+        funnel_config: FunnelConfig,
     }
 
     fn create_fixture(paladin_slots: &[u64]) -> TestFixture {
@@ -1024,7 +1327,7 @@ pub(crate) mod tests {
         // Setup genesis.
         let rent = Rent::default();
         let genesis_config = create_genesis_config_with_leader_ex(
-            sol_to_lamports(1000.0 as f64),
+            sol_to_lamports(1000.0),
             &mint_keypair.pubkey(),
             &leader_keypair.pubkey(),
             &voting_keypair.pubkey(),
@@ -1087,7 +1390,42 @@ pub(crate) mod tests {
         );
 
         // Override the provided leader slots to be our paladin leader.
-        for slot in paladin_slots {
+        set_slot_leaders(
+            &bank,
+            &leader_schedule_cache,
+            paladin_slots,
+            &paladin.pubkey(),
+        );
+
+        TestFixture {
+            bank: Arc::new(bank),
+            blockstore: blockstore.clone(),
+            leader_keypair,
+            leader_schedule_cache: leader_schedule_cache.clone(),
+            tip_manager: TipManager::new(
+                blockstore,
+                cluster_info_paladin,
+                leader_schedule_cache,
+                config,
+            ),
+            paladin: paladin.pubkey(),
+            funnel_config: FunnelConfig {
+                stakers_receiver: Pubkey::new_unique(),
+                holders_receiver: Pubkey::new_unique(),
+                stakers_bps: funnel::DEFAULT_STAKERS_BPS,
+                holders_bps: funnel::DEFAULT_HOLDERS_BPS,
+                commission_bps: funnel::DEFAULT_COMMISSION_BPS,
+            },
+        }
+    }
+
+    fn set_slot_leaders(
+        bank: &Bank,
+        leader_schedule_cache: &LeaderScheduleCache,
+        slots: &[u64],
+        leader: &Pubkey,
+    ) {
+        for slot in slots {
             let (epoch, offset) = bank.get_epoch_and_slot_index(*slot);
             let mut slot_leaders = leader_schedule_cache
                 .get_epoch_leader_schedule(epoch)
@@ -1095,7 +1433,7 @@ pub(crate) mod tests {
                 .get_slot_leaders()
                 .to_vec();
 
-            slot_leaders[offset as usize] = paladin.pubkey();
+            slot_leaders[offset as usize] = *leader;
 
             let leader_schedule = LeaderSchedule::new_from_schedule(slot_leaders);
             *leader_schedule_cache
@@ -1106,20 +1444,50 @@ pub(crate) mod tests {
                 .get_mut(&epoch)
                 .unwrap() = Arc::new(leader_schedule);
         }
+    }
 
-        TestFixture {
-            bank: Arc::new(bank),
-            blockstore: blockstore.clone(),
-            leader_keypair,
-            leader_schedule_cache: leader_schedule_cache.clone(),
-            tip_manager: TipManager::new(
-                blockstore,
-                cluster_info_paladin,
-                leader_schedule_cache,
-                config,
-            ),
-            paladin: paladin.pubkey(),
-        }
+    #[test]
+    fn get_funnel_account_decodes_legacy_layout() {
+        // Arrange.
+        let fixture = create_fixture(&[]);
+        let funnel = Pubkey::new_unique();
+        let receiver = Pubkey::new_unique();
+        let data = [
+            receiver,
+            fixture.funnel_config.stakers_receiver,
+            fixture.funnel_config.holders_receiver,
+        ]
+        .iter()
+        .flat_map(|key| key.to_bytes())
+        .collect::<Vec<_>>();
+        assert_eq!(data.len(), Funnel::LEGACY_LEN);
+        fixture.bank.store_account(
+            &funnel,
+            &Account {
+                lamports: 10u64.pow(9),
+                data,
+                owner: funnel::ID,
+                ..Account::default()
+            }
+            .into(),
+        );
+
+        // Act.
+        let funnel = TipManager::get_funnel_account(&fixture.bank, funnel).unwrap();
+
+        // Assert.
+        assert_eq!(funnel.receiver, receiver);
+        assert_eq!(
+            funnel.config.stakers_receiver,
+            fixture.funnel_config.stakers_receiver
+        );
+        assert_eq!(
+            funnel.config.holders_receiver,
+            fixture.funnel_config.holders_receiver
+        );
+        assert_eq!(funnel.config.stakers_bps, funnel::DEFAULT_STAKERS_BPS);
+        assert_eq!(funnel.config.holders_bps, funnel::DEFAULT_HOLDERS_BPS);
+        assert_eq!(funnel.config.commission_bps, funnel::DEFAULT_COMMISSION_BPS);
     }
 
     #[test]
@@ -1130,7 +1498,8 @@ pub(crate) mod tests {
         // Act.
         let additional = fixture
             .tip_manager
-            .compute_additional_lamports(&fixture.bank);
+            .compute_funnel_payment(&fixture.bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 0);
@@ -1147,7 +1516,10 @@ pub(crate) mod tests {
         );
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&child_bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&child_bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 0);
@@ -1165,10 +1537,35 @@ pub(crate) mod tests {
         fixture.blockstore.write().unwrap().0.push(100);
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&child_bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&child_bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
-        assert_eq!(additional, calculate_funnel_take(100));
+        assert_eq!(additional, fixture.funnel_config.commission(100));
+    }
+
+    #[test]
+    fn compute_additional_lamports_uses_configured_commission() {
+        // Arrange.
+        let mut fixture = create_fixture(&[0]);
+        fixture.funnel_config.commission_bps = 2_500;
+        let child_bank = Bank::new_from_parent(
+            fixture.bank.clone(),
+            &fixture.leader_keypair.pubkey(),
+            fixture.bank.slot() + 1,
+        );
+        fixture.blockstore.write().unwrap().0.push(100);
+
+        // Act.
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&child_bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
+
+        // Assert.
+        assert_eq!(additional, 25);
     }
 
     #[test]
@@ -1195,7 +1592,10 @@ pub(crate) mod tests {
             .unwrap() = Arc::new(leader_schedule);
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&child_bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&child_bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 0);
@@ -1217,10 +1617,13 @@ pub(crate) mod tests {
             .extend(std::iter::repeat(100).take(40));
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&child_bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&child_bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
-        assert_eq!(additional, calculate_funnel_take(400));
+        assert_eq!(additional, fixture.funnel_config.commission(400));
     }
 
     #[test]
@@ -1238,10 +1641,16 @@ pub(crate) mod tests {
             .extend((0..64).map(|i| i * 100));
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
-        assert_eq!(additional, calculate_funnel_take(0 + 1200 + 2000 + 3200));
+        assert_eq!(
+            additional,
+            fixture.funnel_config.commission(1200 + 2000 + 3200)
+        );
     }
 
     #[test]
@@ -1262,7 +1671,10 @@ pub(crate) mod tests {
         bank.store_account(&paladin_leader_state, &Account::default().into());
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 0);
@@ -1286,10 +1698,13 @@ pub(crate) mod tests {
         bank.store_account(&paladin_leader_state, &Account::default().into());
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
-        assert_eq!(additional, calculate_funnel_take(320 + 390));
+        assert_eq!(additional, fixture.funnel_config.commission(320 + 390));
     }
 
     #[test]
@@ -1310,10 +1725,13 @@ pub(crate) mod tests {
         bank.store_account(&paladin_leader_state, &Account::default().into());
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
-        assert_eq!(additional, calculate_funnel_take(340 + 390));
+        assert_eq!(additional, fixture.funnel_config.commission(340 + 390));
     }
 
     #[test]
@@ -1341,7 +1759,10 @@ pub(crate) mod tests {
         );
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 0);
@@ -1372,9 +1793,343 @@ pub(crate) mod tests {
         );
 
         // Act.
-        let additional = fixture.tip_manager.compute_additional_lamports(&bank);
+        let additional = fixture
+            .tip_manager
+            .compute_funnel_payment(&bank, &fixture.funnel_config, &fixture.paladin)
+            .paid;
 
         // Assert.
         assert_eq!(additional, 1);
     }
+
+    #[test]
+    fn compute_funnel_payment_reports_settled_slots_and_shortfall() {
+        // Arrange.
+        let fixture = create_fixture(&[32, 35, 37, 39]);
+        let bank = Bank::new_from_parent(fixture.bank.clone(), &Pubkey::new_unique(), 40);
+        fixture
+            .blockstore
+            .write()
+            .unwrap()
+            .0
+            .extend(std::iter::repeat(100_000).take(40));
+
+        // Leave the paladin leader one lamport above rent exemption.
+        bank.store_account(
+            &fixture.paladin,
+            &Account {
+                lamports: bank.rent_collector().rent.minimum_balance(0) + 1,
+                ..Account::default()
+            }
+            .into(),
+        );
+
+        // Act.
+        let payment = fixture.tip_manager.compute_funnel_payment(
+            &bank,
+            &fixture.funnel_config,
+            &fixture.paladin,
+        );
+        fixture.tip_manager.record_funnel_payment(40, &payment);
+
+        // Assert.
+        let owed = fixture.funnel_config.commission(400_000);
+        assert_eq!(
+            payment,
+            FunnelPayment {
+                epoch: 1,
+                slot_range: Some((32, 39)),
+                rewards: 400_000,
+                owed,
+                paid: 1,
+                signature: Signature::default(),
+            }
+        );
+        assert_eq!(payment.outstanding(), owed - 1);
+    }
+
+    #[test]
+    fn record_funnel_payment_persists_rooted_payments() {
+        // Arrange.
+        let fixture = create_fixture(&[]);
+        let payment = |slot: Slot| FunnelPayment {
+            epoch: 1,
+            slot_range: Some((slot - 4, slot - 1)),
+            rewards: 100,
+            owed: 10,
+            paid: 10,
+            signature: Signature::new_unique(),
+        };
+        let (forked, rooted, unrooted) = (payment(40), payment(44), payment(48));
+
+        // Act.
+        fixture.tip_manager.record_funnel_payment(40, &forked);
+        fixture.tip_manager.record_funnel_payment(44, &rooted);
+        fixture.tip_manager.record_funnel_payment(48, &unrooted);
+        fixture.tip_manager.record_rooted_funnel_payments();
+        assert!(fixture.blockstore.read().unwrap().1.is_empty());
+        fixture.blockstore.write().unwrap().2.extend([36, 44]);
+        fixture.tip_manager.record_rooted_funnel_payments();
+
+        // Assert.
+        assert_eq!(fixture.blockstore.read().unwrap().1, vec![(44, rooted)]);
+        assert_eq!(
+            *fixture.tip_manager.pending_funnel_payments.lock().unwrap(),
+            vec![PendingFunnelPayment {
+                slot: 48,
+                payment: unrooted,
+                prior_slots: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn compute_funnel_payment_settles_prior_identity() {
+        // Arrange.
+        let fixture = create_fixture(&[32, 33, 38]);
+        let new_identity = Pubkey::new_unique();
+        set_slot_leaders(
+            &fixture.bank,
+            &fixture.leader_schedule_cache,
+            &[36, 40],
+            &new_identity,
+        );
+        fixture
+            .blockstore
+            .write()
+            .unwrap()
+            .0
+            .extend(std::iter::repeat(100).take(41));
+
+        // We produce 32 as the paladin, then `setIdentity` before leading 36. Slots 33 and 38 are
+        // led by whichever validator runs the paladin identity now.
+        let bank = Bank::new_from_parent(fixture.bank.clone(), &fixture.paladin, 32);
+        fixture.tip_manager.observe_leader_bank(&bank);
+        let bank = Bank::new_from_parent(fixture.bank.clone(), &new_identity, 36);
+        fixture.tip_manager.observe_leader_bank(&bank);
+        let bank = Bank::new_from_parent(fixture.bank.clone(), &new_identity, 40);
+        bank.store_account(
+            &new_identity,
+            &Account {
+                lamports: 10u64.pow(9),
+                ..Account::default()
+            }
+            .into(),
+        );
+
+        // Act.
+        let payment = fixture.tip_manager.compute_funnel_payment(
+            &bank,
+            &fixture.funnel_config,
+            &new_identity,
+        );
+
+        // Assert.
+        assert_eq!(payment.slot_range, Some((32, 36)));
+        assert_eq!(payment.rewards, 200);
+        assert_eq!(payment.paid, fixture.funnel_config.commission(200));
+    }
+
+    #[test]
+    fn record_funnel_payment_settles_prior_identities_once_rooted() {
+        // Arrange.
+        let fixture = create_fixture(&[32, 33]);
+        let new_identity = Pubkey::new_unique();
+        set_slot_leaders(
+            &fixture.bank,
+            &fixture.leader_schedule_cache,
+            &[36, 40, 41],
+            &new_identity,
+        );
+        fixture
+            .blockstore
+            .write()
+            .unwrap()
+            .0
+            .extend(std::iter::repeat(100_000).take(42));
+        for (slot, identity) in [
+            (32, fixture.paladin),
+            (33, fixture.paladin),
+            (36, new_identity),
+        ] {
+            let bank = Bank::new_from_parent(fixture.bank.clone(), &identity, slot);
+            fixture.tip_manager.observe_leader_bank(&bank);
+        }
+        let bank = Arc::new(Bank::new_from_parent(
+            fixture.bank.clone(),
+            &new_identity,
+            40,
+        ));
+        bank.store_account(
+            &new_identity,
+            &Account {
+                lamports: 10u64.pow(9),
+                ..Account::default()
+            }
+            .into(),
+        );
+        fixture.tip_manager.observe_leader_bank(&bank);
+        let payment = fixture.tip_manager.compute_funnel_payment(
+            &bank,
+            &fixture.funnel_config,
+            &new_identity,
+        );
+        assert_eq!(payment.slot_range, Some((32, 36)));
+        assert_eq!(payment.outstanding(), 0);
+
+        // Act.
+        fixture.tip_manager.record_funnel_payment(40, &payment);
+
+        // Assert.
+        // Until 40 is rooted, forks descending from it don't owe the paladin's slots again, but
+        // other forks still do.
+        let child = Bank::new_from_parent(bank.clone(), &new_identity, 41);
+        let payment = fixture.tip_manager.compute_funnel_payment(
+            &child,
+            &fixture.funnel_config,
+            &new_identity,
+        );
+        // Without a `LeaderState`, the new identity still owes its current epoch slots.
+        assert_eq!(payment.slot_range, Some((36, 40)));
+        assert_eq!(payment.rewards, 200_000);
+        let sibling = Bank::new_from_parent(fixture.bank.clone(), &new_identity, 41);
+        let payment = fixture.tip_manager.compute_funnel_payment(
+            &sibling,
+            &fixture.funnel_config,
+            &new_identity,
+        );
+        assert_eq!(payment.slot_range, Some((32, 40)));
+        assert_eq!(
+            fixture
+                .tip_manager
+                .identity_history
+                .lock()
+                .unwrap()
+                .prior(&new_identity, 41),
+            BTreeMap::from([(fixture.paladin, vec![32, 33])])
+        );
+
+        fixture.blockstore.write().unwrap().2.insert(40);
+        fixture.tip_manager.record_rooted_funnel_payments();
+        assert!(fixture
+            .tip_manager
+            .identity_history
+            .lock()
+            .unwrap()
+            .prior(&new_identity, 41)
+            .is_empty());
+    }
+
+    #[test]
+    fn record_funnel_payment_leaves_prior_identities_owed_when_underpaid() {
+        // Arrange.
+        let fixture = create_fixture(&[32, 33]);
+        let new_identity = Pubkey::new_unique();
+        for (slot, identity) in [
+            (32, fixture.paladin),
+            (33, fixture.paladin),
+            (40, new_identity),
+        ] {
+            let bank = Bank::new_from_parent(fixture.bank.clone(), &identity, slot);
+            fixture.tip_manager.observe_leader_bank(&bank);
+        }
+        let payment = FunnelPayment {
+            epoch: 1,
+            slot_range: Some((32, 33)),
+            rewards: 200,
+            owed: 20,
+            paid: 19,
+            signature: Signature::new_unique(),
+        };
+
+        // Act.
+        fixture.tip_manager.record_funnel_payment(40, &payment);
+        fixture.blockstore.write().unwrap().2.insert(40);
+        fixture.tip_manager.record_rooted_funnel_payments();
+
+        // Assert.
+        assert_eq!(fixture.blockstore.read().unwrap().1, vec![(40, payment)]);
+        assert_eq!(
+            fixture
+                .tip_manager
+                .identity_history
+                .lock()
+                .unwrap()
+                .prior(&new_identity, 41),
+            BTreeMap::from([(fixture.paladin, vec![32, 33])])
+        );
+    }
+
+    #[test]
+    fn identity_history_forgets_slots_before_previous_epoch() {
+        // Arrange.
+        let mut history = IdentityHistory::default();
+        let (first, second, third) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // Act.
+        history.observe(&first, 10, 0);
+        history.observe(&first, 20, 0);
+        history.observe(&second, 30, 0);
+        history.observe(&second, 40, 0);
+        history.observe(&third, 70, 32);
+
+        // Assert.
+        assert_eq!(
+            history.prior(&third, 70),
+            BTreeMap::from([(second, vec![40])])
+        );
+        assert_eq!(history.prior(&third, 40), BTreeMap::new());
+        assert_eq!(history.identity(70), Some(third));
+        assert_eq!(history.identity(30), None);
+    }
+
+    #[test]
+    fn find_funnel_error_decodes_funnel_instruction_errors() {
+        // Arrange.
+        let payer = Keypair::new();
+        let other_program = Pubkey::new_unique();
+        let tx =
+            SanitizedTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                &[
+                    Instruction::new_with_bytes(funnel::ID, &[], vec![]),
+                    Instruction::new_with_bytes(other_program, &[], vec![]),
+                ],
+                Some(&payer.pubkey()),
+                &[&payer],
+                solana_sdk::hash::Hash::default(),
+            ));
+        let signature = *tx.signature();
+        let bundle = SanitizedBundle {
+            transactions: vec![tx],
+            bundle_id: String::default(),
+        };
+        let failure = |index, code| {
+            BundleExecutionError::TransactionFailure(LoadAndExecuteBundleError::TransactionError {
+                signature,
+                execution_result: Box::new(Err(TransactionError::InstructionError(
+                    index,
+                    InstructionError::Custom(code),
+                ))),
+            })
+        };
+
+        // Act & Assert.
+        assert_eq!(
+            TipManager::find_funnel_error(&bundle, &failure(0, 5)),
+            Some(FunnelError::ReceiverMismatch)
+        );
+        assert_eq!(TipManager::find_funnel_error(&bundle, &failure(1, 5)), None);
+        assert_eq!(
+            TipManager::find_funnel_error(&bundle, &failure(0, 1_000)),
+            None
+        );
+        assert_eq!(
+            TipManager::find_funnel_error(&bundle, &BundleExecutionError::LockError),
+            None
+        );
+    }
 }
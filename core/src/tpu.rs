@@ -6,17 +6,21 @@ use {
     crate::{
         banking_stage::BankingStage,
         banking_trace::{BankingTracer, TracerThread},
-        bundle_stage::{bundle_account_locker::BundleAccountLocker, BundleStage},
+        bundle_stage::{
+            amm_registry::SharedAmmRegistry, bundle_account_locker::BundleAccountLocker,
+            sandwich_tracker::SandwichTracker, BundleStage,
+        },
         cluster_info_vote_listener::{
             ClusterInfoVoteListener, DuplicateConfirmedSlotsSender, GossipVerifiedVoteHashSender,
             VerifiedVoteSender, VoteTracker,
         },
         fetch_stage::FetchStage,
-        p3_quic::P3Quic,
+        p3_quic::{P3Config, P3Quic},
         proxy::{
-            block_engine_stage::{BlockBuilderFeeInfo, BlockEngineConfig, BlockEngineStage},
+            block_engine_stage::{BlockEngineConfig, BlockEngineStage},
             fetch_stage_manager::FetchStageManager,
-            relayer_stage::{RelayerConfig, RelayerStage},
+            packet_relay::{PacketRelay, PacketRelayStage},
+            relayer_stage::{RelayerConfig, RelayerStage, RelayerStates},
         },
         sigverify::TransactionSigVerifier,
         sigverify_stage::SigVerifyStage,
@@ -44,12 +48,7 @@ use {
         prioritization_fee_cache::PrioritizationFeeCache,
         vote_sender_types::{ReplayVoteReceiver, ReplayVoteSender},
     },
-    solana_sdk::{
-        clock::Slot,
-        pubkey::Pubkey,
-        quic::NotifyKeyUpdate,
-        signature::{Keypair, Signer},
-    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey, quic::NotifyKeyUpdate, signature::Keypair},
     solana_streamer::{
         nonblocking::{
             quic::{DEFAULT_MAX_STREAMS_PER_MS, DEFAULT_WAIT_FOR_CHUNK_TIMEOUT},
@@ -60,7 +59,10 @@ use {
         },
         streamer::StakedNodes,
     },
-    solana_turbine::broadcast_stage::{BroadcastStage, BroadcastStageType},
+    solana_turbine::{
+        broadcast_stage::{BroadcastStage, BroadcastStageType},
+        shred_receiver::ShredReceivers,
+    },
     std::{
         collections::{HashMap, HashSet},
         net::{SocketAddr, UdpSocket},
@@ -100,6 +102,7 @@ fn calculate_block_cost_limit_reservation(
 pub struct Tpu {
     fetch_stage: FetchStage,
     sigverify_stage: SigVerifyStage,
+    external_sigverify_stage: SigVerifyStage,
     vote_sigverify_stage: SigVerifyStage,
     banking_stage: BankingStage,
     cluster_info_vote_listener: ClusterInfoVoteListener,
@@ -112,6 +115,7 @@ pub struct Tpu {
     relayer_stage: RelayerStage,
     block_engine_stage: BlockEngineStage,
     fetch_stage_manager: FetchStageManager,
+    packet_relay_stage: PacketRelayStage,
     bundle_stage: BundleStage,
     p3_quic: std::thread::JoinHandle<()>,
 }
@@ -156,12 +160,15 @@ impl Tpu {
         _generator_config: Option<GeneratorConfig>, /* vestigial code for replay invalidator */
         block_engine_config: Arc<Mutex<BlockEngineConfig>>,
         relayer_config: Arc<Mutex<RelayerConfig>>,
+        relayer_states: Arc<Mutex<RelayerStates>>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         tip_manager_config: TipManagerConfig,
-        shred_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: Arc<ShredReceivers>,
         preallocated_bundle_cost: u64,
         batch_interval: Duration,
         (p3_socket, p3_mev_socket): (SocketAddr, SocketAddr),
+        p3_config: Arc<Mutex<P3Config>>,
+        amm_registry: SharedAmmRegistry,
     ) -> (Self, Vec<Arc<dyn NotifyKeyUpdate + Sync + Send>>) {
         let TpuSockets {
             transactions: transactions_sockets,
@@ -173,7 +180,7 @@ impl Tpu {
         } = sockets;
 
         // Packets from fetch stage and quic server are intercepted and sent through fetch_stage_manager
-        // If relayer is connected, packets are dropped. If not, packets are forwarded on to packet_sender
+        // If relayer is connected, packets are dropped. If not, packets are forwarded on to tpu_packet_sender
         let (packet_intercept_sender, packet_intercept_receiver) = unbounded();
 
         let (vote_packet_sender, vote_packet_receiver) = unbounded();
@@ -224,7 +231,7 @@ impl Tpu {
             tpu_max_connections_per_ipaddr_per_minute,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             tpu_coalesce,
-            false,
+            None,
         )
         .unwrap();
 
@@ -250,15 +257,39 @@ impl Tpu {
             tpu_max_connections_per_ipaddr_per_minute,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             tpu_coalesce,
-            false,
+            None,
         )
         .unwrap();
 
-        let (packet_sender, packet_receiver) = unbounded();
+        let packet_relay = Arc::new(PacketRelay::default());
+        let (packet_relay_stage, packet_relay_sender) =
+            PacketRelayStage::new(packet_relay.clone(), bank_forks.clone(), exit.clone());
 
+        // Packets received on this validator's tpu are verified apart from the packets of relayers,
+        // block engines and P3, so only they are relayed to block engines
+        let (tpu_packet_sender, tpu_packet_receiver) = unbounded();
         let sigverify_stage = {
+            let verifier = TransactionSigVerifier::new_with_packet_relay(
+                non_vote_sender.clone(),
+                packet_relay_sender,
+            );
+            SigVerifyStage::new(
+                tpu_packet_receiver,
+                verifier,
+                "solSigVerTpu",
+                "tpu-verifier",
+            )
+        };
+
+        let (packet_sender, packet_receiver) = unbounded();
+        let external_sigverify_stage = {
             let verifier = TransactionSigVerifier::new(non_vote_sender.clone());
-            SigVerifyStage::new(packet_receiver, verifier, "solSigVerTpu", "tpu-verifier")
+            SigVerifyStage::new(
+                packet_receiver,
+                verifier,
+                "solSigVerTpuExt",
+                "tpu-external-verifier",
+            )
         };
 
         let (tpu_vote_sender, tpu_vote_receiver) = banking_tracer.create_channel_tpu_vote();
@@ -276,29 +307,29 @@ impl Tpu {
         let (gossip_vote_sender, gossip_vote_receiver) =
             banking_tracer.create_channel_gossip_vote();
 
-        let block_builder_fee_info = Arc::new(Mutex::new(BlockBuilderFeeInfo {
-            block_builder: cluster_info.keypair().pubkey(),
-            block_builder_commission: 0,
-        }));
-
-        let (bundle_sender, bundle_receiver) = unbounded();
+        let (bundle_sender, bundle_receiver) = banking_tracer.create_channel_bundle();
+        let trusted_non_vote_sender =
+            banking_tracer.create_sender_trusted_non_vote(&non_vote_sender);
+        let (bundle_result_sender, bundle_result_receiver) = unbounded();
         let block_engine_stage = BlockEngineStage::new(
             block_engine_config,
             bundle_sender,
+            bundle_result_receiver,
             cluster_info.clone(),
             packet_sender.clone(),
-            non_vote_sender.clone(),
+            trusted_non_vote_sender.clone(),
+            packet_relay,
             exit.clone(),
-            &block_builder_fee_info,
         );
 
         // Launch paladin threads.
         let (p3_quic, p3_quic_key_updaters) = P3Quic::spawn(
             exit.clone(),
             packet_sender.clone(),
-            poh_recorder.clone(),
+            bank_forks.clone(),
             keypair,
             (p3_socket, p3_mev_socket),
+            p3_config,
         );
 
         let (heartbeat_tx, heartbeat_rx) = unbounded();
@@ -306,16 +337,17 @@ impl Tpu {
             cluster_info.clone(),
             heartbeat_rx,
             packet_intercept_receiver,
-            packet_sender.clone(),
+            tpu_packet_sender,
             exit.clone(),
         );
 
         let relayer_stage = RelayerStage::new(
             relayer_config,
+            relayer_states,
             cluster_info.clone(),
             heartbeat_tx,
             packet_sender,
-            non_vote_sender,
+            trusted_non_vote_sender,
             exit.clone(),
         );
 
@@ -342,6 +374,7 @@ impl Tpu {
         );
 
         let bundle_account_locker = BundleAccountLocker::default();
+        let sandwich_tracker = SandwichTracker::new(amm_registry.clone());
 
         // The tip program can't be used in BankingStage to avoid someone from stealing tips mid-slot.
         // The first 80% of the block, based on poh ticks, has `preallocated_bundle_cost` less compute units.
@@ -371,6 +404,7 @@ impl Tpu {
             enable_block_production_forwarding,
             blacklisted_accounts,
             bundle_account_locker.clone(),
+            sandwich_tracker.clone(),
             move |bank| {
                 calculate_block_cost_limit_reservation(
                     bank,
@@ -385,14 +419,17 @@ impl Tpu {
             cluster_info,
             poh_recorder,
             bundle_receiver,
+            bundle_result_sender,
             transaction_status_sender,
             replay_vote_sender,
             log_messages_bytes_limit,
             exit.clone(),
             tip_manager,
             bundle_account_locker,
-            &block_builder_fee_info,
             prioritization_fee_cache,
+            amm_registry,
+            sandwich_tracker,
+            BundleStage::num_threads(),
         );
 
         let (entry_receiver, tpu_entry_notifier) =
@@ -419,13 +456,14 @@ impl Tpu {
             bank_forks,
             shred_version,
             turbine_quic_endpoint_sender,
-            shred_receiver_address,
+            shred_receivers,
         );
 
         (
             Self {
                 fetch_stage,
                 sigverify_stage,
+                external_sigverify_stage,
                 vote_sigverify_stage,
                 banking_stage,
                 cluster_info_vote_listener,
@@ -438,6 +476,7 @@ impl Tpu {
                 block_engine_stage,
                 relayer_stage,
                 fetch_stage_manager,
+                packet_relay_stage,
                 bundle_stage,
                 p3_quic,
             },
@@ -453,6 +492,7 @@ impl Tpu {
         let results = vec![
             self.fetch_stage.join(),
             self.sigverify_stage.join(),
+            self.external_sigverify_stage.join(),
             self.vote_sigverify_stage.join(),
             self.cluster_info_vote_listener.join(),
             self.banking_stage.join(),
@@ -463,6 +503,7 @@ impl Tpu {
             self.relayer_stage.join(),
             self.block_engine_stage.join(),
             self.fetch_stage_manager.join(),
+            self.packet_relay_stage.join(),
             self.p3_quic.join(),
         ];
         let broadcast_result = self.broadcast_stage.join();
@@ -45,8 +45,9 @@ use {
         commitment::BlockCommitmentCache, prioritization_fee_cache::PrioritizationFeeCache,
         vote_sender_types::ReplayVoteSender,
     },
+    solana_runtime_plugin::runtime_plugin_event::RuntimePluginEventSender,
     solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Keypair},
-    solana_turbine::retransmit_stage::RetransmitStage,
+    solana_turbine::{retransmit_stage::RetransmitStage, shred_receiver::ShredReceivers},
     std::{
         collections::HashSet,
         net::{SocketAddr, UdpSocket},
@@ -162,7 +163,8 @@ impl Tvu {
         cluster_slots: Arc<ClusterSlots>,
         wen_restart_repair_slots: Option<Arc<RwLock<Vec<Slot>>>>,
         slot_status_notifier: Option<SlotStatusNotifier>,
-        shred_receiver_addr: Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: Arc<ShredReceivers>,
+        runtime_plugin_event_sender: Option<RuntimePluginEventSender>,
     ) -> Result<Self, String> {
         let in_wen_restart = wen_restart_repair_slots.is_some();
 
@@ -213,7 +215,7 @@ impl Tvu {
             max_slots.clone(),
             Some(rpc_subscriptions.clone()),
             slot_status_notifier.clone(),
-            shred_receiver_addr,
+            shred_receivers,
         );
 
         let (ancestor_duplicate_slots_sender, ancestor_duplicate_slots_receiver) = unbounded();
@@ -291,6 +293,7 @@ impl Tvu {
             wait_to_vote_slot,
             replay_forks_threads: tvu_config.replay_forks_threads,
             replay_transactions_threads: tvu_config.replay_transactions_threads,
+            runtime_plugin_event_sender,
         };
 
         let (voting_sender, voting_receiver) = unbounded();
@@ -548,7 +551,8 @@ pub mod tests {
             cluster_slots,
             wen_restart_repair_slots,
             None,
-            Arc::new(RwLock::new(None)),
+            Arc::default(),
+            None,
         )
         .expect("assume success");
         if enable_wen_restart {
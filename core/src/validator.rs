@@ -9,6 +9,7 @@ use {
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
         banking_stage::DEFAULT_BATCH_INTERVAL,
         banking_trace::{self, BankingTracer, TraceError},
+        bundle_stage::{amm_registry::SharedAmmRegistry, bundle_checks::BundleStageChecks},
         cache_block_meta_service::{CacheBlockMetaSender, CacheBlockMetaService},
         cluster_info_vote_listener::VoteTracker,
         completed_data_sets_service::CompletedDataSetsService,
@@ -17,8 +18,13 @@ use {
             tower_storage::{NullTowerStorage, TowerStorage},
             ExternalRootSource, Tower,
         },
+        p3_quic::P3Config,
         poh_timing_report_service::PohTimingReportService,
-        proxy::{block_engine_stage::BlockEngineConfig, relayer_stage::RelayerConfig},
+        proxy::{
+            block_engine_stage::BlockEngineConfig,
+            relayer_stage::RelayerConfig,
+            shredstream_stage::{ShredstreamConfig, ShredstreamStage},
+        },
         repair::{
             self,
             quic_endpoint::{RepairQuicAsyncSenders, RepairQuicSenders, RepairQuicSockets},
@@ -119,6 +125,7 @@ use {
     },
     solana_runtime_plugin::{
         runtime_plugin_admin_rpc_service::RuntimePluginManagerRpcRequest,
+        runtime_plugin_event::runtime_plugin_event_channel,
         runtime_plugin_service::RuntimePluginService,
     },
     solana_sdk::{
@@ -135,7 +142,7 @@ use {
     },
     solana_send_transaction_service::send_transaction_service,
     solana_streamer::{socket::SocketAddrSpace, streamer::StakedNodes},
-    solana_turbine::{self, broadcast_stage::BroadcastStageType},
+    solana_turbine::{self, broadcast_stage::BroadcastStageType, shred_receiver::ShredReceivers},
     solana_unified_scheduler_pool::DefaultSchedulerPool,
     solana_vote_program::vote_state,
     solana_wen_restart::wen_restart::{wait_for_wen_restart, WenRestartConfig},
@@ -298,13 +305,16 @@ pub struct ValidatorConfig {
     pub delay_leader_block_for_pending_fork: bool,
     pub relayer_config: Arc<Mutex<RelayerConfig>>,
     pub block_engine_config: Arc<Mutex<BlockEngineConfig>>,
-    pub shred_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
-    pub shred_retransmit_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
+    pub shred_receivers: Arc<ShredReceivers>,
+    pub shredstream_config: Arc<Mutex<ShredstreamConfig>>,
     pub tip_manager_config: TipManagerConfig,
     pub preallocated_bundle_cost: u64,
     pub batch_interval: Duration,
     pub p3_socket: SocketAddr,
     pub p3_mev_socket: SocketAddr,
+    pub p3_config: Arc<Mutex<P3Config>>,
+    pub amm_registry: SharedAmmRegistry,
+    pub amm_registry_path: Option<PathBuf>,
 }
 
 impl Default for ValidatorConfig {
@@ -379,13 +389,16 @@ impl Default for ValidatorConfig {
             delay_leader_block_for_pending_fork: false,
             relayer_config: Arc::new(Mutex::new(RelayerConfig::default())),
             block_engine_config: Arc::new(Mutex::new(BlockEngineConfig::default())),
-            shred_receiver_address: Arc::new(RwLock::new(None)),
-            shred_retransmit_receiver_address: Arc::new(RwLock::new(None)),
+            shred_receivers: Arc::default(),
+            shredstream_config: Arc::new(Mutex::new(ShredstreamConfig::default())),
             tip_manager_config: TipManagerConfig::default(),
             preallocated_bundle_cost: u64::default(),
             batch_interval: DEFAULT_BATCH_INTERVAL,
             p3_socket: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 4819)),
             p3_mev_socket: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 4820)),
+            p3_config: Arc::new(Mutex::new(P3Config::default())),
+            amm_registry: SharedAmmRegistry::default(),
+            amm_registry_path: None,
         }
     }
 }
@@ -523,6 +536,7 @@ pub struct Validator {
     poh_service: PohService,
     tpu: Tpu,
     tvu: Tvu,
+    shredstream_stage: ShredstreamStage,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
     pub bank_forks: Arc<RwLock<BankForks>>,
@@ -989,10 +1003,14 @@ impl Validator {
             None,
         ));
 
-        if let Some((runtime_plugin_configs, request_rx)) = runtime_plugin_configs_and_request_rx {
+        let runtime_plugin_event_sender = if let Some((runtime_plugin_configs, request_rx)) =
+            runtime_plugin_configs_and_request_rx
+        {
+            let (event_sender, event_receiver) = runtime_plugin_event_channel();
             RuntimePluginService::start(
                 &runtime_plugin_configs,
                 request_rx,
+                event_receiver,
                 bank_forks.clone(),
                 block_commitment_cache.clone(),
                 exit.clone(),
@@ -1000,7 +1018,10 @@ impl Validator {
             .map_err(|e| {
                 ValidatorError::Other(format!("Failed to start runtime plugin service: {e:?}"))
             })?;
-        }
+            Some(event_sender)
+        } else {
+            None
+        };
 
         let max_slots = Arc::new(MaxSlots::default());
 
@@ -1082,7 +1103,13 @@ impl Validator {
 
             let json_rpc_service = JsonRpcService::new(
                 rpc_addr,
-                config.rpc_config.clone(),
+                JsonRpcConfig {
+                    bundle_checks: Some(Arc::new(BundleStageChecks::new(
+                        &config.tip_manager_config,
+                        config.amm_registry.clone(),
+                    ))),
+                    ..config.rpc_config.clone()
+                },
                 Some(config.snapshot_config.clone()),
                 bank_forks.clone(),
                 block_commitment_cache.clone(),
@@ -1465,10 +1492,20 @@ impl Validator {
             cluster_slots.clone(),
             wen_restart_repair_slots.clone(),
             slot_status_notifier,
-            config.shred_retransmit_receiver_address.clone(),
+            config.shred_receivers.clone(),
+            runtime_plugin_event_sender,
         )
         .map_err(ValidatorError::Other)?;
 
+        let relayer_states = Arc::default();
+        let shredstream_state = Arc::default();
+        let shredstream_stage = ShredstreamStage::new(
+            config.shredstream_config.clone(),
+            Arc::clone(&shredstream_state),
+            cluster_info.clone(),
+            exit.clone(),
+        );
+
         if in_wen_restart {
             info!("Waiting for wen_restart phase one to finish");
             wait_for_wen_restart(WenRestartConfig {
@@ -1534,12 +1571,15 @@ impl Validator {
             config.generator_config.clone(),
             config.block_engine_config.clone(),
             config.relayer_config.clone(),
+            Arc::clone(&relayer_states),
             leader_schedule_cache.clone(),
             config.tip_manager_config.clone(),
-            config.shred_receiver_address.clone(),
+            config.shred_receivers.clone(),
             config.preallocated_bundle_cost,
             config.batch_interval,
             (config.p3_socket, config.p3_mev_socket),
+            config.p3_config.clone(),
+            config.amm_registry.clone(),
         );
 
         datapoint_info!(
@@ -1566,8 +1606,13 @@ impl Validator {
             cluster_slots,
             block_engine_config: config.block_engine_config.clone(),
             relayer_config: config.relayer_config.clone(),
-            shred_receiver_address: config.shred_receiver_address.clone(),
-            shred_retransmit_receiver_address: config.shred_retransmit_receiver_address.clone(),
+            relayer_states,
+            shred_receivers: config.shred_receivers.clone(),
+            shredstream_config: config.shredstream_config.clone(),
+            shredstream_state,
+            p3_config: config.p3_config.clone(),
+            amm_registry: config.amm_registry.clone(),
+            amm_registry_path: config.amm_registry_path.clone(),
         });
 
         Ok(Self {
@@ -1589,6 +1634,7 @@ impl Validator {
             completed_data_sets_service,
             tpu,
             tvu,
+            shredstream_stage,
             poh_service,
             poh_recorder,
             ip_echo_server,
@@ -1747,6 +1793,7 @@ impl Validator {
         }
         self.tpu.join().expect("tpu");
         self.tvu.join().expect("tvu");
+        self.shredstream_stage.join().expect("shredstream_stage");
         if let Some(turbine_quic_endpoint_join_handle) = self.turbine_quic_endpoint_join_handle {
             self.turbine_quic_endpoint_runtime
                 .map(|runtime| runtime.block_on(turbine_quic_endpoint_join_handle))
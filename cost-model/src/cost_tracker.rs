@@ -1,6 +1,7 @@
 //! `cost_tracker` keeps tracking transaction cost per chained accounts as well as for entire block
 //! The main functions are:
 //! - would_fit(&tx_cost), immutable function to test if tx with tx_cost would fit into current block
+//! - would_fit_all(&[&tx_cost]), immutable function to test if a sequence of txs would all fit
 //! - add_transaction_cost(&tx_cost), mutable function to accumulate tx_cost to tracker.
 //!
 use {
@@ -78,6 +79,43 @@ pub struct CostTracker {
     in_flight_transaction_count: usize,
 }
 
+/// Costs of transactions checked by `CostTracker::would_fit_all` ahead of the one being checked.
+#[derive(Default)]
+struct PendingCosts {
+    block_cost: u64,
+    vote_cost: u64,
+    allocated_accounts_data_size: u64,
+    cost_by_writable_accounts: HashMap<Pubkey, u64>,
+}
+
+impl PendingCosts {
+    fn add(&mut self, tx_cost: &TransactionCost<impl SVMMessage>) {
+        let cost = tx_cost.sum();
+        saturating_add_assign!(self.block_cost, cost);
+        if tx_cost.is_simple_vote() {
+            saturating_add_assign!(self.vote_cost, cost);
+        }
+        saturating_add_assign!(
+            self.allocated_accounts_data_size,
+            tx_cost.allocated_accounts_data_size()
+        );
+        for account_key in tx_cost.writable_accounts() {
+            let account_cost = self
+                .cost_by_writable_accounts
+                .entry(*account_key)
+                .or_insert(0);
+            *account_cost = account_cost.saturating_add(cost);
+        }
+    }
+
+    fn writable_account_cost(&self, account_key: &Pubkey) -> u64 {
+        self.cost_by_writable_accounts
+            .get(account_key)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
 impl Default for CostTracker {
     fn default() -> Self {
         // Clippy doesn't like asserts in const contexts, so need to explicitly allow them.  For
@@ -180,6 +218,21 @@ impl CostTracker {
         })
     }
 
+    /// Checks whether the transactions would all fit into the current block if added one after
+    /// another, without adding them. Returns the index of the first one that wouldn't fit.
+    pub fn would_fit_all(
+        &self,
+        tx_costs: &[&TransactionCost<impl SVMMessage>],
+    ) -> Result<(), (usize, CostTrackerError)> {
+        let mut pending = PendingCosts::default();
+        for (index, tx_cost) in tx_costs.iter().enumerate() {
+            self.would_fit_after(tx_cost, 0, &pending)
+                .map_err(|err| (index, err))?;
+            pending.add(tx_cost);
+        }
+        Ok(())
+    }
+
     pub fn update_execution_cost(
         &mut self,
         estimated_tx_cost: &TransactionCost<impl SVMMessage>,
@@ -285,17 +338,39 @@ impl CostTracker {
         &self,
         tx_cost: &TransactionCost<impl SVMMessage>,
         block_cost_limit_reservation: u64,
+    ) -> Result<(), CostTrackerError> {
+        self.would_fit_after(
+            tx_cost,
+            block_cost_limit_reservation,
+            &PendingCosts::default(),
+        )
+    }
+
+    /// Same as `would_fit`, with the `pending` costs counted as already added to the tracker.
+    fn would_fit_after(
+        &self,
+        tx_cost: &TransactionCost<impl SVMMessage>,
+        block_cost_limit_reservation: u64,
+        pending: &PendingCosts,
     ) -> Result<(), CostTrackerError> {
         let cost: u64 = tx_cost.sum();
 
         if tx_cost.is_simple_vote() {
             // if vote transaction, check if it exceeds vote_transaction_limit
-            if self.vote_cost.saturating_add(cost) > self.vote_cost_limit {
+            if self
+                .vote_cost
+                .saturating_add(pending.vote_cost)
+                .saturating_add(cost)
+                > self.vote_cost_limit
+            {
                 return Err(CostTrackerError::WouldExceedVoteMaxLimit);
             }
         }
 
-        if self.block_cost.saturating_add(cost)
+        if self
+            .block_cost
+            .saturating_add(pending.block_cost)
+            .saturating_add(cost)
             > self
                 .block_cost_limit
                 .saturating_sub(block_cost_limit_reservation)
@@ -311,6 +386,7 @@ impl CostTracker {
 
         let allocated_accounts_data_size = self
             .allocated_accounts_data_size
+            .saturating_add(pending.allocated_accounts_data_size)
             .saturating_add(tx_cost.allocated_accounts_data_size());
 
         if allocated_accounts_data_size > MAX_BLOCK_ACCOUNTS_DATA_SIZE_DELTA {
@@ -319,15 +395,14 @@ impl CostTracker {
 
         // check each account against account_cost_limit,
         for account_key in tx_cost.writable_accounts() {
-            match self.cost_by_writable_accounts.get(account_key) {
-                Some(chained_cost) => {
-                    if chained_cost.saturating_add(cost) > self.account_cost_limit {
-                        return Err(CostTrackerError::WouldExceedAccountMaxLimit);
-                    } else {
-                        continue;
-                    }
-                }
-                None => continue,
+            let chained_cost = self
+                .cost_by_writable_accounts
+                .get(account_key)
+                .copied()
+                .unwrap_or_default()
+                .saturating_add(pending.writable_account_cost(account_key));
+            if chained_cost.saturating_add(cost) > self.account_cost_limit {
+                return Err(CostTrackerError::WouldExceedAccountMaxLimit);
             }
         }
 
@@ -1010,4 +1085,37 @@ mod tests {
         );
         cost_tracker.try_add(&tx_cost, 0).unwrap();
     }
+
+    #[test]
+    fn test_cost_tracker_would_fit_all() {
+        let mint_keypair = test_setup();
+        let second_account = Keypair::new();
+        let tx1 = build_simple_transaction(&mint_keypair);
+        let tx_cost1 = simple_transaction_cost(&tx1, 5);
+        let tx2 = build_simple_transaction(&mint_keypair);
+        let tx_cost2 = simple_transaction_cost(&tx2, 5);
+        let tx3 = build_simple_transaction(&second_account);
+        let tx_cost3 = simple_transaction_cost(&tx3, 5);
+        let cost = tx_cost1.sum();
+
+        // Room for each transaction on its own and for two of them in the block, but only one on
+        // each account
+        let mut testee = CostTracker::new(cost, 2 * cost, 2 * cost);
+        assert_eq!(testee.would_fit_all(&[&tx_cost1, &tx_cost3]), Ok(()));
+        assert_eq!(
+            testee.would_fit_all(&[&tx_cost1, &tx_cost2]),
+            Err((1, CostTrackerError::WouldExceedAccountMaxLimit))
+        );
+        // Nothing was added
+        assert_eq!(testee.block_cost, 0);
+        assert!(testee.cost_by_writable_accounts.is_empty());
+
+        // The transactions are checked on top of the costs already in the block
+        testee.try_add(&tx_cost3, 0).unwrap();
+        assert_eq!(testee.would_fit_all(&[&tx_cost1]), Ok(()));
+        assert_eq!(
+            testee.would_fit_all(&[&tx_cost1, &tx_cost3]),
+            Err((1, CostTrackerError::WouldExceedBlockMaxLimit))
+        );
+    }
 }
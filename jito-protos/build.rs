@@ -15,6 +15,7 @@ fn main() -> Result<(), std::io::Error> {
         "packet.proto",
         "relayer.proto",
         "shared.proto",
+        "shredstream.proto",
     ];
     let mut protos = Vec::new();
     for proto_file in &proto_files {
@@ -25,7 +26,8 @@ fn main() -> Result<(), std::io::Error> {
 
     configure()
         .build_client(true)
-        .build_server(false)
+        // Servers are only used by tests mocking the block engine
+        .build_server(true)
         .type_attribute(
             "TransactionErrorType",
             "#[cfg_attr(test, derive(enum_iterator::Sequence))]",
@@ -22,4 +22,8 @@ pub mod proto {
     pub mod shared {
         tonic::include_proto!("shared");
     }
+
+    pub mod shredstream {
+        tonic::include_proto!("shredstream");
+    }
 }
@@ -5,7 +5,10 @@ use {
         error::{LedgerToolError, Result},
         ledger_path::canonicalize_ledger_path,
         ledger_utils::{get_program_ids, get_shred_storage_type},
-        output::{output_ledger, output_slot, CliDuplicateSlotProof, SlotBounds, SlotInfo},
+        output::{
+            output_ledger, output_slot, CliDuplicateSlotProof, CliFunnelPayments, SlotBounds,
+            SlotInfo,
+        },
     },
     chrono::{DateTime, Utc},
     clap::{
@@ -15,7 +18,10 @@ use {
     log::*,
     regex::Regex,
     serde_json::json,
-    solana_clap_utils::{hidden_unless_forced, input_validators::is_slot},
+    solana_clap_utils::{
+        hidden_unless_forced,
+        input_validators::{is_parsable, is_slot},
+    },
     solana_cli_output::OutputFormat,
     solana_ledger::{
         ancestor_iterator::AncestorIterator,
@@ -25,7 +31,7 @@ use {
         shred::Shred,
     },
     solana_sdk::{
-        clock::{Slot, UnixTimestamp},
+        clock::{Epoch, Slot, UnixTimestamp},
         hash::Hash,
     },
     std::{
@@ -134,7 +140,8 @@ fn analyze_storage(database: &Database) -> Result<()> {
     analyze_column::<PerfSamples>(database, "PerfSamples")?;
     analyze_column::<BlockHeight>(database, "BlockHeight")?;
     analyze_column::<ProgramCosts>(database, "ProgramCosts")?;
-    analyze_column::<OptimisticSlots>(database, "OptimisticSlots")
+    analyze_column::<OptimisticSlots>(database, "OptimisticSlots")?;
+    analyze_column::<FunnelPayments>(database, "FunnelPayments")
 }
 
 fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
@@ -166,6 +173,7 @@ fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
         cf::OptimisticSlots::NAME => {
             Some(cf::OptimisticSlots::slot(cf::OptimisticSlots::index(key)))
         }
+        cf::FunnelPayments::NAME => Some(cf::FunnelPayments::slot(cf::FunnelPayments::index(key))),
         &_ => None,
     }
 }
@@ -347,6 +355,17 @@ pub fn blockstore_subcommands<'a, 'b>(hidden: bool) -> Vec<App<'a, 'b>> {
             .about("Print all the duplicate slots in the ledger")
             .settings(&hidden)
             .arg(&starting_slot_arg),
+        SubCommand::with_name("funnel-payments")
+            .about("Print the funnel payments this validator's tip cranks made")
+            .settings(&hidden)
+            .arg(
+                Arg::with_name("epoch")
+                    .long("epoch")
+                    .value_name("EPOCH")
+                    .takes_value(true)
+                    .validator(is_parsable::<Epoch>)
+                    .help("Only print payments committed in this epoch"),
+            ),
         SubCommand::with_name("latest-optimistic-slots")
             .about(
                 "Output up to the most recent <num-slots> optimistic slots with their hashes \
@@ -718,6 +737,22 @@ fn do_blockstore_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -
                 }
             }
         }
+        ("funnel-payments", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let (start_epoch, end_epoch) = match value_t!(arg_matches, "epoch", Epoch) {
+                Ok(epoch) => (epoch, epoch),
+                Err(_) => (0, Epoch::MAX),
+            };
+
+            let blockstore =
+                crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
+            let funnel_payments = CliFunnelPayments::new(blockstore.get_funnel_payments(
+                start_epoch,
+                end_epoch,
+                usize::MAX,
+            )?);
+            println!("{}", output_format.formatted_string(&funnel_payments));
+        }
         ("latest-optimistic-slots", Some(arg_matches)) => {
             let blockstore =
                 crate::open_blockstore(&ledger_path, arg_matches, AccessType::Secondary);
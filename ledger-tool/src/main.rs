@@ -23,16 +23,17 @@ use {
     solana_accounts_db::{accounts_db::CalcAccountsHashDataSource, accounts_index::ScanConfig},
     solana_clap_utils::{
         hidden_unless_forced,
-        input_parsers::{cluster_type_of, pubkey_of, pubkeys_of},
+        input_parsers::{cluster_type_of, keypair_of, pubkey_of, pubkeys_of},
         input_validators::{
-            is_parsable, is_pubkey, is_pubkey_or_keypair, is_slot, is_valid_percentage,
+            is_keypair, is_parsable, is_pubkey, is_pubkey_or_keypair, is_slot, is_valid_percentage,
             is_within_range,
         },
     },
     solana_cli_output::OutputFormat,
     solana_core::{
-        banking_simulation::{BankingSimulator, BankingTraceEvents},
+        banking_simulation::{BankingSimulator, BankingTraceEvents, BundleSimulationConfig},
         system_monitor_service::{SystemMonitorService, SystemMonitorStatsReportConfig},
+        tip_manager::{TipDistributionAccountConfig, TipManagerConfig},
         validator::{BlockProductionMethod, BlockVerificationMethod},
     },
     solana_cost_model::{cost_model::CostModel, cost_tracker::CostTracker},
@@ -1506,6 +1507,91 @@ fn main() {
                         .long("no-block-cost-limits")
                         .takes_value(false)
                         .help("Disable block cost limits effectively by setting them to the max"),
+                )
+                .arg(
+                    Arg::with_name("identity")
+                        .long("identity")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .validator(is_keypair)
+                        .requires_all(&[
+                            "tip_payment_program_pubkey",
+                            "tip_distribution_program_pubkey",
+                            "merkle_root_upload_authority",
+                            "vote_account",
+                            "commission_bps",
+                        ])
+                        .help(
+                            "Identity keypair of the simulated leader. Traced bundles are \
+                             simulated with BundleStage only if given",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("tip_payment_program_pubkey")
+                        .long("tip-payment-program-pubkey")
+                        .value_name("TIP_PAYMENT_PROGRAM_PUBKEY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("The public key of the tip-payment program"),
+                )
+                .arg(
+                    Arg::with_name("tip_distribution_program_pubkey")
+                        .long("tip-distribution-program-pubkey")
+                        .value_name("TIP_DISTRIBUTION_PROGRAM_PUBKEY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("The public key of the tip-distribution program"),
+                )
+                .arg(
+                    Arg::with_name("merkle_root_upload_authority")
+                        .long("merkle-root-upload-authority")
+                        .value_name("MERKLE_ROOT_UPLOAD_AUTHORITY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("The public key of the authorized merkle-root uploader"),
+                )
+                .arg(
+                    Arg::with_name("vote_account")
+                        .long("vote-account")
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("The simulated leader's vote account"),
+                )
+                .arg(
+                    Arg::with_name("commission_bps")
+                        .long("commission-bps")
+                        .value_name("COMMISSION_BPS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u16>)
+                        .help("The commission the simulated leader takes from tips in basis points"),
+                )
+                .arg(
+                    Arg::with_name("funnel")
+                        .long("funnel")
+                        .value_name("FUNNEL_PUBKEY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .requires("identity")
+                        .help("The funnel the simulated leader pays its tips through, as with the validator's --funnel"),
+                )
+                .arg(
+                    Arg::with_name("rewards_split_minimum_lamports")
+                        .long("rewards-split-minimum-lamports")
+                        .value_name("LAMPORTS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u64>)
+                        .requires_all(&["identity", "rewards_split_bp"])
+                        .help("The minimum number of lamports the simulated leader reserves for its identity, as with the validator's --rewards-split-minimum-lamports"),
+                )
+                .arg(
+                    Arg::with_name("rewards_split_bp")
+                        .long("rewards-split-bp")
+                        .value_name("BASIS_POINTS")
+                        .takes_value(true)
+                        .validator(is_parsable::<u16>)
+                        .requires_all(&["identity", "rewards_split_minimum_lamports"])
+                        .help("The share of block rewards the simulated leader sends to stakers, as with the validator's --rewards-split-bp"),
                 ),
         )
         .subcommand(
@@ -1661,6 +1747,7 @@ fn main() {
         | ("copy", Some(_))
         | ("dead-slots", Some(_))
         | ("duplicate-slots", Some(_))
+        | ("funnel-payments", Some(_))
         | ("latest-optimistic-slots", Some(_))
         | ("list-roots", Some(_))
         | ("parse_full_frozen", Some(_))
@@ -2526,11 +2613,53 @@ fn main() {
 
                     info!("Using: block-production-method: {block_production_method}");
 
+                    let bundle_simulation_config =
+                        keypair_of(arg_matches, "identity").map(|identity_keypair| {
+                            BundleSimulationConfig {
+                                identity_keypair: Arc::new(identity_keypair),
+                                tip_manager_config: TipManagerConfig {
+                                    funnel: pubkey_of(arg_matches, "funnel"),
+                                    rewards_split: value_t!(
+                                        arg_matches,
+                                        "rewards_split_minimum_lamports",
+                                        u64
+                                    )
+                                    .ok()
+                                    .zip(value_t!(arg_matches, "rewards_split_bp", u16).ok()),
+                                    tip_payment_program_id: pubkey_of(
+                                        arg_matches,
+                                        "tip_payment_program_pubkey",
+                                    )
+                                    .unwrap(),
+                                    tip_distribution_program_id: pubkey_of(
+                                        arg_matches,
+                                        "tip_distribution_program_pubkey",
+                                    )
+                                    .unwrap(),
+                                    tip_distribution_account_config: TipDistributionAccountConfig {
+                                        merkle_root_upload_authority: pubkey_of(
+                                            arg_matches,
+                                            "merkle_root_upload_authority",
+                                        )
+                                        .unwrap(),
+                                        vote_account: pubkey_of(arg_matches, "vote_account")
+                                            .unwrap(),
+                                        commission_bps: value_t_or_exit!(
+                                            arg_matches,
+                                            "commission_bps",
+                                            u16
+                                        ),
+                                    },
+                                },
+                            }
+                        });
+
                     match simulator.start(
                         genesis_config,
                         bank_forks,
                         blockstore,
                         block_production_method,
+                        bundle_simulation_config,
                     ) {
                         Ok(()) => println!("Ok"),
                         Err(error) => {
@@ -14,13 +14,13 @@ use {
     },
     solana_ledger::{
         blockstore::{Blockstore, BlockstoreError},
-        blockstore_meta::{DuplicateSlotProof, ErasureMeta},
+        blockstore_meta::{DuplicateSlotProof, ErasureMeta, FunnelPayment},
         shred::{Shred, ShredType},
     },
     solana_runtime::bank::{Bank, TotalAccountsStats},
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
-        clock::{Slot, UnixTimestamp},
+        clock::{Epoch, Slot, UnixTimestamp},
         hash::Hash,
         native_token::lamports_to_sol,
         pubkey::Pubkey,
@@ -122,6 +122,109 @@ impl Display for SlotBankHash {
     }
 }
 
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CliFunnelPayment {
+    pub slot: Slot,
+    pub epoch: Epoch,
+    pub first_slot: Option<Slot>,
+    pub last_slot: Option<Slot>,
+    pub rewards: u64,
+    pub owed: u64,
+    pub paid: u64,
+    pub outstanding: u64,
+    pub signature: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CliFunnelPayments {
+    pub payments: Vec<CliFunnelPayment>,
+}
+
+impl CliFunnelPayments {
+    pub fn new(payments: Vec<(Slot, FunnelPayment)>) -> Self {
+        Self {
+            payments: payments
+                .into_iter()
+                .map(|(slot, payment)| CliFunnelPayment {
+                    slot,
+                    epoch: payment.epoch,
+                    first_slot: payment.slot_range.map(|(first_slot, _)| first_slot),
+                    last_slot: payment.slot_range.map(|(_, last_slot)| last_slot),
+                    rewards: payment.rewards,
+                    owed: payment.owed,
+                    paid: payment.paid,
+                    outstanding: payment.outstanding(),
+                    signature: payment.signature.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl VerboseDisplay for CliFunnelPayments {}
+impl QuietDisplay for CliFunnelPayments {}
+
+impl Display for CliFunnelPayments {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.payments.is_empty() {
+            return writeln!(f, "No funnel payments");
+        }
+
+        writeln!(
+            f,
+            "{:>12} {:>6} {:>25} {:>16} {:>16} {:>16} {:>16}  Signature",
+            "Slot", "Epoch", "Settled Slots", "Rewards", "Owed", "Paid", "Outstanding"
+        )?;
+        let mut epoch_totals: Vec<(Epoch, u64, u64, u64, u64)> = vec![];
+        for payment in &self.payments {
+            let settled_slots = match (payment.first_slot, payment.last_slot) {
+                (Some(first_slot), Some(last_slot)) => format!("{first_slot}-{last_slot}"),
+                _ => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:>12} {:>6} {:>25} {:>16} {:>16} {:>16} {:>16}  {}",
+                payment.slot,
+                payment.epoch,
+                settled_slots,
+                payment.rewards,
+                payment.owed,
+                payment.paid,
+                payment.outstanding,
+                payment.signature,
+            )?;
+
+            match epoch_totals.last_mut() {
+                Some((epoch, rewards, owed, paid, outstanding)) if *epoch == payment.epoch => {
+                    *rewards += payment.rewards;
+                    *owed += payment.owed;
+                    *paid += payment.paid;
+                    *outstanding += payment.outstanding;
+                }
+                _ => epoch_totals.push((
+                    payment.epoch,
+                    payment.rewards,
+                    payment.owed,
+                    payment.paid,
+                    payment.outstanding,
+                )),
+            }
+        }
+
+        writeln!(f)?;
+        for (epoch, rewards, owed, paid, outstanding) in epoch_totals {
+            writeln!(
+                f,
+                "Epoch {epoch}: rewards {rewards}, owed {owed}, paid {paid}, outstanding \
+                 {outstanding} lamports",
+            )?;
+        }
+        Ok(())
+    }
+}
+
 fn writeln_entry(f: &mut dyn fmt::Write, i: usize, entry: &CliEntry, prefix: &str) -> fmt::Result {
     writeln!(
         f,
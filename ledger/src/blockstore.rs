@@ -45,7 +45,7 @@ use {
     solana_sdk::{
         account::ReadableAccount,
         address_lookup_table::state::AddressLookupTable,
-        clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND},
+        clock::{Epoch, Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND},
         feature_set::FeatureSet,
         genesis_config::{GenesisConfig, DEFAULT_GENESIS_ARCHIVE, DEFAULT_GENESIS_FILE},
         hash::Hash,
@@ -257,6 +257,7 @@ pub struct Blockstore {
     optimistic_slots_cf: LedgerColumn<cf::OptimisticSlots>,
     max_root: AtomicU64,
     merkle_root_meta_cf: LedgerColumn<cf::MerkleRootMeta>,
+    funnel_payments_cf: LedgerColumn<cf::FunnelPayments>,
     insert_shreds_lock: Mutex<()>,
     new_shreds_signals: Mutex<Vec<Sender<bool>>>,
     completed_slots_senders: Mutex<Vec<CompletedSlotsSender>>,
@@ -371,6 +372,7 @@ impl Blockstore {
         let bank_hash_cf = db.column();
         let optimistic_slots_cf = db.column();
         let merkle_root_meta_cf = db.column();
+        let funnel_payments_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -409,6 +411,7 @@ impl Blockstore {
             bank_hash_cf,
             optimistic_slots_cf,
             merkle_root_meta_cf,
+            funnel_payments_cf,
             new_shreds_signals: Mutex::default(),
             completed_slots_senders: Mutex::default(),
             shred_timing_point_sender: None,
@@ -860,6 +863,7 @@ impl Blockstore {
         self.bank_hash_cf.submit_rocksdb_cf_metrics();
         self.optimistic_slots_cf.submit_rocksdb_cf_metrics();
         self.merkle_root_meta_cf.submit_rocksdb_cf_metrics();
+        self.funnel_payments_cf.submit_rocksdb_cf_metrics();
     }
 
     /// Report the accumulated RPC API metrics
@@ -3524,6 +3528,30 @@ impl Blockstore {
         self.perf_samples_cf.put_bytes(index, &bytes)
     }
 
+    pub fn write_funnel_payment(&self, slot: Slot, funnel_payment: &FunnelPayment) -> Result<()> {
+        self.funnel_payments_cf
+            .put((funnel_payment.epoch, slot), funnel_payment)
+    }
+
+    /// Returns up to `limit` funnel payments committed in epochs `start_epoch..=end_epoch`, in
+    /// slot order
+    pub fn get_funnel_payments(
+        &self,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+        limit: usize,
+    ) -> Result<Vec<(Slot, FunnelPayment)>> {
+        self.db
+            .iter::<cf::FunnelPayments>(IteratorMode::From(
+                (start_epoch, 0),
+                IteratorDirection::Forward,
+            ))?
+            .take_while(|((epoch, _), _)| *epoch <= end_epoch)
+            .take(limit)
+            .map(|((_, slot), bytes)| Ok((slot, deserialize::<FunnelPayment>(&bytes)?)))
+            .collect()
+    }
+
     pub fn read_program_costs(&self) -> Result<Vec<(Pubkey, u64)>> {
         Ok(self
             .db
@@ -10275,6 +10303,63 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_write_funnel_payments() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let payment = |epoch: Epoch, slot: Slot| FunnelPayment {
+            epoch,
+            slot_range: Some((slot - 4, slot - 1)),
+            rewards: 1_000,
+            owed: 100,
+            paid: 90,
+            signature: Signature::new_unique(),
+        };
+        let payments: Vec<(Slot, FunnelPayment)> = vec![
+            (10, payment(0, 10)),
+            (20, payment(0, 20)),
+            (40, payment(1, 40)),
+            (70, payment(2, 70)),
+        ];
+        for (slot, payment) in payments.iter().rev() {
+            blockstore.write_funnel_payment(*slot, payment).unwrap();
+        }
+
+        assert_eq!(
+            blockstore
+                .get_funnel_payments(0, Epoch::MAX, usize::MAX)
+                .unwrap(),
+            payments
+        );
+        assert_eq!(
+            blockstore.get_funnel_payments(0, 0, usize::MAX).unwrap(),
+            payments[..2]
+        );
+        assert_eq!(
+            blockstore.get_funnel_payments(1, 2, usize::MAX).unwrap(),
+            payments[2..]
+        );
+        assert_eq!(
+            blockstore.get_funnel_payments(0, 2, 3).unwrap(),
+            payments[..3]
+        );
+        assert!(blockstore
+            .get_funnel_payments(3, Epoch::MAX, usize::MAX)
+            .unwrap()
+            .is_empty());
+        assert_eq!(payments[0].1.outstanding(), 10);
+
+        // Payments are kept when the slots they were made in are purged.
+        blockstore.purge_and_compact_slots(0, 100);
+        assert_eq!(
+            blockstore
+                .get_funnel_payments(0, Epoch::MAX, usize::MAX)
+                .unwrap(),
+            payments
+        );
+    }
+
     #[test]
     fn test_lowest_slot() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -28,7 +28,7 @@ use {
     serde::{de::DeserializeOwned, Serialize},
     solana_accounts_db::hardened_unpack::UnpackError,
     solana_sdk::{
-        clock::{Slot, UnixTimestamp},
+        clock::{Epoch, Slot, UnixTimestamp},
         pubkey::Pubkey,
         signature::Signature,
     },
@@ -105,6 +105,8 @@ const PROGRAM_COSTS_CF: &str = "program_costs";
 const OPTIMISTIC_SLOTS_CF: &str = "optimistic_slots";
 /// Column family for merkle roots
 const MERKLE_ROOT_META_CF: &str = "merkle_root_meta";
+/// Column family for funnel payments
+const FUNNEL_PAYMENTS_CF: &str = "funnel_payments";
 
 #[derive(Error, Debug)]
 pub enum BlockstoreError {
@@ -364,6 +366,16 @@ pub mod columns {
     /// * value type: [`blockstore_meta::MerkleRootMeta`]`
     pub struct MerkleRootMeta;
 
+    #[derive(Debug)]
+    /// The funnel payments column
+    ///
+    /// Keyed by epoch so payments outlive the slots they were made in: this column is never
+    /// purged or compacted by slot.
+    ///
+    /// * index type: `(Epoch, Slot)`
+    /// * value type: [`blockstore_meta::FunnelPayment`]
+    pub struct FunnelPayments;
+
     // When adding a new column ...
     // - Add struct below and implement `Column` and `ColumnName` traits
     // - Add descriptor in Rocks::cf_descriptors() and name in Rocks::columns()
@@ -503,6 +515,7 @@ impl Rocks {
             new_cf_descriptor::<ProgramCosts>(options, oldest_slot),
             new_cf_descriptor::<OptimisticSlots>(options, oldest_slot),
             new_cf_descriptor::<MerkleRootMeta>(options, oldest_slot),
+            new_cf_descriptor::<FunnelPayments>(options, oldest_slot),
         ];
 
         // If the access type is Secondary, we don't need to open all of the
@@ -576,6 +589,7 @@ impl Rocks {
             ProgramCosts::NAME,
             OptimisticSlots::NAME,
             MerkleRootMeta::NAME,
+            FunnelPayments::NAME,
         ]
     }
 
@@ -1326,6 +1340,39 @@ impl TypedColumn for columns::OptimisticSlots {
     type Type = blockstore_meta::OptimisticSlotMetaVersioned;
 }
 
+impl Column for columns::FunnelPayments {
+    type Index = (Epoch, Slot);
+
+    fn key((epoch, slot): Self::Index) -> Vec<u8> {
+        let mut key = vec![0; 16];
+        BigEndian::write_u64(&mut key[0..8], epoch);
+        BigEndian::write_u64(&mut key[8..16], slot);
+        key
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        let epoch = BigEndian::read_u64(&key[0..8]);
+        let slot = BigEndian::read_u64(&key[8..16]);
+        (epoch, slot)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.1
+    }
+
+    // The FunnelPayments column is not keyed by slot so this method is meaningless
+    // See Column::as_index() declaration for more details
+    fn as_index(_index: u64) -> Self::Index {
+        (0, 0)
+    }
+}
+impl ColumnName for columns::FunnelPayments {
+    const NAME: &'static str = FUNNEL_PAYMENTS_CF;
+}
+impl TypedColumn for columns::FunnelPayments {
+    type Type = blockstore_meta::FunnelPayment;
+}
+
 impl Column for columns::MerkleRootMeta {
     type Index = (Slot, /*fec_set_index:*/ u32);
 
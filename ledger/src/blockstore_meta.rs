@@ -6,8 +6,9 @@ use {
     bitflags::bitflags,
     serde::{Deserialize, Deserializer, Serialize, Serializer},
     solana_sdk::{
-        clock::{Slot, UnixTimestamp},
+        clock::{Epoch, Slot, UnixTimestamp},
         hash::Hash,
+        signature::Signature,
     },
     std::{
         collections::BTreeSet,
@@ -813,6 +814,31 @@ pub struct ProgramCost {
     pub cost: u64,
 }
 
+/// A funnel settlement made by this validator's `become_receiver` transaction, keyed by the slot
+/// it was committed in.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct FunnelPayment {
+    /// Epoch of the slot the settlement was committed in.
+    pub epoch: Epoch,
+    /// First and last leader slots whose block rewards the settlement covers, if any.
+    pub slot_range: Option<(Slot, Slot)>,
+    /// Block rewards observed over `slot_range`.
+    pub rewards: u64,
+    /// The funnel's commission on `rewards`.
+    pub owed: u64,
+    /// Lamports paid to the funnel; less than `owed` when the identity couldn't cover it.
+    pub paid: u64,
+    /// Signature of the `become_receiver` transaction.
+    pub signature: Signature,
+}
+
+impl FunnelPayment {
+    /// Lamports owed to the funnel that this settlement didn't pay.
+    pub fn outstanding(&self) -> u64 {
+        self.owed.saturating_sub(self.paid)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct OptimisticSlotMetaV0 {
     pub hash: Hash,
@@ -267,6 +267,10 @@ impl ShredId {
         self.0
     }
 
+    pub fn shred_type(&self) -> ShredType {
+        self.2
+    }
+
     pub(crate) fn unpack(&self) -> (Slot, /*shred index:*/ u32, ShredType) {
         (self.0, self.1, self.2)
     }
@@ -75,13 +75,16 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         delay_leader_block_for_pending_fork: config.delay_leader_block_for_pending_fork,
         relayer_config: config.relayer_config.clone(),
         block_engine_config: config.block_engine_config.clone(),
-        shred_receiver_address: config.shred_receiver_address.clone(),
-        shred_retransmit_receiver_address: config.shred_retransmit_receiver_address.clone(),
+        shred_receivers: config.shred_receivers.clone(),
+        shredstream_config: config.shredstream_config.clone(),
         tip_manager_config: config.tip_manager_config.clone(),
         preallocated_bundle_cost: config.preallocated_bundle_cost,
         batch_interval: config.batch_interval,
         p3_socket: config.p3_socket,
         p3_mev_socket: config.p3_mev_socket,
+        p3_config: config.p3_config.clone(),
+        amm_registry: config.amm_registry.clone(),
+        amm_registry_path: config.amm_registry_path.clone(),
     }
 }
 
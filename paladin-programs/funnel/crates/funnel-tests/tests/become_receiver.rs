@@ -1,8 +1,12 @@
 use std::sync::Arc;
 
 use funnel::instructions::become_receiver::BecomeReceiverAccounts;
+use funnel::instructions::set_authority::SetAuthorityAccounts;
+use funnel::instructions::update_config::UpdateConfigAccounts;
+use funnel::instructions::update_split::UpdateSplitAccounts;
 use funnel::{
-    Funnel, FunnelConfig, FunnelInstruction, JITO_TIP_ACCOUNT_0, JITO_TIP_PAYMENT_CONFIG,
+    Funnel, FunnelConfig, FunnelError, FunnelInstruction, DEFAULT_COMMISSION_BPS,
+    DEFAULT_HOLDERS_BPS, DEFAULT_STAKERS_BPS, JITO_TIP_ACCOUNT_0, JITO_TIP_PAYMENT_CONFIG,
 };
 use solana_sdk::account::Account;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
@@ -48,10 +52,16 @@ fn setup() -> BaseState {
     svm.load_program(funnel::ID, "funnel");
 
     // Initialize the funnel.
-    let funnel_config = FunnelConfig { stakers_receiver, holders_receiver };
+    let funnel_config = FunnelConfig {
+        stakers_receiver,
+        holders_receiver,
+        stakers_bps: DEFAULT_STAKERS_BPS,
+        holders_bps: DEFAULT_HOLDERS_BPS,
+        commission_bps: DEFAULT_COMMISSION_BPS,
+    };
     let initialize = Instruction::new_with_borsh(
         funnel::ID,
-        &FunnelInstruction::InitializeFunnel { config: funnel_config },
+        &FunnelInstruction::InitializeFunnel { config: funnel_config, authority: TEST_PAYER },
         vec![
             AccountMeta { pubkey: system_program::ID, is_signer: false, is_writable: false },
             AccountMeta { pubkey: TEST_PAYER, is_signer: true, is_writable: true },
@@ -271,3 +281,453 @@ fn additional_lamports() {
     // Assert - Holder receiver has 50 lamports.
     assert_eq!(svm.get(&funnel_config.holders_receiver).unwrap().lamports, 50);
 }
+
+fn update_split(
+    svm: &mut Svm<Arc<Scenario>>,
+    funnel: &Keypair,
+    stakers_bps: u16,
+    holders_bps: u16,
+    commission_bps: u16,
+) -> Transaction {
+    let update_split = funnel::instructions::update_split::ix(
+        UpdateSplitAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: TEST_PAYER,
+        },
+        stakers_bps,
+        holders_bps,
+        commission_bps,
+    );
+
+    Transaction::new_signed_with_payer(
+        &[update_split],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair()],
+        svm.blockhash(),
+    )
+}
+
+fn assert_funnel_error<T, E: std::fmt::Debug>(result: Result<T, E>, error: FunnelError) {
+    let err = format!("{:?}", result.err().expect("Transaction succeeded"));
+    assert!(err.contains(&format!("Custom({})", error as u32)), "{err}");
+}
+
+#[test]
+fn sweep_with_updated_split() {
+    let BaseState {
+        mut svm,
+        funnel,
+        funnel_config,
+        paladin_receiver_new: paladin_receiver_0,
+        paladin_receiver_new_state: paladin_receiver_0_state,
+        tip_receiver_old,
+        block_builder_old,
+    } = setup();
+
+    // Update the split to 10% stakers, 20% holders.
+    let update = update_split(&mut svm, &funnel, 1_000, 2_000, 500);
+    svm.execute_transaction(update).unwrap();
+    let funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    let funnel_s = bytemuck::from_bytes::<Funnel>(&funnel_s.data);
+    assert_eq!(funnel_s.config.stakers_bps, 1_000);
+    assert_eq!(funnel_s.config.holders_bps, 2_000);
+    assert_eq!(funnel_s.config.commission_bps, 500);
+    assert_eq!(funnel_s.config.stakers_receiver, funnel_config.stakers_receiver);
+    assert_eq!(funnel_s.config.holders_receiver, funnel_config.holders_receiver);
+
+    // Initial receiver.
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            block_builder_old,
+            tip_receiver_old,
+            paladin_receiver_old: TEST_PAYER,
+            paladin_receiver_new: paladin_receiver_0.pubkey(),
+            paladin_receiver_new_state: paladin_receiver_0_state,
+        },
+        &funnel_config,
+        0,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_0],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(become_receiver).unwrap();
+
+    // Add 1 SOL rewards to tip account 0.
+    let mut tip_account_0 = svm.get(&JITO_TIP_ACCOUNT_0).unwrap();
+    tip_account_0.lamports += 10u64.pow(9);
+    svm.set(JITO_TIP_ACCOUNT_0, tip_account_0);
+
+    // New receiver should sweep rewards using the updated split.
+    let paladin_receiver_1 = Keypair::new();
+    svm.set(paladin_receiver_1.pubkey(), Account::default());
+    let (paladin_receiver_1_state, _) = funnel::find_leader_state(&paladin_receiver_1.pubkey());
+    svm.set(paladin_receiver_1_state, Account { lamports: 10u64.pow(9), ..Account::default() });
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            block_builder_old,
+            tip_receiver_old: funnel.pubkey(),
+            paladin_receiver_old: paladin_receiver_0.pubkey(),
+            paladin_receiver_new: paladin_receiver_1.pubkey(),
+            paladin_receiver_new_state: paladin_receiver_1_state,
+        },
+        &funnel_config,
+        0,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_1],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(become_receiver).unwrap();
+
+    // Assert - Rewards were split 70/10/20.
+    assert_eq!(
+        svm.get(&paladin_receiver_0.pubkey()).unwrap().lamports,
+        10u64.pow(9) * 95 * 70 / 10_000
+    );
+    assert_eq!(
+        svm.get(&funnel_config.stakers_receiver).unwrap().lamports,
+        10u64.pow(9) * 95 * 10 / 10_000
+    );
+    assert_eq!(
+        svm.get(&funnel_config.holders_receiver).unwrap().lamports,
+        10u64.pow(9) * 95 * 20 / 10_000
+    );
+}
+
+#[test]
+fn additional_lamports_follow_split() {
+    let BaseState {
+        mut svm,
+        funnel,
+        funnel_config,
+        paladin_receiver_new,
+        paladin_receiver_new_state,
+        tip_receiver_old,
+        block_builder_old,
+    } = setup();
+
+    // Stakers receive 3x the holders share.
+    let update = update_split(&mut svm, &funnel, 750, 250, 1_000);
+    svm.execute_transaction(update).unwrap();
+
+    // Give our receiver 101 lamports.
+    let mut receiver = svm.get(&paladin_receiver_new.pubkey()).unwrap();
+    receiver.lamports = 101;
+    svm.set(paladin_receiver_new.pubkey(), receiver);
+
+    // Become the receiver.
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            block_builder_old,
+            tip_receiver_old,
+            paladin_receiver_old: TEST_PAYER,
+            paladin_receiver_new: paladin_receiver_new.pubkey(),
+            paladin_receiver_new_state,
+        },
+        &funnel_config,
+        101,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_new],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(become_receiver).unwrap();
+
+    // Assert - Stakers share is rounded down, holders receive the remainder.
+    assert_eq!(svm.get(&funnel_config.stakers_receiver).unwrap().lamports, 75);
+    assert_eq!(svm.get(&funnel_config.holders_receiver).unwrap().lamports, 26);
+}
+
+#[test]
+fn update_split_requires_authority_signature() {
+    let BaseState { mut svm, funnel, .. } = setup();
+
+    // The authority account is present but does not sign.
+    let authority = Keypair::new();
+    let mut update_split = funnel::instructions::update_split::ix(
+        UpdateSplitAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: authority.pubkey(),
+        },
+        10_000,
+        0,
+        0,
+    );
+    update_split.accounts[3].is_signer = false;
+    let update_split = Transaction::new_signed_with_payer(
+        &[update_split],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair()],
+        svm.blockhash(),
+    );
+
+    // Assert - Rejected & split unchanged.
+    assert_funnel_error(
+        svm.execute_transaction(update_split),
+        FunnelError::MissingAuthoritySignature,
+    );
+    let funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    let funnel_s = bytemuck::from_bytes::<Funnel>(&funnel_s.data);
+    assert_eq!(funnel_s.config.stakers_bps, DEFAULT_STAKERS_BPS);
+}
+
+#[test]
+fn update_split_rejects_wrong_authority() {
+    let BaseState { mut svm, funnel, .. } = setup();
+
+    // Signed by a key that is not the authority.
+    let authority = Keypair::new();
+    let update_split = funnel::instructions::update_split::ix(
+        UpdateSplitAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: authority.pubkey(),
+        },
+        10_000,
+        0,
+        0,
+    );
+    let update_split = Transaction::new_signed_with_payer(
+        &[update_split],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &authority],
+        svm.blockhash(),
+    );
+
+    // Assert.
+    assert_funnel_error(svm.execute_transaction(update_split), FunnelError::InvalidAuthority);
+}
+
+#[test]
+fn update_split_rejects_invalid_split() {
+    let BaseState { mut svm, funnel, .. } = setup();
+
+    // Shares above 100%.
+    let update = update_split(&mut svm, &funnel, 5_001, 5_000, 1_000);
+    assert_funnel_error(svm.execute_transaction(update), FunnelError::InvalidSplit);
+
+    // Commission above 100%.
+    let update = update_split(&mut svm, &funnel, 0, 0, 10_001);
+    assert_funnel_error(svm.execute_transaction(update), FunnelError::InvalidSplit);
+
+    // Exactly 100% is allowed.
+    let update = update_split(&mut svm, &funnel, 5_000, 5_000, 10_000);
+    svm.execute_transaction(update).unwrap();
+}
+
+#[test]
+fn update_config_rotates_receivers() {
+    let BaseState {
+        mut svm,
+        funnel,
+        funnel_config,
+        paladin_receiver_new,
+        paladin_receiver_new_state,
+        tip_receiver_old,
+        block_builder_old,
+    } = setup();
+
+    // Rotate the receivers.
+    let stakers_receiver = Pubkey::new_unique();
+    let holders_receiver = Pubkey::new_unique();
+    svm.set(stakers_receiver, Account::default());
+    svm.set(holders_receiver, Account::default());
+    let update_config = funnel::instructions::update_config::ix(
+        UpdateConfigAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: TEST_PAYER,
+        },
+        stakers_receiver,
+        holders_receiver,
+    );
+    let update_config = Transaction::new_signed_with_payer(
+        &[update_config],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair()],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(update_config).unwrap();
+
+    // Assert - Receivers rotated, split unchanged.
+    let funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    let funnel_s = bytemuck::from_bytes::<Funnel>(&funnel_s.data);
+    assert_eq!(funnel_s.config.stakers_receiver, stakers_receiver);
+    assert_eq!(funnel_s.config.holders_receiver, holders_receiver);
+    assert_eq!(funnel_s.config.stakers_bps, DEFAULT_STAKERS_BPS);
+    assert_eq!(funnel_s.authority, TEST_PAYER);
+
+    // Assert - Becoming the receiver with the old config fails.
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            paladin_receiver_new_state,
+            block_builder_old,
+            tip_receiver_old,
+            paladin_receiver_old: TEST_PAYER,
+            paladin_receiver_new: paladin_receiver_new.pubkey(),
+        },
+        &funnel_config,
+        0,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_new],
+        svm.blockhash(),
+    );
+    assert_funnel_error(
+        svm.execute_transaction(become_receiver),
+        FunnelError::StakersReceiverMismatch,
+    );
+}
+
+#[test]
+fn set_authority() {
+    let BaseState { mut svm, funnel, .. } = setup();
+
+    // Hand the authority to a new key.
+    let new_authority = Keypair::new();
+    let set_authority = funnel::instructions::set_authority::ix(
+        SetAuthorityAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: TEST_PAYER,
+        },
+        new_authority.pubkey(),
+    );
+    let set_authority = Transaction::new_signed_with_payer(
+        &[set_authority],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair()],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(set_authority).unwrap();
+    let funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    let funnel_s = bytemuck::from_bytes::<Funnel>(&funnel_s.data);
+    assert_eq!(funnel_s.authority, new_authority.pubkey());
+
+    // Assert - The old authority is rejected.
+    let update = update_split(&mut svm, &funnel, 0, 0, 0);
+    assert_funnel_error(svm.execute_transaction(update), FunnelError::InvalidAuthority);
+
+    // Assert - The new authority is accepted.
+    let update_split = funnel::instructions::update_split::ix(
+        UpdateSplitAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            authority: new_authority.pubkey(),
+        },
+        0,
+        0,
+        0,
+    );
+    let update_split = Transaction::new_signed_with_payer(
+        &[update_split],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &new_authority],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(update_split).unwrap();
+}
+
+#[test]
+fn become_receiver_rejects_wrong_leader_state() {
+    let BaseState {
+        mut svm,
+        funnel,
+        funnel_config,
+        paladin_receiver_new,
+        tip_receiver_old,
+        block_builder_old,
+        ..
+    } = setup();
+
+    // Use a leader state that is not the receiver's PDA.
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            paladin_receiver_new_state: Pubkey::new_unique(),
+            block_builder_old,
+            tip_receiver_old,
+            paladin_receiver_old: TEST_PAYER,
+            paladin_receiver_new: paladin_receiver_new.pubkey(),
+        },
+        &funnel_config,
+        0,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_new],
+        svm.blockhash(),
+    );
+
+    // Assert.
+    assert_funnel_error(svm.execute_transaction(become_receiver), FunnelError::LeaderStateMismatch);
+}
+
+#[test]
+fn become_receiver_with_legacy_funnel() {
+    let BaseState {
+        mut svm,
+        funnel,
+        funnel_config,
+        paladin_receiver_new,
+        paladin_receiver_new_state,
+        tip_receiver_old,
+        block_builder_old,
+    } = setup();
+
+    // Shrink the funnel back to the layout from before the split was configurable.
+    let mut funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    funnel_s.data.truncate(Funnel::LEGACY_LEN);
+    svm.set(funnel.pubkey(), funnel_s);
+
+    // Become the receiver.
+    let become_receiver = funnel::instructions::become_receiver::ix(
+        BecomeReceiverAccounts {
+            payer: TEST_PAYER,
+            funnel_config: funnel.pubkey(),
+            paladin_receiver_new_state,
+            block_builder_old,
+            tip_receiver_old,
+            paladin_receiver_old: TEST_PAYER,
+            paladin_receiver_new: paladin_receiver_new.pubkey(),
+        },
+        &funnel_config,
+        0,
+    );
+    let become_receiver = Transaction::new_signed_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), become_receiver],
+        Some(&TEST_PAYER),
+        &[test_payer_keypair(), &paladin_receiver_new],
+        svm.blockhash(),
+    );
+    svm.execute_transaction(become_receiver).unwrap();
+
+    // Assert - The receiver is updated in place, the layout is untouched.
+    let funnel_s = svm.get(&funnel.pubkey()).unwrap();
+    assert_eq!(funnel_s.data.len(), Funnel::LEGACY_LEN);
+    let funnel_s = Funnel::load(&funnel_s.data).unwrap();
+    assert_eq!(funnel_s.receiver, paladin_receiver_new.pubkey());
+    assert_eq!(funnel_s.config.stakers_bps, DEFAULT_STAKERS_BPS);
+    assert_eq!(funnel_s.config.commission_bps, DEFAULT_COMMISSION_BPS);
+}
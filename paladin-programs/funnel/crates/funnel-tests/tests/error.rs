@@ -0,0 +1,13 @@
+use funnel::FunnelError;
+use solana_sdk::program_error::ProgramError;
+
+#[test]
+fn error_codes_round_trip() {
+    for code in 0..10 {
+        let error = FunnelError::from_code(code).unwrap();
+        assert_eq!(error as u32, code);
+        assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+    }
+
+    assert_eq!(FunnelError::from_code(10), None);
+}
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use funnel::{FunnelConfig, FunnelInstruction};
+use funnel::{
+    FunnelConfig, FunnelInstruction, DEFAULT_COMMISSION_BPS, DEFAULT_HOLDERS_BPS,
+    DEFAULT_STAKERS_BPS,
+};
 use solana_sdk::account::Account;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
@@ -29,7 +32,11 @@ fn initialize_funnel() {
             config: FunnelConfig {
                 stakers_receiver: Pubkey::default(),
                 holders_receiver: Pubkey::default(),
+                stakers_bps: DEFAULT_STAKERS_BPS,
+                holders_bps: DEFAULT_HOLDERS_BPS,
+                commission_bps: DEFAULT_COMMISSION_BPS,
             },
+            authority: TEST_PAYER,
         },
         vec![
             AccountMeta { pubkey: system_program::ID, is_signer: false, is_writable: false },
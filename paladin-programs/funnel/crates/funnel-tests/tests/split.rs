@@ -0,0 +1,82 @@
+use funnel::{FunnelConfig, RewardSplit, MAX_BPS};
+use solana_sdk::pubkey::Pubkey;
+
+fn config(stakers_bps: u16, holders_bps: u16, commission_bps: u16) -> FunnelConfig {
+    FunnelConfig {
+        stakers_receiver: Pubkey::new_unique(),
+        holders_receiver: Pubkey::new_unique(),
+        stakers_bps,
+        holders_bps,
+        commission_bps,
+    }
+}
+
+#[test]
+fn split_tips_default() {
+    let config = config(500, 500, 1_000);
+
+    assert_eq!(config.split_tips(1_000), RewardSplit { stakers: 50, holders: 50, validator: 900 });
+    assert_eq!(config.split_tips(0), RewardSplit { stakers: 0, holders: 0, validator: 0 });
+}
+
+#[test]
+fn split_tips_rounds_down_to_validator() {
+    let config = config(333, 667, 1_000);
+
+    // 19 * 333 / 10_000 = 0.63, 19 * 667 / 10_000 = 1.27.
+    assert_eq!(config.split_tips(19), RewardSplit { stakers: 0, holders: 1, validator: 18 });
+
+    // Shares always sum to the total.
+    for total in [1, 7, 999, 10_001, 123_456_789] {
+        let RewardSplit { stakers, holders, validator } = config.split_tips(total);
+        assert_eq!(stakers + holders + validator, total);
+    }
+}
+
+#[test]
+fn split_tips_extremes() {
+    assert_eq!(
+        config(0, 0, 0).split_tips(1_000),
+        RewardSplit { stakers: 0, holders: 0, validator: 1_000 }
+    );
+    assert_eq!(
+        config(MAX_BPS, 0, 0).split_tips(1_000),
+        RewardSplit { stakers: 1_000, holders: 0, validator: 0 }
+    );
+    assert_eq!(
+        config(5_000, 5_000, 0).split_tips(u64::MAX),
+        RewardSplit { stakers: u64::MAX / 2, holders: u64::MAX / 2, validator: 1 }
+    );
+}
+
+#[test]
+fn split_prepay() {
+    // Even split gives the odd lamport to holders.
+    assert_eq!(config(500, 500, 1_000).split_prepay(101), (50, 51));
+
+    // Proportional to the tip shares.
+    assert_eq!(config(750, 250, 1_000).split_prepay(100), (75, 25));
+    assert_eq!(config(0, 250, 1_000).split_prepay(100), (0, 100));
+
+    // No shares falls back to an even split.
+    assert_eq!(config(0, 0, 1_000).split_prepay(100), (50, 50));
+
+    // Does not overflow.
+    assert_eq!(config(MAX_BPS, 0, 0).split_prepay(u64::MAX), (u64::MAX, 0));
+}
+
+#[test]
+fn commission() {
+    assert_eq!(config(500, 500, 1_000).commission(1_009), 100);
+    assert_eq!(config(500, 500, 0).commission(1_000), 0);
+    assert_eq!(config(500, 500, MAX_BPS).commission(u64::MAX), u64::MAX);
+}
+
+#[test]
+fn is_valid() {
+    assert!(config(500, 500, 1_000).is_valid());
+    assert!(config(5_000, 5_000, MAX_BPS).is_valid());
+    assert!(!config(5_000, 5_001, 1_000).is_valid());
+    assert!(!config(500, 500, MAX_BPS + 1).is_valid());
+    assert!(!config(u16::MAX, u16::MAX, 0).is_valid());
+}
@@ -0,0 +1,109 @@
+//! Account checks shared by the instructions restricted to the funnel
+//! authority.
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::sysvar::Sysvar;
+
+use crate::{
+    Funnel, FunnelError, DEFAULT_COMMISSION_BPS, DEFAULT_HOLDERS_BPS, DEFAULT_STAKERS_BPS,
+};
+
+pub(crate) struct AdminAccounts<'a, 'info> {
+    /// We need the system_program loaded for CPIs.
+    #[allow(dead_code)]
+    pub(crate) system_program: &'a AccountInfo<'info>,
+    /// CHECK: Must be writeable.
+    pub(crate) payer: &'a AccountInfo<'info>,
+    /// CHECK: Must be a [`crate::Funnel`].
+    pub(crate) funnel: &'a AccountInfo<'info>,
+    /// CHECK: Must be signer & match [`crate::Funnel::authority`].
+    pub(crate) authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> AdminAccounts<'a, 'info> {
+    pub(crate) fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let mut accounts = accounts.iter();
+
+        Ok(AdminAccounts {
+            system_program: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            payer: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            funnel: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            authority: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+
+    /// Checks that the funnel authority signed.
+    ///
+    /// Funnels created before the authority existed are first grown to the
+    /// current layout. This must be signed by the funnel keypair and makes
+    /// `authority` the funnel authority.
+    pub(crate) fn check_authority(&self) -> ProgramResult {
+        if self.funnel.owner != &crate::ID {
+            return Err(FunnelError::InvalidFunnelOwner.into());
+        }
+        if !self.authority.is_signer {
+            return Err(FunnelError::MissingAuthoritySignature.into());
+        }
+
+        let data_len = self.funnel.data_len();
+        if data_len < Funnel::LEN {
+            if !self.funnel.is_signer {
+                return Err(FunnelError::MissingAuthoritySignature.into());
+            }
+            self.migrate(data_len)?;
+        }
+
+        let funnel = self.funnel.data.borrow();
+        let funnel = Funnel::try_from_bytes(&funnel).map_err(|_| FunnelError::InvalidFunnelData)?;
+        if &funnel.authority != self.authority.key {
+            return Err(FunnelError::InvalidAuthority.into());
+        }
+
+        Ok(())
+    }
+
+    fn migrate(&self, data_len: usize) -> ProgramResult {
+        // Transfer lamports for rent if necessary.
+        let required = Rent::get()?.minimum_balance(Funnel::LEN);
+        let existing = self.funnel.lamports();
+        let additional = required.saturating_sub(existing);
+        if additional > 0 {
+            invoke(
+                &system_instruction::transfer(self.payer.key, self.funnel.key, additional),
+                &[self.payer.clone(), self.funnel.clone()],
+            )?;
+        }
+
+        // Grow the account, new fields are zeroed.
+        self.funnel.realloc(Funnel::LEN, true)?;
+
+        let mut funnel = self.funnel.data.borrow_mut();
+        let funnel = bytemuck::try_from_bytes_mut::<Funnel>(&mut funnel)
+            .map_err(|_| FunnelError::InvalidFunnelData)?;
+        if data_len == Funnel::LEGACY_LEN {
+            funnel.config.stakers_bps = DEFAULT_STAKERS_BPS;
+            funnel.config.holders_bps = DEFAULT_HOLDERS_BPS;
+            funnel.config.commission_bps = DEFAULT_COMMISSION_BPS;
+        }
+        funnel.authority = *self.authority.key;
+
+        Ok(())
+    }
+
+    /// Mutably borrows the funnel, must be called after
+    /// [`Self::check_authority`].
+    pub(crate) fn with_funnel<T>(
+        &self,
+        f: impl FnOnce(&mut Funnel) -> T,
+    ) -> Result<T, ProgramError> {
+        let mut funnel = self.funnel.data.borrow_mut();
+        let funnel = bytemuck::try_from_bytes_mut::<Funnel>(&mut funnel)
+            .map_err(|_| FunnelError::InvalidFunnelData)?;
+
+        Ok(f(funnel))
+    }
+}
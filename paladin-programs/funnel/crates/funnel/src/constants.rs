@@ -12,3 +12,12 @@ pub const JITO_TIP_ACCOUNT_4: Pubkey = pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pb
 pub const JITO_TIP_ACCOUNT_5: Pubkey = pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt");
 pub const JITO_TIP_ACCOUNT_6: Pubkey = pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL");
 pub const JITO_TIP_ACCOUNT_7: Pubkey = pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT");
+
+/// Denominator for all basis-point shares.
+pub const MAX_BPS: u16 = 10_000;
+/// Default share of swept tips paid to the stakers receiver.
+pub const DEFAULT_STAKERS_BPS: u16 = 500;
+/// Default share of swept tips paid to the holders receiver.
+pub const DEFAULT_HOLDERS_BPS: u16 = 500;
+/// Default share of block rewards each leader prepays to the funnel.
+pub const DEFAULT_COMMISSION_BPS: u16 = 1_000;
@@ -0,0 +1,76 @@
+use std::fmt;
+
+use solana_program::program_error::ProgramError;
+
+/// Errors returned by the funnel program as [`ProgramError::Custom`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FunnelError {
+    /// The funnel account is not owned by the funnel program.
+    InvalidFunnelOwner = 0,
+    /// The funnel account data is not a [`crate::Funnel`].
+    InvalidFunnelData = 1,
+    /// The funnel account has already been initialized.
+    FunnelAlreadyInitialized = 2,
+    /// The stakers receiver does not match the funnel config.
+    StakersReceiverMismatch = 3,
+    /// The holders receiver does not match the funnel config.
+    HoldersReceiverMismatch = 4,
+    /// The old receiver does not match the funnel's current receiver.
+    ReceiverMismatch = 5,
+    /// The leader state account is not the new receiver's PDA.
+    LeaderStateMismatch = 6,
+    /// The reward shares or the commission exceed 100%.
+    InvalidSplit = 7,
+    /// The funnel authority did not sign the instruction.
+    MissingAuthoritySignature = 8,
+    /// The signer is not the funnel authority.
+    InvalidAuthority = 9,
+}
+
+impl FunnelError {
+    const ALL: [FunnelError; 10] = [
+        FunnelError::InvalidFunnelOwner,
+        FunnelError::InvalidFunnelData,
+        FunnelError::FunnelAlreadyInitialized,
+        FunnelError::StakersReceiverMismatch,
+        FunnelError::HoldersReceiverMismatch,
+        FunnelError::ReceiverMismatch,
+        FunnelError::LeaderStateMismatch,
+        FunnelError::InvalidSplit,
+        FunnelError::MissingAuthoritySignature,
+        FunnelError::InvalidAuthority,
+    ];
+
+    /// Decodes the error from a [`ProgramError::Custom`] code.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|error| *error as u32 == code)
+    }
+}
+
+impl fmt::Display for FunnelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            FunnelError::InvalidFunnelOwner => "funnel account not owned by the funnel program",
+            FunnelError::InvalidFunnelData => "funnel account data is invalid",
+            FunnelError::FunnelAlreadyInitialized => "funnel account already initialized",
+            FunnelError::StakersReceiverMismatch => "stakers receiver does not match config",
+            FunnelError::HoldersReceiverMismatch => "holders receiver does not match config",
+            FunnelError::ReceiverMismatch => "old receiver does not match funnel receiver",
+            FunnelError::LeaderStateMismatch => "leader state is not the receiver's PDA",
+            FunnelError::InvalidSplit => "reward split exceeds 100%",
+            FunnelError::MissingAuthoritySignature => "funnel authority did not sign",
+            FunnelError::InvalidAuthority => "signer is not the funnel authority",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for FunnelError {}
+
+impl From<FunnelError> for ProgramError {
+    fn from(error: FunnelError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
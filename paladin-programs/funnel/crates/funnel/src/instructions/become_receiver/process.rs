@@ -3,12 +3,15 @@ use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program::{invoke, invoke_signed};
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::system_instruction;
 use solana_program::sysvar::Sysvar;
 
-use crate::{Funnel, LeaderState, JITO_TIP_PAYMENT_PROGRAM};
+use crate::{
+    Funnel, FunnelConfig, FunnelError, LeaderState, RewardSplit, JITO_TIP_PAYMENT_PROGRAM,
+};
 
 const FUNNEL_ACCOUNTS_LEN: usize = 7;
 const _: () = match std::mem::size_of::<BecomeReceiverAccounts>() == FUNNEL_ACCOUNTS_LEN * 8 {
@@ -38,27 +41,39 @@ pub(crate) fn process(
     new_receiver: Pubkey,
     additional_lamports: u64,
 ) -> ProgramResult {
+    if accounts.len() < FUNNEL_ACCOUNTS_LEN {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
     let (accounts, jito_accounts) = accounts.split_at(FUNNEL_ACCOUNTS_LEN);
-    let mut accounts_iter = accounts.iter();
     let funnel_accounts = BecomeReceiverAccounts {
-        system_program: accounts_iter.next().unwrap(),
-        funnel: accounts_iter.next().unwrap(),
-        stakers_receiver: accounts_iter.next().unwrap(),
-        holders_receiver: accounts_iter.next().unwrap(),
-        receiver_old: accounts_iter.next().unwrap(),
-        receiver_new: accounts_iter.next().unwrap(),
-        receiver_new_state: accounts_iter.next().unwrap(),
+        system_program: &accounts[0],
+        funnel: &accounts[1],
+        stakers_receiver: &accounts[2],
+        holders_receiver: &accounts[3],
+        receiver_old: &accounts[4],
+        receiver_new: &accounts[5],
+        receiver_new_state: &accounts[6],
     };
 
     // Validate & deserialize the funnel account.
-    assert_eq!(funnel_accounts.funnel.owner, &crate::ID);
+    if funnel_accounts.funnel.owner != &crate::ID {
+        return Err(FunnelError::InvalidFunnelOwner.into());
+    }
+    // NB: Funnels that have not been migrated yet still take the default split.
     let funnel_borrow = funnel_accounts.funnel.data.borrow();
-    let funnel = bytemuck::from_bytes::<Funnel>(&funnel_borrow);
+    let funnel = Funnel::load(&funnel_borrow).map_err(|_| FunnelError::InvalidFunnelData)?;
 
     // Validate the remaining accounts.
-    assert_eq!(funnel_accounts.stakers_receiver.key, &funnel.config.stakers_receiver);
-    assert_eq!(funnel_accounts.holders_receiver.key, &funnel.config.holders_receiver);
-    assert_eq!(funnel_accounts.receiver_old.key, &funnel.receiver);
+    if funnel_accounts.stakers_receiver.key != &funnel.config.stakers_receiver {
+        return Err(FunnelError::StakersReceiverMismatch.into());
+    }
+    if funnel_accounts.holders_receiver.key != &funnel.config.holders_receiver {
+        return Err(FunnelError::HoldersReceiverMismatch.into());
+    }
+    if funnel_accounts.receiver_old.key != &funnel.receiver {
+        return Err(FunnelError::ReceiverMismatch.into());
+    }
+    let config = funnel.config;
     // NB: Jito needs to be able to take a borrow to funnel.
     drop(funnel_borrow);
 
@@ -79,17 +94,18 @@ pub(crate) fn process(
     invoke(&ix, jito_accounts)?;
 
     // Split & push the rewards.
-    split_rewards(&funnel_accounts, additional_lamports);
+    split_rewards(&funnel_accounts, &config, additional_lamports)?;
 
     // Update the funnel receiver.
-    let mut funnel = funnel_accounts.funnel.data.borrow_mut();
-    let funnel = bytemuck::from_bytes_mut::<Funnel>(&mut funnel);
-    funnel.receiver = new_receiver;
+    Funnel::set_receiver(&mut funnel_accounts.funnel.data.borrow_mut(), new_receiver)
+        .map_err(|_| FunnelError::InvalidFunnelData)?;
 
     // Initialize the leader state account if necessary.
     let (leader_state, leader_state_bump) =
         crate::find_leader_state(funnel_accounts.receiver_new.key);
-    assert_eq!(funnel_accounts.receiver_new_state.key, &leader_state,);
+    if funnel_accounts.receiver_new_state.key != &leader_state {
+        return Err(FunnelError::LeaderStateMismatch.into());
+    }
     if funnel_accounts.receiver_new_state.owner != &crate::ID {
         // Transfer lamports for rent if necessary.
         let required = Rent::get()?.minimum_balance(LeaderState::LEN);
@@ -126,39 +142,39 @@ pub(crate) fn process(
 
     // Set the last slot to the current slot.
     let mut leader_state = funnel_accounts.receiver_new_state.data.borrow_mut();
-    let leader_state = bytemuck::from_bytes_mut::<LeaderState>(&mut leader_state);
-    leader_state.last_slot = Clock::get().unwrap().slot;
+    let leader_state = bytemuck::try_from_bytes_mut::<LeaderState>(&mut leader_state)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    leader_state.last_slot = Clock::get()?.slot;
 
     Ok(())
 }
 
 // SAFETY:
 //
-// - Division by non-zero constant cannot trigger division by zero.
+// - `split_tips` never returns more than `total` across all three shares.
 // - Overflowing lamport balance is economically unfeasible. Additionally, would
 //   be caught by the solana runtime as an unbalanced transfer.
 #[allow(clippy::arithmetic_side_effects)]
-fn split_rewards(accounts: &BecomeReceiverAccounts, additional_lamports: u64) {
+fn split_rewards(
+    accounts: &BecomeReceiverAccounts,
+    config: &FunnelConfig,
+    additional_lamports: u64,
+) -> ProgramResult {
     // Get balance & rent requirements.
     let balance = accounts.funnel.lamports();
-    let rent = Rent::get()
-        .unwrap()
-        .minimum_balance(accounts.funnel.data_len());
+    let rent = Rent::get()?.minimum_balance(accounts.funnel.data_len());
     let total = balance.saturating_sub(rent);
 
     // Compute reward split.
-    let stakers_reward = total / 20; // 5%
-    let holders_reward = total / 20; // 5%
-    let validator_reward = total // 90%
-        .checked_sub(stakers_reward)
-        .unwrap()
-        .checked_sub(holders_reward)
-        .unwrap();
+    let RewardSplit {
+        stakers: stakers_reward,
+        holders: holders_reward,
+        validator: validator_reward,
+    } = config.split_tips(total);
     debug_assert_eq!(stakers_reward + holders_reward + validator_reward, total);
 
     // Add prepay_lamports.
-    let stakers_additional = additional_lamports / 2;
-    let holders_additional = additional_lamports - stakers_additional;
+    let (stakers_additional, holders_additional) = config.split_prepay(additional_lamports);
 
     // Process payments.
     **accounts.stakers_receiver.lamports.borrow_mut() += stakers_reward;
@@ -173,8 +189,7 @@ fn split_rewards(accounts: &BecomeReceiverAccounts, additional_lamports: u64) {
                 stakers_additional,
             ),
             &[accounts.receiver_new.clone(), accounts.stakers_receiver.clone()],
-        )
-        .unwrap();
+        )?;
     }
     if holders_additional > 0 {
         invoke(
@@ -184,9 +199,10 @@ fn split_rewards(accounts: &BecomeReceiverAccounts, additional_lamports: u64) {
                 holders_additional,
             ),
             &[accounts.receiver_new.clone(), accounts.holders_receiver.clone()],
-        )
-        .unwrap();
+        )?;
     }
+
+    Ok(())
 }
 
 use sha2_const_stable::Sha256;
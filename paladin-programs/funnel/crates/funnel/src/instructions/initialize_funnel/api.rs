@@ -9,10 +9,14 @@ pub struct InitializeFunnelAccounts {
     pub funnel_config: Pubkey,
 }
 
-pub fn ix(accounts: InitializeFunnelAccounts, config: FunnelConfig) -> Instruction {
+pub fn ix(
+    accounts: InitializeFunnelAccounts,
+    config: FunnelConfig,
+    authority: Pubkey,
+) -> Instruction {
     Instruction::new_with_borsh(
         crate::ID,
-        &crate::instructions::FunnelInstruction::InitializeFunnel { config },
+        &crate::instructions::FunnelInstruction::InitializeFunnel { config, authority },
         account_metas(accounts),
     )
 }
@@ -1,11 +1,13 @@
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program::invoke;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
 use solana_program::sysvar::Sysvar;
 use solana_program::{system_instruction, system_program};
 
-use crate::{Funnel, FunnelConfig};
+use crate::{Funnel, FunnelConfig, FunnelError};
 
 struct InitializeFunnelAccounts<'a, 'info> {
     /// We need the system_program loaded for CPIs.
@@ -17,18 +19,26 @@ struct InitializeFunnelAccounts<'a, 'info> {
     funnel: &'a AccountInfo<'info>,
 }
 
-pub(crate) fn process(accounts: &[AccountInfo], config: FunnelConfig) -> ProgramResult {
+pub(crate) fn process(
+    accounts: &[AccountInfo],
+    config: FunnelConfig,
+    authority: Pubkey,
+) -> ProgramResult {
     // Pull out all the required accounts.
     let mut accounts = accounts.iter();
     let accounts = InitializeFunnelAccounts {
-        system_program: accounts.next().unwrap(),
-        payer: accounts.next().unwrap(),
-        funnel: accounts.next().unwrap(),
+        system_program: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        payer: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        funnel: accounts.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
     };
 
     // Check accounts.
-    assert!(accounts.funnel.data_is_empty());
-    assert_eq!(accounts.funnel.owner, &system_program::ID);
+    if !accounts.funnel.data_is_empty() || accounts.funnel.owner != &system_program::ID {
+        return Err(FunnelError::FunnelAlreadyInitialized.into());
+    }
+    if !config.is_valid() {
+        return Err(FunnelError::InvalidSplit.into());
+    }
 
     // Transfer lamports for rent if necessary.
     let required = Rent::get()?.minimum_balance(Funnel::LEN);
@@ -58,6 +68,7 @@ pub(crate) fn process(accounts: &[AccountInfo], config: FunnelConfig) -> Program
     let funnel = bytemuck::from_bytes_mut::<Funnel>(&mut funnel);
     funnel.receiver = *accounts.payer.key;
     funnel.config = config;
+    funnel.authority = authority;
 
     Ok(())
 }
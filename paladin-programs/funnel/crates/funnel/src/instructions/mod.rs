@@ -5,12 +5,31 @@ use crate::FunnelConfig;
 
 pub mod become_receiver;
 pub mod initialize_funnel;
+pub mod set_authority;
+pub mod update_config;
+pub mod update_split;
 
 /// All possible Funnel actions.
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub enum FunnelInstruction {
     /// Initializes the [`crate::Funnel`] PDA account.
-    InitializeFunnel { config: FunnelConfig },
+    InitializeFunnel { config: FunnelConfig, authority: Pubkey },
     /// Sweeps the previous owner's rewards and claims ownership of the funnel.
     BecomeReceiver { new_receiver: Pubkey, prepay_lamports: u64 },
+    /// Updates the reward split & commission of the [`crate::Funnel`].
+    ///
+    /// Must be signed by the funnel authority. Funnels created before the split
+    /// was configurable must be updated once before they can be swept again,
+    /// see [`FunnelInstruction::SetAuthority`].
+    UpdateSplit { stakers_bps: u16, holders_bps: u16, commission_bps: u16 },
+    /// Rotates the stakers & holders receivers of the [`crate::Funnel`].
+    ///
+    /// Must be signed by the funnel authority.
+    UpdateConfig { stakers_receiver: Pubkey, holders_receiver: Pubkey },
+    /// Transfers the funnel authority to `new_authority`.
+    ///
+    /// Must be signed by the funnel authority. Funnels created before the
+    /// authority existed have none, any admin instruction signed by both the
+    /// funnel keypair and `authority` migrates them and sets `authority`.
+    SetAuthority { new_authority: Pubkey },
 }
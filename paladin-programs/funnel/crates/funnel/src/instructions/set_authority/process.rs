@@ -0,0 +1,13 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::admin::AdminAccounts;
+
+pub(crate) fn process(accounts: &[AccountInfo], new_authority: Pubkey) -> ProgramResult {
+    let accounts = AdminAccounts::parse(accounts)?;
+    accounts.check_authority()?;
+
+    // Hand over the authority.
+    accounts.with_funnel(|funnel| funnel.authority = new_authority)
+}
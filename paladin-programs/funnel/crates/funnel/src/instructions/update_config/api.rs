@@ -0,0 +1,33 @@
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+pub struct UpdateConfigAccounts {
+    pub payer: Pubkey,
+    pub funnel_config: Pubkey,
+    pub authority: Pubkey,
+}
+
+pub fn ix(
+    accounts: UpdateConfigAccounts,
+    stakers_receiver: Pubkey,
+    holders_receiver: Pubkey,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        crate::ID,
+        &crate::instructions::FunnelInstruction::UpdateConfig {
+            stakers_receiver,
+            holders_receiver,
+        },
+        account_metas(accounts),
+    )
+}
+
+pub fn account_metas(accounts: UpdateConfigAccounts) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta { pubkey: system_program::ID, is_signer: false, is_writable: false },
+        AccountMeta { pubkey: accounts.payer, is_signer: true, is_writable: true },
+        AccountMeta { pubkey: accounts.funnel_config, is_signer: false, is_writable: true },
+        AccountMeta { pubkey: accounts.authority, is_signer: true, is_writable: false },
+    ]
+}
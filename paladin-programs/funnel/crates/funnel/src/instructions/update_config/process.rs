@@ -0,0 +1,20 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use crate::admin::AdminAccounts;
+
+pub(crate) fn process(
+    accounts: &[AccountInfo],
+    stakers_receiver: Pubkey,
+    holders_receiver: Pubkey,
+) -> ProgramResult {
+    let accounts = AdminAccounts::parse(accounts)?;
+    accounts.check_authority()?;
+
+    // Rotate the receivers.
+    accounts.with_funnel(|funnel| {
+        funnel.config.stakers_receiver = stakers_receiver;
+        funnel.config.holders_receiver = holders_receiver;
+    })
+}
@@ -0,0 +1,35 @@
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program;
+
+pub struct UpdateSplitAccounts {
+    pub payer: Pubkey,
+    pub funnel_config: Pubkey,
+    pub authority: Pubkey,
+}
+
+pub fn ix(
+    accounts: UpdateSplitAccounts,
+    stakers_bps: u16,
+    holders_bps: u16,
+    commission_bps: u16,
+) -> Instruction {
+    Instruction::new_with_borsh(
+        crate::ID,
+        &crate::instructions::FunnelInstruction::UpdateSplit {
+            stakers_bps,
+            holders_bps,
+            commission_bps,
+        },
+        account_metas(accounts),
+    )
+}
+
+pub fn account_metas(accounts: UpdateSplitAccounts) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta { pubkey: system_program::ID, is_signer: false, is_writable: false },
+        AccountMeta { pubkey: accounts.payer, is_signer: true, is_writable: true },
+        AccountMeta { pubkey: accounts.funnel_config, is_signer: false, is_writable: true },
+        AccountMeta { pubkey: accounts.authority, is_signer: true, is_writable: false },
+    ]
+}
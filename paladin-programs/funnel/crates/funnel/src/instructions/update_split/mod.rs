@@ -0,0 +1,5 @@
+mod api;
+mod process;
+
+pub use api::*;
+pub(crate) use process::*;
@@ -0,0 +1,29 @@
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::admin::AdminAccounts;
+use crate::FunnelError;
+
+pub(crate) fn process(
+    accounts: &[AccountInfo],
+    stakers_bps: u16,
+    holders_bps: u16,
+    commission_bps: u16,
+) -> ProgramResult {
+    let accounts = AdminAccounts::parse(accounts)?;
+    accounts.check_authority()?;
+
+    // Update the split.
+    accounts.with_funnel(|funnel| -> ProgramResult {
+        let mut config = funnel.config;
+        config.stakers_bps = stakers_bps;
+        config.holders_bps = holders_bps;
+        config.commission_bps = commission_bps;
+        if !config.is_valid() {
+            return Err(FunnelError::InvalidSplit.into());
+        }
+        funnel.config = config;
+
+        Ok(())
+    })?
+}
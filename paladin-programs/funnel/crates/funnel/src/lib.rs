@@ -1,11 +1,14 @@
+mod admin;
 mod constants;
 #[cfg(feature = "bpf-entrypoint")]
 mod entrypoint;
+mod error;
 pub mod instructions;
 mod processor;
 mod state;
 
 pub use constants::*;
+pub use error::FunnelError;
 pub use instructions::FunnelInstruction;
 pub use processor::process;
 pub use state::*;
@@ -1,26 +1,46 @@
 use borsh::BorshDeserialize;
 use solana_program::account_info::AccountInfo;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
 
 use crate::FunnelInstruction;
 
 /// Program instruction dispatch.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if any accounts fail validation or other invariants are broken.
+/// Returns a [`crate::FunnelError`] if any accounts fail validation.
 pub fn process(_: &Pubkey, accounts: &[AccountInfo], mut data: &[u8]) -> ProgramResult {
     // Deserialize.
-    let instruction = FunnelInstruction::deserialize(&mut data).unwrap();
+    let instruction = FunnelInstruction::deserialize(&mut data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
 
     // Dispatch.
     match instruction {
-        FunnelInstruction::InitializeFunnel { config } => {
-            crate::instructions::initialize_funnel::process(accounts, config)
+        FunnelInstruction::InitializeFunnel { config, authority } => {
+            crate::instructions::initialize_funnel::process(accounts, config, authority)
         }
         FunnelInstruction::BecomeReceiver { new_receiver, prepay_lamports } => {
             crate::instructions::become_receiver::process(accounts, new_receiver, prepay_lamports)
         }
+        FunnelInstruction::UpdateSplit { stakers_bps, holders_bps, commission_bps } => {
+            crate::instructions::update_split::process(
+                accounts,
+                stakers_bps,
+                holders_bps,
+                commission_bps,
+            )
+        }
+        FunnelInstruction::UpdateConfig { stakers_receiver, holders_receiver } => {
+            crate::instructions::update_config::process(
+                accounts,
+                stakers_receiver,
+                holders_receiver,
+            )
+        }
+        FunnelInstruction::SetAuthority { new_authority } => {
+            crate::instructions::set_authority::process(accounts, new_authority)
+        }
     }
 }
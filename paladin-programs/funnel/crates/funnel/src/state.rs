@@ -2,6 +2,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, PodCastError, Zeroable};
 use solana_program::pubkey::Pubkey;
 
+use crate::{DEFAULT_COMMISSION_BPS, DEFAULT_HOLDERS_BPS, DEFAULT_STAKERS_BPS, MAX_BPS};
+
 #[derive(Debug, Clone, Copy, Zeroable, Pod, BorshSerialize, BorshDeserialize)]
 #[repr(C)]
 pub struct Funnel {
@@ -10,10 +12,15 @@ pub struct Funnel {
     pub receiver: Pubkey,
     /// Static state that controls the static reward recipients.
     pub config: FunnelConfig,
+    /// The key allowed to update the config & authority.
+    pub authority: Pubkey,
 }
 
 impl Funnel {
-    pub const LEN: usize = 32 + FunnelConfig::LEN;
+    pub const LEN: usize = 32 + FunnelConfig::LEN + 32;
+    /// Size of funnels created before the reward split & authority were
+    /// added.
+    pub const LEGACY_LEN: usize = 96;
     const _LEN_CHECK: () = match std::mem::size_of::<Self>() == Self::LEN {
         true => (),
         false => panic!(),
@@ -23,6 +30,44 @@ impl Funnel {
         bytemuck::try_from_bytes(bytes)
     }
 
+    /// Decodes a funnel in either layout.
+    ///
+    /// Funnels still in the [`Self::LEGACY_LEN`] layout get the default reward
+    /// split & commission (as they would when migrated) and no authority.
+    pub fn load(bytes: &[u8]) -> Result<Self, PodCastError> {
+        if bytes.len() != Self::LEGACY_LEN {
+            return Self::try_from_bytes(bytes).copied();
+        }
+
+        let [receiver, stakers_receiver, holders_receiver] =
+            *bytemuck::try_from_bytes::<[Pubkey; 3]>(bytes)?;
+
+        Ok(Self {
+            receiver,
+            config: FunnelConfig {
+                stakers_receiver,
+                holders_receiver,
+                stakers_bps: DEFAULT_STAKERS_BPS,
+                holders_bps: DEFAULT_HOLDERS_BPS,
+                commission_bps: DEFAULT_COMMISSION_BPS,
+            },
+            authority: Pubkey::default(),
+        })
+    }
+
+    /// Updates the receiver of a funnel in either layout.
+    pub fn set_receiver(bytes: &mut [u8], receiver: Pubkey) -> Result<(), PodCastError> {
+        match bytes.len() {
+            // NB: The receiver leads both layouts.
+            Self::LEN | Self::LEGACY_LEN => {
+                bytes[..32].copy_from_slice(receiver.as_ref());
+
+                Ok(())
+            }
+            _ => Err(PodCastError::SizeMismatch),
+        }
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         bytemuck::bytes_of(self)
     }
@@ -33,15 +78,63 @@ impl Funnel {
 pub struct FunnelConfig {
     pub stakers_receiver: Pubkey,
     pub holders_receiver: Pubkey,
+    /// Share of swept tips paid to `stakers_receiver`, in basis points.
+    pub stakers_bps: u16,
+    /// Share of swept tips paid to `holders_receiver`, in basis points.
+    pub holders_bps: u16,
+    /// Share of its block rewards a leader prepays when becoming the receiver,
+    /// in basis points.
+    pub commission_bps: u16,
 }
 
 impl FunnelConfig {
-    pub const LEN: usize = 64;
+    pub const LEN: usize = 64 + 3 * 2;
     const _LEN_CHECK: () = match std::mem::size_of::<Self>() == Self::LEN {
         true => (),
         false => panic!(),
     };
 
+    /// Returns true if the tip shares sum to at most 100% and the commission
+    /// is at most 100%.
+    pub fn is_valid(&self) -> bool {
+        self.stakers_bps.saturating_add(self.holders_bps) <= MAX_BPS
+            && self.commission_bps <= MAX_BPS
+    }
+
+    /// Splits `total` swept tips between stakers, holders and the validator.
+    ///
+    /// Stakers and holders shares are rounded down, the validator receives the
+    /// remainder.
+    pub fn split_tips(&self, total: u64) -> RewardSplit {
+        let stakers = bps_of(total, self.stakers_bps);
+        let holders = bps_of(total, self.holders_bps);
+        let validator = total.saturating_sub(stakers).saturating_sub(holders);
+
+        RewardSplit { stakers, holders, validator }
+    }
+
+    /// Splits the lamports prepaid by the new receiver between stakers and
+    /// holders, in proportion to their tip shares.
+    ///
+    /// Stakers' share is rounded down, holders receive the remainder. If both
+    /// shares are zero the prepayment is split evenly.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn split_prepay(&self, prepay_lamports: u64) -> (u64, u64) {
+        let shares = u128::from(self.stakers_bps) + u128::from(self.holders_bps);
+        let stakers = match shares {
+            0 => prepay_lamports / 2,
+            // SAFETY: `stakers_bps <= shares` so the result fits in a u64.
+            _ => (u128::from(prepay_lamports) * u128::from(self.stakers_bps) / shares) as u64,
+        };
+
+        (stakers, prepay_lamports - stakers)
+    }
+
+    /// The commission owed to the funnel on `rewards` block rewards.
+    pub fn commission(&self, rewards: u64) -> u64 {
+        bps_of(rewards, self.commission_bps)
+    }
+
     pub fn try_from_bytes(bytes: &[u8]) -> Result<&Self, PodCastError> {
         bytemuck::try_from_bytes(bytes)
     }
@@ -51,6 +144,25 @@ impl FunnelConfig {
     }
 }
 
+/// Lamports owed to each party when the funnel is swept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardSplit {
+    pub stakers: u64,
+    pub holders: u64,
+    pub validator: u64,
+}
+
+// SAFETY:
+//
+// - `MAX_BPS` is a non-zero constant.
+// - `bps` is clamped to `MAX_BPS` so the result is at most `amount`.
+#[allow(clippy::arithmetic_side_effects)]
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    let bps = std::cmp::min(bps, MAX_BPS);
+
+    (u128::from(amount) * u128::from(bps) / u128::from(MAX_BPS)) as u64
+}
+
 pub fn find_leader_state(leader: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[&leader.to_bytes()], &crate::id())
 }
@@ -36,6 +36,10 @@ pub enum PaladinLockupError {
     /// The provided pool account did not match the lockup.
     #[error("Incorrect pool account")]
     IncorrectPool,
+    /// The amount is zero, or not less than the lockup's amount for a partial
+    /// unlock.
+    #[error("Invalid amount.")]
+    InvalidAmount,
 }
 
 impl PrintProgramError for PaladinLockupError {
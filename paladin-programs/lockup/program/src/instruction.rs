@@ -178,6 +178,142 @@ pub enum PaladinLockupInstruction {
         description = "Token program"
     )]
     Withdraw,
+    /// Increase the amount of an active lockup.
+    ///
+    /// Transfers the additional tokens into escrow and moves the lockup's pool
+    /// entry to its new position. A lockup that was evicted from the pool is
+    /// re-entered if the new amount is large enough.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[s]` Lockup authority.
+    /// 1. `[s]` Token owner.
+    /// 2. `[w]` Depositor token account.
+    /// 3. `[w]` Lockup pool account.
+    /// 4. `[w]` Lockup account.
+    /// 5. `[ ]` Escrow authority.
+    /// 6. `[w]` Escrow token account.
+    /// 7. `[ ]` Token mint.
+    /// 8. `[ ]` Token program.
+    #[account(
+        0,
+        signer,
+        name = "lockup_authority",
+        description = "Lockup authority"
+    )]
+    #[account(
+        1,
+        signer,
+        name = "token_owner",
+        description = "Token owner"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "depositor_token_account",
+        description = "Depositor token account"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "lockup_pool",
+        description = "Lockup pool"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "lockup_account",
+        description = "Lockup account"
+    )]
+    #[account(
+        5,
+        name = "escrow_authority",
+        description = "Escrow authority"
+    )]
+    #[account(
+        6,
+        writable,
+        name = "escrow_token_account",
+        description = "Escrow token account"
+    )]
+    #[account(
+        7,
+        name = "token_mint",
+        description = "Token mint"
+    )]
+    #[account(
+        8,
+        name = "token_program",
+        description = "Token program"
+    )]
+    IncreaseLockup { amount: u64 },
+    /// Extend an active lockup, restarting its lockup period and returning it
+    /// to the pool if it was evicted.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[s]` Lockup authority.
+    /// 1. `[w]` Lockup pool account.
+    /// 2. `[w]` Lockup account.
+    #[account(
+        0,
+        signer,
+        name = "lockup_authority",
+        description = "Lockup authority"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "lockup_pool",
+        description = "Lockup pool"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "lockup_account",
+        description = "Lockup account"
+    )]
+    ExtendLockup,
+    /// Unlock part of a token lockup.
+    ///
+    /// The unlocked amount is moved into a new, already unlocked, lockup
+    /// account that can be withdrawn after cooldown. The rest stays locked up.
+    ///
+    /// Expects an uninitialized unlocked lockup account with enough
+    /// rent-exempt lamports to store lockup state, owned by the Paladin Lockup
+    /// program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[s]` Lockup authority.
+    /// 1. `[w]` Lockup pool account.
+    /// 2. `[w]` Lockup account.
+    /// 3. `[w]` Unlocked lockup account.
+    #[account(
+        0,
+        signer,
+        name = "lockup_authority",
+        description = "Lockup authority"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "lockup_pool",
+        description = "Lockup pool"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "lockup_account",
+        description = "Lockup account"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "unlocked_lockup_account",
+        description = "Unlocked lockup account"
+    )]
+    PartialUnlock { amount: u64 },
 }
 
 impl PaladinLockupInstruction {
@@ -196,6 +332,19 @@ impl PaladinLockupInstruction {
             }
             Self::Unlock => vec![2],
             Self::Withdraw => vec![3],
+            Self::IncreaseLockup { amount } => {
+                let mut buf = Vec::with_capacity(1 + 8);
+                buf.push(4);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf
+            }
+            Self::ExtendLockup => vec![5],
+            Self::PartialUnlock { amount } => {
+                let mut buf = Vec::with_capacity(1 + 8);
+                buf.push(6);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf
+            }
         }
     }
 
@@ -212,6 +361,17 @@ impl PaladinLockupInstruction {
             }
             Some((&2, _)) => Ok(Self::Unlock),
             Some((&3, _)) => Ok(Self::Withdraw),
+            Some((&4, rest)) if rest.len() == 8 => {
+                let amount = u64::from_le_bytes(rest.try_into().unwrap());
+
+                Ok(Self::IncreaseLockup { amount })
+            }
+            Some((&5, _)) => Ok(Self::ExtendLockup),
+            Some((&6, rest)) if rest.len() == 8 => {
+                let amount = u64::from_le_bytes(rest.try_into().unwrap());
+
+                Ok(Self::PartialUnlock { amount })
+            }
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -314,6 +474,81 @@ pub fn withdraw(
     Instruction::new_with_bytes(crate::id(), &data, accounts)
 }
 
+/// Creates an
+/// [IncreaseLockup](enum.PaladinLockupInstruction.html)
+/// instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn increase_lockup(
+    lockup_authority_address: &Pubkey,
+    token_owner_address: &Pubkey,
+    token_account_address: &Pubkey,
+    pool: Pubkey,
+    lockup_address: &Pubkey,
+    mint_address: &Pubkey,
+    amount: u64,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let escrow_authority_address = get_escrow_authority_address(&crate::id());
+    let escrow_token_account_address = get_associated_token_address_with_program_id(
+        &escrow_authority_address,
+        mint_address,
+        token_program_id,
+    );
+    let accounts = vec![
+        AccountMeta::new_readonly(*lockup_authority_address, true),
+        AccountMeta::new_readonly(*token_owner_address, true),
+        AccountMeta::new(*token_account_address, false),
+        AccountMeta::new(pool, false),
+        AccountMeta::new(*lockup_address, false),
+        AccountMeta::new_readonly(escrow_authority_address, false),
+        AccountMeta::new(escrow_token_account_address, false),
+        AccountMeta::new_readonly(*mint_address, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    let data = PaladinLockupInstruction::IncreaseLockup { amount }.pack();
+
+    Instruction::new_with_bytes(crate::id(), &data, accounts)
+}
+
+/// Creates an
+/// [ExtendLockup](enum.PaladinLockupInstruction.html)
+/// instruction.
+pub fn extend_lockup(
+    lockup_authority_address: &Pubkey,
+    lockup_pool: Pubkey,
+    lockup_address: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*lockup_authority_address, true),
+        AccountMeta::new(lockup_pool, false),
+        AccountMeta::new(*lockup_address, false),
+    ];
+    let data = PaladinLockupInstruction::ExtendLockup.pack();
+
+    Instruction::new_with_bytes(crate::id(), &data, accounts)
+}
+
+/// Creates a
+/// [PartialUnlock](enum.PaladinLockupInstruction.html)
+/// instruction.
+pub fn partial_unlock(
+    lockup_authority_address: &Pubkey,
+    lockup_pool: Pubkey,
+    lockup_address: &Pubkey,
+    unlocked_lockup_address: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*lockup_authority_address, true),
+        AccountMeta::new(lockup_pool, false),
+        AccountMeta::new(*lockup_address, false),
+        AccountMeta::new(*unlocked_lockup_address, false),
+    ];
+    let data = PaladinLockupInstruction::PartialUnlock { amount }.pack();
+
+    Instruction::new_with_bytes(crate::id(), &data, accounts)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,4 +581,19 @@ mod tests {
     fn test_pack_unpack_withdraw() {
         test_pack_unpack(PaladinLockupInstruction::Withdraw);
     }
+
+    #[test]
+    fn test_pack_unpack_increase_lockup() {
+        test_pack_unpack(PaladinLockupInstruction::IncreaseLockup { amount: 42 });
+    }
+
+    #[test]
+    fn test_pack_unpack_extend_lockup() {
+        test_pack_unpack(PaladinLockupInstruction::ExtendLockup);
+    }
+
+    #[test]
+    fn test_pack_unpack_partial_unlock() {
+        test_pack_unpack(PaladinLockupInstruction::PartialUnlock { amount: 42 });
+    }
 }
@@ -26,6 +26,62 @@ use {
     std::{cmp::Reverse, num::NonZeroU64},
 };
 
+/// Inserts an entry into the pool, keeping the entries sorted by descending
+/// amount.
+///
+/// Evicts the smallest entry if the pool is full, failing if the new entry is
+/// not larger than it.
+fn insert_pool_entry(pool: &mut LockupPool, entry: LockupPoolEntry) -> ProgramResult {
+    // Evict the smallest lock if necessary.
+    let last_index = std::cmp::min(pool.entries_len, LockupPool::LOCKUP_CAPACITY - 1);
+    let last_amount = pool.entries[last_index].amount;
+    match (
+        pool.entries_len == pool.entries.len(),
+        entry.amount > last_amount,
+    ) {
+        (true, true) => {}
+        (true, false) => return Err(PaladinLockupError::AmountTooLow.into()),
+        (false, _) => pool.entries_len = pool.entries_len.checked_add(1).unwrap(),
+    }
+
+    // Binary search & insert the entry.
+    let index = match pool
+        .entries
+        .binary_search_by_key(&Reverse(entry.amount), |entry| Reverse(entry.amount))
+    {
+        Ok(index) => index,
+        Err(index) => index,
+    };
+    *pool.entries.last_mut().unwrap() = entry;
+    pool.entries[index..].rotate_right(1);
+
+    Ok(())
+}
+
+/// Removes a lockup's entry from the pool, keeping the remaining entries
+/// sorted.
+///
+/// Returns `false` if the lockup was not in the pool (i.e. it was evicted).
+fn remove_pool_entry(pool: &mut LockupPool, lockup: &Pubkey, amount: u64) -> bool {
+    let entries = &pool.entries[..pool.entries_len];
+    let partition_point = entries.partition_point(|entry| entry.amount > amount);
+    let Some(offset) = entries[partition_point..]
+        .iter()
+        .take_while(|entry| entry.amount == amount)
+        .position(|entry| &entry.lockup == lockup)
+    else {
+        return false;
+    };
+    #[allow(clippy::arithmetic_side_effects)]
+    let index = partition_point + offset;
+
+    pool.entries[index] = LockupPoolEntry::default();
+    pool.entries[index..].rotate_left(1);
+    pool.entries_len = pool.entries_len.checked_sub(1).unwrap();
+
+    true
+}
+
 /// Processes a
 /// [InitializeLockupPool](enum.PaladinInitializeLockupPoolInstruction.html)
 /// instruction.
@@ -128,37 +184,15 @@ fn process_lockup(
             metadata,
         };
 
-    // Evict the smallest lock if necessary.
-    let last_index = std::cmp::min(
-        lockup_pool_state.entries_len,
-        LockupPool::LOCKUP_CAPACITY - 1,
-    );
-    let last_amount = lockup_pool_state.entries[last_index].amount;
-    match (
-        lockup_pool_state.entries_len == lockup_pool_state.entries.len(),
-        amount > last_amount,
-    ) {
-        (true, true) => {}
-        (true, false) => return Err(PaladinLockupError::AmountTooLow.into()),
-        (false, _) => {
-            lockup_pool_state.entries_len = lockup_pool_state.entries_len.checked_add(1).unwrap()
-        }
-    }
-
-    // Binary search & insert the entry.
-    let index = match lockup_pool_state
-        .entries
-        .binary_search_by_key(&Reverse(amount), |entry| Reverse(entry.amount))
-    {
-        Ok(index) => index,
-        Err(index) => index,
-    };
-    *lockup_pool_state.entries.last_mut().unwrap() = LockupPoolEntry {
-        lockup: *lockup_info.key,
-        amount,
-        metadata,
-    };
-    lockup_pool_state.entries[index..].rotate_right(1);
+    // Insert the entry into the pool.
+    insert_pool_entry(
+        lockup_pool_state,
+        LockupPoolEntry {
+            lockup: *lockup_info.key,
+            amount,
+            metadata,
+        },
+    )?;
 
     // Transfer the tokens to the escrow token account.
     {
@@ -249,22 +283,7 @@ fn process_unlock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResul
     }
 
     // Remove the entry from the pool (if it exists).
-    let partition_point = lockup_pool_state
-        .entries
-        .partition_point(|entry| entry.amount > state.amount);
-    if partition_point != lockup_pool_state.entries_len {
-        let offset = lockup_pool_state.entries[partition_point..]
-            .iter()
-            .take_while(|entry| entry.amount == state.amount)
-            .position(|entry| &entry.lockup == lockup_info.key)
-            .unwrap();
-        #[allow(clippy::arithmetic_side_effects)]
-        let index = partition_point + offset;
-
-        lockup_pool_state.entries[index] = LockupPoolEntry::default();
-        lockup_pool_state.entries[index..].rotate_left(1);
-        lockup_pool_state.entries_len = lockup_pool_state.entries_len.checked_sub(1).unwrap();
-    }
+    remove_pool_entry(lockup_pool_state, lockup_info.key, state.amount);
 
     Ok(())
 }
@@ -394,6 +413,341 @@ fn process_withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRes
     Ok(())
 }
 
+/// Processes an
+/// [IncreaseLockup](enum.PaladinLockupInstruction.html)
+/// instruction.
+fn process_increase_lockup(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let lockup_authority_info = next_account_info(accounts_iter)?;
+    let token_owner_info = next_account_info(accounts_iter)?;
+    let token_account_info = next_account_info(accounts_iter)?;
+    let lockup_pool_info = next_account_info(accounts_iter)?;
+    let lockup_info = next_account_info(accounts_iter)?;
+    let escrow_authority_info = next_account_info(accounts_iter)?;
+    let escrow_token_account_info = next_account_info(accounts_iter)?;
+    let mint_info = next_account_info(accounts_iter)?;
+    let token_program_info = next_account_info(accounts_iter)?;
+
+    // Validate & deserialize the lockup pool.
+    if lockup_pool_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if lockup_pool_info.data_len() != LockupPool::LEN {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let mut lockup_pool_data = lockup_pool_info.data.borrow_mut();
+    let lockup_pool_state = bytemuck::from_bytes_mut::<LockupPool>(&mut lockup_pool_data);
+
+    // Ensure the lockup authority is a signer.
+    if !lockup_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Ensure the lockup account is owned by the Paladin Lockup program.
+    if lockup_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Ensure the lockup account is initialized.
+    if !(lockup_info.data_len() == std::mem::size_of::<Lockup>()
+        && &lockup_info.try_borrow_data()?[0..8] == Lockup::SPL_DISCRIMINATOR_SLICE)
+    {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Ensure the provided escrow authority address is correct.
+    if !escrow_authority_info
+        .key
+        .eq(&get_escrow_authority_address(program_id))
+    {
+        return Err(PaladinLockupError::IncorrectEscrowAuthorityAddress.into());
+    }
+
+    // Ensure the provided escrow token account address is correct.
+    if !escrow_token_account_info
+        .key
+        .eq(&get_associated_token_address_with_program_id(
+            escrow_authority_info.key,
+            mint_info.key,
+            token_program_info.key,
+        ))
+    {
+        return Err(PaladinLockupError::IncorrectEscrowTokenAccount.into());
+    }
+
+    {
+        let mut data = lockup_info.try_borrow_mut_data()?;
+        let state = bytemuck::try_from_bytes_mut::<Lockup>(&mut data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        // Ensure the provided authority is the same as the lockup's authority.
+        if state.authority != *lockup_authority_info.key {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        // Ensure the lockup account has not been unlocked.
+        if state.lockup_end_timestamp.is_some() {
+            return Err(PaladinLockupError::LockupAlreadyUnlocked.into());
+        }
+
+        // Ensure the lockup matches the pool.
+        if lockup_pool_info.key != &state.pool {
+            return Err(PaladinLockupError::IncorrectPool.into());
+        }
+
+        // Ensure the provided mint is the same as the lockup's mint.
+        if state.mint != *mint_info.key {
+            return Err(PaladinLockupError::IncorrectMint.into());
+        }
+
+        if amount == 0 {
+            return Err(PaladinLockupError::InvalidAmount.into());
+        }
+        let new_amount = state
+            .amount
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        // Re-insert the entry with the new amount. If the lockup was
+        // previously evicted it must now be large enough to re-enter the
+        // pool.
+        remove_pool_entry(lockup_pool_state, lockup_info.key, state.amount);
+        insert_pool_entry(
+            lockup_pool_state,
+            LockupPoolEntry {
+                lockup: *lockup_info.key,
+                amount: new_amount,
+                metadata: state.metadata,
+            },
+        )?;
+
+        state.amount = new_amount;
+    }
+
+    // Transfer the tokens to the escrow token account.
+    {
+        let decimals = {
+            let mint_data = mint_info.try_borrow_data()?;
+            let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+            mint.base.decimals
+        };
+
+        spl_token_2022::onchain::invoke_transfer_checked(
+            &spl_token_2022::id(),
+            token_account_info.clone(),
+            mint_info.clone(),
+            escrow_token_account_info.clone(),
+            token_owner_info.clone(),
+            accounts_iter.as_slice(),
+            amount,
+            decimals,
+            &[],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Processes an
+/// [ExtendLockup](enum.PaladinLockupInstruction.html)
+/// instruction.
+fn process_extend_lockup(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let lockup_authority_info = next_account_info(accounts_iter)?;
+    let lockup_pool_info = next_account_info(accounts_iter)?;
+    let lockup_info = next_account_info(accounts_iter)?;
+
+    // Validate & deserialize the lockup pool.
+    if lockup_pool_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if lockup_pool_info.data_len() != LockupPool::LEN {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let mut lockup_pool_data = lockup_pool_info.data.borrow_mut();
+    let lockup_pool_state = bytemuck::from_bytes_mut::<LockupPool>(&mut lockup_pool_data);
+
+    // Ensure the lockup authority is a signer.
+    if !lockup_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Ensure the lockup account is owned by the Paladin Lockup program.
+    if lockup_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Ensure the lockup account is initialized.
+    if !(lockup_info.data_len() == std::mem::size_of::<Lockup>()
+        && &lockup_info.try_borrow_data()?[0..8] == Lockup::SPL_DISCRIMINATOR_SLICE)
+    {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let mut data = lockup_info.try_borrow_mut_data()?;
+    let state = bytemuck::try_from_bytes_mut::<Lockup>(&mut data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // Ensure the provided authority is the same as the lockup's authority.
+    if state.authority != *lockup_authority_info.key {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    // Ensure the lockup account has not been unlocked. Unlocked tokens must
+    // be withdrawn and locked up again with `Lockup`.
+    if state.lockup_end_timestamp.is_some() {
+        return Err(PaladinLockupError::LockupAlreadyUnlocked.into());
+    }
+
+    // Ensure the lockup matches the pool.
+    if lockup_pool_info.key != &state.pool {
+        return Err(PaladinLockupError::IncorrectPool.into());
+    }
+
+    // Re-insert the entry, returning it to the pool if it was evicted. It must
+    // then be large enough to re-enter the pool.
+    remove_pool_entry(lockup_pool_state, lockup_info.key, state.amount);
+    insert_pool_entry(
+        lockup_pool_state,
+        LockupPoolEntry {
+            lockup: *lockup_info.key,
+            amount: state.amount,
+            metadata: state.metadata,
+        },
+    )?;
+
+    // Restart the lockup period.
+    state.lockup_start_timestamp = <Clock as Sysvar>::get()?.unix_timestamp as u64;
+
+    Ok(())
+}
+
+/// Processes a
+/// [PartialUnlock](enum.PaladinLockupInstruction.html)
+/// instruction.
+fn process_partial_unlock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let lockup_authority_info = next_account_info(accounts_iter)?;
+    let lockup_pool_info = next_account_info(accounts_iter)?;
+    let lockup_info = next_account_info(accounts_iter)?;
+    let unlocked_lockup_info = next_account_info(accounts_iter)?;
+
+    // Validate & deserialize the lockup pool.
+    if lockup_pool_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if lockup_pool_info.data_len() != LockupPool::LEN {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let mut lockup_pool_data = lockup_pool_info.data.borrow_mut();
+    let lockup_pool_state = bytemuck::from_bytes_mut::<LockupPool>(&mut lockup_pool_data);
+
+    // Ensure the lockup authority is a signer.
+    if !lockup_authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Ensure the lockup account is owned by the Paladin Lockup program.
+    if lockup_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Ensure the lockup account is initialized.
+    if !(lockup_info.data_len() == std::mem::size_of::<Lockup>()
+        && &lockup_info.try_borrow_data()?[0..8] == Lockup::SPL_DISCRIMINATOR_SLICE)
+    {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Ensure the unlocked lockup account is owned by the Paladin Lockup
+    // program.
+    if unlocked_lockup_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Ensure the unlocked lockup account has enough space.
+    if unlocked_lockup_info.data_len() != std::mem::size_of::<Lockup>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Ensure the unlocked lockup account is not initialized.
+    if &unlocked_lockup_info.try_borrow_data()?[0..8]
+        != ArrayDiscriminator::UNINITIALIZED.as_slice()
+    {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mut data = lockup_info.try_borrow_mut_data()?;
+    let state = bytemuck::try_from_bytes_mut::<Lockup>(&mut data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    // Ensure the provided authority is the same as the lockup's authority.
+    if state.authority != *lockup_authority_info.key {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    // Ensure the lockup account has not already been unlocked.
+    if state.lockup_end_timestamp.is_some() {
+        return Err(PaladinLockupError::LockupAlreadyUnlocked.into());
+    }
+
+    // Ensure the lockup matches the pool.
+    if lockup_pool_info.key != &state.pool {
+        return Err(PaladinLockupError::IncorrectPool.into());
+    }
+
+    // Ensure some, but not all, of the lockup is being unlocked. Unlocking
+    // the full amount is done with `Unlock`.
+    if amount == 0 || amount >= state.amount {
+        return Err(PaladinLockupError::InvalidAmount.into());
+    }
+    #[allow(clippy::arithmetic_side_effects)]
+    let remaining_amount = state.amount - amount;
+
+    // Re-insert the entry with the remaining amount. The entry was removed
+    // first so this cannot evict another lockup.
+    if remove_pool_entry(lockup_pool_state, lockup_info.key, state.amount) {
+        insert_pool_entry(
+            lockup_pool_state,
+            LockupPoolEntry {
+                lockup: *lockup_info.key,
+                amount: remaining_amount,
+                metadata: state.metadata,
+            },
+        )?;
+    }
+    state.amount = remaining_amount;
+
+    // Move the unlocked amount into the new lockup account, which can be
+    // withdrawn after cooldown. The tokens stay in the escrow token account.
+    let clock = <Clock as Sysvar>::get()?;
+    let mut unlocked_data = unlocked_lockup_info.try_borrow_mut_data()?;
+    *bytemuck::try_from_bytes_mut(&mut unlocked_data)
+        .map_err(|_| ProgramError::InvalidAccountData)? = Lockup {
+        discriminator: Lockup::SPL_DISCRIMINATOR.into(),
+        amount,
+        authority: state.authority,
+        lockup_start_timestamp: state.lockup_start_timestamp,
+        lockup_end_timestamp: NonZeroU64::new(clock.unix_timestamp as u64),
+        mint: state.mint,
+        pool: state.pool,
+        metadata: state.metadata,
+    };
+
+    Ok(())
+}
+
 /// Processes a
 /// [PaladinLockupInstruction](enum.PaladinLockupInstruction.html).
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
@@ -415,5 +769,17 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
             msg!("Instruction: Withdraw");
             process_withdraw(program_id, accounts)
         }
+        PaladinLockupInstruction::IncreaseLockup { amount } => {
+            msg!("Instruction: IncreaseLockup");
+            process_increase_lockup(program_id, accounts, amount)
+        }
+        PaladinLockupInstruction::ExtendLockup => {
+            msg!("Instruction: ExtendLockup");
+            process_extend_lockup(program_id, accounts)
+        }
+        PaladinLockupInstruction::PartialUnlock { amount } => {
+            msg!("Instruction: PartialUnlock");
+            process_partial_unlock(program_id, accounts, amount)
+        }
     }
 }
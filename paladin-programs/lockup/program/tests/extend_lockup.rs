@@ -0,0 +1,357 @@
+#![cfg(feature = "test-sbf")]
+
+mod setup;
+
+use {
+    paladin_lockup_program::{
+        error::PaladinLockupError,
+        state::{Lockup, LockupPool, LockupPoolEntry},
+    },
+    setup::{get_lockup_pool, setup, setup_lockup, setup_lockup_pool_with_entries},
+    solana_program_test::*,
+    solana_sdk::{
+        account::AccountSharedData,
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_discriminator::SplDiscriminate,
+    std::num::NonZeroU64,
+};
+
+fn active_lockup(authority: &Pubkey, pool: Pubkey, amount: u64) -> Lockup {
+    Lockup {
+        discriminator: Lockup::SPL_DISCRIMINATOR.into(),
+        amount,
+        authority: *authority,
+        lockup_start_timestamp: 10,
+        lockup_end_timestamp: None,
+        mint: Pubkey::new_unique(),
+        pool,
+        metadata: [7; 32],
+    }
+}
+
+async fn extend_lockup(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    pool: Pubkey,
+    lockup: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let instruction =
+        paladin_lockup_program::instruction::extend_lockup(&authority.pubkey(), pool, lockup);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    let _ = context.get_new_latest_blockhash().await.unwrap();
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn fail_extend_lockup_authority_not_signer() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    let mut instruction =
+        paladin_lockup_program::instruction::extend_lockup(&authority.pubkey(), pool, &lockup);
+    instruction.accounts[0].is_signer = false;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer], // authority not signer.
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_incorrect_lockup_authority() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        active_lockup(&Pubkey::new_unique(), pool, 10_000), // Incorrect authority.
+    )
+    .await;
+
+    let err = extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::IncorrectAuthority)
+    );
+}
+
+#[tokio::test]
+async fn fail_extend_lockup_invalid_pool() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // The pool account is not owned by the program.
+    let pool = Pubkey::new_unique();
+    context.set_account(
+        &pool,
+        &AccountSharedData::new(100_000_000, LockupPool::LEN, &Pubkey::new_unique()),
+    );
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        active_lockup(&authority.pubkey(), pool, 10_000),
+    )
+    .await;
+
+    let err = extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountOwner)
+    );
+}
+
+#[tokio::test]
+async fn fail_extend_lockup_already_unlocked() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        Lockup {
+            lockup_end_timestamp: NonZeroU64::new(20),
+            ..active_lockup(&authority.pubkey(), pool, 10_000)
+        },
+    )
+    .await;
+
+    let err = extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::LockupAlreadyUnlocked as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_extend_lockup_incorrect_pool() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool accounts.
+    let pool1 = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool1, &[]).await;
+    let pool2 = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool2, &[]).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        active_lockup(&authority.pubkey(), pool1, 10_000),
+    )
+    .await;
+
+    let err = extend_lockup(&mut context, &authority, pool2, &lockup)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::IncorrectPool as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_extend_lockup_amount_too_low() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Fill the pool with lockups larger than ours.
+    let pool = Pubkey::new_unique();
+    let entries = [LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 50_000,
+        metadata: [0; 32],
+    }; LockupPool::LOCKUP_CAPACITY];
+    setup_lockup_pool_with_entries(&mut context, &pool, &entries).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        active_lockup(&authority.pubkey(), pool, 10_000),
+    )
+    .await;
+
+    let err = extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::AmountTooLow as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn success_evicted() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account with a larger & a smaller lockup, the
+    // lockup itself was evicted.
+    let pool = Pubkey::new_unique();
+    let larger = LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 20_000,
+        metadata: [1; 32],
+    };
+    let smaller = LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 5_000,
+        metadata: [2; 32],
+    };
+    setup_lockup_pool_with_entries(&mut context, &pool, &[larger, smaller]).await;
+
+    let state = active_lockup(&authority.pubkey(), pool, 10_000);
+    setup_lockup(&mut context, &lockup, state).await;
+
+    extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap();
+
+    // Check the lockup period was restarted.
+    let lockup_account = context
+        .banks_client
+        .get_account(lockup)
+        .await
+        .unwrap()
+        .unwrap();
+    let new_state = bytemuck::from_bytes::<Lockup>(&lockup_account.data);
+    assert!(new_state.lockup_start_timestamp > state.lockup_start_timestamp);
+    assert_eq!(
+        new_state,
+        &Lockup {
+            lockup_start_timestamp: new_state.lockup_start_timestamp,
+            ..state
+        }
+    );
+
+    // Check the lockup is back in the pool, in order.
+    let lockup_pool = get_lockup_pool(&mut context, &pool).await;
+    assert_eq!(lockup_pool.entries_len, 3);
+    assert_eq!(
+        lockup_pool.entries[..3],
+        [
+            larger,
+            LockupPoolEntry {
+                lockup,
+                amount: 10_000,
+                metadata: state.metadata,
+            },
+            smaller,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn success_in_pool() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account with the lockup already in it.
+    let pool = Pubkey::new_unique();
+    let state = active_lockup(&authority.pubkey(), pool, 10_000);
+    let entry = LockupPoolEntry {
+        lockup,
+        amount: 10_000,
+        metadata: state.metadata,
+    };
+    let other = LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 10_000,
+        metadata: [1; 32],
+    };
+    setup_lockup_pool_with_entries(&mut context, &pool, &[other, entry]).await;
+    setup_lockup(&mut context, &lockup, state).await;
+
+    extend_lockup(&mut context, &authority, pool, &lockup)
+        .await
+        .unwrap();
+
+    // Check the lockup was not duplicated.
+    let lockup_pool = get_lockup_pool(&mut context, &pool).await;
+    assert_eq!(lockup_pool.entries_len, 2);
+    assert_eq!(
+        lockup_pool.entries[..2]
+            .iter()
+            .filter(|entry| entry.lockup == lockup)
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,423 @@
+#![cfg(feature = "test-sbf")]
+
+mod setup;
+
+use {
+    paladin_lockup_program::{
+        error::PaladinLockupError,
+        state::{get_escrow_authority_address, Lockup, LockupPool, LockupPoolEntry},
+    },
+    setup::{
+        get_lockup_pool, setup, setup_lockup, setup_lockup_pool_with_entries, setup_mint,
+        setup_token_account,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_discriminator::SplDiscriminate,
+    spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount},
+    std::num::NonZeroU64,
+};
+
+struct Fixture {
+    lockup_authority: Keypair,
+    token_owner: Keypair,
+    token_account: Pubkey,
+    escrow_token_account: Pubkey,
+    mint: Pubkey,
+    pool: Pubkey,
+}
+
+async fn setup_fixture(context: &mut ProgramTestContext, token_amount: u64) -> Fixture {
+    let lockup_authority = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let token_owner = Keypair::new();
+    let token_account = get_associated_token_address_with_program_id(
+        &token_owner.pubkey(),
+        &mint,
+        &spl_token_2022::id(),
+    );
+    let escrow_authority = get_escrow_authority_address(&paladin_lockup_program::id());
+    let escrow_token_account = get_associated_token_address_with_program_id(
+        &escrow_authority,
+        &mint,
+        &spl_token_2022::id(),
+    );
+
+    setup_mint(context, &mint, &Pubkey::new_unique(), token_amount).await;
+    setup_token_account(
+        context,
+        &token_account,
+        &token_owner.pubkey(),
+        &mint,
+        token_amount,
+    )
+    .await;
+    setup_token_account(context, &escrow_token_account, &escrow_authority, &mint, 0).await;
+
+    Fixture {
+        lockup_authority,
+        token_owner,
+        token_account,
+        escrow_token_account,
+        mint,
+        pool: Pubkey::new_unique(),
+    }
+}
+
+fn lockup_state(fixture: &Fixture, amount: u64, metadata: [u8; 32]) -> Lockup {
+    Lockup {
+        discriminator: Lockup::SPL_DISCRIMINATOR.into(),
+        amount,
+        authority: fixture.lockup_authority.pubkey(),
+        lockup_start_timestamp: 10,
+        lockup_end_timestamp: None,
+        mint: fixture.mint,
+        pool: fixture.pool,
+        metadata,
+    }
+}
+
+async fn increase_lockup(
+    context: &mut ProgramTestContext,
+    fixture: &Fixture,
+    lockup: &Pubkey,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let instruction = paladin_lockup_program::instruction::increase_lockup(
+        &fixture.lockup_authority.pubkey(),
+        &fixture.token_owner.pubkey(),
+        &fixture.token_account,
+        fixture.pool,
+        lockup,
+        &fixture.mint,
+        amount,
+        &spl_token_2022::id(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[
+            &context.payer,
+            &fixture.lockup_authority,
+            &fixture.token_owner,
+        ],
+        context.last_blockhash,
+    );
+    let _ = context.get_new_latest_blockhash().await.unwrap();
+    context.banks_client.process_transaction(transaction).await
+}
+
+async fn check_token_account_balance(
+    context: &mut ProgramTestContext,
+    token_account_address: &Pubkey,
+    check_amount: u64,
+) {
+    let account = context
+        .banks_client
+        .get_account(*token_account_address)
+        .await
+        .expect("get_account")
+        .expect("account not found");
+    let actual_amount = StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount;
+    assert_eq!(actual_amount, check_amount);
+}
+
+#[tokio::test]
+async fn fail_increase_lockup_authority_not_signer() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &[]).await;
+
+    let lockup = Pubkey::new_unique();
+    setup_lockup(
+        &mut context,
+        &lockup,
+        lockup_state(&fixture, 1_000, [1; 32]),
+    )
+    .await;
+
+    let mut instruction = paladin_lockup_program::instruction::increase_lockup(
+        &fixture.lockup_authority.pubkey(),
+        &fixture.token_owner.pubkey(),
+        &fixture.token_account,
+        fixture.pool,
+        &lockup,
+        &fixture.mint,
+        1_000,
+        &spl_token_2022::id(),
+    );
+    instruction.accounts[0].is_signer = false;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &fixture.token_owner], // Lockup authority not signer.
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_increase_lockup_already_unlocked() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &[]).await;
+
+    let lockup = Pubkey::new_unique();
+    setup_lockup(
+        &mut context,
+        &lockup,
+        Lockup {
+            lockup_end_timestamp: NonZeroU64::new(20),
+            ..lockup_state(&fixture, 1_000, [1; 32])
+        },
+    )
+    .await;
+
+    let err = increase_lockup(&mut context, &fixture, &lockup, 1_000)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::LockupAlreadyUnlocked as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_increase_lockup_zero_amount() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &[]).await;
+
+    let lockup = Pubkey::new_unique();
+    setup_lockup(
+        &mut context,
+        &lockup,
+        lockup_state(&fixture, 1_000, [1; 32]),
+    )
+    .await;
+
+    let err = increase_lockup(&mut context, &fixture, &lockup, 0)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::InvalidAmount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_increase_lockup_incorrect_mint() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &[]).await;
+
+    let lockup = Pubkey::new_unique();
+    setup_lockup(
+        &mut context,
+        &lockup,
+        Lockup {
+            mint: Pubkey::new_unique(), // Incorrect mint.
+            ..lockup_state(&fixture, 1_000, [1; 32])
+        },
+    )
+    .await;
+
+    let err = increase_lockup(&mut context, &fixture, &lockup, 1_000)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::IncorrectMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_increase_lockup_evicted_amount_too_low() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+
+    // Fill the pool with lockups larger than ours.
+    let entries = [LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 5_000,
+        metadata: [0; 32],
+    }; LockupPool::LOCKUP_CAPACITY];
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &entries).await;
+
+    let lockup = Pubkey::new_unique();
+    setup_lockup(
+        &mut context,
+        &lockup,
+        lockup_state(&fixture, 1_000, [1; 32]),
+    )
+    .await;
+
+    let err = increase_lockup(&mut context, &fixture, &lockup, 1_000)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::AmountTooLow as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn success() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 10_000).await;
+
+    let lockup = Pubkey::new_unique();
+    let entry = LockupPoolEntry {
+        lockup,
+        amount: 1_000,
+        metadata: [1; 32],
+    };
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &[entry]).await;
+    setup_lockup(
+        &mut context,
+        &lockup,
+        lockup_state(&fixture, 1_000, [1; 32]),
+    )
+    .await;
+
+    increase_lockup(&mut context, &fixture, &lockup, 2_500)
+        .await
+        .unwrap();
+
+    // Check the lockup account.
+    let lockup_account = context
+        .banks_client
+        .get_account(lockup)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        bytemuck::from_bytes::<Lockup>(&lockup_account.data),
+        &lockup_state(&fixture, 3_500, [1; 32]),
+    );
+
+    // Check the pool entry.
+    let lockup_pool = get_lockup_pool(&mut context, &fixture.pool).await;
+    assert_eq!(lockup_pool.entries_len, 1);
+    assert_eq!(
+        lockup_pool.entries[0],
+        LockupPoolEntry {
+            amount: 3_500,
+            ..entry
+        }
+    );
+
+    // Validate tokens were transferred from the token account to the escrow.
+    check_token_account_balance(&mut context, &fixture.token_account, 7_500).await;
+    check_token_account_balance(&mut context, &fixture.escrow_token_account, 2_500).await;
+}
+
+#[tokio::test]
+async fn increase_lockup_pool_scenarios() {
+    let mut context = setup().start_with_context().await;
+    let fixture = setup_fixture(&mut context, 1_000_000).await;
+
+    // Setup lockups of 100, 200 & 300 in the pool.
+    let lockups = [
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    ];
+    let entries: Vec<_> = lockups
+        .iter()
+        .zip([300, 200, 100])
+        .map(|(lockup, amount)| LockupPoolEntry {
+            lockup: *lockup,
+            amount,
+            metadata: lockup.to_bytes(),
+        })
+        .collect();
+    setup_lockup_pool_with_entries(&mut context, &fixture.pool, &entries).await;
+    for entry in &entries {
+        setup_lockup(
+            &mut context,
+            &entry.lockup,
+            lockup_state(&fixture, entry.amount, entry.metadata),
+        )
+        .await;
+    }
+
+    // Act - Increase the smallest lockup past the largest.
+    increase_lockup(&mut context, &fixture, &lockups[2], 250)
+        .await
+        .unwrap();
+
+    // Assert - The lockup moved to the front.
+    let lockup_pool = get_lockup_pool(&mut context, &fixture.pool).await;
+    assert_eq!(lockup_pool.entries_len, 3);
+    assert_eq!(
+        lockup_pool.entries[..3]
+            .iter()
+            .map(|entry| (entry.lockup, entry.amount))
+            .collect::<Vec<_>>(),
+        vec![(lockups[2], 350), (lockups[0], 300), (lockups[1], 200)],
+    );
+
+    // Act - Increase the middle lockup to tie with the largest.
+    increase_lockup(&mut context, &fixture, &lockups[0], 50)
+        .await
+        .unwrap();
+
+    // Assert - Entries remain sorted & no entries were duplicated or lost.
+    let lockup_pool = get_lockup_pool(&mut context, &fixture.pool).await;
+    assert_eq!(lockup_pool.entries_len, 3);
+    assert!(lockup_pool.entries[..3]
+        .windows(2)
+        .all(|pair| pair[0].amount >= pair[1].amount));
+    let mut pool_lockups: Vec<_> = lockup_pool.entries[..3]
+        .iter()
+        .map(|entry| (entry.lockup, entry.amount))
+        .collect();
+    pool_lockups.sort();
+    let mut expected = vec![(lockups[0], 350), (lockups[1], 200), (lockups[2], 350)];
+    expected.sort();
+    assert_eq!(pool_lockups, expected);
+    assert_eq!(lockup_pool.entries[3], LockupPoolEntry::default());
+}
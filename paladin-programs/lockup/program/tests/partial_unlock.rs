@@ -0,0 +1,439 @@
+#![cfg(feature = "test-sbf")]
+
+mod setup;
+
+use {
+    paladin_lockup_program::{
+        error::PaladinLockupError,
+        state::{get_escrow_authority_address, Lockup, LockupPoolEntry},
+        LOCKUP_COOLDOWN_SECONDS,
+    },
+    setup::{
+        add_seconds_to_clock, get_lockup_pool, setup, setup_lockup, setup_lockup_pool_with_entries,
+        setup_mint, setup_token_account, setup_uninitialized_lockup,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        clock::Clock,
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_discriminator::SplDiscriminate,
+    spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount},
+    std::num::NonZeroU64,
+    test_case::test_case,
+};
+
+fn active_lockup(authority: &Pubkey, pool: Pubkey, amount: u64) -> Lockup {
+    Lockup {
+        discriminator: Lockup::SPL_DISCRIMINATOR.into(),
+        amount,
+        authority: *authority,
+        lockup_start_timestamp: 10,
+        lockup_end_timestamp: None,
+        mint: Pubkey::new_unique(),
+        pool,
+        metadata: [7; 32],
+    }
+}
+
+async fn partial_unlock(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    pool: Pubkey,
+    lockup: &Pubkey,
+    unlocked_lockup: &Pubkey,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let instruction = paladin_lockup_program::instruction::partial_unlock(
+        &authority.pubkey(),
+        pool,
+        lockup,
+        unlocked_lockup,
+        amount,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    let _ = context.get_new_latest_blockhash().await.unwrap();
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn fail_partial_unlock_authority_not_signer() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    let mut instruction = paladin_lockup_program::instruction::partial_unlock(
+        &authority.pubkey(),
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        1_000,
+    );
+    instruction.accounts[0].is_signer = false;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer], // authority not signer.
+        context.last_blockhash,
+    );
+
+    let err = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_partial_unlock_already_unlocked() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        Lockup {
+            lockup_end_timestamp: NonZeroU64::new(20),
+            ..active_lockup(&authority.pubkey(), pool, 10_000)
+        },
+    )
+    .await;
+    setup_uninitialized_lockup(&mut context, &unlocked_lockup).await;
+
+    let err = partial_unlock(
+        &mut context,
+        &authority,
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        1_000,
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::LockupAlreadyUnlocked as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_partial_unlock_unlocked_lockup_already_initialized() {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    let state = active_lockup(&authority.pubkey(), pool, 10_000);
+    setup_lockup(&mut context, &lockup, state).await;
+    setup_lockup(&mut context, &unlocked_lockup, state).await; // Already initialized.
+
+    let err = partial_unlock(
+        &mut context,
+        &authority,
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        1_000,
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[test_case(0; "zero")]
+#[test_case(10_000; "full amount")]
+#[test_case(10_001; "more than locked up")]
+#[tokio::test]
+async fn fail_partial_unlock_invalid_amount(amount: u64) {
+    let mut context = setup().start_with_context().await;
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    setup_lockup_pool_with_entries(&mut context, &pool, &[]).await;
+
+    setup_lockup(
+        &mut context,
+        &lockup,
+        active_lockup(&authority.pubkey(), pool, 10_000),
+    )
+    .await;
+    setup_uninitialized_lockup(&mut context, &unlocked_lockup).await;
+
+    let err = partial_unlock(
+        &mut context,
+        &authority,
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        amount,
+    )
+    .await
+    .unwrap_err()
+    .unwrap();
+
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(PaladinLockupError::InvalidAmount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn success() {
+    let mut context = setup().start_with_context().await;
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    let authority = Keypair::new();
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    // Create the lockup pool account with our lockup ahead of another.
+    let pool = Pubkey::new_unique();
+    let state = active_lockup(&authority.pubkey(), pool, 10_000);
+    let other = LockupPoolEntry {
+        lockup: Pubkey::new_unique(),
+        amount: 5_000,
+        metadata: [1; 32],
+    };
+    let entry = LockupPoolEntry {
+        lockup,
+        amount: state.amount,
+        metadata: state.metadata,
+    };
+    setup_lockup_pool_with_entries(&mut context, &pool, &[entry, other]).await;
+    setup_lockup(&mut context, &lockup, state).await;
+    setup_uninitialized_lockup(&mut context, &unlocked_lockup).await;
+
+    partial_unlock(
+        &mut context,
+        &authority,
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        6_000,
+    )
+    .await
+    .unwrap();
+
+    // Check the lockup account kept the remainder locked up.
+    let lockup_account = context
+        .banks_client
+        .get_account(lockup)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        bytemuck::from_bytes::<Lockup>(&lockup_account.data),
+        &Lockup {
+            amount: 4_000,
+            ..state
+        }
+    );
+
+    // Check the unlocked lockup account holds the unlocked amount.
+    let unlocked_lockup_account = context
+        .banks_client
+        .get_account(unlocked_lockup)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        bytemuck::from_bytes::<Lockup>(&unlocked_lockup_account.data),
+        &Lockup {
+            amount: 6_000,
+            lockup_end_timestamp: NonZeroU64::new(clock.unix_timestamp as u64),
+            ..state
+        }
+    );
+
+    // Check the pool entry moved behind the larger lockup.
+    let lockup_pool = get_lockup_pool(&mut context, &pool).await;
+    assert_eq!(lockup_pool.entries_len, 2);
+    assert_eq!(
+        lockup_pool.entries[..2],
+        [
+            other,
+            LockupPoolEntry {
+                amount: 4_000,
+                ..entry
+            },
+        ]
+    );
+    assert_eq!(lockup_pool.entries[2], LockupPoolEntry::default());
+}
+
+#[tokio::test]
+async fn success_withdraw_unlocked_amount() {
+    let mint = Pubkey::new_unique();
+
+    let authority = Keypair::new();
+    let token_account = get_associated_token_address_with_program_id(
+        &authority.pubkey(),
+        &mint,
+        &spl_token_2022::id(),
+    );
+    let escrow_authority = get_escrow_authority_address(&paladin_lockup_program::id());
+    let escrow_token_account = get_associated_token_address_with_program_id(
+        &escrow_authority,
+        &mint,
+        &spl_token_2022::id(),
+    );
+
+    let lockup = Pubkey::new_unique();
+    let unlocked_lockup = Pubkey::new_unique();
+
+    let mut context = setup().start_with_context().await;
+
+    // Create the lockup pool account.
+    let pool = Pubkey::new_unique();
+    let state = Lockup {
+        mint,
+        ..active_lockup(&authority.pubkey(), pool, 10_000)
+    };
+    setup_lockup_pool_with_entries(
+        &mut context,
+        &pool,
+        &[LockupPoolEntry {
+            lockup,
+            amount: state.amount,
+            metadata: state.metadata,
+        }],
+    )
+    .await;
+    setup_lockup(&mut context, &lockup, state).await;
+    setup_uninitialized_lockup(&mut context, &unlocked_lockup).await;
+    setup_token_account(&mut context, &token_account, &authority.pubkey(), &mint, 0).await;
+    setup_token_account(
+        &mut context,
+        &escrow_token_account,
+        &escrow_authority,
+        &mint,
+        10_000,
+    )
+    .await;
+    setup_mint(&mut context, &mint, &Pubkey::new_unique(), 1_000_000).await;
+
+    partial_unlock(
+        &mut context,
+        &authority,
+        pool,
+        &lockup,
+        &unlocked_lockup,
+        3_000,
+    )
+    .await
+    .unwrap();
+    add_seconds_to_clock(&mut context, LOCKUP_COOLDOWN_SECONDS).await;
+
+    // Withdraw the unlocked amount.
+    let instruction = paladin_lockup_program::instruction::withdraw(
+        &authority.pubkey(),
+        &Pubkey::new_unique(), // Lamport destination.
+        &token_account,
+        &unlocked_lockup,
+        &mint,
+        &spl_token_2022::id(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Check only the unlocked amount left escrow.
+    for (token_account, amount) in [(token_account, 3_000), (escrow_token_account, 7_000)] {
+        let account = context
+            .banks_client
+            .get_account(token_account)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            StateWithExtensions::<TokenAccount>::unpack(&account.data)
+                .unwrap()
+                .base
+                .amount,
+            amount
+        );
+    }
+
+    // Check the remaining lockup is untouched.
+    let lockup_account = context
+        .banks_client
+        .get_account(lockup)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        bytemuck::from_bytes::<Lockup>(&lockup_account.data),
+        &Lockup {
+            amount: 7_000,
+            ..state
+        }
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_account(unlocked_lockup)
+            .await
+            .unwrap(),
+        None
+    );
+}
@@ -136,6 +136,54 @@ pub async fn setup_lockup_pool(context: &mut ProgramTestContext, address: &Pubke
     );
 }
 
+pub async fn setup_lockup_pool_with_entries(
+    context: &mut ProgramTestContext,
+    address: &Pubkey,
+    entries: &[LockupPoolEntry],
+) {
+    let mut state = LockupPool {
+        discriminator: LockupPool::SPL_DISCRIMINATOR.into(),
+        entries: [LockupPoolEntry::default(); 1024],
+        entries_len: entries.len(),
+    };
+    state.entries[..entries.len()].copy_from_slice(entries);
+    let data = bytemuck::bytes_of(&state).to_vec();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let lamports = rent.minimum_balance(data.len());
+
+    context.set_account(
+        address,
+        &AccountSharedData::from(Account {
+            lamports,
+            data,
+            owner: paladin_lockup_program::id(),
+            ..Account::default()
+        }),
+    );
+}
+
+pub async fn get_lockup_pool(context: &mut ProgramTestContext, address: &Pubkey) -> LockupPool {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+
+    *bytemuck::from_bytes::<LockupPool>(&account.data)
+}
+
+pub async fn setup_uninitialized_lockup(context: &mut ProgramTestContext, address: &Pubkey) {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let space = std::mem::size_of::<Lockup>();
+    let lamports = rent.minimum_balance(space);
+
+    context.set_account(
+        address,
+        &AccountSharedData::new(lamports, space, &paladin_lockup_program::id()),
+    );
+}
+
 pub async fn setup_lockup(context: &mut ProgramTestContext, address: &Pubkey, state: Lockup) {
     let data = bytemuck::bytes_of(&state).to_vec();
     let rent = context.banks_client.get_rent().await.unwrap();
@@ -93,7 +93,7 @@ mod tests {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -180,7 +180,7 @@ mod tests {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             Duration::from_secs(1), // wait_for_chunk_timeout
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -245,7 +245,7 @@ mod tests {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -276,7 +276,7 @@ mod tests {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -56,6 +56,42 @@ pub enum RpcBundleExecutionError {
 pub struct RpcSimulateBundleResult {
     pub summary: RpcBundleSimulationSummary,
     pub transaction_results: Vec<RpcSimulateBundleTransactionResult>,
+    /// Lamports the bundle pays to the tip accounts, if the RPC node knows them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tip_lamports: Option<u64>,
+    /// Front-run found in the executed bundle, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub front_run: Option<RpcBundleFrontRun>,
+    /// Whether the bundle fits in the block the node is producing, whichever bank it was
+    /// simulated on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_model: Option<RpcBundleCostModelFit>,
+}
+
+/// A front-run of an AMM pool between transactions of the bundle with no common signer.
+///
+/// Sandwiches spanning other bundles or transactions of the slot aren't detected by simulation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBundleFrontRun {
+    /// The AMM program that owns the front-run pool account.
+    pub program: String,
+    /// The block producer's policy for the AMM program.
+    pub policy: String,
+    /// Whether the block producer drops the bundle.
+    pub rejected: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBundleCostModelFit {
+    /// Estimated cost of the bundle's transactions, as reserved by the block producer.
+    pub bundle_cost: u64,
+    /// Cost of the block before the bundle.
+    pub block_cost: u64,
+    pub block_cost_limit: u64,
+    /// Why the bundle doesn't fit in the block, if it doesn't.
+    pub err: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -67,6 +103,10 @@ pub struct RpcSimulateBundleTransactionResult {
     pub post_execution_accounts: Option<Vec<UiAccount>>,
     pub units_consumed: Option<u64>,
     pub return_data: Option<UiTransactionReturnData>,
+    /// Whether the transaction requested to be dropped if it reverts, failing the whole bundle
+    /// instead of being committed.
+    #[serde(default)]
+    pub drop_on_revert: bool,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -227,6 +227,16 @@ pub struct RpcSignaturesForAddressConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFunnelPaymentsConfig {
+    /// First epoch to return payments committed in, the earliest if unset
+    pub start_epoch: Option<Epoch>,
+    /// Last epoch to return payments committed in, the latest if unset
+    pub end_epoch: Option<Epoch>,
+    pub limit: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcEncodingConfigWrapper<T> {
@@ -23,6 +23,7 @@ pub enum RpcRequest {
     GetEpochSchedule,
     GetFeeForMessage,
     GetFirstAvailableBlock,
+    GetFunnelPayments,
     GetGenesisHash,
     GetHealth,
     GetIdentity,
@@ -89,6 +90,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetEpochSchedule => "getEpochSchedule",
             RpcRequest::GetFeeForMessage => "getFeeForMessage",
             RpcRequest::GetFirstAvailableBlock => "getFirstAvailableBlock",
+            RpcRequest::GetFunnelPayments => "getFunnelPayments",
             RpcRequest::GetGenesisHash => "getGenesisHash",
             RpcRequest::GetHealth => "getHealth",
             RpcRequest::GetIdentity => "getIdentity",
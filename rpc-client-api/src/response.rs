@@ -472,6 +472,22 @@ pub struct RpcPerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFunnelPayment {
+    /// Slot the `become_receiver` transaction was committed in
+    pub slot: Slot,
+    pub epoch: Epoch,
+    /// First and last leader slots whose block rewards were settled
+    pub first_slot: Option<Slot>,
+    pub last_slot: Option<Slot>,
+    pub rewards: u64,
+    pub owed: u64,
+    pub paid: u64,
+    pub outstanding: u64,
+    pub signature: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcInflationReward {
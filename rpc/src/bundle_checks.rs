@@ -0,0 +1,105 @@
+//! Checks the block producer runs on bundles before committing them, reported by `simulateBundle`
+//! so searchers can tell why a bundle wouldn't land.
+use {
+    itertools::izip,
+    solana_bundle::{bundle_execution::LoadAndExecuteBundleOutput, SanitizedBundle},
+    solana_cost_model::cost_model::CostModel,
+    solana_rpc_client_api::bundles::{RpcBundleCostModelFit, RpcBundleFrontRun},
+    solana_runtime::bank::Bank,
+    solana_runtime_transaction::drop_on_revert::MAX_DROP_ON_REVERT_COST,
+    solana_sdk::{pubkey::Pubkey, transaction::TransactionError},
+    std::{collections::HashSet, fmt::Debug},
+};
+
+/// Checks that need the bundle stage's state. The bundle stage lives in solana-core, which
+/// depends on this crate, so they're injected through `JsonRpcConfig::bundle_checks`.
+pub trait BundleChecks: Debug + Send + Sync {
+    /// Accounts tips are paid to.
+    fn tip_accounts(&self) -> &HashSet<Pubkey>;
+
+    /// Returns the front-run found in the successfully executed bundle, if any.
+    fn find_front_run<'a>(
+        &self,
+        bundle: &'a LoadAndExecuteBundleOutput<'a>,
+    ) -> Option<RpcBundleFrontRun>;
+}
+
+/// Returns the lamports the executed transactions of the bundle paid to the tip accounts.
+pub(crate) fn tip_lamports(
+    bundle: &LoadAndExecuteBundleOutput,
+    tip_accounts: &HashSet<Pubkey>,
+) -> u64 {
+    let mut tip_lamports = 0u64;
+    for batch in bundle.bundle_transaction_results() {
+        for (transaction, pre_balances, post_balances) in izip!(
+            batch.transactions(),
+            &batch.pre_balance_info().native,
+            &batch.post_balance_info().0,
+        ) {
+            for (account, pre_balance, post_balance) in izip!(
+                transaction.message().account_keys().iter(),
+                pre_balances,
+                post_balances,
+            ) {
+                if tip_accounts.contains(account) {
+                    tip_lamports =
+                        tip_lamports.saturating_add(post_balance.saturating_sub(*pre_balance));
+                }
+            }
+        }
+    }
+    tip_lamports
+}
+
+/// Checks whether the bundle fits in the bank's block, reserving block space for all of its
+/// transactions up-front like the bundle stage.
+///
+/// Only meaningful for the working bank: a frozen bank's block won't take any more transactions,
+/// so whether the bundle would have fit in it doesn't tell searchers whether it lands now.
+pub(crate) fn cost_model_fit(bank: &Bank, bundle: &SanitizedBundle) -> RpcBundleCostModelFit {
+    let costs: Vec<_> = bundle
+        .transactions
+        .iter()
+        .map(|transaction| CostModel::calculate_cost(transaction, &bank.feature_set))
+        .collect();
+    let bundle_cost = costs.iter().fold(0u64, |bundle_cost, cost| {
+        bundle_cost.saturating_add(cost.sum())
+    });
+
+    let cost_tracker = bank.read_cost_tracker().unwrap();
+    let drop_on_revert_over_limit =
+        bundle
+            .transactions
+            .iter()
+            .zip(&costs)
+            .find(|(transaction, cost)| {
+                transaction.drop_on_revert() && cost.sum() > MAX_DROP_ON_REVERT_COST
+            });
+    let err = if let Some((transaction, cost)) = drop_on_revert_over_limit {
+        Some(format!(
+            "drop-on-revert transaction {} costs {} CUs, more than the {} allowed",
+            transaction.signature(),
+            cost.sum(),
+            MAX_DROP_ON_REVERT_COST,
+        ))
+    } else {
+        let costs: Vec<_> = costs.iter().collect();
+        cost_tracker
+            .would_fit_all(&costs)
+            .err()
+            .map(|(index, error)| {
+                format!(
+                    "transaction {}: {}",
+                    bundle.transactions[index].signature(),
+                    TransactionError::from(error),
+                )
+            })
+    };
+
+    RpcBundleCostModelFit {
+        bundle_cost,
+        block_cost: cost_tracker.block_cost(),
+        block_cost_limit: cost_tracker.block_cost_limit(),
+        err,
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(clippy::arithmetic_side_effects)]
+pub mod bundle_checks;
 mod cluster_tpu_info;
 pub mod filter;
 pub mod max_slots;
@@ -1,9 +1,13 @@
 //! The `rpc` module implements the Solana RPC interface.
 use {
     crate::{
-        filter::filter_allows, max_slots::MaxSlots,
+        bundle_checks::{cost_model_fit, tip_lamports, BundleChecks},
+        filter::filter_allows,
+        max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_cache::LargestAccountsCache, rpc_health::*,
+        parsed_token_accounts::*,
+        rpc_cache::LargestAccountsCache,
+        rpc_health::*,
     },
     base64::{prelude::BASE64_STANDARD, Engine},
     bincode::{config::Options, serialize},
@@ -36,7 +40,7 @@ use {
     solana_ledger::{
         blockstore::{Blockstore, SignatureInfosForAddress},
         blockstore_db::BlockstoreError,
-        blockstore_meta::{PerfSample, PerfSampleV1, PerfSampleV2},
+        blockstore_meta::{FunnelPayment, PerfSample, PerfSampleV1, PerfSampleV2},
         leader_schedule_cache::LeaderScheduleCache,
     },
     solana_metrics::inc_new_counter_info,
@@ -65,9 +69,10 @@ use {
         snapshot_utils,
         verify_precompiles::verify_precompiles,
     },
+    solana_runtime_transaction::drop_on_revert::is_drop_on_revert_requested,
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
-        clock::{Slot, UnixTimestamp},
+        clock::{Epoch, Slot, UnixTimestamp},
         commitment_config::{CommitmentConfig, CommitmentLevel},
         epoch_info::EpochInfo,
         epoch_rewards_hasher::EpochRewardsHasher,
@@ -87,6 +92,7 @@ use {
     solana_send_transaction_service::send_transaction_service::TransactionInfo,
     solana_stake_program,
     solana_storage_bigtable::Error as StorageError,
+    solana_svm_transaction::instruction::SVMInstruction,
     solana_transaction_status::{
         map_inner_instructions, BlockEncodingOptions, ConfirmedBlock,
         ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta,
@@ -136,6 +142,7 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+pub const FUNNEL_PAYMENTS_LIMIT: usize = 1_000;
 
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     RpcResponse {
@@ -172,6 +179,8 @@ pub struct JsonRpcConfig {
     pub max_request_body_size: Option<usize>,
     /// Disable the health check, used for tests and TestValidator
     pub disable_health_check: bool,
+    /// Bundle stage checks reported by simulateBundle
+    pub bundle_checks: Option<Arc<dyn BundleChecks>>,
 }
 
 impl Default for JsonRpcConfig {
@@ -192,6 +201,7 @@ impl Default for JsonRpcConfig {
             rpc_scan_and_fix_roots: Default::default(),
             max_request_body_size: Option::default(),
             disable_health_check: Default::default(),
+            bundle_checks: None,
         }
     }
 }
@@ -3670,6 +3680,7 @@ pub mod utils {
                                 .map(|data| UiTransactionReturnData::from(data.clone()))
                         })
                         .unwrap_or_default(),
+                    drop_on_revert: bundle_output.transactions()[index].drop_on_revert(),
                 });
             }
         }
@@ -3677,6 +3688,9 @@ pub mod utils {
         Ok(RpcSimulateBundleResult {
             summary,
             transaction_results,
+            tip_lamports: None,
+            front_run: None,
+            cost_model: None,
         })
     }
 
@@ -3750,6 +3764,13 @@ pub mod rpc_full {
             limit: Option<usize>,
         ) -> Result<Vec<RpcPerfSample>>;
 
+        #[rpc(meta, name = "getFunnelPayments")]
+        fn get_funnel_payments(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcFunnelPaymentsConfig>,
+        ) -> Result<Vec<RpcFunnelPayment>>;
+
         #[rpc(meta, name = "getSignatureStatuses")]
         fn get_signature_statuses(
             &self,
@@ -3921,6 +3942,45 @@ pub mod rpc_full {
                 .collect())
         }
 
+        fn get_funnel_payments(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcFunnelPaymentsConfig>,
+        ) -> Result<Vec<RpcFunnelPayment>> {
+            debug!("get_funnel_payments request received: {:?}", config);
+
+            let RpcFunnelPaymentsConfig {
+                start_epoch,
+                end_epoch,
+                limit,
+            } = config.unwrap_or_default();
+            let start_epoch = start_epoch.unwrap_or_default();
+            let end_epoch = end_epoch.unwrap_or(Epoch::MAX);
+            let limit = limit.unwrap_or(FUNNEL_PAYMENTS_LIMIT);
+
+            if limit > FUNNEL_PAYMENTS_LIMIT {
+                return Err(Error::invalid_params(format!(
+                    "Invalid limit; max {FUNNEL_PAYMENTS_LIMIT}"
+                )));
+            }
+            if start_epoch > end_epoch {
+                return Err(Error::invalid_params(
+                    "start epoch must be less than or equal to end epoch",
+                ));
+            }
+
+            Ok(meta
+                .blockstore
+                .get_funnel_payments(start_epoch, end_epoch, limit)
+                .map_err(|err| {
+                    warn!("get_funnel_payments failed: {:?}", err);
+                    Error::invalid_request()
+                })?
+                .into_iter()
+                .map(|(slot, payment)| rpc_funnel_payment_from_funnel_payment(slot, payment))
+                .collect())
+        }
+
         fn get_cluster_nodes(&self, meta: Self::Metadata) -> Result<Vec<RpcContactInfo>> {
             debug!("get_cluster_nodes rpc request received");
             let cluster_info = &meta.cluster_info;
@@ -4426,7 +4486,9 @@ pub mod rpc_full {
 
             let runtime_txs = decoded_transactions
                 .into_iter()
-                .map(|tx| sanitize_transaction(tx, bank.as_ref(), bank.get_reserved_account_keys()))
+                .map(|tx| {
+                    sanitize_bundle_transaction(tx, bank.as_ref(), bank.get_reserved_account_keys())
+                })
                 .collect::<Result<Vec<SanitizedTransaction>>>()?;
             let sanitized_bundle = SanitizedBundle {
                 transactions: runtime_txs,
@@ -4482,8 +4544,28 @@ pub mod rpc_full {
                 }
             }
 
-            let rpc_bundle_result =
+            // whatever bank the bundle was simulated on, it lands in the block being produced
+            let working_bank = meta.bank_forks.read().unwrap().working_bank();
+            let cost_model = cost_model_fit(&working_bank, &sanitized_bundle);
+            let (tip_lamports, front_run) = match &meta.config.bundle_checks {
+                Some(bundle_checks) => (
+                    Some(tip_lamports(
+                        &bundle_execution_result,
+                        bundle_checks.tip_accounts(),
+                    )),
+                    bundle_execution_result
+                        .executed_ok()
+                        .then(|| bundle_checks.find_front_run(&bundle_execution_result))
+                        .flatten(),
+                ),
+                None => (None, None),
+            };
+
+            let mut rpc_bundle_result =
                 rpc_bundle_result_from_bank_result(bundle_execution_result, config)?;
+            rpc_bundle_result.tip_lamports = tip_lamports;
+            rpc_bundle_result.front_run = front_run;
+            rpc_bundle_result.cost_model = Some(cost_model);
 
             Ok(new_response(&bank, rpc_bundle_result))
         }
@@ -4700,6 +4782,20 @@ pub mod rpc_full {
     }
 }
 
+fn rpc_funnel_payment_from_funnel_payment(slot: Slot, payment: FunnelPayment) -> RpcFunnelPayment {
+    RpcFunnelPayment {
+        slot,
+        epoch: payment.epoch,
+        first_slot: payment.slot_range.map(|(first_slot, _)| first_slot),
+        last_slot: payment.slot_range.map(|(_, last_slot)| last_slot),
+        rewards: payment.rewards,
+        owed: payment.owed,
+        paid: payment.paid,
+        outstanding: payment.outstanding(),
+        signature: payment.signature.to_string(),
+    }
+}
+
 fn rpc_perf_sample_from_perf_sample(slot: u64, sample: PerfSample) -> RpcPerfSample {
     match sample {
         PerfSample::V1(PerfSampleV1 {
@@ -4808,6 +4904,30 @@ fn sanitize_transaction(
     .map_err(|err| Error::invalid_params(format!("invalid transaction: {err}")))
 }
 
+/// Sanitizes a bundle transaction, flagging it drop-on-revert if it carries the memo
+fn sanitize_bundle_transaction(
+    transaction: VersionedTransaction,
+    address_loader: impl AddressLoader,
+    reserved_account_keys: &HashSet<Pubkey>,
+) -> Result<SanitizedTransaction> {
+    let drop_on_revert =
+        is_drop_on_revert_requested(transaction.message.instructions().iter().map(|ix| {
+            (
+                ix.program_id(transaction.message.static_account_keys()),
+                SVMInstruction::from(ix),
+            )
+        }));
+    SanitizedTransaction::try_create(
+        transaction,
+        MessageHash::Compute,
+        None,
+        drop_on_revert,
+        address_loader,
+        reserved_account_keys,
+    )
+    .map_err(|err| Error::invalid_params(format!("invalid transaction: {err}")))
+}
+
 pub fn create_validator_exit(exit: Arc<AtomicBool>) -> Arc<RwLock<Exit>> {
     let mut validator_exit = Exit::default();
     validator_exit.register_exit(Box::new(move || exit.store(true, Ordering::Relaxed)));
@@ -4918,6 +5038,8 @@ pub mod tests {
         jsonrpc_core_client::transports::local,
         serde::de::DeserializeOwned,
         solana_accounts_db::accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
+        solana_bundle::bundle_execution::LoadAndExecuteBundleOutput,
+        solana_cost_model::cost_model::CostModel,
         solana_entry::entry::next_versioned_entry,
         solana_gossip::{contact_info::ContactInfo, socketaddr},
         solana_ledger::{
@@ -4927,6 +5049,7 @@ pub mod tests {
             get_tmp_ledger_path,
         },
         solana_rpc_client_api::{
+            bundles::RpcBundleFrontRun,
             custom_error::{
                 JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
@@ -4940,6 +5063,9 @@ pub mod tests {
             commitment::{BlockCommitment, CommitmentSlots},
             non_circulating_supply::non_circulating_accounts,
         },
+        solana_runtime_transaction::drop_on_revert::{
+            DROP_ON_REVERT_MEMO, MAX_DROP_ON_REVERT_COST, MEMO_PROGRAM_ID,
+        },
         solana_sdk::{
             account::{Account, WritableAccount},
             address_lookup_table::{
@@ -4949,7 +5075,7 @@ pub mod tests {
             compute_budget::ComputeBudgetInstruction,
             fee_calculator::FeeRateGovernor,
             hash::{hash, Hash},
-            instruction::InstructionError,
+            instruction::{Instruction, InstructionError},
             message::{
                 v0::{self, MessageAddressTableLookup},
                 Message, MessageHeader, VersionedMessage,
@@ -5515,6 +5641,71 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_get_funnel_payments() {
+        let rpc = RpcHandler::start();
+
+        let signature = Signature::new_unique();
+        for (slot, epoch) in [(4, 0), (40, 1), (70, 2)] {
+            rpc.blockstore
+                .write_funnel_payment(
+                    slot,
+                    &FunnelPayment {
+                        epoch,
+                        slot_range: Some((slot - 4, slot - 1)),
+                        rewards: 10_000,
+                        owed: 1_000,
+                        paid: 600,
+                        signature,
+                    },
+                )
+                .expect("write to blockstore");
+        }
+
+        let request = create_test_request(
+            "getFunnelPayments",
+            Some(json!([{"startEpoch": 1, "endEpoch": 1}])),
+        );
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+        let expected = json!([{
+            "slot": 40,
+            "epoch": 1,
+            "firstSlot": 36,
+            "lastSlot": 39,
+            "rewards": 10_000,
+            "owed": 1_000,
+            "paid": 600,
+            "outstanding": 400,
+            "signature": signature.to_string(),
+        }]);
+        assert_eq!(result, expected);
+
+        for (config, expected_slots) in [
+            (None, vec![4, 40, 70]),
+            (Some(json!([{"startEpoch": 1}])), vec![40, 70]),
+            (Some(json!([{"endEpoch": 1, "limit": 1}])), vec![4]),
+        ] {
+            let request = create_test_request("getFunnelPayments", config);
+            let result: Vec<RpcFunnelPayment> =
+                parse_success_result(rpc.handle_request_sync(request));
+            assert_eq!(
+                result
+                    .iter()
+                    .map(|payment| payment.slot)
+                    .collect::<Vec<_>>(),
+                expected_slots
+            );
+        }
+
+        let request = create_test_request("getFunnelPayments", Some(json!([{"limit": 10_000}])));
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        let expected = (
+            ErrorCode::InvalidParams.code(),
+            String::from("Invalid limit; max 1000"),
+        );
+        assert_eq!(response, expected);
+    }
+
     #[test]
     fn test_rpc_get_recent_performance_samples_invalid_limit() {
         let rpc = RpcHandler::start();
@@ -6284,6 +6475,23 @@ pub mod tests {
         let encoded_tip_tx = general_purpose::STANDARD.encode(serialize(&tip_tx).unwrap());
         let b64_data = general_purpose::STANDARD.encode(leader_account_data.data());
 
+        let bundle_cost: u64 = [&mev_tx, &tip_tx]
+            .into_iter()
+            .map(|tx| {
+                let tx = sanitize_bundle_transaction(
+                    tx.clone(),
+                    bank.as_ref(),
+                    bank.get_reserved_account_keys(),
+                )
+                .unwrap();
+                CostModel::calculate_cost(&tx, &bank.feature_set).sum()
+            })
+            .sum();
+        let (block_cost, block_cost_limit) = {
+            let cost_tracker = bank.read_cost_tracker().unwrap();
+            (cost_tracker.block_cost(), cost_tracker.block_cost_limit())
+        };
+
         // 3. test and assert
         let skip_sig_verify = true;
         let replace_recent_blockhash = false;
@@ -6310,6 +6518,7 @@ pub mod tests {
                                     "space": 100
                                 }
                             ],
+                            "dropOnRevert": false,
                         },
                         {
                             "err": null,
@@ -6327,8 +6536,15 @@ pub mod tests {
                                     "space": 100
                                 }
                             ],
+                            "dropOnRevert": false,
                         },
                     ],
+                    "costModel": {
+                        "bundleCost": bundle_cost,
+                        "blockCost": block_cost,
+                        "blockCostLimit": block_cost_limit,
+                        "err": null,
+                    },
                 }
             },
             "id": 1,
@@ -6378,6 +6594,181 @@ pub mod tests {
         assert_eq!(expected_response, actual_response);
     }
 
+    #[derive(Debug)]
+    struct MockBundleChecks {
+        tip_accounts: HashSet<Pubkey>,
+        front_run: Option<RpcBundleFrontRun>,
+    }
+
+    impl BundleChecks for MockBundleChecks {
+        fn tip_accounts(&self) -> &HashSet<Pubkey> {
+            &self.tip_accounts
+        }
+
+        fn find_front_run<'a>(
+            &self,
+            _bundle: &'a LoadAndExecuteBundleOutput<'a>,
+        ) -> Option<RpcBundleFrontRun> {
+            self.front_run.clone()
+        }
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle_tip_lamports() {
+        let tip_account = solana_sdk::pubkey::new_rand();
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            enable_rpc_transaction_history: true,
+            bundle_checks: Some(Arc::new(MockBundleChecks {
+                tip_accounts: HashSet::from([tip_account]),
+                front_run: None,
+            })),
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+
+        let tip_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let mev_tx = VersionedTransaction::from(system_transaction::transfer(
+            &rpc.mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            tip_amount,
+            recent_blockhash,
+        ));
+        let tip_tx = VersionedTransaction::from(system_transaction::transfer(
+            &rpc.mint_keypair,
+            &tip_account,
+            tip_amount,
+            recent_blockhash,
+        ));
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "simulateBundle",
+            "params": [
+                {
+                    "encodedTransactions": [
+                        general_purpose::STANDARD.encode(serialize(&mev_tx).unwrap()),
+                        general_purpose::STANDARD.encode(serialize(&tip_tx).unwrap()),
+                    ],
+                },
+            ],
+        });
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+
+        assert_eq!(result["value"]["summary"], json!("succeeded"));
+        assert_eq!(result["value"]["tipLamports"], json!(tip_amount));
+        assert_eq!(result["value"]["frontRun"], Value::Null);
+        assert_eq!(result["value"]["costModel"]["err"], Value::Null);
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle_front_run_rejected() {
+        let front_run = RpcBundleFrontRun {
+            program: solana_sdk::pubkey::new_rand().to_string(),
+            policy: "reject".to_string(),
+            rejected: true,
+        };
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            enable_rpc_transaction_history: true,
+            bundle_checks: Some(Arc::new(MockBundleChecks {
+                tip_accounts: HashSet::new(),
+                front_run: Some(front_run.clone()),
+            })),
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let tx = VersionedTransaction::from(system_transaction::transfer(
+            &rpc.mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            bank.get_minimum_balance_for_rent_exemption(0),
+            bank.confirmed_last_blockhash(),
+        ));
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "simulateBundle",
+            "params": [
+                {
+                    "encodedTransactions": [
+                        general_purpose::STANDARD.encode(serialize(&tx).unwrap()),
+                    ],
+                },
+            ],
+        });
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+
+        assert_eq!(result["value"]["summary"], json!("succeeded"));
+        assert_eq!(
+            result["value"]["frontRun"],
+            serde_json::to_value(front_run).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle_cost_model_err() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        let simulate_bundle = |tx: &Transaction| -> Value {
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "simulateBundle",
+                "params": [
+                    {
+                        "encodedTransactions": [
+                            general_purpose::STANDARD.encode(serialize(tx).unwrap()),
+                        ],
+                    },
+                ],
+            });
+            let result: Value = parse_success_result(rpc.handle_request_sync(request));
+            result["value"]["costModel"]["err"].clone()
+        };
+
+        // a drop-on-revert transaction costing more than the block producer schedules
+        let drop_on_revert_tx = Transaction::new_signed_with_payer(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(
+                    MAX_DROP_ON_REVERT_COST as u32 + 1,
+                ),
+                Instruction::new_with_bytes(MEMO_PROGRAM_ID, DROP_ON_REVERT_MEMO, vec![]),
+            ],
+            Some(&rpc.mint_keypair.pubkey()),
+            &[&rpc.mint_keypair],
+            recent_blockhash,
+        );
+        let err = simulate_bundle(&drop_on_revert_tx);
+        assert!(err.as_str().unwrap().starts_with(&format!(
+            "drop-on-revert transaction {}",
+            drop_on_revert_tx.signatures[0]
+        )));
+
+        // a transaction that doesn't fit in the block being produced
+        let tx = system_transaction::transfer(
+            &rpc.mint_keypair,
+            &solana_sdk::pubkey::new_rand(),
+            bank.get_minimum_balance_for_rent_exemption(0),
+            recent_blockhash,
+        );
+        assert_eq!(simulate_bundle(&tx), Value::Null);
+        {
+            let mut cost_tracker = bank.write_cost_tracker().unwrap();
+            let block_cost = cost_tracker.block_cost();
+            cost_tracker.set_block_cost_limit(block_cost);
+        }
+        assert_eq!(
+            simulate_bundle(&tx),
+            json!(format!(
+                "transaction {}: {}",
+                tx.signatures[0],
+                TransactionError::WouldExceedMaxBlockCostLimit
+            ))
+        );
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let rpc = RpcHandler::start();
@@ -1,4 +1,8 @@
 pub mod runtime_plugin;
 pub mod runtime_plugin_admin_rpc_service;
+pub mod runtime_plugin_event;
 pub mod runtime_plugin_manager;
 pub mod runtime_plugin_service;
+
+#[macro_use]
+extern crate serde_derive;
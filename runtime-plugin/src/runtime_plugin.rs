@@ -1,15 +1,28 @@
 use {
+    crate::runtime_plugin_event::BankInfo,
     solana_runtime::{bank_forks::BankForks, commitment::BlockCommitmentCache},
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        pubkey::Pubkey,
+    },
     std::{
         any::Any,
         error,
-        fmt::Debug,
+        fmt::{self, Debug},
         io,
         sync::{atomic::AtomicBool, Arc, RwLock},
     },
     thiserror::Error,
 };
 
+/// Version of the [RuntimePlugin] interface, bumped whenever the trait's methods change.
+///
+/// Plugins export it as
+/// `#[no_mangle] pub extern "C" fn _runtime_plugin_interface_version() -> u32`; libraries that
+/// don't, or that were built against another version, are refused at load time rather than
+/// having their vtable misread.
+pub const RUNTIME_PLUGIN_INTERFACE_VERSION: u32 = 2;
+
 pub type Result<T> = std::result::Result<T, RuntimePluginError>;
 
 /// Errors returned by plugin calls
@@ -34,8 +47,57 @@ pub struct PluginDependencies {
     pub exit: Arc<AtomicBool>,
 }
 
+/// Health reported by a plugin through the `listPlugins` admin RPC.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum PluginHealth {
+    Healthy,
+    Unhealthy(String),
+}
+
+impl fmt::Display for PluginHealth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginHealth::Healthy => write!(f, "healthy"),
+            PluginHealth::Unhealthy(reason) => write!(f, "unhealthy ({reason})"),
+        }
+    }
+}
+
+/// A plugin loaded into the validator at runtime.
+///
+/// Besides `on_load` and `on_unload`, plugins may implement any of the event callbacks. These
+/// are invoked from the runtime plugin service's dispatcher thread, never from replay, so a slow
+/// callback delays later events rather than the validator. Events are dropped if the dispatcher
+/// falls too far behind.
+///
+/// Plugins must export [RUNTIME_PLUGIN_INTERFACE_VERSION] alongside `_create_plugin`.
 pub trait RuntimePlugin: Any + Debug + Send + Sync {
     fn name(&self) -> &'static str;
     fn on_load(&mut self, config_file: &str, dependencies: PluginDependencies) -> Result<()>;
     fn on_unload(&mut self);
+
+    /// Version reported through the `listPlugins` admin RPC.
+    fn version(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Health reported through the `listPlugins` admin RPC.
+    fn health(&self) -> PluginHealth {
+        PluginHealth::Healthy
+    }
+
+    /// Called after replay freezes a bank, including our own leader banks.
+    fn on_bank_frozen(&self, _bank: &BankInfo) {}
+
+    /// Called after the validator sets a new root.
+    fn on_new_root(&self, _root: &BankInfo) {}
+
+    /// Called when this validator, as `leader`, starts producing a block for `slot`.
+    fn on_slot_leader_start(&self, _slot: Slot, _leader: &Pubkey) {}
+
+    /// Called when this validator's block for `slot` is frozen.
+    fn on_slot_leader_end(&self, _slot: Slot, _leader: &Pubkey) {}
+
+    /// Called when the root crosses into a new epoch, with the first root of that epoch.
+    fn on_epoch_boundary(&self, _epoch: Epoch, _root: &BankInfo) {}
 }
@@ -1,6 +1,7 @@
 //! RPC interface to dynamically make changes to runtime plugins.
 
 use {
+    crate::runtime_plugin::PluginHealth,
     crossbeam_channel::Sender,
     jsonrpc_core::{BoxFuture, ErrorCode, MetaIoHandler, Metadata, Result as JsonRpcResult},
     jsonrpc_core_client::{transports::ipc, RpcError},
@@ -37,10 +38,18 @@ pub enum RuntimePluginManagerRpcRequest {
         response_sender: OneShotSender<JsonRpcResult<String>>,
     },
     ListPlugins {
-        response_sender: OneShotSender<JsonRpcResult<Vec<String>>>,
+        response_sender: OneShotSender<JsonRpcResult<Vec<RuntimePluginInfo>>>,
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimePluginInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub health: PluginHealth,
+}
+
 #[rpc]
 pub trait RuntimePluginAdminRpc {
     type Metadata;
@@ -64,7 +73,10 @@ pub trait RuntimePluginAdminRpc {
     ) -> BoxFuture<JsonRpcResult<String>>;
 
     #[rpc(meta, name = "listPlugins")]
-    fn list_plugins(&self, meta: Self::Metadata) -> BoxFuture<JsonRpcResult<Vec<String>>>;
+    fn list_plugins(
+        &self,
+        meta: Self::Metadata,
+    ) -> BoxFuture<JsonRpcResult<Vec<RuntimePluginInfo>>>;
 }
 
 #[derive(Clone)]
@@ -276,7 +288,10 @@ impl RuntimePluginAdminRpc for RuntimePluginAdminRpcImpl {
         })
     }
 
-    fn list_plugins(&self, meta: Self::Metadata) -> BoxFuture<JsonRpcResult<Vec<String>>> {
+    fn list_plugins(
+        &self,
+        meta: Self::Metadata,
+    ) -> BoxFuture<JsonRpcResult<Vec<RuntimePluginInfo>>> {
         Box::pin(async move {
             let (response_sender, response_receiver) = oneshot_channel();
 
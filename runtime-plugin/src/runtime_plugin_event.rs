@@ -0,0 +1,85 @@
+//! Events forwarded from replay to runtime plugins.
+
+use {
+    crossbeam_channel::{bounded, Receiver, Sender, TrySendError},
+    log::warn,
+    solana_runtime::bank::Bank,
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        hash::Hash,
+        pubkey::Pubkey,
+    },
+    std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Maximum number of events queued for the dispatcher before new events are dropped.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// The parts of a bank plugins are told about. Events carry this rather than the bank itself so
+/// queued events don't keep banks alive; plugins that need more can look the slot up in
+/// `BankForks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankInfo {
+    pub slot: Slot,
+    pub parent_slot: Slot,
+    pub epoch: Epoch,
+    pub hash: Hash,
+    /// The bank's collector, i.e. the leader that produced the block.
+    pub leader: Pubkey,
+}
+
+impl BankInfo {
+    pub fn new(bank: &Bank) -> Self {
+        Self {
+            slot: bank.slot(),
+            parent_slot: bank.parent_slot(),
+            epoch: bank.epoch(),
+            hash: bank.hash(),
+            leader: *bank.collector_id(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimePluginEvent {
+    BankFrozen(BankInfo),
+    NewRoot(BankInfo),
+    SlotLeaderStart { slot: Slot, leader: Pubkey },
+    SlotLeaderEnd { slot: Slot, leader: Pubkey },
+}
+
+/// Sends events to the runtime plugin service without ever blocking the caller.
+#[derive(Clone, Debug)]
+pub struct RuntimePluginEventSender {
+    sender: Sender<RuntimePluginEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RuntimePluginEventSender {
+    pub fn send(&self, event: RuntimePluginEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!("runtime plugin event channel full, dropped {event:?} ({dropped} total)");
+            }
+            // The service has shut down.
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+pub fn runtime_plugin_event_channel() -> (RuntimePluginEventSender, Receiver<RuntimePluginEvent>) {
+    let (sender, receiver) = bounded(EVENT_CHANNEL_CAPACITY);
+
+    (
+        RuntimePluginEventSender {
+            sender,
+            dropped: Arc::default(),
+        },
+        receiver,
+    )
+}
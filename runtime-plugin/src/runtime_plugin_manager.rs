@@ -1,14 +1,21 @@
 use {
-    crate::runtime_plugin::{PluginDependencies, RuntimePlugin},
+    crate::{
+        runtime_plugin::{PluginDependencies, RuntimePlugin, RUNTIME_PLUGIN_INTERFACE_VERSION},
+        runtime_plugin_admin_rpc_service::RuntimePluginInfo,
+        runtime_plugin_event::RuntimePluginEvent,
+    },
     jsonrpc_core::{serde_json, ErrorCode, Result as JsonRpcResult},
     libloading::Library,
     log::*,
     solana_runtime::{bank_forks::BankForks, commitment::BlockCommitmentCache},
+    solana_sdk::clock::Epoch,
     std::{
         fs::File,
         io::Read,
         path::{Path, PathBuf},
         sync::{atomic::AtomicBool, Arc, RwLock},
+        thread,
+        time::Duration,
     },
 };
 
@@ -32,6 +39,12 @@ pub enum RuntimePluginManagerError {
     #[error("Cannot load plugin shared library")]
     PluginLoadError(String),
 
+    #[error(
+        "The plugin implements runtime plugin interface version {0}, expected {}",
+        RUNTIME_PLUGIN_INTERFACE_VERSION
+    )]
+    IncompatibleInterfaceVersion(u32),
+
     #[error("The runtime plugin {0} is already loaded shared library")]
     PluginAlreadyLoaded(String),
 
@@ -40,8 +53,8 @@ pub enum RuntimePluginManagerError {
 }
 
 pub struct RuntimePluginManager {
-    plugins: Vec<Box<dyn RuntimePlugin>>,
-    libs: Vec<Library>,
+    plugins: Vec<Arc<dyn RuntimePlugin>>,
+    libs: Vec<Arc<Library>>,
     bank_forks: Arc<RwLock<BankForks>>,
     block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
     exit: Arc<AtomicBool>,
@@ -109,8 +122,8 @@ impl RuntimePluginManager {
                 data: None,
             })?;
 
-        self.plugins.push(new_plugin);
-        self.libs.push(new_lib);
+        self.plugins.push(Arc::from(new_plugin));
+        self.libs.push(Arc::new(new_lib));
 
         Ok(name.to_string())
     }
@@ -182,8 +195,8 @@ impl RuntimePluginManager {
         ) {
             // On success, push plugin and library
             Ok(()) => {
-                self.plugins.push(new_plugin);
-                self.libs.push(new_lib);
+                self.plugins.push(Arc::from(new_plugin));
+                self.libs.push(Arc::new(new_lib));
                 Ok(())
             }
             // On failure, return error
@@ -197,26 +210,87 @@ impl RuntimePluginManager {
         }
     }
 
-    pub(crate) fn list_plugins(&self) -> JsonRpcResult<Vec<String>> {
-        Ok(self.plugins.iter().map(|p| p.name().to_owned()).collect())
+    pub(crate) fn list_plugins(&self) -> JsonRpcResult<Vec<RuntimePluginInfo>> {
+        Ok(self
+            .plugins
+            .iter()
+            .map(|p| RuntimePluginInfo {
+                name: p.name().to_owned(),
+                version: p.version().map(str::to_owned),
+                health: p.health(),
+            })
+            .collect())
+    }
+
+    /// Takes a snapshot of the loaded plugins, so events can be dispatched to them without
+    /// holding the manager's lock.
+    pub(crate) fn snapshot(&self) -> PluginsSnapshot {
+        PluginsSnapshot {
+            plugins: self.plugins.clone(),
+            _libs: self.libs.clone(),
+        }
     }
 
     fn try_drop_plugin(&mut self, idx: usize) {
         if idx < self.plugins.len() {
             let mut plugin = self.plugins.remove(idx);
             let lib = self.libs.remove(idx);
+            // The event dispatcher may still be calling into the plugin through a snapshot. It
+            // can't take another while we hold the manager for writing, so wait it out.
+            while Arc::strong_count(&plugin) > 1 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Arc::get_mut(&mut plugin)
+                .expect("plugin is no longer shared")
+                .on_unload();
+            drop(plugin);
             drop(lib);
-            plugin.on_unload();
         } else {
             error!("failed to drop plugin: index {idx} out of bounds");
         }
     }
 }
 
+/// The loaded plugins at one point in time. Keeps the plugins' libraries loaded until dropped.
+pub(crate) struct PluginsSnapshot {
+    plugins: Vec<Arc<dyn RuntimePlugin>>,
+    // Declared after `plugins` so the plugins are dropped before their code is unloaded.
+    _libs: Vec<Arc<Library>>,
+}
+
+impl PluginsSnapshot {
+    /// Forwards an event to every plugin in the snapshot, also raising an epoch boundary when a
+    /// new root is in a later epoch than `root_epoch`.
+    pub(crate) fn notify(&self, event: &RuntimePluginEvent, root_epoch: &mut Epoch) {
+        for plugin in &self.plugins {
+            match event {
+                RuntimePluginEvent::BankFrozen(bank) => plugin.on_bank_frozen(bank),
+                RuntimePluginEvent::NewRoot(root) => plugin.on_new_root(root),
+                RuntimePluginEvent::SlotLeaderStart { slot, leader } => {
+                    plugin.on_slot_leader_start(*slot, leader)
+                }
+                RuntimePluginEvent::SlotLeaderEnd { slot, leader } => {
+                    plugin.on_slot_leader_end(*slot, leader)
+                }
+            }
+        }
+
+        if let RuntimePluginEvent::NewRoot(root) = event {
+            if root.epoch > *root_epoch {
+                *root_epoch = root.epoch;
+                for plugin in &self.plugins {
+                    plugin.on_epoch_boundary(root.epoch, root);
+                }
+            }
+        }
+    }
+}
+
 fn load_plugin_from_config(
     plugin_config_path: &Path,
 ) -> Result<(Box<dyn RuntimePlugin>, Library, &str), RuntimePluginManagerError> {
     type PluginConstructor = unsafe fn() -> *mut dyn RuntimePlugin;
+    type PluginInterfaceVersion = unsafe extern "C" fn() -> u32;
     use libloading::Symbol;
 
     let mut file = match File::open(plugin_config_path) {
@@ -265,6 +339,15 @@ fn load_plugin_from_config(
     let (plugin, lib) = unsafe {
         let lib = Library::new(libpath)
             .map_err(|e| RuntimePluginManagerError::PluginLoadError(e.to_string()))?;
+        // Plugins built before the interface was versioned don't export the symbol.
+        let interface_version = lib
+            .get::<PluginInterfaceVersion>(b"_runtime_plugin_interface_version")
+            .map_or(1, |version| version());
+        if interface_version != RUNTIME_PLUGIN_INTERFACE_VERSION {
+            return Err(RuntimePluginManagerError::IncompatibleInterfaceVersion(
+                interface_version,
+            ));
+        }
         let constructor: Symbol<PluginConstructor> = lib
             .get(b"_create_plugin")
             .map_err(|e| RuntimePluginManagerError::PluginLoadError(e.to_string()))?;
@@ -273,3 +356,161 @@ fn load_plugin_from_config(
 
     Ok((plugin, lib, config_file))
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::runtime_plugin_event::BankInfo,
+        solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey},
+        std::sync::Mutex,
+    };
+
+    #[derive(Debug, Default)]
+    struct RecordingPlugin {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl RuntimePlugin for RecordingPlugin {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn on_load(
+            &mut self,
+            _config_file: &str,
+            _dependencies: PluginDependencies,
+        ) -> crate::runtime_plugin::Result<()> {
+            Ok(())
+        }
+
+        fn on_unload(&mut self) {}
+
+        fn on_bank_frozen(&self, bank: &BankInfo) {
+            self.record(format!("frozen {}", bank.slot));
+        }
+
+        fn on_new_root(&self, root: &BankInfo) {
+            self.record(format!("root {}", root.slot));
+        }
+
+        fn on_slot_leader_start(&self, slot: Slot, _leader: &Pubkey) {
+            self.record(format!("leader start {slot}"));
+        }
+
+        fn on_slot_leader_end(&self, slot: Slot, _leader: &Pubkey) {
+            self.record(format!("leader end {slot}"));
+        }
+
+        fn on_epoch_boundary(&self, epoch: Epoch, root: &BankInfo) {
+            self.record(format!("epoch {epoch} at {}", root.slot));
+        }
+    }
+
+    impl RecordingPlugin {
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+
+        fn take_calls(&self) -> Vec<String> {
+            std::mem::take(&mut self.calls.lock().unwrap())
+        }
+    }
+
+    fn bank_info(slot: Slot, epoch: Epoch) -> BankInfo {
+        BankInfo {
+            slot,
+            parent_slot: slot.saturating_sub(1),
+            epoch,
+            hash: Hash::new_unique(),
+            leader: Pubkey::new_unique(),
+        }
+    }
+
+    fn snapshot_of(plugin: &Arc<RecordingPlugin>) -> PluginsSnapshot {
+        PluginsSnapshot {
+            plugins: vec![plugin.clone() as Arc<dyn RuntimePlugin>],
+            _libs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_notify_forwards_events() {
+        let plugin = Arc::new(RecordingPlugin::default());
+        let snapshot = snapshot_of(&plugin);
+        let leader = Pubkey::new_unique();
+        let mut root_epoch = 0;
+
+        for event in [
+            RuntimePluginEvent::SlotLeaderStart { slot: 5, leader },
+            RuntimePluginEvent::SlotLeaderEnd { slot: 5, leader },
+            RuntimePluginEvent::BankFrozen(bank_info(5, 0)),
+            RuntimePluginEvent::NewRoot(bank_info(4, 0)),
+        ] {
+            snapshot.notify(&event, &mut root_epoch);
+        }
+
+        assert_eq!(
+            plugin.take_calls(),
+            vec!["leader start 5", "leader end 5", "frozen 5", "root 4"]
+        );
+        assert_eq!(root_epoch, 0);
+    }
+
+    #[test]
+    fn test_notify_epoch_boundary_once_per_epoch() {
+        let plugin = Arc::new(RecordingPlugin::default());
+        let snapshot = snapshot_of(&plugin);
+        let mut root_epoch = 1;
+
+        snapshot.notify(
+            &RuntimePluginEvent::NewRoot(bank_info(63, 1)),
+            &mut root_epoch,
+        );
+        assert_eq!(plugin.take_calls(), vec!["root 63"]);
+
+        snapshot.notify(
+            &RuntimePluginEvent::NewRoot(bank_info(65, 2)),
+            &mut root_epoch,
+        );
+        assert_eq!(plugin.take_calls(), vec!["root 65", "epoch 2 at 65"]);
+        assert_eq!(root_epoch, 2);
+
+        // Later roots in the same epoch, and frozen banks of later epochs, don't raise it again.
+        snapshot.notify(
+            &RuntimePluginEvent::NewRoot(bank_info(66, 2)),
+            &mut root_epoch,
+        );
+        snapshot.notify(
+            &RuntimePluginEvent::BankFrozen(bank_info(100, 3)),
+            &mut root_epoch,
+        );
+        assert_eq!(plugin.take_calls(), vec!["root 66", "frozen 100"]);
+        assert_eq!(root_epoch, 2);
+    }
+
+    #[test]
+    fn test_snapshot_outlives_unload() {
+        let bank = solana_runtime::bank::Bank::default_for_tests();
+        let mut manager =
+            RuntimePluginManager::new(BankForks::new_rw_arc(bank), Arc::default(), Arc::default());
+        let plugin = Arc::new(RecordingPlugin::default());
+        manager.plugins.push(plugin.clone());
+        manager.libs.push(Arc::new(Library::from(
+            libloading::os::unix::Library::this(),
+        )));
+
+        // Unloading waits until nothing else holds the plugin, so dispatch from another thread
+        // while it does.
+        let snapshot = manager.snapshot();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            snapshot.notify(&RuntimePluginEvent::BankFrozen(bank_info(1, 0)), &mut 0);
+            drop(snapshot);
+            plugin.take_calls()
+        });
+        manager.unload_plugin("recording").unwrap();
+        assert!(manager.plugins.is_empty());
+        assert_eq!(releaser.join().unwrap(), vec!["frozen 1"]);
+    }
+}
@@ -2,11 +2,12 @@ use {
     crate::{
         runtime_plugin::RuntimePluginError,
         runtime_plugin_admin_rpc_service::RuntimePluginManagerRpcRequest,
-        runtime_plugin_manager::RuntimePluginManager,
+        runtime_plugin_event::RuntimePluginEvent, runtime_plugin_manager::RuntimePluginManager,
     },
-    crossbeam_channel::Receiver,
+    crossbeam_channel::{Receiver, RecvTimeoutError},
     log::{error, info},
     solana_runtime::{bank_forks::BankForks, commitment::BlockCommitmentCache},
+    solana_sdk::clock::Epoch,
     std::{
         path::PathBuf,
         sync::{
@@ -21,16 +22,19 @@ use {
 pub struct RuntimePluginService {
     plugin_manager: Arc<RwLock<RuntimePluginManager>>,
     rpc_thread: JoinHandle<()>,
+    event_thread: JoinHandle<()>,
 }
 
 impl RuntimePluginService {
     pub fn start(
         plugin_config_files: &[PathBuf],
         rpc_receiver: Receiver<RuntimePluginManagerRpcRequest>,
+        event_receiver: Receiver<RuntimePluginEvent>,
         bank_forks: Arc<RwLock<BankForks>>,
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         exit: Arc<AtomicBool>,
     ) -> Result<Self, RuntimePluginError> {
+        let root_epoch = bank_forks.read().unwrap().root_bank().epoch();
         let mut plugin_manager =
             RuntimePluginManager::new(bank_forks, block_commitment_cache, exit.clone());
 
@@ -43,11 +47,14 @@ impl RuntimePluginService {
 
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
         let rpc_thread =
-            Self::start_rpc_request_handler(rpc_receiver, plugin_manager.clone(), exit);
+            Self::start_rpc_request_handler(rpc_receiver, plugin_manager.clone(), exit.clone());
+        let event_thread =
+            Self::start_event_dispatcher(event_receiver, plugin_manager.clone(), root_epoch, exit);
 
         Ok(Self {
             plugin_manager,
             rpc_thread,
+            event_thread,
         })
     }
 
@@ -55,9 +62,39 @@ impl RuntimePluginService {
         if let Err(e) = self.rpc_thread.join() {
             error!("error joining rpc thread: {e:?}");
         }
+        if let Err(e) = self.event_thread.join() {
+            error!("error joining event thread: {e:?}");
+        }
         self.plugin_manager.write().unwrap().unload_all_plugins();
     }
 
+    /// Forwards replay events to the plugins, keeping plugin callbacks off the replay thread.
+    fn start_event_dispatcher(
+        event_receiver: Receiver<RuntimePluginEvent>,
+        plugin_manager: Arc<RwLock<RuntimePluginManager>>,
+        mut root_epoch: Epoch,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        thread::Builder::new()
+            .name("solRuntimePluginEvt".to_string())
+            .spawn(move || {
+                const TIMEOUT: Duration = Duration::from_secs(1);
+                while !exit.load(Ordering::Relaxed) {
+                    let event = match event_receiver.recv_timeout(TIMEOUT) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+
+                    // Don't hold the manager across plugin callbacks, so a slow plugin can't
+                    // stall admin RPC requests.
+                    let plugins = plugin_manager.read().unwrap().snapshot();
+                    plugins.notify(&event, &mut root_epoch);
+                }
+            })
+            .unwrap()
+    }
+
     fn start_rpc_request_handler(
         rpc_receiver: Receiver<RuntimePluginManagerRpcRequest>,
         plugin_manager: Arc<RwLock<RuntimePluginManager>>,
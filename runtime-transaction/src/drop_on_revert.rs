@@ -0,0 +1,84 @@
+//! Per-transaction opt-in to drop-on-revert semantics.
+//!
+//! A transaction requests drop-on-revert by including an SPL memo instruction whose data is
+//! exactly [`DROP_ON_REVERT_MEMO`]. Such a transaction is not committed (and pays no fee) if it
+//! fails execution, the same as packets received on the P3 MEV socket.
+use {
+    solana_pubkey::Pubkey, solana_sdk::pubkey, solana_svm_transaction::instruction::SVMInstruction,
+};
+
+/// SPL memo program (v2).
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Memo data marking a transaction as drop-on-revert.
+pub const DROP_ON_REVERT_MEMO: &[u8] = b"paladin:drop-on-revert";
+
+/// Estimated cost above which the block producer doesn't schedule drop-on-revert transactions.
+pub const MAX_DROP_ON_REVERT_COST: u64 = 550_000;
+
+/// Returns true if any of the instructions is the drop-on-revert memo.
+pub fn is_drop_on_revert_requested<'a>(
+    mut instructions: impl Iterator<Item = (&'a Pubkey, SVMInstruction<'a>)>,
+) -> bool {
+    instructions.any(|(program_id, instruction)| {
+        program_id == &MEMO_PROGRAM_ID && instruction.data == DROP_ON_REVERT_MEMO
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::instruction::{CompiledInstruction, Instruction},
+    };
+
+    fn is_requested(instructions: &[Instruction]) -> bool {
+        let compiled: Vec<_> = instructions
+            .iter()
+            .enumerate()
+            .map(|(i, ix)| {
+                CompiledInstruction::new_from_raw_parts(i as u8, ix.data.clone(), vec![])
+            })
+            .collect();
+
+        is_drop_on_revert_requested(
+            instructions
+                .iter()
+                .zip(compiled.iter())
+                .map(|(ix, compiled)| (&ix.program_id, SVMInstruction::from(compiled))),
+        )
+    }
+
+    #[test]
+    fn test_memo_marker() {
+        assert!(is_requested(&[Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            DROP_ON_REVERT_MEMO,
+            vec![],
+        )]));
+    }
+
+    #[test]
+    fn test_marker_among_other_instructions() {
+        assert!(is_requested(&[
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]),
+            Instruction::new_with_bytes(MEMO_PROGRAM_ID, b"hello", vec![]),
+            Instruction::new_with_bytes(MEMO_PROGRAM_ID, DROP_ON_REVERT_MEMO, vec![]),
+        ]));
+    }
+
+    #[test]
+    fn test_no_marker() {
+        assert!(!is_requested(&[]));
+        assert!(!is_requested(&[Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            b"paladin:drop-on-revert ",
+            vec![],
+        )]));
+        assert!(!is_requested(&[Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            DROP_ON_REVERT_MEMO,
+            vec![],
+        )]));
+    }
+}
@@ -4,6 +4,7 @@
 mod builtin_programs_filter;
 mod compute_budget_instruction_details;
 mod compute_budget_program_id_filter;
+pub mod drop_on_revert;
 pub mod instructions_processor;
 pub mod runtime_transaction;
 pub mod signature_details;
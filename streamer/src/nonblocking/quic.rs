@@ -38,6 +38,7 @@ use {
     solana_transaction_metrics_tracker::signature_if_should_track_packet,
     std::{
         array,
+        collections::HashMap,
         fmt,
         iter::repeat_with,
         net::{IpAddr, SocketAddr, UdpSocket},
@@ -69,6 +70,19 @@ use {
 
 pub const DEFAULT_WAIT_FOR_CHUNK_TIMEOUT: Duration = Duration::from_secs(2);
 
+/// Remote address to identity (and connection `stable_id`) of every live P3 connection, shared
+/// with the consumer so that it can attribute received packets to the P3 peer that sent them.
+pub type P3PeerIdentities = Arc<RwLock<HashMap<SocketAddr, (Pubkey, usize)>>>;
+
+/// State a P3 server shares with its consumer.
+#[derive(Clone, Default)]
+pub struct P3ServerState {
+    pub peer_identities: P3PeerIdentities,
+    /// The server's staked connection table, used by the consumer to purge connections whose
+    /// stake changed.
+    pub connection_table: Arc<Mutex<ConnectionTable>>,
+}
+
 pub const ALPN_TPU_PROTOCOL_ID: &[u8] = b"solana-tpu";
 
 const CONNECTION_CLOSE_CODE_DROPPED_ENTRY: u32 = 1;
@@ -164,7 +178,7 @@ pub fn spawn_server(
     max_connections_per_ipaddr_per_min: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
-    is_p3: bool,
+    p3_state: Option<P3ServerState>,
 ) -> Result<SpawnNonBlockingServerResult, QuicServerError> {
     spawn_server_multi(
         name,
@@ -180,7 +194,7 @@ pub fn spawn_server(
         max_connections_per_ipaddr_per_min,
         wait_for_chunk_timeout,
         coalesce,
-        is_p3,
+        p3_state,
     )
 }
 
@@ -199,7 +213,7 @@ pub fn spawn_server_multi(
     max_connections_per_ipaddr_per_min: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
-    is_p3: bool,
+    p3_state: Option<P3ServerState>,
 ) -> Result<SpawnNonBlockingServerResult, QuicServerError> {
     info!("Start {name} quic server on {sockets:?}");
     let concurrent_connections = max_staked_connections + max_unstaked_connections;
@@ -234,7 +248,7 @@ pub fn spawn_server_multi(
         wait_for_chunk_timeout,
         coalesce,
         max_concurrent_connections,
-        is_p3,
+        p3_state,
     ));
     Ok(SpawnNonBlockingServerResult {
         endpoints,
@@ -306,7 +320,7 @@ async fn run_server(
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
     max_concurrent_connections: usize,
-    is_p3: bool,
+    p3_state: Option<P3ServerState>,
 ) {
     let rate_limiter = ConnectionRateLimiter::new(max_connections_per_ipaddr_per_min);
     let overall_connection_rate_limiter =
@@ -325,8 +339,12 @@ async fn run_server(
     stats
         .quic_endpoints_count
         .store(endpoints.len(), Ordering::Relaxed);
-    let staked_connection_table: Arc<Mutex<ConnectionTable>> =
-        Arc::new(Mutex::new(ConnectionTable::new()));
+    // NB: P3 servers share their staked connection table with the consumer.
+    let staked_connection_table: Arc<Mutex<ConnectionTable>> = p3_state
+        .as_ref()
+        .map(|p3_state| p3_state.connection_table.clone())
+        .unwrap_or_default();
+    let p3_peer_identities = p3_state.map(|p3_state| p3_state.peer_identities);
     let (sender, receiver) = async_unbounded();
     tokio::spawn(packet_batch_sender(
         packet_sender,
@@ -442,7 +460,7 @@ async fn run_server(
                         stats.clone(),
                         wait_for_chunk_timeout,
                         stream_load_ema.clone(),
-                        is_p3,
+                        p3_peer_identities.clone(),
                     ));
                 }
                 Err(err) => {
@@ -544,6 +562,7 @@ struct NewConnectionHandlerParams {
     stats: Arc<StreamerStats>,
     max_stake: u64,
     min_stake: u64,
+    p3_peer_identities: Option<P3PeerIdentities>,
 }
 
 impl NewConnectionHandlerParams {
@@ -561,6 +580,7 @@ impl NewConnectionHandlerParams {
             stats,
             max_stake: 0,
             min_stake: 0,
+            p3_peer_identities: None,
         }
     }
 }
@@ -608,6 +628,15 @@ fn handle_and_cache_new_connection(
         {
             drop(connection_table_l);
 
+            if let (Some(p3_peer_identities), Some(remote_pubkey)) =
+                (&params.p3_peer_identities, params.remote_pubkey)
+            {
+                p3_peer_identities
+                    .write()
+                    .unwrap()
+                    .insert(remote_addr, (remote_pubkey, connection.stable_id()));
+            }
+
             if let Ok(receive_window) = receive_window {
                 connection.set_receive_window(receive_window);
             }
@@ -738,7 +767,7 @@ async fn setup_connection(
     stats: Arc<StreamerStats>,
     wait_for_chunk_timeout: Duration,
     stream_load_ema: Arc<StakedStreamLoadEMA>,
-    is_p3: bool,
+    p3_peer_identities: Option<P3PeerIdentities>,
 ) {
     const PRUNE_RANDOM_SAMPLE_SIZE: usize = 2;
     let from = connecting.remote_address();
@@ -768,7 +797,7 @@ async fn setup_connection(
                         let min_stake_ratio =
                             1_f64 / (max_streams_per_ms * stream_throttling_interval_ms) as f64;
                         let stake_ratio = stake as f64 / total_stake as f64;
-                        let peer_type = if is_p3 {
+                        let peer_type = if p3_peer_identities.is_some() {
                             ConnectionPeerType::P3(stake)
                         } else if stake_ratio < min_stake_ratio {
                             // If it is a staked connection with ultra low stake ratio, treat it as unstaked.
@@ -786,6 +815,7 @@ async fn setup_connection(
                             stats: stats.clone(),
                             max_stake,
                             min_stake,
+                            p3_peer_identities: p3_peer_identities.clone(),
                         }
                     },
                 );
@@ -1055,6 +1085,7 @@ fn track_streamer_fetch_packet_performance(
         .fetch_add(measure.as_us(), Ordering::Relaxed);
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_connection(
     connection: Connection,
     remote_addr: SocketAddr,
@@ -1073,6 +1104,7 @@ async fn handle_connection(
         remote_pubkey,
         stats,
         total_stake,
+        p3_peer_identities,
         ..
     } = params;
 
@@ -1232,6 +1264,17 @@ async fn handle_connection(
             .connection_remove_failed
             .fetch_add(1, Ordering::Relaxed);
     }
+    if let Some(p3_peer_identities) = p3_peer_identities {
+        let mut p3_peer_identities = p3_peer_identities.write().unwrap();
+        // NB: A newer connection from the same address may have replaced our entry, only remove
+        // it if it still belongs to this connection.
+        if p3_peer_identities
+            .get(&remote_addr)
+            .map_or(false, |(_, entry_stable_id)| *entry_stable_id == stable_id)
+        {
+            p3_peer_identities.remove(&remote_addr);
+        }
+    }
     stats.total_connections.fetch_sub(1, Ordering::Relaxed);
 }
 
@@ -1403,6 +1446,7 @@ impl ConnectionTableKey {
 }
 
 // Map of IP to list of connection entries
+#[derive(Default)]
 pub struct ConnectionTable {
     table: IndexMap<ConnectionTableKey, Vec<ConnectionEntry>>,
     total_size: usize,
@@ -1412,10 +1456,7 @@ pub struct ConnectionTable {
 // Return number pruned
 impl ConnectionTable {
     pub fn new() -> Self {
-        Self {
-            table: IndexMap::default(),
-            total_size: 0,
-        }
+        Self::default()
     }
 
     pub fn table(&self) -> &IndexMap<ConnectionTableKey, Vec<ConnectionEntry>> {
@@ -1466,6 +1507,7 @@ impl ConnectionTable {
         num_pruned
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn try_add_connection(
         &mut self,
         key: ConnectionTableKey,
@@ -1938,6 +1980,116 @@ pub mod test {
         join_handle.await.unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quic_server_p3_peer_identity_survives_replaced_connection() {
+        solana_logger::setup();
+
+        let client_keypair = Keypair::new();
+        let stakes = HashMap::from([(client_keypair.pubkey(), 100_000)]);
+        let staked_nodes = Arc::new(RwLock::new(StakedNodes::new(
+            Arc::new(stakes),
+            HashMap::<Pubkey, u64>::default(), // overrides
+        )));
+        let p3_state = P3ServerState::default();
+        let p3_peer_identities = p3_state.peer_identities.clone();
+        let p3_connection_table = p3_state.connection_table.clone();
+        let s = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let exit = Arc::new(AtomicBool::new(false));
+        let (sender, _receiver) = unbounded();
+        let keypair = Keypair::new();
+        let server_address = s.local_addr().unwrap();
+        let SpawnNonBlockingServerResult {
+            endpoints: _,
+            stats,
+            thread: t,
+            max_concurrent_connections: _,
+        } = spawn_server(
+            "quic_streamer_test",
+            s,
+            &keypair,
+            sender,
+            exit.clone(),
+            2,
+            staked_nodes,
+            MAX_STAKED_CONNECTIONS,
+            0,
+            StakedStreamLoadEMAArgs::default(),
+            DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
+            DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
+            DEFAULT_TPU_COALESCE,
+            Some(p3_state),
+        )
+        .unwrap();
+
+        // Both connections share the client endpoint and therefore the remote address.
+        let client_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client_address = client_socket.local_addr().unwrap();
+        let mut endpoint = quinn::Endpoint::new(
+            EndpointConfig::default(),
+            None,
+            client_socket,
+            Arc::new(TokioRuntime),
+        )
+        .unwrap();
+        endpoint.set_default_client_config(get_client_config(&client_keypair));
+        let conn1 = endpoint
+            .connect(server_address, "localhost")
+            .expect("Failed in connecting")
+            .await
+            .expect("Failed in waiting");
+        let start = Instant::now();
+        while stats.total_connections.load(Ordering::Relaxed) != 1 {
+            assert!(start.elapsed().as_secs() < 1);
+            sleep(Duration::from_millis(10)).await;
+        }
+        let conn2 = endpoint
+            .connect(server_address, "localhost")
+            .expect("Failed in connecting")
+            .await
+            .expect("Failed in waiting");
+        let start = Instant::now();
+        while stats.total_connections.load(Ordering::Relaxed) != 2 {
+            assert!(start.elapsed().as_secs() < 1);
+            sleep(Duration::from_millis(10)).await;
+        }
+        // The consumer shares the server's staked connection table.
+        assert_eq!(p3_connection_table.lock().await.total_size, 2);
+
+        // Closing the replaced connection must not unregister the live one.
+        conn1.close(
+            CONNECTION_CLOSE_CODE_DROPPED_ENTRY.into(),
+            CONNECTION_CLOSE_REASON_DROPPED_ENTRY,
+        );
+        let start = Instant::now();
+        while stats.connection_removed.load(Ordering::Relaxed) != 1 {
+            assert!(start.elapsed().as_secs() < 1);
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            p3_peer_identities
+                .read()
+                .unwrap()
+                .get(&client_address)
+                .map(|(identity, _)| *identity),
+            Some(client_keypair.pubkey())
+        );
+
+        // Closing the live connection unregisters it.
+        conn2.close(
+            CONNECTION_CLOSE_CODE_DROPPED_ENTRY.into(),
+            CONNECTION_CLOSE_REASON_DROPPED_ENTRY,
+        );
+        let start = Instant::now();
+        while stats.connection_removed.load(Ordering::Relaxed) != 2 {
+            assert!(start.elapsed().as_secs() < 1);
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(p3_peer_identities.read().unwrap().is_empty());
+
+        exit.store(true, Ordering::Relaxed);
+        t.await.unwrap();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_quic_server_multiple_writes() {
         solana_logger::setup();
@@ -2068,7 +2220,7 @@ pub mod test {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -2105,7 +2257,7 @@ pub mod test {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -1,6 +1,9 @@
 use {
     crate::{
-        nonblocking::{quic::ALPN_TPU_PROTOCOL_ID, stream_throttle::StakedStreamLoadEMAArgs},
+        nonblocking::{
+            quic::{P3ServerState, ALPN_TPU_PROTOCOL_ID},
+            stream_throttle::StakedStreamLoadEMAArgs,
+        },
         streamer::StakedNodes,
         tls_certificates::new_dummy_x509_certificate,
     },
@@ -622,7 +625,7 @@ pub fn spawn_server(
     max_connections_per_ipaddr_per_min: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
-    is_p3: bool,
+    p3_state: Option<P3ServerState>,
 ) -> Result<SpawnServerResult, QuicServerError> {
     spawn_server_multi(
         thread_name,
@@ -639,7 +642,7 @@ pub fn spawn_server(
         max_connections_per_ipaddr_per_min,
         wait_for_chunk_timeout,
         coalesce,
-        is_p3,
+        p3_state,
     )
 }
 
@@ -659,7 +662,7 @@ pub fn spawn_server_multi(
     max_connections_per_ipaddr_per_min: u64,
     wait_for_chunk_timeout: Duration,
     coalesce: Duration,
-    is_p3: bool,
+    p3_state: Option<P3ServerState>,
 ) -> Result<SpawnServerResult, QuicServerError> {
     let runtime = rt(format!("{thread_name}Rt"));
     let result = {
@@ -678,7 +681,7 @@ pub fn spawn_server_multi(
             max_connections_per_ipaddr_per_min,
             wait_for_chunk_timeout,
             coalesce,
-            is_p3,
+            p3_state,
         )
     }?;
     let handle = thread::Builder::new()
@@ -742,7 +745,7 @@ mod test {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
         (t, exit, receiver, server_address)
@@ -804,7 +807,7 @@ mod test {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -853,7 +856,7 @@ mod test {
             DEFAULT_MAX_CONNECTIONS_PER_IPADDR_PER_MINUTE,
             DEFAULT_WAIT_FOR_CHUNK_TIMEOUT,
             DEFAULT_TPU_COALESCE,
-            false,
+            None,
         )
         .unwrap();
 
@@ -11,6 +11,7 @@ pub struct TransactionErrorMetrics {
     pub blockhash_too_old: usize,
     pub call_chain_too_deep: usize,
     pub already_processed: usize,
+    pub drop_on_revert: usize,
     pub instruction_error: usize,
     pub insufficient_funds: usize,
     pub invalid_account_for_fee: usize,
@@ -43,6 +44,7 @@ impl TransactionErrorMetrics {
         saturating_add_assign!(self.blockhash_too_old, other.blockhash_too_old);
         saturating_add_assign!(self.call_chain_too_deep, other.call_chain_too_deep);
         saturating_add_assign!(self.already_processed, other.already_processed);
+        saturating_add_assign!(self.drop_on_revert, other.drop_on_revert);
         saturating_add_assign!(self.instruction_error, other.instruction_error);
         saturating_add_assign!(self.insufficient_funds, other.insufficient_funds);
         saturating_add_assign!(self.invalid_account_for_fee, other.invalid_account_for_fee);
@@ -357,6 +357,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                         );
 
                         if !executed_tx.was_successful() && tx.drop_on_revert() {
+                            saturating_add_assign!(error_metrics.drop_on_revert, 1);
                             return Err(TransactionError::AlreadyProcessed);
                         }
 
@@ -1,4 +1,5 @@
-//! This binary generates a merkle tree for each [TipDistributionAccount]; they are derived
+//! This binary generates a merkle tree for each [TipDistributionAccount], and one for the lockups
+//! sharing the funnel's stakers and holders tips if there's a lockup pool; they are derived
 //! using a user provided [StakeMetaCollection] JSON file.
 
 use {
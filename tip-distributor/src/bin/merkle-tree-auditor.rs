@@ -0,0 +1,104 @@
+//! This binary audits a [GeneratedMerkleTreeCollection] JSON file: it verifies every proof against
+//! its tree's root, optionally checks the nodes against the delegations in a snapshot and the
+//! roots against the ones uploaded on-chain, then writes the discrepancies found to a JSON
+//! [AuditReport]. Exits with an error if there are any.
+
+use {
+    clap::Parser,
+    log::*,
+    solana_sdk::pubkey::Pubkey,
+    solana_tip_distributor::merkle_tree_audit_workflow::{audit_merkle_trees, SnapshotAuditConfig},
+    std::{fs, path::PathBuf, process::exit},
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path to JSON file containing the [GeneratedMerkleTreeCollection] object.
+    #[arg(long, env)]
+    merkle_trees_path: PathBuf,
+
+    /// Ledger path with a snapshot at the trees' slot, to check the tree nodes against the
+    /// delegations in it.
+    #[arg(
+        long,
+        env,
+        value_parser = Args::ledger_path_parser,
+        requires_all = ["tip_distribution_program_id", "tip_payment_program_id"]
+    )]
+    ledger_path: Option<PathBuf>,
+
+    /// The tip-distribution program id.
+    #[arg(long, env)]
+    tip_distribution_program_id: Option<Pubkey>,
+
+    /// The tip-payment program id.
+    #[arg(long, env)]
+    tip_payment_program_id: Option<Pubkey>,
+
+    /// The Paladin funnel the trees were generated with, if any.
+    #[arg(long, env, requires = "ledger_path")]
+    funnel: Option<Pubkey>,
+
+    /// The lockup pool the trees were generated with, if any.
+    #[arg(long, env, requires = "funnel")]
+    lockup_pool: Option<Pubkey>,
+
+    /// The previous epoch's [StakeMetaCollection] the trees were generated with, if any.
+    #[arg(long, env, requires = "funnel")]
+    previous_stake_meta_path: Option<PathBuf>,
+
+    /// RPC to fetch the [TipDistributionAccount]s from, to check the roots against the uploaded ones.
+    #[arg(long, env)]
+    rpc_url: Option<String>,
+
+    /// Path to JSON file to get populated with the [AuditReport].
+    #[arg(long, env)]
+    out_path: PathBuf,
+}
+
+impl Args {
+    fn ledger_path_parser(ledger_path: &str) -> Result<PathBuf, &'static str> {
+        Ok(fs::canonicalize(ledger_path).unwrap_or_else(|err| {
+            error!("Unable to access ledger path '{}': {}", ledger_path, err);
+            exit(1);
+        }))
+    }
+}
+
+fn main() {
+    env_logger::init();
+    info!("Starting merkle-tree-auditor...");
+
+    let args: Args = Args::parse();
+    let maybe_snapshot_config = args.ledger_path.map(|ledger_path| SnapshotAuditConfig {
+        ledger_path,
+        // both required by the ledger path
+        tip_distribution_program_id: args.tip_distribution_program_id.unwrap(),
+        tip_payment_program_id: args.tip_payment_program_id.unwrap(),
+        maybe_funnel_pubkey: args.funnel,
+        maybe_lockup_pool_pubkey: args.lockup_pool,
+        maybe_previous_stake_meta_path: args.previous_stake_meta_path,
+    });
+
+    let report = audit_merkle_trees(
+        &args.merkle_trees_path,
+        maybe_snapshot_config.as_ref(),
+        args.rpc_url.as_deref(),
+        &args.out_path,
+    )
+    .unwrap_or_else(|e| {
+        error!("error auditing merkle trees: {:?}", e);
+        exit(1);
+    });
+
+    info!("saved audit report to {:?}", args.out_path);
+    if !report.discrepancies.is_empty() {
+        error!(
+            "found {} discrepancies in the merkle trees for epoch {}",
+            report.discrepancies.len(),
+            report.epoch
+        );
+        exit(1);
+    }
+}
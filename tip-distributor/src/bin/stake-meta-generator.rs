@@ -36,6 +36,19 @@ struct Args {
     /// The expected snapshot slot.
     #[arg(long, env)]
     snapshot_slot: Slot,
+
+    /// The Paladin funnel, to include its stakers and holders flows in the [StakeMetaCollection].
+    #[arg(long, env)]
+    funnel: Option<Pubkey>,
+
+    /// The lockup pool whose lockups share the funnel's stakers and holders tips.
+    #[arg(long, env, requires = "funnel")]
+    lockup_pool: Option<Pubkey>,
+
+    /// The previous epoch's [StakeMetaCollection], so only what reached the funnel's stakers and
+    /// holders receivers since then is counted. Without it, everything they hold is counted.
+    #[arg(long, env, requires = "funnel")]
+    previous_stake_meta_path: Option<PathBuf>,
 }
 
 impl Args {
@@ -59,6 +72,9 @@ fn main() {
         &args.tip_distribution_program_id,
         &args.out_path,
         &args.tip_payment_program_id,
+        args.funnel.as_ref(),
+        args.lockup_pool.as_ref(),
+        args.previous_stake_meta_path.as_deref(),
     ) {
         error!("error producing stake-meta: {:?}", e);
     } else {
@@ -0,0 +1,143 @@
+//! This binary runs the stake-meta-generator, merkle-root-generator, merkle-root-uploader and
+//! claim-mev-tips workflows for every epoch as it ends, checkpointing its progress to resume
+//! where it stopped after a restart.
+
+use {
+    clap::Parser,
+    gethostname::gethostname,
+    log::*,
+    signal_hook::consts::{SIGINT, SIGTERM},
+    solana_metrics::set_host_id,
+    solana_sdk::{clock::Epoch, pubkey::Pubkey},
+    solana_tip_distributor::daemon_workflow::{DaemonConfig, TipDistributorDaemon},
+    std::{
+        fs,
+        path::PathBuf,
+        process::exit,
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Ledger path of a validator following the cluster, opened with secondary access.
+    #[arg(long, env, value_parser = Args::path_parser)]
+    ledger_path: PathBuf,
+
+    /// Where the validator writes its full and incremental snapshot archives.
+    #[arg(long, env, value_parser = Args::path_parser)]
+    snapshots_dir: PathBuf,
+
+    /// Where the checkpoint and the generated [StakeMetaCollection] and
+    /// [GeneratedMerkleTreeCollection] files are written.
+    #[arg(long, env)]
+    working_dir: PathBuf,
+
+    /// The RPC to send transactions to.
+    #[arg(long, env, default_value = "http://localhost:8899")]
+    rpc_url: String,
+
+    /// The path to the keypair used to sign and pay for the upload, claim and reclaim transactions.
+    #[arg(long, env)]
+    keypair_path: PathBuf,
+
+    /// The tip-distribution program id.
+    #[arg(long, env)]
+    tip_distribution_program_id: Pubkey,
+
+    /// The tip-payment program id.
+    #[arg(long, env)]
+    tip_payment_program_id: Pubkey,
+
+    /// The Paladin funnel, to include its stakers and holders flows in the [StakeMetaCollection].
+    #[arg(long, env)]
+    funnel: Option<Pubkey>,
+
+    /// The lockup pool whose lockups share the funnel's stakers and holders tips.
+    #[arg(long, env, requires = "funnel")]
+    lockup_pool: Option<Pubkey>,
+
+    /// The first epoch to distribute when there's no checkpoint, defaults to the last epoch that
+    /// ended.
+    #[arg(long, env)]
+    start_epoch: Option<Epoch>,
+
+    /// Rate-limits the maximum number of requests per RPC connection
+    #[arg(long, env, default_value_t = 100)]
+    max_concurrent_rpc_get_reqs: usize,
+
+    /// Number of transactions to send to RPC at a time.
+    #[arg(long, env, default_value_t = 64)]
+    txn_send_batch_size: usize,
+
+    /// Limits how long before send loop runs before stopping
+    #[arg(long, env, default_value_t = 60 * 60)]
+    max_retry_duration_secs: u64,
+
+    /// Specifies whether to reclaim rent on behalf of validators from respective TDAs.
+    #[arg(long, env)]
+    should_reclaim_tdas: bool,
+
+    /// The price to pay for priority fee
+    #[arg(long, env, default_value_t = 1)]
+    micro_lamports: u64,
+
+    /// How long to wait before checking the ledger again for an ended epoch.
+    #[arg(long, env, default_value_t = 60)]
+    poll_interval_secs: u64,
+}
+
+impl Args {
+    fn path_parser(path: &str) -> Result<PathBuf, &'static str> {
+        Ok(fs::canonicalize(path).unwrap_or_else(|err| {
+            error!("Unable to access path '{}': {}", path, err);
+            exit(1);
+        }))
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    gethostname()
+        .into_string()
+        .map(set_host_id)
+        .expect("set hostname");
+
+    let args: Args = Args::parse();
+    info!("Starting tip-distributor-daemon...");
+
+    let daemon = TipDistributorDaemon::new(DaemonConfig {
+        ledger_path: args.ledger_path,
+        snapshots_dir: args.snapshots_dir,
+        working_dir: args.working_dir,
+        rpc_url: args.rpc_url,
+        keypair_path: args.keypair_path,
+        tip_distribution_program_id: args.tip_distribution_program_id,
+        tip_payment_program_id: args.tip_payment_program_id,
+        maybe_funnel_pubkey: args.funnel,
+        maybe_lockup_pool_pubkey: args.lockup_pool,
+        maybe_start_epoch: args.start_epoch,
+        max_concurrent_rpc_get_reqs: args.max_concurrent_rpc_get_reqs,
+        txn_send_batch_size: args.txn_send_batch_size,
+        max_retry_duration: Duration::from_secs(args.max_retry_duration_secs),
+        micro_lamports: args.micro_lamports,
+        should_reclaim_tdas: args.should_reclaim_tdas,
+        poll_interval: Duration::from_secs(args.poll_interval_secs),
+    })
+    .unwrap_or_else(|e| {
+        error!("error starting tip-distributor-daemon: {:?}", e);
+        exit(1);
+    });
+
+    // stop between steps on a signal, so the checkpoint reflects the last completed step
+    let exit_flag = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register(signal, exit_flag.clone()).expect("register signal handler");
+    }
+
+    daemon.run(&exit_flag);
+    info!("tip-distributor-daemon exited");
+}
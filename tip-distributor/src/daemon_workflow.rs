@@ -0,0 +1,594 @@
+use {
+    crate::{
+        claim_mev_workflow::{claim_mev_tips, ClaimMevError},
+        merkle_root_generator_workflow::{generate_merkle_root, MerkleRootGeneratorError},
+        merkle_root_upload_workflow::{upload_merkle_root, MerkleRootUploadError},
+        read_json_from_file,
+        reclaim_rent_workflow::reclaim_rent,
+        stake_meta_generator_workflow::{
+            create_bank_from_ledger, generate_stake_meta_collection, read_previous_funnel_meta,
+            write_to_json_file, StakeMetaGeneratorError,
+        },
+        GeneratedMerkleTreeCollection,
+    },
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_accounts_db::hardened_unpack::{
+        open_genesis_config, OpenGenesisConfigError, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+    },
+    solana_ledger::{
+        blockstore::{Blockstore, BlockstoreError},
+        blockstore_options::{AccessType, BlockstoreOptions, LedgerColumnOptions},
+    },
+    solana_metrics::{datapoint_error, datapoint_info},
+    solana_runtime::{snapshot_archive_info::SnapshotArchiveInfoGetter, snapshot_utils},
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        epoch_schedule::EpochSchedule,
+        genesis_config::GenesisConfig,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair},
+    },
+    std::{
+        collections::HashSet,
+        fs::{self, File},
+        io::{BufWriter, ErrorKind, Write},
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+    thiserror::Error,
+    tokio::runtime::{Builder, Runtime},
+};
+
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    BlockstoreError(#[from] BlockstoreError),
+
+    #[error(transparent)]
+    GenesisConfigError(#[from] OpenGenesisConfigError),
+
+    #[error("failed to read keypair: {0}")]
+    KeypairError(String),
+
+    #[error(transparent)]
+    StakeMetaGeneratorError(#[from] StakeMetaGeneratorError),
+
+    #[error(transparent)]
+    MerkleRootGeneratorError(#[from] MerkleRootGeneratorError),
+
+    #[error(transparent)]
+    MerkleRootUploadError(#[from] MerkleRootUploadError),
+
+    #[error(transparent)]
+    ClaimMevError(#[from] ClaimMevError),
+
+    #[error("expected a bank in epoch {expected}, found slot {slot} in epoch {actual}")]
+    UnexpectedBankEpoch {
+        expected: Epoch,
+        actual: Epoch,
+        slot: Slot,
+    },
+}
+
+/// How often a sleeping daemon checks whether it should exit.
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The steps run to distribute an epoch's tips, in order.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DistributionStep {
+    GenerateStakeMeta,
+    GenerateMerkleTrees,
+    UploadMerkleRoots,
+    ClaimTips,
+    ReclaimRent,
+}
+
+impl DistributionStep {
+    fn next(self) -> Option<DistributionStep> {
+        match self {
+            DistributionStep::GenerateStakeMeta => Some(DistributionStep::GenerateMerkleTrees),
+            DistributionStep::GenerateMerkleTrees => Some(DistributionStep::UploadMerkleRoots),
+            DistributionStep::UploadMerkleRoots => Some(DistributionStep::ClaimTips),
+            DistributionStep::ClaimTips => Some(DistributionStep::ReclaimRent),
+            DistributionStep::ReclaimRent => None,
+        }
+    }
+}
+
+/// The daemon's progress, saved after every step so a restarted daemon resumes where it stopped.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The epoch being distributed.
+    pub epoch: Epoch,
+
+    /// The last step completed for the epoch, if any.
+    pub maybe_completed_step: Option<DistributionStep>,
+}
+
+impl Checkpoint {
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            epoch,
+            maybe_completed_step: None,
+        }
+    }
+
+    /// Returns the step to run next, `None` once the epoch is distributed.
+    pub fn next_step(&self) -> Option<DistributionStep> {
+        match self.maybe_completed_step {
+            None => Some(DistributionStep::GenerateStakeMeta),
+            Some(step) => step.next(),
+        }
+    }
+
+    /// Reads the checkpoint at `path`, `None` if there's none yet.
+    pub fn load(path: &Path) -> Result<Option<Self>, DaemonError> {
+        match File::open(path) {
+            Ok(file) => Ok(Some(serde_json::from_reader(file)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the checkpoint to `path`, replacing the previous one atomically so a crash never
+    /// leaves a partially written checkpoint behind.
+    pub fn save(&self, path: &Path) -> Result<(), DaemonError> {
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+}
+
+pub struct DaemonConfig {
+    /// The ledger of a validator following the cluster, read with secondary access.
+    pub ledger_path: PathBuf,
+
+    /// Where the validator writes its full and incremental snapshot archives.
+    pub snapshots_dir: PathBuf,
+
+    /// Where the checkpoint, the generated JSON files, the full snapshot archives kept for the
+    /// epochs still to distribute and the accounts of loaded banks are kept. Archives are hard
+    /// linked from `snapshots_dir`, so it should be on the same file system.
+    pub working_dir: PathBuf,
+
+    pub rpc_url: String,
+
+    /// Pays for and signs the upload, claim and reclaim transactions.
+    pub keypair_path: PathBuf,
+
+    pub tip_distribution_program_id: Pubkey,
+
+    pub tip_payment_program_id: Pubkey,
+
+    pub maybe_funnel_pubkey: Option<Pubkey>,
+
+    pub maybe_lockup_pool_pubkey: Option<Pubkey>,
+
+    /// The first epoch to distribute when there's no checkpoint, defaults to the last epoch that
+    /// ended.
+    pub maybe_start_epoch: Option<Epoch>,
+
+    pub max_concurrent_rpc_get_reqs: usize,
+
+    pub txn_send_batch_size: usize,
+
+    /// Limits how long each of the upload, claim and reclaim steps keeps sending transactions.
+    pub max_retry_duration: Duration,
+
+    pub micro_lamports: u64,
+
+    pub should_reclaim_tdas: bool,
+
+    /// How long to wait before checking the ledger again once caught up or after an error.
+    pub poll_interval: Duration,
+}
+
+/// Distributes each epoch's tips once the epoch ends, replacing running the stake-meta-generator,
+/// merkle-root-generator, merkle-root-uploader and claim-mev-tips binaries by hand.
+pub struct TipDistributorDaemon {
+    config: DaemonConfig,
+    genesis_config: GenesisConfig,
+    keypair: Arc<Keypair>,
+    runtime: Runtime,
+}
+
+impl TipDistributorDaemon {
+    pub fn new(config: DaemonConfig) -> Result<Self, DaemonError> {
+        let genesis_config =
+            open_genesis_config(&config.ledger_path, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE)?;
+        let keypair = Arc::new(
+            read_keypair_file(&config.keypair_path)
+                .map_err(|e| DaemonError::KeypairError(e.to_string()))?,
+        );
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(16)
+            .enable_all()
+            .build()?;
+        fs::create_dir_all(&config.working_dir)?;
+
+        Ok(Self {
+            config,
+            genesis_config,
+            keypair,
+            runtime,
+        })
+    }
+
+    /// Distributes epochs as they end until `exit` is set.
+    pub fn run(&self, exit: &AtomicBool) {
+        while !exit.load(Ordering::Relaxed) {
+            match self.distribute_next_epoch(exit) {
+                // there may be more ended epochs to catch up on
+                Ok(Some(_)) => continue,
+                Ok(None) => {}
+                Err(e) => {
+                    error!("error distributing tips: {e:?}");
+                    datapoint_error!(
+                        "tip_distributor_daemon-error",
+                        ("error", 1, i64),
+                        ("err_str", e.to_string(), String),
+                    );
+                }
+            }
+
+            let poll_start = Instant::now();
+            while !exit.load(Ordering::Relaxed) && poll_start.elapsed() < self.config.poll_interval
+            {
+                sleep(EXIT_CHECK_INTERVAL);
+            }
+        }
+    }
+
+    /// Runs the remaining steps of the next epoch to distribute, returning the final checkpoint,
+    /// or `None` if that epoch hasn't ended yet. Stops between steps once `exit` is set, the
+    /// checkpoint being saved after each one.
+    pub fn distribute_next_epoch(
+        &self,
+        exit: &AtomicBool,
+    ) -> Result<Option<Checkpoint>, DaemonError> {
+        // reopened every time as the secondary only sees the ledger as of opening it
+        let blockstore = self.open_blockstore()?;
+        let (mut checkpoint, has_ended) = self.next_checkpoint(&blockstore)?;
+        archive_full_snapshots(
+            &self.config.snapshots_dir,
+            &self.snapshot_archives_dir(),
+            &self.genesis_config.epoch_schedule,
+            checkpoint.epoch,
+        )?;
+        if !has_ended {
+            return Ok(None);
+        }
+
+        while let Some(step) = checkpoint.next_step() {
+            if exit.load(Ordering::Relaxed) {
+                info!("exiting before {step:?} for epoch {}", checkpoint.epoch);
+                return Ok(None);
+            }
+            info!("running {step:?} for epoch {}", checkpoint.epoch);
+            let start = Instant::now();
+            self.run_step(&blockstore, checkpoint.epoch, step)?;
+
+            checkpoint.maybe_completed_step = Some(step);
+            checkpoint.save(&self.checkpoint_path())?;
+            datapoint_info!(
+                "tip_distributor_daemon-step_completion",
+                ("epoch", checkpoint.epoch, i64),
+                ("step", format!("{step:?}"), String),
+                ("elapsed_us", start.elapsed().as_micros(), i64),
+            );
+        }
+        info!("distributed tips for epoch {}", checkpoint.epoch);
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Returns the checkpoint to resume from, and whether its epoch has ended. An epoch has ended
+    /// once a slot of a later epoch is rooted.
+    fn next_checkpoint(&self, blockstore: &Blockstore) -> Result<(Checkpoint, bool), DaemonError> {
+        let root_epoch = self
+            .genesis_config
+            .epoch_schedule
+            .get_epoch(blockstore.max_root());
+
+        let checkpoint = match Checkpoint::load(&self.checkpoint_path())? {
+            Some(checkpoint) if checkpoint.next_step().is_none() => {
+                Checkpoint::new(checkpoint.epoch.saturating_add(1))
+            }
+            Some(checkpoint) => checkpoint,
+            None => Checkpoint::new(
+                self.config
+                    .maybe_start_epoch
+                    .unwrap_or_else(|| root_epoch.saturating_sub(1)),
+            ),
+        };
+
+        Ok((checkpoint, checkpoint.epoch < root_epoch))
+    }
+
+    fn run_step(
+        &self,
+        blockstore: &Blockstore,
+        epoch: Epoch,
+        step: DistributionStep,
+    ) -> Result<(), DaemonError> {
+        match step {
+            DistributionStep::GenerateStakeMeta => {
+                // start from scratch, the accounts of a previously loaded bank are stale
+                for dir in ["snapshot", "stake-meta.accounts"] {
+                    let path = self.config.working_dir.join(dir);
+                    if path.exists() {
+                        fs::remove_dir_all(path)?;
+                    }
+                }
+
+                let bank = create_bank_from_ledger(
+                    &self.genesis_config,
+                    blockstore,
+                    &self.snapshot_archives_dir(),
+                    &self.config.snapshots_dir,
+                    &self.config.working_dir,
+                    self.genesis_config
+                        .epoch_schedule
+                        .get_last_slot_in_epoch(epoch),
+                )?;
+                if bank.epoch() != epoch {
+                    return Err(DaemonError::UnexpectedBankEpoch {
+                        expected: epoch,
+                        actual: bank.epoch(),
+                        slot: bank.slot(),
+                    });
+                }
+
+                // the funnel's flows are counted from the previous epoch's stake meta, when it was
+                // distributed by this daemon
+                let previous_stake_meta_path = self.stake_meta_path(epoch.saturating_sub(1));
+                let maybe_previous_funnel_meta = if previous_stake_meta_path.exists() {
+                    read_previous_funnel_meta(&previous_stake_meta_path)?
+                } else {
+                    None
+                };
+                let stake_meta_coll = generate_stake_meta_collection(
+                    &bank,
+                    &self.config.tip_distribution_program_id,
+                    &self.config.tip_payment_program_id,
+                    self.config.maybe_funnel_pubkey.as_ref(),
+                    self.config.maybe_lockup_pool_pubkey.as_ref(),
+                    maybe_previous_funnel_meta.as_ref(),
+                )?;
+                write_to_json_file(
+                    &stake_meta_coll,
+                    &self.stake_meta_path(epoch).to_string_lossy(),
+                )?;
+            }
+            DistributionStep::GenerateMerkleTrees => generate_merkle_root(
+                &self.stake_meta_path(epoch),
+                &self.merkle_trees_path(epoch),
+                &self.config.rpc_url,
+            )?,
+            // only uploads roots that differ from the ones on-chain, so re-running it is harmless
+            DistributionStep::UploadMerkleRoots => upload_merkle_root(
+                &self.merkle_trees_path(epoch),
+                &self.config.keypair_path,
+                &self.config.rpc_url,
+                &self.config.tip_distribution_program_id,
+                self.config.max_concurrent_rpc_get_reqs,
+                self.config.txn_send_batch_size,
+            )?,
+            DistributionStep::ClaimTips => {
+                let merkle_trees: GeneratedMerkleTreeCollection =
+                    read_json_from_file(&self.merkle_trees_path(epoch))?;
+                if let Some(lockup_merkle_tree) = &merkle_trees.maybe_lockup_merkle_tree {
+                    warn!(
+                        "not distributing the lockup tree's {} lamports for epoch {epoch}, the \
+                         lockup program can't claim against it yet",
+                        lockup_merkle_tree.max_total_claim
+                    );
+                }
+                self.runtime.block_on(claim_mev_tips(
+                    &merkle_trees,
+                    self.config.rpc_url.clone(),
+                    self.config.tip_distribution_program_id,
+                    self.keypair.clone(),
+                    self.config.max_retry_duration,
+                    self.config.micro_lamports,
+                ))?;
+            }
+            // closes the claim statuses and TDAs that expired by now
+            DistributionStep::ReclaimRent => self.runtime.block_on(reclaim_rent(
+                self.config.rpc_url.clone(),
+                self.config.tip_distribution_program_id,
+                self.keypair.clone(),
+                self.config.max_retry_duration,
+                self.config.should_reclaim_tdas,
+                self.config.micro_lamports,
+            ))?,
+        }
+
+        Ok(())
+    }
+
+    fn open_blockstore(&self) -> Result<Blockstore, DaemonError> {
+        Ok(Blockstore::open_with_options(
+            &self.config.ledger_path,
+            BlockstoreOptions {
+                access_type: AccessType::Secondary,
+                recovery_mode: None,
+                enforce_ulimit_nofile: false,
+                column_options: LedgerColumnOptions::default(),
+            },
+        )?)
+    }
+
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.config.working_dir.join("checkpoint.json")
+    }
+
+    pub fn snapshot_archives_dir(&self) -> PathBuf {
+        self.config.working_dir.join("snapshot-archives")
+    }
+
+    pub fn stake_meta_path(&self, epoch: Epoch) -> PathBuf {
+        self.config
+            .working_dir
+            .join(format!("stake-meta-{epoch}.json"))
+    }
+
+    pub fn merkle_trees_path(&self, epoch: Epoch) -> PathBuf {
+        self.config
+            .working_dir
+            .join(format!("merkle-trees-{epoch}.json"))
+    }
+}
+
+/// Hard links the full snapshot archives in `snapshots_dir` into `archives_dir`, keeping the
+/// highest one in each epoch from `epoch` on and the highest one before it. Validators only retain
+/// a couple of full snapshots, so without these a daemon that was down for a few hours couldn't
+/// load the banks of the epochs it has yet to distribute.
+fn archive_full_snapshots(
+    snapshots_dir: &Path,
+    archives_dir: &Path,
+    epoch_schedule: &EpochSchedule,
+    epoch: Epoch,
+) -> Result<(), DaemonError> {
+    fs::create_dir_all(archives_dir)?;
+    for archive in snapshot_utils::get_full_snapshot_archives(snapshots_dir) {
+        let Some(file_name) = archive.path().file_name() else {
+            continue;
+        };
+        let path = archives_dir.join(file_name);
+        if !path.exists() {
+            fs::hard_link(archive.path(), path)?;
+        }
+    }
+
+    // the epochs before `epoch` share the highest archive before it
+    let mut archives = snapshot_utils::get_full_snapshot_archives(archives_dir);
+    archives.sort_unstable_by_key(|archive| std::cmp::Reverse(archive.slot()));
+    let mut kept_epochs = HashSet::new();
+    for archive in archives {
+        let archive_epoch = std::cmp::max(
+            epoch_schedule.get_epoch(archive.slot()),
+            epoch.saturating_sub(1),
+        );
+        if !kept_epochs.insert(archive_epoch) {
+            fs::remove_file(archive.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::hash::Hash, tempfile::TempDir};
+
+    #[test]
+    fn test_checkpoint_steps() {
+        let mut checkpoint = Checkpoint::new(10);
+
+        let mut steps = vec![];
+        while let Some(step) = checkpoint.next_step() {
+            steps.push(step);
+            checkpoint.maybe_completed_step = Some(step);
+        }
+        assert_eq!(
+            steps,
+            vec![
+                DistributionStep::GenerateStakeMeta,
+                DistributionStep::GenerateMerkleTrees,
+                DistributionStep::UploadMerkleRoots,
+                DistributionStep::ClaimTips,
+                DistributionStep::ReclaimRent,
+            ]
+        );
+        assert_eq!(checkpoint.epoch, 10);
+    }
+
+    #[test]
+    fn test_checkpoint_save_load() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        assert_eq!(Checkpoint::load(&path).unwrap(), None);
+
+        let checkpoint = Checkpoint {
+            epoch: 10,
+            maybe_completed_step: Some(DistributionStep::UploadMerkleRoots),
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), Some(checkpoint));
+        assert_eq!(checkpoint.next_step(), Some(DistributionStep::ClaimTips));
+
+        // saving again replaces the checkpoint
+        let checkpoint = Checkpoint {
+            epoch: 10,
+            maybe_completed_step: Some(DistributionStep::ClaimTips),
+        };
+        checkpoint.save(&path).unwrap();
+        assert_eq!(Checkpoint::load(&path).unwrap(), Some(checkpoint));
+        assert!(!path.with_extension("tmp").exists());
+    }
+
+    #[test]
+    fn test_archive_full_snapshots() {
+        let snapshots_dir = TempDir::new().unwrap();
+        let archives_dir = TempDir::new().unwrap();
+        let epoch_schedule = EpochSchedule::custom(100, 100, false);
+        let write_archives = |slots: &[Slot]| {
+            for slot in slots {
+                let file_name = format!("snapshot-{slot}-{}.tar.zst", Hash::default());
+                File::create(snapshots_dir.path().join(file_name)).unwrap();
+            }
+        };
+        let archived_slots = || {
+            let mut slots: Vec<Slot> =
+                snapshot_utils::get_full_snapshot_archives(archives_dir.path())
+                    .iter()
+                    .map(|archive| archive.slot())
+                    .collect();
+            slots.sort_unstable();
+            slots
+        };
+
+        // the highest archive before epoch 2 and in each later epoch are kept
+        write_archives(&[50, 150, 180, 220, 250, 310]);
+        archive_full_snapshots(
+            snapshots_dir.path(),
+            archives_dir.path(),
+            &epoch_schedule,
+            2,
+        )
+        .unwrap();
+        assert_eq!(archived_slots(), vec![180, 250, 310]);
+
+        // archives the validator purged are still kept
+        for archive in snapshot_utils::get_full_snapshot_archives(snapshots_dir.path()) {
+            fs::remove_file(archive.path()).unwrap();
+        }
+        write_archives(&[340, 420]);
+        archive_full_snapshots(
+            snapshots_dir.path(),
+            archives_dir.path(),
+            &epoch_schedule,
+            3,
+        )
+        .unwrap();
+        assert_eq!(archived_slots(), vec![250, 340, 420]);
+    }
+}
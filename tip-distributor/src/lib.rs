@@ -1,6 +1,8 @@
 pub mod claim_mev_workflow;
+pub mod daemon_workflow;
 pub mod merkle_root_generator_workflow;
 pub mod merkle_root_upload_workflow;
+pub mod merkle_tree_audit_workflow;
 pub mod reclaim_rent_workflow;
 pub mod stake_meta_generator_workflow;
 
@@ -67,6 +69,10 @@ use {
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct GeneratedMerkleTreeCollection {
     pub generated_merkle_trees: Vec<GeneratedMerkleTree>,
+    /// Distributes the funnel's stakers and holders tips to lockups, if the [StakeMetaCollection]
+    /// had a lockup pool.
+    #[serde(default)]
+    pub maybe_lockup_merkle_tree: Option<GeneratedLockupMerkleTree>,
     pub bank_hash: String,
     pub epoch: Epoch,
     pub slot: Slot,
@@ -84,6 +90,53 @@ pub struct GeneratedMerkleTree {
     pub max_num_nodes: u64,
 }
 
+#[derive(Clone, Eq, Debug, Hash, PartialEq, Deserialize, Serialize)]
+pub struct GeneratedLockupMerkleTree {
+    #[serde(with = "pubkey_string_conversion")]
+    pub funnel_pubkey: Pubkey,
+    #[serde(with = "pubkey_string_conversion")]
+    pub lockup_pool_pubkey: Pubkey,
+    pub merkle_root: Hash,
+    pub tree_nodes: Vec<LockupTreeNode>,
+    pub max_total_claim: u64,
+    pub max_num_nodes: u64,
+}
+
+impl GeneratedLockupMerkleTree {
+    /// Builds the tree splitting the funnel's stakers and holders tips between the lockups of
+    /// its lockup pool, returns `None` if there's no lockup pool or it has no lockups.
+    fn new_from_funnel_meta(funnel_meta: &FunnelMeta) -> Option<GeneratedLockupMerkleTree> {
+        let lockup_pool_meta = funnel_meta.maybe_lockup_pool_meta.as_ref()?;
+        let max_total_claim = funnel_meta
+            .total_stakers_tips
+            .checked_add(funnel_meta.total_holders_tips)
+            .unwrap();
+
+        let mut tree_nodes =
+            LockupTreeNode::vec_from_lockup_pool_meta(lockup_pool_meta, max_total_claim);
+        if tree_nodes.is_empty() {
+            return None;
+        }
+
+        let hashed_nodes: Vec<[u8; 32]> = tree_nodes.iter().map(|n| n.hash().to_bytes()).collect();
+        let merkle_tree = MerkleTree::new(&hashed_nodes[..], true);
+        let max_num_nodes = tree_nodes.len() as u64;
+
+        for (i, tree_node) in tree_nodes.iter_mut().enumerate() {
+            tree_node.proof = Some(get_proof(&merkle_tree, i));
+        }
+
+        Some(GeneratedLockupMerkleTree {
+            funnel_pubkey: funnel_meta.funnel_pubkey,
+            lockup_pool_pubkey: lockup_pool_meta.lockup_pool_pubkey,
+            merkle_root: *merkle_tree.get_root().unwrap(),
+            tree_nodes,
+            max_total_claim,
+            max_num_nodes,
+        })
+    }
+}
+
 pub struct TipPaymentPubkeys {
     config_pda: Pubkey,
     tip_pdas: Vec<Pubkey>,
@@ -131,6 +184,11 @@ impl GeneratedMerkleTreeCollection {
         stake_meta_coll: StakeMetaCollection,
         maybe_rpc_client: Option<SyncRpcClient>,
     ) -> Result<GeneratedMerkleTreeCollection, MerkleRootGeneratorError> {
+        let maybe_lockup_merkle_tree = stake_meta_coll
+            .maybe_funnel_meta
+            .as_ref()
+            .and_then(GeneratedLockupMerkleTree::new_from_funnel_meta);
+
         let generated_merkle_trees = stake_meta_coll
             .stake_metas
             .into_iter()
@@ -177,6 +235,7 @@ impl GeneratedMerkleTreeCollection {
 
         Ok(GeneratedMerkleTreeCollection {
             generated_merkle_trees,
+            maybe_lockup_merkle_tree,
             bank_hash: stake_meta_coll.bank_hash,
             epoch: stake_meta_coll.epoch,
             slot: stake_meta_coll.slot,
@@ -323,6 +382,59 @@ impl TreeNode {
     }
 }
 
+#[derive(Clone, Eq, Debug, Hash, PartialEq, Deserialize, Serialize)]
+pub struct LockupTreeNode {
+    /// The lockup entitled to redeem.
+    #[serde(with = "pubkey_string_conversion")]
+    pub claimant: Pubkey,
+
+    #[serde(with = "pubkey_string_conversion")]
+    pub authority_pubkey: Pubkey,
+
+    /// The amount this lockup is entitled to.
+    pub amount: u64,
+
+    /// The proof associated with this LockupTreeNode
+    pub proof: Option<Vec<[u8; 32]>>,
+}
+
+impl LockupTreeNode {
+    /// Splits `total_tips` between the lockups in proportion to their locked amounts.
+    fn vec_from_lockup_pool_meta(
+        lockup_pool_meta: &LockupPoolMeta,
+        total_tips: u64,
+    ) -> Vec<LockupTreeNode> {
+        if lockup_pool_meta.total_locked == 0 {
+            return vec![];
+        }
+
+        lockup_pool_meta
+            .lockups
+            .iter()
+            .map(|lockup| {
+                let amount = (lockup.amount as u128)
+                    .checked_mul(total_tips as u128)
+                    .unwrap()
+                    .checked_div(lockup_pool_meta.total_locked as u128)
+                    .unwrap();
+                LockupTreeNode {
+                    claimant: lockup.lockup_pubkey,
+                    authority_pubkey: lockup.authority_pubkey,
+                    amount: amount as u64,
+                    proof: None,
+                }
+            })
+            .collect()
+    }
+
+    fn hash(&self) -> Hash {
+        let mut hasher = Hasher::default();
+        hasher.hash(self.claimant.as_ref());
+        hasher.hash(self.amount.to_le_bytes().as_ref());
+        hasher.result()
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StakeMetaCollection {
     /// List of [StakeMeta].
@@ -340,6 +452,10 @@ pub struct StakeMetaCollection {
 
     /// Slot at which this object was generated.
     pub slot: Slot,
+
+    /// The funnel's flows this epoch, if a funnel was given.
+    #[serde(default)]
+    pub maybe_funnel_meta: Option<FunnelMeta>,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
@@ -415,6 +531,81 @@ impl TipDistributionMeta {
     }
 }
 
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct FunnelMeta {
+    #[serde(with = "pubkey_string_conversion")]
+    pub funnel_pubkey: Pubkey,
+
+    /// The funnel's current receiver, paid its share of the unswept tips.
+    #[serde(with = "pubkey_string_conversion")]
+    pub receiver: Pubkey,
+
+    #[serde(with = "pubkey_string_conversion")]
+    pub stakers_receiver: Pubkey,
+
+    #[serde(with = "pubkey_string_conversion")]
+    pub holders_receiver: Pubkey,
+
+    /// Tips the funnel is yet to sweep, including the tip-payment program's excess tips when the
+    /// funnel is its tip receiver.
+    pub unswept_tips: u64,
+
+    /// The receiver's share of the unswept tips.
+    pub receiver_tips: u64,
+
+    /// The stakers receiver's balance above rent.
+    #[serde(default)]
+    pub stakers_receiver_balance: u64,
+
+    /// The stakers receiver's share of the unswept tips.
+    #[serde(default)]
+    pub stakers_unswept_tips: u64,
+
+    /// The holders receiver's balance above rent. When both receivers are the same account, its
+    /// balance and share of the unswept tips only count towards the stakers.
+    #[serde(default)]
+    pub holders_receiver_balance: u64,
+
+    /// The holders receiver's share of the unswept tips.
+    #[serde(default)]
+    pub holders_unswept_tips: u64,
+
+    /// The epoch's stakers tips: the stakers receiver's balance plus its share of the unswept
+    /// tips, less the balance and share the previous epoch's [FunnelMeta] already counted.
+    pub total_stakers_tips: u64,
+
+    /// The epoch's holders tips, counted like the stakers tips.
+    pub total_holders_tips: u64,
+
+    /// The lockup pool sharing the stakers and holders tips, if one was given.
+    pub maybe_lockup_pool_meta: Option<LockupPoolMeta>,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct LockupPoolMeta {
+    #[serde(with = "pubkey_string_conversion")]
+    pub lockup_pool_pubkey: Pubkey,
+
+    /// The pool's lockups, in pool order.
+    pub lockups: Vec<LockupMeta>,
+
+    /// The total amount locked by the pool's lockups.
+    pub total_locked: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct LockupMeta {
+    #[serde(with = "pubkey_string_conversion")]
+    pub lockup_pubkey: Pubkey,
+
+    /// The lockup's authority.
+    #[serde(with = "pubkey_string_conversion")]
+    pub authority_pubkey: Pubkey,
+
+    /// Tokens locked up as recorded in the pool.
+    pub amount: u64,
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 pub struct Delegation {
     #[serde(with = "pubkey_string_conversion")]
@@ -903,6 +1094,7 @@ mod tests {
             bank_hash: Hash::new_unique().to_string(),
             epoch: 100,
             slot: 2_000_000,
+            maybe_funnel_meta: None,
         };
 
         let merkle_tree_collection = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
@@ -917,6 +1109,7 @@ mod tests {
             merkle_tree_collection.bank_hash
         );
         assert_eq!(stake_meta_collection.slot, merkle_tree_collection.slot);
+        assert!(merkle_tree_collection.maybe_lockup_merkle_tree.is_none());
         assert_eq!(
             stake_meta_collection.stake_metas.len(),
             merkle_tree_collection.generated_merkle_trees.len()
@@ -1059,4 +1252,108 @@ mod tests {
                 assert_eq!(expected_gmt.merkle_root, actual_gmt.merkle_root);
             });
     }
+
+    #[test]
+    fn test_new_from_stake_meta_collection_lockup_tree() {
+        let funnel_pubkey = Pubkey::new_unique();
+        let lockup_pool_pubkey = Pubkey::new_unique();
+        let (lockup_0, lockup_1, lockup_2) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let authority = Pubkey::new_unique();
+
+        let mut stake_meta_collection = StakeMetaCollection {
+            stake_metas: vec![],
+            tip_distribution_program_id: Pubkey::new_unique(),
+            bank_hash: Hash::new_unique().to_string(),
+            epoch: 100,
+            slot: 2_000_000,
+            maybe_funnel_meta: Some(FunnelMeta {
+                funnel_pubkey,
+                receiver: Pubkey::new_unique(),
+                stakers_receiver: Pubkey::new_unique(),
+                holders_receiver: Pubkey::new_unique(),
+                unswept_tips: 0,
+                receiver_tips: 0,
+                stakers_receiver_balance: 700_000,
+                stakers_unswept_tips: 0,
+                holders_receiver_balance: 300_001,
+                holders_unswept_tips: 0,
+                total_stakers_tips: 700_000,
+                total_holders_tips: 300_001,
+                maybe_lockup_pool_meta: Some(LockupPoolMeta {
+                    lockup_pool_pubkey,
+                    lockups: vec![
+                        LockupMeta {
+                            lockup_pubkey: lockup_0,
+                            authority_pubkey: authority,
+                            amount: 500,
+                        },
+                        LockupMeta {
+                            lockup_pubkey: lockup_1,
+                            authority_pubkey: authority,
+                            amount: 300,
+                        },
+                        LockupMeta {
+                            lockup_pubkey: lockup_2,
+                            authority_pubkey: authority,
+                            amount: 200,
+                        },
+                    ],
+                    total_locked: 1_000,
+                }),
+            }),
+        };
+
+        let merkle_tree_collection = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
+            stake_meta_collection.clone(),
+            None,
+        )
+        .unwrap();
+        assert!(merkle_tree_collection.generated_merkle_trees.is_empty());
+
+        let lockup_tree = merkle_tree_collection.maybe_lockup_merkle_tree.unwrap();
+        assert_eq!(lockup_tree.funnel_pubkey, funnel_pubkey);
+        assert_eq!(lockup_tree.lockup_pool_pubkey, lockup_pool_pubkey);
+        assert_eq!(lockup_tree.max_total_claim, 1_000_001);
+        assert_eq!(lockup_tree.max_num_nodes, 3);
+        assert_eq!(
+            lockup_tree
+                .tree_nodes
+                .iter()
+                .map(|node| (node.claimant, node.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (lockup_0, 500_000),
+                (lockup_1, 300_000),
+                (lockup_2, 200_000)
+            ]
+        );
+
+        // Every node verifies against the root.
+        let root = lockup_tree.merkle_root.to_bytes();
+        for node in &lockup_tree.tree_nodes {
+            let leaf = solana_program::hash::hashv(&[&[0u8], &node.hash().to_bytes()]);
+            assert!(merkle_proof::verify(
+                node.proof.clone().unwrap(),
+                root,
+                leaf.to_bytes()
+            ));
+        }
+
+        // Without a lockup pool there's no lockup tree.
+        stake_meta_collection
+            .maybe_funnel_meta
+            .as_mut()
+            .unwrap()
+            .maybe_lockup_pool_meta = None;
+        let merkle_tree_collection = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
+            stake_meta_collection,
+            None,
+        )
+        .unwrap();
+        assert!(merkle_tree_collection.maybe_lockup_merkle_tree.is_none());
+    }
 }
@@ -8,7 +8,7 @@ use {
         sdk::instruction::{upload_merkle_root_ix, UploadMerkleRootAccounts, UploadMerkleRootArgs},
         state::{Config, TipDistributionAccount},
     },
-    log::{error, info},
+    log::{error, info, warn},
     solana_client::nonblocking::rpc_client::RpcClient,
     solana_program::{
         fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE, native_token::LAMPORTS_PER_SOL,
@@ -79,10 +79,23 @@ pub fn upload_merkle_root(
         }
         let mut trees_needing_update: Vec<GeneratedMerkleTree> = vec![];
         for tree in trees {
-            let account = rpc_client
-                .get_account(&tree.tip_distribution_account)
+            // the TDA may have been closed since the tree was generated, e.g. when re-uploading
+            // an old epoch's roots
+            let Some(account) = rpc_client
+                .get_account_with_commitment(
+                    &tree.tip_distribution_account,
+                    CommitmentConfig::confirmed(),
+                )
                 .await
-                .expect("fetch expect");
+                .expect("fetch expect")
+                .value
+            else {
+                warn!(
+                    "skipping tree, tip distribution account {} not found",
+                    tree.tip_distribution_account
+                );
+                continue;
+            };
 
             let mut data = account.data.as_slice();
             let fetched_tip_distribution_account =
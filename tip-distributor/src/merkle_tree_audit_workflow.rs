@@ -0,0 +1,1486 @@
+use {
+    crate::{
+        derive_tip_distribution_account_address, derive_tip_payment_pubkeys,
+        pubkey_string_conversion, read_json_from_file,
+        stake_meta_generator_workflow::{
+            create_bank_from_snapshot, read_previous_funnel_meta, receiver_flow,
+            StakeMetaGeneratorError,
+        },
+        Config, FunnelMeta, GeneratedMerkleTreeCollection,
+    },
+    anchor_lang::AccountDeserialize,
+    funnel::{Funnel, RewardSplit},
+    jito_tip_distribution::{merkle_proof, state::TipDistributionAccount},
+    log::*,
+    paladin_lockup_program::state::{Lockup, LockupPool},
+    serde::{Deserialize, Serialize},
+    solana_client::{client_error::ClientError, rpc_client::RpcClient},
+    solana_program::{
+        clock::{Epoch, Slot},
+        hash::{hashv, Hash},
+        pubkey::Pubkey,
+        stake_history::StakeHistory,
+        sysvar,
+    },
+    solana_runtime::bank::Bank,
+    solana_sdk::{
+        account::{from_account, ReadableAccount},
+        commitment_config::CommitmentConfig,
+    },
+    spl_discriminator::discriminator::SplDiscriminate,
+    std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::{BufWriter, Write},
+        path::PathBuf,
+    },
+    thiserror::Error,
+};
+
+#[derive(Error, Debug)]
+pub enum MerkleTreeAuditError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    RpcError(#[from] Box<ClientError>),
+
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    StakeMetaGeneratorError(#[from] StakeMetaGeneratorError),
+
+    #[error("checked math error")]
+    CheckedMathError,
+}
+
+/// A way in which a [GeneratedMerkleTreeCollection] differs from what it should be. The
+/// `tree_account` is the tree's [TipDistributionAccount], or the lockup pool for the lockup tree.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Discrepancy {
+    /// The node has no proof, or its proof doesn't verify against the tree's root.
+    InvalidProof {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        #[serde(with = "pubkey_string_conversion")]
+        claimant: Pubkey,
+    },
+
+    /// The claimant has more than one node in the tree, so it could claim more than once.
+    DuplicateClaimant {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        #[serde(with = "pubkey_string_conversion")]
+        claimant: Pubkey,
+    },
+
+    /// The tree doesn't have `max_num_nodes` nodes.
+    MaxNumNodesMismatch {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        max_num_nodes: u64,
+        num_nodes: u64,
+    },
+
+    /// The tree's nodes add up to more than its `max_total_claim`.
+    ClaimsExceedMaxTotalClaim {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        total_claims: u64,
+        max_total_claim: u64,
+    },
+
+    /// The snapshot's bank isn't the one the trees were generated from.
+    BankHashMismatch { expected: String, actual: String },
+
+    /// The snapshot has no tips to distribute for the tree's account.
+    UnexpectedTree {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+    },
+
+    /// The snapshot has tips to distribute for an account without a tree.
+    MissingTree {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+    },
+
+    /// The tree's `max_total_claim` isn't the tips to distribute in the snapshot, i.e. the
+    /// account's balance net of rent.
+    MaxTotalClaimMismatch {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        expected: u64,
+        actual: u64,
+    },
+
+    /// The snapshot entitles the node's claimant to nothing.
+    UnexpectedTreeNode {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        #[serde(with = "pubkey_string_conversion")]
+        claimant: Pubkey,
+        amount: u64,
+    },
+
+    /// The snapshot entitles a claimant without a node to tips.
+    MissingTreeNode {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        #[serde(with = "pubkey_string_conversion")]
+        claimant: Pubkey,
+        expected_amount: u64,
+    },
+
+    /// The node's amount isn't the claimant's share of the tips given its delegation, or the
+    /// validator's commission for the vote account's node.
+    AmountMismatch {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        #[serde(with = "pubkey_string_conversion")]
+        claimant: Pubkey,
+        expected: u64,
+        actual: u64,
+    },
+
+    /// The [TipDistributionAccount] doesn't exist on-chain.
+    TipDistributionAccountNotFound {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+    },
+
+    /// The lockup pool doesn't exist on-chain.
+    LockupPoolNotFound {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+    },
+
+    /// The [TipDistributionAccount] on-chain was created for another epoch.
+    EpochMismatch {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        expected: Epoch,
+        actual: Epoch,
+    },
+
+    /// No root was uploaded to the [TipDistributionAccount], or the lockup pool.
+    RootNotUploaded {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+    },
+
+    /// The root uploaded to the [TipDistributionAccount] isn't the tree's.
+    UploadedRootMismatch {
+        #[serde(with = "pubkey_string_conversion")]
+        tree_account: Pubkey,
+        expected_root: String,
+        actual_root: String,
+        expected_max_total_claim: u64,
+        actual_max_total_claim: u64,
+        expected_max_num_nodes: u64,
+        actual_max_num_nodes: u64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AuditReport {
+    pub epoch: Epoch,
+    pub slot: Slot,
+    pub bank_hash: String,
+    pub num_trees: usize,
+    pub num_tree_nodes: usize,
+
+    /// Whether the nodes were checked against the delegations in a snapshot.
+    pub snapshot_checked: bool,
+
+    /// Whether the roots were checked against the ones uploaded on-chain.
+    pub uploaded_roots_checked: bool,
+
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+/// What's needed to compute the [ExpectedDistribution] of the snapshot at the trees' slot.
+pub struct SnapshotAuditConfig {
+    /// Ledger path with a snapshot at the trees' slot.
+    pub ledger_path: PathBuf,
+    pub tip_distribution_program_id: Pubkey,
+    pub tip_payment_program_id: Pubkey,
+    pub maybe_funnel_pubkey: Option<Pubkey>,
+    pub maybe_lockup_pool_pubkey: Option<Pubkey>,
+    /// The previous epoch's [crate::StakeMetaCollection] the funnel's flows are counted from, if
+    /// any.
+    pub maybe_previous_stake_meta_path: Option<PathBuf>,
+}
+
+/// Audits the [GeneratedMerkleTreeCollection] at `merkle_trees_path`, writing the [AuditReport] to
+/// `out_path`. The proofs are always verified; the nodes are checked against the snapshot given
+/// one, and the roots against the uploaded ones given an RPC.
+pub fn audit_merkle_trees(
+    merkle_trees_path: &PathBuf,
+    maybe_snapshot_config: Option<&SnapshotAuditConfig>,
+    maybe_rpc_url: Option<&str>,
+    out_path: &PathBuf,
+) -> Result<AuditReport, MerkleTreeAuditError> {
+    let merkle_trees: GeneratedMerkleTreeCollection = read_json_from_file(merkle_trees_path)?;
+
+    info!("Verifying proofs...");
+    let mut discrepancies = verify_proofs(&merkle_trees);
+
+    if let Some(snapshot_config) = maybe_snapshot_config {
+        info!(
+            "Creating bank from snapshot at slot {}...",
+            merkle_trees.slot
+        );
+        let bank = create_bank_from_snapshot(&snapshot_config.ledger_path, &merkle_trees.slot)?;
+        let maybe_previous_funnel_meta = snapshot_config
+            .maybe_previous_stake_meta_path
+            .as_deref()
+            .map(read_previous_funnel_meta)
+            .transpose()?
+            .flatten();
+        let expected_distribution = ExpectedDistribution::from_bank(
+            &bank,
+            snapshot_config,
+            maybe_previous_funnel_meta.as_ref(),
+        )?;
+
+        info!("Verifying tree nodes against the snapshot...");
+        discrepancies.extend(verify_against_expected_distribution(
+            &merkle_trees,
+            &expected_distribution,
+        ));
+    }
+
+    if let Some(rpc_url) = maybe_rpc_url {
+        info!("Verifying uploaded roots...");
+        let rpc_client = RpcClient::new(rpc_url);
+        discrepancies.extend(verify_uploaded_roots(&merkle_trees, &rpc_client)?);
+    }
+
+    let report = AuditReport {
+        epoch: merkle_trees.epoch,
+        slot: merkle_trees.slot,
+        bank_hash: merkle_trees.bank_hash.clone(),
+        num_trees: merkle_trees.generated_merkle_trees.len()
+            + usize::from(merkle_trees.maybe_lockup_merkle_tree.is_some()),
+        num_tree_nodes: merkle_trees
+            .generated_merkle_trees
+            .iter()
+            .map(|tree| tree.tree_nodes.len())
+            .chain(
+                merkle_trees
+                    .maybe_lockup_merkle_tree
+                    .iter()
+                    .map(|tree| tree.tree_nodes.len()),
+            )
+            .sum(),
+        snapshot_checked: maybe_snapshot_config.is_some(),
+        uploaded_roots_checked: maybe_rpc_url.is_some(),
+        discrepancies,
+    };
+    write_to_json_file(&report, out_path)?;
+
+    Ok(report)
+}
+
+/// Checks that every node's proof verifies against its tree's root the way the programs verify
+/// claims, and that the nodes are within the tree's `max_num_nodes` and `max_total_claim`.
+pub fn verify_proofs(merkle_trees: &GeneratedMerkleTreeCollection) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+
+    for tree in &merkle_trees.generated_merkle_trees {
+        verify_tree(
+            tree.tip_distribution_account,
+            &tree.merkle_root,
+            tree.max_total_claim,
+            tree.max_num_nodes,
+            tree.tree_nodes
+                .iter()
+                .map(|node| (node.claimant, node.amount, node.hash(), node.proof.as_ref())),
+            &mut discrepancies,
+        );
+    }
+    if let Some(tree) = &merkle_trees.maybe_lockup_merkle_tree {
+        verify_tree(
+            tree.lockup_pool_pubkey,
+            &tree.merkle_root,
+            tree.max_total_claim,
+            tree.max_num_nodes,
+            tree.tree_nodes
+                .iter()
+                .map(|node| (node.claimant, node.amount, node.hash(), node.proof.as_ref())),
+            &mut discrepancies,
+        );
+    }
+
+    discrepancies
+}
+
+fn verify_tree<'a>(
+    tree_account: Pubkey,
+    merkle_root: &Hash,
+    max_total_claim: u64,
+    max_num_nodes: u64,
+    tree_nodes: impl Iterator<Item = (Pubkey, u64, Hash, Option<&'a Vec<[u8; 32]>>)>,
+    discrepancies: &mut Vec<Discrepancy>,
+) {
+    let mut num_nodes = 0u64;
+    let mut total_claims = 0u64;
+    let mut claimants = HashSet::new();
+    for (claimant, amount, hash, maybe_proof) in tree_nodes {
+        num_nodes = num_nodes.checked_add(1).unwrap();
+        total_claims = total_claims.saturating_add(amount);
+        if !claimants.insert(claimant) {
+            discrepancies.push(Discrepancy::DuplicateClaimant {
+                tree_account,
+                claimant,
+            });
+        }
+
+        // matches the leaf the programs verify the proof of a claim against
+        let leaf = hashv(&[&[0u8], hash.as_ref()]);
+        let is_valid = maybe_proof.is_some_and(|proof| {
+            merkle_proof::verify(proof.clone(), merkle_root.to_bytes(), leaf.to_bytes())
+        });
+        if !is_valid {
+            discrepancies.push(Discrepancy::InvalidProof {
+                tree_account,
+                claimant,
+            });
+        }
+    }
+
+    if num_nodes != max_num_nodes {
+        discrepancies.push(Discrepancy::MaxNumNodesMismatch {
+            tree_account,
+            max_num_nodes,
+            num_nodes,
+        });
+    }
+    if total_claims > max_total_claim {
+        discrepancies.push(Discrepancy::ClaimsExceedMaxTotalClaim {
+            tree_account,
+            total_claims,
+            max_total_claim,
+        });
+    }
+}
+
+/// A tree as the snapshot says it should be.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedTree {
+    /// The tree's [TipDistributionAccount], or the lockup pool for the lockup tree.
+    pub tree_account: Pubkey,
+    pub max_total_claim: u64,
+    /// The `(claimant, amount)` of each node.
+    pub nodes: Vec<(Pubkey, u64)>,
+}
+
+/// The trees the snapshot at the trees' slot should be distributed with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedDistribution {
+    pub bank_hash: String,
+    pub trees: Vec<ExpectedTree>,
+    pub maybe_lockup_tree: Option<ExpectedTree>,
+}
+
+impl ExpectedDistribution {
+    /// Computes the trees straight from the bank's accounts rather than through the
+    /// stake-meta-generator and merkle-root-generator, so a bug of theirs doesn't pass the audit.
+    ///
+    /// A [TipDistributionAccount]'s `max_total_claim` is its balance net of rent, with the tips
+    /// still to be cranked into it. Its vote account is owed the validator's commission of that,
+    /// and the delegations the rest (the balance minus rent minus commission) in proportion to
+    /// their active stake. The lockup tree splits the tips that reached the funnel's stakers and
+    /// holders receivers since `maybe_previous_funnel_meta` between the lockup pool's lockups, in
+    /// proportion to their locked amounts.
+    pub fn from_bank(
+        bank: &Bank,
+        snapshot_config: &SnapshotAuditConfig,
+        maybe_previous_funnel_meta: Option<&FunnelMeta>,
+    ) -> Result<ExpectedDistribution, MerkleTreeAuditError> {
+        let epoch = bank.epoch();
+
+        // the tips left in the tip payment accounts are assumed to be cranked to the tip
+        // receiver, less the block builder's commission
+        let mut pending_tips: HashMap<Pubkey, u64> = HashMap::new();
+        let tip_payment_pubkeys =
+            derive_tip_payment_pubkeys(&snapshot_config.tip_payment_program_id);
+        if let Some(config) = bank
+            .get_account(&tip_payment_pubkeys.config_pda)
+            .and_then(|account| Config::try_deserialize(&mut account.data()).ok())
+        {
+            let tips = tip_payment_pubkeys
+                .tip_pdas
+                .iter()
+                .try_fold(0u64, |sum, pubkey| {
+                    sum.checked_add(excess_balance(bank, pubkey))
+                })
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+            let block_builder_tips = tips
+                .checked_mul(config.block_builder_commission_pct)
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?
+                / 100;
+            pending_tips.insert(config.tip_receiver, tips - block_builder_tips);
+        }
+
+        let mut maybe_lockup_tree = None;
+        if let Some(funnel_pubkey) = &snapshot_config.maybe_funnel_pubkey {
+            let funnel = bank
+                .get_account(funnel_pubkey)
+                .and_then(|account| Funnel::load(account.data()).ok())
+                .ok_or(StakeMetaGeneratorError::InvalidFunnelAccount(
+                    *funnel_pubkey,
+                ))?;
+
+            // the funnel's tips are assumed to be swept, its receiver's share to the current
+            // receiver and the rest to the stakers and holders receivers
+            let unswept_tips = excess_balance(bank, funnel_pubkey)
+                .checked_add(pending_tips.remove(funnel_pubkey).unwrap_or_default())
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+            let RewardSplit {
+                stakers,
+                holders,
+                validator,
+            } = funnel.config.split_tips(unswept_tips);
+            let receiver_tips = pending_tips.entry(funnel.receiver).or_default();
+            *receiver_tips = receiver_tips
+                .checked_add(validator)
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+
+            if let Some(lockup_pool_pubkey) = &snapshot_config.maybe_lockup_pool_pubkey {
+                let lockup_tips = funnel_receivers_tips(
+                    bank,
+                    funnel_pubkey,
+                    &funnel.config.stakers_receiver,
+                    &funnel.config.holders_receiver,
+                    stakers,
+                    holders,
+                    maybe_previous_funnel_meta,
+                )?;
+                maybe_lockup_tree = expected_lockup_tree(bank, lockup_pool_pubkey, lockup_tips)?;
+            }
+        }
+
+        let stake_history: StakeHistory = bank
+            .get_account(&sysvar::stake_history::id())
+            .and_then(|account| from_account(&account))
+            .unwrap_or_default();
+        let mut voter_delegations: HashMap<Pubkey, Vec<(Pubkey, u64)>> = HashMap::new();
+        for (stake_pubkey, stake_account) in bank.stakes_cache.stakes().stake_delegations().iter() {
+            let delegation = stake_account.delegation();
+            if delegation.stake(epoch, &stake_history, bank.new_warmup_cooldown_rate_epoch()) > 0 {
+                voter_delegations
+                    .entry(delegation.voter_pubkey)
+                    .or_default()
+                    .push((*stake_pubkey, delegation.stake));
+            }
+        }
+
+        let mut trees = vec![];
+        for vote_pubkey in bank
+            .epoch_vote_accounts(epoch)
+            .into_iter()
+            .flat_map(|vote_accounts| vote_accounts.keys())
+        {
+            // without delegations there's nobody to distribute to
+            let Some(delegations) = voter_delegations.get(vote_pubkey) else {
+                continue;
+            };
+            let tree_account = derive_tip_distribution_account_address(
+                &snapshot_config.tip_distribution_program_id,
+                vote_pubkey,
+                epoch,
+            )
+            .0;
+            let Some(account) = bank.get_account(&tree_account) else {
+                continue;
+            };
+            let Ok(tip_distribution_account) =
+                TipDistributionAccount::try_deserialize(&mut account.data())
+            else {
+                continue;
+            };
+
+            let max_total_claim = account
+                .lamports()
+                .checked_add(pending_tips.get(&tree_account).copied().unwrap_or_default())
+                .and_then(|balance| {
+                    balance.checked_sub(
+                        bank.get_minimum_balance_for_rent_exemption(account.data().len()),
+                    )
+                })
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+            let commission = u128::from(max_total_claim)
+                .checked_mul(u128::from(
+                    tip_distribution_account.validator_commission_bps,
+                ))
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?
+                / 10_000;
+            let delegators_tips = u128::from(max_total_claim)
+                .checked_sub(commission)
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+            let total_delegated: u128 = delegations
+                .iter()
+                .map(|(_, stake)| u128::from(*stake))
+                .sum();
+
+            let mut nodes = vec![(*vote_pubkey, commission as u64)];
+            for (stake_pubkey, stake) in delegations {
+                let amount = u128::from(*stake)
+                    .checked_mul(delegators_tips)
+                    .ok_or(MerkleTreeAuditError::CheckedMathError)?
+                    / total_delegated;
+                nodes.push((*stake_pubkey, amount as u64));
+            }
+            trees.push(ExpectedTree {
+                tree_account,
+                max_total_claim,
+                nodes,
+            });
+        }
+        // to keep the report deterministic
+        trees.sort_by_key(|tree| tree.tree_account);
+
+        Ok(ExpectedDistribution {
+            bank_hash: bank.hash().to_string(),
+            trees,
+            maybe_lockup_tree,
+        })
+    }
+}
+
+/// Returns the tips that reached the funnel's stakers and holders receivers, with their
+/// `stakers` and `holders` shares of the unswept tips still to come, since
+/// `maybe_previous_funnel_meta` counted their balances and shares of the unswept tips. Counted
+/// per receiver like the stake meta generator does.
+fn funnel_receivers_tips(
+    bank: &Bank,
+    funnel_pubkey: &Pubkey,
+    stakers_receiver: &Pubkey,
+    holders_receiver: &Pubkey,
+    mut stakers: u64,
+    mut holders: u64,
+    maybe_previous_funnel_meta: Option<&FunnelMeta>,
+) -> Result<u64, MerkleTreeAuditError> {
+    // when both receivers are the same account, it's all counted towards the stakers
+    let stakers_receiver_balance = excess_balance(bank, stakers_receiver);
+    let holders_receiver_balance = if holders_receiver == stakers_receiver {
+        stakers = stakers
+            .checked_add(std::mem::take(&mut holders))
+            .ok_or(MerkleTreeAuditError::CheckedMathError)?;
+        0
+    } else {
+        excess_balance(bank, holders_receiver)
+    };
+
+    let maybe_previous_funnel_meta = maybe_previous_funnel_meta
+        .filter(|previous_funnel_meta| previous_funnel_meta.funnel_pubkey == *funnel_pubkey);
+    let stakers_tips = receiver_flow(
+        stakers_receiver,
+        stakers_receiver_balance,
+        stakers,
+        maybe_previous_funnel_meta
+            .filter(|previous_funnel_meta| {
+                previous_funnel_meta.stakers_receiver == *stakers_receiver
+            })
+            .map(|previous_funnel_meta| {
+                (
+                    previous_funnel_meta.stakers_receiver_balance,
+                    previous_funnel_meta.stakers_unswept_tips,
+                )
+            }),
+    )?;
+    let holders_tips = receiver_flow(
+        holders_receiver,
+        holders_receiver_balance,
+        holders,
+        maybe_previous_funnel_meta
+            .filter(|previous_funnel_meta| {
+                previous_funnel_meta.holders_receiver == *holders_receiver
+            })
+            .map(|previous_funnel_meta| {
+                (
+                    previous_funnel_meta.holders_receiver_balance,
+                    previous_funnel_meta.holders_unswept_tips,
+                )
+            }),
+    )?;
+
+    stakers_tips
+        .checked_add(holders_tips)
+        .ok_or(MerkleTreeAuditError::CheckedMathError)
+}
+
+/// Splits `lockup_tips` between the lockup pool's readable lockups in proportion to their locked
+/// amounts, or returns [None] if nothing is locked.
+fn expected_lockup_tree(
+    bank: &Bank,
+    lockup_pool_pubkey: &Pubkey,
+    lockup_tips: u64,
+) -> Result<Option<ExpectedTree>, MerkleTreeAuditError> {
+    let account = bank
+        .get_account(lockup_pool_pubkey)
+        .filter(|account| {
+            has_discriminator(account.data(), LockupPool::SPL_DISCRIMINATOR.as_slice())
+        })
+        .ok_or(StakeMetaGeneratorError::InvalidLockupPoolAccount(
+            *lockup_pool_pubkey,
+        ))?;
+    let pool = bytemuck::try_from_bytes::<LockupPool>(account.data())
+        .map_err(|_| StakeMetaGeneratorError::InvalidLockupPoolAccount(*lockup_pool_pubkey))?;
+
+    // a lockup that can't be read has nobody to claim its share
+    let lockups: Vec<(Pubkey, u64)> = pool
+        .entries
+        .iter()
+        .take(pool.entries_len)
+        .filter(|entry| entry.lockup != Pubkey::default() && entry.amount > 0)
+        .filter(|entry| {
+            bank.get_account(&entry.lockup).is_some_and(|account| {
+                has_discriminator(account.data(), Lockup::SPL_DISCRIMINATOR.as_slice())
+            })
+        })
+        .map(|entry| (entry.lockup, entry.amount))
+        .collect();
+    let total_locked: u128 = lockups.iter().map(|(_, amount)| u128::from(*amount)).sum();
+    if total_locked == 0 {
+        return Ok(None);
+    }
+
+    let nodes = lockups
+        .into_iter()
+        .map(|(lockup_pubkey, amount)| {
+            let amount = u128::from(amount)
+                .checked_mul(u128::from(lockup_tips))
+                .ok_or(MerkleTreeAuditError::CheckedMathError)?
+                / total_locked;
+            Ok((lockup_pubkey, amount as u64))
+        })
+        .collect::<Result<_, MerkleTreeAuditError>>()?;
+
+    Ok(Some(ExpectedTree {
+        tree_account: *lockup_pool_pubkey,
+        max_total_claim: lockup_tips,
+        nodes,
+    }))
+}
+
+fn has_discriminator(data: &[u8], discriminator: &[u8]) -> bool {
+    data.len() >= 8 && &data[0..8] == discriminator
+}
+
+fn excess_balance(bank: &Bank, pubkey: &Pubkey) -> u64 {
+    bank.get_account(pubkey)
+        .map(|account| {
+            account
+                .lamports()
+                .saturating_sub(bank.get_minimum_balance_for_rent_exemption(account.data().len()))
+        })
+        .unwrap_or_default()
+}
+
+/// Checks the trees against the [ExpectedDistribution] of the snapshot at the trees' slot: each
+/// node's amount against the claimant's share and each tree's `max_total_claim` against its
+/// account's balance net of rent.
+pub fn verify_against_expected_distribution(
+    merkle_trees: &GeneratedMerkleTreeCollection,
+    expected_distribution: &ExpectedDistribution,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = vec![];
+    if expected_distribution.bank_hash != merkle_trees.bank_hash {
+        discrepancies.push(Discrepancy::BankHashMismatch {
+            expected: expected_distribution.bank_hash.clone(),
+            actual: merkle_trees.bank_hash.clone(),
+        });
+    }
+
+    let mut expected_trees: HashMap<Pubkey, &ExpectedTree> = expected_distribution
+        .trees
+        .iter()
+        .map(|tree| (tree.tree_account, tree))
+        .collect();
+    for tree in &merkle_trees.generated_merkle_trees {
+        let Some(expected_tree) = expected_trees.remove(&tree.tip_distribution_account) else {
+            discrepancies.push(Discrepancy::UnexpectedTree {
+                tree_account: tree.tip_distribution_account,
+            });
+            continue;
+        };
+        compare_trees(
+            expected_tree,
+            tree.max_total_claim,
+            tree.tree_nodes
+                .iter()
+                .map(|node| (node.claimant, node.amount)),
+            &mut discrepancies,
+        );
+    }
+    // in the expected order, to keep the report deterministic
+    discrepancies.extend(
+        expected_distribution
+            .trees
+            .iter()
+            .filter(|tree| expected_trees.contains_key(&tree.tree_account))
+            .map(|tree| Discrepancy::MissingTree {
+                tree_account: tree.tree_account,
+            }),
+    );
+
+    match (
+        &expected_distribution.maybe_lockup_tree,
+        &merkle_trees.maybe_lockup_merkle_tree,
+    ) {
+        (None, None) => {}
+        (Some(expected_tree), None) => discrepancies.push(Discrepancy::MissingTree {
+            tree_account: expected_tree.tree_account,
+        }),
+        (None, Some(tree)) => discrepancies.push(Discrepancy::UnexpectedTree {
+            tree_account: tree.lockup_pool_pubkey,
+        }),
+        (Some(expected_tree), Some(tree))
+            if expected_tree.tree_account != tree.lockup_pool_pubkey =>
+        {
+            discrepancies.push(Discrepancy::UnexpectedTree {
+                tree_account: tree.lockup_pool_pubkey,
+            });
+            discrepancies.push(Discrepancy::MissingTree {
+                tree_account: expected_tree.tree_account,
+            });
+        }
+        (Some(expected_tree), Some(tree)) => compare_trees(
+            expected_tree,
+            tree.max_total_claim,
+            tree.tree_nodes
+                .iter()
+                .map(|node| (node.claimant, node.amount)),
+            &mut discrepancies,
+        ),
+    }
+
+    discrepancies
+}
+
+/// Compares the `max_total_claim` and `(claimant, amount)` nodes of a tree to the expected ones.
+fn compare_trees(
+    expected_tree: &ExpectedTree,
+    max_total_claim: u64,
+    tree_nodes: impl Iterator<Item = (Pubkey, u64)>,
+    discrepancies: &mut Vec<Discrepancy>,
+) {
+    let tree_account = expected_tree.tree_account;
+    if max_total_claim != expected_tree.max_total_claim {
+        discrepancies.push(Discrepancy::MaxTotalClaimMismatch {
+            tree_account,
+            expected: expected_tree.max_total_claim,
+            actual: max_total_claim,
+        });
+    }
+
+    let expected_amounts: HashMap<Pubkey, u64> = expected_tree.nodes.iter().copied().collect();
+    let mut amounts = HashMap::new();
+    for (claimant, amount) in tree_nodes {
+        amounts.insert(claimant, amount);
+        if !expected_amounts.contains_key(&claimant) {
+            discrepancies.push(Discrepancy::UnexpectedTreeNode {
+                tree_account,
+                claimant,
+                amount,
+            });
+        }
+    }
+    for &(claimant, expected_amount) in &expected_tree.nodes {
+        match amounts.get(&claimant) {
+            None => discrepancies.push(Discrepancy::MissingTreeNode {
+                tree_account,
+                claimant,
+                expected_amount,
+            }),
+            Some(&amount) if amount != expected_amount => {
+                discrepancies.push(Discrepancy::AmountMismatch {
+                    tree_account,
+                    claimant,
+                    expected: expected_amount,
+                    actual: amount,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Checks that each tree's root, `max_total_claim` and `max_num_nodes` were uploaded to its
+/// [TipDistributionAccount] for the trees' epoch. The lockup program has nowhere to upload the
+/// lockup tree's root to yet, so given a lockup tree, the lockup pool is checked to exist and the
+/// root is always reported as not uploaded.
+pub fn verify_uploaded_roots(
+    merkle_trees: &GeneratedMerkleTreeCollection,
+    rpc_client: &RpcClient,
+) -> Result<Vec<Discrepancy>, MerkleTreeAuditError> {
+    let mut discrepancies = vec![];
+
+    for trees in merkle_trees.generated_merkle_trees.chunks(100) {
+        let pubkeys: Vec<Pubkey> = trees
+            .iter()
+            .map(|tree| tree.tip_distribution_account)
+            .collect();
+        let accounts = rpc_client
+            .get_multiple_accounts_with_commitment(&pubkeys, CommitmentConfig::confirmed())
+            .map_err(Box::new)?
+            .value;
+
+        for (tree, maybe_account) in trees.iter().zip(accounts) {
+            let tree_account = tree.tip_distribution_account;
+            let Some(tip_distribution_account) = maybe_account.and_then(|account| {
+                TipDistributionAccount::try_deserialize(&mut account.data.as_slice()).ok()
+            }) else {
+                discrepancies.push(Discrepancy::TipDistributionAccountNotFound { tree_account });
+                continue;
+            };
+
+            if tip_distribution_account.epoch_created_at != merkle_trees.epoch {
+                discrepancies.push(Discrepancy::EpochMismatch {
+                    tree_account,
+                    expected: merkle_trees.epoch,
+                    actual: tip_distribution_account.epoch_created_at,
+                });
+            }
+
+            let Some(merkle_root) = tip_distribution_account.merkle_root else {
+                discrepancies.push(Discrepancy::RootNotUploaded { tree_account });
+                continue;
+            };
+            if merkle_root.root != tree.merkle_root.to_bytes()
+                || merkle_root.max_total_claim != tree.max_total_claim
+                || merkle_root.max_num_nodes != tree.max_num_nodes
+            {
+                discrepancies.push(Discrepancy::UploadedRootMismatch {
+                    tree_account,
+                    expected_root: tree.merkle_root.to_string(),
+                    actual_root: Hash::new_from_array(merkle_root.root).to_string(),
+                    expected_max_total_claim: tree.max_total_claim,
+                    actual_max_total_claim: merkle_root.max_total_claim,
+                    expected_max_num_nodes: tree.max_num_nodes,
+                    actual_max_num_nodes: merkle_root.max_num_nodes,
+                });
+            }
+        }
+    }
+
+    if let Some(tree) = &merkle_trees.maybe_lockup_merkle_tree {
+        let tree_account = tree.lockup_pool_pubkey;
+        let is_lockup_pool = rpc_client
+            .get_account_with_commitment(&tree_account, CommitmentConfig::confirmed())
+            .map_err(Box::new)?
+            .value
+            .is_some_and(|account| {
+                has_discriminator(&account.data, LockupPool::SPL_DISCRIMINATOR.as_slice())
+            });
+        if is_lockup_pool {
+            discrepancies.push(Discrepancy::RootNotUploaded { tree_account });
+        } else {
+            discrepancies.push(Discrepancy::LockupPoolNotFound { tree_account });
+        }
+    }
+
+    Ok(discrepancies)
+}
+
+fn write_to_json_file(
+    report: &AuditReport,
+    file_path: &PathBuf,
+) -> Result<(), MerkleTreeAuditError> {
+    let file = File::create(file_path)?;
+    let mut writer = BufWriter::new(file);
+    let json = serde_json::to_string_pretty(&report).unwrap();
+    writer.write_all(json.as_bytes())?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            Delegation, GeneratedLockupMerkleTree, StakeMeta, StakeMetaCollection,
+            TipDistributionMeta,
+        },
+        anchor_lang::AccountSerialize,
+        bytemuck::Zeroable,
+        funnel::FunnelConfig,
+        jito_tip_distribution::state::MerkleRoot,
+        serde_json::{json, Value},
+        solana_account_decoder::{encode_ui_account, UiAccountEncoding},
+        solana_client::{
+            rpc_request::RpcRequest,
+            rpc_response::{Response, RpcResponseContext},
+        },
+        solana_runtime::genesis_utils::{
+            create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
+        },
+        solana_sdk::{
+            account::{AccountSharedData, WritableAccount},
+            signature::Signer,
+        },
+    };
+
+    fn create_stake_meta_collection() -> StakeMetaCollection {
+        let stake_metas = (0..2)
+            .map(|i| StakeMeta {
+                validator_vote_account: Pubkey::new_unique(),
+                validator_node_pubkey: Pubkey::new_unique(),
+                maybe_tip_distribution_meta: Some(TipDistributionMeta {
+                    merkle_root_upload_authority: Pubkey::new_unique(),
+                    tip_distribution_pubkey: Pubkey::new_unique(),
+                    total_tips: 1_900_122_111_000 + i,
+                    validator_fee_bps: 100,
+                }),
+                delegations: vec![
+                    Delegation {
+                        stake_account_pubkey: Pubkey::new_unique(),
+                        staker_pubkey: Pubkey::new_unique(),
+                        withdrawer_pubkey: Pubkey::new_unique(),
+                        lamports_delegated: 123_999_123_555,
+                    },
+                    Delegation {
+                        stake_account_pubkey: Pubkey::new_unique(),
+                        staker_pubkey: Pubkey::new_unique(),
+                        withdrawer_pubkey: Pubkey::new_unique(),
+                        lamports_delegated: 144_555_444_556,
+                    },
+                ],
+                total_delegated: 268_554_568_111,
+                commission: 100,
+            })
+            .collect();
+
+        StakeMetaCollection {
+            stake_metas,
+            tip_distribution_program_id: Pubkey::new_unique(),
+            bank_hash: Hash::new_unique().to_string(),
+            epoch: 100,
+            slot: 2_000_000,
+            maybe_funnel_meta: None,
+        }
+    }
+
+    /// Works out the distribution of `stake_meta_coll` by hand, apart from the generator.
+    fn expected_distribution(stake_meta_coll: &StakeMetaCollection) -> ExpectedDistribution {
+        let trees = stake_meta_coll
+            .stake_metas
+            .iter()
+            .map(|stake_meta| {
+                let tip_distribution_meta =
+                    stake_meta.maybe_tip_distribution_meta.as_ref().unwrap();
+                let total_tips = tip_distribution_meta.total_tips;
+                let commission = total_tips * tip_distribution_meta.validator_fee_bps / 10_000;
+                let mut nodes = vec![(stake_meta.validator_vote_account, commission)];
+                nodes.extend(stake_meta.delegations.iter().map(|delegation| {
+                    let amount = u128::from(delegation.lamports_delegated)
+                        * u128::from(total_tips - commission)
+                        / u128::from(stake_meta.total_delegated);
+                    (delegation.stake_account_pubkey, amount as u64)
+                }));
+                ExpectedTree {
+                    tree_account: tip_distribution_meta.tip_distribution_pubkey,
+                    max_total_claim: total_tips,
+                    nodes,
+                }
+            })
+            .collect();
+
+        ExpectedDistribution {
+            bank_hash: stake_meta_coll.bank_hash.clone(),
+            trees,
+            maybe_lockup_tree: None,
+        }
+    }
+
+    fn tda_to_account_shared_data(
+        tip_distribution_program_id: &Pubkey,
+        lamports: u64,
+        tda: TipDistributionAccount,
+    ) -> AccountSharedData {
+        let mut data = vec![0u8; TipDistributionAccount::SIZE];
+        tda.try_serialize(&mut data.as_mut_slice()).unwrap();
+        let mut account_data = AccountSharedData::new(
+            lamports,
+            TipDistributionAccount::SIZE,
+            tip_distribution_program_id,
+        );
+        account_data.set_data(data);
+        account_data
+    }
+
+    fn encode_account(pubkey: &Pubkey, account: &AccountSharedData) -> Value {
+        serde_json::to_value(encode_ui_account(
+            pubkey,
+            account,
+            UiAccountEncoding::Base64,
+            None,
+            None,
+        ))
+        .unwrap()
+    }
+
+    fn rpc_response(value: Value) -> Value {
+        serde_json::to_value(Response {
+            context: RpcResponseContext {
+                slot: 1,
+                api_version: None,
+            },
+            value,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_audit_generated_merkle_trees() {
+        let stake_meta_coll = create_stake_meta_collection();
+        let merkle_trees = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
+            stake_meta_coll.clone(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(verify_proofs(&merkle_trees), vec![]);
+        assert_eq!(
+            verify_against_expected_distribution(
+                &merkle_trees,
+                &expected_distribution(&stake_meta_coll)
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_audit_tampered_merkle_trees() {
+        let stake_meta_coll = create_stake_meta_collection();
+        let mut merkle_trees = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
+            stake_meta_coll.clone(),
+            None,
+        )
+        .unwrap();
+
+        // inflate a delegator's amount past the tree's max_total_claim
+        let tree = &mut merkle_trees.generated_merkle_trees[0];
+        let tree_account_0 = tree.tip_distribution_account;
+        let claimant_0 = tree.tree_nodes[1].claimant;
+        let amount_0 = tree.tree_nodes[1].amount;
+        tree.tree_nodes[1].amount = tree.max_total_claim;
+        let total_claims: u64 = tree.tree_nodes.iter().map(|node| node.amount).sum();
+        let max_total_claim_0 = tree.max_total_claim;
+
+        // drop a delegator's node and understate the tips
+        let tree = &mut merkle_trees.generated_merkle_trees[1];
+        let tree_account_1 = tree.tip_distribution_account;
+        let dropped_node = tree.tree_nodes.pop().unwrap();
+        let max_total_claim_1 = tree.max_total_claim;
+        tree.max_total_claim -= 1;
+
+        assert_eq!(
+            verify_proofs(&merkle_trees),
+            vec![
+                Discrepancy::InvalidProof {
+                    tree_account: tree_account_0,
+                    claimant: claimant_0,
+                },
+                Discrepancy::ClaimsExceedMaxTotalClaim {
+                    tree_account: tree_account_0,
+                    total_claims,
+                    max_total_claim: max_total_claim_0,
+                },
+                Discrepancy::MaxNumNodesMismatch {
+                    tree_account: tree_account_1,
+                    max_num_nodes: 3,
+                    num_nodes: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            verify_against_expected_distribution(
+                &merkle_trees,
+                &expected_distribution(&stake_meta_coll)
+            ),
+            vec![
+                Discrepancy::AmountMismatch {
+                    tree_account: tree_account_0,
+                    claimant: claimant_0,
+                    expected: amount_0,
+                    actual: max_total_claim_0,
+                },
+                Discrepancy::MaxTotalClaimMismatch {
+                    tree_account: tree_account_1,
+                    expected: max_total_claim_1,
+                    actual: max_total_claim_1 - 1,
+                },
+                Discrepancy::MissingTreeNode {
+                    tree_account: tree_account_1,
+                    claimant: dropped_node.claimant,
+                    expected_amount: dropped_node.amount,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_missing_and_unexpected_trees() {
+        let stake_meta_coll = create_stake_meta_collection();
+        let mut merkle_trees = GeneratedMerkleTreeCollection::new_from_stake_meta_collection(
+            stake_meta_coll.clone(),
+            None,
+        )
+        .unwrap();
+
+        let missing_tree = merkle_trees.generated_merkle_trees.remove(0);
+        let unexpected_tree_account = Pubkey::new_unique();
+        merkle_trees.generated_merkle_trees[0].tip_distribution_account = unexpected_tree_account;
+        merkle_trees.bank_hash = Hash::new_unique().to_string();
+
+        assert_eq!(
+            verify_against_expected_distribution(
+                &merkle_trees,
+                &expected_distribution(&stake_meta_coll)
+            ),
+            vec![
+                Discrepancy::BankHashMismatch {
+                    expected: stake_meta_coll.bank_hash.clone(),
+                    actual: merkle_trees.bank_hash.clone(),
+                },
+                Discrepancy::UnexpectedTree {
+                    tree_account: unexpected_tree_account,
+                },
+                Discrepancy::MissingTree {
+                    tree_account: missing_tree.tip_distribution_account,
+                },
+                Discrepancy::MissingTree {
+                    tree_account: stake_meta_coll.stake_metas[1]
+                        .maybe_tip_distribution_meta
+                        .as_ref()
+                        .unwrap()
+                        .tip_distribution_pubkey,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_duplicate_claimant() {
+        // both delegations are the same stake account, so every proof verifies and the claims fit
+        // within max_total_claim, but the stake account could claim twice
+        let mut stake_meta_coll = create_stake_meta_collection();
+        let stake_meta = &mut stake_meta_coll.stake_metas[0];
+        let claimant = stake_meta.delegations[0].stake_account_pubkey;
+        stake_meta.delegations[1].stake_account_pubkey = claimant;
+        let tree_account = stake_meta
+            .maybe_tip_distribution_meta
+            .as_ref()
+            .unwrap()
+            .tip_distribution_pubkey;
+        let merkle_trees =
+            GeneratedMerkleTreeCollection::new_from_stake_meta_collection(stake_meta_coll, None)
+                .unwrap();
+
+        assert_eq!(
+            verify_proofs(&merkle_trees),
+            vec![Discrepancy::DuplicateClaimant {
+                tree_account,
+                claimant,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_expected_distribution_from_bank() {
+        let validator_keypairs_0 = ValidatorVoteKeypairs::new_rand();
+        let validator_keypairs_1 = ValidatorVoteKeypairs::new_rand();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &[&validator_keypairs_0, &validator_keypairs_1],
+            vec![10_000; 2],
+        );
+        let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+
+        let snapshot_config = SnapshotAuditConfig {
+            ledger_path: PathBuf::new(),
+            tip_distribution_program_id: Pubkey::new_unique(),
+            tip_payment_program_id: Pubkey::new_unique(),
+            maybe_funnel_pubkey: Some(Pubkey::new_unique()),
+            maybe_lockup_pool_pubkey: Some(Pubkey::new_unique()),
+            maybe_previous_stake_meta_path: None,
+        };
+        let funnel_pubkey = snapshot_config.maybe_funnel_pubkey.unwrap();
+        let lockup_pool_pubkey = snapshot_config.maybe_lockup_pool_pubkey.unwrap();
+        let stakers_receiver = Pubkey::new_unique();
+
+        // Only the first validator has a TDA, holding 10_000 lamports, 8% of which is its
+        // commission.
+        let tip_distribution_account = derive_tip_distribution_account_address(
+            &snapshot_config.tip_distribution_program_id,
+            &validator_keypairs_0.vote_keypair.pubkey(),
+            bank.epoch(),
+        );
+        let tda = TipDistributionAccount {
+            validator_vote_account: validator_keypairs_0.vote_keypair.pubkey(),
+            merkle_root_upload_authority: Pubkey::new_unique(),
+            merkle_root: None,
+            epoch_created_at: bank.epoch(),
+            validator_commission_bps: 800,
+            expires_at: bank.epoch() + 3,
+            bump: tip_distribution_account.1,
+        };
+        let tda_rent = bank.get_minimum_balance_for_rent_exemption(TipDistributionAccount::SIZE);
+        bank.store_account(
+            &tip_distribution_account.0,
+            &tda_to_account_shared_data(
+                &snapshot_config.tip_distribution_program_id,
+                tda_rent + 10_000,
+                tda,
+            ),
+        );
+
+        // The funnel holds 100_000 unswept lamports, stakers and holders get 5% each and the rest
+        // goes to its receiver, the TDA.
+        let funnel = Funnel {
+            receiver: tip_distribution_account.0,
+            config: FunnelConfig {
+                stakers_receiver,
+                holders_receiver: stakers_receiver,
+                stakers_bps: 500,
+                holders_bps: 500,
+                commission_bps: 1_000,
+            },
+            authority: Pubkey::new_unique(),
+        };
+        let mut funnel_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(Funnel::LEN) + 100_000,
+            Funnel::LEN,
+            &funnel::ID,
+        );
+        funnel_data.set_data(funnel.as_bytes().to_vec());
+        bank.store_account(&funnel_pubkey, &funnel_data);
+
+        // The receiver already holds 20_000 swept lamports.
+        bank.store_account(
+            &stakers_receiver,
+            &AccountSharedData::new(
+                bank.get_minimum_balance_for_rent_exemption(0) + 20_000,
+                0,
+                &solana_sdk::system_program::id(),
+            ),
+        );
+
+        // Two lockups in the pool, only the first of which has a lockup account.
+        let (lockup_0, lockup_1) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pool = LockupPool::zeroed();
+        pool.discriminator = LockupPool::SPL_DISCRIMINATOR.into();
+        for (entry, (lockup, amount)) in pool
+            .entries
+            .iter_mut()
+            .zip([(lockup_0, 600), (lockup_1, 400)])
+        {
+            entry.lockup = lockup;
+            entry.amount = amount;
+        }
+        pool.entries_len = 2;
+        let mut pool_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(LockupPool::LEN),
+            LockupPool::LEN,
+            &paladin_lockup_program::id(),
+        );
+        pool_data.set_data(bytemuck::bytes_of(&pool).to_vec());
+        bank.store_account(&lockup_pool_pubkey, &pool_data);
+
+        let mut lockup = Lockup::zeroed();
+        lockup.discriminator = Lockup::SPL_DISCRIMINATOR.into();
+        lockup.amount = 600;
+        lockup.pool = lockup_pool_pubkey;
+        let mut lockup_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(Lockup::LEN),
+            Lockup::LEN,
+            &paladin_lockup_program::id(),
+        );
+        lockup_data.set_data(bytemuck::bytes_of(&lockup).to_vec());
+        bank.store_account(&lockup_0, &lockup_data);
+        bank.freeze();
+
+        // The TDA's 10_000 plus the funnel's 90_000, of which the delegator gets all but the
+        // commission. The lockup gets the receiver's 20_000 plus the funnel's 10_000.
+        let expected_tree = ExpectedTree {
+            tree_account: tip_distribution_account.0,
+            max_total_claim: 100_000,
+            nodes: vec![
+                (validator_keypairs_0.vote_keypair.pubkey(), 8_000),
+                (validator_keypairs_0.stake_keypair.pubkey(), 92_000),
+            ],
+        };
+        assert_eq!(
+            ExpectedDistribution::from_bank(&bank, &snapshot_config, None).unwrap(),
+            ExpectedDistribution {
+                bank_hash: bank.hash().to_string(),
+                trees: vec![expected_tree.clone()],
+                maybe_lockup_tree: Some(ExpectedTree {
+                    tree_account: lockup_pool_pubkey,
+                    max_total_claim: 30_000,
+                    nodes: vec![(lockup_0, 30_000)],
+                }),
+            }
+        );
+
+        // Only what reached the receiver since the previous epoch counts.
+        let previous_funnel_meta = FunnelMeta {
+            funnel_pubkey,
+            receiver: tip_distribution_account.0,
+            stakers_receiver,
+            holders_receiver: stakers_receiver,
+            unswept_tips: 0,
+            receiver_tips: 0,
+            stakers_receiver_balance: 15_000,
+            stakers_unswept_tips: 5_000,
+            holders_receiver_balance: 0,
+            holders_unswept_tips: 0,
+            total_stakers_tips: 20_000,
+            total_holders_tips: 0,
+            maybe_lockup_pool_meta: None,
+        };
+        assert_eq!(
+            ExpectedDistribution::from_bank(&bank, &snapshot_config, Some(&previous_funnel_meta))
+                .unwrap(),
+            ExpectedDistribution {
+                bank_hash: bank.hash().to_string(),
+                trees: vec![expected_tree.clone()],
+                maybe_lockup_tree: Some(ExpectedTree {
+                    tree_account: lockup_pool_pubkey,
+                    max_total_claim: 10_000,
+                    nodes: vec![(lockup_0, 10_000)],
+                }),
+            }
+        );
+
+        // Nothing is counted for a receiver holding less than was counted before.
+        let previous_funnel_meta = FunnelMeta {
+            stakers_receiver_balance: 50_000,
+            ..previous_funnel_meta
+        };
+        assert_eq!(
+            ExpectedDistribution::from_bank(&bank, &snapshot_config, Some(&previous_funnel_meta))
+                .unwrap(),
+            ExpectedDistribution {
+                bank_hash: bank.hash().to_string(),
+                trees: vec![expected_tree],
+                maybe_lockup_tree: Some(ExpectedTree {
+                    tree_account: lockup_pool_pubkey,
+                    max_total_claim: 0,
+                    nodes: vec![(lockup_0, 0)],
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_uploaded_roots() {
+        let stake_meta_coll = create_stake_meta_collection();
+        let tip_distribution_program_id = stake_meta_coll.tip_distribution_program_id;
+        let mut merkle_trees =
+            GeneratedMerkleTreeCollection::new_from_stake_meta_collection(stake_meta_coll, None)
+                .unwrap();
+        let lockup_pool_pubkey = Pubkey::new_unique();
+        merkle_trees.maybe_lockup_merkle_tree = Some(GeneratedLockupMerkleTree {
+            funnel_pubkey: Pubkey::new_unique(),
+            lockup_pool_pubkey,
+            merkle_root: Hash::new_unique(),
+            tree_nodes: vec![],
+            max_total_claim: 0,
+            max_num_nodes: 0,
+        });
+
+        // The first tree's root was uploaded, the second's with the wrong max_total_claim.
+        let tda_accounts: Vec<Value> = merkle_trees
+            .generated_merkle_trees
+            .iter()
+            .enumerate()
+            .map(|(i, tree)| {
+                let tda = TipDistributionAccount {
+                    validator_vote_account: Pubkey::new_unique(),
+                    merkle_root_upload_authority: tree.merkle_root_upload_authority,
+                    merkle_root: Some(MerkleRoot {
+                        root: tree.merkle_root.to_bytes(),
+                        max_total_claim: tree.max_total_claim + i as u64,
+                        max_num_nodes: tree.max_num_nodes,
+                        total_funds_claimed: 0,
+                        num_nodes_claimed: 0,
+                    }),
+                    epoch_created_at: merkle_trees.epoch,
+                    validator_commission_bps: 100,
+                    expires_at: merkle_trees.epoch + 3,
+                    bump: 255,
+                };
+                encode_account(
+                    &tree.tip_distribution_account,
+                    &tda_to_account_shared_data(&tip_distribution_program_id, 1_000_000, tda),
+                )
+            })
+            .collect();
+        let mut pool = LockupPool::zeroed();
+        pool.discriminator = LockupPool::SPL_DISCRIMINATOR.into();
+        let mut pool_account =
+            AccountSharedData::new(1_000_000, LockupPool::LEN, &paladin_lockup_program::id());
+        pool_account.set_data(bytemuck::bytes_of(&pool).to_vec());
+        let mocks = HashMap::from([
+            (
+                RpcRequest::GetMultipleAccounts,
+                rpc_response(Value::Array(tda_accounts)),
+            ),
+            (
+                RpcRequest::GetAccountInfo,
+                rpc_response(encode_account(&lockup_pool_pubkey, &pool_account)),
+            ),
+        ]);
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let tree = &merkle_trees.generated_merkle_trees[1];
+        assert_eq!(
+            verify_uploaded_roots(&merkle_trees, &rpc_client).unwrap(),
+            vec![
+                Discrepancy::UploadedRootMismatch {
+                    tree_account: tree.tip_distribution_account,
+                    expected_root: tree.merkle_root.to_string(),
+                    actual_root: tree.merkle_root.to_string(),
+                    expected_max_total_claim: tree.max_total_claim,
+                    actual_max_total_claim: tree.max_total_claim + 1,
+                    expected_max_num_nodes: tree.max_num_nodes,
+                    actual_max_num_nodes: tree.max_num_nodes,
+                },
+                // the lockup program has nowhere to upload the root to
+                Discrepancy::RootNotUploaded {
+                    tree_account: lockup_pool_pubkey,
+                },
+            ]
+        );
+
+        // Neither the TDAs nor the lockup pool exist.
+        let mocks = HashMap::from([(
+            RpcRequest::GetMultipleAccounts,
+            rpc_response(json!([null, null])),
+        )]);
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        assert_eq!(
+            verify_uploaded_roots(&merkle_trees, &rpc_client).unwrap(),
+            vec![
+                Discrepancy::TipDistributionAccountNotFound {
+                    tree_account: merkle_trees.generated_merkle_trees[0].tip_distribution_account,
+                },
+                Discrepancy::TipDistributionAccountNotFound {
+                    tree_account: merkle_trees.generated_merkle_trees[1].tip_distribution_account,
+                },
+                Discrepancy::LockupPoolNotFound {
+                    tree_account: lockup_pool_pubkey,
+                },
+            ]
+        );
+    }
+}
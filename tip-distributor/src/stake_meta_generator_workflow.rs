@@ -1,12 +1,14 @@
 use {
     crate::{
-        derive_tip_distribution_account_address, derive_tip_payment_pubkeys, Config, StakeMeta,
-        StakeMetaCollection, TipDistributionAccount, TipDistributionAccountWrapper,
-        TipDistributionMeta,
+        derive_tip_distribution_account_address, derive_tip_payment_pubkeys, Config, FunnelMeta,
+        LockupMeta, LockupPoolMeta, StakeMeta, StakeMetaCollection, TipDistributionAccount,
+        TipDistributionAccountWrapper, TipDistributionMeta,
     },
     anchor_lang::AccountDeserialize,
+    funnel::{Funnel, RewardSplit},
     itertools::Itertools,
     log::*,
+    paladin_lockup_program::state::{Lockup, LockupPool},
     solana_accounts_db::hardened_unpack::{
         open_genesis_config, OpenGenesisConfigError, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
     },
@@ -16,21 +18,26 @@ use {
         bank_forks_utils::BankForksUtilsError,
         blockstore::{Blockstore, BlockstoreError},
         blockstore_options::{AccessType, BlockstoreOptions, LedgerColumnOptions},
-        blockstore_processor::{BlockstoreProcessorError, ProcessOptions},
+        blockstore_processor::{self, BlockstoreProcessorError, ProcessOptions},
     },
     solana_program::{stake_history::StakeHistory, sysvar},
-    solana_runtime::{bank::Bank, snapshot_config::SnapshotConfig, stakes::StakeAccount},
+    solana_runtime::{
+        accounts_background_service::AbsRequestSender, bank::Bank, snapshot_config::SnapshotConfig,
+        snapshot_utils, stakes::StakeAccount,
+    },
     solana_sdk::{
         account::{from_account, ReadableAccount, WritableAccount},
         clock::Slot,
+        genesis_config::GenesisConfig,
         pubkey::Pubkey,
     },
     solana_vote::vote_account::VoteAccount,
+    spl_discriminator::discriminator::SplDiscriminate,
     std::{
         collections::HashMap,
         fmt::{Debug, Display, Formatter},
-        fs::File,
-        io::{BufWriter, Write},
+        fs::{self, File},
+        io::{BufReader, BufWriter, Write},
         mem::size_of,
         path::{Path, PathBuf},
         sync::{atomic::AtomicBool, Arc},
@@ -65,6 +72,17 @@ pub enum StakeMetaGeneratorError {
     BankForksUtilsError(#[from] BankForksUtilsError),
 
     GenesisConfigError(#[from] OpenGenesisConfigError),
+
+    InvalidFunnelAccount(Pubkey),
+
+    InvalidLockupPoolAccount(Pubkey),
+
+    /// There's no full snapshot archive at or below the slot, and the ledger was purged past
+    /// genesis so it can't be replayed from there either.
+    FullSnapshotArchiveNotFound {
+        halt_slot: Slot,
+        full_snapshot_archives_dir: PathBuf,
+    },
 }
 
 impl Display for StakeMetaGeneratorError {
@@ -74,20 +92,36 @@ impl Display for StakeMetaGeneratorError {
 }
 
 /// Runs the entire workflow of creating a bank from a snapshot to writing stake meta-data
-/// to a JSON file.
+/// to a JSON file. The funnel's flows are counted from the previous epoch's [StakeMetaCollection]
+/// at `maybe_previous_stake_meta_path`, if given.
+#[allow(clippy::too_many_arguments)]
 pub fn generate_stake_meta(
     ledger_path: &Path,
     snapshot_slot: &Slot,
     tip_distribution_program_id: &Pubkey,
     out_path: &str,
     tip_payment_program_id: &Pubkey,
+    maybe_funnel_pubkey: Option<&Pubkey>,
+    maybe_lockup_pool_pubkey: Option<&Pubkey>,
+    maybe_previous_stake_meta_path: Option<&Path>,
 ) -> Result<(), StakeMetaGeneratorError> {
+    let maybe_previous_funnel_meta = maybe_previous_stake_meta_path
+        .map(read_previous_funnel_meta)
+        .transpose()?
+        .flatten();
+
     info!("Creating bank from ledger path...");
     let bank = create_bank_from_snapshot(ledger_path, snapshot_slot)?;
 
     info!("Generating stake_meta_collection object...");
-    let stake_meta_coll =
-        generate_stake_meta_collection(&bank, tip_distribution_program_id, tip_payment_program_id)?;
+    let stake_meta_coll = generate_stake_meta_collection(
+        &bank,
+        tip_distribution_program_id,
+        tip_payment_program_id,
+        maybe_funnel_pubkey,
+        maybe_lockup_pool_pubkey,
+        maybe_previous_funnel_meta.as_ref(),
+    )?;
 
     info!("Writing stake_meta_collection to JSON {}...", out_path);
     write_to_json_file(&stake_meta_coll, out_path)?;
@@ -95,7 +129,7 @@ pub fn generate_stake_meta(
     Ok(())
 }
 
-fn create_bank_from_snapshot(
+pub(crate) fn create_bank_from_snapshot(
     ledger_path: &Path,
     snapshot_slot: &Slot,
 ) -> Result<Arc<Bank>, StakeMetaGeneratorError> {
@@ -142,7 +176,75 @@ fn create_bank_from_snapshot(
     Ok(working_bank)
 }
 
-fn write_to_json_file(
+/// Creates the rooted bank at `halt_slot`, or the highest rooted slot before it, from the highest
+/// full snapshot archive at or below it, replaying the ledger from the snapshot. Without such an
+/// archive, the ledger is replayed from genesis if it still has it. This saves creating a snapshot
+/// at the slot with ledger-tool beforehand.
+pub fn create_bank_from_ledger(
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    full_snapshot_archives_dir: &Path,
+    incremental_snapshot_archives_dir: &Path,
+    working_dir: &Path,
+    halt_slot: Slot,
+) -> Result<Arc<Bank>, StakeMetaGeneratorError> {
+    if snapshot_utils::get_highest_full_snapshot_archive_info(
+        full_snapshot_archives_dir,
+        Some(halt_slot),
+    )
+    .is_none()
+        && blockstore.meta(0)?.is_none()
+    {
+        return Err(StakeMetaGeneratorError::FullSnapshotArchiveNotFound {
+            halt_slot,
+            full_snapshot_archives_dir: full_snapshot_archives_dir.to_path_buf(),
+        });
+    }
+
+    let bank_snapshots_dir = working_dir.join("snapshot");
+    let accounts_dir = working_dir.join("stake-meta.accounts");
+    fs::create_dir_all(&bank_snapshots_dir)?;
+    fs::create_dir_all(&accounts_dir)?;
+
+    let snapshot_config = SnapshotConfig {
+        full_snapshot_archives_dir: full_snapshot_archives_dir.to_path_buf(),
+        incremental_snapshot_archives_dir: incremental_snapshot_archives_dir.to_path_buf(),
+        bank_snapshots_dir,
+        ..SnapshotConfig::new_load_only()
+    };
+    let process_options = ProcessOptions {
+        halt_at_slot: Some(halt_slot),
+        ..ProcessOptions::default()
+    };
+    let (bank_forks, leader_schedule_cache, _) = bank_forks_utils::load_bank_forks(
+        genesis_config,
+        blockstore,
+        vec![accounts_dir],
+        Some(&snapshot_config),
+        &process_options,
+        None,
+        None,
+        None,
+        Arc::new(AtomicBool::new(false)),
+        false,
+    )?;
+    blockstore_processor::process_blockstore_from_root(
+        blockstore,
+        &bank_forks,
+        &leader_schedule_cache,
+        &process_options,
+        None,
+        None,
+        None,
+        &AbsRequestSender::default(),
+    )?;
+
+    // the working bank is the highest replayed one, which may be on a fork that was never rooted
+    let root_bank = bank_forks.read().unwrap().root_bank();
+    Ok(root_bank)
+}
+
+pub(crate) fn write_to_json_file(
     stake_meta_coll: &StakeMetaCollection,
     out_path: &str,
 ) -> Result<(), StakeMetaGeneratorError> {
@@ -155,11 +257,26 @@ fn write_to_json_file(
     Ok(())
 }
 
-/// Creates a collection of [StakeMeta]'s from the given bank.
+/// Returns the [FunnelMeta] of the [StakeMetaCollection] at `path`.
+pub fn read_previous_funnel_meta(
+    path: &Path,
+) -> Result<Option<FunnelMeta>, StakeMetaGeneratorError> {
+    let file = File::open(path)?;
+    let stake_meta_coll: StakeMetaCollection = serde_json::from_reader(BufReader::new(file))?;
+
+    Ok(stake_meta_coll.maybe_funnel_meta)
+}
+
+/// Creates a collection of [StakeMeta]'s from the given bank, along with the funnel's flows if a
+/// funnel is given. The flows are what reached the funnel's receivers since
+/// `maybe_previous_funnel_meta` was generated, or everything they hold without it.
 pub fn generate_stake_meta_collection(
     bank: &Arc<Bank>,
     tip_distribution_program_id: &Pubkey,
     tip_payment_program_id: &Pubkey,
+    maybe_funnel_pubkey: Option<&Pubkey>,
+    maybe_lockup_pool_pubkey: Option<&Pubkey>,
+    maybe_previous_funnel_meta: Option<&FunnelMeta>,
 ) -> Result<StakeMetaCollection, StakeMetaGeneratorError> {
     assert!(bank.is_frozen());
 
@@ -213,6 +330,26 @@ pub fn generate_stake_meta_collection(
         .checked_sub(block_builder_tips)
         .expect("tip_receiver_fee doesnt underflow");
 
+    // with a funnel as the tip receiver, the excess tips are swept to the funnel, which splits
+    // them between the stakers receiver, the holders receiver and its current receiver (the last
+    // leader's TDA) once the next leader becomes the receiver. Like above, assume that happens.
+    let maybe_funnel_meta = maybe_funnel_pubkey
+        .map(|funnel_pubkey| {
+            let funnel_tips = if *funnel_pubkey == tip_receiver {
+                tip_receiver_fee
+            } else {
+                0
+            };
+            generate_funnel_meta(
+                bank,
+                funnel_pubkey,
+                funnel_tips,
+                maybe_lockup_pool_pubkey,
+                maybe_previous_funnel_meta,
+            )
+        })
+        .transpose()?;
+
     let vote_pk_and_maybe_tdas: Vec<(
         (Pubkey, &VoteAccount),
         Option<TipDistributionAccountWrapper>,
@@ -241,6 +378,17 @@ pub fn generate_stake_meta_collection(
                                 .expect("tip overflow"),
                         );
                     }
+                    if let Some(funnel_meta) = maybe_funnel_meta
+                        .as_ref()
+                        .filter(|funnel_meta| funnel_meta.receiver == tip_distribution_pubkey)
+                    {
+                        account_data.set_lamports(
+                            account_data
+                                .lamports()
+                                .checked_add(funnel_meta.receiver_tips)
+                                .expect("tip overflow"),
+                        );
+                    }
                     Some(TipDistributionAccountWrapper {
                         tip_distribution_account,
                         account_data,
@@ -305,9 +453,217 @@ pub fn generate_stake_meta_collection(
         bank_hash: bank.hash().to_string(),
         epoch: bank.epoch(),
         slot: bank.slot(),
+        maybe_funnel_meta,
+    })
+}
+
+/// Reads the funnel's receivers and unswept tips from the bank, `funnel_tips` being the tips
+/// that will be swept into the funnel on top of its balance. The receivers' balances and shares of
+/// the unswept tips accumulate across epochs, so what `maybe_previous_funnel_meta` already counted
+/// for the same receiver is deducted.
+fn generate_funnel_meta(
+    bank: &Bank,
+    funnel_pubkey: &Pubkey,
+    funnel_tips: u64,
+    maybe_lockup_pool_pubkey: Option<&Pubkey>,
+    maybe_previous_funnel_meta: Option<&FunnelMeta>,
+) -> Result<FunnelMeta, StakeMetaGeneratorError> {
+    let account =
+        bank.get_account(funnel_pubkey)
+            .ok_or(StakeMetaGeneratorError::InvalidFunnelAccount(
+                *funnel_pubkey,
+            ))?;
+    let funnel = Funnel::load(account.data())
+        .map_err(|_| StakeMetaGeneratorError::InvalidFunnelAccount(*funnel_pubkey))?;
+
+    let unswept_tips = excess_balance(bank, funnel_pubkey)
+        .checked_add(funnel_tips)
+        .ok_or(StakeMetaGeneratorError::CheckedMathError)?;
+    let RewardSplit {
+        mut stakers,
+        mut holders,
+        validator,
+    } = funnel.config.split_tips(unswept_tips);
+
+    // when both receivers are the same account, it's all counted towards the stakers
+    let stakers_receiver = funnel.config.stakers_receiver;
+    let holders_receiver = funnel.config.holders_receiver;
+    let stakers_receiver_balance = excess_balance(bank, &stakers_receiver);
+    let holders_receiver_balance = if holders_receiver == stakers_receiver {
+        stakers = stakers
+            .checked_add(std::mem::take(&mut holders))
+            .ok_or(StakeMetaGeneratorError::CheckedMathError)?;
+        0
+    } else {
+        excess_balance(bank, &holders_receiver)
+    };
+
+    let maybe_previous_funnel_meta = maybe_previous_funnel_meta
+        .filter(|previous_funnel_meta| previous_funnel_meta.funnel_pubkey == *funnel_pubkey);
+    if maybe_previous_funnel_meta.is_none() {
+        warn!(
+            "no previous funnel meta, counting all the receivers' tips [funnel={}]",
+            funnel_pubkey
+        );
+    }
+    let total_stakers_tips = receiver_flow(
+        &stakers_receiver,
+        stakers_receiver_balance,
+        stakers,
+        maybe_previous_funnel_meta
+            .filter(|previous_funnel_meta| {
+                previous_funnel_meta.stakers_receiver == stakers_receiver
+            })
+            .map(|previous_funnel_meta| {
+                (
+                    previous_funnel_meta.stakers_receiver_balance,
+                    previous_funnel_meta.stakers_unswept_tips,
+                )
+            }),
+    )?;
+    let total_holders_tips = receiver_flow(
+        &holders_receiver,
+        holders_receiver_balance,
+        holders,
+        maybe_previous_funnel_meta
+            .filter(|previous_funnel_meta| {
+                previous_funnel_meta.holders_receiver == holders_receiver
+            })
+            .map(|previous_funnel_meta| {
+                (
+                    previous_funnel_meta.holders_receiver_balance,
+                    previous_funnel_meta.holders_unswept_tips,
+                )
+            }),
+    )?;
+
+    let maybe_lockup_pool_meta = maybe_lockup_pool_pubkey
+        .map(|lockup_pool_pubkey| generate_lockup_pool_meta(bank, lockup_pool_pubkey))
+        .transpose()?;
+
+    Ok(FunnelMeta {
+        funnel_pubkey: *funnel_pubkey,
+        receiver: funnel.receiver,
+        stakers_receiver,
+        holders_receiver,
+        unswept_tips,
+        receiver_tips: validator,
+        stakers_receiver_balance,
+        stakers_unswept_tips: stakers,
+        holders_receiver_balance,
+        holders_unswept_tips: holders,
+        total_stakers_tips,
+        total_holders_tips,
+        maybe_lockup_pool_meta,
+    })
+}
+
+/// Returns the tips that reached a funnel receiver holding `balance` with `unswept_tips` still to
+/// come, since the previous epoch counted the `(balance, unswept_tips)` in `maybe_previous`.
+///
+/// A receiver holding less than was counted before was drawn down, so the epoch's flow to it can't
+/// be told apart; none of it is counted rather than stalling the epoch's distribution.
+pub(crate) fn receiver_flow(
+    receiver: &Pubkey,
+    balance: u64,
+    unswept_tips: u64,
+    maybe_previous: Option<(u64, u64)>,
+) -> Result<u64, StakeMetaGeneratorError> {
+    let total = balance
+        .checked_add(unswept_tips)
+        .ok_or(StakeMetaGeneratorError::CheckedMathError)?;
+    let Some((previous_balance, previous_unswept_tips)) = maybe_previous else {
+        return Ok(total);
+    };
+    let previous_total = previous_balance
+        .checked_add(previous_unswept_tips)
+        .ok_or(StakeMetaGeneratorError::CheckedMathError)?;
+
+    Ok(total.checked_sub(previous_total).unwrap_or_else(|| {
+        warn!(
+            "funnel receiver holds less than the previous epoch counted, counting none of its \
+             tips [receiver={}, total={}, previous_total={}]",
+            receiver, total, previous_total
+        );
+        0
+    }))
+}
+
+/// Reads the initialized lockups of the lockup pool from the bank.
+fn generate_lockup_pool_meta(
+    bank: &Bank,
+    lockup_pool_pubkey: &Pubkey,
+) -> Result<LockupPoolMeta, StakeMetaGeneratorError> {
+    let account = bank.get_account(lockup_pool_pubkey).ok_or(
+        StakeMetaGeneratorError::InvalidLockupPoolAccount(*lockup_pool_pubkey),
+    )?;
+    let data = account.data();
+    if data.len() < 8 || &data[0..8] != LockupPool::SPL_DISCRIMINATOR.as_slice() {
+        return Err(StakeMetaGeneratorError::InvalidLockupPoolAccount(
+            *lockup_pool_pubkey,
+        ));
+    }
+    let pool = bytemuck::try_from_bytes::<LockupPool>(data)
+        .map_err(|_| StakeMetaGeneratorError::InvalidLockupPoolAccount(*lockup_pool_pubkey))?;
+
+    // lockups whose authority can't be read are left out, nobody could claim their share
+    let lockups = pool
+        .entries
+        .iter()
+        .take(pool.entries_len)
+        .filter(|entry| entry.lockup != Pubkey::default() && entry.amount > 0)
+        .filter_map(|entry| {
+            Some(LockupMeta {
+                lockup_pubkey: entry.lockup,
+                authority_pubkey: get_lockup_authority(bank, &entry.lockup)?,
+                amount: entry.amount,
+            })
+        })
+        .collect::<Vec<LockupMeta>>();
+    let total_locked = lockups
+        .iter()
+        .try_fold(0u64, |sum, lockup| sum.checked_add(lockup.amount))
+        .ok_or(StakeMetaGeneratorError::CheckedMathError)?;
+
+    Ok(LockupPoolMeta {
+        lockup_pool_pubkey: *lockup_pool_pubkey,
+        lockups,
+        total_locked,
     })
 }
 
+fn get_lockup_authority(bank: &Bank, lockup_pubkey: &Pubkey) -> Option<Pubkey> {
+    let maybe_authority = bank.get_account(lockup_pubkey).and_then(|account| {
+        let data = account.data();
+        if data.len() < 8 || &data[0..8] != Lockup::SPL_DISCRIMINATOR.as_slice() {
+            return None;
+        }
+        bytemuck::try_from_bytes::<Lockup>(data)
+            .ok()
+            .map(|lockup| lockup.authority)
+    });
+
+    if maybe_authority.is_none() {
+        warn!(
+            "failed to read lockup authority, skipping lockup [lockup={}]",
+            lockup_pubkey
+        );
+    }
+
+    maybe_authority
+}
+
+/// Returns the account's lamports above rent, zero if it doesn't exist.
+fn excess_balance(bank: &Bank, pubkey: &Pubkey) -> u64 {
+    bank.get_account(pubkey)
+        .map(|account| {
+            account
+                .lamports()
+                .saturating_sub(bank.get_minimum_balance_for_rent_exemption(account.data().len()))
+        })
+        .unwrap_or_default()
+}
+
 /// Given an [EpochStakes] object, return delegations grouped by voter_pubkey (validator delegated to).
 fn group_delegations_by_voter_pubkey(
     delegations: &im::HashMap<Pubkey, StakeAccount>,
@@ -358,6 +714,8 @@ mod tests {
         super::*,
         crate::derive_tip_distribution_account_address,
         anchor_lang::AccountSerialize,
+        bytemuck::Zeroable,
+        funnel::FunnelConfig,
         jito_tip_distribution::state::TipDistributionAccount,
         jito_tip_payment::{
             InitBumps, TipPaymentAccount, CONFIG_ACCOUNT_SEED, TIP_ACCOUNT_SEED_0,
@@ -673,7 +1031,8 @@ mod tests {
         let data_2 =
             tda_to_account_shared_data(&tip_distribution_program_id, tip_distro_2_tips, tda_2);
 
-        let accounts_data = create_config_account_data(&tip_payment_program_id, &bank);
+        let accounts_data =
+            create_config_account_data(&tip_payment_program_id, &Pubkey::new_unique(), &bank);
         for (pubkey, data) in accounts_data {
             bank.store_account(&pubkey, &data);
         }
@@ -687,8 +1046,12 @@ mod tests {
             &bank,
             &tip_distribution_program_id,
             &tip_payment_program_id,
+            None,
+            None,
+            None,
         )
         .unwrap();
+        assert!(stake_meta_collection.maybe_funnel_meta.is_none());
         assert_eq!(
             stake_meta_collection.tip_distribution_program_id,
             tip_distribution_program_id
@@ -832,6 +1195,220 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generate_stake_meta_collection_with_funnel() {
+        let validator_keypairs = ValidatorVoteKeypairs::new_rand();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &[&validator_keypairs],
+            vec![10_000],
+        );
+        let (bank, _bank_forks) = Bank::new_with_bank_forks_for_tests(&genesis_config);
+
+        let tip_distribution_program_id = Pubkey::new_unique();
+        let tip_payment_program_id = Pubkey::new_unique();
+        let funnel_pubkey = Pubkey::new_unique();
+        let lockup_pool_pubkey = Pubkey::new_unique();
+        let stakers_receiver = Pubkey::new_unique();
+        let holders_receiver = Pubkey::new_unique();
+
+        // The funnel is the tip receiver and 1_000_000 lamports sit in a tip account, 10% of which
+        // goes to the block builder.
+        for (pubkey, mut data) in
+            create_config_account_data(&tip_payment_program_id, &funnel_pubkey, &bank)
+        {
+            if pubkey
+                == Pubkey::find_program_address(&[TIP_ACCOUNT_SEED_0], &tip_payment_program_id).0
+            {
+                data.set_lamports(data.lamports() + 1_000_000);
+            }
+            bank.store_account(&pubkey, &data);
+        }
+
+        // The last leader's TDA is the funnel's receiver.
+        let tip_distribution_account = derive_tip_distribution_account_address(
+            &tip_distribution_program_id,
+            &validator_keypairs.vote_keypair.pubkey(),
+            bank.epoch(),
+        );
+        let tda = TipDistributionAccount {
+            validator_vote_account: validator_keypairs.vote_keypair.pubkey(),
+            merkle_root_upload_authority: Pubkey::new_unique(),
+            merkle_root: None,
+            epoch_created_at: bank.epoch(),
+            validator_commission_bps: 50,
+            expires_at: bank.epoch() + 3,
+            bump: tip_distribution_account.1,
+        };
+        let tda_rent = bank.get_minimum_balance_for_rent_exemption(TipDistributionAccount::SIZE);
+        bank.store_account(
+            &tip_distribution_account.0,
+            &tda_to_account_shared_data(&tip_distribution_program_id, tda_rent + 5_000, tda),
+        );
+
+        // The funnel holds another 100_000 unswept lamports, stakers and holders get 5% each.
+        let funnel = Funnel {
+            receiver: tip_distribution_account.0,
+            config: FunnelConfig {
+                stakers_receiver,
+                holders_receiver,
+                stakers_bps: 500,
+                holders_bps: 500,
+                commission_bps: 1_000,
+            },
+            authority: Pubkey::new_unique(),
+        };
+        let mut funnel_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(Funnel::LEN) + 100_000,
+            Funnel::LEN,
+            &funnel::ID,
+        );
+        funnel_data.set_data(funnel.as_bytes().to_vec());
+        bank.store_account(&funnel_pubkey, &funnel_data);
+
+        // The stakers receiver already holds swept tips, the holders receiver doesn't exist yet.
+        let receiver_rent = bank.get_minimum_balance_for_rent_exemption(0);
+        bank.store_account(
+            &stakers_receiver,
+            &AccountSharedData::new(receiver_rent + 20_000, 0, &solana_sdk::system_program::id()),
+        );
+
+        // Two lockups in the pool, one of which has no lockup account.
+        let (lockup_0, lockup_1) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let authority = Pubkey::new_unique();
+        let mut pool = LockupPool::zeroed();
+        pool.discriminator = LockupPool::SPL_DISCRIMINATOR.into();
+        for (entry, (lockup, amount)) in pool
+            .entries
+            .iter_mut()
+            .zip([(lockup_0, 600), (lockup_1, 400)])
+        {
+            entry.lockup = lockup;
+            entry.amount = amount;
+        }
+        pool.entries_len = 2;
+        let mut pool_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(LockupPool::LEN),
+            LockupPool::LEN,
+            &paladin_lockup_program::id(),
+        );
+        pool_data.set_data(bytemuck::bytes_of(&pool).to_vec());
+        bank.store_account(&lockup_pool_pubkey, &pool_data);
+
+        let mut lockup = Lockup::zeroed();
+        lockup.discriminator = Lockup::SPL_DISCRIMINATOR.into();
+        lockup.amount = 600;
+        lockup.authority = authority;
+        lockup.pool = lockup_pool_pubkey;
+        let mut lockup_data = AccountSharedData::new(
+            bank.get_minimum_balance_for_rent_exemption(Lockup::LEN),
+            Lockup::LEN,
+            &paladin_lockup_program::id(),
+        );
+        lockup_data.set_data(bytemuck::bytes_of(&lockup).to_vec());
+        bank.store_account(&lockup_0, &lockup_data);
+
+        bank.freeze();
+        let stake_meta_collection = generate_stake_meta_collection(
+            &bank,
+            &tip_distribution_program_id,
+            &tip_payment_program_id,
+            Some(&funnel_pubkey),
+            Some(&lockup_pool_pubkey),
+            None,
+        )
+        .unwrap();
+
+        // 900_000 swept from the tip accounts plus the funnel's 100_000. The lockup without a
+        // lockup account is left out.
+        let funnel_meta = FunnelMeta {
+            funnel_pubkey,
+            receiver: tip_distribution_account.0,
+            stakers_receiver,
+            holders_receiver,
+            unswept_tips: 1_000_000,
+            receiver_tips: 900_000,
+            stakers_receiver_balance: 20_000,
+            stakers_unswept_tips: 50_000,
+            holders_receiver_balance: 0,
+            holders_unswept_tips: 50_000,
+            total_stakers_tips: 70_000,
+            total_holders_tips: 50_000,
+            maybe_lockup_pool_meta: Some(LockupPoolMeta {
+                lockup_pool_pubkey,
+                lockups: vec![LockupMeta {
+                    lockup_pubkey: lockup_0,
+                    authority_pubkey: authority,
+                    amount: 600,
+                }],
+                total_locked: 600,
+            }),
+        };
+        assert_eq!(
+            stake_meta_collection.maybe_funnel_meta,
+            Some(funnel_meta.clone())
+        );
+
+        // Only what reached the receivers since the previous epoch counts.
+        let previous_funnel_meta = FunnelMeta {
+            stakers_receiver_balance: 15_000,
+            stakers_unswept_tips: 5_000,
+            holders_receiver_balance: 0,
+            holders_unswept_tips: 10_000,
+            ..funnel_meta.clone()
+        };
+        let stake_meta_collection = generate_stake_meta_collection(
+            &bank,
+            &tip_distribution_program_id,
+            &tip_payment_program_id,
+            Some(&funnel_pubkey),
+            Some(&lockup_pool_pubkey),
+            Some(&previous_funnel_meta),
+        )
+        .unwrap();
+        let actual_funnel_meta = stake_meta_collection.maybe_funnel_meta.unwrap();
+        assert_eq!(actual_funnel_meta.total_stakers_tips, 50_000);
+        assert_eq!(actual_funnel_meta.total_holders_tips, 40_000);
+
+        // Nothing is counted for a receiver holding less than was counted before, as it can't
+        // be told apart.
+        let previous_funnel_meta = FunnelMeta {
+            stakers_receiver_balance: 80_000,
+            holders_unswept_tips: 10_000,
+            ..funnel_meta
+        };
+        let actual_funnel_meta = generate_stake_meta_collection(
+            &bank,
+            &tip_distribution_program_id,
+            &tip_payment_program_id,
+            Some(&funnel_pubkey),
+            Some(&lockup_pool_pubkey),
+            Some(&previous_funnel_meta),
+        )
+        .unwrap()
+        .maybe_funnel_meta
+        .unwrap();
+        assert_eq!(actual_funnel_meta.total_stakers_tips, 0);
+        assert_eq!(actual_funnel_meta.total_holders_tips, 40_000);
+
+        // The receiver's TDA is credited with its share of the unswept tips.
+        let stake_meta = stake_meta_collection
+            .stake_metas
+            .iter()
+            .find(|stake_meta| {
+                stake_meta.validator_vote_account == validator_keypairs.vote_keypair.pubkey()
+            })
+            .unwrap();
+        assert_eq!(
+            stake_meta
+                .maybe_tip_distribution_meta
+                .as_ref()
+                .unwrap()
+                .total_tips,
+            905_000
+        );
+    }
+
     /// Helper function that sends a delegate stake instruction to the bank.
     /// Returns the created stake account pubkey.
     fn delegate_stake_helper(
@@ -904,6 +1481,7 @@ mod tests {
 
     fn create_config_account_data(
         tip_payment_program_id: &Pubkey,
+        tip_receiver: &Pubkey,
         bank: &Bank,
     ) -> Vec<(Pubkey, AccountSharedData)> {
         let mut account_datas = vec![];
@@ -923,7 +1501,7 @@ mod tests {
         ];
 
         let config = Config {
-            tip_receiver: Pubkey::new_unique(),
+            tip_receiver: *tip_receiver,
             block_builder: Pubkey::new_unique(),
             block_builder_commission_pct: 10,
             bumps: InitBumps {
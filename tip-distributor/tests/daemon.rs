@@ -0,0 +1,296 @@
+use {
+    anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas},
+    jito_tip_distribution::{
+        sdk::{
+            derive_config_account_address,
+            instruction::{
+                initialize_ix, initialize_tip_distribution_account_ix, InitializeAccounts,
+                InitializeArgs, InitializeTipDistributionAccountAccounts,
+                InitializeTipDistributionAccountArgs,
+            },
+        },
+        state::TipDistributionAccount,
+    },
+    jito_tip_payment::{
+        InitBumps, CONFIG_ACCOUNT_SEED, TIP_ACCOUNT_SEED_0, TIP_ACCOUNT_SEED_1, TIP_ACCOUNT_SEED_2,
+        TIP_ACCOUNT_SEED_3, TIP_ACCOUNT_SEED_4, TIP_ACCOUNT_SEED_5, TIP_ACCOUNT_SEED_6,
+        TIP_ACCOUNT_SEED_7,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        epoch_schedule::EpochSchedule,
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+        system_instruction, system_program,
+        transaction::Transaction,
+    },
+    solana_test_validator::{TestValidator, TestValidatorGenesis},
+    solana_tip_distributor::{
+        daemon_workflow::{Checkpoint, DaemonConfig, DistributionStep, TipDistributorDaemon},
+        derive_tip_distribution_account_address, read_json_from_file,
+        GeneratedMerkleTreeCollection,
+    },
+    std::{path::Path, sync::atomic::AtomicBool, thread::sleep, time::Duration},
+    tempfile::TempDir,
+};
+
+const SLOTS_PER_EPOCH: u64 = 64;
+const TIPS: u64 = 10 * LAMPORTS_PER_SOL;
+
+fn send_and_confirm(rpc_client: &RpcClient, instructions: &[Instruction], signers: &[&Keypair]) {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signers[0].pubkey()),
+        signers,
+        rpc_client.get_latest_blockhash().unwrap(),
+    );
+    rpc_client
+        .send_and_confirm_transaction(&transaction)
+        .unwrap();
+}
+
+fn initialize_tip_payment_program(rpc_client: &RpcClient, payer: &Keypair) {
+    let program_id = jito_tip_payment::id();
+    let config = Pubkey::find_program_address(&[CONFIG_ACCOUNT_SEED], &program_id);
+    if rpc_client
+        .get_account_with_commitment(&config.0, CommitmentConfig::confirmed())
+        .unwrap()
+        .value
+        .is_some()
+    {
+        return;
+    }
+
+    let tip_pdas = [
+        TIP_ACCOUNT_SEED_0,
+        TIP_ACCOUNT_SEED_1,
+        TIP_ACCOUNT_SEED_2,
+        TIP_ACCOUNT_SEED_3,
+        TIP_ACCOUNT_SEED_4,
+        TIP_ACCOUNT_SEED_5,
+        TIP_ACCOUNT_SEED_6,
+        TIP_ACCOUNT_SEED_7,
+    ]
+    .map(|seed| Pubkey::find_program_address(&[seed], &program_id));
+    let init_ix = Instruction {
+        program_id,
+        data: jito_tip_payment::instruction::Initialize {
+            _bumps: InitBumps {
+                config: config.1,
+                tip_payment_account_0: tip_pdas[0].1,
+                tip_payment_account_1: tip_pdas[1].1,
+                tip_payment_account_2: tip_pdas[2].1,
+                tip_payment_account_3: tip_pdas[3].1,
+                tip_payment_account_4: tip_pdas[4].1,
+                tip_payment_account_5: tip_pdas[5].1,
+                tip_payment_account_6: tip_pdas[6].1,
+                tip_payment_account_7: tip_pdas[7].1,
+            },
+        }
+        .data(),
+        accounts: jito_tip_payment::accounts::Initialize {
+            config: config.0,
+            tip_payment_account_0: tip_pdas[0].0,
+            tip_payment_account_1: tip_pdas[1].0,
+            tip_payment_account_2: tip_pdas[2].0,
+            tip_payment_account_3: tip_pdas[3].0,
+            tip_payment_account_4: tip_pdas[4].0,
+            tip_payment_account_5: tip_pdas[5].0,
+            tip_payment_account_6: tip_pdas[6].0,
+            tip_payment_account_7: tip_pdas[7].0,
+            system_program: system_program::id(),
+            payer: payer.pubkey(),
+        }
+        .to_account_metas(None),
+    };
+    send_and_confirm(rpc_client, &[init_ix], &[payer]);
+}
+
+/// Initializes the tip-distribution program and the validator's [TipDistributionAccount] for
+/// epoch 0 with `mint_keypair` as the merkle root upload authority, then tips it.
+fn initialize_tip_distribution_account(
+    test_validator: &TestValidator,
+    ledger_path: &Path,
+    mint_keypair: &Keypair,
+) -> Pubkey {
+    let rpc_client =
+        RpcClient::new_with_commitment(test_validator.rpc_url(), CommitmentConfig::confirmed());
+    let program_id = jito_tip_distribution::id();
+    let identity = read_keypair_file(ledger_path.join("validator-keypair.json")).unwrap();
+    let vote_pubkey = test_validator.vote_account_address();
+
+    initialize_tip_payment_program(&rpc_client, mint_keypair);
+
+    let (config, config_bump) = derive_config_account_address(&program_id);
+    send_and_confirm(
+        &rpc_client,
+        &[initialize_ix(
+            program_id,
+            InitializeArgs {
+                authority: mint_keypair.pubkey(),
+                expired_funds_account: mint_keypair.pubkey(),
+                num_epochs_valid: 10,
+                max_validator_commission_bps: 10_000,
+                bump: config_bump,
+            },
+            InitializeAccounts {
+                config,
+                system_program: system_program::id(),
+                initializer: mint_keypair.pubkey(),
+            },
+        )],
+        &[mint_keypair],
+    );
+
+    let (tip_distribution_account, tip_distribution_bump) =
+        derive_tip_distribution_account_address(&program_id, &vote_pubkey, 0);
+    send_and_confirm(
+        &rpc_client,
+        &[
+            system_instruction::transfer(
+                &mint_keypair.pubkey(),
+                &identity.pubkey(),
+                LAMPORTS_PER_SOL,
+            ),
+            initialize_tip_distribution_account_ix(
+                program_id,
+                InitializeTipDistributionAccountArgs {
+                    merkle_root_upload_authority: mint_keypair.pubkey(),
+                    validator_commission_bps: 800,
+                    bump: tip_distribution_bump,
+                },
+                InitializeTipDistributionAccountAccounts {
+                    config,
+                    signer: identity.pubkey(),
+                    system_program: system_program::id(),
+                    tip_distribution_account,
+                    validator_vote_account: vote_pubkey,
+                },
+            ),
+            system_instruction::transfer(&mint_keypair.pubkey(), &tip_distribution_account, TIPS),
+        ],
+        &[mint_keypair, &identity],
+    );
+
+    tip_distribution_account
+}
+
+fn new_daemon(ledger_path: &Path, working_dir: &Path, rpc_url: String) -> TipDistributorDaemon {
+    TipDistributorDaemon::new(DaemonConfig {
+        ledger_path: ledger_path.to_path_buf(),
+        snapshots_dir: ledger_path.to_path_buf(),
+        working_dir: working_dir.to_path_buf(),
+        rpc_url,
+        keypair_path: working_dir.join("keypair.json"),
+        tip_distribution_program_id: jito_tip_distribution::id(),
+        tip_payment_program_id: jito_tip_payment::id(),
+        maybe_funnel_pubkey: None,
+        maybe_lockup_pool_pubkey: None,
+        maybe_start_epoch: Some(0),
+        max_concurrent_rpc_get_reqs: 100,
+        txn_send_batch_size: 64,
+        max_retry_duration: Duration::from_secs(60),
+        micro_lamports: 1,
+        should_reclaim_tdas: false,
+        poll_interval: Duration::from_secs(1),
+    })
+    .unwrap()
+}
+
+fn distribute_next_epoch(daemon: &TipDistributorDaemon) -> Checkpoint {
+    loop {
+        if let Some(checkpoint) = daemon
+            .distribute_next_epoch(&AtomicBool::new(false))
+            .unwrap()
+        {
+            return checkpoint;
+        }
+        sleep(Duration::from_secs(1));
+    }
+}
+
+fn assert_distributed(rpc_client: &RpcClient, daemon: &TipDistributorDaemon, tda: &Pubkey) {
+    let merkle_trees: GeneratedMerkleTreeCollection =
+        read_json_from_file(&daemon.merkle_trees_path(0)).unwrap();
+    assert_eq!(merkle_trees.epoch, 0);
+    let merkle_tree = merkle_trees
+        .generated_merkle_trees
+        .iter()
+        .find(|tree| tree.tip_distribution_account == *tda)
+        .unwrap();
+
+    let account = rpc_client.get_account(tda).unwrap();
+    let tip_distribution_account =
+        TipDistributionAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+    let merkle_root = tip_distribution_account.merkle_root.unwrap();
+    assert_eq!(merkle_root.root, merkle_tree.merkle_root.to_bytes());
+    assert_eq!(merkle_root.max_total_claim, merkle_tree.max_total_claim);
+
+    assert!(!merkle_tree.tree_nodes.is_empty());
+    for tree_node in &merkle_tree.tree_nodes {
+        assert!(rpc_client
+            .get_account_with_commitment(&tree_node.claim_status_pubkey, rpc_client.commitment())
+            .unwrap()
+            .value
+            .is_some());
+    }
+}
+
+#[test]
+fn test_daemon_distributes_and_resumes() {
+    let ledger_dir = TempDir::new().unwrap();
+    let working_dir = TempDir::new().unwrap();
+    let (test_validator, mint_keypair) = TestValidatorGenesis::default()
+        .ledger_path(ledger_dir.path())
+        .epoch_schedule(EpochSchedule::custom(
+            SLOTS_PER_EPOCH,
+            SLOTS_PER_EPOCH,
+            /* enable_warmup_epochs = */ false,
+        ))
+        .start();
+    let rpc_client =
+        RpcClient::new_with_commitment(test_validator.rpc_url(), CommitmentConfig::confirmed());
+
+    let tda =
+        initialize_tip_distribution_account(&test_validator, ledger_dir.path(), &mint_keypair);
+    write_keypair_file(&mint_keypair, working_dir.path().join("keypair.json")).unwrap();
+
+    let daemon = new_daemon(
+        ledger_dir.path(),
+        working_dir.path(),
+        test_validator.rpc_url(),
+    );
+    let checkpoint = distribute_next_epoch(&daemon);
+    assert_eq!(checkpoint.epoch, 0);
+    assert_eq!(checkpoint.next_step(), None);
+    assert_eq!(
+        Checkpoint::load(&daemon.checkpoint_path()).unwrap(),
+        Some(checkpoint)
+    );
+    assert_distributed(&rpc_client, &daemon, &tda);
+
+    // a daemon that crashed after generating the merkle trees resumes by uploading them, which is
+    // harmless as the roots are already uploaded
+    Checkpoint {
+        epoch: 0,
+        maybe_completed_step: Some(DistributionStep::GenerateMerkleTrees),
+    }
+    .save(&daemon.checkpoint_path())
+    .unwrap();
+    drop(daemon);
+    let daemon = new_daemon(
+        ledger_dir.path(),
+        working_dir.path(),
+        test_validator.rpc_url(),
+    );
+    let checkpoint = daemon
+        .distribute_next_epoch(&AtomicBool::new(false))
+        .unwrap()
+        .unwrap();
+    assert_eq!(checkpoint.epoch, 0);
+    assert_eq!(checkpoint.next_step(), None);
+    assert_distributed(&rpc_client, &daemon, &tda);
+}
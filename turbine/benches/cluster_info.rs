@@ -24,6 +24,7 @@ use {
             broadcast_metrics::TransmitShredsStats, broadcast_shreds, BroadcastStage,
         },
         cluster_nodes::ClusterNodesCache,
+        shred_receiver::ShredReceivers,
     },
     std::{collections::HashMap, net::UdpSocket, sync::Arc, time::Duration},
     test::Bencher,
@@ -76,7 +77,7 @@ fn broadcast_shreds_bench(bencher: &mut Bencher) {
             &bank_forks,
             &SocketAddrSpace::Unspecified,
             &quic_endpoint_sender,
-            &None,
+            &ShredReceivers::default(),
         )
         .unwrap();
     });
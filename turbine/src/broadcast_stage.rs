@@ -8,7 +8,10 @@ use {
         fail_entry_verification_broadcast_run::FailEntryVerificationBroadcastRun,
         standard_broadcast_run::StandardBroadcastRun,
     },
-    crate::cluster_nodes::{self, ClusterNodes, ClusterNodesCache},
+    crate::{
+        cluster_nodes::{self, ClusterNodes, ClusterNodesCache},
+        shred_receiver::ShredReceivers,
+    },
     bytes::Bytes,
     crossbeam_channel::{unbounded, Receiver, RecvError, RecvTimeoutError, Sender},
     itertools::{Either, Itertools},
@@ -118,7 +121,7 @@ impl BroadcastStageType {
         bank_forks: Arc<RwLock<BankForks>>,
         shred_version: u16,
         quic_endpoint_sender: AsyncSender<(SocketAddr, Bytes)>,
-        shred_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: Arc<ShredReceivers>,
     ) -> BroadcastStage {
         match self {
             BroadcastStageType::Standard => BroadcastStage::new(
@@ -131,7 +134,7 @@ impl BroadcastStageType {
                 bank_forks,
                 quic_endpoint_sender,
                 StandardBroadcastRun::new(shred_version),
-                shred_receiver_address,
+                shred_receivers,
             ),
 
             BroadcastStageType::FailEntryVerification => BroadcastStage::new(
@@ -144,7 +147,7 @@ impl BroadcastStageType {
                 bank_forks,
                 quic_endpoint_sender,
                 FailEntryVerificationBroadcastRun::new(shred_version),
-                Arc::new(RwLock::new(None)),
+                Arc::default(),
             ),
 
             BroadcastStageType::BroadcastFakeShreds => BroadcastStage::new(
@@ -157,7 +160,7 @@ impl BroadcastStageType {
                 bank_forks,
                 quic_endpoint_sender,
                 BroadcastFakeShredsRun::new(0, shred_version),
-                Arc::new(RwLock::new(None)),
+                Arc::default(),
             ),
 
             BroadcastStageType::BroadcastDuplicates(config) => BroadcastStage::new(
@@ -170,7 +173,7 @@ impl BroadcastStageType {
                 bank_forks,
                 quic_endpoint_sender,
                 BroadcastDuplicatesRun::new(shred_version, config.clone()),
-                Arc::new(RwLock::new(None)),
+                Arc::default(),
             ),
         }
     }
@@ -192,7 +195,7 @@ trait BroadcastRun {
         sock: &UdpSocket,
         bank_forks: &RwLock<BankForks>,
         quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
-        shred_receiver_address: &Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: &ShredReceivers,
     ) -> Result<()>;
     fn record(&mut self, receiver: &RecordReceiver, blockstore: &Blockstore) -> Result<()>;
 }
@@ -289,7 +292,7 @@ impl BroadcastStage {
         bank_forks: Arc<RwLock<BankForks>>,
         quic_endpoint_sender: AsyncSender<(SocketAddr, Bytes)>,
         mut broadcast_stage_run: impl BroadcastRun + Send + 'static + Clone,
-        shred_receiver_address: Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: Arc<ShredReceivers>,
     ) -> Self {
         let (socket_sender, socket_receiver) = unbounded();
         let (blockstore_sender, blockstore_receiver) = unbounded();
@@ -321,7 +324,7 @@ impl BroadcastStage {
             let cluster_info = cluster_info.clone();
             let bank_forks = bank_forks.clone();
             let quic_endpoint_sender = quic_endpoint_sender.clone();
-            let shred_receiver_address = shred_receiver_address.clone();
+            let shred_receivers = shred_receivers.clone();
 
             let run_transmit = move || loop {
                 let res = bs_transmit.transmit(
@@ -330,7 +333,7 @@ impl BroadcastStage {
                     &sock,
                     &bank_forks,
                     &quic_endpoint_sender,
-                    &shred_receiver_address,
+                    &shred_receivers,
                 );
                 let res = Self::handle_error(res, "solana-broadcaster-transmit");
                 if let Some(res) = res {
@@ -451,7 +454,7 @@ pub fn broadcast_shreds(
     bank_forks: &RwLock<BankForks>,
     socket_addr_space: &SocketAddrSpace,
     quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
-    shred_receiver_address: &Option<SocketAddr>,
+    shred_receivers: &ShredReceivers,
 ) -> Result<()> {
     let mut result = Ok(());
     let mut shred_select = Measure::start("shred_select");
@@ -471,11 +474,7 @@ pub fn broadcast_shreds(
                 let key = shred.id();
                 let protocol = cluster_nodes::get_broadcast_protocol(&key);
 
-                let mut addrs = Vec::with_capacity(2);
-                if let Some(shred_receiver_address) = shred_receiver_address {
-                    // Assuming always over UDP for shred_receiver_address
-                    addrs.push((Protocol::UDP, *shred_receiver_address));
-                }
+                let mut addrs = Vec::with_capacity(1);
                 if let Some(peer) = cluster_nodes.get_broadcast_peer(&key) {
                     match protocol {
                         Protocol::QUIC => {
@@ -506,6 +505,12 @@ pub fn broadcast_shreds(
     shred_select.stop();
     transmit_stats.shred_select += shred_select.as_us();
 
+    let receiver_shreds: Vec<_> = shreds
+        .iter()
+        .map(|shred| (shred.shred_type(), &shred.payload()[..]))
+        .collect();
+    shred_receivers.send_shreds(&receiver_shreds, false, s, quic_endpoint_sender);
+
     let mut send_mmsg_time = Measure::start("send_mmsg");
     match batch_send(s, &packets[..]) {
         Ok(()) => (),
@@ -729,7 +734,7 @@ pub mod test {
             bank_forks,
             quic_endpoint_sender,
             StandardBroadcastRun::new(0),
-            Arc::new(RwLock::new(None)),
+            Arc::default(),
         );
 
         MockBroadcastStage {
@@ -5,7 +5,7 @@ use {
         broadcast_utils::{self, ReceiveResults},
         *,
     },
-    crate::cluster_nodes::ClusterNodesCache,
+    crate::{cluster_nodes::ClusterNodesCache, shred_receiver::ShredReceivers},
     solana_entry::entry::Entry,
     solana_ledger::{
         blockstore,
@@ -182,7 +182,7 @@ impl StandardBroadcastRun {
             sock,
             bank_forks,
             quic_endpoint_sender,
-            &Arc::new(RwLock::new(None)),
+            &ShredReceivers::default(),
         );
         let _ = self.record(&brecv, blockstore);
         //coding
@@ -192,7 +192,7 @@ impl StandardBroadcastRun {
             sock,
             bank_forks,
             quic_endpoint_sender,
-            &Arc::new(RwLock::new(None)),
+            &ShredReceivers::default(),
         );
         let _ = self.record(&brecv, blockstore);
         Ok(())
@@ -416,7 +416,7 @@ impl StandardBroadcastRun {
         broadcast_shred_batch_info: Option<BroadcastShredBatchInfo>,
         bank_forks: &RwLock<BankForks>,
         quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
-        shred_receiver_addr: &Option<SocketAddr>,
+        shred_receivers: &ShredReceivers,
     ) -> Result<()> {
         trace!("Broadcasting {:?} shreds", shreds.len());
         let mut transmit_stats = TransmitShredsStats::default();
@@ -433,7 +433,7 @@ impl StandardBroadcastRun {
             bank_forks,
             cluster_info.socket_addr_space(),
             quic_endpoint_sender,
-            shred_receiver_addr,
+            shred_receivers,
         )?;
         transmit_time.stop();
 
@@ -501,7 +501,7 @@ impl BroadcastRun for StandardBroadcastRun {
         sock: &UdpSocket,
         bank_forks: &RwLock<BankForks>,
         quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
-        shred_receiver_address: &Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: &ShredReceivers,
     ) -> Result<()> {
         let (shreds, batch_info) = receiver.recv()?;
         self.broadcast(
@@ -511,7 +511,7 @@ impl BroadcastRun for StandardBroadcastRun {
             batch_info,
             bank_forks,
             quic_endpoint_sender,
-            &shred_receiver_address.read().unwrap(),
+            shred_receivers,
         )
     }
     fn record(&mut self, receiver: &RecordReceiver, blockstore: &Blockstore) -> Result<()> {
@@ -4,6 +4,7 @@ pub mod broadcast_stage;
 pub mod cluster_nodes;
 pub mod quic_endpoint;
 pub mod retransmit_stage;
+pub mod shred_receiver;
 pub mod sigverify_shreds;
 
 #[macro_use]
@@ -2,7 +2,10 @@
 #![allow(clippy::rc_buffer)]
 
 use {
-    crate::cluster_nodes::{self, ClusterNodes, ClusterNodesCache, Error, MAX_NUM_TURBINE_HOPS},
+    crate::{
+        cluster_nodes::{self, ClusterNodes, ClusterNodesCache, Error, MAX_NUM_TURBINE_HOPS},
+        shred_receiver::ShredReceivers,
+    },
     bytes::Bytes,
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::{izip, Itertools},
@@ -188,7 +191,7 @@ fn retransmit(
     max_slots: &MaxSlots,
     rpc_subscriptions: Option<&RpcSubscriptions>,
     slot_status_notifier: Option<&SlotStatusNotifier>,
-    shred_receiver_address: &Arc<RwLock<Option<SocketAddr>>>,
+    shred_receivers: &ShredReceivers,
 ) -> Result<(), RecvTimeoutError> {
     const RECV_TIMEOUT: Duration = Duration::from_secs(1);
     let mut shreds = shreds_receiver.recv_timeout(RECV_TIMEOUT)?;
@@ -256,20 +259,19 @@ fn retransmit(
     let slot_stats = if shreds.len() < PAR_ITER_MIN_NUM_SHREDS {
         stats.num_small_batches += 1;
         shreds
-            .into_iter()
+            .iter()
             .enumerate()
             .filter_map(|(index, ((key, shred), slot_leader, cluster_nodes))| {
                 let (root_distance, num_nodes) = retransmit_shred(
-                    &key,
-                    &shred,
-                    &slot_leader,
+                    key,
+                    shred,
+                    slot_leader,
                     &root_bank,
-                    &cluster_nodes,
+                    cluster_nodes,
                     socket_addr_space,
                     &sockets[index % sockets.len()],
                     quic_endpoint_sender,
                     stats,
-                    &shred_receiver_address.read().unwrap(),
                 )
                 .inspect_err(|err| {
                     stats.record_error(err);
@@ -281,20 +283,19 @@ fn retransmit(
     } else {
         thread_pool.install(|| {
             shreds
-                .into_par_iter()
+                .par_iter()
                 .filter_map(|((key, shred), slot_leader, cluster_nodes)| {
                     let index = thread_pool.current_thread_index().unwrap();
                     let (root_distance, num_nodes) = retransmit_shred(
-                        &key,
-                        &shred,
-                        &slot_leader,
+                        key,
+                        shred,
+                        slot_leader,
                         &root_bank,
-                        &cluster_nodes,
+                        cluster_nodes,
                         socket_addr_space,
                         &sockets[index % sockets.len()],
                         quic_endpoint_sender,
                         stats,
-                        &shred_receiver_address.read().unwrap(),
                     )
                     .inspect_err(|err| {
                         stats.record_error(err);
@@ -306,6 +307,12 @@ fn retransmit(
                 .reduce(HashMap::new, RetransmitSlotStats::merge)
         })
     };
+    // Shred receivers get the whole batch at once, after turbine peers.
+    let receiver_shreds: Vec<_> = shreds
+        .iter()
+        .map(|((key, shred), _, _)| (key.shred_type(), &shred[..]))
+        .collect();
+    shred_receivers.send_shreds(&receiver_shreds, true, &sockets[0], quic_endpoint_sender);
     stats.upsert_slot_stats(
         slot_stats,
         root_bank.slot(),
@@ -329,19 +336,15 @@ fn retransmit_shred(
     socket: &UdpSocket,
     quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
     stats: &RetransmitStats,
-    shred_receiver_addr: &Option<SocketAddr>,
 ) -> Result<(/*root_distance:*/ usize, /*num_nodes:*/ usize), Error> {
     let mut compute_turbine_peers = Measure::start("turbine_start");
     let data_plane_fanout = cluster_nodes::get_data_plane_fanout(key.slot(), root_bank);
     let (root_distance, addrs) =
         cluster_nodes.get_retransmit_addrs(slot_leader, key, data_plane_fanout)?;
-    let mut addrs: Vec<_> = addrs
+    let addrs: Vec<_> = addrs
         .into_iter()
         .filter(|addr| socket_addr_space.check(addr))
         .collect();
-    if let Some(addr) = shred_receiver_addr {
-        addrs.push(*addr);
-    }
 
     compute_turbine_peers.stop();
     stats
@@ -401,7 +404,7 @@ pub fn retransmitter(
     max_slots: Arc<MaxSlots>,
     rpc_subscriptions: Option<Arc<RpcSubscriptions>>,
     slot_status_notifier: Option<SlotStatusNotifier>,
-    shred_receiver_addr: Arc<RwLock<Option<SocketAddr>>>,
+    shred_receivers: Arc<ShredReceivers>,
 ) -> JoinHandle<()> {
     let cluster_nodes_cache = ClusterNodesCache::<RetransmitStage>::new(
         CLUSTER_NODES_CACHE_NUM_EPOCH_CAP,
@@ -434,7 +437,7 @@ pub fn retransmitter(
                 &max_slots,
                 rpc_subscriptions.as_deref(),
                 slot_status_notifier.as_ref(),
-                &shred_receiver_addr,
+                &shred_receivers,
             ) {
                 Ok(()) => (),
                 Err(RecvTimeoutError::Timeout) => (),
@@ -460,7 +463,7 @@ impl RetransmitStage {
         max_slots: Arc<MaxSlots>,
         rpc_subscriptions: Option<Arc<RpcSubscriptions>>,
         slot_status_notifier: Option<SlotStatusNotifier>,
-        shred_receiver_addr: Arc<RwLock<Option<SocketAddr>>>,
+        shred_receivers: Arc<ShredReceivers>,
     ) -> Self {
         let retransmit_thread_handle = retransmitter(
             retransmit_sockets,
@@ -472,7 +475,7 @@ impl RetransmitStage {
             max_slots,
             rpc_subscriptions,
             slot_status_notifier,
-            shred_receiver_addr,
+            shred_receivers,
         );
 
         Self {
@@ -0,0 +1,475 @@
+//! Destinations outside of turbine that shreds are sent to, e.g. indexers, backup rpc nodes and
+//! shredstream proxies.
+use {
+    bytes::Bytes,
+    solana_gossip::contact_info::Protocol,
+    solana_ledger::shred::ShredType,
+    solana_sdk::timing::AtomicInterval,
+    solana_streamer::sendmmsg::{batch_send, SendPktsError},
+    std::{
+        net::{SocketAddr, UdpSocket},
+        str::FromStr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+    },
+    tokio::sync::mpsc::Sender as AsyncSender,
+};
+
+const METRICS_SUBMIT_INTERVAL_MS: u64 = 2_000;
+
+pub struct ShredReceiver {
+    pub addr: SocketAddr,
+    /// Only receives the shreds this node broadcasts as leader, not those it retransmits.
+    pub leader_slots_only: bool,
+    /// Only receives the shreds this node retransmits, not those it broadcasts as leader.
+    pub retransmit_slots_only: bool,
+    /// Only receives data shreds, not coding shreds.
+    pub data_shreds_only: bool,
+    pub protocol: Protocol,
+    num_sent: AtomicU64,
+    num_dropped: AtomicU64,
+}
+
+impl ShredReceiver {
+    pub fn new(
+        addr: SocketAddr,
+        leader_slots_only: bool,
+        retransmit_slots_only: bool,
+        data_shreds_only: bool,
+        protocol: Protocol,
+    ) -> Result<Self, String> {
+        if leader_slots_only && retransmit_slots_only {
+            return Err(format!(
+                "shred receiver {addr} can't be both leader slots only and retransmit slots only"
+            ));
+        }
+        Ok(Self {
+            addr,
+            leader_slots_only,
+            retransmit_slots_only,
+            data_shreds_only,
+            protocol,
+            num_sent: AtomicU64::default(),
+            num_dropped: AtomicU64::default(),
+        })
+    }
+
+    pub fn num_sent(&self) -> u64 {
+        self.num_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn num_dropped(&self) -> u64 {
+        self.num_dropped.load(Ordering::Relaxed)
+    }
+
+    fn wants_slots(&self, retransmit: bool) -> bool {
+        if retransmit {
+            !self.leader_slots_only
+        } else {
+            !self.retransmit_slots_only
+        }
+    }
+
+    fn wants(&self, shred_type: ShredType) -> bool {
+        !self.data_shreds_only || shred_type == ShredType::Data
+    }
+
+    /// Whether both receivers are at the same address and receive the shreds of some of the same
+    /// slots, i.e. unless one only receives leader slots and the other retransmit slots.
+    fn overlaps(&self, other: &ShredReceiver) -> bool {
+        self.addr == other.addr
+            && ((self.wants_slots(false) && other.wants_slots(false))
+                || (self.wants_slots(true) && other.wants_slots(true)))
+    }
+}
+
+/// Parses `ADDRESS[,leader-slots-only|,retransmit-slots-only][,data-shreds-only][,quic]`.
+impl FromStr for ShredReceiver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let addr = parts.next().unwrap_or_default();
+        let addr = SocketAddr::from_str(addr)
+            .map_err(|_| format!("invalid shred receiver address: {addr}"))?;
+        let (mut leader_slots_only, mut retransmit_slots_only, mut data_shreds_only) =
+            (false, false, false);
+        let mut protocol = Protocol::UDP;
+        for option in parts {
+            match option {
+                "leader-slots-only" => leader_slots_only = true,
+                "retransmit-slots-only" => retransmit_slots_only = true,
+                "data-shreds-only" => data_shreds_only = true,
+                "quic" => protocol = Protocol::QUIC,
+                option => return Err(format!("invalid shred receiver option: {option}")),
+            }
+        }
+        Self::new(
+            addr,
+            leader_slots_only,
+            retransmit_slots_only,
+            data_shreds_only,
+            protocol,
+        )
+    }
+}
+
+/// Shred receivers shared by the broadcast and retransmit stages and updated through the admin
+/// rpc.
+#[derive(Default)]
+pub struct ShredReceivers {
+    receivers: RwLock<Vec<Arc<ShredReceiver>>>,
+    last_metrics_submit: AtomicInterval,
+}
+
+impl ShredReceivers {
+    pub fn new(receivers: impl IntoIterator<Item = ShredReceiver>) -> Self {
+        let shred_receivers = Self::default();
+        for receiver in receivers {
+            shred_receivers.add(receiver);
+        }
+        shred_receivers
+    }
+
+    /// Replaces the receivers at the same address that receive any of the same slots, so a leader
+    /// slots only and a retransmit slots only receiver can share an address.
+    pub fn add(&self, receiver: ShredReceiver) {
+        let mut receivers = self.receivers.write().unwrap();
+        receivers.retain(|other| !other.overlaps(&receiver));
+        receivers.push(Arc::new(receiver));
+    }
+
+    /// Removes the receivers at the address, returning whether there were any.
+    pub fn remove(&self, addr: &SocketAddr) -> bool {
+        let mut receivers = self.receivers.write().unwrap();
+        let num_receivers = receivers.len();
+        receivers.retain(|receiver| receiver.addr != *addr);
+        receivers.len() != num_receivers
+    }
+
+    /// Replaces the udp receivers of all data and coding shreds of either leader or retransmit
+    /// slots with one at `addr`, or removes them if `addr` is `None`. This is the single address
+    /// that `setShredReceiverAddress` and `setShredRetransmitReceiverAddress` used to set.
+    pub fn set_address(&self, addr: Option<SocketAddr>, retransmit: bool) {
+        let maybe_receiver = addr.map(|addr| {
+            ShredReceiver::new(addr, !retransmit, retransmit, false, Protocol::UDP)
+                .expect("leader slots only and retransmit slots only are exclusive")
+        });
+        let mut receivers = self.receivers.write().unwrap();
+        receivers.retain(|receiver| {
+            let replaced = receiver.protocol == Protocol::UDP
+                && !receiver.data_shreds_only
+                && receiver.leader_slots_only == !retransmit
+                && receiver.retransmit_slots_only == retransmit;
+            !replaced
+                && !maybe_receiver
+                    .as_ref()
+                    .is_some_and(|new_receiver| receiver.overlaps(new_receiver))
+        });
+        if let Some(receiver) = maybe_receiver {
+            receivers.push(Arc::new(receiver));
+        }
+    }
+
+    pub fn list(&self) -> Vec<Arc<ShredReceiver>> {
+        self.receivers.read().unwrap().clone()
+    }
+
+    /// Sends each receiver the `shreds` it wants in one batch. `retransmit` is set for shreds of
+    /// other leaders' slots.
+    pub(crate) fn send_shreds(
+        &self,
+        shreds: &[(ShredType, &[u8])],
+        retransmit: bool,
+        socket: &UdpSocket,
+        quic_endpoint_sender: &AsyncSender<(SocketAddr, Bytes)>,
+    ) {
+        let receivers = self.receivers.read().unwrap();
+        for receiver in receivers.iter() {
+            if !receiver.wants_slots(retransmit) {
+                continue;
+            }
+            let payloads: Vec<_> = shreds
+                .iter()
+                .filter(|(shred_type, _)| receiver.wants(*shred_type))
+                .map(|(_, payload)| *payload)
+                .collect();
+            if payloads.is_empty() {
+                continue;
+            }
+            let num_dropped = match receiver.protocol {
+                Protocol::UDP => {
+                    let packets: Vec<_> = payloads
+                        .iter()
+                        .map(|payload| (*payload, &receiver.addr))
+                        .collect();
+                    match batch_send(socket, &packets) {
+                        Ok(()) => 0,
+                        Err(SendPktsError::IoError(_, num_failed)) => num_failed,
+                    }
+                }
+                // Never block turbine on an extra destination
+                Protocol::QUIC => payloads
+                    .iter()
+                    .filter(|payload| {
+                        quic_endpoint_sender
+                            .try_send((receiver.addr, Bytes::copy_from_slice(payload)))
+                            .is_err()
+                    })
+                    .count(),
+            };
+            receiver
+                .num_sent
+                .fetch_add((payloads.len() - num_dropped) as u64, Ordering::Relaxed);
+            receiver
+                .num_dropped
+                .fetch_add(num_dropped as u64, Ordering::Relaxed);
+        }
+        drop(receivers);
+        self.maybe_submit_metrics();
+    }
+
+    fn maybe_submit_metrics(&self) {
+        if !self
+            .last_metrics_submit
+            .should_update(METRICS_SUBMIT_INTERVAL_MS)
+        {
+            return;
+        }
+        for receiver in self.list() {
+            datapoint_info!(
+                "shred_receiver-stats",
+                ("addr", receiver.addr.to_string(), String),
+                ("num_sent", receiver.num_sent(), i64),
+                ("num_dropped", receiver.num_dropped(), i64),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio::sync::mpsc::channel};
+
+    fn shred_receiver(
+        socket: &UdpSocket,
+        leader_slots_only: bool,
+        retransmit_slots_only: bool,
+    ) -> ShredReceiver {
+        ShredReceiver::new(
+            socket.local_addr().unwrap(),
+            leader_slots_only,
+            retransmit_slots_only,
+            false,
+            Protocol::UDP,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_shred_receiver() {
+        let shred_receiver = ShredReceiver::from_str("127.0.0.1:8000").unwrap();
+        assert_eq!(shred_receiver.addr, "127.0.0.1:8000".parse().unwrap());
+        assert!(!shred_receiver.leader_slots_only);
+        assert!(!shred_receiver.retransmit_slots_only);
+        assert!(!shred_receiver.data_shreds_only);
+        assert!(shred_receiver.protocol == Protocol::UDP);
+
+        let shred_receiver =
+            ShredReceiver::from_str("127.0.0.1:8000,quic,data-shreds-only,leader-slots-only")
+                .unwrap();
+        assert!(shred_receiver.leader_slots_only);
+        assert!(shred_receiver.data_shreds_only);
+        assert!(shred_receiver.protocol == Protocol::QUIC);
+
+        let shred_receiver =
+            ShredReceiver::from_str("127.0.0.1:8000,retransmit-slots-only").unwrap();
+        assert!(!shred_receiver.leader_slots_only);
+        assert!(shred_receiver.retransmit_slots_only);
+
+        assert!(ShredReceiver::from_str("").is_err());
+        assert!(ShredReceiver::from_str("localhost").is_err());
+        assert!(ShredReceiver::from_str("127.0.0.1:8000,tcp").is_err());
+        assert!(
+            ShredReceiver::from_str("127.0.0.1:8000,leader-slots-only,retransmit-slots-only")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_add_remove_shred_receivers() {
+        let addr = "127.0.0.1:8000".parse().unwrap();
+        let shred_receivers =
+            ShredReceivers::new([
+                ShredReceiver::new(addr, false, false, false, Protocol::UDP).unwrap()
+            ]);
+
+        // the same address replaces the existing receiver
+        shred_receivers.add(ShredReceiver::new(addr, true, false, false, Protocol::QUIC).unwrap());
+        let receivers = shred_receivers.list();
+        assert_eq!(receivers.len(), 1);
+        assert!(receivers[0].leader_slots_only);
+
+        // but a retransmit slots only receiver at the same address is kept alongside it, while an
+        // all slots one replaces both
+        shred_receivers.add(ShredReceiver::new(addr, false, true, false, Protocol::UDP).unwrap());
+        assert_eq!(shred_receivers.list().len(), 2);
+        shred_receivers.add(ShredReceiver::new(addr, false, false, false, Protocol::UDP).unwrap());
+        let receivers = shred_receivers.list();
+        assert_eq!(receivers.len(), 1);
+        assert!(!receivers[0].leader_slots_only && !receivers[0].retransmit_slots_only);
+
+        assert!(shred_receivers.remove(&addr));
+        assert!(!shred_receivers.remove(&addr));
+        assert!(shred_receivers.list().is_empty());
+    }
+
+    #[test]
+    fn test_set_shred_receiver_address() {
+        let addr = "127.0.0.1:8000".parse().unwrap();
+        let other_addr = "127.0.0.1:8001".parse().unwrap();
+        let quic_addr = "127.0.0.1:8002".parse().unwrap();
+        let shred_receivers = ShredReceivers::new([ShredReceiver::new(
+            quic_addr,
+            true,
+            false,
+            false,
+            Protocol::QUIC,
+        )
+        .unwrap()]);
+
+        shred_receivers.set_address(Some(addr), false);
+        shred_receivers.set_address(Some(other_addr), true);
+        let addrs: Vec<_> = shred_receivers
+            .list()
+            .iter()
+            .map(|receiver| {
+                (
+                    receiver.addr,
+                    receiver.leader_slots_only,
+                    receiver.retransmit_slots_only,
+                )
+            })
+            .collect();
+        assert_eq!(
+            addrs,
+            vec![
+                (quic_addr, true, false),
+                (addr, true, false),
+                (other_addr, false, true)
+            ]
+        );
+
+        // setting the leader address again replaces it, but leaves receivers added otherwise and
+        // the retransmit receiver at the same address
+        shred_receivers.set_address(Some(other_addr), false);
+        let addrs: Vec<_> = shred_receivers
+            .list()
+            .iter()
+            .map(|receiver| (receiver.addr, receiver.leader_slots_only))
+            .collect();
+        assert_eq!(
+            addrs,
+            vec![(quic_addr, true), (other_addr, false), (other_addr, true)]
+        );
+
+        shred_receivers.set_address(None, false);
+        let addrs: Vec<_> = shred_receivers
+            .list()
+            .iter()
+            .map(|receiver| (receiver.addr, receiver.leader_slots_only))
+            .collect();
+        assert_eq!(addrs, vec![(quic_addr, true), (other_addr, false)]);
+    }
+
+    #[test]
+    fn test_send_shreds() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let all_slots = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let leader_slots = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let retransmit_slots = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let data_shreds = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (quic_endpoint_sender, mut quic_endpoint_receiver) = channel(1);
+        let quic_addr = "127.0.0.1:1".parse().unwrap();
+        let shred_receivers = ShredReceivers::new([
+            shred_receiver(&all_slots, false, false),
+            shred_receiver(&leader_slots, true, false),
+            shred_receiver(&retransmit_slots, false, true),
+            ShredReceiver::new(
+                data_shreds.local_addr().unwrap(),
+                false,
+                false,
+                true,
+                Protocol::UDP,
+            )
+            .unwrap(),
+            ShredReceiver::new(quic_addr, false, false, false, Protocol::QUIC).unwrap(),
+        ]);
+
+        let shreds = [
+            (ShredType::Data, &[1u8; 8][..]),
+            (ShredType::Code, &[2u8; 8][..]),
+        ];
+        shred_receivers.send_shreds(&shreds, true, &socket, &quic_endpoint_sender);
+
+        let num_sent: Vec<_> = shred_receivers
+            .list()
+            .iter()
+            .map(|receiver| (receiver.num_sent(), receiver.num_dropped()))
+            .collect();
+        // leader slots only receivers don't get retransmitted shreds and the quic channel only
+        // has room for one shred
+        assert_eq!(num_sent, vec![(2, 0), (0, 0), (2, 0), (1, 0), (1, 1)]);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(data_shreds.recv(&mut buf).unwrap(), 8);
+        assert_eq!(buf, [1u8; 8]);
+        let (addr, payload) = quic_endpoint_receiver.try_recv().unwrap();
+        assert_eq!(addr, quic_addr);
+        assert_eq!(&payload[..], &[1u8; 8]);
+
+        // and retransmit slots only receivers don't get broadcast shreds
+        shred_receivers.send_shreds(&shreds, false, &socket, &quic_endpoint_sender);
+        assert_eq!(shred_receivers.list()[1].num_sent(), 2);
+        assert_eq!(shred_receivers.list()[2].num_sent(), 2);
+    }
+
+    #[test]
+    fn test_leader_and_retransmit_receivers_at_the_same_address() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let receiver_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver_socket
+            .set_read_timeout(Some(std::time::Duration::from_secs(1)))
+            .unwrap();
+        let addr = receiver_socket.local_addr().unwrap();
+        let (quic_endpoint_sender, _quic_endpoint_receiver) = channel(1);
+        let shreds = [(ShredType::Data, &[1u8; 8][..])];
+        let mut buf = [0u8; 8];
+
+        // as with --shred-receiver-address X --shred-retransmit-receiver-address X
+        let shred_receivers = ShredReceivers::new([
+            ShredReceiver::from_str(&format!("{addr},leader-slots-only")).unwrap(),
+            ShredReceiver::from_str(&format!("{addr},retransmit-slots-only")).unwrap(),
+        ]);
+        // and with setShredReceiverAddress X and setShredRetransmitReceiverAddress X
+        let set_shred_receivers = ShredReceivers::default();
+        set_shred_receivers.set_address(Some(addr), false);
+        set_shred_receivers.set_address(Some(addr), true);
+
+        for shred_receivers in [shred_receivers, set_shred_receivers] {
+            assert_eq!(shred_receivers.list().len(), 2);
+            for retransmit in [false, true] {
+                shred_receivers.send_shreds(&shreds, retransmit, &socket, &quic_endpoint_sender);
+                assert_eq!(receiver_socket.recv(&mut buf).unwrap(), 8);
+            }
+            let num_sent: Vec<_> = shred_receivers
+                .list()
+                .iter()
+                .map(|receiver| receiver.num_sent())
+                .collect();
+            assert_eq!(num_sent, vec![1, 1]);
+        }
+    }
+}
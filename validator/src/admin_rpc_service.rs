@@ -11,10 +11,13 @@ use {
     solana_accounts_db::accounts_index::AccountIndex,
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
+        bundle_stage::amm_registry::{AmmRegistry, SharedAmmRegistry},
         consensus::{tower_storage::TowerStorage, Tower},
+        p3_quic::P3Config,
         proxy::{
             block_engine_stage::{BlockEngineConfig, BlockEngineStage},
-            relayer_stage::{RelayerConfig, RelayerStage},
+            relayer_stage::{RelayerConfig, RelayerStage, RelayerState},
+            shredstream_stage::{ShredstreamConfig, ShredstreamStage, ShredstreamState},
         },
         repair::repair_service,
         validator::ValidatorStartProgress,
@@ -28,6 +31,7 @@ use {
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
     },
+    solana_turbine::shred_receiver::ShredReceiver,
     std::{
         collections::{HashMap, HashSet},
         env, error,
@@ -145,6 +149,209 @@ impl Display for AdminRpcRepairWhitelist {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcShredReceiver {
+    pub address: SocketAddr,
+    pub leader_slots_only: bool,
+    pub retransmit_slots_only: bool,
+    pub data_shreds_only: bool,
+    pub quic: bool,
+    pub num_sent: u64,
+    pub num_dropped: u64,
+}
+
+impl From<&ShredReceiver> for AdminRpcShredReceiver {
+    fn from(shred_receiver: &ShredReceiver) -> Self {
+        Self {
+            address: shred_receiver.addr,
+            leader_slots_only: shred_receiver.leader_slots_only,
+            retransmit_slots_only: shred_receiver.retransmit_slots_only,
+            data_shreds_only: shred_receiver.data_shreds_only,
+            quic: shred_receiver.protocol == Protocol::QUIC,
+            num_sent: shred_receiver.num_sent(),
+            num_dropped: shred_receiver.num_dropped(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcShredReceivers {
+    pub shred_receivers: Vec<AdminRpcShredReceiver>,
+}
+
+impl Display for AdminRpcShredReceivers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Shred receivers:")?;
+        for shred_receiver in &self.shred_receivers {
+            writeln!(
+                f,
+                "  {} ({}{}{}{}): {} sent, {} dropped",
+                shred_receiver.address,
+                if shred_receiver.quic { "quic" } else { "udp" },
+                if shred_receiver.leader_slots_only {
+                    ", leader slots only"
+                } else {
+                    ""
+                },
+                if shred_receiver.retransmit_slots_only {
+                    ", retransmit slots only"
+                } else {
+                    ""
+                },
+                if shred_receiver.data_shreds_only {
+                    ", data shreds only"
+                } else {
+                    ""
+                },
+                shred_receiver.num_sent,
+                shred_receiver.num_dropped,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcRelayer {
+    pub relayer_url: String,
+    pub connected: bool,
+    pub advertised: bool,
+    pub tpu: Option<SocketAddr>,
+    pub tpu_forward: Option<SocketAddr>,
+    pub num_heartbeats: u64,
+    pub num_missed_heartbeats: u64,
+    pub packets_per_second: u64,
+    pub last_error: Option<String>,
+}
+
+impl From<&RelayerState> for AdminRpcRelayer {
+    fn from(relayer: &RelayerState) -> Self {
+        Self {
+            relayer_url: relayer.relayer_url.clone(),
+            connected: relayer.connected,
+            advertised: relayer.advertised,
+            tpu: relayer.tpu_addresses.map(|(tpu, _)| tpu),
+            tpu_forward: relayer.tpu_addresses.map(|(_, tpu_forward)| tpu_forward),
+            num_heartbeats: relayer.num_heartbeats,
+            num_missed_heartbeats: relayer.num_missed_heartbeats,
+            packets_per_second: relayer.packets_per_second,
+            last_error: relayer.last_error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcRelayers {
+    pub relayers: Vec<AdminRpcRelayer>,
+}
+
+impl Display for AdminRpcRelayers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.relayers.is_empty() {
+            return writeln!(f, "Relayers: disabled");
+        }
+        writeln!(f, "Relayers:")?;
+        for relayer in &self.relayers {
+            if relayer.connected {
+                writeln!(
+                    f,
+                    "  {}{}: tpu {}, {} packets/s, {} heartbeats, {} missed",
+                    relayer.relayer_url,
+                    if relayer.advertised {
+                        " (advertised)"
+                    } else {
+                        ""
+                    },
+                    relayer.tpu.map(|tpu| tpu.to_string()).unwrap_or_default(),
+                    relayer.packets_per_second,
+                    relayer.num_heartbeats,
+                    relayer.num_missed_heartbeats,
+                )?;
+            } else {
+                writeln!(f, "  {}: disconnected", relayer.relayer_url)?;
+            }
+            if let Some(last_error) = &relayer.last_error {
+                writeln!(f, "    Last error: {last_error}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcShredstreamState {
+    pub shredstream_url: String,
+    pub desired_regions: Vec<String>,
+    pub connected: bool,
+    pub socket: Option<SocketAddr>,
+    pub ttl_ms: u32,
+    pub num_heartbeats: u64,
+    pub last_error: Option<String>,
+}
+
+impl AdminRpcShredstreamState {
+    fn new(config: &ShredstreamConfig, state: &ShredstreamState) -> Self {
+        Self {
+            shredstream_url: config.shredstream_url.clone(),
+            desired_regions: config.desired_regions.clone(),
+            connected: state.connected,
+            socket: state.socket,
+            ttl_ms: state.ttl_ms,
+            num_heartbeats: state.num_heartbeats,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+impl Display for AdminRpcShredstreamState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.shredstream_url.is_empty() {
+            return writeln!(f, "Shredstream: disabled");
+        }
+        writeln!(
+            f,
+            "Shredstream: {} ({})",
+            self.shredstream_url,
+            self.desired_regions.join(", ")
+        )?;
+        if self.connected {
+            writeln!(
+                f,
+                "  Connected, sending shreds to {}: {} heartbeats, ttl {}ms",
+                self.socket
+                    .map(|socket| socket.to_string())
+                    .unwrap_or_default(),
+                self.num_heartbeats,
+                self.ttl_ms,
+            )?;
+        } else {
+            writeln!(f, "  Disconnected")?;
+        }
+        if let Some(last_error) = &self.last_error {
+            writeln!(f, "  Last error: {last_error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Urls taken by the `setBlockEngineConfig` and `setRelayerConfig` rpcs, which older clients send
+/// as a single url.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum AdminRpcUrls {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl From<AdminRpcUrls> for Vec<String> {
+    fn from(urls: AdminRpcUrls) -> Self {
+        match urls {
+            AdminRpcUrls::Single(url) => vec![url],
+            AdminRpcUrls::Multiple(urls) => urls,
+        }
+    }
+}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -249,24 +456,58 @@ pub trait AdminRpc {
         public_tpu_forwards_addr: SocketAddr,
     ) -> Result<()>;
 
+    /// Older clients send `(block_engine_url, trust_packets)`, which is still accepted.
     #[rpc(meta, name = "setBlockEngineConfig")]
     fn set_block_engine_config(
         &self,
         meta: Self::Metadata,
-        block_engine_url: String,
-        trust_packets: bool,
+        block_engine_urls: AdminRpcUrls,
+        connect_all: bool,
+        trust_packets: Option<bool>,
     ) -> Result<()>;
 
     #[rpc(meta, name = "setRelayerConfig")]
     fn set_relayer_config(
         &self,
         meta: Self::Metadata,
-        relayer_url: String,
+        relayer_urls: AdminRpcUrls,
         trust_packets: bool,
         expected_heartbeat_interval_ms: u64,
         max_failed_heartbeats: u64,
     ) -> Result<()>;
 
+    #[rpc(meta, name = "relayers")]
+    fn relayers(&self, meta: Self::Metadata) -> Result<AdminRpcRelayers>;
+
+    #[rpc(meta, name = "setShredstreamConfig")]
+    fn set_shredstream_config(
+        &self,
+        meta: Self::Metadata,
+        shredstream_url: String,
+        desired_regions: Vec<String>,
+        socket: Option<String>,
+    ) -> Result<()>;
+
+    #[rpc(meta, name = "shredstreamState")]
+    fn shredstream_state(&self, meta: Self::Metadata) -> Result<AdminRpcShredstreamState>;
+
+    #[rpc(meta, name = "shredReceivers")]
+    fn shred_receivers(&self, meta: Self::Metadata) -> Result<AdminRpcShredReceivers>;
+
+    #[rpc(meta, name = "addShredReceiver")]
+    fn add_shred_receiver(
+        &self,
+        meta: Self::Metadata,
+        addr: String,
+        leader_slots_only: bool,
+        retransmit_slots_only: bool,
+        data_shreds_only: bool,
+        quic: bool,
+    ) -> Result<()>;
+
+    #[rpc(meta, name = "removeShredReceiver")]
+    fn remove_shred_receiver(&self, meta: Self::Metadata, addr: String) -> Result<()>;
+
     #[rpc(meta, name = "setShredReceiverAddress")]
     fn set_shred_receiver_address(&self, meta: Self::Metadata, addr: String) -> Result<()>;
 
@@ -276,6 +517,17 @@ pub trait AdminRpc {
         meta: Self::Metadata,
         addr: String,
     ) -> Result<()>;
+
+    #[rpc(meta, name = "setP3Config")]
+    fn set_p3_config(
+        &self,
+        meta: Self::Metadata,
+        pool: Option<String>,
+        stake_cutoff_percent: Option<u64>,
+    ) -> Result<()>;
+
+    #[rpc(meta, name = "reloadAmmRegistry")]
+    fn reload_amm_registry(&self, meta: Self::Metadata, path: Option<String>) -> Result<()>;
 }
 
 pub struct AdminRpcImpl;
@@ -482,12 +734,19 @@ impl AdminRpc for AdminRpcImpl {
     fn set_block_engine_config(
         &self,
         meta: Self::Metadata,
-        block_engine_url: String,
-        trust_packets: bool,
+        block_engine_urls: AdminRpcUrls,
+        connect_all: bool,
+        trust_packets: Option<bool>,
     ) -> Result<()> {
         debug!("set_block_engine_config request received");
+        // The second parameter of the old shape is trust_packets
+        let (connect_all, trust_packets) = match trust_packets {
+            Some(trust_packets) => (connect_all, trust_packets),
+            None => (false, connect_all),
+        };
         let config = BlockEngineConfig {
-            block_engine_url,
+            block_engine_urls: block_engine_urls.into(),
+            connect_all,
             trust_packets,
         };
         // Detailed log messages are printed inside validate function
@@ -540,7 +799,7 @@ impl AdminRpc for AdminRpcImpl {
     fn set_relayer_config(
         &self,
         meta: Self::Metadata,
-        relayer_url: String,
+        relayer_urls: AdminRpcUrls,
         trust_packets: bool,
         expected_heartbeat_interval_ms: u64,
         max_failed_heartbeats: u64,
@@ -550,7 +809,7 @@ impl AdminRpc for AdminRpcImpl {
         let oldest_allowed_heartbeat =
             Duration::from_millis(max_failed_heartbeats * expected_heartbeat_interval_ms);
         let config = RelayerConfig {
-            relayer_url,
+            relayer_urls: relayer_urls.into(),
             expected_heartbeat_interval,
             oldest_allowed_heartbeat,
             trust_packets,
@@ -568,20 +827,183 @@ impl AdminRpc for AdminRpcImpl {
         }
     }
 
-    fn set_shred_receiver_address(&self, meta: Self::Metadata, addr: String) -> Result<()> {
-        let shred_receiver_address = if addr.is_empty() {
-            None
+    fn relayers(&self, meta: Self::Metadata) -> Result<AdminRpcRelayers> {
+        meta.with_post_init(|post_init| {
+            let relayers = post_init
+                .relayer_states
+                .lock()
+                .unwrap()
+                .relayers
+                .iter()
+                .map(AdminRpcRelayer::from)
+                .collect();
+            Ok(AdminRpcRelayers { relayers })
+        })
+    }
+
+    fn set_shredstream_config(
+        &self,
+        meta: Self::Metadata,
+        shredstream_url: String,
+        desired_regions: Vec<String>,
+        socket: Option<String>,
+    ) -> Result<()> {
+        debug!("set_shredstream_config request received");
+        let socket = socket
+            .map(|socket| {
+                SocketAddr::from_str(&socket).map_err(|_| {
+                    jsonrpc_core::error::Error::invalid_params(format!(
+                        "invalid shredstream socket: {socket}"
+                    ))
+                })
+            })
+            .transpose()?;
+        let config = ShredstreamConfig {
+            shredstream_url,
+            desired_regions,
+            socket,
+        };
+        // An empty url disables shredstream, detailed log messages are printed inside validate
+        // function
+        if config.shredstream_url.is_empty()
+            || ShredstreamStage::is_valid_shredstream_config(&config)
+        {
+            meta.with_post_init(|post_init| {
+                *post_init.shredstream_config.lock().unwrap() = config;
+                Ok(())
+            })
         } else {
-            Some(SocketAddr::from_str(&addr).map_err(|_| {
+            Err(jsonrpc_core::error::Error::invalid_params(
+                "failed to set shredstream config. see logs for details.",
+            ))
+        }
+    }
+
+    fn shredstream_state(&self, meta: Self::Metadata) -> Result<AdminRpcShredstreamState> {
+        meta.with_post_init(|post_init| {
+            Ok(AdminRpcShredstreamState::new(
+                &post_init.shredstream_config.lock().unwrap(),
+                &post_init.shredstream_state.lock().unwrap(),
+            ))
+        })
+    }
+
+    fn set_p3_config(
+        &self,
+        meta: Self::Metadata,
+        pool: Option<String>,
+        stake_cutoff_percent: Option<u64>,
+    ) -> Result<()> {
+        debug!("set_p3_config request received");
+        let pool = pool
+            .map(|pool| {
+                Pubkey::from_str(&pool).map_err(|_| {
+                    jsonrpc_core::error::Error::invalid_params(format!("invalid P3 pool: {pool}"))
+                })
+            })
+            .transpose()?;
+
+        meta.with_post_init(|post_init| {
+            let mut p3_config = post_init.p3_config.lock().unwrap();
+            let config = P3Config {
+                pool: pool.unwrap_or(p3_config.pool),
+                stake_cutoff_percent: stake_cutoff_percent
+                    .unwrap_or(p3_config.stake_cutoff_percent),
+                ..p3_config.clone()
+            };
+            config.validate().map_err(|err| {
+                jsonrpc_core::error::Error::invalid_params(format!("invalid P3 config: {err}"))
+            })?;
+            *p3_config = config;
+            Ok(())
+        })
+    }
+
+    fn reload_amm_registry(&self, meta: Self::Metadata, path: Option<String>) -> Result<()> {
+        debug!("reload_amm_registry request received");
+        meta.with_post_init(|post_init| {
+            let path = path
+                .map(PathBuf::from)
+                .or_else(|| post_init.amm_registry_path.clone())
+                .ok_or_else(|| {
+                    jsonrpc_core::error::Error::invalid_params(
+                        "no AMM registry path given and none configured",
+                    )
+                })?;
+            let registry = AmmRegistry::load(&path).map_err(|err| {
                 jsonrpc_core::error::Error::invalid_params(format!(
-                    "invalid shred receiver address: {}",
-                    addr
+                    "failed to load AMM registry {}: {err}",
+                    path.display()
                 ))
-            })?)
-        };
+            })?;
+
+            info!(
+                "Reloaded AMM registry from {}; programs={}",
+                path.display(),
+                registry.len()
+            );
+            post_init.amm_registry.store(registry);
+            Ok(())
+        })
+    }
+
+    fn shred_receivers(&self, meta: Self::Metadata) -> Result<AdminRpcShredReceivers> {
+        meta.with_post_init(|post_init| {
+            let shred_receivers = post_init
+                .shred_receivers
+                .list()
+                .iter()
+                .map(|shred_receiver| AdminRpcShredReceiver::from(shred_receiver.as_ref()))
+                .collect();
+            Ok(AdminRpcShredReceivers { shred_receivers })
+        })
+    }
+
+    fn add_shred_receiver(
+        &self,
+        meta: Self::Metadata,
+        addr: String,
+        leader_slots_only: bool,
+        retransmit_slots_only: bool,
+        data_shreds_only: bool,
+        quic: bool,
+    ) -> Result<()> {
+        let addr = parse_shred_receiver_address(&addr)?;
+        let protocol = if quic { Protocol::QUIC } else { Protocol::UDP };
+        let shred_receiver = ShredReceiver::new(
+            addr,
+            leader_slots_only,
+            retransmit_slots_only,
+            data_shreds_only,
+            protocol,
+        )
+        .map_err(jsonrpc_core::error::Error::invalid_params)?;
 
         meta.with_post_init(|post_init| {
-            *post_init.shred_receiver_address.write().unwrap() = shred_receiver_address;
+            post_init.shred_receivers.add(shred_receiver);
+            Ok(())
+        })
+    }
+
+    fn remove_shred_receiver(&self, meta: Self::Metadata, addr: String) -> Result<()> {
+        let addr = parse_shred_receiver_address(&addr)?;
+
+        meta.with_post_init(|post_init| {
+            if post_init.shred_receivers.remove(&addr) {
+                Ok(())
+            } else {
+                Err(jsonrpc_core::error::Error::invalid_params(format!(
+                    "no shred receiver at {addr}"
+                )))
+            }
+        })
+    }
+
+    fn set_shred_receiver_address(&self, meta: Self::Metadata, addr: String) -> Result<()> {
+        let addr = parse_optional_shred_receiver_address(&addr)?;
+
+        meta.with_post_init(|post_init| {
+            post_init.shred_receivers.set_address(addr, false);
             Ok(())
         })
     }
@@ -591,19 +1013,10 @@ impl AdminRpc for AdminRpcImpl {
         meta: Self::Metadata,
         addr: String,
     ) -> Result<()> {
-        let shred_receiver_address = if addr.is_empty() {
-            None
-        } else {
-            Some(SocketAddr::from_str(&addr).map_err(|_| {
-                jsonrpc_core::error::Error::invalid_params(format!(
-                    "invalid shred receiver address: {}",
-                    addr
-                ))
-            })?)
-        };
+        let addr = parse_optional_shred_receiver_address(&addr)?;
 
         meta.with_post_init(|post_init| {
-            *post_init.shred_retransmit_receiver_address.write().unwrap() = shred_receiver_address;
+            post_init.shred_receivers.set_address(addr, true);
             Ok(())
         })
     }
@@ -862,6 +1275,23 @@ impl AdminRpcImpl {
     }
 }
 
+fn parse_shred_receiver_address(addr: &str) -> Result<SocketAddr> {
+    SocketAddr::from_str(addr).map_err(|_| {
+        jsonrpc_core::error::Error::invalid_params(format!(
+            "invalid shred receiver address: {addr}"
+        ))
+    })
+}
+
+/// An empty address unsets the shred receiver.
+fn parse_optional_shred_receiver_address(addr: &str) -> Result<Option<SocketAddr>> {
+    if addr.is_empty() {
+        Ok(None)
+    } else {
+        parse_shred_receiver_address(addr).map(Some)
+    }
+}
+
 fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> RpcAccountIndex {
     match account_index {
         AccountIndex::ProgramId => RpcAccountIndex::ProgramId,
@@ -997,7 +1427,9 @@ mod tests {
             accounts_db::{AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING},
             accounts_index::AccountSecondaryIndexes,
         },
-        solana_core::consensus::tower_storage::NullTowerStorage,
+        solana_core::{
+            bundle_stage::amm_registry::AmmPolicy, consensus::tower_storage::NullTowerStorage,
+        },
         solana_gossip::cluster_info::ClusterInfo,
         solana_inline_spl::token,
         solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
@@ -1018,6 +1450,7 @@ mod tests {
         },
         std::{
             collections::HashSet,
+            io::Write,
             sync::{atomic::AtomicBool, Mutex},
         },
     };
@@ -1034,7 +1467,7 @@ mod tests {
     }
 
     impl RpcHandler {
-        fn _start() -> Self {
+        fn start() -> Self {
             Self::start_with_config(TestConfig::default())
         }
 
@@ -1062,8 +1495,6 @@ mod tests {
             let repair_whitelist = Arc::new(RwLock::new(HashSet::new()));
             let block_engine_config = Arc::new(Mutex::new(BlockEngineConfig::default()));
             let relayer_config = Arc::new(Mutex::new(RelayerConfig::default()));
-            let shred_receiver_address = Arc::new(RwLock::new(None));
-            let shred_retransmit_receiver_address = Arc::new(RwLock::new(None));
             let meta = AdminRpcRequestMetadata {
                 rpc_addr: None,
                 start_time: SystemTime::now(),
@@ -1086,8 +1517,13 @@ mod tests {
                     ),
                     block_engine_config,
                     relayer_config,
-                    shred_receiver_address,
-                    shred_retransmit_receiver_address,
+                    relayer_states: Arc::default(),
+                    shred_receivers: Arc::default(),
+                    shredstream_config: Arc::new(Mutex::new(ShredstreamConfig::default())),
+                    shredstream_state: Arc::default(),
+                    p3_config: Arc::new(Mutex::new(P3Config::default())),
+                    amm_registry: SharedAmmRegistry::default(),
+                    amm_registry_path: None,
                 }))),
                 staked_nodes_overrides: Arc::new(RwLock::new(HashMap::new())),
                 rpc_to_plugin_manager_sender: None,
@@ -1120,6 +1556,401 @@ mod tests {
         (BankForks::new_rw_arc(bank), Arc::new(voting_keypair))
     }
 
+    #[test]
+    fn test_set_p3_config() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let p3_config = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .p3_config
+            .clone();
+
+        // Omitted parameters are left unchanged.
+        let pool = Pubkey::new_unique();
+        let req =
+            format!(r#"{{"jsonrpc":"2.0","id":1,"method":"setP3Config","params":["{pool}"]}}"#,);
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *p3_config.lock().unwrap(),
+            P3Config {
+                pool,
+                ..P3Config::default()
+            }
+        );
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setP3Config","params":[null, 5]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *p3_config.lock().unwrap(),
+            P3Config {
+                pool,
+                stake_cutoff_percent: 5,
+                ..P3Config::default()
+            }
+        );
+
+        // Invalid configs are rejected.
+        for params in [r#"["not-a-pubkey"]"#, r#"[null, 100]"#] {
+            let req =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"setP3Config","params":{params}}}"#);
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert!(result["error"].is_object());
+        }
+        assert_eq!(p3_config.lock().unwrap().stake_cutoff_percent, 5);
+    }
+
+    #[test]
+    fn test_set_block_engine_config() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let block_engine_config = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .block_engine_config
+            .clone();
+
+        for (params, expected_config) in [
+            (
+                r#"[["http://be1:1000", "http://be2:1000"], true, false]"#,
+                BlockEngineConfig {
+                    block_engine_urls: vec![
+                        "http://be1:1000".to_string(),
+                        "http://be2:1000".to_string(),
+                    ],
+                    connect_all: true,
+                    trust_packets: false,
+                },
+            ),
+            // the shape sent by older clients
+            (
+                r#"["http://be1:1000", true]"#,
+                BlockEngineConfig {
+                    block_engine_urls: vec!["http://be1:1000".to_string()],
+                    connect_all: false,
+                    trust_packets: true,
+                },
+            ),
+        ] {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"setBlockEngineConfig","params":{params}}}"#
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert_eq!(result["result"], Value::Null);
+            assert_eq!(*block_engine_config.lock().unwrap(), expected_config);
+        }
+    }
+
+    #[test]
+    fn test_set_relayer_config() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let relayer_config = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .relayer_config
+            .clone();
+
+        for (params, relayer_urls) in [
+            (
+                r#"[["http://relayer1:1000", "http://relayer2:1000"], true, 500, 3]"#,
+                vec![
+                    "http://relayer1:1000".to_string(),
+                    "http://relayer2:1000".to_string(),
+                ],
+            ),
+            // the shape sent by older clients
+            (
+                r#"["http://relayer1:1000", true, 500, 3]"#,
+                vec!["http://relayer1:1000".to_string()],
+            ),
+        ] {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"setRelayerConfig","params":{params}}}"#
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert_eq!(result["result"], Value::Null);
+            assert_eq!(
+                *relayer_config.lock().unwrap(),
+                RelayerConfig {
+                    relayer_urls,
+                    expected_heartbeat_interval: Duration::from_millis(500),
+                    oldest_allowed_heartbeat: Duration::from_millis(1500),
+                    trust_packets: true,
+                }
+            );
+        }
+
+        // the same relayer can't be configured twice
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setRelayerConfig","params":[["http://relayer2:1000", "http://relayer2:1000"], true, 500, 3]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+        assert_eq!(
+            relayer_config.lock().unwrap().relayer_urls,
+            vec!["http://relayer1:1000".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_reload_amm_registry() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let amm_registry = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .amm_registry
+            .clone();
+
+        // Without a configured path one must be given.
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"reloadAmmRegistry","params":[]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+        assert_eq!(*amm_registry.load(), AmmRegistry::default());
+
+        let program = Pubkey::new_unique();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"{{"programs": [{{"program_id": "{program}", "policy": "log-only"}}]}}"#
+        )
+        .unwrap();
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"reloadAmmRegistry","params":["{}"]}}"#,
+            file.path().display()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *amm_registry.load(),
+            AmmRegistry::new([(program, AmmPolicy::LogOnly)])
+        );
+
+        // A bad file leaves the current registry in place.
+        write!(file, "not json").unwrap();
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert!(result["error"].is_object());
+        assert_eq!(
+            *amm_registry.load(),
+            AmmRegistry::new([(program, AmmPolicy::LogOnly)])
+        );
+    }
+
+    #[test]
+    fn test_shred_receivers() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let shred_receivers = meta
+            .post_init
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .shred_receivers
+            .clone();
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"addShredReceiver","params":["127.0.0.1:8000", true, false, false, true]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        let receivers = shred_receivers.list();
+        assert_eq!(receivers.len(), 1);
+        assert!(receivers[0].leader_slots_only);
+        assert!(!receivers[0].data_shreds_only);
+        assert!(receivers[0].protocol == Protocol::QUIC);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"shredReceivers"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["result"]["shred_receivers"][0]["address"],
+            "127.0.0.1:8000"
+        );
+        assert_eq!(result["result"]["shred_receivers"][0]["num_sent"], 0);
+
+        // Invalid and unknown addresses are rejected.
+        for (method, params) in [
+            (
+                "addShredReceiver",
+                r#"["not-an-address", false, false, false, false]"#,
+            ),
+            (
+                "addShredReceiver",
+                r#"["127.0.0.1:8001", true, true, false, false]"#,
+            ),
+            ("setShredReceiverAddress", r#"["not-an-address"]"#),
+            ("removeShredReceiver", r#"["127.0.0.1:8001"]"#),
+        ] {
+            let req =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":{params}}}"#);
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert!(result["error"].is_object());
+        }
+        assert_eq!(shred_receivers.list().len(), 1);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"removeShredReceiver","params":["127.0.0.1:8000"]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert!(shred_receivers.list().is_empty());
+
+        // The single address setters replace the previous address, and an empty one unsets it.
+        for (method, addr) in [
+            ("setShredReceiverAddress", "127.0.0.1:8001"),
+            ("setShredRetransmitReceiverAddress", "127.0.0.1:8002"),
+            ("setShredReceiverAddress", "127.0.0.1:8003"),
+        ] {
+            let req =
+                format!(r#"{{"jsonrpc":"2.0","id":1,"method":"{method}","params":["{addr}"]}}"#);
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert_eq!(result["result"], Value::Null);
+        }
+        let receivers: Vec<_> = shred_receivers
+            .list()
+            .iter()
+            .map(|receiver| {
+                (
+                    receiver.addr.to_string(),
+                    receiver.leader_slots_only,
+                    receiver.retransmit_slots_only,
+                )
+            })
+            .collect();
+        assert_eq!(
+            receivers,
+            vec![
+                ("127.0.0.1:8002".to_string(), false, true),
+                ("127.0.0.1:8003".to_string(), true, false),
+            ]
+        );
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setShredRetransmitReceiverAddress","params":[""]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(shred_receivers.list().len(), 1);
+    }
+
+    #[test]
+    fn test_shredstream_config() {
+        let RpcHandler { io, meta, .. } = RpcHandler::start();
+        let (shredstream_config, shredstream_state) = {
+            let post_init = meta.post_init.read().unwrap();
+            let post_init = post_init.as_ref().unwrap();
+            (
+                post_init.shredstream_config.clone(),
+                post_init.shredstream_state.clone(),
+            )
+        };
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setShredstreamConfig","params":["http://shredstream:1002", ["amsterdam", "ny"], "10.0.0.1:8001"]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *shredstream_config.lock().unwrap(),
+            ShredstreamConfig {
+                shredstream_url: "http://shredstream:1002".to_string(),
+                desired_regions: vec!["amsterdam".to_string(), "ny".to_string()],
+                socket: Some("10.0.0.1:8001".parse().unwrap()),
+            }
+        );
+
+        *shredstream_state.lock().unwrap() = ShredstreamState {
+            connected: true,
+            socket: Some("10.0.0.1:8001".parse().unwrap()),
+            ttl_ms: 500,
+            num_heartbeats: 3,
+            last_error: None,
+        };
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"shredstreamState"}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["result"]["shredstream_url"],
+            "http://shredstream:1002"
+        );
+        assert_eq!(result["result"]["connected"], true);
+        assert_eq!(result["result"]["num_heartbeats"], 3);
+
+        // Missing regions and invalid sockets are rejected.
+        for params in [
+            r#"["http://shredstream:1002", [], null]"#,
+            r#"["http://shredstream:1002", ["ny"], "not-a-socket"]"#,
+        ] {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"setShredstreamConfig","params":{params}}}"#
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            assert!(result["error"].is_object());
+        }
+
+        // An empty url disables shredstream.
+        let req =
+            r#"{"jsonrpc":"2.0","id":1,"method":"setShredstreamConfig","params":["", [], null]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *shredstream_config.lock().unwrap(),
+            ShredstreamConfig::default()
+        );
+
+        // And it can be enabled again at runtime.
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"setShredstreamConfig","params":["http://shredstream:1003", ["ny"], null]}"#;
+        let res = io.handle_request_sync(req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+        assert_eq!(
+            *shredstream_config.lock().unwrap(),
+            ShredstreamConfig {
+                shredstream_url: "http://shredstream:1003".to_string(),
+                desired_regions: vec!["ny".to_string()],
+                socket: None,
+            }
+        );
+    }
+
     #[test]
     fn test_secondary_index_key_sizes() {
         for secondary_index_enabled in [true, false] {
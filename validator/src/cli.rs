@@ -103,6 +103,69 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(DEFAULT_P3_MEV_PORT)
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("p3_pool")
+                .long("p3-pool")
+                .value_name("PUBKEY")
+                .validator(is_pubkey)
+                .takes_value(true)
+                .help("Lockup pool that grants P3 connection privileges. Defaults to the mainnet pool")
+        )
+        .arg(
+            Arg::with_name("p3_stake_cutoff_percent")
+                .long("p3-stake-cutoff-percent")
+                .value_name("PERCENT")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Lockups must hold more than this percentage of the P3 pool's stake to be admitted. Defaults to 1")
+        )
+        .arg(
+            Arg::with_name("p3_max_staked_connections")
+                .long("p3-max-staked-connections")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Maximum number of staked P3 connections. Defaults to 256")
+        )
+        .arg(
+            Arg::with_name("p3_max_unstaked_connections")
+                .long("p3-max-unstaked-connections")
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .help("Maximum number of unstaked P3 connections. Defaults to 0")
+        )
+        .arg(
+            Arg::with_name("p3_staked_nodes_update_interval_secs")
+                .long("p3-staked-nodes-update-interval-secs")
+                .value_name("SECONDS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Maximum interval between P3 staked node refreshes, which otherwise happen whenever the pool changes in a rooted bank. Defaults to 900s")
+        )
+        .arg(
+            Arg::with_name("p3_regular_packets_per_second")
+                .long("p3-regular-packets-per-second")
+                .value_name("NUMBER")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Packets per second shared by stake between all P3 identities. Defaults to 50000")
+        )
+        .arg(
+            Arg::with_name("p3_mev_packets_per_second")
+                .long("p3-mev-packets-per-second")
+                .value_name("NUMBER")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help("Packets per second shared by stake between all P3 MEV identities. Defaults to 10000")
+        )
+        .arg(
+            Arg::with_name("amm_registry")
+                .long("amm-registry")
+                .value_name("PATH")
+                .takes_value(true)
+                .help("JSON file listing the AMM programs protected from front-running in bundles and their policy. Defaults to a built-in list of AMMs that are all rejected")
+        )
         .arg(
             Arg::with_name("batch_interval_ms")
                 .long("batch-interval-ms")
@@ -129,14 +192,22 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
         .arg(
             Arg::with_name("block_engine_url")
                 .long("block-engine-url")
-                .help("Block engine url.  Set to empty string to disable block engine connection.")
+                .help("Block engine url. May be specified multiple times to fail over between or connect to several block engines. Set to empty string to disable block engine connection.")
                 .takes_value(true)
+                .multiple(true)
+        )
+        .arg(
+            Arg::with_name("block_engine_connect_all")
+                .long("block-engine-connect-all")
+                .takes_value(false)
+                .help("Stream from every --block-engine-url at once instead of only the lowest latency one, failing over to the next fastest when it disconnects.")
         )
         .arg(
             Arg::with_name("relayer_url")
                 .long("relayer-url")
-                .help("Relayer url. Set to empty string to disable relayer connection.")
+                .help("Relayer url. May be specified multiple times to stream from several relayers, deduplicating their packets and advertising the healthiest one. Set to empty string to disable relayer connection.")
                 .takes_value(true)
+                .multiple(true)
         )
         .arg(
             Arg::with_name("trust_relayer_packets")
@@ -164,6 +235,30 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(false)
                 .help("Skip signature verification on block engine packets. Not recommended unless the block engine is trusted.")
         )
+        .arg(
+            Arg::with_name("shredstream_url")
+                .long("shredstream-url")
+                .help("Shredstream url. Heartbeats are sent to it to receive shreds ahead of turbine. Set to empty string to disable shredstream connection.")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("shredstream_desired_region")
+                .long("shredstream-desired-region")
+                .value_name("REGION")
+                .help("Region to receive shreds from. May be specified multiple times.")
+                .takes_value(true)
+                .multiple(true)
+                .requires("shredstream_url")
+        )
+        .arg(
+            Arg::with_name("shredstream_socket")
+                .long("shredstream-socket")
+                .value_name("HOST:PORT")
+                .validator(solana_net_utils::is_host_port)
+                .help("Socket shredstream sends shreds to. Defaults to the TVU address advertised in gossip.")
+                .takes_value(true)
+                .requires("shredstream_url")
+        )
         .arg(
             Arg::with_name("tip_payment_program_pubkey")
                 .long("tip-payment-program-pubkey")
@@ -200,19 +295,33 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(DEFAULT_PREALLOCATED_BUNDLE_COST)
                 .help("Number of CUs to allocate for bundles at beginning of slot.")
         )
+        .arg(
+            Arg::with_name("shred_receiver")
+                .long("shred-receiver")
+                .value_name("ADDRESS[,leader-slots-only|,retransmit-slots-only][,data-shreds-only][,quic]")
+                .takes_value(true)
+                .multiple(true)
+                .help("Validator will forward shreds to this address in addition to normal turbine operation. \
+                       By default it receives the data and coding shreds of all slots over UDP. \
+                       May be specified multiple times.")
+        )
         .arg(
             Arg::with_name("shred_receiver_address")
                 .long("shred-receiver-address")
                 .value_name("SHRED_RECEIVER_ADDRESS")
                 .takes_value(true)
-                .help("Validator will forward all leader shreds to this address in addition to normal turbine operation. Set to empty string to disable.")
+                .multiple(true)
+                .help("Validator will forward all leader shreds to this address in addition to normal turbine operation. \
+                       Same as --shred-receiver ADDRESS,leader-slots-only. Empty strings are ignored.")
         )
         .arg(
             Arg::with_name("shred_retransmit_receiver_address")
                 .long("shred-retransmit-receiver-address")
                 .value_name("SHRED_RETRANSMIT_RECEIVER_ADDRESS")
                 .takes_value(true)
-                .help("Validator will forward all retransmit shreds to this address in addition to normal turbine operation. Set to empty string to disable.")
+                .multiple(true)
+                .help("Validator will forward all retransmit shreds to this address in addition to normal turbine operation. \
+                       Same as --shred-receiver ADDRESS,retransmit-slots-only. Empty strings are ignored.")
         )
         .arg(
             Arg::with_name("identity")
@@ -1806,10 +1915,17 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .arg(
                     Arg::with_name("block_engine_url")
                         .long("block-engine-url")
-                        .help("Block engine url.  Set to empty string to disable block engine connection.")
+                        .help("Block engine url. May be specified multiple times to fail over between or connect to several block engines. Set to empty string to disable block engine connection.")
                         .takes_value(true)
+                        .multiple(true)
                         .required(true)
                 )
+                .arg(
+                    Arg::with_name("block_engine_connect_all")
+                        .long("block-engine-connect-all")
+                        .takes_value(false)
+                        .help("Stream from every --block-engine-url at once instead of only the lowest latency one, failing over to the next fastest when it disconnects.")
+                )
                 .arg(
                     Arg::with_name("trust_block_engine_packets")
                         .long("trust-block-engine-packets")
@@ -1823,8 +1939,9 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .arg(
                     Arg::with_name("relayer_url")
                         .long("relayer-url")
-                        .help("Relayer url. Set to empty string to disable relayer connection.")
+                        .help("Relayer url. May be specified multiple times to stream from several relayers, deduplicating their packets and advertising the healthiest one. Set to empty string to disable relayer connection.")
                         .takes_value(true)
+                        .multiple(true)
                         .required(true)
                 )
                 .arg(
@@ -1850,6 +1967,169 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         .default_value(DEFAULT_RELAYER_MAX_FAILED_HEARTBEATS)
                 )
         )
+        .subcommand(
+            SubCommand::with_name("relayers")
+                .about("Display the state of the connection to each relayer")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-shredstream-config")
+                .about("Set configuration for connection to shredstream")
+                .arg(
+                    Arg::with_name("shredstream_url")
+                        .long("shredstream-url")
+                        .help("Shredstream url. Set to empty string to disable shredstream connection.")
+                        .takes_value(true)
+                        .required(true)
+                )
+                .arg(
+                    Arg::with_name("shredstream_desired_region")
+                        .long("shredstream-desired-region")
+                        .value_name("REGION")
+                        .help("Region to receive shreds from. May be specified multiple times.")
+                        .takes_value(true)
+                        .multiple(true)
+                )
+                .arg(
+                    Arg::with_name("shredstream_socket")
+                        .long("shredstream-socket")
+                        .value_name("HOST:PORT")
+                        .validator(solana_net_utils::is_host_port)
+                        .help("Socket shredstream sends shreds to. Defaults to the TVU address advertised in gossip.")
+                        .takes_value(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("shredstream-state")
+                .about("Display the shredstream connection state")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-p3-config")
+                .about("Set the P3 lockup pool and stake cutoff")
+                .arg(
+                    Arg::with_name("p3_pool")
+                        .long("pool")
+                        .value_name("PUBKEY")
+                        .validator(is_pubkey)
+                        .takes_value(true)
+                        .help("Lockup pool that grants P3 connection privileges")
+                )
+                .arg(
+                    Arg::with_name("p3_stake_cutoff_percent")
+                        .long("stake-cutoff-percent")
+                        .value_name("PERCENT")
+                        .validator(is_parsable::<u64>)
+                        .takes_value(true)
+                        .help("Lockups must hold more than this percentage of the pool's stake to be admitted")
+                )
+                .group(
+                    ArgGroup::with_name("p3_config")
+                        .args(&["p3_pool", "p3_stake_cutoff_percent"])
+                        .required(true)
+                        .multiple(true)
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("reload-amm-registry")
+                .about("Reload the AMM registry used for bundle front-run detection")
+                .arg(
+                    Arg::with_name("amm_registry")
+                        .long("path")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Registry file to load. Defaults to the file passed with --amm-registry")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("shred-receiver")
+                .about("Manage the addresses shreds are forwarded to in addition to turbine")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .setting(AppSettings::InferSubcommands)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Display the shred receivers and how many shreds each was sent")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .value_name("MODE")
+                                .possible_values(&["json", "json-compact"])
+                                .help("Output display mode"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Add a shred receiver, replacing any with the same address that receives any of the same slots")
+                        .arg(
+                            Arg::with_name("address")
+                                .index(1)
+                                .value_name("ADDRESS")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Address to forward shreds to"),
+                        )
+                        .arg(
+                            Arg::with_name("leader_slots_only")
+                                .long("leader-slots-only")
+                                .takes_value(false)
+                                .help("Only forward the shreds of this validator's leader slots"),
+                        )
+                        .arg(
+                            Arg::with_name("retransmit_slots_only")
+                                .long("retransmit-slots-only")
+                                .takes_value(false)
+                                .conflicts_with("leader_slots_only")
+                                .help("Only forward the shreds this validator retransmits"),
+                        )
+                        .arg(
+                            Arg::with_name("data_shreds_only")
+                                .long("data-shreds-only")
+                                .takes_value(false)
+                                .help("Only forward data shreds"),
+                        )
+                        .arg(
+                            Arg::with_name("quic")
+                                .long("quic")
+                                .takes_value(false)
+                                .help("Forward shreds over QUIC instead of UDP"),
+                        )
+                        .after_help(
+                            "Note: shred receiver changes only apply to the currently running \
+                             validator instance",
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Remove a shred receiver")
+                        .arg(
+                            Arg::with_name("address")
+                                .index(1)
+                                .value_name("ADDRESS")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Address of the shred receiver to remove"),
+                        )
+                        .after_help(
+                            "Note: shred receiver changes only apply to the currently running \
+                             validator instance",
+                        ),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("set-shred-receiver-address")
                 .about("Changes shred receiver address")
@@ -1858,7 +2138,9 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         .long("shred-receiver-address")
                         .value_name("SHRED_RECEIVER_ADDRESS")
                         .takes_value(true)
-                        .help("Validator will forward all shreds to this address in addition to normal turbine operation. Set to empty string to disable.")
+                        .help("Validator will forward all leader shreds to this address in addition to normal turbine operation. \
+                               Same as shred-receiver add ADDRESS --leader-slots-only, replacing the previous address. \
+                               Set to empty string to disable.")
                         .required(true)
                 )
         )
@@ -1870,7 +2152,9 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         .long("shred-receiver-address")
                         .value_name("SHRED_RECEIVER_ADDRESS")
                         .takes_value(true)
-                        .help("Validator will forward all retransmit shreds to this address in addition to normal turbine operation. Set to empty string to disable.")
+                        .help("Validator will forward all retransmit shreds to this address in addition to normal turbine operation. \
+                               Same as shred-receiver add ADDRESS --retransmit-slots-only, replacing the previous address. \
+                               Set to empty string to disable.")
                         .required(true)
                 )
         )
@@ -4,7 +4,7 @@ use jemallocator::Jemalloc;
 use {
     agave_validator::{
         admin_rpc_service,
-        admin_rpc_service::{load_staked_nodes_overrides, StakedNodesOverrides},
+        admin_rpc_service::{load_staked_nodes_overrides, AdminRpcUrls, StakedNodesOverrides},
         bootstrap,
         cli::{self, app, warn_for_deprecated_arguments, DefaultArgs},
         dashboard::Dashboard,
@@ -14,6 +14,7 @@ use {
     clap::{crate_name, value_t, value_t_or_exit, values_t, values_t_or_exit, ArgMatches},
     console::style,
     crossbeam_channel::unbounded,
+    itertools::Itertools,
     log::*,
     rand::{seq::SliceRandom, thread_rng},
     solana_accounts_db::{
@@ -33,8 +34,14 @@ use {
     solana_core::{
         banking_stage::DEFAULT_BATCH_INTERVAL,
         banking_trace::DISABLED_BAKING_TRACE_DIR,
+        bundle_stage::amm_registry::{AmmRegistry, SharedAmmRegistry},
         consensus::tower_storage,
-        proxy::{block_engine_stage::BlockEngineConfig, relayer_stage::RelayerConfig},
+        p3_quic::P3Config,
+        proxy::{
+            block_engine_stage::BlockEngineConfig,
+            relayer_stage::RelayerConfig,
+            shredstream_stage::{ShredstreamConfig, ShredstreamStage},
+        },
         system_monitor_service::SystemMonitorService,
         tip_manager::{TipDistributionAccountConfig, TipManagerConfig},
         tpu::DEFAULT_TPU_COALESCE,
@@ -83,6 +90,7 @@ use {
     solana_send_transaction_service::send_transaction_service,
     solana_streamer::socket::SocketAddrSpace,
     solana_tpu_client::tpu_client::DEFAULT_TPU_ENABLE_UDP,
+    solana_turbine::shred_receiver::{ShredReceiver, ShredReceivers},
     std::{
         collections::{HashSet, VecDeque},
         env,
@@ -435,6 +443,81 @@ fn validators_set(
     }
 }
 
+/// Block engine urls, dropping empty ones so `--block-engine-url ""` disables the connection.
+fn block_engine_urls_of(matches: &ArgMatches<'_>) -> Vec<String> {
+    values_of::<String>(matches, "block_engine_url")
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|block_engine_url| !block_engine_url.is_empty())
+        .collect()
+}
+
+/// Relayer urls, dropping empty ones so `--relayer-url ""` disables the connection and merging
+/// repeated ones.
+fn relayer_urls_of(matches: &ArgMatches<'_>) -> Vec<String> {
+    values_of::<String>(matches, "relayer_url")
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|relayer_url| !relayer_url.is_empty())
+        .unique()
+        .collect()
+}
+
+/// Shred receivers from `--shred-receiver`, and from the `--shred-receiver-address` and
+/// `--shred-retransmit-receiver-address` shorthands, dropping empty addresses.
+fn shred_receivers_of(matches: &ArgMatches<'_>) -> Vec<ShredReceiver> {
+    let shred_receiver_specs = values_of::<String>(matches, "shred_receiver")
+        .unwrap_or_default()
+        .into_iter()
+        .chain(
+            values_of::<String>(matches, "shred_receiver_address")
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|addr| !addr.is_empty())
+                .map(|addr| format!("{addr},leader-slots-only")),
+        )
+        .chain(
+            values_of::<String>(matches, "shred_retransmit_receiver_address")
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|addr| !addr.is_empty())
+                .map(|addr| format!("{addr},retransmit-slots-only")),
+        );
+    shred_receiver_specs
+        .map(|spec| {
+            ShredReceiver::from_str(&spec).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                exit(1);
+            })
+        })
+        .collect()
+}
+
+/// Shredstream config from `--shredstream-url`, `--shredstream-desired-region` and
+/// `--shredstream-socket`. An empty or missing url disables shredstream.
+fn shredstream_config_of(matches: &ArgMatches<'_>) -> ShredstreamConfig {
+    let config = ShredstreamConfig {
+        shredstream_url: matches
+            .value_of("shredstream_url")
+            .unwrap_or_default()
+            .to_string(),
+        desired_regions: values_of(matches, "shredstream_desired_region").unwrap_or_default(),
+        socket: matches.value_of("shredstream_socket").map(|socket| {
+            solana_net_utils::parse_host_port(socket).unwrap_or_else(|err| {
+                eprintln!("failed to parse shredstream socket: {err}");
+                exit(1);
+            })
+        }),
+    };
+    // Detailed log messages are printed inside validate function
+    if !config.shredstream_url.is_empty() && !ShredstreamStage::is_valid_shredstream_config(&config)
+    {
+        eprintln!("invalid shredstream config. at least one --shredstream-desired-region and a valid --shredstream-url are required.");
+        exit(1);
+    }
+    config
+}
+
 fn get_cluster_shred_version(entrypoints: &[SocketAddr]) -> Option<u16> {
     let entrypoints = {
         let mut index: Vec<_> = (0..entrypoints.len()).collect();
@@ -486,14 +569,19 @@ pub fn main() {
     let operation = match matches.subcommand() {
         ("", _) | ("run", _) => Operation::Run,
         ("set-block-engine-config", Some(subcommand_matches)) => {
-            let block_engine_url = value_t_or_exit!(subcommand_matches, "block_engine_url", String);
+            let block_engine_urls = block_engine_urls_of(subcommand_matches);
+            let connect_all = subcommand_matches.is_present("block_engine_connect_all");
             let trust_packets = subcommand_matches.is_present("trust_block_engine_packets");
             let admin_client = admin_rpc_service::connect(&ledger_path);
             admin_rpc_service::runtime()
                 .block_on(async move {
                     admin_client
                         .await?
-                        .set_block_engine_config(block_engine_url, trust_packets)
+                        .set_block_engine_config(
+                            AdminRpcUrls::Multiple(block_engine_urls),
+                            connect_all,
+                            Some(trust_packets),
+                        )
                         .await
                 })
                 .unwrap_or_else(|err| {
@@ -503,7 +591,7 @@ pub fn main() {
             return;
         }
         ("set-relayer-config", Some(subcommand_matches)) => {
-            let relayer_url = value_t_or_exit!(subcommand_matches, "relayer_url", String);
+            let relayer_urls = relayer_urls_of(subcommand_matches);
             let trust_packets = subcommand_matches.is_present("trust_relayer_packets");
             let expected_heartbeat_interval_ms: u64 =
                 value_of(subcommand_matches, "relayer_expected_heartbeat_interval_ms").unwrap();
@@ -515,7 +603,7 @@ pub fn main() {
                     admin_client
                         .await?
                         .set_relayer_config(
-                            relayer_url,
+                            AdminRpcUrls::Multiple(relayer_urls),
                             trust_packets,
                             expected_heartbeat_interval_ms,
                             max_failed_heartbeats,
@@ -528,6 +616,178 @@ pub fn main() {
                 });
             return;
         }
+        ("relayers", Some(subcommand_matches)) => {
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let relayers = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.relayers().await })
+                .unwrap_or_else(|err| {
+                    eprintln!("Relayers query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!("{}", serde_json::to_string_pretty(&relayers).unwrap()),
+                    "json-compact" => print!("{}", serde_json::to_string(&relayers).unwrap()),
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{relayers}");
+            }
+            return;
+        }
+        ("set-shredstream-config", Some(subcommand_matches)) => {
+            let ShredstreamConfig {
+                shredstream_url,
+                desired_regions,
+                socket,
+            } = shredstream_config_of(subcommand_matches);
+            let socket = socket.map(|socket| socket.to_string());
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .set_shredstream_config(shredstream_url, desired_regions, socket)
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    println!("set shredstream config failed: {}", err);
+                    exit(1);
+                });
+            return;
+        }
+        ("shredstream-state", Some(subcommand_matches)) => {
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let shredstream_state = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.shredstream_state().await })
+                .unwrap_or_else(|err| {
+                    eprintln!("Shredstream state query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&shredstream_state).unwrap()
+                    ),
+                    "json-compact" => {
+                        print!("{}", serde_json::to_string(&shredstream_state).unwrap())
+                    }
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{shredstream_state}");
+            }
+            return;
+        }
+        ("set-p3-config", Some(subcommand_matches)) => {
+            let pool = subcommand_matches.value_of("p3_pool").map(str::to_string);
+            let stake_cutoff_percent = value_of(subcommand_matches, "p3_stake_cutoff_percent");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .set_p3_config(pool, stake_cutoff_percent)
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    println!("set P3 config failed: {}", err);
+                    exit(1);
+                });
+            return;
+        }
+        ("reload-amm-registry", Some(subcommand_matches)) => {
+            let path = subcommand_matches
+                .value_of("amm_registry")
+                .map(|path| {
+                    fs::canonicalize(path).unwrap_or_else(|err| {
+                        println!("invalid AMM registry path {path}: {err}");
+                        exit(1);
+                    })
+                })
+                .map(|path| path.display().to_string());
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.reload_amm_registry(path).await })
+                .unwrap_or_else(|err| {
+                    println!("reload AMM registry failed: {}", err);
+                    exit(1);
+                });
+            return;
+        }
+        ("shred-receiver", Some(shred_receiver_subcommand_matches)) => {
+            match shred_receiver_subcommand_matches.subcommand() {
+                ("list", Some(subcommand_matches)) => {
+                    let output_mode = subcommand_matches.value_of("output");
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    let shred_receivers = admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.shred_receivers().await })
+                        .unwrap_or_else(|err| {
+                            eprintln!("Shred receivers query failed: {err}");
+                            exit(1);
+                        });
+                    if let Some(mode) = output_mode {
+                        match mode {
+                            "json" => println!(
+                                "{}",
+                                serde_json::to_string_pretty(&shred_receivers).unwrap()
+                            ),
+                            "json-compact" => {
+                                print!("{}", serde_json::to_string(&shred_receivers).unwrap())
+                            }
+                            _ => unreachable!(),
+                        }
+                    } else {
+                        print!("{shred_receivers}");
+                    }
+                    return;
+                }
+                ("add", Some(subcommand_matches)) => {
+                    let addr = value_t_or_exit!(subcommand_matches, "address", String);
+                    let leader_slots_only = subcommand_matches.is_present("leader_slots_only");
+                    let retransmit_slots_only =
+                        subcommand_matches.is_present("retransmit_slots_only");
+                    let data_shreds_only = subcommand_matches.is_present("data_shreds_only");
+                    let quic = subcommand_matches.is_present("quic");
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    admin_rpc_service::runtime()
+                        .block_on(async move {
+                            admin_client
+                                .await?
+                                .add_shred_receiver(
+                                    addr,
+                                    leader_slots_only,
+                                    retransmit_slots_only,
+                                    data_shreds_only,
+                                    quic,
+                                )
+                                .await
+                        })
+                        .unwrap_or_else(|err| {
+                            eprintln!("addShredReceiver request failed: {err}");
+                            exit(1);
+                        });
+                    return;
+                }
+                ("remove", Some(subcommand_matches)) => {
+                    let addr = value_t_or_exit!(subcommand_matches, "address", String);
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    admin_rpc_service::runtime()
+                        .block_on(
+                            async move { admin_client.await?.remove_shred_receiver(addr).await },
+                        )
+                        .unwrap_or_else(|err| {
+                            eprintln!("removeShredReceiver request failed: {err}");
+                            exit(1);
+                        });
+                    return;
+                }
+                _ => unreachable!(),
+            }
+        }
         ("set-shred-receiver-address", Some(subcommand_matches)) => {
             let addr = value_t_or_exit!(subcommand_matches, "shred_receiver_address", String);
             let admin_client = admin_rpc_service::connect(&ledger_path);
@@ -722,7 +982,12 @@ pub fn main() {
                     if !plugins.is_empty() {
                         println!("Currently the following plugins are loaded:");
                         for (plugin, i) in plugins.into_iter().zip(1..) {
-                            println!("  {i}) {plugin}");
+                            println!(
+                                "  {i}) {} (version: {}, health: {})",
+                                plugin.name,
+                                plugin.version.as_deref().unwrap_or("unknown"),
+                                plugin.health,
+                            );
                         }
                     } else {
                         println!("There are currently no plugins loaded");
@@ -1576,11 +1841,8 @@ pub fn main() {
     let tip_manager_config = tip_manager_config_from_matches(&matches, voting_disabled);
 
     let block_engine_config = BlockEngineConfig {
-        block_engine_url: if matches.is_present("block_engine_url") {
-            value_of(&matches, "block_engine_url").expect("couldn't parse block_engine_url")
-        } else {
-            "".to_string()
-        },
+        block_engine_urls: block_engine_urls_of(&matches),
+        connect_all: matches.is_present("block_engine_connect_all"),
         trust_packets: matches.is_present("trust_block_engine_packets"),
     };
 
@@ -1598,11 +1860,7 @@ pub fn main() {
     );
 
     let relayer_config = RelayerConfig {
-        relayer_url: if matches.is_present("relayer_url") {
-            value_of(&matches, "relayer_url").expect("couldn't parse relayer_url")
-        } else {
-            "".to_string()
-        },
+        relayer_urls: relayer_urls_of(&matches),
         expected_heartbeat_interval: Duration::from_millis(expected_heartbeat_interval_ms),
         oldest_allowed_heartbeat: Duration::from_millis(
             max_failed_heartbeats * expected_heartbeat_interval_ms,
@@ -1626,6 +1884,40 @@ pub fn main() {
         Ipv4Addr::UNSPECIFIED,
         value_of(&matches, "p3_mev_port").expect("couldn't parse --p3-mev-port"),
     ));
+    let p3_config = {
+        let default = P3Config::default();
+        P3Config {
+            pool: pubkey_of(&matches, "p3_pool").unwrap_or(default.pool),
+            stake_cutoff_percent: value_of(&matches, "p3_stake_cutoff_percent")
+                .unwrap_or(default.stake_cutoff_percent),
+            max_staked_connections: value_of(&matches, "p3_max_staked_connections")
+                .unwrap_or(default.max_staked_connections),
+            max_unstaked_connections: value_of(&matches, "p3_max_unstaked_connections")
+                .unwrap_or(default.max_unstaked_connections),
+            staked_nodes_update_interval: value_of(
+                &matches,
+                "p3_staked_nodes_update_interval_secs",
+            )
+            .map(Duration::from_secs)
+            .unwrap_or(default.staked_nodes_update_interval),
+            regular_packets_per_second: value_of(&matches, "p3_regular_packets_per_second")
+                .unwrap_or(default.regular_packets_per_second),
+            mev_packets_per_second: value_of(&matches, "p3_mev_packets_per_second")
+                .unwrap_or(default.mev_packets_per_second),
+        }
+    };
+    if let Err(err) = p3_config.validate() {
+        eprintln!("Invalid P3 config: {err}");
+        exit(1);
+    }
+    let amm_registry_path = matches.value_of("amm_registry").map(PathBuf::from);
+    let amm_registry = match &amm_registry_path {
+        Some(path) => AmmRegistry::load(path).unwrap_or_else(|err| {
+            eprintln!("Failed to load AMM registry {}: {err}", path.display());
+            exit(1);
+        }),
+        None => AmmRegistry::default(),
+    };
 
     let mut validator_config = ValidatorConfig {
         require_tower: matches.is_present("require_tower"),
@@ -1670,6 +1962,7 @@ pub fn main() {
                 usize
             )),
             skip_preflight_health_check: matches.is_present("skip_preflight_health_check"),
+            bundle_checks: None,
         },
         on_start_geyser_plugin_config_files,
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
@@ -1761,18 +2054,8 @@ pub fn main() {
         relayer_config: Arc::new(Mutex::new(relayer_config)),
         block_engine_config: Arc::new(Mutex::new(block_engine_config)),
         tip_manager_config,
-        shred_receiver_address: Arc::new(RwLock::new(
-            matches
-                .value_of("shred_receiver_address")
-                .map(|addr| SocketAddr::from_str(addr).expect("shred_receiver_address invalid")),
-        )),
-        shred_retransmit_receiver_address: Arc::new(RwLock::new(
-            matches
-                .value_of("shred_retransmit_receiver_address")
-                .map(|addr| {
-                    SocketAddr::from_str(addr).expect("shred_retransmit_receiver_address invalid")
-                }),
-        )),
+        shred_receivers: Arc::new(ShredReceivers::new(shred_receivers_of(&matches))),
+        shredstream_config: Arc::new(Mutex::new(shredstream_config_of(&matches))),
         staked_nodes_overrides: staked_nodes_overrides.clone(),
         use_snapshot_archives_at_startup: value_t_or_exit!(
             matches,
@@ -1792,6 +2075,9 @@ pub fn main() {
         batch_interval,
         p3_socket,
         p3_mev_socket,
+        p3_config: Arc::new(Mutex::new(p3_config)),
+        amm_registry: SharedAmmRegistry::new(amm_registry),
+        amm_registry_path,
         ..ValidatorConfig::default()
     };
 